@@ -24,6 +24,7 @@ impl AppDatabase {
         conn.execute(CREATE_API_KEYS_TABLE, [])?;
         conn.execute(CREATE_WORKFLOW_EXECUTIONS_TABLE, [])?;
         conn.execute(CREATE_WORKFLOW_SNAPSHOTS_TABLE, [])?;
+        conn.execute(CREATE_QUERY_HISTORY_TABLE, [])?;
 
         // Create indexes
         for index_sql in CREATE_INDEXES {
@@ -434,4 +435,85 @@ impl AppDatabase {
 
         Ok(snapshots)
     }
+
+    // ============================================================================
+    // Query History
+    // ============================================================================
+
+    pub fn add_query_history(&self, entry: &QueryHistoryEntry) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO query_history (connection_id, connection_name, query, executed_at, duration_ms, row_count, more_rows, affected_rows, error_message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                &entry.connection_id,
+                &entry.connection_name,
+                &entry.query,
+                entry.executed_at.to_rfc3339(),
+                entry.duration_ms,
+                entry.row_count,
+                entry.more_rows,
+                entry.affected_rows,
+                &entry.error_message
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Newest first; `search` matches the query text or connection name
+    pub fn search_query_history(
+        &self,
+        search: Option<&str>,
+        connection_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<QueryHistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let pattern = search.filter(|s| !s.is_empty()).map(|s| {
+            format!("%{}%", s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+        });
+        let mut stmt = conn.prepare(
+            "SELECT id, connection_id, connection_name, query, executed_at, duration_ms, row_count, more_rows, affected_rows, error_message
+             FROM query_history
+             WHERE (?1 IS NULL OR query LIKE ?1 ESCAPE '\\' OR connection_name LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR connection_id = ?2)
+             ORDER BY executed_at DESC, id DESC
+             LIMIT ?3"
+        )?;
+
+        let entries = stmt.query_map(params![pattern, connection_id, limit], |row| {
+            Ok(QueryHistoryEntry {
+                id: row.get(0)?,
+                connection_id: row.get(1)?,
+                connection_name: row.get(2)?,
+                query: row.get(3)?,
+                executed_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(4)?)
+                    .unwrap()
+                    .with_timezone(&Utc),
+                duration_ms: row.get(5)?,
+                row_count: row.get(6)?,
+                more_rows: row.get(7)?,
+                affected_rows: row.get(8)?,
+                error_message: row.get(9)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    pub fn delete_query_history(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM query_history WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Remove the history of one connection, or all of it
+    pub fn clear_query_history(&self, connection_id: Option<&str>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM query_history WHERE ?1 IS NULL OR connection_id = ?1",
+            params![connection_id],
+        )?;
+        Ok(())
+    }
 }
@@ -70,9 +70,26 @@ CREATE TABLE IF NOT EXISTS workflow_snapshots (
 )
 "#;
 
+pub const CREATE_QUERY_HISTORY_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS query_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    connection_id TEXT NOT NULL,
+    connection_name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    executed_at DATETIME NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    row_count INTEGER,
+    more_rows INTEGER NOT NULL DEFAULT 0,
+    affected_rows INTEGER,
+    error_message TEXT
+)
+"#;
+
 pub const CREATE_INDEXES: &[&str] = &[
     "CREATE INDEX IF NOT EXISTS idx_model_settings_session ON model_settings(session_id)",
     "CREATE INDEX IF NOT EXISTS idx_api_keys_session ON api_keys(session_id)",
     "CREATE INDEX IF NOT EXISTS idx_workflow_executions_session ON workflow_executions(session_id)",
     "CREATE INDEX IF NOT EXISTS idx_workflow_snapshots_execution ON workflow_snapshots(execution_id)",
+    "CREATE INDEX IF NOT EXISTS idx_query_history_executed ON query_history(executed_at)",
+    "CREATE INDEX IF NOT EXISTS idx_query_history_connection ON query_history(connection_id)",
 ];
@@ -42,6 +42,23 @@ pub struct WorkflowSnapshot {
     pub timestamp: DateTime<Utc>,
 }
 
+/// A query run from the database panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryHistoryEntry {
+    pub id: i64,
+    pub connection_id: String,
+    pub connection_name: String,
+    pub query: String,
+    pub executed_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    /// Rows returned (the first page of a paged result)
+    pub row_count: Option<i64>,
+    /// The result had more rows than the first page
+    pub more_rows: bool,
+    pub affected_rows: Option<i64>,
+    pub error_message: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelSettings {
     pub design: String,
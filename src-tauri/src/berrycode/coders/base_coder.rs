@@ -20,6 +20,7 @@ use crate::berrycode::{
     speculative_executor::SpeculativeExecutor,
     proactive_agent::ProactiveAgent,
 };
+use crate::local_history::{LocalHistory, RevisionSource};
 use std::path::PathBuf;
 use std::fs;
 
@@ -45,6 +46,8 @@ pub struct Coder {
     // Advanced AI features
     pub speculative_executor: Option<SpeculativeExecutor>,
     pub proactive_agent: Option<ProactiveAgent>,
+    /// The editor's local history of the repository, checkpointed before edits
+    pub local_history: Option<LocalHistory>,
 }
 
 #[derive(Debug, Clone)]
@@ -217,6 +220,12 @@ impl Coder {
             (None, None)
         };
 
+        let local_history = git_repo.as_ref().and_then(|repo| {
+            let history = LocalHistory::new(LocalHistory::default_dir()?);
+            history.set_roots(vec![repo.root().to_path_buf()]);
+            Some(history)
+        });
+
         Self {
             io,
             model,
@@ -237,6 +246,7 @@ impl Coder {
             plan_mode: PlanMode::new(),
             speculative_executor,
             proactive_agent,
+            local_history,
         }
     }
 
@@ -349,10 +359,22 @@ impl Coder {
         let diff_applier = DiffApplier::new(self.dry_run);
         let mut modified_files = Vec::new();
 
+        if !self.dry_run {
+            let paths: Vec<PathBuf> = edit_blocks
+                .iter()
+                .filter_map(|block| block.file_path.as_deref())
+                .map(absolute_path)
+                .collect();
+            self.checkpoint_history("Before agent edit", &paths);
+        }
+
         for block in &edit_blocks {
             match self.apply_single_edit(&diff_applier, block) {
                 Ok(file_path) => {
                     if let Some(path) = file_path {
+                        if !self.dry_run {
+                            self.record_history(&absolute_path(&path.to_string_lossy()));
+                        }
                         modified_files.push(path);
                     }
                 }
@@ -370,6 +392,25 @@ impl Coder {
         Ok(())
     }
 
+    /// Label the local history before the agent changes `paths`, so the
+    /// editor can restore them
+    fn checkpoint_history(&self, label: &str, paths: &[PathBuf]) {
+        let (Some(history), Some(repo)) = (&self.local_history, &self.git_repo) else {
+            return;
+        };
+        if let Err(e) = history.checkpoint(repo.root(), label, paths) {
+            self.io.tool_warning(&format!("Failed to checkpoint local history: {}", e));
+        }
+    }
+
+    fn record_history(&self, path: &std::path::Path) {
+        if let Some(history) = &self.local_history {
+            if let Err(e) = history.record(path, RevisionSource::Agent) {
+                self.io.tool_warning(&format!("Failed to record local history: {}", e));
+            }
+        }
+    }
+
     fn apply_single_edit(&mut self, diff_applier: &DiffApplier, block: &EditBlock) -> Result<Option<PathBuf>> {
         use crate::berrycode::prompts::EditBlockType;
 
@@ -846,3 +887,12 @@ impl Coder {
         }
     }
 }
+
+/// Edit block paths are relative to the working directory; history keys are absolute
+fn absolute_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+    std::env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path)
+}
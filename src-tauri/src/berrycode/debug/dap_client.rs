@@ -22,6 +22,28 @@ pub struct DapClient {
     next_id: Arc<Mutex<i64>>,
 }
 
+/// What the debug adapter launches
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchConfig {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    pub env: HashMap<String, String>,
+}
+
+impl LaunchConfig {
+    /// Arguments of the DAP `launch` request
+    pub fn launch_arguments(&self) -> serde_json::Value {
+        serde_json::json!({
+            "program": self.program.to_string_lossy(),
+            "args": self.args,
+            "cwd": self.cwd.to_string_lossy(),
+            "env": self.env,
+            "stopOnEntry": false,
+        })
+    }
+}
+
 /// A running debug session
 pub struct DebugSession {
     /// The debug adapter process
@@ -145,6 +167,17 @@ impl DapClient {
 
     /// Start a debug session for Rust (using lldb-vscode or codelldb)
     pub fn start_rust_debug(&self, session_id: &str, program_path: &Path) -> Result<()> {
+        let config = LaunchConfig {
+            program: program_path.to_path_buf(),
+            cwd: self.project_root.clone(),
+            ..LaunchConfig::default()
+        };
+        self.launch_rust(session_id, &config)
+    }
+
+    /// Start a debug session for a Rust program with its arguments,
+    /// working directory and environment, e.g. a single test
+    pub fn launch_rust(&self, session_id: &str, config: &LaunchConfig) -> Result<()> {
         // Try lldb-vscode first, fall back to codelldb
         let adapter_cmd = if Command::new("lldb-vscode").arg("--version").output().is_ok() {
             vec!["lldb-vscode"]
@@ -183,15 +216,7 @@ impl DapClient {
         self.read_response(&mut process)?;
 
         // Send launch request
-        self.send_request(
-            &mut process,
-            "launch",
-            Some(serde_json::json!({
-                "program": program_path.to_string_lossy(),
-                "cwd": self.project_root.to_string_lossy(),
-                "stopOnEntry": false,
-            })),
-        )?;
+        self.send_request(&mut process, "launch", Some(config.launch_arguments()))?;
 
         // Create session
         let session = DebugSession {
@@ -813,3 +838,23 @@ impl DapClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_arguments() {
+        let config = LaunchConfig {
+            program: PathBuf::from("/p/target/debug/deps/app-123"),
+            args: vec!["tests::parses".to_string(), "--exact".to_string()],
+            cwd: PathBuf::from("/p"),
+            env: HashMap::from([("RUST_BACKTRACE".to_string(), "1".to_string())]),
+        };
+        let arguments = config.launch_arguments();
+        assert_eq!(arguments["program"], "/p/target/debug/deps/app-123");
+        assert_eq!(arguments["args"], serde_json::json!(["tests::parses", "--exact"]));
+        assert_eq!(arguments["cwd"], "/p");
+        assert_eq!(arguments["env"]["RUST_BACKTRACE"], "1");
+    }
+}
@@ -4,4 +4,4 @@
 
 pub mod dap_client;
 
-pub use dap_client::DapClient;
+pub use dap_client::{DapClient, LaunchConfig};
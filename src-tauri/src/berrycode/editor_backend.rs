@@ -406,23 +406,59 @@ pub async fn lsp_code_actions(
 // Debug Commands (DAP)
 // ============================================================================
 
-// Note: For now, these are simplified implementations.
-// Full DAP integration would connect to src/web/api/debug/debug_api.rs
+// Note: Sessions are started and stopped through the DAP client; the other
+// commands are still simplified implementations.
 
-/// Start a debug session
+use crate::berrycode::debug::{DapClient, LaunchConfig};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Debug adapters of the running sessions
+static DEBUG_SESSIONS: Lazy<Mutex<HashMap<String, DapClient>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start a debug session. `args`, `cwd` and `env` go into the adapter's
+/// launch request, e.g. the filter that runs a single test.
 #[tauri::command]
-pub async fn debug_start_session(program_path: String) -> Result<String, String> {
-    // Simplified: Generate a session ID
-    // In full implementation, this would call the DAP debug_api
+pub async fn debug_start_session(
+    program_path: String,
+    args: Option<Vec<String>>,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+) -> Result<String, String> {
+    let cwd = cwd
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let config = LaunchConfig {
+        program: PathBuf::from(program_path),
+        args: args.unwrap_or_default(),
+        cwd: cwd.clone(),
+        env: env.unwrap_or_default(),
+    };
     let session_id = uuid::Uuid::new_v4().to_string();
+    let client = DapClient::new(cwd);
+
+    // Starting the adapter waits for its initialize response
+    let id = session_id.clone();
+    let client = tokio::task::spawn_blocking(move || client.launch_rust(&id, &config).map(|()| client))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to start debug session: {}", e))?;
+    DEBUG_SESSIONS.lock().unwrap().insert(session_id.clone(), client);
     Ok(session_id)
 }
 
 /// Stop a debug session
 #[tauri::command]
 pub async fn debug_stop_session(session_id: String) -> Result<(), String> {
-    // Simplified implementation
-    Ok(())
+    let client = DEBUG_SESSIONS.lock().unwrap().remove(&session_id);
+    match client {
+        Some(client) => tokio::task::spawn_blocking(move || client.stop_debug(&session_id))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to stop debug session: {}", e)),
+        None => Ok(()),
+    }
 }
 
 /// Set a breakpoint
@@ -84,8 +84,8 @@ pub mod debug;
 // #[cfg(feature = "web")]
 // pub mod web;
 
-// #[cfg(feature = "web")]
-// pub mod remote;
+#[cfg(feature = "remote")]
+pub mod remote;
 
 pub mod collaboration;
 
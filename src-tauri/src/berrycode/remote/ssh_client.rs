@@ -2,7 +2,7 @@
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use ssh2::{Channel, Session, Sftp};
+use ssh2::{Channel, FileStat, OpenFlags, OpenType, Session, Sftp};
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::TcpStream;
@@ -192,8 +192,19 @@ impl SshConnection {
 
         // Get sftp after creating directories to avoid borrowing conflicts
         let sftp = self.get_sftp()?;
+        // Overwriting keeps the file's permissions, e.g. the executable bit
+        let mode = sftp
+            .stat(remote_path)
+            .ok()
+            .and_then(|stat| stat.perm)
+            .map_or(0o644, |perm| perm & 0o7777);
         let mut file = sftp
-            .create(remote_path)
+            .open_mode(
+                remote_path,
+                OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+                mode as i32,
+                OpenType::File,
+            )
             .context(format!("Failed to create remote file: {}", path))?;
 
         file.write_all(contents)
@@ -305,6 +316,54 @@ impl SshConnection {
         })
     }
 
+    /// Start a command whose stdin/stdout stay open (e.g. a language server)
+    pub fn spawn(&mut self, command: &str) -> Result<Channel> {
+        let mut channel = self
+            .session
+            .channel_session()
+            .context("Failed to open SSH channel")?;
+
+        channel.exec(command).context("Failed to execute command")?;
+
+        Ok(channel)
+    }
+
+    /// Rename a file. Servers speaking SFTP v3, such as OpenSSH, refuse to
+    /// replace an existing target.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let sftp = self.get_sftp()?;
+        sftp.rename(Path::new(from), Path::new(to), None)
+            .context(format!("Failed to rename remote file: {} -> {}", from, to))?;
+        Ok(())
+    }
+
+    /// Permission bits of a file
+    pub fn mode(&mut self, path: &str) -> Result<u32> {
+        let sftp = self.get_sftp()?;
+        let stat = sftp
+            .stat(Path::new(path))
+            .context(format!("Failed to stat remote file: {}", path))?;
+        stat.perm
+            .map(|perm| perm & 0o7777)
+            .ok_or_else(|| anyhow!("No permissions reported for {}", path))
+    }
+
+    /// Change the permission bits of a file
+    pub fn set_mode(&mut self, path: &str, mode: u32) -> Result<()> {
+        let sftp = self.get_sftp()?;
+        let stat = FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode),
+            atime: None,
+            mtime: None,
+        };
+        sftp.setstat(Path::new(path), stat)
+            .context(format!("Failed to change mode of remote file: {}", path))?;
+        Ok(())
+    }
+
     /// Open an interactive shell channel
     pub fn open_shell(&mut self) -> Result<Channel> {
         let mut channel = self
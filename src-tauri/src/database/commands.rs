@@ -1,22 +1,75 @@
 use super::{
+    credentials::MasterKey,
+    mongo::MongoClients,
     operations::{test_connection, DatabaseManager},
+    pool::{PoolRegistry, DEFAULT_PAGE_SIZE},
     types::*,
 };
+use crate::app_database::{AppDatabase, QueryHistoryEntry};
 use std::sync::Mutex;
-use tauri::State;
+use std::time::{Duration, Instant};
+use tauri::{Manager, State};
 
 pub struct DbManager {
     manager: Mutex<DatabaseManager>,
+    /// Open sessions by connection id
+    pools: PoolRegistry,
+    /// MongoDB clients by connection id
+    mongo: MongoClients,
+    /// Query history
+    history: AppDatabase,
 }
 
 impl DbManager {
     pub fn new(app_handle: &tauri::AppHandle) -> Self {
+        let manager = DatabaseManager::new(app_handle).expect("Failed to initialize DatabaseManager");
+        let config_dir = app_handle
+            .path()
+            .app_config_dir()
+            .expect("Failed to get app config dir");
         Self {
-            manager: Mutex::new(
-                DatabaseManager::new(app_handle).expect("Failed to initialize DatabaseManager"),
-            ),
+            manager: Mutex::new(manager),
+            pools: PoolRegistry::default(),
+            mongo: MongoClients::default(),
+            history: AppDatabase::new(config_dir.join("berry_app.db"))
+                .expect("Failed to open app database"),
         }
     }
+
+    /// Add a run to the query history; `fill` sets its outcome
+    fn record_query(
+        &self,
+        connection: &DbConnection,
+        query: &str,
+        started: Instant,
+        fill: impl FnOnce(&mut QueryHistoryEntry),
+    ) {
+        let mut entry = QueryHistoryEntry {
+            id: 0,
+            connection_id: connection.id.clone(),
+            connection_name: connection.name.clone(),
+            query: query.to_string(),
+            executed_at: chrono::Utc::now(),
+            duration_ms: started.elapsed().as_millis() as i64,
+            row_count: None,
+            more_rows: false,
+            affected_rows: None,
+            error_message: None,
+        };
+        fill(&mut entry);
+        if let Err(e) = self.history.add_query_history(&entry) {
+            eprintln!("Failed to record query history: {}", e);
+        }
+    }
+
+    /// The connection with its saved or referenced password filled in
+    fn resolve(&self, connection: &DbConnection) -> Result<DbConnection, String> {
+        self.manager
+            .lock()
+            .unwrap()
+            .resolve(connection)
+            .map_err(|e| e.to_string())
+    }
 }
 
 #[tauri::command]
@@ -66,33 +119,410 @@ pub async fn db_delete_connection(
     connection_id: String,
     manager: State<'_, DbManager>,
 ) -> Result<(), String> {
-    let mgr = manager.manager.lock().unwrap();
-    let mut connections = mgr.load_connections().map_err(|e| e.to_string())?;
+    {
+        let mgr = manager.manager.lock().unwrap();
+        let mut connections = mgr.load_connections().map_err(|e| e.to_string())?;
 
-    connections.retain(|c| c.id != connection_id);
-    mgr.save_connections(&connections)
+        connections.retain(|c| c.id != connection_id);
+        mgr.save_connections(&connections)
+            .map_err(|e| e.to_string())?;
+    }
+
+    manager.pools.remove(&connection_id).await;
+    manager.mongo.remove(&connection_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn db_vault_status(manager: State<'_, DbManager>) -> Result<VaultStatus, String> {
+    manager
+        .manager
+        .lock()
+        .unwrap()
+        .vault_status()
+        .map_err(|e| e.to_string())
+}
+
+/// Unlock the credential store with a master passphrase or a key file; the
+/// first unlock sets up the store with that secret
+#[tauri::command]
+pub async fn db_unlock_vault(
+    passphrase: Option<String>,
+    key_file: Option<String>,
+    manager: State<'_, DbManager>,
+) -> Result<VaultStatus, String> {
+    let master = match (passphrase, key_file) {
+        (_, Some(path)) if !path.is_empty() => MasterKey::KeyFile(path.into()),
+        (Some(passphrase), _) => MasterKey::Passphrase(passphrase),
+        _ => return Err("A master passphrase or key file is required".to_string()),
+    };
+    // Key derivation is deliberately slow
+    tokio::task::block_in_place(|| manager.manager.lock().unwrap().unlock(&master))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_lock_vault(manager: State<'_, DbManager>) -> Result<(), String> {
+    manager.manager.lock().unwrap().lock();
+    Ok(())
+}
+
+/// Write saved connections to a file without their passwords
+#[tauri::command]
+pub async fn db_export_connections(
+    path: String,
+    manager: State<'_, DbManager>,
+) -> Result<usize, String> {
+    manager
+        .manager
+        .lock()
+        .unwrap()
+        .export_connections(std::path::Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Add connections from an exported file; returns how many were added
+#[tauri::command]
+pub async fn db_import_connections(
+    path: String,
+    manager: State<'_, DbManager>,
+) -> Result<usize, String> {
+    manager
+        .manager
+        .lock()
+        .unwrap()
+        .import_connections(std::path::Path::new(&path))
         .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn db_test_connection(
     connection: DbConnection,
-    _manager: State<'_, DbManager>,
+    manager: State<'_, DbManager>,
 ) -> Result<ConnectionTestResult, String> {
+    let connection = manager.resolve(&connection)?;
     test_connection(&connection)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Run a query on the connection's persistent session and return the first
+/// page of rows; fetch the rest with `db_fetch_rows` while `cursor` is set
 #[tauri::command]
 pub async fn db_execute_query(
     connection: DbConnection,
     query: String,
-    _manager: State<'_, DbManager>,
+    timeout_ms: Option<u64>,
+    page_size: Option<usize>,
+    manager: State<'_, DbManager>,
+) -> Result<QueryResult, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    let started = Instant::now();
+    let result = pool
+        .query(
+            &query,
+            timeout_ms.map(Duration::from_millis),
+            page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+        )
+        .await
+        .map_err(|e| e.to_string());
+
+    manager.record_query(&connection, &query, started, |entry| match &result {
+        Ok(result) => {
+            if !result.columns.is_empty() {
+                entry.row_count = Some(result.rows.len() as i64);
+            }
+            entry.more_rows = result.cursor.is_some();
+            entry.affected_rows = result.affected_rows.map(|n| n as i64);
+        }
+        Err(e) => entry.error_message = Some(e.clone()),
+    });
+    result
+}
+
+/// Plan of a query; `analyze` executes it (PostgreSQL) to report actual
+/// rows and timings
+#[tauri::command]
+pub async fn db_explain_query(
+    connection: DbConnection,
+    query: String,
+    analyze: Option<bool>,
+    timeout_ms: Option<u64>,
+    manager: State<'_, DbManager>,
+) -> Result<QueryPlan, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.explain(&query, analyze.unwrap_or(false), timeout_ms.map(Duration::from_millis))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// First page of a table's rows for the data editor
+#[tauri::command]
+pub async fn db_open_table(
+    connection: DbConnection,
+    schema: String,
+    table: String,
+    page_size: Option<usize>,
+    manager: State<'_, DbManager>,
+) -> Result<QueryResult, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.open_table(&schema, &table, page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// SQL generated for pending row changes
+#[tauri::command]
+pub async fn db_preview_changes(
+    connection: DbConnection,
+    edit: TableEdit,
+    manager: State<'_, DbManager>,
+) -> Result<Vec<String>, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.preview_changes(&edit).await.map_err(|e| e.to_string())
+}
+
+/// Apply pending row changes in one transaction
+#[tauri::command]
+pub async fn db_apply_changes(
+    connection: DbConnection,
+    edit: TableEdit,
+    manager: State<'_, DbManager>,
+) -> Result<u64, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.apply_changes(&edit).await.map_err(|e| e.to_string())
+}
+
+/// Executed queries, newest first
+#[tauri::command]
+pub async fn db_query_history(
+    search: Option<String>,
+    connection_id: Option<String>,
+    limit: Option<usize>,
+    manager: State<'_, DbManager>,
+) -> Result<Vec<QueryHistoryEntry>, String> {
+    manager
+        .history
+        .search_query_history(search.as_deref(), connection_id.as_deref(), limit.unwrap_or(200))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_delete_history_entry(
+    id: i64,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    manager
+        .history
+        .delete_query_history(id)
+        .map_err(|e| e.to_string())
+}
+
+/// Clear the history of one connection, or everything without an id
+#[tauri::command]
+pub async fn db_clear_query_history(
+    connection_id: Option<String>,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    manager
+        .history
+        .clear_query_history(connection_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_fetch_rows(
+    connection_id: String,
+    cursor: String,
+    count: Option<usize>,
+    timeout_ms: Option<u64>,
+    manager: State<'_, DbManager>,
 ) -> Result<QueryResult, String> {
-    use super::operations::execute_query;
+    let pool = manager.pools.get(&connection_id).ok_or("No open session")?;
+    pool.fetch(
+        &cursor,
+        count.unwrap_or(DEFAULT_PAGE_SIZE),
+        timeout_ms.map(Duration::from_millis),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
 
-    execute_query(&connection, query)
+#[tauri::command]
+pub async fn db_close_cursor(
+    connection_id: String,
+    cursor: String,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    match manager.pools.get(&connection_id) {
+        Some(pool) => pool.close_cursor(&cursor).await.map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Schema metadata of a connection; cached until `refresh` or a DDL statement
+#[tauri::command]
+pub async fn db_get_schema(
+    connection: DbConnection,
+    refresh: Option<bool>,
+    manager: State<'_, DbManager>,
+) -> Result<DatabaseSchema, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.schema(refresh.unwrap_or(false))
         .await
+        .map(|schema| (*schema).clone())
         .map_err(|e| e.to_string())
 }
+
+/// Write all rows of a query to a file; returns the number of rows written
+#[tauri::command]
+pub async fn db_export_query(
+    connection: DbConnection,
+    query: String,
+    format: ExportFormat,
+    path: String,
+    table_name: Option<String>,
+    manager: State<'_, DbManager>,
+) -> Result<usize, String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    let table = table_name.unwrap_or_else(|| "exported".to_string());
+    pool.export(&query, format, std::path::Path::new(&path), &table)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_mongo_collections(
+    connection: DbConnection,
+    manager: State<'_, DbManager>,
+) -> Result<Vec<String>, String> {
+    let connection = manager.resolve(&connection)?;
+    let client = manager
+        .mongo
+        .get_or_connect(&connection)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut names = client
+        .database(&connection.database)
+        .list_collection_names(None)
+        .await
+        .map_err(|e| e.to_string())?;
+    names.sort();
+    Ok(names)
+}
+
+/// Run a MongoDB shell command; inserts, updates and deletes only return a
+/// confirmation prompt until they are sent again with `confirmed`
+#[tauri::command]
+pub async fn db_mongo_execute(
+    connection: DbConnection,
+    command: String,
+    confirmed: Option<bool>,
+    manager: State<'_, DbManager>,
+) -> Result<MongoResult, String> {
+    let connection = manager.resolve(&connection)?;
+    let started = Instant::now();
+    let result = manager
+        .mongo
+        .run(&connection, &command, confirmed.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string());
+
+    // A write waiting for confirmation hasn't run yet
+    if !result.as_ref().is_ok_and(|r| r.needs_confirmation) {
+        manager.record_query(&connection, &command, started, |entry| match &result {
+            Ok(result) => {
+                entry.row_count = Some(result.documents.len() as i64);
+                entry.more_rows = result.truncated;
+            }
+            Err(e) => entry.error_message = Some(e.clone()),
+        });
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn db_begin_transaction(
+    connection: DbConnection,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    let connection = manager.resolve(&connection)?;
+    let pool = manager.pools.get_or_create(&connection);
+    pool.begin().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_commit_transaction(
+    connection_id: String,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    let pool = manager.pools.get(&connection_id).ok_or("No open session")?;
+    pool.commit().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn db_rollback_transaction(
+    connection_id: String,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    let pool = manager.pools.get(&connection_id).ok_or("No open session")?;
+    pool.rollback().await.map_err(|e| e.to_string())
+}
+
+/// Cancel the running statement; false when nothing was running
+#[tauri::command]
+pub async fn db_cancel_query(
+    connection_id: String,
+    manager: State<'_, DbManager>,
+) -> Result<bool, String> {
+    match manager.pools.get(&connection_id) {
+        Some(pool) => Ok(pool.cancel().await),
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub async fn db_session_status(
+    connection_id: String,
+    manager: State<'_, DbManager>,
+) -> Result<SessionStatus, String> {
+    Ok(manager
+        .pools
+        .get(&connection_id)
+        .map(|pool| pool.status())
+        .unwrap_or(SessionStatus {
+            connection_id,
+            ..Default::default()
+        }))
+}
+
+/// Ping a saved connection through its pool
+#[tauri::command]
+pub async fn db_health_check(
+    connection: DbConnection,
+    manager: State<'_, DbManager>,
+) -> Result<ConnectionTestResult, String> {
+    let connection = manager.resolve(&connection)?;
+    if connection.db_type == DbType::MongoDB {
+        return test_connection(&connection).await.map_err(|e| e.to_string());
+    }
+    let pool = manager.pools.get_or_create(&connection);
+    Ok(pool.health_check().await)
+}
+
+/// Close the sessions of a connection (rolls back an open transaction)
+#[tauri::command]
+pub async fn db_close_session(
+    connection_id: String,
+    manager: State<'_, DbManager>,
+) -> Result<(), String> {
+    manager.pools.remove(&connection_id).await;
+    Ok(())
+}
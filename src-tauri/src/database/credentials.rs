@@ -0,0 +1,285 @@
+//! Credential storage for saved connections
+//!
+//! Stored passwords are encrypted with AES-256-GCM. The key is derived from a
+//! master passphrase (PBKDF2-HMAC-SHA256) or from the contents of a key file,
+//! salted per store, so the same secret opens the store on every OS. Each
+//! password is bound to its connection ID as associated data, so a ciphertext
+//! copied onto another connection does not decrypt.
+//! Passwords can also be references resolved when connecting: an environment
+//! variable or a `.pgpass`-style file.
+
+use super::types::*;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Encryption key length (32 bytes for AES-256)
+const KEY_LEN: usize = 32;
+
+/// Nonce length (12 bytes for AES-GCM)
+const NONCE_LEN: usize = 12;
+
+const SALT_LEN: usize = 16;
+
+/// PBKDF2 rounds for new stores
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Plaintext of `VaultHeader::check`
+const CHECK_VALUE: &str = "berry-credentials";
+
+/// Default PostgreSQL port, used for `.pgpass` matching like libpq does
+const DEFAULT_PG_PORT: u16 = 5432;
+
+/// What unlocks the credential store
+pub enum MasterKey {
+    Passphrase(String),
+    KeyFile(PathBuf),
+}
+
+/// Key of an unlocked store
+pub struct VaultKey([u8; KEY_LEN]);
+
+impl VaultKey {
+    fn derive(master: &MasterKey, header: &VaultHeader) -> Result<Self> {
+        let salt = BASE64
+            .decode(&header.salt)
+            .map_err(|e| anyhow!("Invalid credential store salt: {}", e))?;
+        let mut key = [0u8; KEY_LEN];
+        match master {
+            MasterKey::Passphrase(passphrase) => {
+                if passphrase.is_empty() {
+                    return Err(anyhow!("The master passphrase is empty"));
+                }
+                pbkdf2::pbkdf2::<hmac::Hmac<Sha256>>(passphrase.as_bytes(), &salt, header.iterations, &mut key);
+            }
+            MasterKey::KeyFile(path) => {
+                let contents = std::fs::read(path)
+                    .map_err(|e| anyhow!("Failed to read key file {}: {}", path.display(), e))?;
+                if contents.len() < KEY_LEN {
+                    return Err(anyhow!("Key file must hold at least {} bytes", KEY_LEN));
+                }
+                let mut hasher = Sha256::new();
+                hasher.update(&salt);
+                hasher.update(&contents);
+                key.copy_from_slice(&hasher.finalize());
+            }
+        }
+        Ok(Self(key))
+    }
+
+    /// Create a store header for a new master key
+    pub fn create(master: &MasterKey, iterations: u32) -> Result<(Self, VaultHeader)> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut header = VaultHeader {
+            salt: BASE64.encode(salt),
+            iterations,
+            check: String::new(),
+        };
+        let key = Self::derive(master, &header)?;
+        header.check = key.encrypt(CHECK_VALUE, "")?;
+        Ok((key, header))
+    }
+
+    /// Derive the key of an existing store, rejecting a wrong master key
+    pub fn open(master: &MasterKey, header: &VaultHeader) -> Result<Self> {
+        let key = Self::derive(master, header)?;
+        match key.decrypt(&header.check, "") {
+            Ok(check) if check == CHECK_VALUE => Ok(key),
+            _ => Err(anyhow!("Wrong master passphrase or key file")),
+        }
+    }
+
+    /// Base64 of nonce followed by ciphertext; `context` (e.g. the connection
+    /// ID) is authenticated but not stored, and decrypting needs the same one
+    pub fn encrypt(&self, plaintext: &str, context: &str) -> Result<String> {
+        let cipher = Aes256Gcm::new_from_slice(&self.0).map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_bytes);
+        let payload = Payload {
+            msg: plaintext.as_bytes(),
+            aad: context.as_bytes(),
+        };
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), payload)
+            .map_err(|e| anyhow!("Encryption failed: {}", e))?;
+
+        let mut combined = nonce_bytes.to_vec();
+        combined.extend_from_slice(&ciphertext);
+        Ok(BASE64.encode(&combined))
+    }
+
+    pub fn decrypt(&self, encrypted: &str, context: &str) -> Result<String> {
+        let cipher = Aes256Gcm::new_from_slice(&self.0).map_err(|e| anyhow!("Failed to create cipher: {}", e))?;
+        let combined = BASE64.decode(encrypted).map_err(|e| anyhow!("Invalid base64: {}", e))?;
+        if combined.len() < NONCE_LEN {
+            return Err(anyhow!("Encrypted data too short"));
+        }
+        let (nonce_bytes, ciphertext) = combined.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: context.as_bytes(),
+        };
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce_bytes), payload)
+            .map_err(|_| anyhow!("Decryption failed"))?;
+        String::from_utf8(plaintext).map_err(|e| anyhow!("Invalid UTF-8: {}", e))
+    }
+}
+
+/// Password of an environment-variable reference
+pub fn env_password(variable: &str) -> Result<String> {
+    std::env::var(variable).map_err(|_| anyhow!("Environment variable {} is not set", variable))
+}
+
+/// Default password file: `PGPASSFILE`, else `~/.pgpass`
+/// (`%APPDATA%\postgresql\pgpass.conf` on Windows)
+fn default_pgpass_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("PGPASSFILE") {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        dirs::config_dir().map(|dir| dir.join("postgresql").join("pgpass.conf"))
+    } else {
+        dirs::home_dir().map(|dir| dir.join(".pgpass"))
+    }
+}
+
+/// Fields of a `host:port:database:username:password` line; `\:` and `\\` are escapes
+fn parse_pgpass_line(line: &str) -> Option<Vec<String>> {
+    if line.trim_start().starts_with('#') || line.trim().is_empty() {
+        return None;
+    }
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => fields.last_mut()?.push(chars.next().unwrap_or('\\')),
+            ':' if fields.len() < 5 => fields.push(String::new()),
+            c => fields.last_mut()?.push(c),
+        }
+    }
+    (fields.len() == 5).then_some(fields)
+}
+
+/// First matching password of a `.pgpass`-style file; `*` matches any value
+pub fn pgpass_password(path: Option<&Path>, conn: &DbConnection) -> Result<Option<String>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => default_pgpass_path().ok_or_else(|| anyhow!("No home directory for .pgpass"))?,
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read password file {}: {}", path.display(), e))?;
+
+    let port = conn.port.unwrap_or(DEFAULT_PG_PORT).to_string();
+    let wanted = [
+        conn.host.as_deref().unwrap_or("localhost"),
+        port.as_str(),
+        conn.database.as_str(),
+        conn.username.as_deref().unwrap_or(""),
+    ];
+    Ok(contents.lines().filter_map(parse_pgpass_line).find_map(|fields| {
+        fields
+            .iter()
+            .zip(wanted)
+            .all(|(pattern, value)| pattern == "*" || pattern == value)
+            .then(|| fields[4].clone())
+    }))
+}
+
+/// Connection settings that can be shared: passwords removed, references kept
+pub fn without_secrets(conn: &DbConnection) -> DbConnection {
+    DbConnection {
+        password: None,
+        ..conn.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn passphrase(text: &str) -> MasterKey {
+        MasterKey::Passphrase(text.to_string())
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let (key, header) = VaultKey::create(&passphrase("correct horse"), 1_000).unwrap();
+        let encrypted = key.encrypt("s3cret:ü", "conn-1").unwrap();
+        assert!(!encrypted.contains("s3cret"));
+
+        let reopened = VaultKey::open(&passphrase("correct horse"), &header).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted, "conn-1").unwrap(), "s3cret:ü");
+        // Bound to its connection: copied onto another one it does not decrypt
+        assert!(reopened.decrypt(&encrypted, "conn-2").is_err());
+
+        let err = VaultKey::open(&passphrase("wrong"), &header).err().unwrap();
+        assert!(err.to_string().contains("Wrong master passphrase"));
+        assert!(VaultKey::create(&passphrase(""), 1_000).is_err());
+    }
+
+    #[test]
+    fn test_key_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("berry.key");
+        std::fs::write(&path, [7u8; 64]).unwrap();
+
+        let (key, header) = VaultKey::create(&MasterKey::KeyFile(path.clone()), DEFAULT_ITERATIONS).unwrap();
+        let encrypted = key.encrypt("pw", "conn").unwrap();
+        let reopened = VaultKey::open(&MasterKey::KeyFile(path.clone()), &header).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted, "conn").unwrap(), "pw");
+
+        std::fs::write(&path, [8u8; 64]).unwrap();
+        assert!(VaultKey::open(&MasterKey::KeyFile(path.clone()), &header).is_err());
+        std::fs::write(&path, b"short").unwrap();
+        assert!(VaultKey::open(&MasterKey::KeyFile(path), &header).is_err());
+    }
+
+    #[test]
+    fn test_pgpass_lookup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pgpass");
+        std::fs::write(
+            &path,
+            "# comment\n\
+             db.example.com:5432:app:admin:first\n\
+             *:*:app:me:pa\\:ss\\\\word\n\
+             *:*:*:*:fallback\n",
+        )
+        .unwrap();
+
+        let mut conn = DbConnection {
+            id: "pg".to_string(),
+            name: "pg".to_string(),
+            db_type: DbType::PostgreSQL,
+            host: Some("db.example.com".to_string()),
+            port: Some(5432),
+            database: "app".to_string(),
+            username: Some("admin".to_string()),
+            password: None,
+            password_source: PasswordSource::PgPass { path: None },
+            ssl: false,
+            created_at: 0,
+            last_used: None,
+        };
+        assert_eq!(pgpass_password(Some(&path), &conn).unwrap().as_deref(), Some("first"));
+        // No port means the default 5432, as in libpq
+        conn.port = None;
+        assert_eq!(pgpass_password(Some(&path), &conn).unwrap().as_deref(), Some("first"));
+        conn.username = Some("me".to_string());
+        assert_eq!(pgpass_password(Some(&path), &conn).unwrap().as_deref(), Some("pa:ss\\word"));
+        conn.database = "other".to_string();
+        assert_eq!(pgpass_password(Some(&path), &conn).unwrap().as_deref(), Some("fallback"));
+
+        assert!(pgpass_password(Some(&dir.path().join("missing")), &conn).is_err());
+        assert_eq!(parse_pgpass_line("too:few:fields"), None);
+    }
+}
@@ -0,0 +1,230 @@
+//! Table data editing
+//!
+//! Pending row changes from the grid editor become INSERT/UPDATE/DELETE
+//! statements keyed by the table's primary key. Values are inlined as
+//! literals so the same statements can be previewed and executed.
+
+use super::export::{sql_identifier, sql_literal};
+use super::types::*;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// A generated statement; UPDATE and DELETE must hit exactly one row
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditStatement {
+    pub sql: String,
+    pub single_row: bool,
+}
+
+fn identifier(db_type: &DbType, name: &str) -> String {
+    match db_type {
+        DbType::MySQL => format!("`{}`", name.replace('`', "``")),
+        _ => sql_identifier(name),
+    }
+}
+
+fn literal(db_type: &DbType, value: &Value) -> String {
+    match (db_type, value) {
+        // MySQL treats backslashes in strings as escapes by default
+        (DbType::MySQL, Value::String(s)) => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''")),
+        (DbType::SQLite, Value::Bool(b)) => if *b { "1" } else { "0" }.to_string(),
+        _ => sql_literal(value),
+    }
+}
+
+/// Columns of the table's primary key; tables without one can't be edited
+pub fn primary_key(table: &TableInfo) -> Result<Vec<&str>> {
+    let key: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| c.name.as_str())
+        .collect();
+    if key.is_empty() || table.kind != TableKind::Table {
+        return Err(anyhow!("{} has no primary key and can only be viewed", table.name));
+    }
+    Ok(key)
+}
+
+/// `SELECT *` of a table, ordered by its primary key when it has one
+pub fn select_statement(db_type: &DbType, table: &TableInfo) -> String {
+    let mut sql = format!("SELECT * FROM {}", qualified_name(db_type, table));
+    if let Ok(key) = primary_key(table) {
+        let order: Vec<String> = key.iter().map(|c| identifier(db_type, c)).collect();
+        sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+    }
+    sql
+}
+
+fn qualified_name(db_type: &DbType, table: &TableInfo) -> String {
+    format!("{}.{}", identifier(db_type, &table.schema), identifier(db_type, &table.name))
+}
+
+/// Statements applying `changes` to `table`, in order
+pub fn edit_statements(db_type: &DbType, table: &TableInfo, changes: &[RowChange]) -> Result<Vec<EditStatement>> {
+    let key = primary_key(table)?;
+    let name = qualified_name(db_type, table);
+
+    let check_columns = |values: &[ColumnValue]| -> Result<()> {
+        match values.iter().find(|v| !table.columns.iter().any(|c| c.name == v.column)) {
+            Some(unknown) => Err(anyhow!("{} has no column {}", table.name, unknown.column)),
+            None => Ok(()),
+        }
+    };
+    let where_clause = |row_key: &[ColumnValue]| -> Result<String> {
+        let conditions = key
+            .iter()
+            .map(|column| {
+                let value = row_key
+                    .iter()
+                    .find(|v| v.column == *column)
+                    .map(|v| &v.value)
+                    .filter(|v| !v.is_null())
+                    .ok_or_else(|| anyhow!("Missing primary key value for {}", column))?;
+                Ok(format!("{} = {}", identifier(db_type, column), literal(db_type, value)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(conditions.join(" AND "))
+    };
+
+    changes
+        .iter()
+        .map(|change| {
+            Ok(match change {
+                RowChange::Insert { values } => {
+                    check_columns(values)?;
+                    let sql = if values.is_empty() {
+                        match db_type {
+                            DbType::MySQL => format!("INSERT INTO {} () VALUES ()", name),
+                            _ => format!("INSERT INTO {} DEFAULT VALUES", name),
+                        }
+                    } else {
+                        let columns: Vec<String> = values.iter().map(|v| identifier(db_type, &v.column)).collect();
+                        let literals: Vec<String> = values.iter().map(|v| literal(db_type, &v.value)).collect();
+                        format!("INSERT INTO {} ({}) VALUES ({})", name, columns.join(", "), literals.join(", "))
+                    };
+                    EditStatement { sql, single_row: false }
+                }
+                RowChange::Update { key: row_key, values } => {
+                    check_columns(values)?;
+                    if values.is_empty() {
+                        return Err(anyhow!("Update without changed columns"));
+                    }
+                    let assignments: Vec<String> = values
+                        .iter()
+                        .map(|v| format!("{} = {}", identifier(db_type, &v.column), literal(db_type, &v.value)))
+                        .collect();
+                    EditStatement {
+                        sql: format!("UPDATE {} SET {} WHERE {}", name, assignments.join(", "), where_clause(row_key)?),
+                        single_row: true,
+                    }
+                }
+                RowChange::Delete { key: row_key } => EditStatement {
+                    sql: format!("DELETE FROM {} WHERE {}", name, where_clause(row_key)?),
+                    single_row: true,
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(name: &str, primary_key: bool) -> ColumnMeta {
+        ColumnMeta {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable: true,
+            default: None,
+            primary_key,
+        }
+    }
+
+    fn table(columns: Vec<ColumnMeta>) -> TableInfo {
+        TableInfo {
+            schema: "public".to_string(),
+            name: "users".to_string(),
+            kind: TableKind::Table,
+            columns,
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    fn value(column: &str, value: Value) -> ColumnValue {
+        ColumnValue {
+            column: column.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_statements_keyed_by_primary_key() {
+        let users = table(vec![column("id", true), column("name", false), column("active", false)]);
+        let changes = vec![
+            RowChange::Update {
+                key: vec![value("id", json!(1))],
+                values: vec![value("name", json!("O'Brien")), value("active", json!(false))],
+            },
+            RowChange::Insert {
+                values: vec![value("name", json!("New")), value("active", Value::Null)],
+            },
+            RowChange::Delete {
+                key: vec![value("id", json!(2))],
+            },
+        ];
+
+        let sql: Vec<String> = edit_statements(&DbType::PostgreSQL, &users, &changes)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.sql)
+            .collect();
+        assert_eq!(
+            sql,
+            vec![
+                "UPDATE \"public\".\"users\" SET \"name\" = 'O''Brien', \"active\" = FALSE WHERE \"id\" = 1",
+                "INSERT INTO \"public\".\"users\" (\"name\", \"active\") VALUES ('New', NULL)",
+                "DELETE FROM \"public\".\"users\" WHERE \"id\" = 2",
+            ]
+        );
+
+        let mysql = edit_statements(&DbType::MySQL, &users, &changes[..1]).unwrap();
+        assert_eq!(
+            mysql[0].sql,
+            "UPDATE `public`.`users` SET `name` = 'O''Brien', `active` = FALSE WHERE `id` = 1"
+        );
+        assert!(mysql[0].single_row);
+        assert_eq!(
+            select_statement(&DbType::SQLite, &users),
+            "SELECT * FROM \"public\".\"users\" ORDER BY \"id\""
+        );
+    }
+
+    #[test]
+    fn test_rejects_unkeyed_and_invalid_changes() {
+        let no_key = table(vec![column("name", false)]);
+        assert!(primary_key(&no_key).is_err());
+        assert!(edit_statements(&DbType::SQLite, &no_key, &[]).is_err());
+
+        let composite = table(vec![column("a", true), column("b", true), column("x", false)]);
+        let partial_key = RowChange::Delete {
+            key: vec![value("a", json!(1))],
+        };
+        assert!(edit_statements(&DbType::SQLite, &composite, &[partial_key]).is_err());
+        let unknown = RowChange::Insert {
+            values: vec![value("nope", json!(1))],
+        };
+        assert!(edit_statements(&DbType::SQLite, &composite, &[unknown]).is_err());
+
+        let full_key = RowChange::Delete {
+            key: vec![value("b", json!("y")), value("a", json!(1))],
+        };
+        assert_eq!(
+            edit_statements(&DbType::SQLite, &composite, &[full_key]).unwrap()[0].sql,
+            "DELETE FROM \"public\".\"users\" WHERE \"a\" = 1 AND \"b\" = 'y'"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Query result export
+//!
+//! Results are written page by page so large exports never have to be held
+//! in memory.
+
+use super::types::{ColumnInfo, ExportFormat};
+use anyhow::Result;
+use serde_json::Value;
+use std::io::Write;
+
+/// Writes result pages in one of the export formats
+pub struct ExportWriter<W: Write> {
+    format: ExportFormat,
+    columns: Vec<String>,
+    /// Target table of `INSERT` statements
+    table: String,
+    out: W,
+    rows_written: usize,
+}
+
+impl<W: Write> ExportWriter<W> {
+    pub fn new(format: ExportFormat, columns: &[ColumnInfo], table: &str, mut out: W) -> Result<Self> {
+        let columns: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+        match format {
+            ExportFormat::Csv => {
+                let header: Vec<String> = columns.iter().map(|name| csv_field(&Value::String(name.clone()))).collect();
+                writeln!(out, "{}", header.join(","))?;
+            }
+            ExportFormat::Json => write!(out, "[")?,
+            ExportFormat::Sql => {}
+        }
+
+        Ok(Self {
+            format,
+            columns,
+            table: table.to_string(),
+            out,
+            rows_written: 0,
+        })
+    }
+
+    pub fn write_rows(&mut self, rows: &[Vec<Value>]) -> Result<()> {
+        for row in rows {
+            match self.format {
+                ExportFormat::Csv => {
+                    let fields: Vec<String> = row.iter().map(csv_field).collect();
+                    writeln!(self.out, "{}", fields.join(","))?;
+                }
+                ExportFormat::Json => {
+                    // Written by hand so the keys keep the column order
+                    let fields: Vec<String> = self
+                        .columns
+                        .iter()
+                        .zip(row)
+                        .map(|(name, value)| format!("{}: {}", Value::String(name.clone()), value))
+                        .collect();
+                    let separator = if self.rows_written == 0 { "" } else { "," };
+                    write!(self.out, "{}\n  {{{}}}", separator, fields.join(", "))?;
+                }
+                ExportFormat::Sql => {
+                    let names: Vec<String> = self.columns.iter().map(|name| sql_identifier(name)).collect();
+                    let values: Vec<String> = row.iter().map(sql_literal).collect();
+                    writeln!(
+                        self.out,
+                        "INSERT INTO {} ({}) VALUES ({});",
+                        sql_identifier(&self.table),
+                        names.join(", "),
+                        values.join(", ")
+                    )?;
+                }
+            }
+            self.rows_written += 1;
+        }
+        Ok(())
+    }
+
+    /// Close the document and return the number of rows written
+    pub fn finish(mut self) -> Result<usize> {
+        if self.format == ExportFormat::Json {
+            writeln!(self.out, "{}]", if self.rows_written == 0 { "" } else { "\n" })?;
+        }
+        self.out.flush()?;
+        Ok(self.rows_written)
+    }
+}
+
+/// RFC 4180 field: quoted when it contains a separator, quote or line break
+pub fn csv_field(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+pub fn sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        // JSON columns
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns() -> Vec<ColumnInfo> {
+        ["id", "name", "data"]
+            .iter()
+            .map(|name| ColumnInfo {
+                name: name.to_string(),
+                type_name: "text".to_string(),
+            })
+            .collect()
+    }
+
+    fn export(format: ExportFormat, pages: &[Vec<Vec<Value>>]) -> (String, usize) {
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(format, &columns(), "people", &mut out).unwrap();
+        for page in pages {
+            writer.write_rows(page).unwrap();
+        }
+        let rows = writer.finish().unwrap();
+        (String::from_utf8(out).unwrap(), rows)
+    }
+
+    fn pages() -> Vec<Vec<Vec<Value>>> {
+        vec![
+            vec![vec![json!(1), json!("O'Brien, \"Pat\""), json!({"a": 1})]],
+            vec![vec![json!(2), Value::Null, json!(true)]],
+        ]
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let (csv, rows) = export(ExportFormat::Csv, &pages());
+        assert_eq!(rows, 2);
+        assert_eq!(
+            csv,
+            "id,name,data\n1,\"O'Brien, \"\"Pat\"\"\",\"{\"\"a\"\":1}\"\n2,,true\n"
+        );
+    }
+
+    #[test]
+    fn test_json_export_keeps_column_order() {
+        let (text, _) = export(ExportFormat::Json, &pages());
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(parsed[0]["name"], json!("O'Brien, \"Pat\""));
+        assert_eq!(parsed[1]["data"], json!(true));
+        assert!(text.find("\"id\"").unwrap() < text.find("\"name\"").unwrap());
+
+        let (empty, rows) = export(ExportFormat::Json, &[]);
+        assert_eq!(rows, 0);
+        assert_eq!(serde_json::from_str::<Value>(&empty).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_sql_export() {
+        let (sql, _) = export(ExportFormat::Sql, &pages());
+        assert_eq!(
+            sql,
+            "INSERT INTO \"people\" (\"id\", \"name\", \"data\") VALUES (1, 'O''Brien, \"Pat\"', '{\"a\":1}');\n\
+             INSERT INTO \"people\" (\"id\", \"name\", \"data\") VALUES (2, NULL, TRUE);\n"
+        );
+    }
+}
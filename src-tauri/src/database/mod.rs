@@ -1,6 +1,14 @@
 pub mod commands;
+pub mod credentials;
+pub mod editor;
+pub mod export;
+pub mod mongo;
 pub mod operations;
+pub mod plan;
+pub mod pool;
+pub mod schema;
 pub mod types;
+pub mod values;
 
 pub use commands::DbManager;
-pub use types::{ConnectionTestResult, DbConnection, DbType, QueryResult};
+pub use types::{ColumnInfo, ConnectionTestResult, DbConnection, DbType, ExportFormat, QueryResult};
@@ -1,4 +1,8 @@
+use super::credentials::{
+    env_password, pgpass_password, without_secrets, MasterKey, VaultKey, DEFAULT_ITERATIONS,
+};
 use super::types::*;
+use super::values;
 use anyhow::Result;
 use std::fs;
 use std::path::PathBuf;
@@ -6,6 +10,10 @@ use tauri::Manager;
 
 pub struct DatabaseManager {
     config_path: PathBuf,
+    /// Key of the credential store once unlocked
+    vault_key: Option<VaultKey>,
+    /// PBKDF2 rounds used when the store is created
+    kdf_iterations: u32,
 }
 
 impl DatabaseManager {
@@ -17,32 +25,170 @@ impl DatabaseManager {
             .expect("Failed to get app config dir");
 
         fs::create_dir_all(&config_dir)?;
-        let config_path = config_dir.join("db_connections.json");
+        Ok(Self::with_path(config_dir.join("db_connections.json")))
+    }
 
-        Ok(Self { config_path })
+    pub fn with_path(config_path: PathBuf) -> Self {
+        Self {
+            config_path,
+            vault_key: None,
+            kdf_iterations: DEFAULT_ITERATIONS,
+        }
     }
 
-    pub fn load_connections(&self) -> Result<Vec<DbConnection>> {
+    fn read_list(&self) -> Result<DbConnectionList> {
         if !self.config_path.exists() {
-            return Ok(Vec::new());
+            return Ok(DbConnectionList::default());
         }
-
         let data = fs::read_to_string(&self.config_path)?;
-        let list: DbConnectionList = serde_json::from_str(&data)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn write_list(&self, list: &DbConnectionList) -> Result<()> {
+        let data = serde_json::to_string_pretty(list)?;
+        fs::write(&self.config_path, data)?;
+        Ok(())
+    }
 
-        // TODO: Decrypt passwords
-        Ok(list.connections)
+    /// Saved connections without their passwords
+    pub fn load_connections(&self) -> Result<Vec<DbConnection>> {
+        Ok(self.read_list()?.connections.iter().map(without_secrets).collect())
     }
 
+    /// Save the connection list. A password set on a connection is encrypted
+    /// (the store must be unlocked); without one the saved password is kept.
     pub fn save_connections(&self, connections: &[DbConnection]) -> Result<()> {
-        // TODO: Encrypt passwords before saving
+        let old = self.read_list()?;
+        let mut list = DbConnectionList {
+            connections: Vec::new(),
+            secrets: Default::default(),
+            vault: old.vault.clone(),
+        };
+
+        for conn in connections {
+            let mut saved = without_secrets(conn);
+            if conn.password_source == PasswordSource::Stored {
+                let new_password = conn.password.as_deref().filter(|p| !p.is_empty());
+                let legacy = old.connections.iter().find(|c| c.id == conn.id).and_then(|c| c.password.clone());
+                match (new_password, &self.vault_key) {
+                    (Some(password), Some(key)) => {
+                        list.secrets.insert(conn.id.clone(), key.encrypt(password, &conn.id)?);
+                    }
+                    (Some(_), None) => {
+                        return Err(anyhow::anyhow!(
+                            "Credential storage is locked; unlock it to save passwords"
+                        ))
+                    }
+                    (None, _) => {
+                        if let Some(secret) = old.secrets.get(&conn.id) {
+                            list.secrets.insert(conn.id.clone(), secret.clone());
+                        } else {
+                            // Plain text from older versions until the store is unlocked
+                            saved.password = legacy;
+                        }
+                    }
+                }
+            }
+            list.connections.push(saved);
+        }
+
+        self.write_list(&list)
+    }
+
+    /// The connection with its password filled in from storage or its reference
+    pub fn resolve(&self, conn: &DbConnection) -> Result<DbConnection> {
+        let mut resolved = conn.clone();
+        // A password typed into the UI (e.g. testing an unsaved connection) wins
+        if conn.password.as_deref().is_some_and(|p| !p.is_empty()) {
+            return Ok(resolved);
+        }
+
+        resolved.password = match &conn.password_source {
+            PasswordSource::Env { variable } => Some(env_password(variable)?),
+            PasswordSource::PgPass { path } => {
+                pgpass_password(path.as_deref().map(std::path::Path::new), conn)?
+            }
+            PasswordSource::Stored => {
+                let list = self.read_list()?;
+                match (list.secrets.get(&conn.id), &self.vault_key) {
+                    (Some(secret), Some(key)) => Some(key.decrypt(secret, &conn.id)?),
+                    (Some(_), None) => {
+                        return Err(anyhow::anyhow!(
+                            "Credential storage is locked; unlock it to use the saved password"
+                        ))
+                    }
+                    (None, _) => list
+                        .connections
+                        .into_iter()
+                        .find(|c| c.id == conn.id)
+                        .and_then(|c| c.password),
+                }
+            }
+        };
+        Ok(resolved)
+    }
+
+    pub fn vault_status(&self) -> Result<VaultStatus> {
+        let list = self.read_list()?;
+        Ok(VaultStatus {
+            initialized: list.vault.is_some(),
+            unlocked: self.vault_key.is_some(),
+            stored_secrets: list.secrets.len(),
+            plaintext_secrets: list.connections.iter().filter(|c| c.password.is_some()).count(),
+        })
+    }
+
+    /// Unlock the credential store, creating it on first use, and encrypt
+    /// passwords left in plain text by older versions
+    pub fn unlock(&mut self, master: &MasterKey) -> Result<VaultStatus> {
+        let mut list = self.read_list()?;
+        let key = match &list.vault {
+            Some(header) => VaultKey::open(master, header)?,
+            None => {
+                let (key, header) = VaultKey::create(master, self.kdf_iterations)?;
+                list.vault = Some(header);
+                key
+            }
+        };
+
+        for conn in &mut list.connections {
+            if let Some(password) = conn.password.take() {
+                list.secrets.insert(conn.id.clone(), key.encrypt(&password, &conn.id)?);
+            }
+        }
+        self.write_list(&list)?;
+        self.vault_key = Some(key);
+        self.vault_status()
+    }
+
+    pub fn lock(&mut self) {
+        self.vault_key = None;
+    }
+
+    /// Write the connection list without passwords; references are kept
+    pub fn export_connections(&self, path: &std::path::Path) -> Result<usize> {
         let list = DbConnectionList {
-            connections: connections.to_vec(),
+            connections: self.load_connections()?,
+            ..Default::default()
         };
+        fs::write(path, serde_json::to_string_pretty(&list)?)?;
+        Ok(list.connections.len())
+    }
 
-        let data = serde_json::to_string_pretty(&list)?;
-        fs::write(&self.config_path, data)?;
-        Ok(())
+    /// Add the connections of an exported list; ids already present are
+    /// skipped and passwords in the file are ignored
+    pub fn import_connections(&self, path: &std::path::Path) -> Result<usize> {
+        let imported: DbConnectionList = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut connections = self.load_connections()?;
+        let before = connections.len();
+        for conn in imported.connections {
+            if !connections.iter().any(|c| c.id == conn.id) {
+                connections.push(without_secrets(&conn));
+            }
+        }
+        let added = connections.len() - before;
+        self.save_connections(&connections)?;
+        Ok(added)
     }
 }
 
@@ -55,12 +201,7 @@ pub async fn test_connection(conn: &DbConnection) -> Result<ConnectionTestResult
         DbType::PostgreSQL => test_postgres_connection(conn, start).await,
         DbType::MySQL => test_mysql_connection(conn, start).await,
         DbType::SQLite => test_sqlite_connection(conn, start).await,
-        DbType::MongoDB => Ok(ConnectionTestResult {
-            success: false,
-            message: "MongoDB support coming soon".to_string(),
-            latency_ms: Some(start.elapsed().as_millis() as u64),
-            server_version: None,
-        }),
+        DbType::MongoDB => super::mongo::test_connection(conn, start).await,
     }
 }
 
@@ -243,11 +384,35 @@ pub async fn execute_query(conn: &DbConnection, query: String) -> Result<QueryRe
         DbType::PostgreSQL => execute_postgres_query(conn, &query).await,
         DbType::MySQL => execute_mysql_query(conn, &query).await,
         DbType::SQLite => execute_sqlite_query(conn, &query).await,
-        DbType::MongoDB => Err(anyhow::anyhow!("MongoDB support coming soon")),
+        DbType::MongoDB => execute_mongo_query(conn, &query).await,
     }
 }
 
+/// Run a shell command; each document becomes a row with one JSON column
+async fn execute_mongo_query(conn: &DbConnection, query: &str) -> Result<QueryResult> {
+    use super::mongo;
+
+    let command = mongo::parse_command(query)?;
+    let client = mongodb::Client::with_uri_str(mongo::connection_uri(conn)).await?;
+    let result = mongo::execute(&client, &conn.database, command).await?;
+    Ok(QueryResult {
+        columns: vec![ColumnInfo {
+            name: "document".to_string(),
+            type_name: "document".to_string(),
+        }],
+        affected_rows: None,
+        rows: result.documents.into_iter().map(|doc| vec![doc]).collect(),
+        cursor: None,
+    })
+}
+
 async fn execute_postgres_query(conn: &DbConnection, query: &str) -> Result<QueryResult> {
+    let client = connect_postgres(conn).await?;
+    run_postgres_query(&client, query).await
+}
+
+/// Open a PostgreSQL session (the connection task runs in the background)
+pub(crate) async fn connect_postgres(conn: &DbConnection) -> Result<tokio_postgres::Client> {
     use tokio_postgres::NoTls;
 
     let host = conn.host.as_deref().unwrap_or("localhost");
@@ -270,62 +435,119 @@ async fn execute_postgres_query(conn: &DbConnection, query: &str) -> Result<Quer
         }
     });
 
-    let trimmed_query = query.trim().to_uppercase();
-    if trimmed_query.starts_with("SELECT") || trimmed_query.starts_with("EXPLAIN") {
-        let rows = client.query(query, &[]).await?;
-
-        let mut columns = Vec::new();
-        let mut result_rows = Vec::new();
+    Ok(client)
+}
 
-        if let Some(first_row) = rows.first() {
-            columns = first_row
-                .columns()
-                .iter()
-                .map(|col| col.name().to_string())
-                .collect();
+pub(crate) async fn run_postgres_query(client: &tokio_postgres::Client, query: &str) -> Result<QueryResult> {
+    // The prepared statement tells whether rows come back (CTEs, RETURNING, SHOW...)
+    let statement = match client.prepare(query).await {
+        Ok(statement) => statement,
+        Err(e) if is_multiple_commands_error(&e) => {
+            client.batch_execute(query).await?;
+            return Ok(QueryResult::default());
         }
+        Err(e) => return Err(e.into()),
+    };
 
-        for row in rows {
-            let mut row_map = std::collections::HashMap::new();
-            for (idx, column) in row.columns().iter().enumerate() {
-                let value: serde_json::Value = if let Ok(s) = row.try_get::<_, String>(idx) {
-                    serde_json::Value::String(s)
-                } else if let Ok(i) = row.try_get::<_, i64>(idx) {
-                    serde_json::Value::Number(i.into())
-                } else if let Ok(f) = row.try_get::<_, f64>(idx) {
-                    serde_json::Number::from_f64(f)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                } else if let Ok(b) = row.try_get::<_, bool>(idx) {
-                    serde_json::Value::Bool(b)
-                } else {
-                    serde_json::Value::Null
-                };
-
-                row_map.insert(column.name().to_string(), value);
-            }
-            result_rows.push(row_map);
-        }
+    if statement.columns().is_empty() {
+        let affected = client.execute(&statement, &[]).await?;
+        return Ok(QueryResult::affected(affected));
+    }
 
-        Ok(QueryResult {
-            columns: Some(columns),
-            rows: Some(result_rows),
-            affected_rows: None,
-        })
-    } else {
-        let result = client.execute(query, &[]).await?;
+    let rows = client.query(&statement, &[]).await?;
+    Ok(QueryResult {
+        columns: values::postgres_columns(statement.columns()),
+        rows: rows.iter().map(values::postgres_row).collect(),
+        ..Default::default()
+    })
+}
 
-        Ok(QueryResult {
-            columns: None,
-            rows: None,
-            affected_rows: Some(result as usize),
-        })
+fn is_multiple_commands_error(e: &tokio_postgres::Error) -> bool {
+    e.as_db_error()
+        .is_some_and(|db| db.message().contains("multiple commands"))
+}
+
+/// Declare a server-side cursor for `query` and fetch its first page
+///
+/// `WITH HOLD` keeps the cursor usable after the statement's implicit
+/// transaction commits, so paging works with and without an open transaction.
+pub(crate) async fn open_postgres_cursor(
+    client: &tokio_postgres::Client,
+    name: &str,
+    query: &str,
+    page_size: usize,
+) -> Result<QueryResult> {
+    client
+        .batch_execute(&format!("DECLARE {} NO SCROLL CURSOR WITH HOLD FOR {}", name, query))
+        .await?;
+    fetch_postgres_cursor(client, name, page_size).await
+}
+
+pub(crate) async fn fetch_postgres_cursor(
+    client: &tokio_postgres::Client,
+    name: &str,
+    count: usize,
+) -> Result<QueryResult> {
+    let statement = client
+        .prepare(&format!("FETCH FORWARD {} FROM {}", count, name))
+        .await?;
+    let rows = client.query(&statement, &[]).await?;
+    Ok(QueryResult {
+        columns: values::postgres_columns(statement.columns()),
+        rows: rows.iter().map(values::postgres_row).collect(),
+        ..Default::default()
+    })
+}
+
+pub(crate) async fn close_postgres_cursor(client: &tokio_postgres::Client, name: &str) -> Result<()> {
+    client.batch_execute(&format!("CLOSE {}", name)).await?;
+    Ok(())
+}
+
+/// The statement without its trailing `;` when it is a single plain query
+/// whose rows can be paged through a cursor or `LIMIT`/`OFFSET`
+pub fn pageable_query(query: &str) -> Option<&str> {
+    let trimmed = query.trim().trim_end_matches(';').trim_end();
+    if trimmed.contains(';') {
+        return None;
     }
+
+    let upper = trimmed.to_uppercase();
+    let words: Vec<&str> = upper
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has = |word: &str| words.contains(&word);
+
+    match words.first().copied() {
+        Some("SELECT" | "VALUES" | "TABLE") => {}
+        // Data-modifying CTEs can't be declared as a cursor
+        Some("WITH") if !(has("INSERT") || has("UPDATE") || has("DELETE") || has("MERGE")) => {}
+        _ => return None,
+    }
+    // SELECT ... INTO creates a table; locking reads can't be held
+    if has("INTO") || upper.contains("FOR UPDATE") || upper.contains("FOR SHARE") {
+        return None;
+    }
+    Some(trimmed)
+}
+
+/// Page of a pageable query, for servers without usable cursors
+pub fn offset_page_query(query: &str, limit: usize, offset: usize) -> String {
+    format!(
+        "SELECT * FROM ({}) AS berry_page LIMIT {} OFFSET {}",
+        query, limit, offset
+    )
 }
 
 async fn execute_mysql_query(conn: &DbConnection, query: &str) -> Result<QueryResult> {
-    use mysql_async::prelude::*;
+    let mut conn_pool = mysql_async::Conn::new(mysql_opts(conn)?).await?;
+    let result = run_mysql_query(&mut conn_pool, query).await;
+    conn_pool.disconnect().await?;
+    result
+}
 
+pub(crate) fn mysql_opts(conn: &DbConnection) -> Result<mysql_async::Opts> {
     let host = conn.host.as_deref().unwrap_or("localhost");
     let port = conn.port.unwrap_or(3306);
     let database = &conn.database;
@@ -338,120 +560,230 @@ async fn execute_mysql_query(conn: &DbConnection, query: &str) -> Result<QueryRe
     );
 
     let opts = mysql_async::Opts::from_url(&connection_string)?;
-    let mut conn_pool = mysql_async::Conn::new(opts).await?;
-
-    let trimmed_query = query.trim().to_uppercase();
-    if trimmed_query.starts_with("SELECT") || trimmed_query.starts_with("EXPLAIN") {
-        let result: Vec<mysql_async::Row> = conn_pool.query(query).await?;
+    // UPDATE reports the rows it matched rather than the ones it changed, so
+    // an edit writing a column's current value still hits its one row
+    Ok(mysql_async::OptsBuilder::from_opts(opts).client_found_rows(true).into())
+}
 
-        let mut columns = Vec::new();
-        let mut result_rows = Vec::new();
+pub(crate) async fn run_mysql_query(conn_pool: &mut mysql_async::Conn, query: &str) -> Result<QueryResult> {
+    use mysql_async::prelude::*;
 
-        if let Some(first_row) = result.first() {
-            columns = first_row
-                .columns_ref()
-                .iter()
-                .map(|col| col.name_str().to_string())
-                .collect();
+    let mut result = conn_pool.query_iter(query).await?;
+    let mut output = None;
+    let mut affected = 0;
+
+    // Walk every result set; the first one with columns is returned
+    loop {
+        let columns = result.columns().filter(|columns| !columns.is_empty());
+        affected += result.affected_rows();
+        let rows: Vec<mysql_async::Row> = result.collect().await?;
+
+        if let (Some(columns), None) = (columns, &output) {
+            output = Some(QueryResult {
+                columns: values::mysql_columns(&columns),
+                rows: rows.into_iter().map(|row| values::mysql_row(&columns, row)).collect(),
+                ..Default::default()
+            });
         }
-
-        for row in result {
-            let mut row_map = std::collections::HashMap::new();
-            for (idx, column) in row.columns_ref().iter().enumerate() {
-                let value: serde_json::Value = if let Some(s) = row.get::<String, _>(idx) {
-                    serde_json::Value::String(s)
-                } else if let Some(i) = row.get::<i64, _>(idx) {
-                    serde_json::Value::Number(serde_json::Number::from(i))
-                } else if let Some(f) = row.get::<f64, _>(idx) {
-                    serde_json::Number::from_f64(f)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                } else if let Some(b) = row.get::<bool, _>(idx) {
-                    serde_json::Value::Bool(b)
-                } else {
-                    serde_json::Value::Null
-                };
-
-                row_map.insert(column.name_str().to_string(), value);
-            }
-            result_rows.push(row_map);
+        if result.is_empty() {
+            break;
         }
+    }
 
-        conn_pool.disconnect().await?;
+    Ok(output.unwrap_or_else(|| QueryResult::affected(affected)))
+}
 
-        Ok(QueryResult {
-            columns: Some(columns),
-            rows: Some(result_rows),
-            affected_rows: None,
-        })
-    } else {
-        let result = conn_pool.exec_drop(query, ()).await?;
-        conn_pool.disconnect().await?;
+async fn execute_sqlite_query(conn: &DbConnection, query: &str) -> Result<QueryResult> {
+    let connection = open_sqlite(conn)?;
+    run_sqlite_query(&connection, query)
+}
 
-        Ok(QueryResult {
-            columns: None,
-            rows: None,
-            affected_rows: Some(0), // MySQL doesn't return affected rows easily
-        })
-    }
+pub(crate) fn open_sqlite(conn: &DbConnection) -> Result<rusqlite::Connection> {
+    // For SQLite, database field contains the file path
+    Ok(rusqlite::Connection::open(&conn.database)?)
 }
 
-async fn execute_sqlite_query(conn: &DbConnection, query: &str) -> Result<QueryResult> {
-    use rusqlite::Connection;
+pub(crate) fn run_sqlite_query(connection: &rusqlite::Connection, query: &str) -> Result<QueryResult> {
+    let mut stmt = match connection.prepare(query) {
+        Ok(stmt) => stmt,
+        Err(rusqlite::Error::MultipleStatement) => {
+            connection.execute_batch(query)?;
+            return Ok(QueryResult::default());
+        }
+        Err(e) => return Err(e.into()),
+    };
 
-    let db_path = &conn.database;
-    let connection = Connection::open(db_path)?;
+    if stmt.column_count() == 0 {
+        let affected = stmt.execute([])?;
+        return Ok(QueryResult::affected(affected as u64));
+    }
 
-    let trimmed_query = query.trim().to_uppercase();
-    if trimmed_query.starts_with("SELECT")
-        || trimmed_query.starts_with("EXPLAIN")
-        || trimmed_query.starts_with("PRAGMA")
-    {
-        let mut stmt = connection.prepare(query)?;
+    let names: Vec<String> = stmt.column_names().into_iter().map(|s| s.to_string()).collect();
+    // Expression columns have no declared type; the first value's storage class is used
+    let mut types: Vec<Option<String>> = stmt
+        .columns()
+        .iter()
+        .map(|column| column.decl_type().map(|t| t.to_string()))
+        .collect();
+
+    let mut rows = stmt.query([])?;
+    let mut result_rows = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(names.len());
+        for (idx, column_type) in types.iter_mut().enumerate() {
+            let value = row.get_ref(idx)?;
+            if column_type.is_none() && !matches!(value, rusqlite::types::ValueRef::Null) {
+                *column_type = Some(values::sqlite_storage_class(value).to_string());
+            }
+            values.push(values::sqlite_value(value));
+        }
+        result_rows.push(values);
+    }
 
-        let column_names: Vec<String> = stmt
-            .column_names()
+    Ok(QueryResult {
+        columns: names
             .into_iter()
-            .map(|s| s.to_string())
-            .collect();
-
-        let rows = stmt.query_map([], |row| {
-            let mut row_map = std::collections::HashMap::new();
-            for (idx, col_name) in column_names.iter().enumerate() {
-                let value: serde_json::Value = if let Ok(s) = row.get::<_, String>(idx) {
-                    serde_json::Value::String(s)
-                } else if let Ok(i) = row.get::<_, i64>(idx) {
-                    serde_json::Value::Number(i.into())
-                } else if let Ok(f) = row.get::<_, f64>(idx) {
-                    serde_json::Number::from_f64(f)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                } else {
-                    serde_json::Value::Null
-                };
-
-                row_map.insert(col_name.clone(), value);
-            }
-            Ok(row_map)
-        })?;
+            .zip(types)
+            .map(|(name, type_name)| ColumnInfo {
+                name,
+                type_name: type_name.unwrap_or_default(),
+            })
+            .collect(),
+        rows: result_rows,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pageable_query() {
+        assert_eq!(pageable_query(" SELECT * FROM t; "), Some("SELECT * FROM t"));
+        assert_eq!(
+            pageable_query("with x as (select 1) select * from x"),
+            Some("with x as (select 1) select * from x")
+        );
+        assert_eq!(pageable_query("VALUES (1), (2)"), Some("VALUES (1), (2)"));
+        assert_eq!(pageable_query("WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"), None);
+        assert_eq!(pageable_query("INSERT INTO t VALUES (1) RETURNING id"), None);
+        assert_eq!(pageable_query("SELECT * INTO copy FROM t"), None);
+        assert_eq!(pageable_query("SELECT * FROM t FOR UPDATE"), None);
+        assert_eq!(pageable_query("SELECT 1; SELECT 2"), None);
+        assert_eq!(pageable_query("SHOW search_path"), None);
+    }
+
+    fn manager(dir: &tempfile::TempDir) -> DatabaseManager {
+        DatabaseManager {
+            kdf_iterations: 1_000,
+            ..DatabaseManager::with_path(dir.path().join("db_connections.json"))
+        }
+    }
 
-        let mut result_rows = Vec::new();
-        for row in rows {
-            result_rows.push(row?);
+    fn connection(id: &str, password: Option<&str>) -> DbConnection {
+        DbConnection {
+            id: id.to_string(),
+            name: id.to_string(),
+            db_type: DbType::PostgreSQL,
+            host: Some("localhost".to_string()),
+            port: Some(5432),
+            database: "app".to_string(),
+            username: Some("me".to_string()),
+            password: password.map(str::to_string),
+            password_source: PasswordSource::Stored,
+            ssl: false,
+            created_at: 0,
+            last_used: None,
         }
+    }
 
-        Ok(QueryResult {
-            columns: Some(column_names),
-            rows: Some(result_rows),
-            affected_rows: None,
-        })
-    } else {
-        let affected = connection.execute(query, [])?;
+    #[test]
+    fn test_mysql_opts_count_found_rows() {
+        let conn = DbConnection {
+            db_type: DbType::MySQL,
+            port: Some(3306),
+            ..connection("m", Some("secret"))
+        };
+        let opts = mysql_opts(&conn).unwrap();
+        assert!(opts.client_found_rows());
+        assert_eq!((opts.ip_or_hostname(), opts.tcp_port()), ("localhost", 3306));
+        assert_eq!(opts.db_name(), Some("app"));
+    }
 
-        Ok(QueryResult {
-            columns: None,
-            rows: None,
-            affected_rows: Some(affected),
-        })
+    #[test]
+    fn test_passwords_encrypted_at_rest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut mgr = manager(&dir);
+        let passphrase = MasterKey::Passphrase("master".to_string());
+
+        assert!(mgr.save_connections(&[connection("a", Some("hunter2"))]).is_err());
+        mgr.unlock(&passphrase).unwrap();
+        mgr.save_connections(&[connection("a", Some("hunter2"))]).unwrap();
+
+        let on_disk = fs::read_to_string(dir.path().join("db_connections.json")).unwrap();
+        assert!(!on_disk.contains("hunter2"));
+        let listed = mgr.load_connections().unwrap();
+        assert_eq!(listed[0].password, None);
+        assert_eq!(mgr.resolve(&listed[0]).unwrap().password.as_deref(), Some("hunter2"));
+
+        // Saving without a password keeps the stored one
+        mgr.save_connections(&listed).unwrap();
+        let mut reopened = manager(&dir);
+        assert!(reopened.resolve(&listed[0]).is_err());
+        assert!(reopened.unlock(&MasterKey::Passphrase("wrong".to_string())).is_err());
+        reopened.unlock(&passphrase).unwrap();
+        assert_eq!(reopened.resolve(&listed[0]).unwrap().password.as_deref(), Some("hunter2"));
+    }
+
+    #[test]
+    fn test_legacy_plaintext_encrypted_on_unlock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("db_connections.json");
+        fs::write(
+            &path,
+            serde_json::json!({ "connections": [connection("old", Some("plain"))] }).to_string(),
+        )
+        .unwrap();
+
+        let mut mgr = manager(&dir);
+        assert_eq!(mgr.vault_status().unwrap().plaintext_secrets, 1);
+        let conn = mgr.load_connections().unwrap().remove(0);
+        assert_eq!(conn.password_source, PasswordSource::Stored);
+        assert_eq!(mgr.resolve(&conn).unwrap().password.as_deref(), Some("plain"));
+
+        let status = mgr.unlock(&MasterKey::Passphrase("master".to_string())).unwrap();
+        assert_eq!((status.plaintext_secrets, status.stored_secrets), (0, 1));
+        assert!(!fs::read_to_string(&path).unwrap().contains("plain"));
+        assert_eq!(mgr.resolve(&conn).unwrap().password.as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn test_references_and_export_without_secrets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut mgr = manager(&dir);
+        mgr.unlock(&MasterKey::Passphrase("master".to_string())).unwrap();
+
+        std::env::set_var("BERRY_TEST_DB_PASSWORD", "from-env");
+        let mut env = connection("env", None);
+        env.password_source = PasswordSource::Env {
+            variable: "BERRY_TEST_DB_PASSWORD".to_string(),
+        };
+        mgr.save_connections(&[connection("a", Some("hunter2")), env.clone()]).unwrap();
+        assert_eq!(mgr.resolve(&env).unwrap().password.as_deref(), Some("from-env"));
+
+        let export = dir.path().join("export.json");
+        assert_eq!(mgr.export_connections(&export).unwrap(), 2);
+        let exported = fs::read_to_string(&export).unwrap();
+        assert!(!exported.contains("hunter2"));
+        assert!(exported.contains("BERRY_TEST_DB_PASSWORD"));
+
+        let other = tempfile::TempDir::new().unwrap();
+        let target = manager(&other);
+        target.save_connections(&[connection("a", None)]).unwrap();
+        assert_eq!(target.import_connections(&export).unwrap(), 1);
+        let imported = target.load_connections().unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[1].password_source, env.password_source);
     }
 }
@@ -0,0 +1,398 @@
+//! Query plans
+//!
+//! Runs the database's EXPLAIN variant and turns its output into a tree of
+//! `PlanNode`s. Each node gets a `weight`: its share of the plan's exclusive
+//! time (analyzed plans) or cost, which the UI shows as hotspots.
+
+use super::types::*;
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+/// EXPLAIN statement for a query
+pub fn explain_statement(db_type: &DbType, query: &str, analyze: bool) -> Result<String> {
+    let query = query.trim().trim_end_matches(';');
+    Ok(match db_type {
+        DbType::PostgreSQL if analyze => format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {}", query),
+        DbType::PostgreSQL => format!("EXPLAIN (FORMAT JSON) {}", query),
+        DbType::MySQL => format!("EXPLAIN FORMAT=JSON {}", query),
+        DbType::SQLite => format!("EXPLAIN QUERY PLAN {}", query),
+        DbType::MongoDB => return Err(anyhow!("Query plans are not available for MongoDB")),
+    })
+}
+
+/// Build the plan from the rows EXPLAIN returned
+pub fn parse_plan(db_type: &DbType, result: &QueryResult, analyzed: bool) -> Result<QueryPlan> {
+    let first = || {
+        result
+            .rows
+            .first()
+            .and_then(|row| row.first())
+            .ok_or_else(|| anyhow!("EXPLAIN returned no plan"))
+    };
+    let mut plan = match db_type {
+        DbType::PostgreSQL => postgres_plan(&json_value(first()?)?, analyzed)?,
+        DbType::MySQL => mysql_plan(&json_value(first()?)?)?,
+        DbType::SQLite => sqlite_plan(result)?,
+        DbType::MongoDB => return Err(anyhow!("Query plans are not available for MongoDB")),
+    };
+    assign_weights(&mut plan);
+    Ok(plan)
+}
+
+/// JSON columns arrive parsed from PostgreSQL and as text from MySQL
+fn json_value(value: &Value) -> Result<Value> {
+    match value {
+        Value::String(text) => Ok(serde_json::from_str(text)?),
+        other => Ok(other.clone()),
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        // MySQL reports costs as strings
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(object: &Map<String, Value>, key: &str) -> Option<String> {
+    object.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+// ============================================================================
+// PostgreSQL
+// ============================================================================
+
+fn postgres_plan(value: &Value, analyzed: bool) -> Result<QueryPlan> {
+    let top = value
+        .as_array()
+        .and_then(|items| items.first())
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("Unexpected EXPLAIN output"))?;
+    let root = top
+        .get("Plan")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("EXPLAIN output has no Plan"))?;
+
+    Ok(QueryPlan {
+        nodes: vec![postgres_node(root)],
+        planning_time_ms: number(top.get("Planning Time")),
+        execution_time_ms: number(top.get("Execution Time")),
+        analyzed,
+        raw: serde_json::to_string_pretty(value)?,
+    })
+}
+
+fn postgres_node(node: &Map<String, Value>) -> PlanNode {
+    let mut label = text(node, "Node Type").unwrap_or_else(|| "Node".to_string());
+    if let Some(join) = text(node, "Join Type") {
+        label = format!("{} {}", join, label);
+    }
+    if let Some(index) = text(node, "Index Name") {
+        label = format!("{} using {}", label, index);
+    }
+    if let Some(relation) = text(node, "Relation Name") {
+        match text(node, "Alias").filter(|alias| *alias != relation) {
+            Some(alias) => label = format!("{} on {} {}", label, relation, alias),
+            None => label = format!("{} on {}", label, relation),
+        }
+    }
+
+    let details: Vec<String> = [
+        "Index Cond",
+        "Hash Cond",
+        "Merge Cond",
+        "Recheck Cond",
+        "Join Filter",
+        "Filter",
+        "Sort Key",
+        "Group Key",
+    ]
+    .iter()
+    .filter_map(|key| {
+        node.get(*key).map(|value| match value {
+            Value::Array(items) => format!(
+                "{}: {}",
+                key,
+                items.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(", ")
+            ),
+            other => format!("{}: {}", key, other.as_str().unwrap_or(&other.to_string())),
+        })
+    })
+    .collect();
+
+    let loops = number(node.get("Actual Loops")).unwrap_or(1.0);
+    PlanNode {
+        label,
+        detail: (!details.is_empty()).then(|| details.join("\n")),
+        startup_cost: number(node.get("Startup Cost")),
+        total_cost: number(node.get("Total Cost")),
+        estimated_rows: number(node.get("Plan Rows")),
+        // Per-loop figures in the output; totals are more useful
+        actual_rows: number(node.get("Actual Rows")).map(|rows| rows * loops),
+        actual_time_ms: number(node.get("Actual Total Time")).map(|ms| ms * loops),
+        loops: number(node.get("Actual Loops")).map(|l| l as u64),
+        weight: 0.0,
+        children: node
+            .get("Plans")
+            .and_then(Value::as_array)
+            .map(|plans| plans.iter().filter_map(Value::as_object).map(postgres_node).collect())
+            .unwrap_or_default(),
+    }
+}
+
+// ============================================================================
+// MySQL
+// ============================================================================
+
+/// Keys of MySQL plan objects that describe the node rather than nest one
+const MYSQL_NODE_FIELDS: &[&str] = &["cost_info", "used_columns", "possible_keys", "used_key_parts", "key_length", "ref"];
+
+fn mysql_plan(value: &Value) -> Result<QueryPlan> {
+    let block = value
+        .get("query_block")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("EXPLAIN output has no query_block"))?;
+    Ok(QueryPlan {
+        nodes: vec![mysql_node("query_block", block)],
+        planning_time_ms: None,
+        execution_time_ms: None,
+        analyzed: false,
+        raw: serde_json::to_string_pretty(value)?,
+    })
+}
+
+fn mysql_node(key: &str, node: &Map<String, Value>) -> PlanNode {
+    let mut label = key.replace('_', " ");
+    if let Some(table) = text(node, "table_name") {
+        label = match text(node, "access_type") {
+            Some(access) => format!("{} ({})", table, access),
+            None => table,
+        };
+        if let Some(index) = text(node, "key") {
+            label = format!("{} using {}", label, index);
+        }
+    } else if let Some(id) = node.get("select_id") {
+        label = format!("{} #{}", label, id);
+    }
+
+    let cost_info = node.get("cost_info").and_then(Value::as_object);
+    let cost = |name: &str| cost_info.and_then(|info| number(info.get(name)));
+    let detail = ["attached_condition", "message"]
+        .iter()
+        .find_map(|field| text(node, field));
+
+    let mut children = Vec::new();
+    for (child_key, child) in node {
+        if MYSQL_NODE_FIELDS.contains(&child_key.as_str()) {
+            continue;
+        }
+        match child {
+            Value::Object(object) => children.push(mysql_node(child_key, object)),
+            Value::Array(items) => {
+                for item in items.iter().filter_map(Value::as_object) {
+                    // nested_loop entries are {"table": {...}} wrappers
+                    match item.iter().next() {
+                        Some((inner_key, Value::Object(inner))) if item.len() == 1 => {
+                            children.push(mysql_node(inner_key, inner))
+                        }
+                        _ => children.push(mysql_node(child_key, item)),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    PlanNode {
+        label,
+        detail,
+        startup_cost: None,
+        total_cost: cost("query_cost").or_else(|| cost("prefix_cost")).or_else(|| cost("sort_cost")),
+        estimated_rows: number(node.get("rows_produced_per_join")).or_else(|| number(node.get("rows_examined_per_scan"))),
+        actual_rows: None,
+        actual_time_ms: None,
+        loops: None,
+        weight: 0.0,
+        children,
+    }
+}
+
+// ============================================================================
+// SQLite
+// ============================================================================
+
+/// `EXPLAIN QUERY PLAN` rows are (id, parent, notused, detail)
+fn sqlite_plan(result: &QueryResult) -> Result<QueryPlan> {
+    let rows: Vec<(i64, i64, String)> = result
+        .rows
+        .iter()
+        .filter_map(|row| {
+            Some((
+                row.first()?.as_i64()?,
+                row.get(1)?.as_i64()?,
+                row.get(3)?.as_str()?.to_string(),
+            ))
+        })
+        .collect();
+
+    fn children_of(parent: i64, rows: &[(i64, i64, String)]) -> Vec<PlanNode> {
+        rows.iter()
+            .filter(|(_, p, _)| *p == parent)
+            .map(|(id, _, detail)| PlanNode {
+                label: detail.clone(),
+                children: children_of(*id, rows),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    let raw = rows
+        .iter()
+        .map(|(id, parent, detail)| format!("{}|{}|{}", id, parent, detail))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(QueryPlan {
+        nodes: children_of(0, &rows),
+        raw,
+        ..Default::default()
+    })
+}
+
+// ============================================================================
+// Hotspots
+// ============================================================================
+
+/// Time (or cost) spent in a node itself, excluding its children
+fn exclusive(node: &PlanNode, metric: fn(&PlanNode) -> Option<f64>) -> f64 {
+    let own = metric(node).unwrap_or(0.0);
+    let children: f64 = node.children.iter().filter_map(metric).sum();
+    (own - children).max(0.0)
+}
+
+fn assign_weights(plan: &mut QueryPlan) {
+    let metric: fn(&PlanNode) -> Option<f64> = if plan.analyzed {
+        |node| node.actual_time_ms
+    } else {
+        |node| node.total_cost
+    };
+
+    fn total(nodes: &[PlanNode], metric: fn(&PlanNode) -> Option<f64>) -> f64 {
+        nodes
+            .iter()
+            .map(|node| exclusive(node, metric) + total(&node.children, metric))
+            .sum()
+    }
+    fn assign(nodes: &mut [PlanNode], metric: fn(&PlanNode) -> Option<f64>, sum: f64) {
+        for node in nodes {
+            node.weight = exclusive(node, metric) / sum;
+            assign(&mut node.children, metric, sum);
+        }
+    }
+
+    let sum = total(&plan.nodes, metric);
+    if sum > 0.0 {
+        assign(&mut plan.nodes, metric, sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn single(value: Value) -> QueryResult {
+        QueryResult {
+            rows: vec![vec![value]],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_postgres_analyzed_plan() {
+        let output = json!([{
+            "Plan": {
+                "Node Type": "Hash Join", "Join Type": "Inner",
+                "Startup Cost": 1.0, "Total Cost": 40.0, "Plan Rows": 10,
+                "Actual Rows": 12, "Actual Total Time": 10.0, "Actual Loops": 1,
+                "Hash Cond": "(o.user_id = u.id)",
+                "Plans": [
+                    {"Node Type": "Seq Scan", "Relation Name": "orders", "Alias": "o",
+                     "Total Cost": 30.0, "Plan Rows": 1000, "Actual Rows": 1000,
+                     "Actual Total Time": 8.0, "Actual Loops": 1, "Filter": "(paid)"},
+                    {"Node Type": "Index Scan", "Index Name": "users_pkey", "Relation Name": "users", "Alias": "users",
+                     "Total Cost": 5.0, "Plan Rows": 1, "Actual Rows": 1,
+                     "Actual Total Time": 0.5, "Actual Loops": 2}
+                ]
+            },
+            "Planning Time": 0.2,
+            "Execution Time": 10.5
+        }]);
+        let plan = parse_plan(&DbType::PostgreSQL, &single(output), true).unwrap();
+        assert_eq!(plan.execution_time_ms, Some(10.5));
+
+        let root = &plan.nodes[0];
+        assert_eq!(root.label, "Inner Hash Join");
+        assert_eq!(root.detail.as_deref(), Some("Hash Cond: (o.user_id = u.id)"));
+        assert_eq!(root.children[0].label, "Seq Scan on orders o");
+        assert_eq!(root.children[1].label, "Index Scan using users_pkey on users");
+        // Loops multiply the per-loop figures
+        assert_eq!(root.children[1].actual_rows, Some(2.0));
+        assert_eq!(root.children[1].actual_time_ms, Some(1.0));
+
+        // Seq Scan spends 8 of the 10ms; the join itself 10 - 9
+        assert!((root.children[0].weight - 0.8).abs() < 1e-9);
+        assert!((root.weight - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mysql_plan() {
+        let output = json!({
+            "query_block": {
+                "select_id": 1,
+                "cost_info": {"query_cost": "12.50"},
+                "nested_loop": [
+                    {"table": {"table_name": "o", "access_type": "ALL", "rows_examined_per_scan": 100,
+                               "rows_produced_per_join": 10, "cost_info": {"prefix_cost": "10.00"},
+                               "attached_condition": "(`o`.`paid` = 1)"}},
+                    {"table": {"table_name": "u", "access_type": "eq_ref", "key": "PRIMARY",
+                               "rows_produced_per_join": 10, "cost_info": {"prefix_cost": "12.50"},
+                               "used_columns": ["id"]}}
+                ]
+            }
+        });
+        // MySQL returns the JSON as text
+        let plan = parse_plan(&DbType::MySQL, &single(Value::String(output.to_string())), false).unwrap();
+        let root = &plan.nodes[0];
+        assert_eq!(root.label, "query block #1");
+        assert_eq!(root.total_cost, Some(12.5));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].label, "o (ALL)");
+        assert_eq!(root.children[0].detail.as_deref(), Some("(`o`.`paid` = 1)"));
+        assert_eq!(root.children[1].label, "u (eq_ref) using PRIMARY");
+        assert_eq!(root.children[1].estimated_rows, Some(10.0));
+    }
+
+    #[test]
+    fn test_sqlite_plan() {
+        let result = QueryResult {
+            rows: vec![
+                vec![json!(2), json!(0), json!(0), json!("SCAN o")],
+                vec![json!(5), json!(0), json!(0), json!("SEARCH u USING INTEGER PRIMARY KEY (rowid=?)")],
+                vec![json!(7), json!(5), json!(0), json!("USE TEMP B-TREE FOR ORDER BY")],
+            ],
+            ..Default::default()
+        };
+        let plan = parse_plan(&DbType::SQLite, &result, false).unwrap();
+        assert_eq!(plan.nodes.len(), 2);
+        assert_eq!(plan.nodes[1].children[0].label, "USE TEMP B-TREE FOR ORDER BY");
+        assert!(plan.nodes.iter().all(|n| n.weight == 0.0));
+
+        assert_eq!(
+            explain_statement(&DbType::PostgreSQL, "select 1;", true).unwrap(),
+            "EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) select 1"
+        );
+        assert!(explain_statement(&DbType::MongoDB, "db.x.find()", false).is_err());
+    }
+}
@@ -0,0 +1,1053 @@
+//! Pooled database sessions
+//!
+//! Every saved connection gets a pool with a persistent console session, so
+//! transactions, `SET` and temp tables survive between queries, plus idle
+//! sessions reused for background work such as health checks. Statements on
+//! the console session can be cancelled or bounded by a timeout.
+//!
+//! Large results are paged: PostgreSQL pages through a server-side cursor on
+//! the console session, MySQL and SQLite re-run the query with `LIMIT`/`OFFSET`.
+
+use super::editor::{edit_statements, select_statement};
+use super::export::ExportWriter;
+use super::plan::{explain_statement, parse_plan};
+use super::schema::{changes_schema, introspect};
+use super::operations::{
+    close_postgres_cursor, connect_postgres, fetch_postgres_cursor, mysql_opts, offset_page_query,
+    open_postgres_cursor, open_sqlite, pageable_query, run_mysql_query, run_postgres_query,
+    run_sqlite_query,
+};
+use super::types::*;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Idle sessions kept open per connection
+const MAX_IDLE_SESSIONS: usize = 4;
+
+/// How long a timed-out statement gets to stop after it was cancelled
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+/// Rows per page when the caller doesn't ask for a size
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+
+/// Rows fetched per round trip while exporting
+const EXPORT_PAGE_SIZE: usize = 5000;
+
+/// An open connection to a database server
+pub enum Session {
+    Postgres(tokio_postgres::Client),
+    MySql(mysql_async::Conn),
+    Sqlite(Arc<Mutex<rusqlite::Connection>>),
+}
+
+/// Position in a paged result
+#[derive(Debug, Clone)]
+pub enum Cursor {
+    /// Server-side cursor declared on the console session (PostgreSQL)
+    Declared(String),
+    /// Query re-run with `LIMIT`/`OFFSET` for every page (MySQL, SQLite)
+    Offset { query: String, offset: usize },
+}
+
+/// Work for the console session
+enum Work {
+    Execute(String),
+    /// Run a query, paging its rows when it is pageable
+    Open { query: String, cursor: String, page_size: usize },
+    Fetch { cursor: Cursor, count: usize },
+    Close(Cursor),
+}
+
+/// Stops the statement running on a session from another task
+enum CancelHandle {
+    Postgres(tokio_postgres::CancelToken),
+    MySql { opts: mysql_async::Opts, connection_id: u32 },
+    Sqlite(rusqlite::InterruptHandle),
+}
+
+impl Session {
+    pub async fn open(conn: &DbConnection) -> Result<Self> {
+        match conn.db_type {
+            DbType::PostgreSQL => Ok(Session::Postgres(connect_postgres(conn).await?)),
+            DbType::MySQL => Ok(Session::MySql(mysql_async::Conn::new(mysql_opts(conn)?).await?)),
+            DbType::SQLite => Ok(Session::Sqlite(Arc::new(Mutex::new(open_sqlite(conn)?)))),
+            DbType::MongoDB => Err(anyhow!("MongoDB connections use the MongoDB console instead of SQL sessions")),
+        }
+    }
+
+    fn cancel_handle(&self, conn: &DbConnection) -> Result<CancelHandle> {
+        Ok(match self {
+            Session::Postgres(client) => CancelHandle::Postgres(client.cancel_token()),
+            Session::MySql(mysql) => CancelHandle::MySql {
+                opts: mysql_opts(conn)?,
+                connection_id: mysql.id(),
+            },
+            Session::Sqlite(sqlite) => {
+                CancelHandle::Sqlite(sqlite.lock().map_err(|_| anyhow!("SQLite session poisoned"))?.get_interrupt_handle())
+            }
+        })
+    }
+
+    pub async fn execute(&mut self, query: &str) -> Result<QueryResult> {
+        match self {
+            Session::Postgres(client) => run_postgres_query(client, query).await,
+            Session::MySql(mysql) => run_mysql_query(mysql, query).await,
+            Session::Sqlite(sqlite) => {
+                let sqlite = sqlite.clone();
+                let query = query.to_string();
+                tokio::task::spawn_blocking(move || {
+                    let connection = sqlite.lock().map_err(|_| anyhow!("SQLite session poisoned"))?;
+                    run_sqlite_query(&connection, &query)
+                })
+                .await?
+            }
+        }
+    }
+
+    /// Run a query and return its first page, plus a cursor when more rows may follow
+    async fn open_paged(&mut self, query: &str, cursor: &str, page_size: usize) -> Result<(QueryResult, Option<Cursor>)> {
+        let Some(select) = pageable_query(query) else {
+            return Ok((self.execute(query).await?, None));
+        };
+
+        let cursor = match self {
+            Session::Postgres(client) => {
+                let page = open_postgres_cursor(client, cursor, select, page_size).await?;
+                if page.rows.len() < page_size {
+                    close_postgres_cursor(client, cursor).await?;
+                    return Ok((page, None));
+                }
+                return Ok((page, Some(Cursor::Declared(cursor.to_string()))));
+            }
+            _ => Cursor::Offset {
+                query: select.to_string(),
+                offset: 0,
+            },
+        };
+
+        match self.fetch(cursor, page_size).await {
+            Ok(page) => Ok(page),
+            // MySQL rejects some queries as derived tables (e.g. duplicate column names)
+            Err(_) if matches!(self, Session::MySql(_)) => Ok((self.execute(query).await?, None)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Next page of a cursor; the cursor is returned while more rows may follow
+    async fn fetch(&mut self, cursor: Cursor, count: usize) -> Result<(QueryResult, Option<Cursor>)> {
+        let page = match &cursor {
+            Cursor::Declared(name) => match self {
+                Session::Postgres(client) => fetch_postgres_cursor(client, name, count).await?,
+                _ => return Err(anyhow!("Cursor doesn't belong to this session")),
+            },
+            Cursor::Offset { query, offset } => {
+                self.execute(&offset_page_query(query, count, *offset)).await?
+            }
+        };
+
+        if page.rows.len() < count {
+            self.close_cursor(cursor).await?;
+            return Ok((page, None));
+        }
+        let cursor = match cursor {
+            Cursor::Offset { query, offset } => Cursor::Offset {
+                query,
+                offset: offset + page.rows.len(),
+            },
+            declared => declared,
+        };
+        Ok((page, Some(cursor)))
+    }
+
+    async fn close_cursor(&mut self, cursor: Cursor) -> Result<()> {
+        if let (Session::Postgres(client), Cursor::Declared(name)) = (&*self, &cursor) {
+            close_postgres_cursor(client, name).await?;
+        }
+        Ok(())
+    }
+
+    async fn perform(&mut self, work: Work) -> Result<(QueryResult, Option<Cursor>)> {
+        match work {
+            Work::Execute(query) => Ok((self.execute(&query).await?, None)),
+            Work::Open {
+                query,
+                cursor,
+                page_size,
+            } => self.open_paged(&query, &cursor, page_size).await,
+            Work::Fetch { cursor, count } => self.fetch(cursor, count).await,
+            Work::Close(cursor) => {
+                self.close_cursor(cursor).await?;
+                Ok((QueryResult::default(), None))
+            }
+        }
+    }
+
+    /// Round trip to the server
+    pub async fn ping(&mut self) -> Result<()> {
+        match self {
+            Session::Postgres(client) => {
+                client.simple_query("SELECT 1").await?;
+            }
+            Session::MySql(mysql) => {
+                use mysql_async::prelude::Queryable;
+                mysql.ping().await?;
+            }
+            Session::Sqlite(sqlite) => {
+                let connection = sqlite.lock().map_err(|_| anyhow!("SQLite session poisoned"))?;
+                connection.query_row("SELECT 1", [], |_| Ok(()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The server closed the session
+    fn is_closed(&self) -> bool {
+        match self {
+            Session::Postgres(client) => client.is_closed(),
+            Session::MySql(_) | Session::Sqlite(_) => false,
+        }
+    }
+
+    async fn close(self) {
+        if let Session::MySql(mysql) = self {
+            mysql.disconnect().await.ok();
+        }
+    }
+}
+
+impl CancelHandle {
+    async fn cancel(self) -> Result<()> {
+        match self {
+            CancelHandle::Postgres(token) => token.cancel_query(tokio_postgres::NoTls).await?,
+            CancelHandle::MySql { opts, connection_id } => {
+                use mysql_async::prelude::Queryable;
+                let mut killer = mysql_async::Conn::new(opts).await?;
+                killer.query_drop(format!("KILL QUERY {}", connection_id)).await?;
+                killer.disconnect().await?;
+            }
+            CancelHandle::Sqlite(handle) => handle.interrupt(),
+        }
+        Ok(())
+    }
+}
+
+/// How a statement changes the transaction state: Some(true) opens one, Some(false) ends it
+pub fn transaction_effect(query: &str) -> Option<bool> {
+    let upper = query.trim_start().to_uppercase();
+    let mut words = upper
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|w| !w.is_empty());
+    match (words.next()?, words.next()) {
+        ("BEGIN", _) => Some(true),
+        ("START", Some("TRANSACTION")) => Some(true),
+        // ROLLBACK TO SAVEPOINT keeps the transaction open
+        ("ROLLBACK", Some("TO")) => None,
+        ("COMMIT" | "ROLLBACK" | "END" | "ABORT", _) => Some(false),
+        _ => None,
+    }
+}
+
+/// Sessions of one saved connection
+pub struct ConnectionPool {
+    config: DbConnection,
+    /// Session used by the query console
+    console: tokio::sync::Mutex<Option<Session>>,
+    idle: Mutex<Vec<Session>>,
+    /// Cancels the statement running on the console session
+    cancel: Mutex<Option<CancelHandle>>,
+    in_transaction: AtomicBool,
+    running: AtomicBool,
+    /// Open cursors of the console session by id
+    cursors: Mutex<HashMap<String, Cursor>>,
+    next_cursor: AtomicUsize,
+    /// Schema metadata, dropped when the console runs DDL
+    schema: Mutex<Option<Arc<DatabaseSchema>>>,
+}
+
+impl ConnectionPool {
+    pub fn new(config: DbConnection) -> Self {
+        Self {
+            config,
+            console: tokio::sync::Mutex::new(None),
+            idle: Mutex::new(Vec::new()),
+            cancel: Mutex::new(None),
+            in_transaction: AtomicBool::new(false),
+            running: AtomicBool::new(false),
+            cursors: Mutex::new(HashMap::new()),
+            next_cursor: AtomicUsize::new(1),
+            schema: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &DbConnection {
+        &self.config
+    }
+
+    /// Run a statement on the console session, cancelling it after `timeout`
+    pub async fn execute(&self, query: &str, timeout: Option<Duration>) -> Result<QueryResult> {
+        let (result, _) = self.perform(Work::Execute(query.to_string()), timeout).await?;
+        self.track_statement(query);
+        Ok(result)
+    }
+
+    /// Run a statement and return its first `page_size` rows; `cursor` is set
+    /// on the result when more rows can be fetched
+    pub async fn query(&self, query: &str, timeout: Option<Duration>, page_size: usize) -> Result<QueryResult> {
+        let id = format!("berry_cursor_{}", self.next_cursor.fetch_add(1, Ordering::SeqCst));
+        let work = Work::Open {
+            query: query.to_string(),
+            cursor: id.clone(),
+            page_size: page_size.max(1),
+        };
+        let (mut result, cursor) = self.perform(work, timeout).await?;
+        self.track_statement(query);
+        if let Some(cursor) = cursor {
+            self.cursors.lock().unwrap().insert(id.clone(), cursor);
+            result.cursor = Some(id);
+        }
+        Ok(result)
+    }
+
+    /// Next `count` rows of a cursor opened by `query`
+    pub async fn fetch(&self, cursor_id: &str, count: usize, timeout: Option<Duration>) -> Result<QueryResult> {
+        let cursor = self
+            .cursors
+            .lock()
+            .unwrap()
+            .remove(cursor_id)
+            .ok_or_else(|| anyhow!("Cursor {} is closed", cursor_id))?;
+        let work = Work::Fetch {
+            cursor,
+            count: count.max(1),
+        };
+        let (mut result, cursor) = self.perform(work, timeout).await?;
+        if let Some(cursor) = cursor {
+            self.cursors.lock().unwrap().insert(cursor_id.to_string(), cursor);
+            result.cursor = Some(cursor_id.to_string());
+        }
+        Ok(result)
+    }
+
+    /// Release a cursor before it is exhausted
+    pub async fn close_cursor(&self, cursor_id: &str) -> Result<()> {
+        let cursor = self.cursors.lock().unwrap().remove(cursor_id);
+        if let Some(cursor @ Cursor::Declared(_)) = cursor {
+            self.perform(Work::Close(cursor), None).await?;
+        }
+        Ok(())
+    }
+
+    /// Write every row of a query to `path`; returns the number of rows
+    pub async fn export(&self, query: &str, format: ExportFormat, path: &Path, table: &str) -> Result<usize> {
+        let mut page = self.query(query, None, EXPORT_PAGE_SIZE).await?;
+        if page.columns.is_empty() {
+            return Err(anyhow!("The statement returned no rows to export"));
+        }
+
+        let written = async {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            let mut writer = ExportWriter::new(format, &page.columns, table, file)?;
+            loop {
+                writer.write_rows(&page.rows)?;
+                match page.cursor.take() {
+                    Some(id) => page = self.fetch(&id, EXPORT_PAGE_SIZE, None).await?,
+                    None => break,
+                }
+            }
+            writer.finish()
+        }
+        .await;
+
+        if let Some(id) = page.cursor {
+            self.close_cursor(&id).await.ok();
+        }
+        written
+    }
+
+    /// Plan of a query on the console session. On PostgreSQL `analyze` runs
+    /// the statement; anything but a plain query is rolled back afterwards.
+    pub async fn explain(&self, query: &str, analyze: bool, timeout: Option<Duration>) -> Result<QueryPlan> {
+        let db_type = &self.config.db_type;
+        let analyze = analyze && *db_type == DbType::PostgreSQL;
+        let statement = explain_statement(db_type, query, analyze)?;
+
+        let undo = analyze && pageable_query(query).is_none();
+        let (begin, rollback) = if self.in_transaction.load(Ordering::SeqCst) {
+            ("SAVEPOINT berry_explain", "ROLLBACK TO SAVEPOINT berry_explain")
+        } else {
+            ("BEGIN", "ROLLBACK")
+        };
+        if undo {
+            self.execute(begin, None).await?;
+        }
+        let result = self.execute(&statement, timeout).await;
+        if undo {
+            self.execute(rollback, None).await?;
+        }
+        parse_plan(db_type, &result?, analyze)
+    }
+
+    /// Table metadata from the (cached) schema
+    pub async fn table_info(&self, schema: &str, table: &str) -> Result<TableInfo> {
+        self.schema(false)
+            .await?
+            .schemas
+            .iter()
+            .filter(|s| s.name == schema)
+            .flat_map(|s| s.tables.iter())
+            .find(|t| t.name == table)
+            .cloned()
+            .ok_or_else(|| anyhow!("Table {}.{} not found", schema, table))
+    }
+
+    /// First page of a table's rows, ordered by primary key
+    pub async fn open_table(&self, schema: &str, table: &str, page_size: usize) -> Result<QueryResult> {
+        let info = self.table_info(schema, table).await?;
+        self.query(&select_statement(&self.config.db_type, &info), None, page_size)
+            .await
+    }
+
+    /// SQL that `apply_changes` would run for `edit`
+    pub async fn preview_changes(&self, edit: &TableEdit) -> Result<Vec<String>> {
+        let info = self.table_info(&edit.schema, &edit.table).await?;
+        Ok(edit_statements(&self.config.db_type, &info, &edit.changes)?
+            .into_iter()
+            .map(|statement| statement.sql)
+            .collect())
+    }
+
+    /// Apply row changes in one transaction (a savepoint inside an open one);
+    /// returns the number of affected rows. Nothing is applied when a
+    /// statement fails or an edited row no longer matches its key.
+    ///
+    /// The console session stays locked throughout, so console queries wait
+    /// instead of running inside the edit's transaction.
+    pub async fn apply_changes(&self, edit: &TableEdit) -> Result<u64> {
+        let info = self.table_info(&edit.schema, &edit.table).await?;
+        let statements = edit_statements(&self.config.db_type, &info, &edit.changes)?;
+
+        let mut console = self.console.lock().await;
+        let nested = self.in_transaction.load(Ordering::SeqCst);
+        let (begin, commit, rollback) = if nested {
+            (
+                "SAVEPOINT berry_edit",
+                "RELEASE SAVEPOINT berry_edit",
+                "ROLLBACK TO SAVEPOINT berry_edit",
+            )
+        } else {
+            ("BEGIN", "COMMIT", "ROLLBACK")
+        };
+
+        self.execute_on(&mut console, begin).await?;
+        let applied = async {
+            let mut affected = 0;
+            for statement in &statements {
+                let rows = self.execute_on(&mut console, &statement.sql).await?.affected_rows.unwrap_or(0);
+                if statement.single_row && rows != 1 {
+                    return Err(anyhow!(
+                        "{} matched {} rows; the row was changed or deleted since it was loaded",
+                        statement.sql,
+                        rows
+                    ));
+                }
+                affected += rows;
+            }
+            Ok(affected)
+        }
+        .await;
+
+        match applied {
+            Ok(affected) => {
+                self.execute_on(&mut console, commit).await?;
+                Ok(affected)
+            }
+            Err(e) => {
+                self.execute_on(&mut console, rollback).await.ok();
+                if nested {
+                    // Rolling back to a savepoint keeps it open
+                    self.execute_on(&mut console, commit).await.ok();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// `execute` on a console session the caller has already locked
+    async fn execute_on(&self, console: &mut Option<Session>, query: &str) -> Result<QueryResult> {
+        let (result, _) = self.perform_on(console, Work::Execute(query.to_string()), None).await?;
+        self.track_statement(query);
+        Ok(result)
+    }
+
+    fn track_statement(&self, query: &str) {
+        if let Some(open) = transaction_effect(query) {
+            self.in_transaction.store(open, Ordering::SeqCst);
+        }
+        if changes_schema(query) {
+            self.schema.lock().unwrap().take();
+        }
+    }
+
+    /// Schema metadata, read on a pooled session unless cached
+    pub async fn schema(&self, refresh: bool) -> Result<Arc<DatabaseSchema>> {
+        if !refresh {
+            if let Some(schema) = self.schema.lock().unwrap().clone() {
+                return Ok(schema);
+            }
+        }
+
+        let mut session = self.checkout().await?;
+        let schema = match introspect(&mut session, &self.config.db_type).await {
+            Ok(schema) => Arc::new(schema),
+            Err(e) => {
+                session.close().await;
+                return Err(e);
+            }
+        };
+        self.checkin(session).await;
+        *self.schema.lock().unwrap() = Some(schema.clone());
+        Ok(schema)
+    }
+
+    async fn perform(&self, work: Work, timeout: Option<Duration>) -> Result<(QueryResult, Option<Cursor>)> {
+        let mut console = self.console.lock().await;
+        self.perform_on(&mut console, work, timeout).await
+    }
+
+    async fn perform_on(
+        &self,
+        console: &mut Option<Session>,
+        work: Work,
+        timeout: Option<Duration>,
+    ) -> Result<(QueryResult, Option<Cursor>)> {
+        if console.as_ref().is_some_and(Session::is_closed) {
+            *console = None;
+            self.in_transaction.store(false, Ordering::SeqCst);
+            self.cursors.lock().unwrap().clear();
+        }
+        if console.is_none() {
+            *console = Some(Session::open(&self.config).await?);
+        }
+        let Some(session) = console.as_mut() else {
+            return Err(anyhow!("No session"));
+        };
+
+        *self.cancel.lock().unwrap() = Some(session.cancel_handle(&self.config)?);
+        self.running.store(true, Ordering::SeqCst);
+
+        let mut discard = false;
+        let result = {
+            let statement = session.perform(work);
+            tokio::pin!(statement);
+            match timeout {
+                None => statement.await,
+                Some(limit) => match tokio::time::timeout(limit, &mut statement).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        self.cancel().await;
+                        // Wait for the server to give up; a session that doesn't is dropped
+                        if tokio::time::timeout(CANCEL_GRACE, &mut statement).await.is_err() {
+                            discard = true;
+                        }
+                        Err(anyhow!("Query timed out after {} ms", limit.as_millis()))
+                    }
+                },
+            }
+        };
+
+        self.cancel.lock().unwrap().take();
+        self.running.store(false, Ordering::SeqCst);
+
+        if discard || console.as_ref().is_some_and(Session::is_closed) {
+            if let Some(session) = console.take() {
+                session.close().await;
+            }
+            self.cursors.lock().unwrap().clear();
+            let lost = self.in_transaction.swap(false, Ordering::SeqCst);
+            let note = if lost { "; the open transaction was rolled back" } else { "" };
+            return Err(anyhow!(
+                "{}{}",
+                result.err().map_or("Connection lost".to_string(), |e| e.to_string()),
+                note
+            ));
+        }
+
+        result
+    }
+
+    /// Cancel the statement running on the console session; false when none is running
+    pub async fn cancel(&self) -> bool {
+        let handle = self.cancel.lock().unwrap().take();
+        match handle {
+            Some(handle) => {
+                if let Err(e) = handle.cancel().await {
+                    eprintln!("Failed to cancel query: {}", e);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn begin(&self) -> Result<()> {
+        if self.in_transaction.load(Ordering::SeqCst) {
+            return Err(anyhow!("A transaction is already open"));
+        }
+        self.execute("BEGIN", None).await.map(|_| ())
+    }
+
+    pub async fn commit(&self) -> Result<()> {
+        self.end_transaction("COMMIT").await
+    }
+
+    pub async fn rollback(&self) -> Result<()> {
+        self.end_transaction("ROLLBACK").await
+    }
+
+    async fn end_transaction(&self, statement: &str) -> Result<()> {
+        if !self.in_transaction.load(Ordering::SeqCst) {
+            return Err(anyhow!("No transaction in progress"));
+        }
+        self.execute(statement, None).await.map(|_| ())
+    }
+
+    /// Take an idle session (or open a new one) for work outside the console
+    pub async fn checkout(&self) -> Result<Session> {
+        let idle = self.idle.lock().unwrap().pop();
+        match idle {
+            Some(session) if !session.is_closed() => Ok(session),
+            _ => Session::open(&self.config).await,
+        }
+    }
+
+    /// Return a session taken with `checkout`
+    pub async fn checkin(&self, session: Session) {
+        let extra = {
+            let mut idle = self.idle.lock().unwrap();
+            if idle.len() < MAX_IDLE_SESSIONS && !session.is_closed() {
+                idle.push(session);
+                None
+            } else {
+                Some(session)
+            }
+        };
+        if let Some(session) = extra {
+            session.close().await;
+        }
+    }
+
+    /// Ping the server on a pooled session and report the round-trip time
+    pub async fn health_check(&self) -> ConnectionTestResult {
+        let start = Instant::now();
+        let outcome = match self.checkout().await {
+            Ok(mut session) => match session.ping().await {
+                Ok(()) => {
+                    self.checkin(session).await;
+                    Ok(())
+                }
+                Err(e) => {
+                    session.close().await;
+                    Err(e)
+                }
+            },
+            Err(e) => Err(e),
+        };
+        let latency_ms = Some(start.elapsed().as_millis() as u64);
+
+        match outcome {
+            Ok(()) => ConnectionTestResult {
+                success: true,
+                message: if self.in_transaction.load(Ordering::SeqCst) {
+                    "Connection is healthy (transaction open)".to_string()
+                } else {
+                    "Connection is healthy".to_string()
+                },
+                latency_ms,
+                server_version: None,
+            },
+            Err(e) => ConnectionTestResult {
+                success: false,
+                message: format!("Health check failed: {}", e),
+                latency_ms,
+                server_version: None,
+            },
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        let running = self.running.load(Ordering::SeqCst);
+        SessionStatus {
+            connection_id: self.config.id.clone(),
+            // A locked console is in use, hence open
+            connected: running || self.console.try_lock().map_or(true, |console| console.is_some()),
+            in_transaction: self.in_transaction.load(Ordering::SeqCst),
+            running,
+            idle_sessions: self.idle.lock().unwrap().len(),
+        }
+    }
+
+    /// Close every session (an open transaction is rolled back by the server)
+    pub async fn close(&self) {
+        self.cancel().await;
+        if let Some(session) = self.console.lock().await.take() {
+            session.close().await;
+        }
+        self.in_transaction.store(false, Ordering::SeqCst);
+        self.cursors.lock().unwrap().clear();
+        let idle: Vec<Session> = self.idle.lock().unwrap().drain(..).collect();
+        for session in idle {
+            session.close().await;
+        }
+    }
+}
+
+/// Whether two saved connections point at the same server and credentials
+pub(crate) fn same_target(a: &DbConnection, b: &DbConnection) -> bool {
+    a.db_type == b.db_type
+        && a.host == b.host
+        && a.port == b.port
+        && a.database == b.database
+        && a.username == b.username
+        && a.password == b.password
+        && a.ssl == b.ssl
+}
+
+/// Pools by connection id
+#[derive(Default)]
+pub struct PoolRegistry {
+    pools: Mutex<HashMap<String, Arc<ConnectionPool>>>,
+}
+
+impl PoolRegistry {
+    /// Pool for a connection, replacing it when the connection settings changed
+    pub fn get_or_create(&self, connection: &DbConnection) -> Arc<ConnectionPool> {
+        let mut pools = self.pools.lock().unwrap();
+        if let Some(pool) = pools.get(&connection.id) {
+            if same_target(pool.config(), connection) {
+                return pool.clone();
+            }
+        }
+
+        let pool = Arc::new(ConnectionPool::new(connection.clone()));
+        if let Some(old) = pools.insert(connection.id.clone(), pool.clone()) {
+            tokio::spawn(async move { old.close().await });
+        }
+        pool
+    }
+
+    pub fn get(&self, connection_id: &str) -> Option<Arc<ConnectionPool>> {
+        self.pools.lock().unwrap().get(connection_id).cloned()
+    }
+
+    /// Close and forget the pool of a connection
+    pub async fn remove(&self, connection_id: &str) {
+        let pool = self.pools.lock().unwrap().remove(connection_id);
+        if let Some(pool) = pool {
+            pool.close().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sqlite_connection(dir: &TempDir) -> DbConnection {
+        DbConnection {
+            id: "test".to_string(),
+            name: "test".to_string(),
+            db_type: DbType::SQLite,
+            host: None,
+            port: None,
+            database: dir.path().join("test.db").to_string_lossy().to_string(),
+            username: None,
+            password: None,
+            password_source: PasswordSource::Stored,
+            ssl: false,
+            created_at: 0,
+            last_used: None,
+        }
+    }
+
+    fn count(result: &QueryResult) -> i64 {
+        result.rows[0][0].as_i64().unwrap()
+    }
+
+    const ENDLESS: &str = "SELECT count(*) AS n FROM (WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT x FROM c)";
+
+    #[test]
+    fn test_transaction_effect() {
+        assert_eq!(transaction_effect("begin"), Some(true));
+        assert_eq!(transaction_effect("  START TRANSACTION;"), Some(true));
+        assert_eq!(transaction_effect("commit;"), Some(false));
+        assert_eq!(transaction_effect("ROLLBACK"), Some(false));
+        assert_eq!(transaction_effect("rollback to savepoint a"), None);
+        assert_eq!(transaction_effect("SELECT 1"), None);
+        assert_eq!(transaction_effect(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_session_state_persists() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+
+        pool.execute("CREATE TEMP TABLE scratch (x INTEGER)", None).await.unwrap();
+        pool.execute("INSERT INTO scratch VALUES (1)", None).await.unwrap();
+        let result = pool.execute("SELECT count(*) AS n FROM scratch", None).await.unwrap();
+        assert_eq!(count(&result), 1);
+        assert!(pool.status().connected);
+    }
+
+    #[tokio::test]
+    async fn test_transactions() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        pool.execute("CREATE TABLE items (x INTEGER)", None).await.unwrap();
+
+        assert!(pool.commit().await.is_err());
+        pool.begin().await.unwrap();
+        assert!(pool.status().in_transaction);
+        assert!(pool.begin().await.is_err());
+        pool.execute("INSERT INTO items VALUES (1)", None).await.unwrap();
+        pool.rollback().await.unwrap();
+        assert!(!pool.status().in_transaction);
+        let result = pool.execute("SELECT count(*) AS n FROM items", None).await.unwrap();
+        assert_eq!(count(&result), 0);
+
+        // Typed statements are tracked too
+        pool.execute("BEGIN", None).await.unwrap();
+        pool.execute("INSERT INTO items VALUES (2)", None).await.unwrap();
+        pool.commit().await.unwrap();
+        let result = pool.execute("SELECT count(*) AS n FROM items", None).await.unwrap();
+        assert_eq!(count(&result), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_cancel() {
+        let dir = TempDir::new().unwrap();
+        let pool = Arc::new(ConnectionPool::new(sqlite_connection(&dir)));
+
+        let err = pool.execute(ENDLESS, Some(Duration::from_millis(50))).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(pool.execute("SELECT 1 AS n", None).await.is_ok());
+
+        let running = {
+            let pool = pool.clone();
+            tokio::spawn(async move { pool.execute(ENDLESS, None).await })
+        };
+        while !pool.status().running {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(pool.cancel().await);
+        assert!(running.await.unwrap().is_err());
+        assert!(!pool.cancel().await);
+    }
+
+    #[tokio::test]
+    async fn test_typed_results_keep_column_order() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        pool.execute("CREATE TABLE t (z TEXT, a INTEGER, m REAL, b BLOB, created TIMESTAMP)", None)
+            .await
+            .unwrap();
+
+        // RETURNING and CTEs are classified by their result columns, not their first keyword
+        let inserted = pool
+            .execute("INSERT INTO t VALUES ('x', 1, 1.5, x'00ff', '2024-01-02 03:04:05') RETURNING a", None)
+            .await
+            .unwrap();
+        assert_eq!(inserted.rows, vec![vec![serde_json::json!(1)]]);
+        let updated = pool.execute("UPDATE t SET a = 2", None).await.unwrap();
+        assert_eq!(updated.affected_rows, Some(1));
+
+        let result = pool
+            .query("WITH s AS (SELECT * FROM t) SELECT z, a, m, b, created, a + 1 AS next FROM s", None, 10)
+            .await
+            .unwrap();
+        let columns: Vec<(&str, &str)> = result
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("z", "TEXT"),
+                ("a", "INTEGER"),
+                ("m", "REAL"),
+                ("b", "BLOB"),
+                ("created", "TIMESTAMP"),
+                ("next", "INTEGER")
+            ]
+        );
+        assert_eq!(
+            result.rows[0],
+            serde_json::json!(["x", 2, 1.5, "\\x00ff", "2024-01-02 03:04:05", 3])
+                .as_array()
+                .unwrap()
+                .clone()
+        );
+        assert!(result.cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_paging() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        let numbers = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 25) SELECT x FROM c;";
+
+        let first = pool.query(numbers, None, 10).await.unwrap();
+        assert_eq!(first.rows.len(), 10);
+        let cursor = first.cursor.clone().unwrap();
+
+        let second = pool.fetch(&cursor, 10, None).await.unwrap();
+        assert_eq!(second.rows[0][0], serde_json::json!(11));
+        assert_eq!(second.cursor.as_deref(), Some(cursor.as_str()));
+        let last = pool.fetch(&cursor, 10, None).await.unwrap();
+        assert_eq!(last.rows.len(), 5);
+        assert!(last.cursor.is_none());
+        assert!(pool.fetch(&cursor, 10, None).await.is_err());
+
+        let reopened = pool.query(numbers, None, 10).await.unwrap();
+        let cursor = reopened.cursor.unwrap();
+        pool.close_cursor(&cursor).await.unwrap();
+        assert!(pool.fetch(&cursor, 10, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        let path = dir.path().join("out.csv");
+
+        let rows = pool
+            .export(
+                "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 12000) SELECT x, 'n' || x AS label FROM c",
+                ExportFormat::Csv,
+                &path,
+                "numbers",
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows, 12000);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("x,label\n1,n1\n"));
+        assert!(csv.ends_with("12000,n12000\n"));
+
+        assert!(pool
+            .export("CREATE TABLE nothing (x INTEGER)", ExportFormat::Json, &path, "nothing")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_changes() {
+        use serde_json::json;
+
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        pool.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)", None)
+            .await
+            .unwrap();
+        pool.execute("CREATE TABLE log (line TEXT)", None).await.unwrap();
+        pool.execute("INSERT INTO t VALUES (1, 'a'), (2, 'b')", None).await.unwrap();
+
+        let value = |column: &str, value: serde_json::Value| ColumnValue {
+            column: column.to_string(),
+            value,
+        };
+        let edit = |changes: Vec<RowChange>| TableEdit {
+            schema: "main".to_string(),
+            table: "t".to_string(),
+            changes,
+        };
+
+        let affected = pool
+            .apply_changes(&edit(vec![
+                RowChange::Update {
+                    key: vec![value("id", json!(1))],
+                    values: vec![value("name", json!("A"))],
+                },
+                RowChange::Delete {
+                    key: vec![value("id", json!(2))],
+                },
+                RowChange::Insert {
+                    values: vec![value("name", json!("c"))],
+                },
+            ]))
+            .await
+            .unwrap();
+        assert_eq!(affected, 3);
+        let rows = pool.open_table("main", "t", 10).await.unwrap().rows;
+        assert_eq!(rows, vec![vec![json!(1), json!("A")], vec![json!(2), json!("c")]]);
+
+        // A stale key rolls back the whole edit, but not a console statement issued meanwhile
+        let stale_edit = edit(vec![
+            RowChange::Insert {
+                values: vec![value("name", json!("d"))],
+            },
+            RowChange::Delete {
+                key: vec![value("id", json!(99))],
+            },
+        ]);
+        let (stale, logged) = tokio::join!(
+            pool.apply_changes(&stale_edit),
+            pool.execute("INSERT INTO log VALUES ('kept')", None)
+        );
+        assert!(stale.unwrap_err().to_string().contains("matched 0 rows"));
+        logged.unwrap();
+        assert_eq!(pool.open_table("main", "t", 10).await.unwrap().rows.len(), 2);
+        let log = pool.execute("SELECT count(*) FROM log", None).await.unwrap();
+        assert_eq!(count(&log), 1);
+        assert!(!pool.status().in_transaction);
+
+        let read_only = TableEdit {
+            table: "log".to_string(),
+            ..edit(Vec::new())
+        };
+        assert!(pool.apply_changes(&read_only).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_explain() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(sqlite_connection(&dir));
+        pool.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)", None)
+            .await
+            .unwrap();
+
+        let plan = pool
+            .explain("SELECT * FROM t WHERE id = 1 ORDER BY name", true, None)
+            .await
+            .unwrap();
+        // ANALYZE is PostgreSQL only
+        assert!(!plan.analyzed);
+        assert!(plan.nodes.iter().any(|node| node.label.starts_with("SEARCH t")));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_and_registry() {
+        let dir = TempDir::new().unwrap();
+        let connection = sqlite_connection(&dir);
+        let registry = PoolRegistry::default();
+
+        let pool = registry.get_or_create(&connection);
+        let health = pool.health_check().await;
+        assert!(health.success);
+        assert!(health.latency_ms.is_some());
+        assert_eq!(pool.status().idle_sessions, 1);
+
+        assert!(Arc::ptr_eq(&pool, &registry.get_or_create(&connection)));
+        let mut moved = connection.clone();
+        moved.database = dir.path().join("other.db").to_string_lossy().to_string();
+        assert!(!Arc::ptr_eq(&pool, &registry.get_or_create(&moved)));
+
+        registry.remove(&connection.id).await;
+        assert!(registry.get(&connection.id).is_none());
+    }
+}
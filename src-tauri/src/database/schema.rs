@@ -0,0 +1,382 @@
+//! Schema introspection
+//!
+//! Every database answers the same five catalog questions (tables, columns,
+//! index columns, foreign key columns, functions) with one row per item in a
+//! fixed column order; `SchemaBuilder` groups those rows into a
+//! `DatabaseSchema`.
+
+use super::pool::Session;
+use super::types::*;
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Catalog queries of one database type
+struct CatalogQueries {
+    /// name
+    schemas: &'static str,
+    /// schema, table, kind
+    tables: &'static str,
+    /// schema, table, column, type, nullable, default, primary key
+    columns: &'static str,
+    /// schema, table, index, unique, column (in index order)
+    index_columns: &'static str,
+    /// schema, table, constraint, column, referenced schema, table, column
+    foreign_key_columns: &'static str,
+    /// schema, name, arguments, return type
+    functions: Option<&'static str>,
+}
+
+const POSTGRES: CatalogQueries = CatalogQueries {
+    schemas: "SELECT n.nspname FROM pg_namespace n \
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema') \
+        AND n.nspname NOT LIKE 'pg_toast%' AND n.nspname NOT LIKE 'pg_temp%' ORDER BY 1",
+    tables: "SELECT n.nspname, c.relname, CASE WHEN c.relkind IN ('v', 'm') THEN 'view' ELSE 'table' END \
+        FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace \
+        WHERE c.relkind IN ('r', 'p', 'v', 'm', 'f') \
+        AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+        AND n.nspname NOT LIKE 'pg_toast%' AND n.nspname NOT LIKE 'pg_temp%' ORDER BY 1, 2",
+    columns: "SELECT n.nspname, c.relname, a.attname, format_type(a.atttypid, a.atttypmod), \
+        NOT a.attnotnull, pg_get_expr(d.adbin, d.adrelid), \
+        EXISTS (SELECT 1 FROM pg_index i WHERE i.indrelid = c.oid AND i.indisprimary AND a.attnum = ANY (i.indkey)) \
+        FROM pg_attribute a JOIN pg_class c ON c.oid = a.attrelid \
+        JOIN pg_namespace n ON n.oid = c.relnamespace \
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+        WHERE a.attnum > 0 AND NOT a.attisdropped AND c.relkind IN ('r', 'p', 'v', 'm', 'f') \
+        AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+        AND n.nspname NOT LIKE 'pg_toast%' AND n.nspname NOT LIKE 'pg_temp%' ORDER BY 1, 2, a.attnum",
+    index_columns: "SELECT n.nspname, t.relname, i.relname, ix.indisunique, \
+        pg_get_indexdef(ix.indexrelid, k.ord, true) \
+        FROM pg_index ix JOIN pg_class i ON i.oid = ix.indexrelid \
+        JOIN pg_class t ON t.oid = ix.indrelid JOIN pg_namespace n ON n.oid = t.relnamespace \
+        CROSS JOIN LATERAL generate_series(1, ix.indnatts::int) AS k(ord) \
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema') \
+        AND n.nspname NOT LIKE 'pg_toast%' AND n.nspname NOT LIKE 'pg_temp%' ORDER BY 1, 2, 3, k.ord",
+    foreign_key_columns: "SELECT n.nspname, cl.relname, c.conname, a.attname, rn.nspname, rc.relname, ra.attname \
+        FROM pg_constraint c \
+        JOIN pg_class cl ON cl.oid = c.conrelid JOIN pg_namespace n ON n.oid = cl.relnamespace \
+        JOIN pg_class rc ON rc.oid = c.confrelid JOIN pg_namespace rn ON rn.oid = rc.relnamespace \
+        CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(col, ref, ord) \
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.col \
+        JOIN pg_attribute ra ON ra.attrelid = c.confrelid AND ra.attnum = k.ref \
+        WHERE c.contype = 'f' AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+        ORDER BY 1, 2, 3, k.ord",
+    functions: Some(
+        "SELECT n.nspname, p.proname, pg_get_function_arguments(p.oid), pg_get_function_result(p.oid) \
+        FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace \
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema') ORDER BY 1, 2",
+    ),
+};
+
+const MYSQL: CatalogQueries = CatalogQueries {
+    schemas: "SELECT DATABASE()",
+    tables: "SELECT TABLE_SCHEMA, TABLE_NAME, IF(TABLE_TYPE = 'VIEW', 'view', 'table') \
+        FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE() ORDER BY 1, 2",
+    columns: "SELECT TABLE_SCHEMA, TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE = 'YES', \
+        COLUMN_DEFAULT, COLUMN_KEY = 'PRI' \
+        FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() \
+        ORDER BY TABLE_NAME, ORDINAL_POSITION",
+    index_columns: "SELECT TABLE_SCHEMA, TABLE_NAME, INDEX_NAME, NON_UNIQUE = 0, COLUMN_NAME \
+        FROM information_schema.STATISTICS WHERE TABLE_SCHEMA = DATABASE() \
+        ORDER BY TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX",
+    foreign_key_columns: "SELECT TABLE_SCHEMA, TABLE_NAME, CONSTRAINT_NAME, COLUMN_NAME, \
+        REFERENCED_TABLE_SCHEMA, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME \
+        FROM information_schema.KEY_COLUMN_USAGE \
+        WHERE TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME IS NOT NULL \
+        ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION",
+    functions: Some(
+        "SELECT r.ROUTINE_SCHEMA, r.ROUTINE_NAME, \
+        COALESCE((SELECT GROUP_CONCAT(CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER) \
+            ORDER BY p.ORDINAL_POSITION SEPARATOR ', ') \
+            FROM information_schema.PARAMETERS p \
+            WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA AND p.SPECIFIC_NAME = r.SPECIFIC_NAME \
+            AND p.ORDINAL_POSITION > 0), ''), \
+        COALESCE(r.DTD_IDENTIFIER, '') \
+        FROM information_schema.ROUTINES r WHERE r.ROUTINE_SCHEMA = DATABASE() ORDER BY 1, 2",
+    ),
+};
+
+const SQLITE: CatalogQueries = CatalogQueries {
+    schemas: "SELECT 'main'",
+    tables: "SELECT 'main', name, type FROM sqlite_master \
+        WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    columns: "SELECT 'main', m.name, p.name, p.type, NOT p.\"notnull\", p.dflt_value, p.pk > 0 \
+        FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+        WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%' ORDER BY m.name, p.cid",
+    index_columns: "SELECT 'main', m.name, il.name, il.\"unique\", ii.name \
+        FROM sqlite_master m JOIN pragma_index_list(m.name) il JOIN pragma_index_info(il.name) ii \
+        WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' ORDER BY m.name, il.name, ii.seqno",
+    // SQLite foreign keys are unnamed; `id` tells them apart
+    foreign_key_columns: "SELECT 'main', m.name, 'fk_' || m.name || '_' || fk.id, fk.\"from\", \
+        'main', fk.\"table\", fk.\"to\" \
+        FROM sqlite_master m JOIN pragma_foreign_key_list(m.name) fk \
+        WHERE m.type = 'table' ORDER BY m.name, fk.id, fk.seq",
+    functions: None,
+};
+
+fn text(row: &[Value], idx: usize) -> String {
+    match row.get(idx) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn optional_text(row: &[Value], idx: usize) -> Option<String> {
+    match row.get(idx) {
+        Some(Value::Null) | None => None,
+        _ => Some(text(row, idx)),
+    }
+}
+
+/// Boolean column, whichever way the server spells it
+fn flag(row: &[Value], idx: usize) -> bool {
+    match row.get(idx) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().is_some_and(|n| n != 0.0),
+        Some(Value::String(s)) => matches!(s.as_str(), "1" | "t" | "true" | "YES"),
+        _ => false,
+    }
+}
+
+/// Groups catalog rows into tables
+#[derive(Default)]
+pub struct SchemaBuilder {
+    schemas: BTreeMap<String, SchemaInfo>,
+    tables: BTreeMap<(String, String), TableInfo>,
+}
+
+impl SchemaBuilder {
+    pub fn schema(&mut self, name: &str) -> &mut SchemaInfo {
+        self.schemas
+            .entry(name.to_string())
+            .or_insert_with(|| SchemaInfo {
+                name: name.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn table(&mut self, schema: &str, name: &str) -> &mut TableInfo {
+        self.tables
+            .entry((schema.to_string(), name.to_string()))
+            .or_insert_with(|| TableInfo {
+                schema: schema.to_string(),
+                name: name.to_string(),
+                kind: TableKind::Table,
+                columns: Vec::new(),
+                indexes: Vec::new(),
+                foreign_keys: Vec::new(),
+            })
+    }
+
+    pub fn add_table(&mut self, row: &[Value]) {
+        let kind = if text(row, 2).eq_ignore_ascii_case("view") {
+            TableKind::View
+        } else {
+            TableKind::Table
+        };
+        self.table(&text(row, 0), &text(row, 1)).kind = kind;
+    }
+
+    pub fn add_column(&mut self, row: &[Value]) {
+        let column = ColumnMeta {
+            name: text(row, 2),
+            data_type: text(row, 3),
+            nullable: flag(row, 4),
+            default: optional_text(row, 5),
+            primary_key: flag(row, 6),
+        };
+        self.table(&text(row, 0), &text(row, 1)).columns.push(column);
+    }
+
+    /// Rows arrive in index order, one per indexed column
+    pub fn add_index_column(&mut self, row: &[Value]) {
+        let (name, column) = (text(row, 2), text(row, 4));
+        let unique = flag(row, 3);
+        let table = self.table(&text(row, 0), &text(row, 1));
+        match table.indexes.last_mut() {
+            Some(index) if index.name == name => index.columns.push(column),
+            _ => table.indexes.push(IndexInfo {
+                name,
+                columns: vec![column],
+                unique,
+            }),
+        }
+    }
+
+    /// Rows arrive in key order, one per column pair
+    pub fn add_foreign_key_column(&mut self, row: &[Value]) {
+        let (name, column, referenced) = (text(row, 2), text(row, 3), text(row, 6));
+        let referenced_schema = text(row, 4);
+        let referenced_table = text(row, 5);
+        let table = self.table(&text(row, 0), &text(row, 1));
+        match table.foreign_keys.last_mut() {
+            Some(key) if key.name == name => {
+                key.columns.push(column);
+                key.referenced_columns.push(referenced);
+            }
+            _ => table.foreign_keys.push(ForeignKeyInfo {
+                name,
+                columns: vec![column],
+                referenced_schema,
+                referenced_table,
+                referenced_columns: vec![referenced],
+            }),
+        }
+    }
+
+    pub fn add_function(&mut self, row: &[Value]) {
+        let function = FunctionInfo {
+            schema: text(row, 0),
+            name: text(row, 1),
+            arguments: text(row, 2),
+            return_type: text(row, 3),
+        };
+        self.schema(&function.schema.clone()).functions.push(function);
+    }
+
+    pub fn build(mut self) -> DatabaseSchema {
+        let tables = std::mem::take(&mut self.tables);
+        for ((schema, _), table) in tables {
+            self.schema(&schema).tables.push(table);
+        }
+        DatabaseSchema {
+            schemas: self.schemas.into_values().collect(),
+        }
+    }
+}
+
+/// Read the schema metadata through an open session
+pub async fn introspect(session: &mut Session, db_type: &DbType) -> Result<DatabaseSchema> {
+    let queries = match db_type {
+        DbType::PostgreSQL => &POSTGRES,
+        DbType::MySQL => &MYSQL,
+        DbType::SQLite => &SQLITE,
+        DbType::MongoDB => return Err(anyhow::anyhow!("MongoDB has no SQL schema")),
+    };
+
+    let mut builder = SchemaBuilder::default();
+    for row in session.execute(queries.schemas).await?.rows {
+        builder.schema(&text(&row, 0));
+    }
+    for row in session.execute(queries.tables).await?.rows {
+        builder.add_table(&row);
+    }
+    for row in session.execute(queries.columns).await?.rows {
+        builder.add_column(&row);
+    }
+    for row in session.execute(queries.index_columns).await?.rows {
+        builder.add_index_column(&row);
+    }
+    for row in session.execute(queries.foreign_key_columns).await?.rows {
+        builder.add_foreign_key_column(&row);
+    }
+    if let Some(functions) = queries.functions {
+        for row in session.execute(functions).await?.rows {
+            builder.add_function(&row);
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Statements after which cached schema metadata is stale
+pub fn changes_schema(query: &str) -> bool {
+    let upper = query.trim_start().to_uppercase();
+    let first = upper
+        .split(|c: char| !c.is_alphabetic())
+        .find(|w| !w.is_empty())
+        .unwrap_or("");
+    matches!(first, "CREATE" | "ALTER" | "DROP" | "RENAME" | "COMMENT")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::pool::ConnectionPool;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_changes_schema() {
+        assert!(changes_schema("create table t (x int)"));
+        assert!(changes_schema("  ALTER TABLE t ADD y int"));
+        assert!(changes_schema("DROP VIEW v;"));
+        assert!(!changes_schema("SELECT * FROM created"));
+        assert!(!changes_schema("INSERT INTO t VALUES (1)"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_introspection() {
+        let dir = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(DbConnection {
+            id: "schema".to_string(),
+            name: "schema".to_string(),
+            db_type: DbType::SQLite,
+            host: None,
+            port: None,
+            database: dir.path().join("schema.db").to_string_lossy().to_string(),
+            username: None,
+            password: None,
+            password_source: PasswordSource::Stored,
+            ssl: false,
+            created_at: 0,
+            last_used: None,
+        });
+        pool.execute(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL DEFAULT '', name TEXT)",
+            None,
+        )
+        .await
+        .unwrap();
+        pool.execute(
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users(id), total REAL)",
+            None,
+        )
+        .await
+        .unwrap();
+        pool.execute("CREATE UNIQUE INDEX users_email ON users (email, name)", None)
+            .await
+            .unwrap();
+        pool.execute("CREATE VIEW big_orders AS SELECT * FROM orders WHERE total > 100", None)
+            .await
+            .unwrap();
+
+        let schema = pool.schema(false).await.unwrap();
+        assert_eq!(schema.schemas.len(), 1);
+        let main = &schema.schemas[0];
+        assert_eq!(main.name, "main");
+        let names: Vec<&str> = main.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["big_orders", "orders", "users"]);
+        assert_eq!(main.tables[0].kind, TableKind::View);
+
+        let users = &main.tables[2];
+        let columns: Vec<(&str, &str, bool, bool)> = users
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.as_str(), c.nullable, c.primary_key))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("id", "INTEGER", true, true),
+                ("email", "TEXT", false, false),
+                ("name", "TEXT", true, false)
+            ]
+        );
+        assert_eq!(users.columns[1].default.as_deref(), Some("''"));
+        assert_eq!(users.indexes.len(), 1);
+        assert_eq!(users.indexes[0].columns, vec!["email", "name"]);
+        assert!(users.indexes[0].unique);
+
+        let orders = &main.tables[1];
+        assert_eq!(orders.foreign_keys.len(), 1);
+        assert_eq!(orders.foreign_keys[0].columns, vec!["user_id"]);
+        assert_eq!(orders.foreign_keys[0].referenced_table, "users");
+        assert_eq!(orders.foreign_keys[0].referenced_columns, vec!["id"]);
+
+        // Cached until a DDL statement runs on the console
+        assert!(std::sync::Arc::ptr_eq(&schema, &pool.schema(false).await.unwrap()));
+        pool.execute("CREATE TABLE audit (at TEXT)", None).await.unwrap();
+        let refreshed = pool.schema(false).await.unwrap();
+        assert_eq!(refreshed.schemas[0].tables.len(), 4);
+    }
+}
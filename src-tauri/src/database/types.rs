@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DbType {
@@ -17,12 +18,28 @@ pub struct DbConnection {
     pub port: Option<u16>,    // None for SQLite
     pub database: String,     // DB name or file path
     pub username: Option<String>,
-    pub password: Option<String>, // TODO: Encrypt in storage
+    /// Only set when entered in the UI; saved passwords stay encrypted on disk
+    pub password: Option<String>,
+    #[serde(default)]
+    pub password_source: PasswordSource,
     pub ssl: bool,
     pub created_at: i64, // Unix timestamp
     pub last_used: Option<i64>,
 }
 
+/// Where a connection's password comes from
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PasswordSource {
+    /// Encrypted in the credential store
+    #[default]
+    Stored,
+    /// Read from an environment variable when connecting
+    Env { variable: String },
+    /// Looked up in a `.pgpass`-style file (`PGPASSFILE` or `~/.pgpass` by default)
+    PgPass { path: Option<String> },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionTestResult {
     pub success: bool,
@@ -31,17 +48,217 @@ pub struct ConnectionTestResult {
     pub server_version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct DbConnectionList {
     pub connections: Vec<DbConnection>,
+    /// Encrypted stored passwords by connection id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub secrets: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<VaultHeader>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Key derivation parameters of the credential store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VaultHeader {
+    /// Base64 PBKDF2 salt
+    pub salt: String,
+    pub iterations: u32,
+    /// A known value encrypted with the key, to reject a wrong passphrase
+    pub check: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultStatus {
+    /// A master passphrase or key file has been set up
+    pub initialized: bool,
+    pub unlocked: bool,
+    pub stored_secrets: usize,
+    /// Passwords saved in plain text by older versions; encrypted on unlock
+    pub plaintext_secrets: usize,
+}
+
+/// Name and server-side type of a result column
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Type name as reported by the server (e.g. `timestamptz`, `NEWDECIMAL`)
+    pub type_name: String,
+}
+
+/// Result of a statement; rows are in column order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryResult {
+    /// Empty for statements that don't return rows
+    pub columns: Vec<ColumnInfo>,
+    pub rows: Vec<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub columns: Option<Vec<String>>,
+    pub affected_rows: Option<u64>,
+    /// Cursor to fetch the next page with; None once the result is exhausted
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub rows: Option<Vec<std::collections::HashMap<String, serde_json::Value>>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub affected_rows: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl QueryResult {
+    pub fn affected(rows: u64) -> Self {
+        Self {
+            affected_rows: Some(rows),
+            ..Default::default()
+        }
+    }
+}
+
+/// File format for exported query results
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// `INSERT` statements
+    Sql,
+}
+
+/// State of the pooled sessions of a connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub connection_id: String,
+    /// The console session is open
+    pub connected: bool,
+    /// A transaction is open on the console session
+    pub in_transaction: bool,
+    /// A statement is running on the console session
+    pub running: bool,
+    pub idle_sessions: usize,
+}
+
+/// Schema metadata of a database, used by the schema browser and SQL completion
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    pub schemas: Vec<SchemaInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    pub name: String,
+    pub tables: Vec<TableInfo>,
+    pub functions: Vec<FunctionInfo>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableKind {
+    Table,
+    View,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub schema: String,
+    pub name: String,
+    pub kind: TableKind,
+    pub columns: Vec<ColumnMeta>,
+    pub indexes: Vec<IndexInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMeta {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub schema: String,
+    pub name: String,
+    pub arguments: String,
+    pub return_type: String,
+}
+
+/// Outcome of a MongoDB command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MongoResult {
+    /// Documents as relaxed Extended JSON
+    pub documents: Vec<serde_json::Value>,
+    /// Write outcome or confirmation prompt
+    pub message: Option<String>,
+    /// The command modifies data and has to be confirmed first
+    pub needs_confirmation: bool,
+    /// More documents matched than were returned
+    pub truncated: bool,
+}
+
+/// Node of a query plan
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanNode {
+    /// Operation, e.g. `Seq Scan on users`
+    pub label: String,
+    /// Conditions, keys and filters
+    pub detail: Option<String>,
+    pub startup_cost: Option<f64>,
+    pub total_cost: Option<f64>,
+    pub estimated_rows: Option<f64>,
+    /// Rows over all loops (analyzed plans)
+    pub actual_rows: Option<f64>,
+    /// Time over all loops including children (analyzed plans)
+    pub actual_time_ms: Option<f64>,
+    pub loops: Option<u64>,
+    /// Share of the plan's exclusive time, or cost when not analyzed (0..1)
+    pub weight: f64,
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub nodes: Vec<PlanNode>,
+    pub planning_time_ms: Option<f64>,
+    pub execution_time_ms: Option<f64>,
+    /// The statement was executed (EXPLAIN ANALYZE)
+    pub analyzed: bool,
+    /// EXPLAIN output as returned by the server
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnValue {
+    pub column: String,
+    pub value: serde_json::Value,
+}
+
+/// A pending change to a table row; rows are identified by their primary key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RowChange {
+    /// Columns left out get their default
+    Insert { values: Vec<ColumnValue> },
+    /// Sets `values` on the row with primary key `key`
+    Update { key: Vec<ColumnValue>, values: Vec<ColumnValue> },
+    Delete { key: Vec<ColumnValue> },
+}
+
+/// Changes to one table, applied together in a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableEdit {
+    pub schema: String,
+    pub table: String,
+    pub changes: Vec<RowChange>,
 }
@@ -0,0 +1,474 @@
+//! Typed cell conversion
+//!
+//! Turns driver values into JSON without losing precision: exact numerics are
+//! kept as strings, temporal values are formatted as ISO 8601, binary data as
+//! `\x` hex and JSON columns are passed through as JSON.
+
+use super::types::ColumnInfo;
+use serde_json::Value;
+use std::fmt::Write as _;
+
+fn number(value: f64) -> Value {
+    serde_json::Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// Binary data in PostgreSQL's `\x` hex notation
+pub fn hex_bytes(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("\\x");
+    for byte in bytes {
+        write!(out, "{:02x}", byte).ok();
+    }
+    out
+}
+
+fn text_or_hex(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Value::String(text.to_string()),
+        Err(_) => Value::String(hex_bytes(bytes)),
+    }
+}
+
+// ============================================================================
+// PostgreSQL
+// ============================================================================
+
+/// Undecoded binary column value
+struct Raw<'a>(&'a [u8]);
+
+impl<'a> tokio_postgres::types::FromSql<'a> for Raw<'a> {
+    fn from_sql(
+        _ty: &tokio_postgres::types::Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Raw(raw))
+    }
+
+    fn accepts(_ty: &tokio_postgres::types::Type) -> bool {
+        true
+    }
+}
+
+pub fn postgres_columns(columns: &[tokio_postgres::Column]) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .map(|column| ColumnInfo {
+            name: column.name().to_string(),
+            type_name: column.type_().name().to_string(),
+        })
+        .collect()
+}
+
+pub fn postgres_row(row: &tokio_postgres::Row) -> Vec<Value> {
+    row.columns()
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| match row.try_get::<_, Option<Raw>>(idx) {
+            Ok(Some(Raw(raw))) => postgres_value(column.type_(), raw),
+            Ok(None) => Value::Null,
+            Err(e) => Value::String(format!("<{}>", e)),
+        })
+        .collect()
+}
+
+fn be_bytes<const N: usize>(raw: &[u8]) -> Option<[u8; N]> {
+    raw.get(..N)?.try_into().ok()
+}
+
+/// Decode a value sent in PostgreSQL's binary format
+pub fn postgres_value(ty: &tokio_postgres::types::Type, raw: &[u8]) -> Value {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+    use tokio_postgres::types::{FromSql, Kind, Type};
+
+    let decoded = match *ty {
+        Type::BOOL => raw.first().map(|b| Value::Bool(*b != 0)),
+        Type::CHAR => raw.first().map(|b| Value::String((*b as char).to_string())),
+        Type::INT2 => be_bytes(raw).map(|b| Value::from(i16::from_be_bytes(b))),
+        Type::INT4 => be_bytes(raw).map(|b| Value::from(i32::from_be_bytes(b))),
+        Type::INT8 => be_bytes(raw).map(|b| Value::from(i64::from_be_bytes(b))),
+        Type::OID => be_bytes(raw).map(|b| Value::from(u32::from_be_bytes(b))),
+        Type::FLOAT4 => be_bytes(raw).map(|b| number(f32::from_be_bytes(b) as f64)),
+        Type::FLOAT8 => be_bytes(raw).map(|b| number(f64::from_be_bytes(b))),
+        Type::NUMERIC => numeric_to_string(raw).map(Value::String),
+        Type::UUID => be_bytes::<16>(raw).map(|b| Value::String(uuid_to_string(&b))),
+        Type::JSON => serde_json::from_slice(raw).ok(),
+        // jsonb is prefixed with a format version byte
+        Type::JSONB => raw.get(1..).and_then(|json| serde_json::from_slice(json).ok()),
+        Type::BYTEA => Some(Value::String(hex_bytes(raw))),
+        Type::TIMESTAMP => NaiveDateTime::from_sql(ty, raw)
+            .ok()
+            .map(|t| Value::String(t.format("%Y-%m-%dT%H:%M:%S%.f").to_string())),
+        Type::TIMESTAMPTZ => DateTime::<Utc>::from_sql(ty, raw)
+            .ok()
+            .map(|t| Value::String(t.to_rfc3339())),
+        Type::DATE => NaiveDate::from_sql(ty, raw)
+            .ok()
+            .map(|d| Value::String(d.to_string())),
+        Type::TIME => NaiveTime::from_sql(ty, raw)
+            .ok()
+            .map(|t| Value::String(t.to_string())),
+        Type::INTERVAL => interval_to_string(raw).map(Value::String),
+        _ => match ty.kind() {
+            Kind::Array(element) => array_to_json(element, raw),
+            Kind::Domain(base) => Some(postgres_value(base, raw)),
+            // Text-like types (text, varchar, name, enums, citext...) are sent as UTF-8
+            _ => None,
+        },
+    };
+
+    // Special values (infinity timestamps, NaN) and unknown types keep their bytes
+    decoded.unwrap_or_else(|| text_or_hex(raw))
+}
+
+fn uuid_to_string(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Exact decimal text of a binary `numeric` (base-10000 digits)
+fn numeric_to_string(raw: &[u8]) -> Option<String> {
+    let read_i16 = |at: usize| be_bytes::<2>(raw.get(at..)?).map(i16::from_be_bytes);
+    let ndigits = read_i16(0)? as i32;
+    let weight = read_i16(2)? as i32;
+    let sign = read_i16(4)? as u16;
+    let dscale = read_i16(6)? as usize;
+    let digits: Vec<i16> = (0..ndigits)
+        .map(|i| read_i16(8 + 2 * i as usize))
+        .collect::<Option<_>>()?;
+    let digit = |i: i32| {
+        if i >= 0 && i < ndigits {
+            digits[i as usize]
+        } else {
+            0
+        }
+    };
+
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+
+    let mut out = String::new();
+    if sign == 0x4000 {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        for i in 0..=weight {
+            if i == 0 {
+                write!(out, "{}", digit(i)).ok()?;
+            } else {
+                write!(out, "{:04}", digit(i)).ok()?;
+            }
+        }
+    }
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < dscale {
+            write!(fraction, "{:04}", digit(i)).ok()?;
+            i += 1;
+        }
+        fraction.truncate(dscale);
+        out.push('.');
+        out.push_str(&fraction);
+    }
+    Some(out)
+}
+
+fn interval_to_string(raw: &[u8]) -> Option<String> {
+    let micros = i64::from_be_bytes(be_bytes(raw)?);
+    let days = i32::from_be_bytes(be_bytes(raw.get(8..)?)?);
+    let months = i32::from_be_bytes(be_bytes(raw.get(12..)?)?);
+
+    let mut parts = Vec::new();
+    let (years, months) = (months / 12, months % 12);
+    if years != 0 {
+        parts.push(format!("{} years", years));
+    }
+    if months != 0 {
+        parts.push(format!("{} mons", months));
+    }
+    if days != 0 {
+        parts.push(format!("{} days", days));
+    }
+    if micros != 0 || parts.is_empty() {
+        let sign = if micros < 0 { "-" } else { "" };
+        let micros = micros.unsigned_abs();
+        let seconds = micros / 1_000_000;
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+        if micros % 1_000_000 != 0 {
+            write!(time, ".{:06}", micros % 1_000_000).ok()?;
+        }
+        parts.push(time);
+    }
+    Some(parts.join(" "))
+}
+
+/// Binary array: header, dimensions, then length-prefixed elements
+fn array_to_json(element: &tokio_postgres::types::Type, raw: &[u8]) -> Option<Value> {
+    let read_i32 = |at: usize| be_bytes::<4>(raw.get(at..)?).map(i32::from_be_bytes);
+    let ndim = read_i32(0)?.max(0) as usize;
+    let dims: Vec<usize> = (0..ndim)
+        .map(|d| read_i32(12 + 8 * d).map(|len| len.max(0) as usize))
+        .collect::<Option<_>>()?;
+
+    let mut at = 12 + 8 * ndim;
+    let mut elements = Vec::new();
+    let total: usize = if ndim == 0 { 0 } else { dims.iter().product() };
+    for _ in 0..total {
+        let len = read_i32(at)?;
+        at += 4;
+        if len < 0 {
+            elements.push(Value::Null);
+        } else {
+            let bytes = raw.get(at..at + len as usize)?;
+            elements.push(postgres_value(element, bytes));
+            at += len as usize;
+        }
+    }
+
+    // Regroup the flat element list into nested arrays, innermost dimension first
+    for &len in dims.iter().skip(1).rev() {
+        elements = elements
+            .chunks(len.max(1))
+            .map(|chunk| Value::Array(chunk.to_vec()))
+            .collect();
+    }
+    Some(Value::Array(elements))
+}
+
+// ============================================================================
+// MySQL
+// ============================================================================
+
+/// Charset number MySQL reports for binary columns
+const MYSQL_BINARY_CHARSET: u16 = 63;
+
+pub fn mysql_columns(columns: &[mysql_async::Column]) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .map(|column| ColumnInfo {
+            name: column.name_str().to_string(),
+            type_name: format!("{:?}", column.column_type()).trim_start_matches("MYSQL_TYPE_").to_string(),
+        })
+        .collect()
+}
+
+pub fn mysql_row(columns: &[mysql_async::Column], row: mysql_async::Row) -> Vec<Value> {
+    row.unwrap()
+        .into_iter()
+        .zip(columns)
+        .map(|(value, column)| mysql_value(column, value))
+        .collect()
+}
+
+/// Text protocol values arrive as bytes; the column type says how to read them
+fn mysql_value(column: &mysql_async::Column, value: mysql_async::Value) -> Value {
+    use mysql_async::consts::ColumnType::*;
+    use mysql_async::Value as My;
+
+    match value {
+        My::NULL => Value::Null,
+        My::Int(i) => Value::from(i),
+        My::UInt(u) => Value::from(u),
+        My::Float(f) => number(f as f64),
+        My::Double(d) => number(d),
+        My::Date(year, month, day, hour, minute, second, micros) => {
+            let mut text = format!("{:04}-{:02}-{:02}", year, month, day);
+            if column.column_type() != MYSQL_TYPE_DATE {
+                write!(text, "T{:02}:{:02}:{:02}", hour, minute, second).ok();
+                if micros != 0 {
+                    write!(text, ".{:06}", micros).ok();
+                }
+            }
+            Value::String(text)
+        }
+        My::Time(negative, days, hours, minutes, seconds, micros) => {
+            let mut text = format!(
+                "{}{:02}:{:02}:{:02}",
+                if negative { "-" } else { "" },
+                days * 24 + hours as u32,
+                minutes,
+                seconds
+            );
+            if micros != 0 {
+                write!(text, ".{:06}", micros).ok();
+            }
+            Value::String(text)
+        }
+        My::Bytes(bytes) => match column.column_type() {
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+            | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR => std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(|text| {
+                    text.parse::<i64>()
+                        .map(Value::from)
+                        .or_else(|_| text.parse::<u64>().map(Value::from))
+                        .ok()
+                })
+                .unwrap_or_else(|| text_or_hex(&bytes)),
+            MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(|text| text.parse::<f64>().ok())
+                .map(number)
+                .unwrap_or_else(|| text_or_hex(&bytes)),
+            MYSQL_TYPE_JSON => serde_json::from_slice(&bytes).unwrap_or_else(|_| text_or_hex(&bytes)),
+            MYSQL_TYPE_BIT | MYSQL_TYPE_GEOMETRY => Value::String(hex_bytes(&bytes)),
+            _ if column.character_set() == MYSQL_BINARY_CHARSET
+                && !column.column_type().is_numeric_type()
+                && !matches!(
+                    column.column_type(),
+                    MYSQL_TYPE_DATE
+                        | MYSQL_TYPE_TIME
+                        | MYSQL_TYPE_DATETIME
+                        | MYSQL_TYPE_TIMESTAMP
+                        | MYSQL_TYPE_NEWDATE
+                ) =>
+            {
+                Value::String(hex_bytes(&bytes))
+            }
+            // Decimals stay exact; dates are already ISO-like text
+            _ => text_or_hex(&bytes),
+        },
+    }
+}
+
+// ============================================================================
+// SQLite
+// ============================================================================
+
+pub fn sqlite_value(value: rusqlite::types::ValueRef<'_>) -> Value {
+    use rusqlite::types::ValueRef;
+
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => number(f),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::String(hex_bytes(bytes)),
+    }
+}
+
+/// Storage class of a value, for expression columns without a declared type
+pub fn sqlite_storage_class(value: rusqlite::types::ValueRef<'_>) -> &'static str {
+    use rusqlite::types::ValueRef;
+
+    match value {
+        ValueRef::Null => "NULL",
+        ValueRef::Integer(_) => "INTEGER",
+        ValueRef::Real(_) => "REAL",
+        ValueRef::Text(_) => "TEXT",
+        ValueRef::Blob(_) => "BLOB",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_postgres::types::Type;
+
+    fn numeric(weight: i16, sign: u16, dscale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend((digits.len() as i16).to_be_bytes());
+        raw.extend(weight.to_be_bytes());
+        raw.extend(sign.to_be_bytes());
+        raw.extend(dscale.to_be_bytes());
+        for digit in digits {
+            raw.extend(digit.to_be_bytes());
+        }
+        raw
+    }
+
+    #[test]
+    fn test_numeric_is_exact() {
+        assert_eq!(numeric_to_string(&numeric(0, 0, 2, &[12, 5000])).unwrap(), "12.50");
+        assert_eq!(numeric_to_string(&numeric(-1, 0, 4, &[1])).unwrap(), "0.0001");
+        assert_eq!(
+            numeric_to_string(&numeric(1, 0x4000, 0, &[1, 2345])).unwrap(),
+            "-12345"
+        );
+        assert_eq!(
+            numeric_to_string(&numeric(2, 0, 20, &[9999, 9999, 9999, 1234, 5678, 9012, 3456, 7890, 1234]))
+                .unwrap(),
+            "999999999999.12345678901234567890"
+        );
+        assert_eq!(numeric_to_string(&numeric(0, 0xC000, 0, &[])).unwrap(), "NaN");
+    }
+
+    #[test]
+    fn test_postgres_values() {
+        assert_eq!(postgres_value(&Type::INT8, &42i64.to_be_bytes()), Value::from(42));
+        assert_eq!(postgres_value(&Type::BOOL, &[1]), Value::Bool(true));
+        assert_eq!(postgres_value(&Type::TEXT, b"hi"), Value::from("hi"));
+        assert_eq!(postgres_value(&Type::BYTEA, &[0xde, 0xad]), Value::from("\\xdead"));
+        assert_eq!(
+            postgres_value(&Type::JSONB, b"\x01{\"a\": [1]}"),
+            serde_json::json!({"a": [1]})
+        );
+        assert_eq!(
+            postgres_value(&Type::UUID, &[0x12; 16]),
+            Value::from("12121212-1212-1212-1212-121212121212")
+        );
+
+        // 2000-01-02 00:00:01.5 = one day and 1.5 s after the PostgreSQL epoch
+        let micros: i64 = 86_400_000_000 + 1_500_000;
+        assert_eq!(
+            postgres_value(&Type::TIMESTAMP, &micros.to_be_bytes()),
+            Value::from("2000-01-02T00:00:01.500")
+        );
+        assert_eq!(
+            postgres_value(&Type::TIMESTAMPTZ, &micros.to_be_bytes()),
+            Value::from("2000-01-02T00:00:01.500+00:00")
+        );
+        assert_eq!(postgres_value(&Type::DATE, &1i32.to_be_bytes()), Value::from("2000-01-02"));
+
+        let mut interval = 5_400_000_000i64.to_be_bytes().to_vec();
+        interval.extend(3i32.to_be_bytes());
+        interval.extend(14i32.to_be_bytes());
+        assert_eq!(
+            postgres_value(&Type::INTERVAL, &interval),
+            Value::from("1 years 2 mons 3 days 01:30:00")
+        );
+    }
+
+    #[test]
+    fn test_postgres_arrays() {
+        // int4[] {{1,2},{3,NULL}}
+        let mut raw = Vec::new();
+        for header in [2i32, 1, 23, 2, 1, 2, 1] {
+            raw.extend(header.to_be_bytes());
+        }
+        for value in [Some(1i32), Some(2), Some(3), None] {
+            match value {
+                Some(v) => {
+                    raw.extend(4i32.to_be_bytes());
+                    raw.extend(v.to_be_bytes());
+                }
+                None => raw.extend((-1i32).to_be_bytes()),
+            }
+        }
+        assert_eq!(
+            postgres_value(&Type::INT4_ARRAY, &raw),
+            serde_json::json!([[1, 2], [3, null]])
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Lazily loaded explorer tree
+//!
+//! Lists one folder at a time so the explorer only reads what is expanded.
+//! Entries matching the user's exclude patterns are left out, entries ignored
+//! by `.gitignore` are kept but flagged so the tree can grey them out, and
+//! every entry carries its git status.
+
+use crate::git::operations;
+use crate::remote;
+use crate::watcher::IgnoreRules;
+use anyhow::{anyhow, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Exclude patterns used when the frontend sends none
+const DEFAULT_EXCLUDE: &[&str] = &[".git", ".DS_Store", "Thumbs.db"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TreeEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    /// Matched by `.gitignore`
+    pub ignored: bool,
+    /// Git status letter; for folders, the most significant status below it
+    pub git_status: Option<String>,
+}
+
+/// Gitignore-style exclude patterns, relative to the workspace root
+pub(crate) fn exclude_matcher(root: &Path, patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|e| anyhow!("Invalid exclude pattern '{}': {}", pattern, e))?;
+    }
+    Ok(builder.build()?)
+}
+
+pub(crate) fn is_excluded(exclude: &Gitignore, root: &Path, path: &Path, is_dir: bool) -> bool {
+    path.starts_with(root) && exclude.matched_path_or_any_parents(path, is_dir).is_ignore()
+}
+
+/// Rank used to pick a folder's status from its children
+fn status_rank(status: &str) -> u8 {
+    match status {
+        "M" => 4,
+        "D" => 3,
+        "A" => 2,
+        "U" => 1,
+        _ => 0,
+    }
+}
+
+/// Status per direct child of a folder, folding in everything below each child
+fn child_statuses(dir: &Path) -> HashMap<PathBuf, &'static str> {
+    let mut result: HashMap<PathBuf, &'static str> = HashMap::new();
+    let statuses = operations::statuses_under(dir).unwrap_or_else(|e| {
+        tracing::warn!("git status for {} failed: {}", dir.display(), e);
+        HashMap::new()
+    });
+    for (path, status) in statuses {
+        let Some(child) = path.components().next() else {
+            continue;
+        };
+        let slot = result.entry(PathBuf::from(child.as_os_str())).or_insert(status);
+        if status_rank(status) > status_rank(slot) {
+            *slot = status;
+        }
+    }
+    result
+}
+
+/// Decorates the entries of one folder
+struct FolderContext<'a> {
+    root: &'a Path,
+    exclude: &'a Gitignore,
+    rules: IgnoreRules,
+    statuses: HashMap<PathBuf, &'static str>,
+}
+
+impl<'a> FolderContext<'a> {
+    fn new(root: &'a Path, exclude: &'a Gitignore, dir: &Path) -> Self {
+        Self {
+            root,
+            exclude,
+            rules: IgnoreRules::for_dir(root, dir),
+            statuses: child_statuses(dir),
+        }
+    }
+
+    fn entry(&self, path: &Path, is_dir: bool) -> Option<TreeEntry> {
+        if is_excluded(self.exclude, self.root, path, is_dir) {
+            return None;
+        }
+        let name = path.file_name()?.to_string_lossy().to_string();
+        Some(TreeEntry {
+            git_status: self.statuses.get(Path::new(&name)).map(|s| s.to_string()),
+            ignored: path != self.root && self.rules.is_ignored(path, is_dir),
+            name,
+            path: path.to_string_lossy().to_string(),
+            is_dir,
+        })
+    }
+}
+
+pub(crate) fn sort_entries(entries: &mut [TreeEntry]) {
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+/// The direct children of `dir`, folders first
+pub fn read_children(root: &Path, dir: &Path, exclude: &[String]) -> Result<Vec<TreeEntry>> {
+    let exclude = exclude_matcher(root, exclude)?;
+    let context = FolderContext::new(root, &exclude, dir);
+
+    let mut entries: Vec<TreeEntry> = fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read {}: {}", dir.display(), e))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            context.entry(&path, path.is_dir())
+        })
+        .collect();
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Fresh entries for individual paths, e.g. after a watcher event. Paths that
+/// no longer exist or are excluded are left out.
+pub fn read_entries(root: &Path, paths: &[String], exclude: &[String]) -> Result<Vec<TreeEntry>> {
+    let exclude = exclude_matcher(root, exclude)?;
+
+    let mut by_folder: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+    for path in paths.iter().map(Path::new) {
+        if let Some(parent) = path.parent() {
+            by_folder.entry(parent).or_default().push(path);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (dir, paths) in by_folder {
+        let context = FolderContext::new(root, &exclude, dir);
+        for path in paths {
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            entries.extend(context.entry(path, metadata.is_dir()));
+        }
+    }
+    Ok(entries)
+}
+
+pub(crate) fn exclude_or_default(exclude: Option<Vec<String>>) -> Vec<String> {
+    exclude.unwrap_or_else(|| DEFAULT_EXCLUDE.iter().map(|s| s.to_string()).collect())
+}
+
+/// List one folder of the explorer tree
+#[tauri::command]
+pub async fn list_dir(
+    root: String,
+    path: String,
+    exclude: Option<Vec<String>>,
+) -> Result<Vec<TreeEntry>, String> {
+    if remote::is_remote(&root) {
+        return tokio::task::spawn_blocking(move || remote::list_dir(&root, &path, &exclude_or_default(exclude)))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e));
+    }
+    read_children(Path::new(&root), Path::new(&path), &exclude_or_default(exclude)).map_err(|e| e.to_string())
+}
+
+/// Re-read individual explorer entries
+#[tauri::command]
+pub async fn stat_entries(
+    root: String,
+    paths: Vec<String>,
+    exclude: Option<Vec<String>>,
+) -> Result<Vec<TreeEntry>, String> {
+    if remote::is_remote(&root) {
+        return tokio::task::spawn_blocking(move || remote::stat_entries(&root, &paths, &exclude_or_default(exclude)))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e));
+    }
+    read_entries(Path::new(&root), &paths, &exclude_or_default(exclude)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(entries: &[TreeEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_read_children() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join(".env"), "").unwrap();
+        fs::write(root.join("b.txt"), "").unwrap();
+        fs::write(root.join("A.txt"), "").unwrap();
+        fs::write(root.join("src/nested/deep.rs"), "").unwrap();
+
+        let exclude = vec!["node_modules".to_string()];
+        let entries = read_children(root, root, &exclude).unwrap();
+
+        // Dotfiles are shown, excluded entries are not, and nothing is read
+        // below the requested folder
+        assert_eq!(names(&entries), vec!["src", "target", ".env", ".gitignore", "A.txt", "b.txt"]);
+        let ignored: Vec<&str> = entries.iter().filter(|e| e.ignored).map(|e| e.name.as_str()).collect();
+        assert_eq!(ignored, vec!["target"]);
+
+        assert!(read_children(root, root, &["src/{a".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_git_decorations() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let repo = git2::Repository::init(root).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "one").unwrap();
+        fs::write(root.join("clean.txt"), "").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+
+        fs::write(root.join("src/lib.rs"), "two").unwrap();
+        fs::write(root.join("src/new.rs"), "").unwrap();
+
+        let entries = read_children(root, root, &[]).unwrap();
+        let status = |name: &str| entries.iter().find(|e| e.name == name).unwrap().git_status.clone();
+        assert_eq!(status("src").as_deref(), Some("M"));
+        assert_eq!(status("clean.txt"), None);
+
+        let src = root.join("src");
+        let paths = vec![
+            src.join("new.rs").to_string_lossy().to_string(),
+            src.join("gone.rs").to_string_lossy().to_string(),
+        ];
+        let entries = read_entries(root, &paths, &[]).unwrap();
+        assert_eq!(names(&entries), vec!["new.rs"]);
+        assert_eq!(entries[0].git_status.as_deref(), Some("U"));
+    }
+}
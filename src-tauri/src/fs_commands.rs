@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use crate::local_history::{LocalHistory, RevisionSource};
+use crate::remote;
+use crate::text_file::{self, FileContents, TextFormat};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -46,6 +49,16 @@ pub async fn read_file(path: String) -> Result<String, String> {
     // ✅ Safety check: Prevent reading files larger than 10MB at once
     const MAX_SAFE_SIZE: u64 = 10_000_000; // 10MB
 
+    if remote::is_remote(&path) {
+        let bytes = tokio::task::spawn_blocking(move || remote::read_bytes(&path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e))?;
+        return text_file::decode_raw(&bytes, None)
+            .map(|(text, _, _)| text)
+            .map_err(|e| e.to_string());
+    }
+
     let metadata = fs::metadata(&path).map_err(|e| format!("Failed to get file size: {}", e))?;
 
     if metadata.len() > MAX_SAFE_SIZE {
@@ -55,14 +68,54 @@ pub async fn read_file(path: String) -> Result<String, String> {
         ));
     }
 
-    fs::read_to_string(&path).map_err(|e| {
-        let error_msg = e.to_string();
-        if error_msg.contains("stream did not contain valid UTF-8") {
-            format!("Cannot read binary file as text: {}", path)
-        } else {
-            format!("Failed to read file: {}", e)
-        }
-    })
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    if text_file::is_binary(&bytes) {
+        return Err(format!("Cannot read binary file as text: {}", path));
+    }
+    // Any detected encoding; line endings are left as they are
+    text_file::decode_raw(&bytes, None)
+        .map(|(text, _, _)| text)
+        .map_err(|e| e.to_string())
+}
+
+/// Read a file for the editor: text in its detected (or the given) encoding
+/// with `\n` line endings plus its on-disk format, or the size of a binary file
+#[tauri::command]
+pub async fn read_text_file(path: String, encoding: Option<String>) -> Result<FileContents, String> {
+    let bytes = if remote::is_remote(&path) {
+        tokio::task::spawn_blocking(move || remote::read_bytes(&path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e))?
+    } else {
+        fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?
+    };
+    text_file::decode(&bytes, encoding.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Raw bytes of a file range, e.g. for the hex view
+#[tauri::command]
+pub async fn read_file_bytes(path: String, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if remote::is_remote(&path) {
+        let bytes = tokio::task::spawn_blocking(move || remote::read_bytes(&path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e))?;
+        let start = (offset as usize).min(bytes.len());
+        return Ok(bytes[start..bytes.len().min(start + length)].to_vec());
+    }
+
+    let mut file = fs::File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("Failed to seek: {}", e))?;
+
+    let mut buffer = Vec::with_capacity(length);
+    file.take(length as u64)
+        .read_to_end(&mut buffer)
+        .map_err(|e| format!("Failed to read bytes: {}", e))?;
+    Ok(buffer)
 }
 
 /// ✅ IntelliJ Pro: Read file with partial loading (first N bytes only)
@@ -124,10 +177,38 @@ pub async fn read_file_chunk(path: String, offset: u64, length: usize) -> Result
     Ok(content)
 }
 
-/// Write file contents
+/// Write file contents (atomically, as UTF-8)
 #[tauri::command]
 pub async fn write_file(path: String, contents: String) -> Result<(), String> {
-    fs::write(&path, contents).map_err(|e| format!("Failed to write file: {}", e))
+    if remote::is_remote(&path) {
+        return tokio::task::spawn_blocking(move || remote::write_atomic(&path, contents.as_bytes()))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e));
+    }
+    text_file::write_atomic(Path::new(&path), contents.as_bytes()).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+/// Save editor text (with `\n` line endings) in the file's format
+#[tauri::command]
+pub async fn write_text_file(
+    path: String,
+    contents: String,
+    format: TextFormat,
+    history: tauri::State<'_, LocalHistory>,
+) -> Result<(), String> {
+    let bytes = text_file::encode(&contents, &format).map_err(|e| e.to_string())?;
+    if remote::is_remote(&path) {
+        return tokio::task::spawn_blocking(move || remote::write_atomic(&path, &bytes))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{:#}", e));
+    }
+    text_file::write_atomic(Path::new(&path), &bytes).map_err(|e| format!("Failed to write file: {}", e))?;
+    if let Err(e) = history.record(Path::new(&path), RevisionSource::Save) {
+        tracing::warn!("Failed to record local history of {}: {}", path, e);
+    }
+    Ok(())
 }
 
 /// Read directory contents recursively
@@ -262,6 +343,12 @@ pub async fn delete_file(path: String) -> Result<(), String> {
 /// Rename/move a file or directory
 #[tauri::command]
 pub async fn rename_file(old_path: String, new_path: String) -> Result<(), String> {
+    if remote::is_remote(&old_path) {
+        return tokio::task::spawn_blocking(move || remote::rename(&old_path, &new_path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to rename file: {:#}", e));
+    }
     fs::rename(&old_path, &new_path).map_err(|e| format!("Failed to rename file: {}", e))
 }
 
@@ -477,6 +564,33 @@ mod tests {
         assert!(result.is_err(), "Should fail for nonexistent file");
     }
 
+    #[tokio::test]
+    async fn test_read_text_file_and_bytes() {
+        let temp_dir = setup_test_dir();
+        let text_path = temp_dir.path().join("latin1.txt");
+        let binary_path = temp_dir.path().join("image.bin");
+        fs::write(&text_path, b"caf\xe9\r\n").unwrap();
+        fs::write(&binary_path, [0x89, b'P', b'N', b'G', 0, 0, 0, 13]).unwrap();
+        let text_path = text_path.to_str().unwrap().to_string();
+        let binary_path = binary_path.to_str().unwrap().to_string();
+
+        assert_eq!(read_file(text_path.clone()).await.unwrap(), "caf\u{e9}\r\n");
+        match read_text_file(text_path, None).await.unwrap() {
+            FileContents::Text { content, format } => {
+                assert_eq!(content, "caf\u{e9}\n");
+                assert_eq!(format.encoding, "windows-1252");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(read_file(binary_path.clone()).await.is_err());
+        assert_eq!(
+            read_text_file(binary_path.clone(), None).await.unwrap(),
+            FileContents::Binary { size: 8 }
+        );
+        assert_eq!(read_file_bytes(binary_path, 1, 3).await.unwrap(), b"PNG".to_vec());
+    }
+
     #[tokio::test]
     async fn test_file_node_sorting() {
         let temp_dir = setup_test_dir();
@@ -1,21 +1,39 @@
 //! Tauri Git Commands
 
-use super::{operations, types::*};
+use super::credentials::ProgressHandle;
+use super::{history, operations, types::*};
+use crate::remote;
 use std::path::PathBuf;
 use tauri::State;
 
 /// Git repository manager state
 pub struct GitManager {
     repo_path: std::sync::Mutex<Option<PathBuf>>,
+    progress: ProgressHandle,
 }
 
 impl GitManager {
     pub fn new() -> Self {
         Self {
             repo_path: std::sync::Mutex::new(None),
+            progress: ProgressHandle::default(),
         }
     }
 
+    /// Reset the progress for a new network operation and return its handle
+    pub fn start_progress(&self, operation: &str) -> ProgressHandle {
+        *self.progress.lock().unwrap() = Some(GitProgress {
+            operation: operation.to_string(),
+            stage: "connecting".to_string(),
+            ..Default::default()
+        });
+        self.progress.clone()
+    }
+
+    pub fn progress(&self) -> Option<GitProgress> {
+        self.progress.lock().unwrap().clone()
+    }
+
     pub fn set_repo_path(&self, path: PathBuf) {
         *self.repo_path.lock().unwrap() = Some(path);
     }
@@ -42,6 +60,16 @@ pub async fn git_set_repo_path(path: String, manager: State<'_, GitManager>) ->
     Ok(())
 }
 
+/// Repository, branch and change count of each local workspace folder
+#[tauri::command]
+pub async fn git_root_statuses(roots: Vec<String>) -> Result<Vec<RootStatus>, String> {
+    roots
+        .iter()
+        .filter(|root| !remote::is_remote(root))
+        .map(|root| operations::root_status(&PathBuf::from(root)).map_err(|e| e.to_string()))
+        .collect()
+}
+
 /// Get Git status
 #[tauri::command]
 pub async fn git_status(manager: State<'_, GitManager>) -> Result<Vec<FileStatus>, String> {
@@ -132,6 +160,51 @@ pub async fn git_log(
     operations::get_log(&repo_path, limit).map_err(|e| e.to_string())
 }
 
+/// One page of the commit graph, optionally filtered by author, path or message
+#[tauri::command]
+pub async fn git_log_graph(query: LogQuery, manager: State<'_, GitManager>) -> Result<LogPage<GraphRow>, String> {
+    let repo_path = manager.get_repo_path()?;
+    tokio::task::spawn_blocking(move || history::log_graph(&repo_path, &query))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// All file changes of a commit
+#[tauri::command]
+pub async fn git_commit_detail(commit: String, manager: State<'_, GitManager>) -> Result<CommitDetail, String> {
+    let repo_path = manager.get_repo_path()?;
+    history::commit_detail(&repo_path, &commit).map_err(|e| e.to_string())
+}
+
+/// Commits and file changes between two refs
+#[tauri::command]
+pub async fn git_compare_refs(
+    base: String,
+    target: String,
+    manager: State<'_, GitManager>,
+) -> Result<RefComparison, String> {
+    let repo_path = manager.get_repo_path()?;
+    history::compare_refs(&repo_path, &base, &target).map_err(|e| e.to_string())
+}
+
+/// Commits that changed a file, following renames
+#[tauri::command]
+pub async fn git_file_history(
+    file_path: String,
+    cursor: Option<LogCursor>,
+    limit: Option<usize>,
+    manager: State<'_, GitManager>,
+) -> Result<LogPage<FileHistoryEntry>, String> {
+    let repo_path = manager.get_repo_path()?;
+    tokio::task::spawn_blocking(move || {
+        history::file_history(&repo_path, &file_path, cursor.as_ref(), limit.unwrap_or(100))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
 /// Get file diff
 #[tauri::command]
 pub async fn git_diff_file(
@@ -152,6 +225,322 @@ pub async fn git_blame(
     operations::get_blame(&repo_path, &file_path).map_err(|e| e.to_string())
 }
 
+/// Progress of the running (or last) fetch/pull/push
+#[tauri::command]
+pub async fn git_progress(manager: State<'_, GitManager>) -> Result<Option<GitProgress>, String> {
+    Ok(manager.progress())
+}
+
+/// List remotes
+#[tauri::command]
+pub async fn git_list_remotes(manager: State<'_, GitManager>) -> Result<Vec<RemoteInfo>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::list_remotes(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Fetch from a remote (default: the upstream remote)
+#[tauri::command]
+pub async fn git_fetch(remote: Option<String>, manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    let progress = manager.start_progress("fetch");
+    tokio::task::spawn_blocking(move || operations::fetch(&repo_path, remote.as_deref(), progress))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Fetch and merge the upstream of the current branch
+#[tauri::command]
+pub async fn git_pull(remote: Option<String>, manager: State<'_, GitManager>) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    let progress = manager.start_progress("pull");
+    tokio::task::spawn_blocking(move || operations::pull(&repo_path, remote.as_deref(), progress))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Push the current branch
+#[tauri::command]
+pub async fn git_push(
+    remote: Option<String>,
+    force: Option<bool>,
+    manager: State<'_, GitManager>,
+) -> Result<String, String> {
+    let repo_path = manager.get_repo_path()?;
+    let progress = manager.start_progress("push");
+    let force = force.unwrap_or(false);
+    tokio::task::spawn_blocking(move || operations::push(&repo_path, remote.as_deref(), force, progress))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Merge a branch into the current branch
+#[tauri::command]
+pub async fn git_merge_branch(
+    branch_name: String,
+    manager: State<'_, GitManager>,
+) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::merge_branch(&repo_path, &branch_name).map_err(|e| e.to_string())
+}
+
+/// Rebase the current branch onto another branch
+#[tauri::command]
+pub async fn git_rebase(upstream: String, manager: State<'_, GitManager>) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::rebase(&repo_path, &upstream).map_err(|e| e.to_string())
+}
+
+/// Continue a rebase after resolving conflicts
+#[tauri::command]
+pub async fn git_rebase_continue(manager: State<'_, GitManager>) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::rebase_continue(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Skip the commit a rebase stopped on
+#[tauri::command]
+pub async fn git_rebase_skip(manager: State<'_, GitManager>) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::rebase_skip(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Commits after a base, oldest first, to build an interactive rebase plan from
+#[tauri::command]
+pub async fn git_rebase_commits(base: String, manager: State<'_, GitManager>) -> Result<Vec<CommitInfo>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::rebase_commits(&repo_path, &base).map_err(|e| e.to_string())
+}
+
+/// Run an interactive rebase plan
+#[tauri::command]
+pub async fn git_interactive_rebase(
+    onto: String,
+    steps: Vec<RebaseStep>,
+    manager: State<'_, GitManager>,
+) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::interactive_rebase(&repo_path, &onto, steps).map_err(|e| e.to_string())
+}
+
+/// Interactive rebase in progress, if any
+#[tauri::command]
+pub async fn git_interactive_rebase_state(
+    manager: State<'_, GitManager>,
+) -> Result<Option<InteractiveRebaseState>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::interactive_rebase_state(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Abort the merge, rebase or cherry-pick in progress
+#[tauri::command]
+pub async fn git_abort_operation(manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::abort_operation(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Cherry-pick a commit onto the current branch
+#[tauri::command]
+pub async fn git_cherry_pick(commit: String, manager: State<'_, GitManager>) -> Result<OperationResult, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::cherry_pick(&repo_path, &commit).map_err(|e| e.to_string())
+}
+
+/// Stash local changes
+#[tauri::command]
+pub async fn git_stash_save(
+    message: Option<String>,
+    include_untracked: Option<bool>,
+    manager: State<'_, GitManager>,
+) -> Result<String, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stash_save(&repo_path, message.as_deref(), include_untracked.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// List stashes
+#[tauri::command]
+pub async fn git_stash_list(manager: State<'_, GitManager>) -> Result<Vec<StashEntry>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stash_list(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Apply a stash
+#[tauri::command]
+pub async fn git_stash_apply(index: usize, manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stash_apply(&repo_path, index).map_err(|e| e.to_string())
+}
+
+/// Apply and drop a stash
+#[tauri::command]
+pub async fn git_stash_pop(index: usize, manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stash_pop(&repo_path, index).map_err(|e| e.to_string())
+}
+
+/// Drop a stash
+#[tauri::command]
+pub async fn git_stash_drop(index: usize, manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stash_drop(&repo_path, index).map_err(|e| e.to_string())
+}
+
+/// List tags
+#[tauri::command]
+pub async fn git_list_tags(manager: State<'_, GitManager>) -> Result<Vec<TagInfo>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::list_tags(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Create a tag (annotated when a message is given)
+#[tauri::command]
+pub async fn git_create_tag(
+    name: String,
+    target: Option<String>,
+    message: Option<String>,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::create_tag(&repo_path, &name, target.as_deref(), message.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Delete a tag
+#[tauri::command]
+pub async fn git_delete_tag(name: String, manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::delete_tag(&repo_path, &name).map_err(|e| e.to_string())
+}
+
+/// Push a tag to a remote
+#[tauri::command]
+pub async fn git_push_tag(
+    name: String,
+    remote: Option<String>,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    let progress = manager.start_progress("push");
+    tokio::task::spawn_blocking(move || operations::push_tag(&repo_path, &name, remote.as_deref(), progress))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Start bisecting between a bad and a good revision
+#[tauri::command]
+pub async fn git_bisect_start(
+    bad: String,
+    good: String,
+    manager: State<'_, GitManager>,
+) -> Result<BisectStatus, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::bisect_start(&repo_path, &bad, &good).map_err(|e| e.to_string())
+}
+
+/// Mark the current bisect commit "good", "bad" or "skip"
+#[tauri::command]
+pub async fn git_bisect_mark(mark: String, manager: State<'_, GitManager>) -> Result<BisectStatus, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::bisect_mark(&repo_path, &mark).map_err(|e| e.to_string())
+}
+
+/// End bisecting and return to the original branch
+#[tauri::command]
+pub async fn git_bisect_reset(manager: State<'_, GitManager>) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::bisect_reset(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Get bisect status
+#[tauri::command]
+pub async fn git_bisect_status(manager: State<'_, GitManager>) -> Result<BisectStatus, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::bisect_status(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Operation in progress (merge, rebase, ...) and its conflicts
+#[tauri::command]
+pub async fn git_repo_state(manager: State<'_, GitManager>) -> Result<RepoStateInfo, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::repo_state(&repo_path).map_err(|e| e.to_string())
+}
+
+/// Write resolved content for a conflicted file and mark it resolved
+#[tauri::command]
+pub async fn git_resolve_conflict(
+    file_path: String,
+    content: String,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::resolve_conflict(&repo_path, &file_path, &content).map_err(|e| e.to_string())
+}
+
+/// Hunks of a file, unstaged (default) or staged
+#[tauri::command]
+pub async fn git_file_hunks(
+    file_path: String,
+    staged: Option<bool>,
+    manager: State<'_, GitManager>,
+) -> Result<Vec<DiffHunk>, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::file_hunks(&repo_path, &file_path, staged.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Stage selected hunks or lines of a file
+#[tauri::command]
+pub async fn git_stage_hunks(
+    file_path: String,
+    selections: Vec<HunkSelection>,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::stage_hunks(&repo_path, &file_path, &selections).map_err(|e| e.to_string())
+}
+
+/// Unstage selected hunks or lines of a file
+#[tauri::command]
+pub async fn git_unstage_hunks(
+    file_path: String,
+    selections: Vec<HunkSelection>,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::unstage_hunks(&repo_path, &file_path, &selections).map_err(|e| e.to_string())
+}
+
+/// Discard selected hunks or lines of a file from the work tree
+#[tauri::command]
+pub async fn git_discard_hunks(
+    file_path: String,
+    selections: Vec<HunkSelection>,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::discard_hunks(&repo_path, &file_path, &selections).map_err(|e| e.to_string())
+}
+
+/// Base/ours/theirs versions of a conflicted file for the merge editor
+#[tauri::command]
+pub async fn git_three_way_merge(file_path: String, manager: State<'_, GitManager>) -> Result<ThreeWayMerge, String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::three_way_merge(&repo_path, &file_path).map_err(|e| e.to_string())
+}
+
+/// Resolve a conflicted file by taking ours, theirs or both
+#[tauri::command]
+pub async fn git_accept_conflict_side(
+    file_path: String,
+    side: ConflictSide,
+    manager: State<'_, GitManager>,
+) -> Result<(), String> {
+    let repo_path = manager.get_repo_path()?;
+    operations::accept_conflict_side(&repo_path, &file_path, side).map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
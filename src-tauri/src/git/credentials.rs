@@ -0,0 +1,141 @@
+//! Authentication and progress callbacks for network operations
+//!
+//! Credentials are tried in the order git itself uses:
+//! 1. SSH agent, then the default key files in `~/.ssh`
+//! 2. The configured credential helper (`credential.helper`) for HTTPS
+//! 3. Default (Kerberos/NTLM) credentials
+
+use super::types::GitProgress;
+use git2::{Config, Cred, CredentialType, RemoteCallbacks};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Key files tried after the SSH agent
+const SSH_KEY_FILES: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// Shared progress of the running network operation
+pub type ProgressHandle = Arc<Mutex<Option<GitProgress>>>;
+
+fn ssh_key_files() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    SSH_KEY_FILES
+        .iter()
+        .map(|name| home.join(".ssh").join(name))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Credential callback state; git2 calls back until a credential works or we give up
+struct CredentialState {
+    config: Config,
+    tried_agent: bool,
+    key_files: Vec<PathBuf>,
+    tried_helper: bool,
+    tried_default: bool,
+}
+
+impl CredentialState {
+    fn next(&mut self, url: &str, username: Option<&str>, allowed: CredentialType) -> Result<Cred, git2::Error> {
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username.unwrap_or("git"));
+        }
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            let user = username.unwrap_or("git");
+            if !self.tried_agent {
+                self.tried_agent = true;
+                return Cred::ssh_key_from_agent(user);
+            }
+            if let Some(key) = self.key_files.pop() {
+                return Cred::ssh_key(user, None, &key, None);
+            }
+        }
+
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !self.tried_helper {
+            self.tried_helper = true;
+            return Cred::credential_helper(&self.config, url, username);
+        }
+
+        if allowed.contains(CredentialType::DEFAULT) && !self.tried_default {
+            self.tried_default = true;
+            return Cred::default();
+        }
+
+        Err(git2::Error::from_str(&format!(
+            "Authentication failed for {}: no SSH agent identity, key file or credential helper was accepted",
+            url
+        )))
+    }
+}
+
+/// Remote callbacks with authentication and progress reporting
+pub fn remote_callbacks<'a>(config: Config, operation: &str, progress: ProgressHandle) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+
+    let mut state = CredentialState {
+        config,
+        tried_agent: false,
+        key_files: ssh_key_files(),
+        tried_helper: false,
+        tried_default: false,
+    };
+    callbacks.credentials(move |url, username, allowed| state.next(url, username, allowed));
+
+    let update = |progress: &ProgressHandle, operation: &str, f: &dyn Fn(&mut GitProgress)| {
+        let mut guard = progress.lock().unwrap();
+        let current = guard.get_or_insert_with(|| GitProgress {
+            operation: operation.to_string(),
+            stage: "connecting".to_string(),
+            ..Default::default()
+        });
+        f(current);
+    };
+
+    let op = operation.to_string();
+    let handle = progress.clone();
+    callbacks.transfer_progress(move |stats| {
+        update(&handle, &op, &|p| {
+            if stats.received_objects() < stats.total_objects() {
+                p.stage = "receiving".to_string();
+                p.current = stats.received_objects();
+            } else {
+                p.stage = "resolving".to_string();
+                p.current = stats.indexed_deltas();
+            }
+            p.total = if p.stage == "receiving" {
+                stats.total_objects()
+            } else {
+                stats.total_deltas()
+            };
+            p.bytes = stats.received_bytes();
+        });
+        true
+    });
+
+    let op = operation.to_string();
+    let handle = progress.clone();
+    callbacks.push_transfer_progress(move |current, total, bytes| {
+        update(&handle, &op, &|p| {
+            p.stage = "pushing".to_string();
+            p.current = current;
+            p.total = total;
+            p.bytes = bytes;
+        });
+    });
+
+    let op = operation.to_string();
+    let handle = progress;
+    callbacks.sideband_progress(move |data| {
+        let text = String::from_utf8_lossy(data);
+        // Progress lines are overwritten with '\r'; keep the latest one
+        if let Some(line) = text.split(['\r', '\n']).map(str::trim).rfind(|l| !l.is_empty()) {
+            let line = line.to_string();
+            update(&handle, &op, &|p| p.message = Some(line.clone()));
+        }
+        true
+    });
+
+    callbacks
+}
@@ -0,0 +1,719 @@
+//! Commit graph and history browsing
+//!
+//! Paged, filterable log with graph lanes, commit details, ref comparison and
+//! file history that follows renames
+
+use super::operations::relative_path;
+use super::types::*;
+use anyhow::{anyhow, Result};
+use git2::{Commit, Delta, Diff, DiffFindOptions, DiffOptions, Oid, Repository, Sort, Tree};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Upper bound on the commits listed in a ref comparison
+const MAX_COMPARE_COMMITS: usize = 1000;
+
+/// Lane layout of one graph row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaneRow {
+    pub lane: usize,
+    pub incoming: Vec<GraphEdge>,
+    pub outgoing: Vec<GraphEdge>,
+}
+
+/// Assign graph lanes to commits in display order (children before parents).
+///
+/// Each lane holds the commit it is waiting for; a commit takes the lane that
+/// expects it (or a free one), and its parents continue in that lane or open
+/// new ones to the right.
+pub fn graph_layout(commits: &[(String, Vec<String>)]) -> Vec<LaneRow> {
+    continue_layout(&mut Vec::new(), commits)
+}
+
+/// Lay out the next commits below rows whose open lanes are `lanes`
+fn continue_layout(lanes: &mut Vec<Option<String>>, commits: &[(String, Vec<String>)]) -> Vec<LaneRow> {
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for (id, parents) in commits {
+        let lane = lanes
+            .iter()
+            .position(|l| l.as_deref() == Some(id.as_str()))
+            .or_else(|| lanes.iter().position(Option::is_none))
+            .unwrap_or(lanes.len());
+        if lane == lanes.len() {
+            lanes.push(None);
+        }
+
+        let mut incoming = Vec::new();
+        let mut passing = Vec::new();
+        for (i, expected) in lanes.iter_mut().enumerate() {
+            match expected.as_deref() {
+                Some(e) if e == id => {
+                    incoming.push(GraphEdge { from: i, to: lane });
+                    *expected = None;
+                }
+                Some(_) => {
+                    incoming.push(GraphEdge { from: i, to: i });
+                    passing.push(i);
+                }
+                None => {}
+            }
+        }
+
+        let mut outgoing: Vec<GraphEdge> = passing.iter().map(|&i| GraphEdge { from: i, to: i }).collect();
+        for (k, parent) in parents.iter().enumerate() {
+            let target = match lanes.iter().position(|l| l.as_deref() == Some(parent.as_str())) {
+                Some(existing) => existing,
+                None => {
+                    let slot = if k == 0 {
+                        lane
+                    } else {
+                        lanes.iter().position(Option::is_none).unwrap_or(lanes.len())
+                    };
+                    if slot == lanes.len() {
+                        lanes.push(None);
+                    }
+                    lanes[slot] = Some(parent.clone());
+                    slot
+                }
+            };
+            outgoing.push(GraphEdge { from: lane, to: target });
+        }
+
+        while matches!(lanes.last(), Some(None)) {
+            lanes.pop();
+        }
+
+        rows.push(LaneRow { lane, incoming, outgoing });
+    }
+
+    rows
+}
+
+pub(super) fn commit_info(commit: &Commit) -> Result<CommitInfo> {
+    let author = commit.author();
+    Ok(CommitInfo {
+        hash: commit.id().to_string(),
+        short_hash: commit.as_object().short_id()?.as_str().unwrap_or("").to_string(),
+        message: commit.message().unwrap_or("").to_string(),
+        author: author.name().unwrap_or("").to_string(),
+        email: author.email().unwrap_or("").to_string(),
+        timestamp: commit.time().seconds(),
+        parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+    })
+}
+
+/// Branch and tag names by the commit they point to
+fn ref_names(repo: &Repository) -> Result<HashMap<Oid, Vec<String>>> {
+    let mut names: HashMap<Oid, Vec<String>> = HashMap::new();
+    for reference in repo.references()?.flatten() {
+        if !(reference.is_branch() || reference.is_remote() || reference.is_tag()) {
+            continue;
+        }
+        let Some(name) = reference.shorthand() else {
+            continue;
+        };
+        if name.ends_with("/HEAD") {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            names.entry(commit.id()).or_default().push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+fn lowercase_filter(filter: &Option<String>) -> Option<String> {
+    filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Whether a commit changed anything under `path` (compared with each parent, like `git log -- path`)
+fn touches_path(repo: &Repository, commit: &Commit, path: &str) -> Result<bool> {
+    let tree = commit.tree()?;
+    let diff_against = |parent_tree: Option<&Tree>| -> Result<bool> {
+        let mut opts = DiffOptions::new();
+        opts.pathspec(path);
+        let diff = repo.diff_tree_to_tree(parent_tree, Some(&tree), Some(&mut opts))?;
+        Ok(diff.deltas().len() > 0)
+    };
+
+    if commit.parent_count() == 0 {
+        return diff_against(None);
+    }
+    for parent in commit.parents() {
+        if !diff_against(Some(&parent.tree()?))? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Commits the log starts from
+fn tips(repo: &Repository, all_branches: bool) -> Result<Vec<Oid>> {
+    let mut tips = vec![repo.head()?.peel_to_commit()?.id()];
+    if all_branches {
+        for glob in ["refs/heads/*", "refs/remotes/*"] {
+            for reference in repo.references_glob(glob)?.flatten() {
+                if let Ok(commit) = reference.peel_to_commit() {
+                    tips.push(commit.id());
+                }
+            }
+        }
+    }
+    Ok(tips)
+}
+
+fn parse_oids(ids: &[String]) -> Result<Vec<Oid>> {
+    ids.iter().map(|id| Ok(Oid::from_str(id)?)).collect()
+}
+
+/// Walk commits newest first from `start` until `visit` has accepted `limit`
+/// of them. Returns the commits left to walk, or `None` when history is
+/// exhausted.
+fn walk_page(
+    repo: &Repository,
+    start: Vec<Oid>,
+    limit: usize,
+    mut visit: impl FnMut(&Commit) -> Result<bool>,
+) -> Result<Option<Vec<Oid>>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    for oid in &start {
+        revwalk.push(*oid)?;
+    }
+
+    // Parents are walked after all their children, so the commits reached but
+    // not walked yet are where the next page resumes
+    let mut pending = start;
+    let mut accepted = 0;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        pending.retain(|id| *id != commit.id());
+        for parent in commit.parent_ids() {
+            if !pending.contains(&parent) {
+                pending.push(parent);
+            }
+        }
+        if visit(&commit)? {
+            accepted += 1;
+            if accepted >= limit {
+                break;
+            }
+        }
+    }
+    Ok((!pending.is_empty()).then_some(pending))
+}
+
+fn cursor_ids(pending: Vec<Oid>) -> Vec<String> {
+    pending.iter().map(Oid::to_string).collect()
+}
+
+/// One page of the commit graph, continuing from `query.cursor`.
+///
+/// With a filter the matching commits are drawn as a single line.
+pub fn log_graph(repo_path: &Path, query: &LogQuery) -> Result<LogPage<GraphRow>> {
+    let repo = Repository::open(repo_path)?;
+    let (start, mut lanes) = match &query.cursor {
+        Some(cursor) => (parse_oids(&cursor.pending)?, cursor.lanes.clone()),
+        None => (tips(&repo, query.all_branches)?, Vec::new()),
+    };
+
+    let limit = if query.limit == 0 { DEFAULT_PAGE_SIZE } else { query.limit };
+    let author = lowercase_filter(&query.author);
+    let message = lowercase_filter(&query.message);
+    let path = query
+        .path
+        .as_deref()
+        .map(|p| relative_path(&repo, p.trim()))
+        .filter(|p| !p.is_empty());
+    let filtered = author.is_some() || message.is_some() || path.is_some();
+
+    let mut commits = Vec::new();
+    let pending = walk_page(&repo, start, limit, |commit| {
+        if let Some(author) = &author {
+            let signature = commit.author();
+            let name = signature.name().unwrap_or("").to_lowercase();
+            let email = signature.email().unwrap_or("").to_lowercase();
+            if !name.contains(author) && !email.contains(author) {
+                return Ok(false);
+            }
+        }
+        if let Some(message) = &message {
+            if !commit.message().unwrap_or("").to_lowercase().contains(message) {
+                return Ok(false);
+            }
+        }
+        if let Some(path) = &path {
+            if !touches_path(&repo, commit, path)? {
+                return Ok(false);
+            }
+        }
+
+        commits.push(commit_info(commit)?);
+        Ok(true)
+    })?;
+
+    let graph_input: Vec<(String, Vec<String>)> = if filtered {
+        commits
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.hash.clone(), commits.get(i + 1).map(|next| next.hash.clone()).into_iter().collect()))
+            .collect()
+    } else {
+        commits.iter().map(|c| (c.hash.clone(), c.parents.clone())).collect()
+    };
+    let layout = continue_layout(&mut lanes, &graph_input);
+    let mut refs = ref_names(&repo)?;
+
+    let items = commits
+        .into_iter()
+        .zip(layout)
+        .map(|(commit, row)| {
+            let names = Oid::from_str(&commit.hash)
+                .ok()
+                .and_then(|oid| refs.remove(&oid))
+                .unwrap_or_default();
+            GraphRow {
+                commit,
+                lane: row.lane,
+                incoming: row.incoming,
+                outgoing: row.outgoing,
+                refs: names,
+            }
+        })
+        .collect();
+    Ok(LogPage {
+        items,
+        cursor: pending.map(|pending| LogCursor {
+            pending: cursor_ids(pending),
+            lanes,
+            path: None,
+        }),
+    })
+}
+
+fn delta_status(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "A",
+        Delta::Deleted => "D",
+        Delta::Renamed => "R",
+        Delta::Copied => "C",
+        Delta::Typechange => "T",
+        _ => "M",
+    }
+}
+
+fn find_renames(diff: &mut Diff) -> Result<()> {
+    let mut opts = DiffFindOptions::new();
+    opts.renames(true);
+    diff.find_similar(Some(&mut opts))?;
+    Ok(())
+}
+
+/// Per-file hunks and totals of a diff
+fn diff_files(diff: &Diff) -> Result<(Vec<FileDiff>, DiffStats)> {
+    let files = RefCell::new(Vec::<FileDiff>::new());
+
+    diff.foreach(
+        &mut |delta, _progress| {
+            let new_path = delta.new_file().path().map(|p| p.to_string_lossy().replace('\\', "/"));
+            let old_path = delta.old_file().path().map(|p| p.to_string_lossy().replace('\\', "/"));
+            let renamed = matches!(delta.status(), Delta::Renamed | Delta::Copied);
+            files.borrow_mut().push(FileDiff {
+                path: new_path.clone().or_else(|| old_path.clone()).unwrap_or_default(),
+                old_path: if renamed { old_path } else { None },
+                status: delta_status(delta.status()).to_string(),
+                hunks: Vec::new(),
+            });
+            true
+        },
+        None,
+        Some(&mut |_delta, hunk| {
+            if let Some(file) = files.borrow_mut().last_mut() {
+                file.hunks.push(DiffHunk {
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines: Vec::new(),
+                });
+            }
+            true
+        }),
+        Some(&mut |_delta, _hunk, line| {
+            let line_type = match line.origin() {
+                '+' => "add",
+                '-' => "delete",
+                ' ' => "context",
+                // File headers and "no newline" markers
+                _ => return true,
+            };
+            if let Some(hunk) = files.borrow_mut().last_mut().and_then(|f| f.hunks.last_mut()) {
+                hunk.lines.push(DiffLine {
+                    line_type: line_type.to_string(),
+                    content: String::from_utf8_lossy(line.content()).to_string(),
+                    old_line_no: line.old_lineno(),
+                    new_line_no: line.new_lineno(),
+                });
+            }
+            true
+        }),
+    )?;
+
+    let stats = diff.stats()?;
+    Ok((
+        files.into_inner(),
+        DiffStats {
+            files_changed: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        },
+    ))
+}
+
+fn resolve_commit<'r>(repo: &'r Repository, rev: &str) -> Result<Commit<'r>> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| anyhow!("Unknown revision: {}", rev))
+}
+
+/// Full change set of a commit (against its first parent)
+pub fn commit_detail(repo_path: &Path, rev: &str) -> Result<CommitDetail> {
+    let repo = Repository::open(repo_path)?;
+    let commit = resolve_commit(&repo, rev)?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    find_renames(&mut diff)?;
+    let (files, stats) = diff_files(&diff)?;
+
+    Ok(CommitDetail {
+        info: commit_info(&commit)?,
+        files,
+        stats,
+    })
+}
+
+/// Commits and file changes between two refs (`git diff base target`)
+pub fn compare_refs(repo_path: &Path, base: &str, target: &str) -> Result<RefComparison> {
+    let repo = Repository::open(repo_path)?;
+    let base_commit = resolve_commit(&repo, base)?;
+    let target_commit = resolve_commit(&repo, target)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(target_commit.id())?;
+    revwalk.hide(base_commit.id())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut ahead = Vec::new();
+    for oid in revwalk.take(MAX_COMPARE_COMMITS) {
+        ahead.push(commit_info(&repo.find_commit(oid?)?)?);
+    }
+    let (_, behind) = repo.graph_ahead_behind(target_commit.id(), base_commit.id())?;
+
+    let mut diff = repo.diff_tree_to_tree(Some(&base_commit.tree()?), Some(&target_commit.tree()?), None)?;
+    find_renames(&mut diff)?;
+    let (files, stats) = diff_files(&diff)?;
+
+    Ok(RefComparison {
+        base: base.to_string(),
+        target: target.to_string(),
+        ahead,
+        behind,
+        files,
+        stats,
+    })
+}
+
+fn entry_id(tree: &Tree, path: &str) -> Option<Oid> {
+    tree.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+/// Where `path` in `commit` came from, if the commit renamed it
+fn renamed_from(repo: &Repository, commit: &Commit, parent: &Commit, path: &str) -> Result<Option<String>> {
+    let mut diff = repo.diff_tree_to_tree(Some(&parent.tree()?), Some(&commit.tree()?), None)?;
+    find_renames(&mut diff)?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Renamed)
+        .find(|delta| delta.new_file().path() == Some(Path::new(path)))
+        .and_then(|delta| delta.old_file().path().map(|p| p.to_string_lossy().replace('\\', "/"))))
+}
+
+/// Commits that changed a file, newest first, following renames and
+/// continuing from `cursor`
+pub fn file_history(
+    repo_path: &Path,
+    file_path: &str,
+    cursor: Option<&LogCursor>,
+    limit: usize,
+) -> Result<LogPage<FileHistoryEntry>> {
+    let repo = Repository::open(repo_path)?;
+    let (start, mut path) = match cursor {
+        Some(cursor) => (
+            parse_oids(&cursor.pending)?,
+            cursor.path.clone().unwrap_or_else(|| relative_path(&repo, file_path)),
+        ),
+        None => (tips(&repo, false)?, relative_path(&repo, file_path)),
+    };
+    let limit = if limit == 0 { DEFAULT_PAGE_SIZE } else { limit };
+
+    let mut entries = Vec::new();
+    let pending = walk_page(&repo, start, limit, |commit| {
+        let current = entry_id(&commit.tree()?, &path);
+        let parents: Vec<Commit> = commit.parents().collect();
+        let parent_ids = parents
+            .iter()
+            .map(|parent| Ok(entry_id(&parent.tree()?, &path)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Unchanged relative to some parent: the change (if any) is listed there
+        if parent_ids.contains(&current) || (parents.is_empty() && current.is_none()) {
+            return Ok(false);
+        }
+
+        let (status, old_path) = match (parent_ids.first().copied().flatten(), current) {
+            (Some(_), Some(_)) => ("M", None),
+            (Some(_), None) => ("D", None),
+            _ => match parents.first() {
+                Some(parent) => match renamed_from(&repo, commit, parent, &path)? {
+                    Some(old) => ("R", Some(old)),
+                    None => ("A", None),
+                },
+                None => ("A", None),
+            },
+        };
+
+        let entry_path = path.clone();
+        if let Some(old) = &old_path {
+            path = old.clone();
+        }
+
+        entries.push(FileHistoryEntry {
+            commit: commit_info(commit)?,
+            path: entry_path,
+            old_path,
+            status: status.to_string(),
+        });
+        Ok(true)
+    })?;
+
+    Ok(LogPage {
+        items: entries,
+        cursor: pending.map(|pending| LogCursor {
+            pending: cursor_ids(pending),
+            lanes: Vec::new(),
+            path: Some(path),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit(id: &str, parents: &[&str]) -> (String, Vec<String>) {
+        (id.to_string(), parents.iter().map(|p| p.to_string()).collect())
+    }
+
+    fn edge(from: usize, to: usize) -> GraphEdge {
+        GraphEdge { from, to }
+    }
+
+    #[test]
+    fn test_graph_layout_branch_and_merge() {
+        // m merges b into a; both fork from r
+        let rows = graph_layout(&[
+            commit("m", &["a", "b"]),
+            commit("a", &["r"]),
+            commit("b", &["r"]),
+            commit("r", &[]),
+        ]);
+
+        assert_eq!(rows[0].lane, 0);
+        assert_eq!(rows[0].outgoing, vec![edge(0, 0), edge(0, 1)]);
+        assert_eq!(rows[1].lane, 0);
+        assert_eq!(rows[1].incoming, vec![edge(0, 0), edge(1, 1)]);
+        assert_eq!(rows[2].lane, 1);
+        // b's parent is already expected in lane 0
+        assert_eq!(rows[2].outgoing, vec![edge(0, 0), edge(1, 0)]);
+        assert_eq!(rows[3].lane, 0);
+        assert_eq!(rows[3].incoming, vec![edge(0, 0)]);
+        assert!(rows[3].outgoing.is_empty());
+    }
+
+    #[test]
+    fn test_graph_layout_separate_tips() {
+        let rows = graph_layout(&[commit("x", &["r"]), commit("y", &["r"]), commit("r", &[])]);
+        assert_eq!(rows[1].lane, 1);
+        assert_eq!(rows[1].outgoing, vec![edge(0, 0), edge(1, 0)]);
+        assert_eq!(rows[2].incoming, vec![edge(0, 0)]);
+    }
+
+    fn create_repo() -> (TempDir, Repository) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        (temp_dir, repo)
+    }
+
+    /// Commit the given files (None deletes) on top of HEAD
+    fn commit_files(repo: &Repository, files: &[(&str, Option<&str>)], message: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (name, content) in files {
+            match content {
+                Some(content) => {
+                    fs::write(workdir.join(name), content).unwrap();
+                    index.add_path(Path::new(name)).unwrap();
+                }
+                None => {
+                    fs::remove_file(workdir.join(name)).unwrap();
+                    index.remove_path(Path::new(name)).unwrap();
+                }
+            }
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parents: Vec<Commit> = repo.head().ok().and_then(|h| h.peel_to_commit().ok()).into_iter().collect();
+        let parent_refs: Vec<&Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parent_refs)
+            .unwrap()
+    }
+
+    const CONTENT: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n";
+
+    #[test]
+    fn test_log_graph_paging_and_filters() {
+        let (temp_dir, repo) = create_repo();
+        commit_files(&repo, &[("a.txt", Some("a\n"))], "add a");
+        commit_files(&repo, &[("b.txt", Some("b\n"))], "add b");
+        commit_files(&repo, &[("a.txt", Some("a2\n"))], "fix a");
+
+        let all = log_graph(temp_dir.path(), &LogQuery::default()).unwrap();
+        assert_eq!(all.items.len(), 3);
+        assert!(all.cursor.is_none());
+        assert!(all.items[0].refs.iter().any(|r| r == "main" || r == "master"));
+
+        let first = log_graph(temp_dir.path(), &LogQuery { limit: 1, ..Default::default() }).unwrap();
+        assert_eq!(first.items[0].commit.message, "fix a");
+        let page = log_graph(
+            temp_dir.path(),
+            &LogQuery { cursor: first.cursor, limit: 1, ..Default::default() },
+        )
+        .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].commit.message, "add b");
+
+        let by_path = log_graph(temp_dir.path(), &LogQuery { path: Some("a.txt".into()), ..Default::default() }).unwrap();
+        let messages: Vec<&str> = by_path.items.iter().map(|r| r.commit.message.as_str()).collect();
+        assert_eq!(messages, vec!["fix a", "add a"]);
+
+        let by_message =
+            log_graph(temp_dir.path(), &LogQuery { message: Some("ADD".into()), ..Default::default() }).unwrap();
+        assert_eq!(by_message.items.len(), 2);
+
+        let by_author =
+            log_graph(temp_dir.path(), &LogQuery { author: Some("nobody".into()), ..Default::default() }).unwrap();
+        assert!(by_author.items.is_empty());
+    }
+
+    #[test]
+    fn test_log_graph_pages_continue_lanes() {
+        let (temp_dir, repo) = create_repo();
+        let base = commit_files(&repo, &[("a.txt", Some("a\n"))], "base");
+        commit_files(&repo, &[("a.txt", Some("a2\n"))], "main 1");
+        commit_files(&repo, &[("a.txt", Some("a3\n"))], "main 2");
+        // A side branch forking from the first commit
+        let signature = repo.signature().unwrap();
+        let base = repo.find_commit(base).unwrap();
+        repo.commit(Some("refs/heads/side"), &signature, &signature, "side", &base.tree().unwrap(), &[&base])
+            .unwrap();
+
+        let query = LogQuery { all_branches: true, ..Default::default() };
+        let all = log_graph(temp_dir.path(), &query).unwrap().items;
+        assert_eq!(all.len(), 4);
+
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = log_graph(temp_dir.path(), &LogQuery { cursor, limit: 1, ..query.clone() }).unwrap();
+            paged.extend(page.items);
+            cursor = page.cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let rows = |rows: &[GraphRow]| -> Vec<(String, usize, Vec<GraphEdge>, Vec<GraphEdge>)> {
+            rows.iter()
+                .map(|r| (r.commit.hash.clone(), r.lane, r.incoming.clone(), r.outgoing.clone()))
+                .collect()
+        };
+        assert_eq!(rows(&paged), rows(&all));
+    }
+
+    #[test]
+    fn test_commit_detail_and_compare() {
+        let (temp_dir, repo) = create_repo();
+        let first = commit_files(&repo, &[("a.txt", Some("a\n"))], "add a");
+        commit_files(&repo, &[("a.txt", Some("a\nb\n")), ("c.txt", Some("c\n"))], "more");
+
+        let detail = commit_detail(temp_dir.path(), "HEAD").unwrap();
+        assert_eq!(detail.stats.files_changed, 2);
+        assert_eq!(detail.stats.insertions, 2);
+        let a = detail.files.iter().find(|f| f.path == "a.txt").unwrap();
+        assert_eq!(a.status, "M");
+        assert!(a.hunks[0].lines.iter().any(|l| l.line_type == "add" && l.content == "b\n"));
+        assert_eq!(detail.files.iter().find(|f| f.path == "c.txt").unwrap().status, "A");
+
+        let comparison = compare_refs(temp_dir.path(), &first.to_string(), "HEAD").unwrap();
+        assert_eq!(comparison.ahead.len(), 1);
+        assert_eq!(comparison.behind, 0);
+        assert_eq!(comparison.files.len(), 2);
+
+        assert!(commit_detail(temp_dir.path(), "no-such-ref").is_err());
+    }
+
+    #[test]
+    fn test_file_history_follows_renames() {
+        let (temp_dir, repo) = create_repo();
+        commit_files(&repo, &[("old.txt", Some(CONTENT))], "create");
+        commit_files(&repo, &[("other.txt", Some("x\n"))], "unrelated");
+        commit_files(&repo, &[("old.txt", None), ("new.txt", Some(CONTENT))], "rename");
+        commit_files(&repo, &[("new.txt", Some(&format!("{}nine\n", CONTENT)))], "edit");
+
+        let history = file_history(temp_dir.path(), "new.txt", None, 0).unwrap().items;
+        let summary: Vec<(&str, &str, &str)> = history
+            .iter()
+            .map(|e| (e.commit.message.as_str(), e.status.as_str(), e.path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("edit", "M", "new.txt"), ("rename", "R", "new.txt"), ("create", "A", "old.txt")]
+        );
+        assert_eq!(history[1].old_path.as_deref(), Some("old.txt"));
+
+        let first = file_history(temp_dir.path(), "new.txt", None, 2).unwrap();
+        assert_eq!(first.items.len(), 2);
+        // The next page follows the file under its old name
+        let page = file_history(temp_dir.path(), "new.txt", first.cursor.as_ref(), 2).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].commit.message, "create");
+        assert_eq!(page.items[0].path, "old.txt");
+    }
+}
@@ -1,6 +1,8 @@
 //! Git integration module
 
 pub mod commands;
+pub mod credentials;
+pub mod history;
 pub mod operations;
 pub mod types;
 
@@ -1,10 +1,18 @@
 //! Git operations using git2-rs
 
+use super::credentials::{remote_callbacks, ProgressHandle};
 use super::types::*;
-use anyhow::{Context, Result};
-use git2::{Branch, BranchType, Commit, DiffOptions, Repository, Status, StatusOptions};
+use anyhow::{anyhow, Context, Result};
+use git2::build::CheckoutBuilder;
+use git2::{
+    AnnotatedCommit, AutotagOption, Branch, BranchType, CherrypickOptions, Commit, DiffOptions, ErrorCode,
+    FetchOptions, Oid, PushOptions, Rebase, Repository, RepositoryState, ResetType, Sort, Status, StatusOptions,
+};
 use std::cell::RefCell;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
 
 /// Get Git repository status
 pub fn get_status(repo_path: &Path) -> Result<Vec<FileStatus>> {
@@ -21,22 +29,7 @@ pub fn get_status(repo_path: &Path) -> Result<Vec<FileStatus>> {
         let path = entry.path().unwrap_or("").to_string();
         let git_status = entry.status();
 
-        // Determine status string
-        let status_str = if git_status.contains(Status::INDEX_NEW) {
-            "A"
-        } else if git_status.contains(Status::INDEX_MODIFIED) {
-            "M"
-        } else if git_status.contains(Status::INDEX_DELETED) {
-            "D"
-        } else if git_status.contains(Status::WT_NEW) {
-            "U"
-        } else if git_status.contains(Status::WT_MODIFIED) {
-            "M"
-        } else if git_status.contains(Status::WT_DELETED) {
-            "D"
-        } else {
-            "?"
-        };
+        let status_str = status_code(git_status);
 
         // Determine if staged
         let is_staged = git_status.contains(Status::INDEX_NEW)
@@ -53,6 +46,76 @@ pub fn get_status(repo_path: &Path) -> Result<Vec<FileStatus>> {
     Ok(result)
 }
 
+/// Status letter shown for a file: staged changes win over the worktree
+fn status_code(status: Status) -> &'static str {
+    if status.contains(Status::INDEX_NEW) {
+        "A"
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        "M"
+    } else if status.contains(Status::INDEX_DELETED) {
+        "D"
+    } else if status.contains(Status::WT_NEW) {
+        "U"
+    } else if status.contains(Status::WT_MODIFIED) {
+        "M"
+    } else if status.contains(Status::WT_DELETED) {
+        "D"
+    } else {
+        "?"
+    }
+}
+
+/// Status letters of the changed paths below `dir`, relative to `dir`.
+/// Untracked folders are reported once rather than file by file. Empty when
+/// `dir` is not inside a repository.
+pub fn statuses_under(dir: &Path) -> Result<HashMap<PathBuf, &'static str>> {
+    let Ok(repo) = Repository::discover(dir) else {
+        return Ok(HashMap::new());
+    };
+    let Some(workdir) = repo.workdir() else {
+        return Ok(HashMap::new());
+    };
+    let dir = dir.canonicalize()?;
+    let relative = dir.strip_prefix(workdir.canonicalize()?)?.to_path_buf();
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true);
+    opts.recurse_untracked_dirs(false);
+    if !relative.as_os_str().is_empty() {
+        opts.pathspec(&relative);
+    }
+
+    let mut result = HashMap::new();
+    for entry in repo.statuses(Some(&mut opts))?.iter() {
+        let Some(path) = entry.path() else { continue };
+        if let Ok(below) = Path::new(path.trim_end_matches('/')).strip_prefix(&relative) {
+            result.insert(below.to_path_buf(), status_code(entry.status()));
+        }
+    }
+    Ok(result)
+}
+
+/// Repository, branch and number of changed paths of a workspace folder,
+/// which may be the repository itself or a folder inside it
+pub fn root_status(root: &Path) -> Result<RootStatus> {
+    let mut status = RootStatus {
+        root: root.to_string_lossy().to_string(),
+        repo_path: None,
+        branch: None,
+        changes: 0,
+    };
+    let Some(workdir) = Repository::discover(root)
+        .ok()
+        .and_then(|repo| repo.workdir().map(|dir| dir.components().collect::<PathBuf>()))
+    else {
+        return Ok(status);
+    };
+    status.branch = current_branch(&workdir).ok();
+    status.changes = statuses_under(root)?.len();
+    status.repo_path = Some(workdir.to_string_lossy().to_string());
+    Ok(status)
+}
+
 /// List all branches
 pub fn list_branches(repo_path: &Path) -> Result<Vec<BranchInfo>> {
     let repo = Repository::open(repo_path)?;
@@ -70,9 +133,14 @@ pub fn list_branches(repo_path: &Path) -> Result<Vec<BranchInfo>> {
             .ok()
             .and_then(|u| u.name().ok().flatten().map(|s| s.to_string()));
 
-        // Calculate ahead/behind (simplified - would need more complex logic)
-        let ahead = 0;
-        let behind = 0;
+        // Commits ahead of / behind the upstream branch
+        let (ahead, behind) = match (branch.get().target(), branch.upstream().ok().and_then(|u| u.get().target())) {
+            (Some(local), Some(upstream)) => repo
+                .graph_ahead_behind(local, upstream)
+                .map(|(a, b)| (a as u32, b as u32))
+                .unwrap_or((0, 0)),
+            _ => (0, 0),
+        };
 
         result.push(BranchInfo {
             name,
@@ -133,7 +201,16 @@ pub fn stage_all(repo_path: &Path) -> Result<()> {
 
 /// Create a commit
 pub fn commit(repo_path: &Path, message: &str) -> Result<String> {
-    let repo = Repository::open(repo_path)?;
+    let mut repo = Repository::open(repo_path)?;
+
+    // Concluding a merge: the merged commits are parents too
+    let mut merge_parents = Vec::new();
+    if repo.state() == RepositoryState::Merge {
+        repo.mergehead_foreach(|oid| {
+            merge_parents.push(*oid);
+            true
+        })?;
+    }
 
     // Get signature
     let signature = repo.signature()?;
@@ -146,6 +223,13 @@ pub fn commit(repo_path: &Path, message: &str) -> Result<String> {
     // Get parent commit
     let parent_commit = repo.head()?.peel_to_commit()?;
 
+    let merge_parents = merge_parents
+        .into_iter()
+        .map(|oid| repo.find_commit(oid))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut parents = vec![&parent_commit];
+    parents.extend(merge_parents.iter());
+
     // Create commit
     let commit_id = repo.commit(
         Some("HEAD"),
@@ -153,9 +237,17 @@ pub fn commit(repo_path: &Path, message: &str) -> Result<String> {
         &signature,
         message,
         &tree,
-        &[&parent_commit],
+        &parents,
     )?;
 
+    // Merge, cherry-pick and revert are done once their result is committed
+    if matches!(
+        repo.state(),
+        RepositoryState::Merge | RepositoryState::CherryPick | RepositoryState::Revert
+    ) {
+        repo.cleanup_state()?;
+    }
+
     Ok(commit_id.to_string())
 }
 
@@ -304,6 +396,270 @@ pub fn get_file_diff(repo_path: &Path, file_path: &str) -> Result<FileDiff> {
     Ok(file_diff.into_inner())
 }
 
+// ========== Hunk staging ==========
+
+/// Path relative to the repository root (absolute paths inside the work tree are accepted)
+pub(super) fn relative_path(repo: &Repository, file_path: &str) -> String {
+    let path = Path::new(file_path);
+    if path.is_absolute() {
+        if let Some(relative) = repo.workdir().and_then(|workdir| path.strip_prefix(workdir).ok()) {
+            return relative.to_string_lossy().replace('\\', "/");
+        }
+    }
+    file_path.to_string()
+}
+
+fn head_content(repo: &Repository, path: &str) -> Result<Option<Vec<u8>>> {
+    let Ok(head) = repo.head() else {
+        return Ok(None);
+    };
+    match head.peel_to_tree()?.get_path(Path::new(path)) {
+        Ok(entry) => Ok(Some(entry.to_object(repo)?.peel_to_blob()?.content().to_vec())),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn index_content(repo: &Repository, path: &str) -> Result<Option<Vec<u8>>> {
+    match repo.index()?.get_path(Path::new(path), 0) {
+        Some(entry) => Ok(Some(repo.find_blob(entry.id)?.content().to_vec())),
+        None => Ok(None),
+    }
+}
+
+fn workdir_content(repo: &Repository, path: &str) -> Result<Option<Vec<u8>>> {
+    let full_path = repo.workdir().context("Repository has no work tree")?.join(path);
+    if full_path.exists() {
+        Ok(Some(std::fs::read(full_path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The bytes of one side of a diff. Hunks are applied to bytes, so files in
+/// other encodings than UTF-8 are written back unchanged.
+fn text_bytes(content: &Option<Vec<u8>>) -> Result<&[u8]> {
+    let bytes = content.as_deref().unwrap_or_default();
+    if bytes.contains(&0) {
+        return Err(anyhow!("Hunk operations are not supported for binary files"));
+    }
+    Ok(bytes)
+}
+
+/// Hunks between two versions of a file (3 lines of context, like `git diff`)
+fn buffer_hunks(old: &[u8], new: &[u8], path: &str) -> Result<Vec<DiffHunk>> {
+    let mut opts = DiffOptions::new();
+    opts.context_lines(3);
+    let patch = git2::Patch::from_buffers(
+        old,
+        Some(Path::new(path)),
+        new,
+        Some(Path::new(path)),
+        Some(&mut opts),
+    )?;
+
+    let mut hunks = Vec::new();
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index)?;
+        let mut lines = Vec::new();
+
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index)?;
+            let line_type = match line.origin() {
+                '+' => "add",
+                '-' => "delete",
+                ' ' => "context",
+                // "\ No newline at end of file" markers
+                _ => continue,
+            };
+            lines.push(DiffLine {
+                line_type: line_type.to_string(),
+                content: String::from_utf8_lossy(line.content()).to_string(),
+                old_line_no: line.old_lineno(),
+                new_line_no: line.new_lineno(),
+            });
+        }
+
+        hunks.push(DiffHunk {
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
+
+    Ok(hunks)
+}
+
+/// Rebuild `old` with the selected lines of its diff to `new` applied
+///
+/// Unselected deletions keep the old line, unselected additions are left out.
+pub fn apply_hunk_lines(
+    old: &[u8],
+    new: &[u8],
+    hunks: &[DiffHunk],
+    selected: impl Fn(usize, usize) -> bool,
+) -> Vec<u8> {
+    let old_lines: Vec<&[u8]> = old.split_inclusive(|&b| b == b'\n').collect();
+    let new_lines: Vec<&[u8]> = new.split_inclusive(|&b| b == b'\n').collect();
+    let mut result = Vec::with_capacity(new.len().max(old.len()));
+    let mut next_old = 0;
+
+    let push_line = |result: &mut Vec<u8>, line: &[u8]| {
+        // A line that had no final newline is no longer the last one
+        if !result.is_empty() && !result.ends_with(b"\n") {
+            result.push(b'\n');
+        }
+        result.extend_from_slice(line);
+    };
+
+    for (hunk_index, hunk) in hunks.iter().enumerate() {
+        for (line_index, line) in hunk.lines.iter().enumerate() {
+            match (line.line_type.as_str(), line.old_line_no, line.new_line_no) {
+                ("context", Some(old_no), _) | ("delete", Some(old_no), _) => {
+                    let old_index = old_no as usize - 1;
+                    while next_old < old_index.min(old_lines.len()) {
+                        push_line(&mut result, old_lines[next_old]);
+                        next_old += 1;
+                    }
+                    let keep_old = line.line_type == "context" || !selected(hunk_index, line_index);
+                    if keep_old {
+                        if let Some(old_line) = old_lines.get(old_index) {
+                            push_line(&mut result, old_line);
+                        }
+                    }
+                    next_old = old_index + 1;
+                }
+                ("add", _, Some(new_no)) if selected(hunk_index, line_index) => {
+                    // Pure insertions come after the old line they follow
+                    let insert_after = hunk.old_start as usize - usize::from(hunk.old_lines != 0);
+                    while next_old < insert_after.min(old_lines.len()) {
+                        push_line(&mut result, old_lines[next_old]);
+                        next_old += 1;
+                    }
+                    if let Some(new_line) = new_lines.get(new_no as usize - 1) {
+                        push_line(&mut result, new_line);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for old_line in old_lines.iter().skip(next_old) {
+        push_line(&mut result, old_line);
+    }
+
+    result
+}
+
+fn is_selected(selections: &[HunkSelection], hunk_index: usize, line_index: usize) -> bool {
+    selections.iter().any(|s| {
+        s.hunk_index == hunk_index && s.lines.as_ref().is_none_or(|lines| lines.contains(&line_index))
+    })
+}
+
+fn write_index_content(repo: &Repository, path: &str, content: Option<&[u8]>) -> Result<()> {
+    let mut index = repo.index()?;
+
+    match content {
+        Some(content) => {
+            let mut entry = index.get_path(Path::new(path), 0).unwrap_or(git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100644,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            });
+            entry.file_size = content.len() as u32;
+            index.add_frombuffer(&entry, content)?;
+        }
+        None => index.remove_path(Path::new(path))?,
+    }
+
+    index.write()?;
+    Ok(())
+}
+
+/// Hunks of a file: unstaged (index → work tree) or staged (HEAD → index)
+pub fn file_hunks(repo_path: &Path, file_path: &str, staged: bool) -> Result<Vec<DiffHunk>> {
+    let repo = Repository::open(repo_path)?;
+    let path = relative_path(&repo, file_path);
+
+    let (old, new) = if staged {
+        (head_content(&repo, &path)?, index_content(&repo, &path)?)
+    } else {
+        let index = index_content(&repo, &path)?;
+        (index, workdir_content(&repo, &path)?)
+    };
+
+    buffer_hunks(text_bytes(&old)?, text_bytes(&new)?, &path)
+}
+
+/// Stage the selected hunks/lines of the work tree changes
+pub fn stage_hunks(repo_path: &Path, file_path: &str, selections: &[HunkSelection]) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let path = relative_path(&repo, file_path);
+    let old = index_content(&repo, &path)?;
+    let new = workdir_content(&repo, &path)?;
+    let (old_text, new_text) = (text_bytes(&old)?, text_bytes(&new)?);
+
+    let hunks = buffer_hunks(old_text, new_text, &path)?;
+    let result = apply_hunk_lines(old_text, new_text, &hunks, |h, l| is_selected(selections, h, l));
+
+    // Staging the whole deletion of a file removes it from the index
+    let deleted = new.is_none() && result.is_empty();
+    write_index_content(&repo, &path, (!deleted).then_some(result.as_slice()))
+}
+
+/// Unstage the selected hunks/lines of the staged changes
+pub fn unstage_hunks(repo_path: &Path, file_path: &str, selections: &[HunkSelection]) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let path = relative_path(&repo, file_path);
+    let old = head_content(&repo, &path)?;
+    let new = index_content(&repo, &path)?;
+    let (old_text, new_text) = (text_bytes(&old)?, text_bytes(&new)?);
+
+    let hunks = buffer_hunks(old_text, new_text, &path)?;
+    // Keep every staged change except the selected ones
+    let result = apply_hunk_lines(old_text, new_text, &hunks, |h, l| !is_selected(selections, h, l));
+
+    // Unstaging all of a newly added file removes it from the index
+    let removed = old.is_none() && result.is_empty();
+    write_index_content(&repo, &path, (!removed).then_some(result.as_slice()))
+}
+
+/// Revert the selected hunks/lines of the work tree changes to the staged version
+pub fn discard_hunks(repo_path: &Path, file_path: &str, selections: &[HunkSelection]) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let path = relative_path(&repo, file_path);
+    let old = index_content(&repo, &path)?;
+    let new = workdir_content(&repo, &path)?;
+    let (old_text, new_text) = (text_bytes(&old)?, text_bytes(&new)?);
+
+    let hunks = buffer_hunks(old_text, new_text, &path)?;
+    let result = apply_hunk_lines(old_text, new_text, &hunks, |h, l| !is_selected(selections, h, l));
+
+    let full_path = repo.workdir().context("Repository has no work tree")?.join(&path);
+    if old.is_none() && result.is_empty() {
+        // Discarding all of an untracked file deletes it
+        if full_path.exists() {
+            std::fs::remove_file(full_path)?;
+        }
+    } else {
+        std::fs::write(full_path, result)?;
+    }
+    Ok(())
+}
+
 /// Get blame for a file
 pub fn get_blame(repo_path: &Path, file_path: &str) -> Result<Vec<BlameLine>> {
     let repo = Repository::open(repo_path)?;
@@ -335,6 +691,945 @@ pub fn get_blame(repo_path: &Path, file_path: &str) -> Result<Vec<BlameLine>> {
     Ok(result)
 }
 
+// ========== Remotes ==========
+
+/// List configured remotes
+pub fn list_remotes(repo_path: &Path) -> Result<Vec<RemoteInfo>> {
+    let repo = Repository::open(repo_path)?;
+    let mut result = Vec::new();
+
+    for name in repo.remotes()?.iter().flatten() {
+        let remote = repo.find_remote(name)?;
+        result.push(RemoteInfo {
+            name: name.to_string(),
+            url: remote.url().unwrap_or("").to_string(),
+            fetch_url: remote.url().map(|s| s.to_string()),
+            push_url: remote.pushurl().or(remote.url()).map(|s| s.to_string()),
+        });
+    }
+
+    Ok(result)
+}
+
+/// Remote to use: the given one, else the current branch's upstream remote, else "origin"
+fn resolve_remote_name(repo: &Repository, remote: Option<&str>) -> String {
+    if let Some(remote) = remote {
+        return remote.to_string();
+    }
+    repo.head()
+        .ok()
+        .and_then(|head| head.name().map(|s| s.to_string()))
+        .and_then(|refname| repo.branch_upstream_remote(&refname).ok())
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "origin".to_string())
+}
+
+fn head_branch_name(repo: &Repository) -> Result<String> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(anyhow!("HEAD is detached; check out a branch first"));
+    }
+    Ok(head.shorthand().unwrap_or("").to_string())
+}
+
+fn finish_progress(progress: &ProgressHandle) {
+    if let Some(p) = progress.lock().unwrap().as_mut() {
+        p.stage = "done".to_string();
+    }
+}
+
+/// Fetch from a remote (tags that point into fetched history are included)
+pub fn fetch(repo_path: &Path, remote: Option<&str>, progress: ProgressHandle) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let name = resolve_remote_name(&repo, remote);
+    let mut remote = repo
+        .find_remote(&name)
+        .with_context(|| format!("Remote '{}' not found", name))?;
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks(repo.config()?, "fetch", progress.clone()));
+    options.download_tags(AutotagOption::Auto);
+
+    // No refspecs: use the remote's configured ones
+    remote.fetch::<&str>(&[], Some(&mut options), None)?;
+    finish_progress(&progress);
+    Ok(())
+}
+
+/// Fetch, then merge the current branch's upstream into it
+pub fn pull(repo_path: &Path, remote: Option<&str>, progress: ProgressHandle) -> Result<OperationResult> {
+    fetch(repo_path, remote, progress)?;
+
+    let repo = Repository::open(repo_path)?;
+    let branch_name = head_branch_name(&repo)?;
+    let branch = repo.find_branch(&branch_name, BranchType::Local)?;
+
+    let upstream = match branch.upstream() {
+        Ok(upstream) => upstream.into_reference(),
+        Err(_) => {
+            let name = resolve_remote_name(&repo, remote);
+            repo.find_reference(&format!("refs/remotes/{}/{}", name, branch_name))
+                .with_context(|| format!("No upstream for '{}' and no {}/{}", branch_name, name, branch_name))?
+        }
+    };
+    let label = upstream.shorthand().unwrap_or("upstream").to_string();
+    let annotated = repo.reference_to_annotated_commit(&upstream)?;
+
+    merge_annotated(&repo, &annotated, &label)
+}
+
+/// Push the current branch; sets its upstream when it has none
+pub fn push(repo_path: &Path, remote: Option<&str>, force: bool, progress: ProgressHandle) -> Result<String> {
+    let repo = Repository::open(repo_path)?;
+    let branch_name = head_branch_name(&repo)?;
+    let name = resolve_remote_name(&repo, remote);
+    let refspec = format!(
+        "{}refs/heads/{}:refs/heads/{}",
+        if force { "+" } else { "" },
+        branch_name,
+        branch_name
+    );
+
+    push_refspec(&repo, &name, &refspec, progress)?;
+
+    let mut branch = repo.find_branch(&branch_name, BranchType::Local)?;
+    if branch.upstream().is_err() {
+        branch.set_upstream(Some(&format!("{}/{}", name, branch_name)))?;
+    }
+
+    Ok(format!("Pushed {} to {}", branch_name, name))
+}
+
+fn push_refspec(repo: &Repository, remote_name: &str, refspec: &str, progress: ProgressHandle) -> Result<()> {
+    let mut remote = repo
+        .find_remote(remote_name)
+        .with_context(|| format!("Remote '{}' not found", remote_name))?;
+
+    // The server reports rejected refs (e.g. non-fast-forward) per reference
+    let rejected: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let mut callbacks = remote_callbacks(repo.config()?, "push", progress.clone());
+    let rejected_ref = rejected.clone();
+    callbacks.push_update_reference(move |refname, status| {
+        if let Some(status) = status {
+            *rejected_ref.borrow_mut() = Some(format!("{} rejected: {}", refname, status));
+        }
+        Ok(())
+    });
+
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    remote.push(&[refspec], Some(&mut options))?;
+    finish_progress(&progress);
+
+    if let Some(message) = rejected.borrow_mut().take() {
+        return Err(anyhow!(message));
+    }
+    Ok(())
+}
+
+// ========== Merge / Rebase / Cherry-pick ==========
+
+/// Merge a branch (local or remote-tracking) into the current branch
+pub fn merge_branch(repo_path: &Path, branch_name: &str) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    let reference = repo
+        .resolve_reference_from_short_name(branch_name)
+        .with_context(|| format!("Branch '{}' not found", branch_name))?;
+    let annotated = repo.reference_to_annotated_commit(&reference)?;
+    merge_annotated(&repo, &annotated, branch_name)
+}
+
+fn merge_annotated(repo: &Repository, annotated: &AnnotatedCommit, label: &str) -> Result<OperationResult> {
+    let (analysis, _) = repo.merge_analysis(&[annotated])?;
+
+    if analysis.is_up_to_date() {
+        return Ok(OperationResult {
+            status: OperationStatus::UpToDate,
+            message: "Already up to date".to_string(),
+            commit: None,
+            conflicts: Vec::new(),
+        });
+    }
+
+    if analysis.is_fast_forward() {
+        let target = repo.find_commit(annotated.id())?;
+        repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))?;
+        let head = repo.head()?;
+        let refname = head.name().context("HEAD has no name")?.to_string();
+        repo.find_reference(&refname)?
+            .set_target(annotated.id(), &format!("merge {}: Fast-forward", label))?;
+
+        return Ok(OperationResult {
+            status: OperationStatus::FastForward,
+            message: format!("Fast-forwarded to {}", label),
+            commit: Some(annotated.id().to_string()),
+            conflicts: Vec::new(),
+        });
+    }
+
+    repo.merge(&[annotated], None, None)?;
+
+    let conflicts = conflict_paths(repo)?;
+    if !conflicts.is_empty() {
+        return Ok(OperationResult {
+            status: OperationStatus::Conflicts,
+            message: format!("Merge of {} has conflicts in {} file(s)", label, conflicts.len()),
+            commit: None,
+            conflicts,
+        });
+    }
+
+    // Clean merge: commit it right away
+    let signature = repo.signature()?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let their_commit = repo.find_commit(annotated.id())?;
+    let message = format!("Merge {}", label);
+    let commit_id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &[&head_commit, &their_commit],
+    )?;
+    repo.cleanup_state()?;
+
+    Ok(OperationResult {
+        status: OperationStatus::Completed,
+        message,
+        commit: Some(commit_id.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Rebase the current branch onto another branch
+pub fn rebase(repo_path: &Path, upstream: &str) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    let reference = repo
+        .resolve_reference_from_short_name(upstream)
+        .with_context(|| format!("Branch '{}' not found", upstream))?;
+    let annotated = repo.reference_to_annotated_commit(&reference)?;
+
+    let mut rebase = repo.rebase(None, Some(&annotated), None, None)?;
+    run_rebase(&repo, &mut rebase, upstream)
+}
+
+/// Continue a rebase stopped by conflicts (after they are resolved and staged)
+pub fn rebase_continue(repo_path: &Path) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    if !conflict_paths(&repo)?.is_empty() {
+        return Err(anyhow!("Resolve all conflicts before continuing"));
+    }
+
+    if let Some(mut state) = load_interactive_state(&repo)? {
+        if state.pending {
+            finish_pending_step(&repo, &mut state)?;
+        }
+        return run_interactive_rebase(&repo, &mut state);
+    }
+
+    let mut rebase = repo.open_rebase(None)?;
+    commit_rebase_step(&repo, &mut rebase)?;
+    run_rebase(&repo, &mut rebase, "upstream")
+}
+
+/// Drop the changes of the step a rebase stopped on and go on with the next one
+pub fn rebase_skip(repo_path: &Path) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    let head = repo.head()?.peel_to_commit()?;
+
+    if let Some(mut state) = load_interactive_state(&repo)? {
+        repo.reset(head.as_object(), ResetType::Hard, None)?;
+        repo.cleanup_state()?;
+        if state.pending {
+            state.pending = false;
+            state.next += 1;
+        }
+        return run_interactive_rebase(&repo, &mut state);
+    }
+
+    let mut rebase = repo
+        .open_rebase(None)
+        .map_err(|_| anyhow!("No rebase in progress"))?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    run_rebase(&repo, &mut rebase, "upstream")
+}
+
+fn commit_rebase_step(repo: &Repository, rebase: &mut Rebase) -> Result<()> {
+    match rebase.commit(None, &repo.signature()?, None) {
+        // The patch was already applied upstream; nothing to commit
+        Err(e) if e.code() == ErrorCode::Applied => Ok(()),
+        result => result.map(|_| ()).map_err(Into::into),
+    }
+}
+
+fn run_rebase(repo: &Repository, rebase: &mut Rebase, upstream: &str) -> Result<OperationResult> {
+    while let Some(operation) = rebase.next() {
+        operation?;
+
+        let conflicts = conflict_paths(repo)?;
+        if !conflicts.is_empty() {
+            return Ok(OperationResult {
+                status: OperationStatus::Conflicts,
+                message: format!("Rebase stopped with conflicts in {} file(s)", conflicts.len()),
+                commit: None,
+                conflicts,
+            });
+        }
+        commit_rebase_step(repo, rebase)?;
+    }
+
+    rebase.finish(Some(&repo.signature()?))?;
+    let head = repo.head()?.peel_to_commit()?;
+
+    Ok(OperationResult {
+        status: OperationStatus::Completed,
+        message: format!("Rebased onto {}", upstream),
+        commit: Some(head.id().to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Apply a commit onto the current branch, keeping its author and message
+pub fn cherry_pick(repo_path: &Path, commit_ref: &str) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    let commit = repo
+        .revparse_single(commit_ref)
+        .with_context(|| format!("Commit '{}' not found", commit_ref))?
+        .peel_to_commit()?;
+
+    repo.cherrypick(&commit, None)?;
+
+    let conflicts = conflict_paths(&repo)?;
+    if !conflicts.is_empty() {
+        return Ok(OperationResult {
+            status: OperationStatus::Conflicts,
+            message: format!("Cherry-pick has conflicts in {} file(s)", conflicts.len()),
+            commit: None,
+            conflicts,
+        });
+    }
+
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let parent = repo.head()?.peel_to_commit()?;
+    let commit_id = repo.commit(
+        Some("HEAD"),
+        &commit.author(),
+        &repo.signature()?,
+        commit.message().unwrap_or(""),
+        &tree,
+        &[&parent],
+    )?;
+    repo.cleanup_state()?;
+
+    Ok(OperationResult {
+        status: OperationStatus::Completed,
+        message: format!("Cherry-picked {}", commit.as_object().short_id()?.as_str().unwrap_or("")),
+        commit: Some(commit_id.to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Abort the merge, rebase, cherry-pick or revert in progress
+pub fn abort_operation(repo_path: &Path) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    if let Some(state) = load_interactive_state(&repo)? {
+        return abort_interactive_rebase(&repo, &state);
+    }
+
+    match repo.state() {
+        RepositoryState::Clean => Err(anyhow!("No operation in progress")),
+        RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge => {
+            repo.open_rebase(None)?.abort()?;
+            Ok(())
+        }
+        _ => {
+            let head = repo.head()?.peel_to_commit()?;
+            repo.reset(head.as_object(), ResetType::Hard, None)?;
+            repo.cleanup_state()?;
+            Ok(())
+        }
+    }
+}
+
+// ========== Interactive rebase ==========
+//
+// git2's rebase replays commits as they are, so the plan is run as a series
+// of cherry-picks onto a detached HEAD; the branch is moved at the end.
+
+fn interactive_state_path(repo: &Repository) -> std::path::PathBuf {
+    repo.path().join("berry-rebase.json")
+}
+
+fn load_interactive_state(repo: &Repository) -> Result<Option<InteractiveRebaseState>> {
+    let path = interactive_state_path(repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&content).context("Corrupt interactive rebase state")?))
+}
+
+fn save_interactive_state(repo: &Repository, state: &InteractiveRebaseState) -> Result<()> {
+    std::fs::write(interactive_state_path(repo), serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+fn clear_interactive_state(repo: &Repository) -> Result<()> {
+    let path = interactive_state_path(repo);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Commits after `base` on the current branch, oldest first (the default plan)
+pub fn rebase_commits(repo_path: &Path, base: &str) -> Result<Vec<CommitInfo>> {
+    let repo = Repository::open(repo_path)?;
+    let base_commit = repo
+        .revparse_single(base)
+        .with_context(|| format!("Revision '{}' not found", base))?
+        .peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.hide(base_commit.id())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    revwalk
+        .map(|oid| super::history::commit_info(&repo.find_commit(oid?)?))
+        .collect()
+}
+
+/// Interactive rebase in progress, if any
+pub fn interactive_rebase_state(repo_path: &Path) -> Result<Option<InteractiveRebaseState>> {
+    let repo = Repository::open(repo_path)?;
+    load_interactive_state(&repo)
+}
+
+/// Replay `steps` onto `onto`, stopping on conflicts
+pub fn interactive_rebase(repo_path: &Path, onto: &str, steps: Vec<RebaseStep>) -> Result<OperationResult> {
+    let repo = Repository::open(repo_path)?;
+    if repo.state() != RepositoryState::Clean || load_interactive_state(&repo)?.is_some() {
+        return Err(anyhow!("Another operation is in progress"));
+    }
+    if let Some(first) = steps.iter().find(|step| step.action != RebaseAction::Drop) {
+        if matches!(first.action, RebaseAction::Squash | RebaseAction::Fixup) {
+            return Err(anyhow!("The first commit cannot be squashed or fixed up"));
+        }
+    }
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    if !repo.statuses(Some(&mut opts))?.is_empty() {
+        return Err(anyhow!("Commit or stash your changes before rebasing"));
+    }
+
+    let onto_commit = repo
+        .revparse_single(onto)
+        .with_context(|| format!("Revision '{}' not found", onto))?
+        .peel_to_commit()?;
+    let head = repo.head()?;
+    let orig_head = head.peel_to_commit()?.id();
+    let head_name = if head.is_branch() { head.name().map(String::from) } else { None };
+
+    repo.checkout_tree(onto_commit.as_object(), None)?;
+    repo.set_head_detached(onto_commit.id())?;
+
+    let mut state = InteractiveRebaseState {
+        onto: onto_commit.id().to_string(),
+        orig_head: orig_head.to_string(),
+        head_name,
+        steps,
+        next: 0,
+        pending: false,
+    };
+    run_interactive_rebase(&repo, &mut state).or_else(|e| {
+        // Nothing is saved to continue from, so put the branch back
+        abort_interactive_rebase(&repo, &state)?;
+        Err(e)
+    })
+}
+
+fn run_interactive_rebase(repo: &Repository, state: &mut InteractiveRebaseState) -> Result<OperationResult> {
+    while let Some(step) = state.steps.get(state.next).cloned() {
+        if step.action == RebaseAction::Drop {
+            state.next += 1;
+            continue;
+        }
+
+        let commit = repo.find_commit(Oid::from_str(&step.commit)?)?;
+        let mut opts = CherrypickOptions::new();
+        if commit.parent_count() > 1 {
+            opts.mainline(1);
+        }
+        repo.cherrypick(&commit, Some(&mut opts))?;
+
+        let conflicts = conflict_paths(repo)?;
+        if !conflicts.is_empty() {
+            state.pending = true;
+            save_interactive_state(repo, state)?;
+            return Ok(OperationResult {
+                status: OperationStatus::Conflicts,
+                message: format!(
+                    "Stopped at {} ({} of {}) with conflicts in {} file(s)",
+                    commit.as_object().short_id()?.as_str().unwrap_or(""),
+                    state.next + 1,
+                    state.steps.len(),
+                    conflicts.len()
+                ),
+                commit: Some(commit.id().to_string()),
+                conflicts,
+            });
+        }
+
+        commit_interactive_step(repo, &step, &commit)?;
+        state.next += 1;
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    if let Some(name) = &state.head_name {
+        repo.reference(name, head.id(), true, "rebase (interactive): finish")?;
+        repo.set_head(name)?;
+    }
+    clear_interactive_state(repo)?;
+
+    let applied = state.steps.iter().filter(|s| s.action != RebaseAction::Drop).count();
+    Ok(OperationResult {
+        status: OperationStatus::Completed,
+        message: format!("Rebased {} commit(s)", applied),
+        commit: Some(head.id().to_string()),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Commit the step that stopped on conflicts, now that they are resolved
+fn finish_pending_step(repo: &Repository, state: &mut InteractiveRebaseState) -> Result<()> {
+    let step = state.steps[state.next].clone();
+    let commit = repo.find_commit(Oid::from_str(&step.commit)?)?;
+    commit_interactive_step(repo, &step, &commit)?;
+    state.pending = false;
+    state.next += 1;
+    Ok(())
+}
+
+/// Commit the index for one step: a new commit, or an amended HEAD for squash/fixup
+fn commit_interactive_step(repo: &Repository, step: &RebaseStep, original: &Commit) -> Result<()> {
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let head = repo.head()?.peel_to_commit()?;
+    let committer = repo.signature()?;
+    repo.cleanup_state()?;
+    let original_message = original.message().unwrap_or("");
+
+    match step.action {
+        RebaseAction::Squash | RebaseAction::Fixup => {
+            let head_message = head.message().unwrap_or("");
+            let message = match (step.action, &step.message) {
+                (RebaseAction::Squash, Some(message)) => message.clone(),
+                (RebaseAction::Squash, None) => {
+                    format!("{}\n\n{}", head_message.trim_end(), original_message)
+                }
+                _ => head_message.to_string(),
+            };
+            let parents: Vec<Commit> = head.parents().collect();
+            let parent_refs: Vec<&Commit> = parents.iter().collect();
+            let oid = repo.commit(None, &head.author(), &committer, &message, &tree, &parent_refs)?;
+            repo.set_head_detached(oid)?;
+        }
+        _ => {
+            // The change is already in HEAD; nothing to commit
+            if tree.id() == head.tree_id() {
+                return Ok(());
+            }
+            let message = match (step.action, &step.message) {
+                (RebaseAction::Reword, Some(message)) => message.as_str(),
+                _ => original_message,
+            };
+            repo.commit(Some("HEAD"), &original.author(), &committer, message, &tree, &[&head])?;
+        }
+    }
+    Ok(())
+}
+
+fn abort_interactive_rebase(repo: &Repository, state: &InteractiveRebaseState) -> Result<()> {
+    let orig_head = repo.find_commit(Oid::from_str(&state.orig_head)?)?;
+    match &state.head_name {
+        Some(name) => repo.set_head(name)?,
+        None => repo.set_head_detached(orig_head.id())?,
+    }
+    repo.reset(orig_head.as_object(), ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+    clear_interactive_state(repo)
+}
+
+// ========== Stash ==========
+
+/// Stash local changes; returns the stash commit
+pub fn stash_save(repo_path: &Path, message: Option<&str>, include_untracked: bool) -> Result<String> {
+    let mut repo = Repository::open(repo_path)?;
+    let signature = repo.signature()?;
+    let flags = if include_untracked {
+        Some(git2::StashFlags::INCLUDE_UNTRACKED)
+    } else {
+        None
+    };
+    let oid = repo.stash_save2(&signature, message, flags)?;
+    Ok(oid.to_string())
+}
+
+pub fn stash_list(repo_path: &Path) -> Result<Vec<StashEntry>> {
+    let mut repo = Repository::open(repo_path)?;
+    let mut result = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        result.push(StashEntry {
+            index,
+            message: message.to_string(),
+            commit: oid.to_string(),
+        });
+        true
+    })?;
+    Ok(result)
+}
+
+pub fn stash_apply(repo_path: &Path, index: usize) -> Result<()> {
+    let mut repo = Repository::open(repo_path)?;
+    repo.stash_apply(index, None)?;
+    Ok(())
+}
+
+/// Apply a stash and drop it if it applied cleanly
+pub fn stash_pop(repo_path: &Path, index: usize) -> Result<()> {
+    let mut repo = Repository::open(repo_path)?;
+    repo.stash_pop(index, None)?;
+    Ok(())
+}
+
+pub fn stash_drop(repo_path: &Path, index: usize) -> Result<()> {
+    let mut repo = Repository::open(repo_path)?;
+    repo.stash_drop(index)?;
+    Ok(())
+}
+
+// ========== Tags ==========
+
+pub fn list_tags(repo_path: &Path) -> Result<Vec<TagInfo>> {
+    let repo = Repository::open(repo_path)?;
+    let mut result = Vec::new();
+
+    repo.tag_foreach(|oid, name| {
+        let name = String::from_utf8_lossy(name).trim_start_matches("refs/tags/").to_string();
+        let (target, message) = match repo.find_tag(oid) {
+            Ok(tag) => (tag.target_id().to_string(), tag.message().map(|m| m.trim().to_string())),
+            Err(_) => (oid.to_string(), None),
+        };
+        result.push(TagInfo { name, target, message });
+        true
+    })?;
+
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(result)
+}
+
+/// Create a tag at `target` (default HEAD); annotated when a message is given
+pub fn create_tag(repo_path: &Path, name: &str, target: Option<&str>, message: Option<&str>) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let object = repo.revparse_single(target.unwrap_or("HEAD"))?;
+
+    match message {
+        Some(message) => {
+            repo.tag(name, &object, &repo.signature()?, message, false)?;
+        }
+        None => {
+            repo.tag_lightweight(name, &object, false)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn delete_tag(repo_path: &Path, name: &str) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    repo.tag_delete(name)?;
+    Ok(())
+}
+
+pub fn push_tag(repo_path: &Path, name: &str, remote: Option<&str>, progress: ProgressHandle) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let remote_name = resolve_remote_name(&repo, remote);
+    push_refspec(&repo, &remote_name, &format!("refs/tags/{}:refs/tags/{}", name, name), progress)
+}
+
+// ========== Conflicts ==========
+
+fn conflict_paths(repo: &Repository) -> Result<Vec<String>> {
+    Ok(list_repo_conflicts(repo)?.into_iter().map(|c| c.path).collect())
+}
+
+fn list_repo_conflicts(repo: &Repository) -> Result<Vec<ConflictFile>> {
+    let index = repo.index()?;
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+
+    let mut result = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let Some(entry) = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref()) else {
+            continue;
+        };
+        let kind = match (conflict.ancestor.is_some(), conflict.our.is_some(), conflict.their.is_some()) {
+            (true, true, true) => "both-modified",
+            (false, true, true) => "both-added",
+            (_, false, true) => "deleted-by-us",
+            (_, true, false) => "deleted-by-them",
+            _ => "both-deleted",
+        };
+        result.push(ConflictFile {
+            path: String::from_utf8_lossy(&entry.path).to_string(),
+            kind: kind.to_string(),
+        });
+    }
+    Ok(result)
+}
+
+/// Operation in progress and its unresolved conflicts
+pub fn repo_state(repo_path: &Path) -> Result<RepoStateInfo> {
+    let repo = Repository::open(repo_path)?;
+    let state = match repo.state() {
+        // Interactive rebase steps are cherry-picks under the hood
+        _ if interactive_state_path(&repo).exists() => "rebase",
+        RepositoryState::Clean => "clean",
+        RepositoryState::Merge => "merge",
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailboxOrRebase => "rebase",
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => "cherry-pick",
+        RepositoryState::Revert | RepositoryState::RevertSequence => "revert",
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::ApplyMailbox => "apply-mailbox",
+    };
+
+    Ok(RepoStateInfo {
+        state: state.to_string(),
+        conflicts: list_repo_conflicts(&repo)?,
+    })
+}
+
+/// Write the resolved content of a conflicted file and mark it resolved
+pub fn resolve_conflict(repo_path: &Path, file_path: &str, content: &str) -> Result<()> {
+    std::fs::write(repo_path.join(file_path), content)?;
+    let repo = Repository::open(repo_path)?;
+    let mut index = repo.index()?;
+    index.add_path(Path::new(file_path))?;
+    index.write()?;
+    Ok(())
+}
+
+/// Resolve a conflicted file by taking one side (or both, ours first)
+pub fn accept_conflict_side(repo_path: &Path, file_path: &str, side: ConflictSide) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let mut index = repo.index()?;
+
+    let conflict = index
+        .conflicts()?
+        .filter_map(|c| c.ok())
+        .find(|c| {
+            [&c.our, &c.their, &c.ancestor]
+                .iter()
+                .any(|e| e.as_ref().is_some_and(|e| e.path == file_path.as_bytes()))
+        })
+        .ok_or_else(|| anyhow!("{} has no conflicts", file_path))?;
+
+    let full_path = repo_path.join(file_path);
+    let entry = match side {
+        ConflictSide::Ours => conflict.our,
+        ConflictSide::Theirs => conflict.their,
+        ConflictSide::Both => {
+            let content = std::fs::read_to_string(&full_path)?;
+            std::fs::write(&full_path, resolve_conflict_markers(&content, ConflictSide::Both))?;
+            index.add_path(Path::new(file_path))?;
+            index.write()?;
+            return Ok(());
+        }
+    };
+
+    match entry {
+        Some(entry) => {
+            let blob = repo.find_blob(entry.id)?;
+            std::fs::write(&full_path, blob.content())?;
+            index.add_path(Path::new(file_path))?;
+        }
+        // The chosen side deleted the file
+        None => {
+            if full_path.exists() {
+                std::fs::remove_file(&full_path)?;
+            }
+            index.remove_path(Path::new(file_path))?;
+        }
+    }
+    index.write()?;
+    Ok(())
+}
+
+/// Base, ours and theirs versions of a conflicted file, read from the index conflict stages
+pub fn three_way_merge(repo_path: &Path, file_path: &str) -> Result<ThreeWayMerge> {
+    let repo = Repository::open(repo_path)?;
+    let index = repo.index()?;
+
+    let conflict = index
+        .conflicts()?
+        .filter_map(|c| c.ok())
+        .find(|c| {
+            [&c.our, &c.their, &c.ancestor]
+                .iter()
+                .any(|e| e.as_ref().is_some_and(|e| e.path == file_path.as_bytes()))
+        })
+        .ok_or_else(|| anyhow!("{} has no conflicts", file_path))?;
+
+    let blob_text = |entry: Option<git2::IndexEntry>| -> Result<Option<String>> {
+        match entry {
+            Some(entry) => {
+                let blob = repo.find_blob(entry.id)?;
+                Ok(Some(String::from_utf8_lossy(blob.content()).to_string()))
+            }
+            None => Ok(None),
+        }
+    };
+
+    Ok(ThreeWayMerge {
+        file_path: file_path.to_string(),
+        base: blob_text(conflict.ancestor)?,
+        ours: blob_text(conflict.our)?,
+        theirs: blob_text(conflict.their)?,
+        result: std::fs::read_to_string(repo_path.join(file_path)).unwrap_or_default(),
+    })
+}
+
+/// Replace conflict marker blocks by the chosen side (diff3 base sections are dropped)
+pub fn resolve_conflict_markers(content: &str, side: ConflictSide) -> String {
+    #[derive(PartialEq)]
+    enum Section {
+        Outside,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut result = String::with_capacity(content.len());
+    let mut section = Section::Outside;
+
+    for line in content.split_inclusive('\n') {
+        if line.starts_with("<<<<<<<") {
+            section = Section::Ours;
+            continue;
+        }
+        if section != Section::Outside {
+            if line.starts_with("|||||||") {
+                section = Section::Base;
+                continue;
+            }
+            if line.starts_with("=======") {
+                section = Section::Theirs;
+                continue;
+            }
+            if line.starts_with(">>>>>>>") {
+                section = Section::Outside;
+                continue;
+            }
+        }
+
+        let keep = match section {
+            Section::Outside => true,
+            Section::Ours => side != ConflictSide::Theirs,
+            Section::Base => false,
+            Section::Theirs => side != ConflictSide::Ours,
+        };
+        if keep {
+            result.push_str(line);
+        }
+    }
+
+    result
+}
+
+// ========== Bisect ==========
+//
+// libgit2 has no bisect support; these drive the git CLI.
+
+fn run_git(repo_path: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Remaining steps and first bad commit from `git bisect` output
+pub fn parse_bisect_output(output: &str) -> (Option<u32>, Option<String>) {
+    let mut steps = None;
+    let mut first_bad = None;
+
+    for line in output.lines() {
+        // "Bisecting: 5 revisions left to test after this (roughly 3 steps)"
+        if let Some(rest) = line.split("(roughly ").nth(1) {
+            steps = rest.split_whitespace().next().and_then(|n| n.parse().ok());
+        }
+        if let Some(sha) = line.strip_suffix(" is the first bad commit") {
+            first_bad = Some(sha.trim().to_string());
+        }
+    }
+
+    (steps, first_bad)
+}
+
+fn bisect_status_from(repo_path: &Path, output: &str) -> Result<BisectStatus> {
+    let (steps, first_bad) = parse_bisect_output(output);
+    let repo = Repository::open(repo_path)?;
+    let active = repo.path().join("BISECT_LOG").exists();
+
+    Ok(BisectStatus {
+        active,
+        current: if active { repo.head()?.target().map(|oid| oid.to_string()) } else { None },
+        steps,
+        first_bad,
+        log: if active { run_git(repo_path, &["bisect", "log"]).unwrap_or_default() } else { String::new() },
+    })
+}
+
+/// Start bisecting between a bad and a good revision
+pub fn bisect_start(repo_path: &Path, bad: &str, good: &str) -> Result<BisectStatus> {
+    let output = run_git(repo_path, &["bisect", "start", bad, good])?;
+    bisect_status_from(repo_path, &output)
+}
+
+/// Mark the current commit "good", "bad" or "skip"
+pub fn bisect_mark(repo_path: &Path, mark: &str) -> Result<BisectStatus> {
+    if !["good", "bad", "skip"].contains(&mark) {
+        return Err(anyhow!("Invalid bisect mark: {}", mark));
+    }
+    let output = run_git(repo_path, &["bisect", mark])?;
+    bisect_status_from(repo_path, &output)
+}
+
+pub fn bisect_reset(repo_path: &Path) -> Result<()> {
+    run_git(repo_path, &["bisect", "reset"])?;
+    Ok(())
+}
+
+pub fn bisect_status(repo_path: &Path) -> Result<BisectStatus> {
+    bisect_status_from(repo_path, "")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,22 +1640,28 @@ mod tests {
         let temp_dir = TempDir::new()?;
         let repo = Repository::init(temp_dir.path())?;
 
+        // Don't depend on a global identity
+        let mut config = repo.config()?;
+        config.set_str("user.name", "Test")?;
+        config.set_str("user.email", "test@example.com")?;
+
         // Create initial commit
         let signature = repo.signature()?;
         let tree_id = {
             let mut index = repo.index()?;
             index.write_tree()?
         };
-        let tree = repo.find_tree(tree_id)?;
-
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "Initial commit",
-            &tree,
-            &[],
-        )?;
+        {
+            let tree = repo.find_tree(tree_id)?;
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Initial commit",
+                &tree,
+                &[],
+            )?;
+        }
 
         Ok((temp_dir, repo))
     }
@@ -386,4 +1687,410 @@ mod tests {
         assert!(!branches.is_empty());
         assert!(branches.iter().any(|b| b.is_head));
     }
+
+    fn commit_file(repo: &Repository, name: &str, content: impl AsRef<[u8]>, message: &str) -> git2::Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::write(workdir.join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &[&parent])
+            .unwrap()
+    }
+
+    fn checkout(repo: &Repository, branch: &str) {
+        let refname = format!("refs/heads/{}", branch);
+        let object = repo.revparse_single(&refname).unwrap();
+        repo.checkout_tree(&object, Some(CheckoutBuilder::new().force())).unwrap();
+        repo.set_head(&refname).unwrap();
+    }
+
+    /// Repo where `feature` and the default branch both changed `file.txt`
+    fn create_conflicting_branches() -> (TempDir, Repository, String) {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        commit_file(&repo, "file.txt", "base\n", "base");
+        let main = current_branch(temp_dir.path()).unwrap();
+
+        {
+            let head = repo.head().unwrap().peel_to_commit().unwrap();
+            repo.branch("feature", &head, false).unwrap();
+        }
+        commit_file(&repo, "file.txt", "ours\n", "ours");
+
+        checkout(&repo, "feature");
+        commit_file(&repo, "file.txt", "theirs\n", "theirs");
+        checkout(&repo, &main);
+
+        (temp_dir, repo, main)
+    }
+
+    #[test]
+    fn test_resolve_conflict_markers() {
+        let content = "a\n<<<<<<< HEAD\nours\n||||||| base\nbase\n=======\ntheirs\n>>>>>>> feature\nb\n";
+        assert_eq!(resolve_conflict_markers(content, ConflictSide::Ours), "a\nours\nb\n");
+        assert_eq!(resolve_conflict_markers(content, ConflictSide::Theirs), "a\ntheirs\nb\n");
+        assert_eq!(resolve_conflict_markers(content, ConflictSide::Both), "a\nours\ntheirs\nb\n");
+    }
+
+    #[test]
+    fn test_parse_bisect_output() {
+        let output = "Bisecting: 5 revisions left to test after this (roughly 3 steps)\n[abc123] msg\n";
+        assert_eq!(parse_bisect_output(output), (Some(3), None));
+
+        let done = "abc123def is the first bad commit\ncommit abc123def\n";
+        assert_eq!(parse_bisect_output(done), (None, Some("abc123def".to_string())));
+    }
+
+    #[test]
+    fn test_merge_fast_forward() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        let main = current_branch(temp_dir.path()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        checkout(&repo, "feature");
+        let tip = commit_file(&repo, "new.txt", "new\n", "add new");
+        checkout(&repo, &main);
+
+        let result = merge_branch(temp_dir.path(), "feature").unwrap();
+        assert_eq!(result.status, OperationStatus::FastForward);
+        assert_eq!(repo.head().unwrap().target(), Some(tip));
+        assert!(temp_dir.path().join("new.txt").exists());
+
+        let again = merge_branch(temp_dir.path(), "feature").unwrap();
+        assert_eq!(again.status, OperationStatus::UpToDate);
+    }
+
+    #[test]
+    fn test_merge_conflict_accept_side_and_commit() {
+        let (temp_dir, repo, _main) = create_conflicting_branches();
+
+        let result = merge_branch(temp_dir.path(), "feature").unwrap();
+        assert_eq!(result.status, OperationStatus::Conflicts);
+        assert_eq!(result.conflicts, vec!["file.txt".to_string()]);
+
+        let state = repo_state(temp_dir.path()).unwrap();
+        assert_eq!(state.state, "merge");
+        assert_eq!(state.conflicts[0].kind, "both-modified");
+
+        let merge = three_way_merge(temp_dir.path(), "file.txt").unwrap();
+        assert_eq!(merge.base.as_deref(), Some("base\n"));
+        assert_eq!(merge.ours.as_deref(), Some("ours\n"));
+        assert_eq!(merge.theirs.as_deref(), Some("theirs\n"));
+        assert!(merge.result.contains("<<<<<<<"));
+
+        accept_conflict_side(temp_dir.path(), "file.txt", ConflictSide::Theirs).unwrap();
+        assert_eq!(fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(), "theirs\n");
+        assert!(repo_state(temp_dir.path()).unwrap().conflicts.is_empty());
+
+        commit(temp_dir.path(), "Merge feature").unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert_eq!(repo.state(), RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_abort_merge() {
+        let (temp_dir, repo, _main) = create_conflicting_branches();
+        merge_branch(temp_dir.path(), "feature").unwrap();
+
+        abort_operation(temp_dir.path()).unwrap();
+        assert_eq!(repo.state(), RepositoryState::Clean);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(), "ours\n");
+    }
+
+    #[test]
+    fn test_rebase_and_cherry_pick() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        commit_file(&repo, "a.txt", "a\n", "a");
+        let main = current_branch(temp_dir.path()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &head, false).unwrap();
+        commit_file(&repo, "b.txt", "b\n", "b");
+
+        checkout(&repo, "feature");
+        let picked = commit_file(&repo, "c.txt", "c\n", "c");
+
+        let result = rebase(temp_dir.path(), &main).unwrap();
+        assert_eq!(result.status, OperationStatus::Completed);
+        assert!(temp_dir.path().join("b.txt").exists());
+        assert!(temp_dir.path().join("c.txt").exists());
+
+        checkout(&repo, &main);
+        let result = cherry_pick(temp_dir.path(), &picked.to_string()).unwrap();
+        assert_eq!(result.status, OperationStatus::Completed);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("c"));
+    }
+
+    fn step(commit: git2::Oid, action: RebaseAction, message: Option<&str>) -> RebaseStep {
+        RebaseStep {
+            commit: commit.to_string(),
+            action,
+            message: message.map(String::from),
+        }
+    }
+
+    fn head_messages(repo: &Repository, count: usize) -> Vec<String> {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk
+            .take(count)
+            .map(|oid| repo.find_commit(oid.unwrap()).unwrap().message().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_interactive_rebase_plan() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        let base = commit_file(&repo, "base.txt", "base\n", "base");
+        let c1 = commit_file(&repo, "a.txt", "a\n", "a");
+        let c2 = commit_file(&repo, "b.txt", "b\n", "b");
+        let c3 = commit_file(&repo, "c.txt", "c\n", "c");
+        let c4 = commit_file(&repo, "a.txt", "a fixed\n", "fix a");
+        let branch = current_branch(temp_dir.path()).unwrap();
+
+        let commits = rebase_commits(temp_dir.path(), &base.to_string()).unwrap();
+        let hashes: Vec<String> = commits.iter().map(|c| c.hash.clone()).collect();
+        assert_eq!(hashes, vec![c1.to_string(), c2.to_string(), c3.to_string(), c4.to_string()]);
+
+        let plan = vec![
+            step(c2, RebaseAction::Reword, Some("b, reworded")),
+            step(c1, RebaseAction::Pick, None),
+            step(c4, RebaseAction::Fixup, None),
+            step(c3, RebaseAction::Drop, None),
+        ];
+        let result = interactive_rebase(temp_dir.path(), &base.to_string(), plan).unwrap();
+        assert_eq!(result.status, OperationStatus::Completed);
+
+        assert_eq!(head_messages(&repo, 3), vec!["a", "b, reworded", "base"]);
+        assert_eq!(current_branch(temp_dir.path()).unwrap(), branch);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(), "a fixed\n");
+        assert!(!temp_dir.path().join("c.txt").exists());
+        assert!(interactive_rebase_state(temp_dir.path()).unwrap().is_none());
+
+        // Squash needs a commit to meld into
+        let plan = vec![step(c1, RebaseAction::Squash, None)];
+        assert!(interactive_rebase(temp_dir.path(), &base.to_string(), plan).is_err());
+    }
+
+    #[test]
+    fn test_interactive_rebase_conflicts() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        let base = commit_file(&repo, "file.txt", "base\n", "base");
+        let c1 = commit_file(&repo, "file.txt", "one\n", "one");
+        let c2 = commit_file(&repo, "file.txt", "two\n", "two");
+        let orig_head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let plan = || vec![step(c1, RebaseAction::Drop, None), step(c2, RebaseAction::Pick, None)];
+
+        // Abort restores the branch
+        let result = interactive_rebase(temp_dir.path(), &base.to_string(), plan()).unwrap();
+        assert_eq!(result.status, OperationStatus::Conflicts);
+        assert_eq!(repo_state(temp_dir.path()).unwrap().state, "rebase");
+        abort_operation(temp_dir.path()).unwrap();
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().id(), orig_head);
+        assert!(repo.head().unwrap().is_branch());
+        assert_eq!(repo_state(temp_dir.path()).unwrap().state, "clean");
+
+        // Skip drops the conflicting commit
+        interactive_rebase(temp_dir.path(), &base.to_string(), plan()).unwrap();
+        let result = rebase_skip(temp_dir.path()).unwrap();
+        assert_eq!(result.status, OperationStatus::Completed);
+        assert_eq!(head_messages(&repo, 1), vec!["base"]);
+
+        // Continue after resolving
+        repo.reset(repo.find_commit(orig_head).unwrap().as_object(), ResetType::Hard, None).unwrap();
+        interactive_rebase(temp_dir.path(), &base.to_string(), plan()).unwrap();
+        assert!(rebase_continue(temp_dir.path()).is_err());
+        resolve_conflict(temp_dir.path(), "file.txt", "two\n").unwrap();
+        let result = rebase_continue(temp_dir.path()).unwrap();
+        assert_eq!(result.status, OperationStatus::Completed);
+        assert_eq!(head_messages(&repo, 2), vec!["two", "base"]);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(), "two\n");
+    }
+
+    #[test]
+    fn test_interactive_rebase_failed_step_restores_branch() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        let base = commit_file(&repo, "base.txt", "base\n", "base");
+        let c1 = commit_file(&repo, "a.txt", "a\n", "a");
+        let orig_head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let branch = current_branch(temp_dir.path()).unwrap();
+
+        let bogus = Oid::from_bytes(&[0xab; 20]).unwrap();
+        let plan = vec![step(c1, RebaseAction::Pick, None), step(bogus, RebaseAction::Pick, None)];
+        assert!(interactive_rebase(temp_dir.path(), &base.to_string(), plan).is_err());
+
+        assert!(repo.head().unwrap().is_branch());
+        assert_eq!(current_branch(temp_dir.path()).unwrap(), branch);
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().id(), orig_head);
+        assert!(interactive_rebase_state(temp_dir.path()).unwrap().is_none());
+        assert_eq!(repo_state(temp_dir.path()).unwrap().state, "clean");
+    }
+
+    #[test]
+    fn test_stash_round_trip() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        commit_file(&repo, "file.txt", "one\n", "one");
+        fs::write(temp_dir.path().join("file.txt"), "two\n").unwrap();
+
+        stash_save(temp_dir.path(), Some("wip"), false).unwrap();
+        assert_eq!(fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(), "one\n");
+
+        let stashes = stash_list(temp_dir.path()).unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert!(stashes[0].message.contains("wip"));
+
+        stash_pop(temp_dir.path(), 0).unwrap();
+        assert_eq!(fs::read_to_string(temp_dir.path().join("file.txt")).unwrap(), "two\n");
+        assert!(stash_list(temp_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tags() {
+        let (temp_dir, _repo) = create_test_repo().unwrap();
+        create_tag(temp_dir.path(), "v1", None, None).unwrap();
+        create_tag(temp_dir.path(), "v2", None, Some("Release 2")).unwrap();
+
+        let tags = list_tags(temp_dir.path()).unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "v1");
+        assert_eq!(tags[1].message.as_deref(), Some("Release 2"));
+        assert_eq!(tags[0].target, tags[1].target);
+
+        delete_tag(temp_dir.path(), "v1").unwrap();
+        assert_eq!(list_tags(temp_dir.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_push_and_pull_with_local_remote() {
+        let remote_dir = TempDir::new().unwrap();
+        Repository::init_bare(remote_dir.path()).unwrap();
+        let remote_url = remote_dir.path().to_str().unwrap();
+
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        repo.remote("origin", remote_url).unwrap();
+        commit_file(&repo, "file.txt", "one\n", "one");
+        let branch = current_branch(temp_dir.path()).unwrap();
+
+        let progress = ProgressHandle::default();
+        push(temp_dir.path(), None, false, progress.clone()).unwrap();
+        assert_eq!(progress.lock().unwrap().as_ref().unwrap().stage, "done");
+
+        let remotes = list_remotes(temp_dir.path()).unwrap();
+        assert_eq!(remotes[0].name, "origin");
+
+        // A second clone pushes a new commit; the first one pulls it
+        let other_dir = TempDir::new().unwrap();
+        let other = Repository::clone(remote_url, other_dir.path()).unwrap();
+        let mut config = other.config().unwrap();
+        config.set_str("user.name", "Other").unwrap();
+        config.set_str("user.email", "other@example.com").unwrap();
+        let tip = commit_file(&other, "file.txt", "two\n", "two");
+        push(other_dir.path(), None, false, ProgressHandle::default()).unwrap();
+
+        let result = pull(temp_dir.path(), None, ProgressHandle::default()).unwrap();
+        assert_eq!(result.status, OperationStatus::FastForward);
+        assert_eq!(repo.head().unwrap().target(), Some(tip));
+        assert_eq!(current_branch(temp_dir.path()).unwrap(), branch);
+    }
+
+    #[test]
+    fn test_apply_hunk_lines() {
+        let old = b"a\nb\nc\n";
+        let new = b"a\nB\nc\nd\n";
+        let hunks = buffer_hunks(old, new, "f.txt").unwrap();
+
+        assert_eq!(apply_hunk_lines(old, new, &hunks, |_, _| true), new);
+        assert_eq!(apply_hunk_lines(old, new, &hunks, |_, _| false), old);
+
+        // Only the added last line
+        let add_index = hunks[0].lines.iter().position(|l| l.content == "d\n").unwrap();
+        assert_eq!(apply_hunk_lines(old, new, &hunks, |_, l| l == add_index), b"a\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn test_hunks_keep_non_utf8_bytes() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        // Latin-1 "café" and "naïve"
+        let original = b"caf\xe9\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\nline 8\nna\xefve\n".to_vec();
+        commit_file(&repo, "latin1.txt", &original, "add latin1");
+
+        let mut changed = original.clone();
+        changed.splice(0..0, b"\xa1hola!\n".iter().copied());
+        changed.extend_from_slice(b"fin\xe9\n");
+        fs::write(temp_dir.path().join("latin1.txt"), &changed).unwrap();
+        assert_eq!(file_hunks(temp_dir.path(), "latin1.txt", false).unwrap().len(), 2);
+        let staged = || index_content(&Repository::open(temp_dir.path()).unwrap(), "latin1.txt").unwrap();
+
+        let first = [HunkSelection { hunk_index: 0, lines: None }];
+        stage_hunks(temp_dir.path(), "latin1.txt", &first).unwrap();
+        let mut expected = b"\xa1hola!\n".to_vec();
+        expected.extend_from_slice(&original);
+        assert_eq!(staged(), Some(expected.clone()));
+
+        // The remaining work tree hunk is the last line
+        discard_hunks(temp_dir.path(), "latin1.txt", &first).unwrap();
+        assert_eq!(fs::read(temp_dir.path().join("latin1.txt")).unwrap(), expected);
+
+        unstage_hunks(temp_dir.path(), "latin1.txt", &first).unwrap();
+        assert_eq!(staged(), Some(original));
+    }
+
+    #[test]
+    fn test_stage_unstage_and_discard_hunks() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        let original: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        commit_file(&repo, "file.txt", &original, "add file");
+
+        let changed = original.replace("line 2\n", "line two\n").replace("line 19\n", "line nineteen\n");
+        fs::write(temp_dir.path().join("file.txt"), &changed).unwrap();
+
+        let hunks = file_hunks(temp_dir.path(), "file.txt", false).unwrap();
+        assert_eq!(hunks.len(), 2);
+
+        // Stage only the first hunk
+        let first = HunkSelection { hunk_index: 0, lines: None };
+        stage_hunks(temp_dir.path(), "file.txt", &[first.clone()]).unwrap();
+        let staged = file_hunks(temp_dir.path(), "file.txt", true).unwrap();
+        assert_eq!(staged.len(), 1);
+        assert!(staged[0].lines.iter().any(|l| l.content == "line two\n"));
+        assert_eq!(file_hunks(temp_dir.path(), "file.txt", false).unwrap().len(), 1);
+
+        // Unstage it again
+        unstage_hunks(temp_dir.path(), "file.txt", &[first.clone()]).unwrap();
+        assert!(file_hunks(temp_dir.path(), "file.txt", true).unwrap().is_empty());
+
+        // Discard the second hunk from the work tree, keeping the first
+        let second = HunkSelection { hunk_index: 1, lines: None };
+        discard_hunks(temp_dir.path(), "file.txt", &[second]).unwrap();
+        let content = fs::read_to_string(temp_dir.path().join("file.txt")).unwrap();
+        assert!(content.contains("line two\n"));
+        assert!(content.contains("line 19\n"));
+    }
+
+    #[test]
+    fn test_root_status() {
+        let (temp_dir, repo) = create_test_repo().unwrap();
+        fs::create_dir_all(temp_dir.path().join("app")).unwrap();
+        commit_file(&repo, "app/main.rs", "fn main() {}\n", "add app");
+        fs::write(temp_dir.path().join("app/main.rs"), "changed").unwrap();
+        fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("docs/new.md"), "").unwrap();
+
+        let status = root_status(temp_dir.path()).unwrap();
+        assert_eq!(status.changes, 2);
+        assert!(status.branch.is_some());
+
+        // A folder inside the repository only counts its own changes
+        let app = root_status(&temp_dir.path().join("app")).unwrap();
+        assert_eq!(app.changes, 1);
+        assert_eq!(app.repo_path, status.repo_path);
+
+        let plain = TempDir::new().unwrap();
+        let status = root_status(plain.path()).unwrap();
+        assert_eq!((status.repo_path, status.changes), (None, 0));
+    }
 }
@@ -9,6 +9,16 @@ pub struct FileStatus {
     pub is_staged: bool,
 }
 
+/// Git summary of one folder of a multi-root workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootStatus {
+    pub root: String,
+    /// Work tree of the repository containing the folder
+    pub repo_path: Option<String>,
+    pub branch: Option<String>,
+    pub changes: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchInfo {
     pub name: String,
@@ -85,6 +95,242 @@ pub struct DiffStats {
     pub deletions: usize,
 }
 
+/// Which commits to list in the history view
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogQuery {
+    /// Where the previous page stopped; `None` starts from the tips
+    pub cursor: Option<LogCursor>,
+    /// Page size (0 = 100)
+    pub limit: usize,
+    /// Substring of the author name or email (case-insensitive)
+    pub author: Option<String>,
+    /// Only commits touching this file or directory
+    pub path: Option<String>,
+    /// Substring of the commit message (case-insensitive)
+    pub message: Option<String>,
+    /// Include all local and remote branches, not just HEAD
+    pub all_branches: bool,
+}
+
+/// Where a paged history walk resumes.
+///
+/// Every commit not listed yet is reachable from `pending`, and none that was
+/// listed is, so the next page walks only the remaining commits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogCursor {
+    /// Commits reached but not walked yet
+    pub pending: Vec<String>,
+    /// Commit each graph lane is waiting for
+    pub lanes: Vec<Option<String>>,
+    /// Path of the followed file after the renames seen so far (file history)
+    pub path: Option<String>,
+}
+
+/// One page of a history walk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage<T> {
+    pub items: Vec<T>,
+    /// Where the next page starts; `None` once history is exhausted
+    pub cursor: Option<LogCursor>,
+}
+
+/// A segment of the commit graph inside one row, between lane columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// One row of the commit graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphRow {
+    pub commit: CommitInfo,
+    /// Column of the commit's node
+    pub lane: usize,
+    /// Segments from the top of the row to the node's height
+    pub incoming: Vec<GraphEdge>,
+    /// Segments from the node's height to the bottom of the row
+    pub outgoing: Vec<GraphEdge>,
+    /// Branch and tag names pointing at the commit
+    pub refs: Vec<String>,
+}
+
+/// Differences between two refs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefComparison {
+    pub base: String,
+    pub target: String,
+    /// Commits in target that are not in base
+    pub ahead: Vec<CommitInfo>,
+    /// Number of commits in base that are not in target
+    pub behind: usize,
+    pub files: Vec<FileDiff>,
+    pub stats: DiffStats,
+}
+
+/// A commit in the history of one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    /// Path of the file in this commit
+    pub path: String,
+    /// Path before a rename in this commit
+    pub old_path: Option<String>,
+    /// "A", "M", "D" or "R"
+    pub status: String,
+}
+
+/// Progress of a network operation (fetch, pull, push)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitProgress {
+    /// "fetch", "pull" or "push"
+    pub operation: String,
+    /// "connecting", "receiving", "resolving", "pushing" or "done"
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+    pub bytes: usize,
+    /// Last message from the remote ("Counting objects: ...")
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperationStatus {
+    UpToDate,
+    FastForward,
+    Completed,
+    /// Stopped with conflicts; resolve them, then commit or continue
+    Conflicts,
+}
+
+/// Result of merge, pull, rebase and cherry-pick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationResult {
+    pub status: OperationStatus,
+    pub message: String,
+    /// Commit created by the operation
+    pub commit: Option<String>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub commit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagInfo {
+    pub name: String,
+    /// Commit the tag points to
+    pub target: String,
+    /// Annotation message (annotated tags only)
+    pub message: Option<String>,
+}
+
+/// A file with unresolved conflicts in the index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictFile {
+    pub path: String,
+    /// "both-modified", "both-added", "deleted-by-us" or "deleted-by-them"
+    pub kind: String,
+}
+
+/// Which side to keep when resolving a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// Operation in progress in the repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoStateInfo {
+    /// "clean", "merge", "rebase", "cherry-pick", "revert" or "bisect"
+    pub state: String,
+    pub conflicts: Vec<ConflictFile>,
+}
+
+/// What to do with a commit in an interactive rebase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RebaseAction {
+    Pick,
+    /// Pick with a new message
+    Reword,
+    /// Meld into the previous commit, combining messages
+    Squash,
+    /// Meld into the previous commit, keeping its message
+    Fixup,
+    Drop,
+}
+
+/// One line of an interactive rebase plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebaseStep {
+    pub commit: String,
+    pub action: RebaseAction,
+    /// New message for reword and squash steps
+    pub message: Option<String>,
+}
+
+/// Progress of an interactive rebase (kept in the git directory between steps)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractiveRebaseState {
+    /// Commit the plan is replayed onto
+    pub onto: String,
+    pub orig_head: String,
+    /// Branch to update when done (None when started on a detached HEAD)
+    pub head_name: Option<String>,
+    pub steps: Vec<RebaseStep>,
+    /// Index of the step being applied
+    pub next: usize,
+    /// The current step is applied to the work tree and stopped on conflicts
+    pub pending: bool,
+}
+
+/// Hunks (and optionally some of their lines) to stage, unstage or discard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkSelection {
+    /// Index into the hunks returned by `file_hunks`
+    pub hunk_index: usize,
+    /// Indices into the hunk's lines; None selects every line
+    pub lines: Option<Vec<usize>>,
+}
+
+/// The three versions of a conflicted file plus its current (marked-up) content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreeWayMerge {
+    pub file_path: String,
+    /// Common ancestor version (None when both sides added the file)
+    pub base: Option<String>,
+    /// Version on the current branch (None when deleted there)
+    pub ours: Option<String>,
+    /// Version being merged in (None when deleted there)
+    pub theirs: Option<String>,
+    /// Working tree content, with conflict markers
+    pub result: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BisectStatus {
+    pub active: bool,
+    /// Commit currently checked out for testing
+    pub current: Option<String>,
+    /// Remaining steps as estimated by git
+    pub steps: Option<u32>,
+    /// First bad commit once found
+    pub first_bad: Option<String>,
+    pub log: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,36 @@
+use super::types::*;
+use super::BackupStore;
+use tauri::State;
+
+/// The previous session and unsaved buffers, or `None` on the first start
+#[tauri::command]
+pub async fn restore_session(store: State<'_, BackupStore>) -> Result<Option<RestoredSession>, String> {
+    store
+        .restore()
+        .map_err(|e| format!("Failed to restore session: {}", e))
+}
+
+/// Save the layout and open tabs; backups of tabs that were closed are dropped
+#[tauri::command]
+pub async fn save_session(session: Session, store: State<'_, BackupStore>) -> Result<(), String> {
+    let paths: Vec<String> = session.tabs.iter().map(|tab| tab.path.clone()).collect();
+    store.retain(&paths);
+    store
+        .save_session(&session)
+        .map_err(|e| format!("Failed to save session: {}", e))
+}
+
+/// Back up the unsaved contents of a tab
+#[tauri::command]
+pub async fn backup_buffer(backup: Backup, store: State<'_, BackupStore>) -> Result<(), String> {
+    store
+        .backup(&backup)
+        .map_err(|e| format!("Failed to back up {}: {}", backup.path, e))
+}
+
+/// Drop the backup of a tab that no longer has unsaved edits
+#[tauri::command]
+pub async fn discard_backup(path: String, store: State<'_, BackupStore>) -> Result<(), String> {
+    store.discard(&path);
+    Ok(())
+}
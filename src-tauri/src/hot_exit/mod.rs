@@ -0,0 +1,14 @@
+//! Hot exit and crash recovery
+//!
+//! Dirty buffers (including untitled ones) are backed up to the app data
+//! directory while editing, together with the window layout and open tabs.
+//! On the next start the session is restored; backups of files that changed
+//! on disk in the meantime are returned with the new disk content so the
+//! editor can show a merge instead of overwriting either side.
+
+pub mod commands;
+pub mod store;
+pub mod types;
+
+pub use store::BackupStore;
+pub use types::*;
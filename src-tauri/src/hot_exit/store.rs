@@ -0,0 +1,223 @@
+use super::types::*;
+use crate::text_file::{self, FileContents};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SESSION_FILE: &str = "session.json";
+const BACKUP_DIR: &str = "backups";
+
+fn hash(text: &str) -> String {
+    format!("{:x}", md5::compute(text))
+}
+
+/// Session and buffer backups below the app data directory. A backup file is
+/// named `<hash of path>-<hash of content>.json`, so an unchanged buffer is
+/// never written twice and each tab keeps only its latest backup.
+pub struct BackupStore {
+    dir: PathBuf,
+    // Backups of the same tab may be written from concurrent commands
+    lock: Mutex<()>,
+}
+
+impl BackupStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn backup_dir(&self) -> PathBuf {
+        self.dir.join(BACKUP_DIR)
+    }
+
+    /// Backup files of the tab with `path_hash`, or all backups
+    fn backup_files(&self, path_hash: Option<&str>) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(self.backup_dir()) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.ends_with(".json") && path_hash.is_none_or(|h| name.starts_with(&format!("{}-", h)))
+            })
+            .collect()
+    }
+
+    pub fn save_session(&self, session: &Session) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_vec_pretty(session)?;
+        text_file::write_atomic(&self.dir.join(SESSION_FILE), &json)
+    }
+
+    pub fn load_session(&self) -> Result<Option<Session>> {
+        let path = self.dir.join(SESSION_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Some(serde_json::from_slice(&json)?))
+    }
+
+    /// Store the unsaved contents of a tab, replacing its previous backup
+    pub fn backup(&self, backup: &Backup) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let path_hash = hash(&backup.path);
+        let target = self
+            .backup_dir()
+            .join(format!("{}-{}.json", path_hash, hash(&backup.content)));
+        if !target.exists() {
+            fs::create_dir_all(self.backup_dir())?;
+            text_file::write_atomic(&target, &serde_json::to_vec(backup)?)?;
+        }
+        for old in self.backup_files(Some(&path_hash)) {
+            if old != target {
+                let _ = fs::remove_file(old);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the backup of a tab that was saved, reverted or closed
+    pub fn discard(&self, path: &str) {
+        let _guard = self.lock.lock().unwrap();
+        for file in self.backup_files(Some(&hash(path))) {
+            let _ = fs::remove_file(file);
+        }
+    }
+
+    /// Drop backups of tabs that are no longer open
+    pub fn retain(&self, paths: &[String]) {
+        let _guard = self.lock.lock().unwrap();
+        let keep: Vec<String> = paths.iter().map(|p| format!("{}-", hash(p))).collect();
+        for file in self.backup_files(None) {
+            let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if !keep.iter().any(|prefix| name.starts_with(prefix)) {
+                let _ = fs::remove_file(file);
+            }
+        }
+    }
+
+    fn read_backup(file: &Path) -> Result<RestoredBackup> {
+        let backup: Backup = serde_json::from_slice(&fs::read(file)?)?;
+        let disk = match &backup.base {
+            Some(base) if !backup.path.starts_with(UNTITLED_PREFIX) => current_text(&backup.path)
+                .filter(|text| text != base),
+            _ => None,
+        };
+        Ok(RestoredBackup { backup, disk })
+    }
+
+    /// The last session and all backups, or `None` on the first start
+    pub fn restore(&self) -> Result<Option<RestoredSession>> {
+        let backups: Vec<RestoredBackup> = self
+            .backup_files(None)
+            .iter()
+            .filter_map(|file| {
+                Self::read_backup(file)
+                    .map_err(|e| tracing::warn!("Skipping backup {}: {}", file.display(), e))
+                    .ok()
+            })
+            .collect();
+        let session = self.load_session()?;
+        if session.is_none() && backups.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(RestoredSession {
+            session: session.unwrap_or_default(),
+            backups,
+        }))
+    }
+}
+
+/// Text of a file as the editor would load it; `None` if it is gone or binary
+fn current_text(path: &str) -> Option<String> {
+    match text_file::decode(&fs::read(path).ok()?, None).ok()? {
+        FileContents::Text { content, .. } => Some(content),
+        FileContents::Binary { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn backup(path: &str, base: Option<&str>, content: &str) -> Backup {
+        Backup {
+            path: path.to_string(),
+            base: base.map(str::to_string),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_backups_replace_previous() {
+        let dir = TempDir::new().unwrap();
+        let store = BackupStore::new(dir.path().join("hot_exit"));
+        assert_eq!(store.restore().unwrap(), None);
+
+        store.backup(&backup("untitled:Untitled-1", None, "a")).unwrap();
+        store.backup(&backup("untitled:Untitled-1", None, "ab")).unwrap();
+        store.backup(&backup("untitled:Untitled-2", None, "x")).unwrap();
+        assert_eq!(store.backup_files(None).len(), 2);
+
+        store.discard("untitled:Untitled-2");
+        let restored = store.restore().unwrap().unwrap();
+        assert_eq!(restored.session, Session::default());
+        assert_eq!(restored.backups.len(), 1);
+        assert_eq!(restored.backups[0].backup.content, "ab");
+
+        store.retain(&[]);
+        assert!(store.backup_files(None).is_empty());
+    }
+
+    #[test]
+    fn test_restore_detects_disk_changes() {
+        let dir = TempDir::new().unwrap();
+        let store = BackupStore::new(dir.path().join("hot_exit"));
+        let unchanged = dir.path().join("unchanged.txt");
+        let changed = dir.path().join("changed.txt");
+        fs::write(&unchanged, "one\r\ntwo\r\n").unwrap();
+        fs::write(&changed, "theirs\n").unwrap();
+        let unchanged = unchanged.to_string_lossy().to_string();
+        let changed = changed.to_string_lossy().to_string();
+
+        store.backup(&backup(&unchanged, Some("one\ntwo\n"), "one\nmine\n")).unwrap();
+        store.backup(&backup(&changed, Some("base\n"), "mine\n")).unwrap();
+        let session = Session {
+            root_path: dir.path().to_string_lossy().to_string(),
+            workspace_file: None,
+            folders: vec![dir.path().to_string_lossy().to_string()],
+            active_panel: "explorer".to_string(),
+            sidebar_width: 250.0,
+            tabs: vec![SessionTab {
+                path: changed.clone(),
+                cursor_line: 1,
+                cursor_col: 2,
+                scroll_top: 0.0,
+            }],
+            active_tab: Some(0),
+            editor_groups: None,
+        };
+        store.save_session(&session).unwrap();
+
+        let restored = store.restore().unwrap().unwrap();
+        assert_eq!(restored.session, session);
+        let disk = |path: &str| {
+            restored
+                .backups
+                .iter()
+                .find(|b| b.backup.path == path)
+                .unwrap()
+                .disk
+                .clone()
+        };
+        assert_eq!(disk(&unchanged), None);
+        assert_eq!(disk(&changed).as_deref(), Some("theirs\n"));
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Tab paths starting with this prefix have no file on disk yet
+pub const UNTITLED_PREFIX: &str = "untitled:";
+
+/// An open editor tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub path: String,
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub scroll_top: f64,
+}
+
+/// Window layout and open tabs, saved while the app runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub root_path: String,
+    /// Workspace file of a multi-root workspace
+    pub workspace_file: Option<String>,
+    /// Open folders when there is no workspace file
+    pub folders: Vec<String>,
+    /// Sidebar panel, e.g. "explorer"
+    pub active_panel: String,
+    pub sidebar_width: f64,
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: Option<usize>,
+    /// Split panes and their tab groups; only the editor reads them
+    pub editor_groups: Option<serde_json::Value>,
+}
+
+/// Unsaved contents of a tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub path: String,
+    /// Text the edits started from (last loaded or saved); `None` for untitled tabs
+    pub base: Option<String>,
+    pub content: String,
+}
+
+/// A backup read back at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoredBackup {
+    #[serde(flatten)]
+    pub backup: Backup,
+    /// Current file content when the file changed on disk since `base`
+    pub disk: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoredSession {
+    pub session: Session,
+    pub backups: Vec<RestoredBackup>,
+}
@@ -4,6 +4,11 @@ pub mod database;      // External database connections (PostgreSQL, MySQL, etc.
 pub mod terminal;
 pub mod workflow;
 pub mod persistent_terminal;
+pub mod shell_integration;
+pub mod tasks;
+pub mod testing;
+pub mod text_file;      // Encoding detection and atomic saves
+pub mod local_history;  // Revisions of project files, also recorded by the BerryCode agent
 
 // BerryCode CLI modules (integrated from parent)
 pub mod berrycode;
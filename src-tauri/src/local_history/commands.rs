@@ -0,0 +1,52 @@
+use super::types::*;
+use super::LocalHistory;
+use std::path::Path;
+use tauri::State;
+
+/// Revisions of a file, or of all files below a folder, with the project's checkpoints
+#[tauri::command]
+pub async fn local_history_list(
+    root: String,
+    path: String,
+    history: State<'_, LocalHistory>,
+) -> Result<HistoryListing, String> {
+    history
+        .list(Path::new(&root), Path::new(&path))
+        .map_err(|e| format!("Failed to read local history: {}", e))
+}
+
+/// Compare a revision with the current content of its file
+#[tauri::command]
+pub async fn local_history_diff(
+    root: String,
+    revision_id: String,
+    history: State<'_, LocalHistory>,
+) -> Result<RevisionDiff, String> {
+    history
+        .diff(Path::new(&root), &revision_id)
+        .map_err(|e| format!("Failed to compare revision: {}", e))
+}
+
+/// Write a revision back to its file
+#[tauri::command]
+pub async fn local_history_restore(
+    root: String,
+    revision_id: String,
+    history: State<'_, LocalHistory>,
+) -> Result<Revision, String> {
+    history
+        .restore(Path::new(&root), &revision_id)
+        .map_err(|e| format!("Failed to restore revision: {}", e))
+}
+
+/// Label the current state of a project
+#[tauri::command]
+pub async fn local_history_put_label(
+    root: String,
+    label: String,
+    history: State<'_, LocalHistory>,
+) -> Result<Checkpoint, String> {
+    history
+        .put_label(Path::new(&root), &label)
+        .map_err(|e| format!("Failed to add label: {}", e))
+}
@@ -0,0 +1,14 @@
+//! Local history
+//!
+//! Every save, external change and agent edit of a project file is recorded
+//! as a compressed revision in a per-project store below the app data
+//! directory, pruned by a retention policy. Revisions can be listed per file
+//! or folder, compared with the current content and restored; labelled
+//! checkpoints mark points in time such as "before the agent edited".
+
+pub mod commands;
+pub mod store;
+pub mod types;
+
+pub use store::LocalHistory;
+pub use types::*;
@@ -0,0 +1,552 @@
+use super::types::*;
+use crate::text_file::{self, FileContents};
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tauri identifier of the app, whose data directory holds the history
+pub const APP_IDENTIFIER: &str = "com.berry.editor";
+
+const INDEX_FILE: &str = "index.json";
+const LOCK_FILE: &str = "index.lock";
+const BLOB_DIR: &str = "blobs";
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// A lock older than this was left behind by a process that died holding it
+const STALE_LOCK: Duration = Duration::from_secs(5);
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn hash(bytes: &[u8]) -> String {
+    format!("{:x}", md5::compute(bytes))
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// A workspace folder as the key of its store: `/p/app/` (as git reports
+/// work trees) and `/p/app` are the same project
+fn normalize_root(root: &Path) -> PathBuf {
+    root.components().collect()
+}
+
+/// Held while a project's index is read, changed and written back. A lock
+/// file rather than a mutex, since the berrycode CLI records into the same
+/// store from its own process.
+struct IndexLock(PathBuf);
+
+impl IndexLock {
+    fn acquire(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LOCK_FILE);
+        let start = Instant::now();
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self(path)),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                    } else if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(anyhow!("Local history is locked by {}", path.display()));
+                    } else {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                }
+                Err(e) => return Err(e).with_context(|| format!("Failed to lock {}", path.display())),
+            }
+        }
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Revisions and checkpoints of one project, oldest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    root: String,
+    revisions: Vec<Revision>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// Local history below the app data directory. Each project (workspace
+/// folder) has a directory named after the hash of its path, holding
+/// `index.json` and gzip-compressed contents in `blobs/`, named after their
+/// hash so identical contents are stored once.
+pub struct LocalHistory {
+    dir: PathBuf,
+    policy: RetentionPolicy,
+    /// Workspace folders, used to find the project of a saved file
+    roots: Mutex<Vec<PathBuf>>,
+}
+
+impl LocalHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self::with_policy(dir, RetentionPolicy::default())
+    }
+
+    pub fn with_policy(dir: PathBuf, policy: RetentionPolicy) -> Self {
+        Self {
+            dir,
+            policy,
+            roots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The app's history directory, for use outside the app (the berrycode CLI)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER).join("local_history"))
+    }
+
+    pub fn set_roots(&self, roots: Vec<PathBuf>) {
+        *self.roots.lock().unwrap() = roots.iter().map(|root| normalize_root(root)).collect();
+    }
+
+    /// The workspace folder containing `path`; the innermost one when folders are nested
+    pub fn root_for(&self, path: &Path) -> Option<PathBuf> {
+        self.roots
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+    }
+
+    fn project_dir(&self, root: &Path) -> PathBuf {
+        self.dir.join(hash(normalize_root(root).to_string_lossy().as_bytes()))
+    }
+
+    fn blob_path(&self, root: &Path, hash: &str) -> PathBuf {
+        self.project_dir(root).join(BLOB_DIR).join(format!("{}.gz", hash))
+    }
+
+    fn load_index(&self, root: &Path) -> Result<Index> {
+        let path = self.project_dir(root).join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Index {
+                root: normalize_root(root).to_string_lossy().to_string(),
+                ..Index::default()
+            });
+        }
+        let json = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    fn save_index(&self, root: &Path, index: &Index) -> Result<()> {
+        let dir = self.project_dir(root);
+        fs::create_dir_all(&dir)?;
+        text_file::write_atomic(&dir.join(INDEX_FILE), &serde_json::to_vec(index)?)
+    }
+
+    fn write_blob(&self, root: &Path, hash: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.blob_path(root, hash);
+        if path.exists() {
+            return Ok(());
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes)?;
+        fs::create_dir_all(self.project_dir(root).join(BLOB_DIR))?;
+        text_file::write_atomic(&path, &encoder.finish()?)
+    }
+
+    fn read_blob(&self, root: &Path, hash: &str) -> Result<Vec<u8>> {
+        let path = self.blob_path(root, hash);
+        let file = fs::File::open(&path).with_context(|| format!("Missing revision content {}", path.display()))?;
+        let mut bytes = Vec::new();
+        GzDecoder::new(file).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Record the current content of `path` in the workspace folder containing it
+    pub fn record(&self, path: &Path, source: RevisionSource) -> Result<Option<Revision>> {
+        match self.root_for(path) {
+            Some(root) => self.record_in(&root, path, source),
+            None => Ok(None),
+        }
+    }
+
+    /// Record the current content of `path`. Nothing is stored when the file
+    /// is unchanged since its last revision, missing, a folder or too large.
+    pub fn record_in(&self, root: &Path, path: &Path, source: RevisionSource) -> Result<Option<Revision>> {
+        let Ok(metadata) = fs::metadata(path) else {
+            return Ok(None);
+        };
+        if !metadata.is_file() || metadata.len() > self.policy.max_file_size {
+            return Ok(None);
+        }
+        let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        self.record_bytes(root, path, &bytes, source, now_ms())
+    }
+
+    fn record_bytes(
+        &self,
+        root: &Path,
+        path: &Path,
+        bytes: &[u8],
+        source: RevisionSource,
+        now: i64,
+    ) -> Result<Option<Revision>> {
+        let _lock = IndexLock::acquire(&self.project_dir(root))?;
+        let mut index = self.load_index(root)?;
+        let path = path.to_string_lossy().to_string();
+        let hash = hash(bytes);
+        let latest = index.revisions.iter().rev().find(|revision| revision.path == path);
+        if latest.is_some_and(|revision| revision.hash == hash) {
+            return Ok(None);
+        }
+
+        self.write_blob(root, &hash, bytes)?;
+        let revision = Revision {
+            id: uuid::Uuid::new_v4().to_string(),
+            path,
+            timestamp: now,
+            source,
+            size: bytes.len() as u64,
+            hash,
+        };
+        index.revisions.push(revision.clone());
+        self.prune(root, &mut index, now);
+        self.save_index(root, &index)?;
+        Ok(Some(revision))
+    }
+
+    /// Apply the retention policy. The newest revision of each file is always
+    /// kept so its next change can still be undone.
+    fn prune(&self, root: &Path, index: &mut Index, now: i64) {
+        let cutoff = now - i64::from(self.policy.max_age_days) * DAY_MS;
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut keep: Vec<bool> = index
+            .revisions
+            .iter()
+            .rev()
+            .map(|revision| {
+                let count = seen.entry(revision.path.clone()).or_default();
+                *count += 1;
+                *count == 1 || (*count <= self.policy.max_revisions_per_file && revision.timestamp >= cutoff)
+            })
+            .collect();
+        keep.reverse();
+        let before = index.revisions.len();
+        let mut keep = keep.into_iter();
+        index.revisions.retain(|_| keep.next().unwrap_or(true));
+        index.checkpoints.retain(|checkpoint| checkpoint.timestamp >= cutoff);
+        if index.revisions.len() == before {
+            return;
+        }
+
+        // Drop contents no revision refers to anymore
+        let used: HashSet<&str> = index.revisions.iter().map(|revision| revision.hash.as_str()).collect();
+        let Ok(entries) = fs::read_dir(self.project_dir(root).join(BLOB_DIR)) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !used.contains(name.trim_end_matches(".gz")) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    /// Add a labelled checkpoint
+    pub fn put_label(&self, root: &Path, label: &str) -> Result<Checkpoint> {
+        let _lock = IndexLock::acquire(&self.project_dir(root))?;
+        let mut index = self.load_index(root)?;
+        let checkpoint = Checkpoint {
+            id: uuid::Uuid::new_v4().to_string(),
+            label: label.to_string(),
+            timestamp: now_ms(),
+        };
+        index.checkpoints.push(checkpoint.clone());
+        self.save_index(root, &index)?;
+        Ok(checkpoint)
+    }
+
+    /// Record the current content of `paths`, then add a checkpoint, e.g.
+    /// before the agent edits them
+    pub fn checkpoint(&self, root: &Path, label: &str, paths: &[PathBuf]) -> Result<Checkpoint> {
+        for path in paths {
+            self.record_in(root, path, RevisionSource::Checkpoint)?;
+        }
+        self.put_label(root, label)
+    }
+
+    /// Revisions of a file, or of every file below a folder, and all checkpoints
+    pub fn list(&self, root: &Path, path: &Path) -> Result<HistoryListing> {
+        let index = self.load_index(root)?;
+        Ok(HistoryListing {
+            revisions: index
+                .revisions
+                .into_iter()
+                .rev()
+                .filter(|revision| Path::new(&revision.path).starts_with(path))
+                .collect(),
+            checkpoints: index.checkpoints.into_iter().rev().collect(),
+        })
+    }
+
+    fn find(&self, root: &Path, id: &str) -> Result<Revision> {
+        self.load_index(root)?
+            .revisions
+            .into_iter()
+            .find(|revision| revision.id == id)
+            .ok_or_else(|| anyhow!("Revision {} not found", id))
+    }
+
+    pub fn content(&self, root: &Path, id: &str) -> Result<Vec<u8>> {
+        let revision = self.find(root, id)?;
+        self.read_blob(root, &revision.hash)
+    }
+
+    /// Compare a revision with the current content of its file
+    pub fn diff(&self, root: &Path, id: &str) -> Result<RevisionDiff> {
+        let revision = self.find(root, id)?;
+        let old = text(&self.read_blob(root, &revision.hash)?);
+        let current = fs::read(&revision.path).ok();
+        let deleted = current.is_none();
+        let new = match current {
+            Some(bytes) => text(&bytes),
+            None => Some(String::new()),
+        };
+        Ok(match (old, new) {
+            (Some(old), Some(new)) => RevisionDiff {
+                hunks: diff_hunks(&old, &new),
+                revision,
+                binary: false,
+                deleted,
+            },
+            _ => RevisionDiff {
+                revision,
+                hunks: Vec::new(),
+                binary: true,
+                deleted,
+            },
+        })
+    }
+
+    /// Write a revision back to its file (recreating it if it was deleted).
+    /// The content it replaces is recorded first.
+    pub fn restore(&self, root: &Path, id: &str) -> Result<Revision> {
+        let revision = self.find(root, id)?;
+        let bytes = self.read_blob(root, &revision.hash)?;
+        let path = Path::new(&revision.path);
+        self.record_in(root, path, RevisionSource::External)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        text_file::write_atomic(path, &bytes)?;
+        Ok(self
+            .record_bytes(root, path, &bytes, RevisionSource::Restore, now_ms())?
+            .unwrap_or(revision))
+    }
+}
+
+/// Text as the editor shows it; `None` for binary content
+fn text(bytes: &[u8]) -> Option<String> {
+    match text_file::decode(bytes, None).ok()? {
+        FileContents::Text { content, .. } => Some(content),
+        FileContents::Binary { .. } => None,
+    }
+}
+
+/// Line diff hunks from `old` to `new`, with three lines of context
+pub fn diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(3)
+        .iter()
+        .filter_map(|group| {
+            let (first, last) = (group.first()?, group.last()?);
+            let old_range = first.old_range().start..last.old_range().end;
+            let new_range = first.new_range().start..last.new_range().end;
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    line_type: match change.tag() {
+                        ChangeTag::Delete => "delete",
+                        ChangeTag::Insert => "add",
+                        ChangeTag::Equal => "context",
+                    }
+                    .to_string(),
+                    content: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                    old_line_no: change.old_index().map(|i| i as u32 + 1),
+                    new_line_no: change.new_index().map(|i| i as u32 + 1),
+                })
+                .collect();
+            Some(DiffHunk {
+                old_start: old_range.start as u32 + 1,
+                old_lines: old_range.len() as u32,
+                new_start: new_range.start as u32 + 1,
+                new_lines: new_range.len() as u32,
+                lines,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _dir: TempDir,
+        root: PathBuf,
+        history: LocalHistory,
+    }
+
+    fn fixture(policy: RetentionPolicy) -> Fixture {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        let history = LocalHistory::with_policy(dir.path().join("local_history"), policy);
+        history.set_roots(vec![root.clone()]);
+        Fixture { _dir: dir, root, history }
+    }
+
+    fn blobs(f: &Fixture) -> usize {
+        fs::read_dir(f.history.project_dir(&f.root).join(BLOB_DIR)).unwrap().count()
+    }
+
+    #[test]
+    fn test_records_and_restores_revisions() {
+        let f = fixture(RetentionPolicy::default());
+        let main = f.root.join("src/main.rs");
+        fs::write(&main, "fn main() {}\n").unwrap();
+        let first = f.history.record(&main, RevisionSource::Save).unwrap().unwrap();
+        // Unchanged content is not recorded twice
+        assert_eq!(f.history.record(&main, RevisionSource::External).unwrap(), None);
+        assert_eq!(f.history.record(Path::new("/elsewhere/x.rs"), RevisionSource::Save).unwrap(), None);
+
+        fs::write(&main, "fn main() {\n    run();\n}\n").unwrap();
+        f.history.record(&main, RevisionSource::Save).unwrap().unwrap();
+        let notes = f.root.join("notes.txt");
+        fs::write(&notes, "todo\n").unwrap();
+        f.history.checkpoint(&f.root, "Before agent edit", std::slice::from_ref(&notes)).unwrap();
+
+        let listing = f.history.list(&f.root, &main).unwrap();
+        assert_eq!(listing.revisions.len(), 2);
+        assert_eq!(listing.revisions[1], first);
+        assert_eq!(listing.checkpoints[0].label, "Before agent edit");
+        let notes_listing = f.history.list(&f.root, &notes).unwrap();
+        assert_eq!(notes_listing.revisions[0].source, RevisionSource::Checkpoint);
+        assert_eq!(f.history.list(&f.root, &f.root).unwrap().revisions.len(), 3);
+        assert_eq!(f.history.list(&f.root, &f.root.join("src")).unwrap().revisions.len(), 2);
+
+        let diff = f.history.diff(&f.root, &first.id).unwrap();
+        assert_eq!((diff.binary, diff.deleted, diff.hunks.len()), (false, false, 1));
+        let changed: Vec<_> = diff.hunks[0]
+            .lines
+            .iter()
+            .map(|line| (line.line_type.as_str(), line.content.as_str()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![("delete", "fn main() {}"), ("add", "fn main() {"), ("add", "    run();"), ("add", "}")]
+        );
+
+        // Deleted files can be restored; the restore is itself a revision
+        fs::remove_file(&main).unwrap();
+        assert!(f.history.diff(&f.root, &first.id).unwrap().deleted);
+        let restored = f.history.restore(&f.root, &first.id).unwrap();
+        assert_eq!(restored.source, RevisionSource::Restore);
+        assert_eq!(fs::read_to_string(&main).unwrap(), "fn main() {}\n");
+        assert_eq!(f.history.content(&f.root, &restored.id).unwrap(), b"fn main() {}\n");
+        // Same content as the first revision: stored once
+        assert_eq!(blobs(&f), 3);
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        let f = fixture(RetentionPolicy {
+            max_age_days: 7,
+            max_revisions_per_file: 2,
+            max_file_size: 10,
+        });
+        let a = f.root.join("a.txt");
+        let b = f.root.join("b.txt");
+        let day = |n: i64| n * DAY_MS;
+        let record = |path: &Path, text: &str, at: i64| {
+            f.history
+                .record_bytes(&f.root, path, text.as_bytes(), RevisionSource::Save, at)
+                .unwrap()
+        };
+        record(&b, "old b", day(1));
+        for (i, text) in ["a1", "a2", "a3"].iter().enumerate() {
+            record(&a, text, day(10) + i as i64);
+        }
+        let revisions = f.history.list(&f.root, &f.root).unwrap().revisions;
+        let texts: Vec<_> = revisions
+            .iter()
+            .map(|r| String::from_utf8(f.history.content(&f.root, &r.id).unwrap()).unwrap())
+            .collect();
+        // Only two revisions per file; b's only revision survives its age
+        assert_eq!(texts, vec!["a3", "a2", "old b"]);
+        assert_eq!(blobs(&f), 3);
+
+        record(&b, "new b", day(10) + 5);
+        let b_revisions = f.history.list(&f.root, &b).unwrap().revisions;
+        assert_eq!(b_revisions.len(), 1);
+
+        fs::write(&a, "longer than ten bytes").unwrap();
+        assert_eq!(f.history.record(&a, RevisionSource::External).unwrap(), None);
+    }
+
+    #[test]
+    fn test_root_with_trailing_slash_shares_store() {
+        let f = fixture(RetentionPolicy::default());
+        // git2 reports work trees as `/p/app/`
+        let git_root = PathBuf::from(format!("{}/", f.root.display()));
+        let agent = LocalHistory::new(f.history.dir.clone());
+        agent.set_roots(vec![git_root.clone()]);
+
+        let lib = f.root.join("src/lib.rs");
+        fs::write(&lib, "pub fn a() {}\n").unwrap();
+        agent.record(&lib, RevisionSource::Agent).unwrap().unwrap();
+        agent.checkpoint(&git_root, "Before agent edit", &[]).unwrap();
+
+        let listing = f.history.list(&f.root, &lib).unwrap();
+        assert_eq!(listing.revisions.len(), 1);
+        assert_eq!(listing.checkpoints.len(), 1);
+        assert_eq!(f.history.project_dir(&git_root), f.history.project_dir(&f.root));
+    }
+
+    #[test]
+    fn test_concurrent_stores_keep_all_revisions() {
+        let f = fixture(RetentionPolicy::default());
+        // Separate instances stand in for the app and the CLI process
+        std::thread::scope(|scope| {
+            for writer in 0..4 {
+                let (dir, root) = (f.history.dir.clone(), f.root.clone());
+                scope.spawn(move || {
+                    let history = LocalHistory::new(dir);
+                    for n in 0..10 {
+                        let path = root.join(format!("w{}-{}.txt", writer, n));
+                        let text = format!("{} {}", writer, n);
+                        history
+                            .record_bytes(&root, &path, text.as_bytes(), RevisionSource::Save, now_ms())
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(f.history.list(&f.root, &f.root).unwrap().revisions.len(), 40);
+        assert!(!f.history.project_dir(&f.root).join(LOCK_FILE).exists());
+    }
+}
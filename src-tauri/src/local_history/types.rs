@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// What produced a revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionSource {
+    /// Saved from the editor
+    Save,
+    /// Changed on disk by another program
+    External,
+    /// Edited by the berrycode agent
+    Agent,
+    /// Restored from local history
+    Restore,
+    /// Recorded for a checkpoint, e.g. before the agent edits the file
+    Checkpoint,
+}
+
+/// One recorded version of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: String,
+    pub path: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: i64,
+    pub source: RevisionSource,
+    pub size: u64,
+    /// Hash of the content, also the name of its blob
+    pub hash: String,
+}
+
+/// A labelled point in time, e.g. before the agent applied edits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+/// How long revisions are kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Revisions and checkpoints older than this are dropped
+    pub max_age_days: u32,
+    /// Only the newest revisions of each file are kept
+    pub max_revisions_per_file: usize,
+    /// Larger files are not recorded
+    pub max_file_size: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            max_revisions_per_file: 100,
+            max_file_size: 2_000_000,
+        }
+    }
+}
+
+/// History of a file or folder, newest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryListing {
+    pub revisions: Vec<Revision>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// "add", "delete" or "context"
+    pub line_type: String,
+    pub content: String,
+    pub old_line_no: Option<u32>,
+    pub new_line_no: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A revision compared with the current file content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevisionDiff {
+    pub revision: Revision,
+    pub hunks: Vec<DiffHunk>,
+    /// Either side is binary; no hunks are computed
+    pub binary: bool,
+    /// The file no longer exists
+    pub deleted: bool,
+}
@@ -2,9 +2,10 @@
 //! Manages LSP server process and communication
 
 use super::protocol::*;
+use crate::remote::{self, RemoteUri};
 use serde_json::Value;
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
@@ -12,12 +13,29 @@ use std::sync::{Arc, Mutex};
 pub struct LspClient {
     language: String,
     process: Option<Child>,
-    stdin: Option<ChildStdin>,
-    stdout: Option<BufReader<ChildStdout>>,
+    stdin: Option<Box<dyn Write + Send>>,
+    stdout: Option<BufReader<Box<dyn Read + Send>>>,
+    /// Host of a server running over SSH; its `file://` URIs are rewritten
+    /// to and from the editor's `ssh://` paths
+    remote_authority: Option<String>,
     request_id: Arc<AtomicU64>,
     capabilities: ServerCapabilities,
 }
 
+/// Editor URIs of a remote host (`ssh://host/...`, possibly written as
+/// `file://ssh://host/...`) as the server's `file:///...` URIs
+fn to_server_uris(message: &str, authority: &str) -> String {
+    let remote_root = format!("ssh://{}/", authority);
+    message
+        .replace(&format!("file://{}", remote_root), "file:///")
+        .replace(&remote_root, "file:///")
+}
+
+/// The server's `file:///...` URIs as the editor's `ssh://host/...` paths
+fn from_server_uris(message: &str, authority: &str) -> String {
+    message.replace("file:///", &format!("ssh://{}/", authority))
+}
+
 impl LspClient {
     /// Create new LSP client for a language
     pub fn new(language: &str, root_uri: &str) -> Result<Self, String> {
@@ -26,6 +44,7 @@ impl LspClient {
             process: None,
             stdin: None,
             stdout: None,
+            remote_authority: None,
             request_id: Arc::new(AtomicU64::new(1)),
             capabilities: ServerCapabilities::default(),
         };
@@ -39,6 +58,40 @@ impl LspClient {
         Ok(client)
     }
 
+    /// Create a client for a server started on the host of an `ssh://` root,
+    /// speaking over the SSH channel
+    pub fn new_remote(language: &str, root_uri: &str) -> Result<Self, String> {
+        let root = RemoteUri::parse(root_uri).map_err(|e| e.to_string())?;
+        let host = remote::host(&root.authority).map_err(|e| format!("{:#}", e))?;
+        let command = Self::get_remote_server_command(language)?;
+        let process = host
+            .spawn(&format!("cd {} && exec {}", remote::quote(&root.path), command))
+            .map_err(|e| format!("Failed to start remote LSP server: {:#}", e))?;
+
+        let mut client = Self {
+            language: language.to_string(),
+            process: None,
+            stdin: Some(process.stdin),
+            stdout: Some(BufReader::new(process.stdout)),
+            remote_authority: Some(root.authority.clone()),
+            request_id: Arc::new(AtomicU64::new(1)),
+            capabilities: ServerCapabilities::default(),
+        };
+        client.initialize(&root.to_uri())?;
+
+        Ok(client)
+    }
+
+    /// Server command line on a remote host, found through the remote `PATH`
+    fn get_remote_server_command(language: &str) -> Result<&'static str, String> {
+        match language {
+            "rust" => Ok("rust-analyzer"),
+            "typescript" | "javascript" => Ok("typescript-language-server --stdio"),
+            "python" => Ok("pyright-langserver --stdio"),
+            _ => Err(format!("Unsupported language: {}", language)),
+        }
+    }
+
     /// Start language server process
     fn start_server(&mut self) -> Result<(), String> {
         let (command, args) = Self::get_server_command(&self.language)?;
@@ -56,8 +109,8 @@ impl LspClient {
         let stdout = process.stdout.take().ok_or("Failed to get stdout")?;
 
         self.process = Some(process);
-        self.stdin = Some(stdin);
-        self.stdout = Some(BufReader::new(stdout));
+        self.stdin = Some(Box::new(stdin));
+        self.stdout = Some(BufReader::new(Box::new(stdout)));
 
         Ok(())
     }
@@ -191,6 +244,10 @@ impl LspClient {
 
     /// Write message to stdin
     fn write_message(&mut self, message: &str) -> Result<(), String> {
+        let message = match &self.remote_authority {
+            Some(authority) => to_server_uris(message, authority),
+            None => message.to_string(),
+        };
         let stdin = self.stdin.as_mut().ok_or("No stdin available")?;
 
         let content_length = message.len();
@@ -246,7 +303,10 @@ impl LspClient {
             std::io::Read::read_exact(stdout, &mut buffer)
                 .map_err(|e| format!("Failed to read content: {}", e))?;
 
-            let content = String::from_utf8(buffer).map_err(|e| format!("Invalid UTF-8: {}", e))?;
+            let mut content = String::from_utf8(buffer).map_err(|e| format!("Invalid UTF-8: {}", e))?;
+            if let Some(authority) = &self.remote_authority {
+                content = from_server_uris(&content, authority);
+            }
 
             // Parse message
             let message: LspMessage = serde_json::from_str(&content)
@@ -495,6 +555,7 @@ mod tests {
             process: None,
             stdin: None,
             stdout: None,
+            remote_authority: None,
             request_id: Arc::new(AtomicU64::new(1)),
             capabilities: ServerCapabilities::default(),
         };
@@ -503,4 +564,45 @@ mod tests {
         assert_eq!(client.next_request_id(), 2);
         assert_eq!(client.next_request_id(), 3);
     }
+
+    #[test]
+    fn test_remote_uri_rewriting() {
+        let request = r#"{"uri":"file://ssh://dev@box/srv/app/main.rs","rootUri":"ssh://dev@box/srv/app"}"#;
+        assert_eq!(
+            to_server_uris(request, "dev@box"),
+            r#"{"uri":"file:///srv/app/main.rs","rootUri":"file:///srv/app"}"#
+        );
+        assert_eq!(
+            from_server_uris(r#"{"uri":"file:///srv/app/lib.rs"}"#, "dev@box"),
+            r#"{"uri":"ssh://dev@box/srv/app/lib.rs"}"#
+        );
+    }
+
+    #[test]
+    fn test_remote_server_over_channel() {
+        // A stand-in server that answers `initialize` and then exits
+        let authority = remote::local_host("lsp");
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = RemoteUri {
+            authority: authority.clone(),
+            path: dir.path().to_string_lossy().to_string(),
+        };
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":{"hoverProvider":true}}}"#;
+        let script = format!("printf 'Content-Length: {}\\r\\n\\r\\n%s' '{}'; cat >/dev/null", body.len(), body);
+        let host = remote::host(&authority).unwrap();
+        let process = host.spawn(&script).unwrap();
+
+        let mut client = LspClient {
+            language: "rust".to_string(),
+            process: None,
+            stdin: Some(process.stdin),
+            stdout: Some(BufReader::new(process.stdout)),
+            remote_authority: Some(authority),
+            request_id: Arc::new(AtomicU64::new(1)),
+            capabilities: ServerCapabilities::default(),
+        };
+        client.initialize(&root.to_uri()).unwrap();
+        assert_eq!(client.capabilities().hover_provider, Some(true));
+        client.stdin = None;
+    }
 }
@@ -49,7 +49,7 @@ pub async fn lsp_get_completions(
     let mgr = manager.lock().unwrap();
 
     let client_arc = mgr
-        .get_client(&language)
+        .client_for(&language, &file_path)
         .ok_or_else(|| format!("LSP not initialized for {}", language))?;
 
     let mut client = client_arc.lock().unwrap();
@@ -76,7 +76,7 @@ pub async fn lsp_get_hover(
     let mgr = manager.lock().unwrap();
 
     let client_arc = mgr
-        .get_client(&language)
+        .client_for(&language, &file_path)
         .ok_or_else(|| format!("LSP not initialized for {}", language))?;
 
     let mut client = client_arc.lock().unwrap();
@@ -108,7 +108,7 @@ pub async fn lsp_goto_definition(
     let mgr = manager.lock().unwrap();
 
     let client_arc = mgr
-        .get_client(&language)
+        .client_for(&language, &file_path)
         .ok_or_else(|| {
             let err = format!("LSP not initialized for {}", language);
             eprintln!("[LSP COMMAND] ❌ ERROR: {}", err);
@@ -12,9 +12,30 @@ pub use protocol::{LspMessage, LspNotification, LspRequest, LspResponse};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-/// Global LSP manager
+/// Clients keyed by (language, root URI)
+type Clients = HashMap<(String, String), Arc<Mutex<LspClient>>>;
+
+/// Global LSP manager: one client per language and workspace folder
 pub struct LspManager {
-    clients: Arc<Mutex<HashMap<String, Arc<Mutex<LspClient>>>>>,
+    clients: Arc<Mutex<Clients>>,
+}
+
+/// Folder path of a root URI (`file:///ws` or `/ws`)
+fn root_path(root_uri: &str) -> &str {
+    root_uri.strip_prefix("file://").unwrap_or(root_uri).trim_end_matches('/')
+}
+
+/// The root containing `file_path`; the innermost one when folders are nested
+fn root_for<'a>(roots: impl Iterator<Item = &'a str>, file_path: &str) -> Option<&'a str> {
+    let file_path = root_path(file_path);
+    roots
+        .filter(|root| {
+            let root = root_path(root);
+            file_path
+                .strip_prefix(root)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\'))
+        })
+        .max_by_key(|root| root_path(root).len())
 }
 
 impl LspManager {
@@ -24,33 +45,62 @@ impl LspManager {
         }
     }
 
-    /// Get or create LSP client for a language
+    /// Get an LSP client for a language (any workspace folder)
     pub fn get_client(&self, language: &str) -> Option<Arc<Mutex<LspClient>>> {
         let clients = self.clients.lock().unwrap();
-        clients.get(language).cloned()
+        clients
+            .iter()
+            .find(|((lang, _), _)| lang == language)
+            .map(|(_, client)| client.clone())
     }
 
-    /// Initialize LSP client for a language
+    /// The client of the workspace folder containing `file_path`, falling back
+    /// to any client for the language
+    pub fn client_for(&self, language: &str, file_path: &str) -> Option<Arc<Mutex<LspClient>>> {
+        let clients = self.clients.lock().unwrap();
+        let roots = clients
+            .keys()
+            .filter(|(lang, _)| lang == language)
+            .map(|(_, root)| root.as_str());
+        match root_for(roots, file_path) {
+            Some(root) => clients.get(&(language.to_string(), root.to_string())).cloned(),
+            None => {
+                drop(clients);
+                self.get_client(language)
+            }
+        }
+    }
+
+    /// Initialize LSP client for a language in a workspace folder
     pub fn initialize_client(&self, language: String, root_uri: String) -> Result<(), String> {
         let mut clients = self.clients.lock().unwrap();
 
-        if clients.contains_key(&language) {
+        let key = (language, root_uri);
+        if clients.contains_key(&key) {
             return Ok(()); // Already initialized
         }
 
-        let client = LspClient::new(&language, &root_uri)?;
-        clients.insert(language, Arc::new(Mutex::new(client)));
+        // Servers of remote folders run on their host
+        let client = if crate::remote::is_remote(&key.1) {
+            LspClient::new_remote(&key.0, &key.1)?
+        } else {
+            LspClient::new(&key.0, &key.1)?
+        };
+        clients.insert(key, Arc::new(Mutex::new(client)));
 
         Ok(())
     }
 
-    /// Shutdown LSP client for a language
+    /// Shutdown the LSP clients of a language in all workspace folders
     pub fn shutdown_client(&self, language: &str) -> Result<(), String> {
         let mut clients = self.clients.lock().unwrap();
 
-        if let Some(client_arc) = clients.remove(language) {
-            let mut client = client_arc.lock().unwrap();
-            client.shutdown()?;
+        let keys: Vec<_> = clients.keys().filter(|(lang, _)| lang == language).cloned().collect();
+        for key in keys {
+            if let Some(client_arc) = clients.remove(&key) {
+                let mut client = client_arc.lock().unwrap();
+                client.shutdown()?;
+            }
         }
 
         Ok(())
@@ -60,7 +110,7 @@ impl LspManager {
     pub fn shutdown_all(&self) -> Result<(), String> {
         let mut clients = self.clients.lock().unwrap();
 
-        for (_lang, client_arc) in clients.drain() {
+        for (_key, client_arc) in clients.drain() {
             let mut client = client_arc.lock().unwrap();
             if let Err(e) = client.shutdown() {
                 eprintln!("Error shutting down LSP client: {}", e);
@@ -92,4 +142,15 @@ mod tests {
         let manager = LspManager::default();
         assert!(manager.get_client("typescript").is_none());
     }
+
+    #[test]
+    fn test_root_for_file() {
+        let roots = ["/ws/api", "file:///ws/web", "/ws/api/vendor/lib"];
+        let root = |file: &str| root_for(roots.iter().copied(), file);
+        assert_eq!(root("/ws/api/src/main.rs"), Some("/ws/api"));
+        assert_eq!(root("file:///ws/web/index.ts"), Some("file:///ws/web"));
+        assert_eq!(root("/ws/api/vendor/lib/x.rs"), Some("/ws/api/vendor/lib"));
+        assert_eq!(root("/ws/apis/main.rs"), None);
+        assert!(LspManager::new().client_for("rust", "/ws/api/main.rs").is_none());
+    }
 }
@@ -1,26 +1,44 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app_database; // ✅ Database Tools: Query history
 mod berrycode_commands; // ✅ BerryCode CLI integration
 mod database; // ✅ Database Tools: Connection management
+mod file_tree; // ✅ Explorer: Lazy tree with git decorations
 mod fs_commands;
 mod git;
+mod hot_exit; // ✅ Hot exit: Session and unsaved buffer backups
 mod hyper_search;
 mod indexer; // ✅ IntelliJ Pro: Background symbol indexing
+mod local_history; // ✅ Local history: Revisions of every save and change
 mod lsp;
+#[cfg(feature = "parsing")]
+mod markdown; // ✅ Markdown preview: Highlighted code, diagrams and HTML export
 mod persistent_terminal; // ✅ Terminal: PTY-based persistent sessions
+mod remote; // ✅ Remote development over SSH
 mod search_commands;
+mod shell_integration; // ✅ Terminal: OSC 133/633 prompt markers
 mod streaming; // ✅ Async streaming for large files
 mod syntax_highlighter; // ✅ Parallel syntax highlighting with rayon // ✅ Strategy 3: Zero-memory parallel search
+mod tasks; // ✅ Task runner with problem matchers
+mod testing; // ✅ Test explorer
 mod terminal; // ✅ Terminal: Tauri commands for terminal management
+mod text_file; // ✅ Editor: Encoding detection and atomic saves
+mod watcher; // ✅ Workspace file watcher
+mod workspace; // ✅ Multi-root workspaces
 mod workflow; // ✅ Workflow Automation: Pipeline execution
 
 use berrycode_commands::BerryCodeState;
 use database::DbManager;
 use git::GitManager;
+use hot_exit::BackupStore;
 use indexer::SymbolIndex;
+use local_history::LocalHistory;
 use lsp::LspManager;
+use tasks::TaskManager;
+use testing::TestManager;
 use terminal::TerminalManagerState;
+use watcher::WorkspaceWatcher;
 use workflow::WorkflowManager;
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
@@ -38,23 +56,56 @@ fn main() {
     // ✅ Terminal: Create Terminal Manager
     let terminal_manager = TerminalManagerState::new();
 
+    // ✅ Tasks: Create Task Manager
+    let task_manager = TaskManager::new();
+    let test_manager = TestManager::new();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(lsp_manager)
         .manage(git_manager)
         .manage(symbol_index) // ✅ IntelliJ Pro: Manage symbol index state
         .manage(terminal_manager) // ✅ Terminal: Manage terminal sessions
+        .manage(task_manager) // ✅ Tasks: Manage task runs
+        .manage(test_manager) // ✅ Testing: Manage test runs
+        .manage(WorkspaceWatcher::new()) // ✅ Watcher: External file changes
         .invoke_handler(tauri::generate_handler![
             fs_commands::get_current_dir,
             fs_commands::read_file,
             fs_commands::read_file_partial, // ✅ IntelliJ Pro: Lazy file loading
             fs_commands::read_file_chunk,   // ✅ IntelliJ Pro: Streaming large files
             fs_commands::write_file,
+            fs_commands::read_text_file,  // ✅ Editor: Encodings and line endings
+            fs_commands::write_text_file,
+            fs_commands::read_file_bytes, // ✅ Editor: Hex view
             fs_commands::read_dir,
+            file_tree::list_dir,      // ✅ Explorer: Lazy, gitignore-aware listing
+            file_tree::stat_entries,
+            workspace::open_workspace,  // ✅ Workspace: Multi-root workspace files
+            workspace::save_workspace,
+            remote::commands::remote_connect,  // ✅ Remote: Folders over SSH
+            remote::commands::remote_disconnect,
+            remote::commands::remote_terminal_execute,
+            remote::commands::remote_terminal_current_directory,
             fs_commands::create_file,
             fs_commands::delete_file,
             fs_commands::rename_file,
             fs_commands::get_file_metadata,
+            watcher::commands::watch_workspace,
+            hot_exit::commands::restore_session, // ✅ Hot exit: Crash recovery
+            hot_exit::commands::save_session,
+            hot_exit::commands::backup_buffer,
+            hot_exit::commands::discard_backup,
+            local_history::commands::local_history_list, // ✅ Local history
+            local_history::commands::local_history_diff,
+            local_history::commands::local_history_restore,
+            local_history::commands::local_history_put_label,
+            #[cfg(feature = "parsing")]
+            markdown::commands::markdown_render, // ✅ Markdown preview
+            #[cfg(feature = "parsing")]
+            markdown::commands::markdown_export_html,
+            watcher::commands::unwatch_workspace,
+            watcher::commands::merge_external_change,
             search_commands::search_in_files,
             lsp::commands::lsp_initialize,
             lsp::commands::lsp_get_completions,
@@ -65,6 +116,7 @@ fn main() {
             lsp::commands::lsp_shutdown,
             git::commands::git_set_repo_path,
             git::commands::git_status,
+            git::commands::git_root_statuses, // ✅ Workspace: Git summary per folder
             git::commands::git_list_branches,
             git::commands::git_current_branch,
             git::commands::git_stage_file,
@@ -77,6 +129,48 @@ fn main() {
             git::commands::git_log,
             git::commands::git_diff_file,
             git::commands::git_blame,
+            // ✅ Git remote, stash, merge/rebase and conflict commands
+            git::commands::git_progress,
+            git::commands::git_list_remotes,
+            git::commands::git_fetch,
+            git::commands::git_pull,
+            git::commands::git_push,
+            git::commands::git_merge_branch,
+            git::commands::git_rebase,
+            git::commands::git_rebase_continue,
+            git::commands::git_rebase_skip,
+            git::commands::git_rebase_commits,
+            git::commands::git_interactive_rebase,
+            git::commands::git_interactive_rebase_state,
+            git::commands::git_abort_operation,
+            git::commands::git_cherry_pick,
+            git::commands::git_stash_save,
+            git::commands::git_stash_list,
+            git::commands::git_stash_apply,
+            git::commands::git_stash_pop,
+            git::commands::git_stash_drop,
+            git::commands::git_list_tags,
+            git::commands::git_create_tag,
+            git::commands::git_delete_tag,
+            git::commands::git_push_tag,
+            git::commands::git_bisect_start,
+            git::commands::git_bisect_mark,
+            git::commands::git_bisect_reset,
+            git::commands::git_bisect_status,
+            git::commands::git_repo_state,
+            git::commands::git_resolve_conflict,
+            git::commands::git_accept_conflict_side,
+            git::commands::git_three_way_merge,
+            // ✅ Git hunk staging commands
+            git::commands::git_file_hunks,
+            git::commands::git_stage_hunks,
+            git::commands::git_unstage_hunks,
+            git::commands::git_discard_hunks,
+            // ✅ Git history and commit graph commands
+            git::commands::git_log_graph,
+            git::commands::git_commit_detail,
+            git::commands::git_compare_refs,
+            git::commands::git_file_history,
             // ✅ IntelliJ Pro: Background indexing commands
             indexer::commands::index_workspace,
             indexer::commands::search_symbols,
@@ -98,7 +192,32 @@ fn main() {
             database::commands::db_update_connection,
             database::commands::db_delete_connection,
             database::commands::db_test_connection,
+            database::commands::db_vault_status,
+            database::commands::db_unlock_vault,
+            database::commands::db_lock_vault,
+            database::commands::db_export_connections,
+            database::commands::db_import_connections,
             database::commands::db_execute_query,
+            database::commands::db_fetch_rows,
+            database::commands::db_close_cursor,
+            database::commands::db_export_query,
+            database::commands::db_get_schema,
+            database::commands::db_explain_query,
+            database::commands::db_open_table,
+            database::commands::db_preview_changes,
+            database::commands::db_apply_changes,
+            database::commands::db_query_history,
+            database::commands::db_delete_history_entry,
+            database::commands::db_clear_query_history,
+            database::commands::db_mongo_collections,
+            database::commands::db_mongo_execute,
+            database::commands::db_begin_transaction,
+            database::commands::db_commit_transaction,
+            database::commands::db_rollback_transaction,
+            database::commands::db_cancel_query,
+            database::commands::db_session_status,
+            database::commands::db_health_check,
+            database::commands::db_close_session,
             // ✅ Workflow Automation commands
             workflow::commands::workflow_list_presets,
             workflow::commands::workflow_start,
@@ -113,6 +232,22 @@ fn main() {
             terminal::commands::terminal_kill_process,
             terminal::commands::terminal_change_directory,
             terminal::commands::terminal_get_current_directory,
+            terminal::commands::terminal_list_commands,
+            terminal::commands::terminal_get_last_command,
+            terminal::commands::terminal_rerun_command,
+            // ✅ Task runner commands
+            tasks::commands::task_list,
+            tasks::commands::task_run,
+            tasks::commands::task_get_run,
+            tasks::commands::task_list_runs,
+            tasks::commands::task_cancel,
+            // ✅ Test explorer commands
+            testing::commands::test_discover,
+            testing::commands::test_run,
+            testing::commands::test_get_run,
+            testing::commands::test_list_runs,
+            testing::commands::test_cancel,
+            testing::commands::test_debug_target,
             // ✅ BerryCode CLI commands
             berrycode_commands::berrycode_init,
             berrycode_commands::berrycode_chat,
@@ -142,6 +277,22 @@ fn main() {
             let workflow_manager = WorkflowManager::new();
             app.manage(workflow_manager);
 
+            // ✅ Hot exit: Backups of unsaved buffers live in the app data directory
+            let hot_exit_dir = app
+                .path()
+                .app_data_dir()
+                .expect("Failed to get app data dir")
+                .join("hot_exit");
+            app.manage(BackupStore::new(hot_exit_dir));
+
+            // ✅ Local history: One compressed store per project next to the backups
+            let history_dir = app
+                .path()
+                .app_data_dir()
+                .expect("Failed to get app data dir")
+                .join("local_history");
+            app.manage(LocalHistory::new(history_dir));
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -0,0 +1,29 @@
+use super::render::{self, RenderedMarkdown};
+use crate::{remote, text_file};
+use std::path::Path;
+
+/// Render the content of the Markdown file at `path`, which may be unsaved
+#[tauri::command]
+pub async fn markdown_render(path: String, content: String) -> Result<RenderedMarkdown, String> {
+    tokio::task::spawn_blocking(move || render::render(&path, &content))
+        .await
+        .map_err(|e| format!("Failed to render Markdown: {}", e))
+}
+
+/// Write the rendered document to `target` as a standalone HTML page
+#[tauri::command]
+pub async fn markdown_export_html(path: String, content: String, target: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let rendered = render::render_export(&path, &content);
+        let fallback = Path::new(&path).file_stem().and_then(|s| s.to_str()).unwrap_or("Document");
+        let page = render::standalone_html(&rendered, fallback);
+        if remote::is_remote(&target) {
+            remote::write_atomic(&target, page.as_bytes())
+        } else {
+            text_file::write_atomic(Path::new(&target), page.as_bytes())
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to export HTML: {}", e))?
+    .map_err(|e| format!("Failed to export HTML: {}", e))
+}
@@ -0,0 +1,468 @@
+//! Mermaid-style flowcharts as plain SVG
+//!
+//! Supports the flowchart subset that covers most diagrams in READMEs:
+//! `graph`/`flowchart` with a direction, nodes with `[]`, `()`, `([])`,
+//! `(())`, `{}` and `>]` shapes, and `-->`, `---`, `-.->` and `==>` edges with
+//! optional `|labels|` or `-- labels -->`. Other diagram types are left to
+//! be shown as code.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const NODE_HEIGHT: f64 = 36.0;
+const RANK_GAP: f64 = 56.0;
+const NODE_GAP: f64 = 28.0;
+const MARGIN: f64 = 12.0;
+const CHAR_WIDTH: f64 = 7.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    TopDown,
+    LeftRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Rect,
+    Round,
+    Stadium,
+    Circle,
+    Diamond,
+    Flag,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    id: String,
+    label: String,
+    shape: Shape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    dashed: bool,
+    thick: bool,
+    arrow: bool,
+}
+
+#[derive(Debug, Default)]
+struct Flowchart {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Flowchart {
+    /// Index of a node, added on first mention. A later mention with a label
+    /// (e.g. `A[Start]` after a bare `A`) sets the label and shape.
+    fn node(&mut self, id: &str, label: Option<NodeLabel>) -> usize {
+        let index = match self.nodes.iter().position(|n| n.id == id) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(Node {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    shape: Shape::Rect,
+                });
+                self.nodes.len() - 1
+            }
+        };
+        if let Some((label, shape)) = label {
+            self.nodes[index].label = label;
+            self.nodes[index].shape = shape;
+        }
+        index
+    }
+}
+
+/// SVG for a flowchart block, or `None` if it is not a supported diagram
+pub fn render_svg(source: &str) -> Option<String> {
+    let (direction, chart) = parse(source)?;
+    if chart.nodes.is_empty() {
+        return None;
+    }
+    Some(layout_svg(direction, &chart))
+}
+
+fn parse(source: &str) -> Option<(Direction, Flowchart)> {
+    let mut lines = source
+        .lines()
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("%%"));
+
+    let mut header = lines.next()?.split_whitespace();
+    if !matches!(header.next()?, "graph" | "flowchart") {
+        return None;
+    }
+    let direction = match header.next().unwrap_or("TD") {
+        "LR" | "RL" => Direction::LeftRight,
+        _ => Direction::TopDown,
+    };
+
+    let mut chart = Flowchart::default();
+    for line in lines {
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+        if matches!(keyword, "subgraph" | "end" | "direction" | "classDef" | "class" | "style" | "linkStyle" | "click") {
+            continue;
+        }
+        parse_statement(line, &mut chart);
+    }
+    Some((direction, chart))
+}
+
+/// `A --> B[Label] -->|yes| C` and `A & B --> C`
+fn parse_statement(line: &str, chart: &mut Flowchart) {
+    let mut rest = line;
+    let mut previous: Vec<usize> = Vec::new();
+    let mut pending: Option<Edge> = None;
+    loop {
+        let mut group = Vec::new();
+        loop {
+            let Some((id, label, after)) = parse_node(rest) else {
+                return;
+            };
+            group.push(chart.node(id, label));
+            rest = after.trim_start();
+            match rest.strip_prefix('&') {
+                Some(after) => rest = after.trim_start(),
+                None => break,
+            }
+        }
+        if let Some(edge) = pending.take() {
+            for &from in &previous {
+                for &to in &group {
+                    chart.edges.push(Edge { from, to, ..edge.clone() });
+                }
+            }
+        }
+        previous = group;
+        match parse_edge(rest) {
+            Some((edge, after)) => {
+                pending = Some(edge);
+                rest = after.trim_start();
+            }
+            None => return,
+        }
+    }
+}
+
+/// Label and shape of a node mention such as `B{Ok?}`
+type NodeLabel = (String, Shape);
+
+fn parse_node(input: &str) -> Option<(&str, Option<NodeLabel>, &str)> {
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(input.len());
+    if end == 0 {
+        return None;
+    }
+    let (id, rest) = input.split_at(end);
+    let shapes = [
+        ("((", "))", Shape::Circle),
+        ("([", "])", Shape::Stadium),
+        ("[", "]", Shape::Rect),
+        ("(", ")", Shape::Round),
+        ("{", "}", Shape::Diamond),
+        (">", "]", Shape::Flag),
+    ];
+    for (open, close, shape) in shapes {
+        if let Some(inner) = rest.strip_prefix(open) {
+            let close_at = inner.find(close)?;
+            let label = inner[..close_at].trim().trim_matches('"').to_string();
+            return Some((id, Some((label, shape)), &inner[close_at + close.len()..]));
+        }
+    }
+    Some((id, None, rest))
+}
+
+fn parse_edge(input: &str) -> Option<(Edge, &str)> {
+    let arrows = [
+        ("-.->", true, false, true),
+        ("-.-", true, false, false),
+        ("==>", false, true, true),
+        ("===", false, true, false),
+        ("-->", false, false, true),
+        ("---", false, false, false),
+    ];
+    let mut edge = Edge {
+        from: 0,
+        to: 0,
+        label: None,
+        dashed: false,
+        thick: false,
+        arrow: true,
+    };
+
+    let mut rest = None;
+    for (arrow, dashed, thick, head) in arrows {
+        if let Some(after) = input.strip_prefix(arrow) {
+            (edge.dashed, edge.thick, edge.arrow) = (dashed, thick, head);
+            rest = Some(after.trim_start_matches(['-', '.', '=', '>']));
+            break;
+        }
+    }
+    let rest = match rest {
+        Some(rest) => rest,
+        // `-- label -->`
+        None => {
+            let inner = input.strip_prefix("--")?;
+            let (label, after) = ["-->", "---"].iter().find_map(|arrow| {
+                let at = inner.find(arrow)?;
+                Some((&inner[..at], &inner[at + arrow.len()..]))
+            })?;
+            edge.label = Some(label.trim().to_string());
+            edge.arrow = input.contains("-->");
+            after
+        }
+    };
+
+    // `-->|label|`
+    let rest = rest.trim_start();
+    if let Some(inner) = rest.strip_prefix('|') {
+        let end = inner.find('|')?;
+        edge.label = Some(inner[..end].trim().to_string());
+        return Some((edge, &inner[end + 1..]));
+    }
+    Some((edge, rest))
+}
+
+fn node_width(node: &Node) -> f64 {
+    let text = node.label.chars().count() as f64 * CHAR_WIDTH + 28.0;
+    match node.shape {
+        Shape::Diamond => text.max(60.0) * 1.4,
+        _ => text.max(60.0),
+    }
+}
+
+/// Edges that close a cycle, found by a depth-first walk in order of
+/// appearance; ranking ignores them so loops point back up the chart
+fn back_edges(chart: &Flowchart) -> Vec<bool> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Active,
+        Done,
+    }
+    fn walk(chart: &Flowchart, node: usize, state: &mut [Visit], back: &mut [bool]) {
+        state[node] = Visit::Active;
+        for (index, edge) in chart.edges.iter().enumerate().filter(|(_, e)| e.from == node) {
+            match state[edge.to] {
+                Visit::New => walk(chart, edge.to, state, back),
+                Visit::Active => back[index] = true,
+                Visit::Done => {}
+            }
+        }
+        state[node] = Visit::Done;
+    }
+
+    let mut state = vec![Visit::New; chart.nodes.len()];
+    let mut back = vec![false; chart.edges.len()];
+    for node in 0..chart.nodes.len() {
+        if state[node] == Visit::New {
+            walk(chart, node, &mut state, &mut back);
+        }
+    }
+    back
+}
+
+/// Rank of each node along the flow: the longest path from a source
+fn ranks(chart: &Flowchart) -> Vec<usize> {
+    let back = back_edges(chart);
+    let mut rank = vec![0usize; chart.nodes.len()];
+    // Without back edges the chart is acyclic, so this settles within n passes
+    for _ in 0..chart.nodes.len() {
+        let mut changed = false;
+        for (edge, _) in chart.edges.iter().zip(&back).filter(|(_, &back)| !back) {
+            if rank[edge.to] < rank[edge.from] + 1 {
+                rank[edge.to] = rank[edge.from] + 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    rank
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn layout_svg(direction: Direction, chart: &Flowchart) -> String {
+    let rank = ranks(chart);
+    let rank_count = rank.iter().max().map_or(0, |r| r + 1);
+    let mut by_rank: Vec<Vec<usize>> = vec![Vec::new(); rank_count];
+    for (index, &r) in rank.iter().enumerate() {
+        by_rank[r].push(index);
+    }
+
+    // Extent of each rank across the flow, and the widest node per rank
+    let across = |index: usize| match direction {
+        Direction::TopDown => node_width(&chart.nodes[index]),
+        Direction::LeftRight => NODE_HEIGHT,
+    };
+    let along = |index: usize| match direction {
+        Direction::TopDown => NODE_HEIGHT,
+        Direction::LeftRight => node_width(&chart.nodes[index]),
+    };
+    let rank_extent: Vec<f64> = by_rank
+        .iter()
+        .map(|nodes| nodes.iter().map(|&i| across(i)).sum::<f64>() + NODE_GAP * nodes.len().saturating_sub(1) as f64)
+        .collect();
+    let rank_depth: Vec<f64> = by_rank
+        .iter()
+        .map(|nodes| nodes.iter().map(|&i| along(i)).fold(0.0, f64::max))
+        .collect();
+    let total_across = rank_extent.iter().cloned().fold(0.0, f64::max);
+
+    // Centers of the nodes as (along, across)
+    let mut centers: HashMap<usize, (f64, f64)> = HashMap::new();
+    let mut offset_along = MARGIN;
+    for (r, nodes) in by_rank.iter().enumerate() {
+        let mut offset_across = MARGIN + (total_across - rank_extent[r]) / 2.0;
+        for &index in nodes {
+            let size = across(index);
+            centers.insert(index, (offset_along + rank_depth[r] / 2.0, offset_across + size / 2.0));
+            offset_across += size + NODE_GAP;
+        }
+        offset_along += rank_depth[r] + RANK_GAP;
+    }
+    let total_along = offset_along - RANK_GAP + MARGIN;
+    let total_across = total_across + 2.0 * MARGIN;
+
+    let point = |index: usize| {
+        let (along, across) = centers[&index];
+        match direction {
+            Direction::TopDown => (across, along),
+            Direction::LeftRight => (along, across),
+        }
+    };
+    let (width, height) = match direction {
+        Direction::TopDown => (total_across, total_along),
+        Direction::LeftRight => (total_along, total_across),
+    };
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" class="berry-diagram" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.0} {height:.0}" font-family="sans-serif" font-size="13"><defs><marker id="berry-arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="7" markerHeight="7" orient="auto-start-reverse"><path d="M0,0 L10,5 L0,10 z" fill="#A9B7C6"/></marker></defs>"##
+    );
+
+    // Edges from the border of one node to the border of the other
+    for edge in &chart.edges {
+        let ((x1, y1), (x2, y2)) = (point(edge.from), point(edge.to));
+        let (half_from, half_to) = match direction {
+            Direction::TopDown => (NODE_HEIGHT / 2.0, NODE_HEIGHT / 2.0),
+            Direction::LeftRight => (node_width(&chart.nodes[edge.from]) / 2.0, node_width(&chart.nodes[edge.to]) / 2.0),
+        };
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length = (dx * dx + dy * dy).sqrt().max(1.0);
+        let (ux, uy) = (dx / length, dy / length);
+        let trim = |half: f64| match direction {
+            Direction::TopDown => half / uy.abs().max(0.3),
+            Direction::LeftRight => half / ux.abs().max(0.3),
+        };
+        let (start, end) = (trim(half_from).min(length / 2.0), trim(half_to).min(length / 2.0));
+        let (sx, sy, ex, ey) = (x1 + ux * start, y1 + uy * start, x2 - ux * end, y2 - uy * end);
+        let dash = if edge.dashed { r#" stroke-dasharray="5,4""# } else { "" };
+        let stroke_width = if edge.thick { 3 } else { 1 };
+        let marker = if edge.arrow { r#" marker-end="url(#berry-arrow)""# } else { "" };
+        let _ = write!(
+            svg,
+            r##"<line x1="{sx:.1}" y1="{sy:.1}" x2="{ex:.1}" y2="{ey:.1}" stroke="#A9B7C6" stroke-width="{stroke_width}"{dash}{marker}/>"##
+        );
+        if let Some(label) = edge.label.as_deref().filter(|l| !l.is_empty()) {
+            let (mx, my) = ((sx + ex) / 2.0, (sy + ey) / 2.0);
+            let w = label.chars().count() as f64 * CHAR_WIDTH + 8.0;
+            let _ = write!(
+                svg,
+                r##"<rect x="{:.1}" y="{:.1}" width="{w:.1}" height="18" fill="#2B2D30"/><text x="{mx:.1}" y="{:.1}" text-anchor="middle" fill="#A9B7C6">{}</text>"##,
+                mx - w / 2.0,
+                my - 9.0,
+                my + 4.5,
+                escape(label)
+            );
+        }
+    }
+
+    for (index, node) in chart.nodes.iter().enumerate() {
+        let (cx, cy) = point(index);
+        let w = node_width(node);
+        let h = NODE_HEIGHT;
+        let (x, y) = (cx - w / 2.0, cy - h / 2.0);
+        let style = r##"fill="#313335" stroke="#6897BB" stroke-width="1.5""##;
+        let _ = match node.shape {
+            Shape::Rect => write!(svg, r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" {style}/>"#),
+            Shape::Round => write!(svg, r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="8" {style}/>"#),
+            Shape::Stadium => write!(svg, r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="{:.1}" {style}/>"#, h / 2.0),
+            Shape::Circle => write!(svg, r#"<ellipse cx="{cx:.1}" cy="{cy:.1}" rx="{:.1}" ry="{:.1}" {style}/>"#, w / 2.0, h / 2.0),
+            Shape::Diamond => write!(
+                svg,
+                r#"<polygon points="{cx:.1},{y:.1} {:.1},{cy:.1} {cx:.1},{:.1} {x:.1},{cy:.1}" {style}/>"#,
+                x + w,
+                y + h
+            ),
+            Shape::Flag => write!(
+                svg,
+                r#"<polygon points="{x:.1},{y:.1} {:.1},{y:.1} {:.1},{cy:.1} {:.1},{:.1} {x:.1},{:.1} {:.1},{cy:.1}" {style}/>"#,
+                x + w,
+                x + w,
+                x + w,
+                y + h,
+                y + h,
+                x + 10.0
+            ),
+        };
+        let _ = write!(
+            svg,
+            r##"<text x="{cx:.1}" y="{:.1}" text-anchor="middle" fill="#DFE1E5">{}</text>"##,
+            cy + 4.5,
+            escape(&node.label)
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flowchart() {
+        let (direction, chart) = parse(
+            "flowchart LR\n  A[Start] --> B{Ok?}\n  B -->|yes| C([Done]); B -- no --> A\n  %% comment\n  C & A -.-> D",
+        )
+        .unwrap();
+        assert_eq!(direction, Direction::LeftRight);
+        let labels: Vec<&str> = chart.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, vec!["Start", "Ok?", "Done", "D"]);
+        assert_eq!(chart.nodes[1].shape, Shape::Diamond);
+        assert_eq!(chart.nodes[2].shape, Shape::Stadium);
+        let edges: Vec<(usize, usize, Option<&str>)> =
+            chart.edges.iter().map(|e| (e.from, e.to, e.label.as_deref())).collect();
+        assert_eq!(
+            edges,
+            vec![(0, 1, None), (1, 2, Some("yes")), (1, 0, Some("no")), (2, 3, None), (0, 3, None)]
+        );
+        assert!(chart.edges[3].dashed);
+        assert_eq!(ranks(&chart), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg("graph TD\nA[<b>x</b>] --> B(Two)").unwrap();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        assert!(svg.contains("&lt;b&gt;x&lt;/b&gt;"));
+        assert!(svg.contains(">Two</text>"));
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(render_svg("sequenceDiagram\nA->>B: hi").is_none());
+    }
+}
@@ -0,0 +1,13 @@
+//! Markdown preview
+//!
+//! Renders Markdown documents for the preview pane beside the editor:
+//! highlighted code blocks, flowchart blocks drawn as SVG, images next to
+//! the document embedded and relative links resolved to files. The same
+//! rendering, with relative links left as written, is exported as a
+//! standalone HTML page.
+
+pub mod commands;
+pub mod diagram;
+pub mod render;
+
+pub use render::RenderedMarkdown;
@@ -0,0 +1,309 @@
+//! Markdown to HTML for the preview pane and for export
+
+use super::diagram;
+use crate::remote;
+use base64::Engine;
+use comrak::adapters::SyntaxHighlighterAdapter;
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::plugins::syntect::SyntectAdapter;
+use comrak::{format_html_with_plugins, parse_document, Arena, Options, Plugins};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Larger images are linked instead of embedded
+const MAX_INLINE_IMAGE: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    /// Text of the first heading
+    pub title: Option<String>,
+}
+
+/// Highlights fenced code with syntect and draws flowchart blocks as SVG
+struct CodeBlocks {
+    syntect: SyntectAdapter,
+}
+
+impl SyntaxHighlighterAdapter for CodeBlocks {
+    fn write_highlighted(&self, output: &mut dyn Write, lang: Option<&str>, code: &str) -> io::Result<()> {
+        if lang == Some("mermaid") {
+            if let Some(svg) = diagram::render_svg(code) {
+                return output.write_all(svg.as_bytes());
+            }
+        }
+        self.syntect.write_highlighted(output, lang, code)
+    }
+
+    fn write_pre_tag(&self, output: &mut dyn Write, attributes: HashMap<String, String>) -> io::Result<()> {
+        self.syntect.write_pre_tag(output, attributes)
+    }
+
+    fn write_code_tag(&self, output: &mut dyn Write, attributes: HashMap<String, String>) -> io::Result<()> {
+        self.syntect.write_code_tag(output, attributes)
+    }
+}
+
+// Loading the syntax and theme sets is slow, so it happens once
+static CODE_BLOCKS: Lazy<CodeBlocks> = Lazy::new(|| CodeBlocks {
+    syntect: SyntectAdapter::new(Some("base16-ocean.dark")),
+});
+
+fn options() -> Options<'static> {
+    let mut options = Options::default();
+    options.extension.strikethrough = true;
+    options.extension.table = true;
+    options.extension.autolink = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.extension.header_ids = Some(String::new());
+    // data-sourcepos on block elements lets the preview follow the editor
+    options.render.sourcepos = true;
+    options.render.unsafe_ = false;
+    options
+}
+
+/// Render the Markdown file at `path`, local or `ssh://`, for the preview.
+/// Relative images are embedded and relative links point at the files next
+/// to it.
+pub fn render(path: &str, source: &str) -> RenderedMarkdown {
+    render_with(path, source, true)
+}
+
+/// Render for an exported page: images are embedded as in the preview, but
+/// relative links stay as written so the page does not carry local or
+/// `ssh://` paths
+pub fn render_export(path: &str, source: &str) -> RenderedMarkdown {
+    render_with(path, source, false)
+}
+
+fn render_with(path: &str, source: &str, resolve_links: bool) -> RenderedMarkdown {
+    let arena = Arena::new();
+    let options = options();
+    let root = parse_document(&arena, source, &options);
+
+    if let Some(base) = Base::of(path) {
+        for node in root.descendants() {
+            match &mut node.data.borrow_mut().value {
+                NodeValue::Image(link) => {
+                    if let Some(uri) = base.inline_image(&link.url) {
+                        link.url = uri;
+                    }
+                }
+                NodeValue::Link(link) if resolve_links => {
+                    if let Some(target) = base.resolve_link(&link.url) {
+                        link.url = target;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let title = root
+        .descendants()
+        .find(|node| matches!(node.data.borrow().value, NodeValue::Heading(_)))
+        .map(plain_text)
+        .filter(|title| !title.is_empty());
+
+    let mut plugins = Plugins::default();
+    plugins.render.codefence_syntax_highlighter = Some(&*CODE_BLOCKS);
+    let mut html = Vec::new();
+    // Writing into a Vec cannot fail
+    let _ = format_html_with_plugins(root, &options, &mut html, &plugins);
+    RenderedMarkdown {
+        html: String::from_utf8_lossy(&html).into_owned(),
+        title,
+    }
+}
+
+fn plain_text<'a>(node: &'a AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|n| match &n.data.borrow().value {
+            NodeValue::Text(text) => Some(text.clone()),
+            NodeValue::Code(code) => Some(code.literal.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A path relative to the document, without query and fragment; `None` for
+/// URLs, absolute paths and in-page anchors
+fn relative_target(url: &str) -> Option<(&str, &str)> {
+    let split = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split);
+    let has_scheme = path.split('/').next().is_some_and(|first| first.contains(':'));
+    if path.is_empty() || path.starts_with('/') || path.starts_with('\\') || has_scheme {
+        return None;
+    }
+    Some((path, suffix))
+}
+
+/// Join without touching the file system, resolving `.` and `..`
+fn join(base: &Path, relative: &str) -> PathBuf {
+    let mut joined = base.to_path_buf();
+    for component in Path::new(&relative.replace("%20", " ")).components() {
+        match component {
+            Component::ParentDir => {
+                joined.pop();
+            }
+            Component::CurDir => {}
+            other => joined.push(other),
+        }
+    }
+    joined
+}
+
+/// The folder of a document, which relative references point into
+struct Base<'a> {
+    /// `ssh://[user@]host[:port]` for remote documents, empty for local ones
+    authority: &'a str,
+    dir: PathBuf,
+}
+
+impl<'a> Base<'a> {
+    fn of(path: &'a str) -> Option<Self> {
+        let (authority, local) = if remote::is_remote(path) {
+            let start = "ssh://".len();
+            path.split_at(start + path[start..].find('/')?)
+        } else {
+            ("", path)
+        };
+        Some(Base {
+            authority,
+            dir: Path::new(local).parent()?.to_path_buf(),
+        })
+    }
+
+    fn resolve(&self, relative: &str) -> String {
+        format!("{}{}", self.authority, join(&self.dir, relative).display())
+    }
+
+    fn resolve_link(&self, url: &str) -> Option<String> {
+        let (path, suffix) = relative_target(url)?;
+        // Fragments stay for jumping to headings; queries mean nothing on disk
+        let fragment = suffix.find('#').map_or("", |i| &suffix[i..]);
+        Some(format!("{}{}", self.resolve(path), fragment))
+    }
+
+    fn inline_image(&self, url: &str) -> Option<String> {
+        let (path, _) = relative_target(url)?;
+        let target = self.resolve(path);
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        let mime = match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => return None,
+        };
+        let bytes = if self.authority.is_empty() {
+            if std::fs::metadata(&target).ok()?.len() > MAX_INLINE_IMAGE {
+                return None;
+            }
+            std::fs::read(&target).ok()?
+        } else {
+            remote::read_bytes(&target).ok()?
+        };
+        if bytes.len() as u64 > MAX_INLINE_IMAGE {
+            return None;
+        }
+        Some(format!(
+            "data:{};base64,{}",
+            mime,
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+const EXPORT_STYLE: &str = r#"
+body { margin: 0; background: #1E1F22; color: #BCBEC4; font: 15px/1.6 -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+article { max-width: 880px; margin: 0 auto; padding: 32px 24px; }
+h1, h2 { border-bottom: 1px solid #393B40; padding-bottom: 0.3em; }
+h1, h2, h3, h4, h5, h6 { color: #DFE1E5; }
+a { color: #589DF6; }
+code { font-family: "JetBrains Mono", Menlo, Consolas, monospace; font-size: 0.9em; background: #2B2D30; padding: 0.15em 0.35em; border-radius: 3px; }
+pre { padding: 12px 16px; border-radius: 6px; overflow-x: auto; }
+pre code { background: none; padding: 0; }
+blockquote { margin: 0; padding: 0 1em; color: #868A91; border-left: 3px solid #393B40; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #393B40; padding: 6px 12px; }
+img { max-width: 100%; }
+svg.berry-diagram { display: block; margin: 12px 0; max-width: 100%; height: auto; }
+"#;
+
+/// A standalone page with the rendered document and its styles
+pub fn standalone_html(rendered: &RenderedMarkdown, fallback_title: &str) -> String {
+    let title = rendered.title.as_deref().unwrap_or(fallback_title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<article>\n{}</article>\n</body>\n</html>\n",
+        escape(title),
+        EXPORT_STYLE,
+        rendered.html
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_blocks() {
+        let source = "# Guide `v2`\n\n<script>alert(1)</script>\n\n```rust\nfn main() {}\n```\n\n```mermaid\ngraph LR\nA --> B\n```\n";
+        let rendered = render("/p/README.md", source);
+        assert_eq!(rendered.title.as_deref(), Some("Guide v2"));
+        assert!(rendered.html.contains(r#"data-sourcepos="1:1-1:12""#));
+        assert!(rendered.html.contains(r#"id="guide-v2""#));
+        assert!(!rendered.html.contains("<script>"));
+        // syntect emits inline styles for highlighted tokens
+        assert!(rendered.html.contains("<span style="));
+        assert!(rendered.html.contains("<svg"));
+    }
+
+    #[test]
+    fn test_relative_images_and_links() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("img")).unwrap();
+        std::fs::write(dir.path().join("img/logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let doc = dir.path().join("docs/guide.md");
+
+        let source = "![logo](../img/logo.png) ![web](https://x.dev/a.png) [api](api.md#usage) [top](#top) [site](https://x.dev)";
+        let html = render(doc.to_str().unwrap(), source).html;
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+        assert!(html.contains("src=\"https://x.dev/a.png\""));
+        let api = dir.path().join("docs/api.md");
+        assert!(html.contains(&format!("href=\"{}#usage\"", api.display())));
+        assert!(html.contains("href=\"#top\""));
+        assert!(html.contains("href=\"https://x.dev\""));
+
+        let html = render("ssh://me@box:2222/srv/app/docs/guide.md", "[api](../api.md#usage)").html;
+        assert!(html.contains("href=\"ssh://me@box:2222/srv/app/api.md#usage\""));
+
+        // Exported pages keep relative links but still embed images
+        let html = render_export(doc.to_str().unwrap(), source).html;
+        assert!(html.contains("href=\"api.md#usage\""));
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+        let html = render_export("ssh://me@box:2222/srv/app/docs/guide.md", "[api](../api.md#usage)").html;
+        assert!(html.contains("href=\"../api.md#usage\""));
+    }
+
+    #[test]
+    fn test_standalone_html() {
+        let rendered = RenderedMarkdown {
+            html: "<p>hi</p>\n".to_string(),
+            title: None,
+        };
+        let page = standalone_html(&rendered, "a<b>.md");
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>a&lt;b&gt;.md</title>"));
+        assert!(page.contains("<article>\n<p>hi</p>\n</article>"));
+    }
+}
@@ -9,15 +9,26 @@
 //! Inspired by portable-pty (WezTerm) and zellij architectures.
 
 use anyhow::{anyhow, Result};
-use portable_pty::{Child, CommandBuilder, PtySize, native_pty_system};
-use std::collections::HashMap;
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use uuid::Uuid;
 
+use crate::shell_integration::{OscParser, ShellEvent, ShellKind};
+
+/// Upper bound for waiting on a command's finish marker
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Finished commands kept per session
+const MAX_COMMAND_RECORDS: usize = 500;
+
+/// Output captured per command before truncation
+const MAX_OUTPUT_BYTES: usize = 1_000_000;
+
 /// Process status
 #[derive(Debug, Clone, PartialEq)]
 pub enum ProcessStatus {
@@ -36,6 +47,27 @@ pub struct BackgroundProcess {
     pub output_buffer: Vec<String>,
 }
 
+/// A command delimited by shell integration markers
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    pub id: String,
+    pub command: String,
+    /// Working directory the command was started in
+    pub cwd: Option<PathBuf>,
+    pub output: String,
+    /// None if the command had not finished when the timeout expired
+    pub exit_code: Option<i32>,
+    /// Unix timestamp (milliseconds)
+    pub started_at: i64,
+    pub duration_ms: u64,
+}
+
+impl CommandRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
 /// Persistent terminal session
 ///
 /// Maintains state across multiple command executions.
@@ -47,11 +79,17 @@ pub struct PersistentTerminal {
     /// Working directory
     pub working_dir: PathBuf,
 
+    /// Shell running in the PTY
+    pub shell: ShellKind,
+
+    /// PTY master (kept alive for the lifetime of the session)
+    _pty_master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
+
     /// PTY writer
     pty_writer: Arc<Mutex<Box<dyn Write + Send>>>,
 
-    /// PTY reader
-    pty_reader: Arc<Mutex<Box<dyn Read + Send>>>,
+    /// Events decoded from PTY output by the reader thread
+    shell_events: Arc<Mutex<mpsc::UnboundedReceiver<ShellEvent>>>,
 
     /// PTY child process
     pty_child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
@@ -62,13 +100,27 @@ pub struct PersistentTerminal {
     /// Command history
     command_history: Arc<RwLock<Vec<String>>>,
 
+    /// Finished commands with output, exit code and timing
+    command_records: Arc<RwLock<VecDeque<CommandRecord>>>,
+
+    /// Working directory as last reported by the shell
+    current_dir: Arc<RwLock<Option<PathBuf>>>,
+
     /// Environment variables
     env_vars: Arc<RwLock<HashMap<String, String>>>,
+
+    /// Directory holding the generated shell init script
+    script_dir: PathBuf,
 }
 
 impl PersistentTerminal {
-    /// Create a new persistent terminal session
+    /// Create a new persistent terminal session using the user's shell
     pub fn new(working_dir: PathBuf) -> Result<Self> {
+        Self::with_shell(working_dir, ShellKind::detect())
+    }
+
+    /// Create a new persistent terminal session with shell integration for `shell`
+    pub fn with_shell(working_dir: PathBuf, shell: ShellKind) -> Result<Self> {
         let session_id = Uuid::new_v4().to_string();
 
         // Get native PTY system
@@ -82,15 +134,16 @@ impl PersistentTerminal {
             pixel_height: 0,
         })?;
 
-        // Spawn shell process
-        let mut cmd = CommandBuilder::new("bash");
-        cmd.cwd(&working_dir);
+        // Spawn shell process with prompt markers installed
+        let script_dir = std::env::temp_dir().join(format!("berry-shell-{}", session_id));
+        let launch = shell.prepare_launch(&script_dir)?;
 
-        // Add initialization commands to make shell more predictable
-        cmd.args(&[
-            "-c",
-            "PS1='$ '; unset PROMPT_COMMAND; exec bash --norc --noprofile",
-        ]);
+        let mut cmd = CommandBuilder::new(launch.program);
+        cmd.args(&launch.args);
+        for (key, value) in &launch.env {
+            cmd.env(key, value);
+        }
+        cmd.cwd(&working_dir);
 
         let child = pty_pair.slave.spawn_command(cmd)?;
 
@@ -98,24 +151,64 @@ impl PersistentTerminal {
         let reader = pty_pair.master.try_clone_reader()?;
         let writer = pty_pair.master.take_writer()?;
 
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Self::spawn_reader(&session_id, reader, event_tx)?;
+
         tracing::info!(
-            "Created persistent terminal session: {} in {:?}",
+            "Created persistent terminal session: {} in {:?} ({:?})",
             session_id,
-            working_dir
+            working_dir,
+            shell
         );
 
         Ok(Self {
             session_id,
             working_dir,
+            shell,
+            _pty_master: Arc::new(Mutex::new(pty_pair.master)),
             pty_writer: Arc::new(Mutex::new(writer)),
-            pty_reader: Arc::new(Mutex::new(reader)),
+            shell_events: Arc::new(Mutex::new(event_rx)),
             pty_child: Arc::new(Mutex::new(child)),
             background_processes: Arc::new(RwLock::new(HashMap::new())),
             command_history: Arc::new(RwLock::new(Vec::new())),
+            command_records: Arc::new(RwLock::new(VecDeque::new())),
+            current_dir: Arc::new(RwLock::new(None)),
             env_vars: Arc::new(RwLock::new(HashMap::new())),
+            script_dir: launch.script_dir,
         })
     }
 
+    /// Read the PTY on a dedicated thread and forward decoded shell events
+    fn spawn_reader(
+        session_id: &str,
+        mut reader: Box<dyn Read + Send>,
+        event_tx: mpsc::UnboundedSender<ShellEvent>,
+    ) -> Result<()> {
+        std::thread::Builder::new()
+            .name(format!("pty-reader-{}", session_id))
+            .spawn(move || {
+                let mut parser = OscParser::new();
+                let mut buf = [0u8; 4096];
+
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) => break, // EOF
+                        Ok(n) => {
+                            for event in parser.feed(&buf[..n]) {
+                                if event_tx.send(event).is_err() {
+                                    return; // Session dropped
+                                }
+                            }
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                        Err(_) => break,
+                    }
+                }
+            })?;
+
+        Ok(())
+    }
+
     /// Execute a command in the persistent session
     ///
     /// Unlike std::process::Command, this maintains state:
@@ -123,9 +216,28 @@ impl PersistentTerminal {
     /// - Environment variables persist
     /// - Background processes continue running
     pub async fn execute(&self, command: &str) -> Result<String> {
+        Ok(self.run_command(command).await?.output)
+    }
+
+    /// Execute a command and return its full record (output, exit code, duration)
+    pub async fn run_command(&self, command: &str) -> Result<CommandRecord> {
         // Add to history
         self.command_history.write().await.push(command.to_string());
 
+        // Hold the event stream for the whole round trip so commands don't interleave
+        let mut events = self.shell_events.lock().await;
+
+        // Drop output produced since the previous command (prompts, background jobs)
+        while let Ok(event) = events.try_recv() {
+            if let ShellEvent::Cwd(dir) = event {
+                *self.current_dir.write().await = Some(PathBuf::from(dir));
+            }
+        }
+
+        let cwd = self.current_dir.read().await.clone();
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let start = Instant::now();
+
         // Write command to PTY
         let mut writer = self.pty_writer.lock().await;
         let command_bytes = format!("{}\n", command).into_bytes();
@@ -133,17 +245,66 @@ impl PersistentTerminal {
         writer.flush()?;
         drop(writer);
 
-        // Read output with timeout
-        let output = self.read_output_with_timeout(Duration::from_secs(10)).await?;
+        let (output, exit_code) = self.read_command_output(&mut events, COMMAND_TIMEOUT).await?;
+        drop(events);
+
+        let record = CommandRecord {
+            id: Uuid::new_v4().to_string(),
+            command: command.to_string(),
+            cwd,
+            output,
+            exit_code,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+        };
 
         tracing::debug!(
-            "Executed command '{}' in session {}: {} bytes output",
+            "Executed command '{}' in session {}: exit {:?}, {} ms, {} bytes output",
             command,
             self.session_id,
-            output.len()
+            record.exit_code,
+            record.duration_ms,
+            record.output.len()
         );
 
-        Ok(output)
+        let mut records = self.command_records.write().await;
+        records.push_back(record.clone());
+        while records.len() > MAX_COMMAND_RECORDS {
+            records.pop_front();
+        }
+
+        Ok(record)
+    }
+
+    /// Re-run a previously recorded command
+    pub async fn rerun_command(&self, record_id: &str) -> Result<CommandRecord> {
+        let command = self
+            .get_command_record(record_id)
+            .await
+            .map(|record| record.command)
+            .ok_or_else(|| anyhow!("Command not found: {}", record_id))?;
+
+        self.run_command(&command).await
+    }
+
+    /// Get a recorded command by ID
+    pub async fn get_command_record(&self, record_id: &str) -> Option<CommandRecord> {
+        self.command_records
+            .read()
+            .await
+            .iter()
+            .find(|record| record.id == record_id)
+            .cloned()
+    }
+
+    /// Get all recorded commands (oldest first)
+    pub async fn get_command_records(&self) -> Vec<CommandRecord> {
+        self.command_records.read().await.iter().cloned().collect()
+    }
+
+    /// Get the most recently finished command
+    pub async fn last_command_record(&self) -> Option<CommandRecord> {
+        self.command_records.read().await.back().cloned()
     }
 
     /// Execute a command in background (for long-running processes)
@@ -241,9 +402,8 @@ impl PersistentTerminal {
         let cd_command = format!("cd {}", path);
         self.execute(&cd_command).await?;
 
-        // Update internal working_dir tracking
-        let pwd_output = self.execute("pwd").await?;
-        let new_dir = PathBuf::from(pwd_output.trim());
+        // The shell reports its new directory with the finish marker
+        let new_dir = self.get_cwd().await?;
 
         tracing::info!(
             "Changed directory in session {} to {:?}",
@@ -271,14 +431,19 @@ impl PersistentTerminal {
 
     /// Get current working directory
     pub async fn get_cwd(&self) -> Result<PathBuf> {
+        if let Some(dir) = self.current_dir.read().await.clone() {
+            return Ok(dir);
+        }
+
+        // No command has finished yet - ask the shell directly
         let output = self.execute("pwd").await?;
 
-        // Parse output which may contain command echo and prompt
-        // Format: "pwd\r\n/tmp\r\nbash-3.2$" or just "/tmp"
-        // Find the line that looks like a path (starts with /)
-        let lines: Vec<&str> = output.lines().collect();
+        if let Some(dir) = self.current_dir.read().await.clone() {
+            return Ok(dir);
+        }
 
-        let path = lines.iter()
+        let path = output
+            .lines()
             .map(|line| line.trim())
             .find(|line| line.starts_with('/'))
             .unwrap_or_else(|| output.trim());
@@ -291,42 +456,50 @@ impl PersistentTerminal {
         self.command_history.read().await.clone()
     }
 
-    /// Read output from PTY with timeout
-    async fn read_output_with_timeout(&self, _timeout: Duration) -> Result<String> {
-        // For now, use a simple blocking read
-        // TODO: Implement proper async reading with timeout
-        let mut reader = self.pty_reader.lock().await;
-        let mut output = Vec::new();
-        let mut buf = [0u8; 4096];
-
-        // Read with a simple timeout (blocking)
-        std::thread::sleep(Duration::from_millis(500));
+    /// Collect a command's output between the `C` (output start) and `D`
+    /// (finished) markers. Returns the exit code, or None on timeout.
+    async fn read_command_output(
+        &self,
+        events: &mut mpsc::UnboundedReceiver<ShellEvent>,
+        timeout: Duration,
+    ) -> Result<(String, Option<i32>)> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut output = String::new();
+        let mut in_output = false;
 
         loop {
-            match reader.read(&mut buf) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    output.extend_from_slice(&buf[..n]);
-                    let text = String::from_utf8_lossy(&output);
-
-                    // Simple heuristic: stop after seeing prompt
-                    if text.contains("$ ") || text.ends_with("$ ") {
-                        break;
+            let event = match tokio::time::timeout_at(deadline, events.recv()).await {
+                Ok(Some(event)) => event,
+                Ok(None) => {
+                    return Err(anyhow!("Terminal session {} closed", self.session_id));
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "Command in session {} did not finish within {:?}",
+                        self.session_id,
+                        timeout
+                    );
+                    return Ok((normalize_output(&output), None));
+                }
+            };
+
+            match event {
+                // Markers from an earlier prompt are ignored until our command starts
+                ShellEvent::OutputStart => in_output = true,
+                ShellEvent::Output(text) if in_output => {
+                    if output.len() < MAX_OUTPUT_BYTES {
+                        output.push_str(&text);
                     }
                 }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    break; // No more data available
+                ShellEvent::CommandFinished { exit_code } if in_output => {
+                    return Ok((normalize_output(&output), exit_code));
                 }
-                Err(e) => return Err(anyhow!("Failed to read from PTY: {}", e)),
-            }
-
-            // Prevent infinite loop
-            if output.len() > 1_000_000 {
-                break;
+                ShellEvent::Cwd(dir) => {
+                    *self.current_dir.write().await = Some(PathBuf::from(dir));
+                }
+                _ => {}
             }
         }
-
-        Ok(String::from_utf8_lossy(&output).to_string())
     }
 
     /// Extract PID from bash background job output
@@ -361,7 +534,12 @@ impl PersistentTerminal {
         }
 
         // Send exit to shell
-        let _ = self.execute("exit").await;
+        let mut writer = self.pty_writer.lock().await;
+        let _ = writer.write_all(b"exit\n");
+        let _ = writer.flush();
+        drop(writer);
+
+        let _ = std::fs::remove_dir_all(&self.script_dir);
 
         tracing::info!("Terminated persistent terminal session {}", self.session_id);
 
@@ -369,6 +547,11 @@ impl PersistentTerminal {
     }
 }
 
+/// PTY output uses CRLF line endings
+fn normalize_output(output: &str) -> String {
+    output.replace("\r\n", "\n")
+}
+
 /// Terminal session manager
 ///
 /// Manages multiple persistent terminal sessions.
@@ -482,4 +665,48 @@ mod tests {
 
         manager.terminate_session(&session_id).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_exit_code_and_duration_recorded() {
+        let terminal =
+            PersistentTerminal::with_shell(env::current_dir().unwrap(), ShellKind::Bash).unwrap();
+
+        let ok = terminal.run_command("true").await.unwrap();
+        assert_eq!(ok.exit_code, Some(0));
+        assert!(ok.succeeded());
+
+        let failed = terminal.run_command("(exit 3)").await.unwrap();
+        assert_eq!(failed.exit_code, Some(3));
+        assert!(!failed.succeeded());
+
+        let slow = terminal.run_command("sleep 0.2").await.unwrap();
+        assert!(slow.duration_ms >= 200);
+
+        let last = terminal.last_command_record().await.unwrap();
+        assert_eq!(last.id, slow.id);
+        assert_eq!(terminal.get_command_records().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_output_excludes_command_echo() {
+        let terminal =
+            PersistentTerminal::with_shell(env::current_dir().unwrap(), ShellKind::Bash).unwrap();
+
+        let output = terminal.execute("echo result-$((1 + 2))").await.unwrap();
+        assert_eq!(output.trim(), "result-3");
+    }
+
+    #[tokio::test]
+    async fn test_rerun_command() {
+        let terminal =
+            PersistentTerminal::with_shell(env::current_dir().unwrap(), ShellKind::Bash).unwrap();
+
+        let first = terminal.run_command("echo again").await.unwrap();
+        let second = terminal.rerun_command(&first.id).await.unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(second.command, "echo again");
+        assert_eq!(second.output.trim(), "again");
+        assert!(terminal.rerun_command("missing").await.is_err());
+    }
 }
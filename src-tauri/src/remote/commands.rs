@@ -0,0 +1,57 @@
+use super::ops;
+use crate::terminal::TerminalCommandResponse;
+use std::time::Instant;
+
+/// Connect to a remote folder (`[user@]host:path` or `ssh://host/path`) and
+/// return its `ssh://` URI for the workspace
+#[tauri::command]
+pub async fn remote_connect(target: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || ops::connect_folder(&target))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// Close the connection used by a remote folder
+#[tauri::command]
+pub async fn remote_disconnect(uri: String) -> Result<(), String> {
+    let uri = super::RemoteUri::parse(&uri).map_err(|e| e.to_string())?;
+    super::disconnect(&uri.authority);
+    Ok(())
+}
+
+/// Run a terminal command in a remote project
+#[tauri::command]
+pub async fn remote_terminal_execute(
+    project_path: String,
+    command: String,
+    background: Option<bool>,
+) -> Result<TerminalCommandResponse, String> {
+    if background.unwrap_or(false) {
+        return Err("Background commands are not supported in remote terminals".to_string());
+    }
+    let started = Instant::now();
+    let result = tokio::task::spawn_blocking(move || ops::run_command(&project_path, &command))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to execute command: {:#}", e))?;
+
+    Ok(TerminalCommandResponse {
+        success: result.exit_code == 0,
+        output: result.output,
+        process_id: None,
+        command_id: None,
+        exit_code: Some(result.exit_code),
+        duration_ms: Some(started.elapsed().as_millis() as u64),
+        cwd: Some(result.cwd),
+    })
+}
+
+/// Working directory of a remote project's terminal
+#[tauri::command]
+pub async fn remote_terminal_current_directory(project_path: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || ops::current_dir(&project_path))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
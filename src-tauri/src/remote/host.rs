@@ -0,0 +1,309 @@
+use anyhow::Result;
+use std::io::{Read, Write};
+
+/// Result of a command run to completion
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+}
+
+/// An entry of a remote directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// A command running on the host, e.g. a language server speaking over stdio
+pub struct RemoteProcess {
+    pub stdin: Box<dyn Write + Send>,
+    pub stdout: Box<dyn Read + Send>,
+}
+
+/// File and process access on a remote host. Paths are absolute paths on the
+/// host; commands run through the login shell of the remote user.
+pub trait RemoteHost: Send + Sync {
+    fn exec(&self, command: &str) -> Result<ExecOutput>;
+    fn read_file(&self, path: &str) -> Result<Vec<u8>>;
+    fn write_file(&self, path: &str, bytes: &[u8]) -> Result<()>;
+    /// Move `from` to `to`. Like an SFTP v3 server, this may fail when `to`
+    /// already exists.
+    fn rename(&self, from: &str, to: &str) -> Result<()>;
+    /// Permission bits of a file
+    fn mode(&self, path: &str) -> Result<u32>;
+    fn set_mode(&self, path: &str, mode: u32) -> Result<()>;
+    fn read_dir(&self, path: &str) -> Result<Vec<RemoteEntry>>;
+    fn is_dir(&self, path: &str) -> Result<bool>;
+    /// Start a command that keeps running, with its stdin and stdout piped
+    fn spawn(&self, command: &str) -> Result<RemoteProcess>;
+}
+
+/// Quote one argument for a POSIX shell
+pub fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(feature = "remote")]
+pub use ssh::SshHost;
+
+#[cfg(feature = "remote")]
+mod ssh {
+    use super::*;
+    use crate::remote::ssh_config;
+    use crate::remote::RemoteUri;
+    use anyhow::{anyhow, Context};
+    use berry_editor_tauri::berrycode::remote::{SshAuth, SshConfig, SshConnection};
+    use ssh2::Channel;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Default keys tried when `~/.ssh/config` names none
+    const DEFAULT_KEYS: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+    /// A host reached through one shared SSH connection; language servers get
+    /// a connection of their own so their blocking reads do not stall file access
+    pub struct SshHost {
+        config: SshConfig,
+        keys: Vec<PathBuf>,
+        connection: Mutex<SshConnection>,
+    }
+
+    /// Read everything a finished command wrote, then its exit status
+    fn finish(mut channel: Channel) -> Result<ExecOutput> {
+        let mut stdout = String::new();
+        channel.read_to_string(&mut stdout).context("Failed to read command output")?;
+        let mut stderr = String::new();
+        channel.stderr().read_to_string(&mut stderr).ok();
+        channel.wait_close().context("Failed to close channel")?;
+        Ok(ExecOutput {
+            stdout,
+            stderr,
+            status: channel.exit_status()?,
+        })
+    }
+
+    /// Writes to a channel, keeping its connection open while the process runs
+    struct ChannelWriter {
+        channel: Channel,
+        _connection: SshConnection,
+    }
+
+    impl Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.channel.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.channel.flush()
+        }
+    }
+
+    impl SshHost {
+        /// Connect to `[user@]host[:port]`, resolving the host through
+        /// `~/.ssh/config` and authenticating with the SSH agent, then with
+        /// the configured (or default) identity files
+        pub fn connect(authority: &str) -> Result<Self> {
+            let uri = RemoteUri {
+                authority: authority.to_string(),
+                path: "/".to_string(),
+            };
+            let (user, host, port) = uri.user_host_port()?;
+            let resolved = ssh_config::load(host);
+            let user = user
+                .map(str::to_string)
+                .or(resolved.user)
+                .or_else(|| std::env::var("USER").ok())
+                .ok_or_else(|| anyhow!("No user for {}", authority))?;
+            let keys = if resolved.identity_files.is_empty() {
+                let ssh_dir = dirs::home_dir().unwrap_or_default().join(".ssh");
+                DEFAULT_KEYS.iter().map(|key| ssh_dir.join(key)).collect()
+            } else {
+                resolved.identity_files
+            };
+            let config = SshConfig {
+                host: resolved.host_name.unwrap_or_else(|| host.to_string()),
+                port: port.or(resolved.port).unwrap_or(22),
+                username: user,
+                auth: SshAuth::Agent,
+            };
+            let connection = Self::open(&config, &keys)?;
+            Ok(Self {
+                config,
+                keys,
+                connection: Mutex::new(connection),
+            })
+        }
+
+        fn open(config: &SshConfig, keys: &[PathBuf]) -> Result<SshConnection> {
+            let mut errors = Vec::new();
+            let key_auths = keys.iter().filter(|key| key.exists()).map(|key| SshAuth::PublicKey {
+                private_key_path: key.to_string_lossy().to_string(),
+                passphrase: None,
+            });
+            for auth in std::iter::once(SshAuth::Agent).chain(key_auths) {
+                let attempt = SshConfig {
+                    auth,
+                    ..config.clone()
+                };
+                match SshConnection::connect(attempt) {
+                    Ok(connection) => return Ok(connection),
+                    Err(e) => errors.push(format!("{:#}", e)),
+                }
+            }
+            Err(anyhow!(
+                "Could not connect to {}@{}:{}: {}",
+                config.username,
+                config.host,
+                config.port,
+                errors.join("; ")
+            ))
+        }
+
+        fn with_connection<T>(&self, f: impl FnOnce(&mut SshConnection) -> Result<T>) -> Result<T> {
+            let mut connection = self.connection.lock().unwrap();
+            f(&mut connection)
+        }
+    }
+
+    impl RemoteHost for SshHost {
+        fn exec(&self, command: &str) -> Result<ExecOutput> {
+            let channel = self.with_connection(|c| c.spawn(command))?;
+            finish(channel)
+        }
+
+        fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+            self.with_connection(|c| c.read_file(path))
+        }
+
+        fn write_file(&self, path: &str, bytes: &[u8]) -> Result<()> {
+            self.with_connection(|c| c.write_file(path, bytes))
+        }
+
+        fn rename(&self, from: &str, to: &str) -> Result<()> {
+            self.with_connection(|c| c.rename(from, to))
+        }
+
+        fn mode(&self, path: &str) -> Result<u32> {
+            self.with_connection(|c| c.mode(path))
+        }
+
+        fn set_mode(&self, path: &str, mode: u32) -> Result<()> {
+            self.with_connection(|c| c.set_mode(path, mode))
+        }
+
+        fn read_dir(&self, path: &str) -> Result<Vec<RemoteEntry>> {
+            let entries = self.with_connection(|c| c.read_dir(path))?;
+            Ok(entries
+                .into_iter()
+                .map(|entry| RemoteEntry {
+                    name: entry.name,
+                    path: entry.path,
+                    is_dir: entry.is_dir,
+                })
+                .collect())
+        }
+
+        fn is_dir(&self, path: &str) -> Result<bool> {
+            Ok(self.with_connection(|c| c.stat(path))?.is_dir)
+        }
+
+        fn spawn(&self, command: &str) -> Result<RemoteProcess> {
+            let mut connection = Self::open(&self.config, &self.keys)?;
+            let channel = connection.spawn(command)?;
+            let stdout = channel.stream(0);
+            Ok(RemoteProcess {
+                stdin: Box::new(ChannelWriter {
+                    channel,
+                    _connection: connection,
+                }),
+                stdout: Box::new(stdout),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+pub use local::LocalHost;
+
+/// Stand-in for an SSH host in tests: runs commands with `sh` and accesses
+/// files directly on this machine
+#[cfg(test)]
+mod local {
+    use super::*;
+    use anyhow::anyhow;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::{Command, Stdio};
+
+    pub struct LocalHost;
+
+    impl RemoteHost for LocalHost {
+        fn exec(&self, command: &str) -> Result<ExecOutput> {
+            let output = Command::new("sh").arg("-c").arg(command).output()?;
+            Ok(ExecOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                status: output.status.code().unwrap_or(-1),
+            })
+        }
+
+        fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+            Ok(fs::read(path)?)
+        }
+
+        fn write_file(&self, path: &str, bytes: &[u8]) -> Result<()> {
+            Ok(fs::write(path, bytes)?)
+        }
+
+        fn rename(&self, from: &str, to: &str) -> Result<()> {
+            // OpenSSH's SFTP server does not replace existing files
+            if fs::symlink_metadata(to).is_ok() {
+                return Err(anyhow!("{} already exists", to));
+            }
+            Ok(fs::rename(from, to)?)
+        }
+
+        fn mode(&self, path: &str) -> Result<u32> {
+            Ok(fs::metadata(path)?.permissions().mode() & 0o7777)
+        }
+
+        fn set_mode(&self, path: &str, mode: u32) -> Result<()> {
+            Ok(fs::set_permissions(path, fs::Permissions::from_mode(mode))?)
+        }
+
+        fn read_dir(&self, path: &str) -> Result<Vec<RemoteEntry>> {
+            fs::read_dir(path)?
+                .map(|entry| {
+                    let entry = entry?;
+                    Ok(RemoteEntry {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        path: entry.path().to_string_lossy().to_string(),
+                        is_dir: entry.file_type()?.is_dir(),
+                    })
+                })
+                .collect()
+        }
+
+        fn is_dir(&self, path: &str) -> Result<bool> {
+            Ok(fs::metadata(path)?.is_dir())
+        }
+
+        fn spawn(&self, command: &str) -> Result<RemoteProcess> {
+            let mut child = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()?;
+            let stdin = child.stdin.take().ok_or_else(|| anyhow!("No stdin"))?;
+            let stdout = child.stdout.take().ok_or_else(|| anyhow!("No stdout"))?;
+            Ok(RemoteProcess {
+                stdin: Box::new(stdin),
+                stdout: Box::new(stdout),
+            })
+        }
+    }
+}
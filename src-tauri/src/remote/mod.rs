@@ -0,0 +1,69 @@
+//! Remote development over SSH
+//!
+//! Remote folders are opened as `ssh://[user@]host[:port]/path` paths. The
+//! file, explorer, search, terminal and LSP commands check for that scheme and
+//! route through the host's connection instead of the local file system.
+//! Connections are opened on first use and shared by every command.
+
+pub mod commands;
+mod host;
+mod ops;
+mod ssh_config;
+mod uri;
+
+pub use host::{quote, ExecOutput, RemoteEntry, RemoteHost, RemoteProcess};
+pub use ops::*;
+pub use uri::{is_remote, RemoteUri};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Open connections keyed by authority
+static HOSTS: Lazy<Mutex<HashMap<String, Arc<dyn RemoteHost>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The connection to `authority`, connecting if needed
+pub fn host(authority: &str) -> Result<Arc<dyn RemoteHost>> {
+    if let Some(host) = HOSTS.lock().unwrap().get(authority) {
+        return Ok(host.clone());
+    }
+    let host = connect(authority)?;
+    HOSTS
+        .lock()
+        .unwrap()
+        .insert(authority.to_string(), host.clone());
+    Ok(host)
+}
+
+#[cfg(feature = "remote")]
+fn connect(authority: &str) -> Result<Arc<dyn RemoteHost>> {
+    Ok(Arc::new(host::SshHost::connect(authority)?))
+}
+
+#[cfg(not(feature = "remote"))]
+fn connect(authority: &str) -> Result<Arc<dyn RemoteHost>> {
+    Err(anyhow::anyhow!(
+        "Cannot connect to {}: built without the `remote` feature",
+        authority
+    ))
+}
+
+/// Close the connection to `authority`
+pub fn disconnect(authority: &str) {
+    HOSTS.lock().unwrap().remove(authority);
+}
+
+/// Use `host` for `authority` instead of an SSH connection
+#[cfg(test)]
+pub(crate) fn register(authority: &str, host: Arc<dyn RemoteHost>) {
+    HOSTS.lock().unwrap().insert(authority.to_string(), host);
+}
+
+/// A local sshd stand-in registered under a unique authority
+#[cfg(test)]
+pub(crate) fn local_host(name: &str) -> String {
+    let authority = format!("test@{}", name);
+    register(&authority, Arc::new(host::LocalHost));
+    authority
+}
@@ -0,0 +1,350 @@
+//! File, explorer, search and terminal operations on `ssh://` paths
+//!
+//! Paths going in and out are `ssh://` URIs so the frontend can treat remote
+//! files like local ones; only the host sees plain paths.
+
+use super::host::{quote, RemoteHost};
+use super::{host, RemoteUri};
+use crate::file_tree::{self, TreeEntry};
+use crate::search_commands::{self, SearchOptions, SearchResult};
+use anyhow::{anyhow, Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Separates a terminal command's output from the directory it ended in
+const CWD_MARKER: char = '\u{1e}';
+
+/// Working directory of each remote terminal, keyed by project URI
+static TERMINAL_DIRS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn open(uri: &str) -> Result<(RemoteUri, Arc<dyn RemoteHost>)> {
+    let uri = RemoteUri::parse(uri)?;
+    let host = host(&uri.authority)?;
+    Ok((uri, host))
+}
+
+/// Connect to a folder typed as `[user@]host:path` or `ssh://host/path`,
+/// returning its `ssh://` URI with the path made absolute
+pub fn connect_folder(target: &str) -> Result<String> {
+    let target = RemoteUri::from_target(target)?;
+    let host = host(&target.authority)?;
+    let output = host.exec(&format!("cd {} && pwd", quote(&target.path)))?;
+    if output.status != 0 {
+        return Err(anyhow!("{} is not a folder on {}", target.path, target.authority));
+    }
+    Ok(target.with_path(output.stdout.trim_end()))
+}
+
+pub fn read_bytes(uri: &str) -> Result<Vec<u8>> {
+    let (uri, host) = open(uri)?;
+    host.read_file(&uri.path)
+        .with_context(|| format!("Failed to read {}", uri.to_uri()))
+}
+
+/// Replace a remote file through a temp file in the same folder, so a dropped
+/// connection never leaves it half written. The file keeps its permissions.
+pub fn write_atomic(uri: &str, bytes: &[u8]) -> Result<()> {
+    let (uri, host) = open(uri)?;
+    let (dir, name) = uri.path.rsplit_once('/').unwrap_or(("", &uri.path));
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let temp = format!("{}/.berry-save-{}-{}", dir, nanos, name);
+    let mode = host.mode(&uri.path).ok();
+    let result = host
+        .write_file(&temp, bytes)
+        .with_context(|| format!("Failed to write {}", temp))
+        .and_then(|()| match mode {
+            Some(mode) => host.set_mode(&temp, mode),
+            None => Ok(()),
+        })
+        .and_then(|()| replace(host.as_ref(), &temp, &uri.path));
+    if let Err(e) = result {
+        host.exec(&format!("rm -f {}", quote(&temp))).ok();
+        return Err(e.context(format!("Failed to replace {}", uri.to_uri())));
+    }
+    Ok(())
+}
+
+/// Move `from` over `to`. SFTP v3 servers such as OpenSSH refuse to rename
+/// onto an existing file, so that case goes through `mv -f` on the host,
+/// which is still an atomic rename within one folder.
+fn replace(host: &dyn RemoteHost, from: &str, to: &str) -> Result<()> {
+    if host.rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let output = host.exec(&format!("mv -f {} {}", quote(from), quote(to)))?;
+    if output.status != 0 {
+        return Err(anyhow!("mv failed: {}", output.stderr.trim()));
+    }
+    Ok(())
+}
+
+pub fn rename(from: &str, to: &str) -> Result<()> {
+    let (from, host) = open(from)?;
+    let to = RemoteUri::parse(to)?;
+    if to.authority != from.authority {
+        return Err(anyhow!("Cannot move files between hosts"));
+    }
+    host.rename(&from.path, &to.path)
+}
+
+/// The root's `.gitignore`, read over the connection
+fn gitignore(host: &dyn RemoteHost, root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    if let Ok(bytes) = host.read_file(&root.join(".gitignore").to_string_lossy()) {
+        for line in String::from_utf8_lossy(&bytes).lines() {
+            builder.add_line(None, line).ok();
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Builds explorer entries for one remote root. Git status is not shown for
+/// remote folders.
+struct RemoteTree<'a> {
+    uri: &'a RemoteUri,
+    root: &'a Path,
+    exclude: Gitignore,
+    gitignore: Gitignore,
+}
+
+impl RemoteTree<'_> {
+    fn entry(&self, path: &str, is_dir: bool) -> Option<TreeEntry> {
+        let path = Path::new(path);
+        if file_tree::is_excluded(&self.exclude, self.root, path, is_dir) {
+            return None;
+        }
+        let ignored = path != self.root
+            && path.starts_with(self.root)
+            && self.gitignore.matched_path_or_any_parents(path, is_dir).is_ignore();
+        Some(TreeEntry {
+            name: path.file_name()?.to_string_lossy().to_string(),
+            path: self.uri.with_path(&path.to_string_lossy()),
+            is_dir,
+            ignored,
+            git_status: None,
+        })
+    }
+}
+
+/// The direct children of a remote folder, folders first
+pub fn list_dir(root: &str, dir: &str, exclude: &[String]) -> Result<Vec<TreeEntry>> {
+    let (uri, host) = open(root)?;
+    let dir = RemoteUri::parse(dir)?;
+    let root = Path::new(&uri.path);
+    let tree = RemoteTree {
+        uri: &uri,
+        root,
+        exclude: file_tree::exclude_matcher(root, exclude)?,
+        gitignore: gitignore(host.as_ref(), root),
+    };
+    let mut entries: Vec<TreeEntry> = host
+        .read_dir(&dir.path)
+        .with_context(|| format!("Failed to read {}", dir.to_uri()))?
+        .into_iter()
+        .filter_map(|entry| tree.entry(&entry.path, entry.is_dir))
+        .collect();
+    file_tree::sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Fresh entries for individual remote paths; missing or excluded paths are left out
+pub fn stat_entries(root: &str, paths: &[String], exclude: &[String]) -> Result<Vec<TreeEntry>> {
+    let (uri, host) = open(root)?;
+    let root = Path::new(&uri.path);
+    let tree = RemoteTree {
+        uri: &uri,
+        root,
+        exclude: file_tree::exclude_matcher(root, exclude)?,
+        gitignore: gitignore(host.as_ref(), root),
+    };
+    let mut entries = Vec::new();
+    for path in paths {
+        let path = RemoteUri::parse(path)?.path;
+        if let Ok(is_dir) = host.is_dir(&path) {
+            entries.extend(tree.entry(&path, is_dir));
+        }
+    }
+    Ok(entries)
+}
+
+/// Search a remote folder with ripgrep on the host
+pub fn search(query: &str, root: &str, opts: &SearchOptions) -> Result<Vec<SearchResult>, String> {
+    let (uri, host) = open(root).map_err(|e| format!("{:#}", e))?;
+    let args: Vec<String> = search_commands::ripgrep_args(opts).iter().map(|arg| quote(arg)).collect();
+    let command = format!("rg {} -- {} {}", args.join(" "), quote(query), quote(&uri.path));
+    let output = host.exec(&command).map_err(|e| format!("{:#}", e))?;
+    match output.status {
+        0 => {}
+        // No matches
+        1 => return Ok(vec![]),
+        127 => return Err(format!("ripgrep (rg) is not installed on {}", uri.authority)),
+        _ => return Err(format!("Ripgrep failed: {}", output.stderr)),
+    }
+    let results = search_commands::parse_ripgrep_json(&output.stdout)?;
+    Ok(with_host(&uri, results))
+}
+
+/// Turn the host paths of search results into `ssh://` URIs
+fn with_host(uri: &RemoteUri, results: Vec<SearchResult>) -> Vec<SearchResult> {
+    results
+        .into_iter()
+        .map(|result| SearchResult {
+            path: uri.with_path(&result.path),
+            ..result
+        })
+        .collect()
+}
+
+/// Output of a terminal command run on the host
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutput {
+    pub output: String,
+    pub exit_code: i32,
+    /// Directory the command ended in, as an `ssh://` URI
+    pub cwd: String,
+}
+
+/// Shell script running `command` in `cwd` and reporting the directory it ends in
+fn terminal_script(cwd: &str, command: &str) -> String {
+    let script = format!(
+        "cd {} 2>/dev/null; {{ {}\n}} 2>&1; s=$?; printf '{}%s' \"$PWD\"; exit $s",
+        quote(cwd),
+        command,
+        CWD_MARKER
+    );
+    // Run under `sh` whatever the remote login shell is
+    format!("sh -c {}", quote(&script))
+}
+
+/// Split a terminal script's output from the directory it reported, if any
+fn split_cwd(stdout: &str) -> (&str, Option<&str>) {
+    match stdout.rsplit_once(CWD_MARKER) {
+        Some((output, cwd)) => (output, Some(cwd)),
+        None => (stdout, None),
+    }
+}
+
+/// Run a terminal command in the remote project's terminal. Each project
+/// keeps its working directory between commands, like a shell session.
+pub fn run_command(project: &str, command: &str) -> Result<CommandOutput> {
+    let (uri, host) = open(project)?;
+    let cwd = current_path(project, &uri);
+    let output = host.exec(&terminal_script(&cwd, command))?;
+    let (text, new_cwd) = split_cwd(&output.stdout);
+    let cwd = new_cwd.map(str::to_string).unwrap_or(cwd);
+    TERMINAL_DIRS
+        .lock()
+        .unwrap()
+        .insert(project.to_string(), cwd.clone());
+    Ok(CommandOutput {
+        output: text.to_string(),
+        exit_code: output.status,
+        cwd: uri.with_path(&cwd),
+    })
+}
+
+fn current_path(project: &str, uri: &RemoteUri) -> String {
+    TERMINAL_DIRS
+        .lock()
+        .unwrap()
+        .get(project)
+        .cloned()
+        .unwrap_or_else(|| uri.path.clone())
+}
+
+/// Working directory of the remote project's terminal, as an `ssh://` URI
+pub fn current_dir(project: &str) -> Result<String> {
+    let uri = RemoteUri::parse(project)?;
+    Ok(uri.with_path(&current_path(project, &uri)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::local_host;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn uri(authority: &str, path: &Path) -> String {
+        format!("ssh://{}{}", authority, path.display())
+    }
+
+    #[test]
+    fn test_remote_files_and_tree() {
+        let authority = local_host("files");
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+
+        let folder = connect_folder(&format!("{}:{}", authority, root.display())).unwrap();
+        assert_eq!(folder, uri(&authority, &root));
+
+        let file = uri(&authority, &root.join("src/main.rs"));
+        write_atomic(&file, b"fn main() {}\n").unwrap();
+        // Saving again renames over the existing file, which SFTP v3 refuses
+        write_atomic(&file, b"fn main() { run() }\n").unwrap();
+        assert_eq!(read_bytes(&file).unwrap(), b"fn main() { run() }\n");
+        // The temp file is renamed over the target
+        assert_eq!(std::fs::read_dir(root.join("src")).unwrap().count(), 1);
+
+        // Saving over an existing file keeps its permissions
+        let script = root.join("run.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        write_atomic(&uri(&authority, &script), b"#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(std::fs::read(&script).unwrap(), b"#!/bin/sh\necho hi\n");
+        assert_eq!(std::fs::metadata(&script).unwrap().permissions().mode() & 0o777, 0o755);
+        std::fs::remove_file(&script).unwrap();
+
+        let entries = list_dir(&folder, &folder, &[".gitignore".to_string()]).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| (e.name.as_str(), e.ignored)).collect();
+        assert_eq!(names, vec![("src", false), ("target", true), ("notes.txt", false)]);
+        assert_eq!(entries[0].path, uri(&authority, &root.join("src")));
+
+        let missing = uri(&authority, &root.join("gone.rs"));
+        let fresh = stat_entries(&folder, &[file.clone(), missing], &[]).unwrap();
+        assert_eq!(fresh.len(), 1);
+        assert_eq!((fresh[0].path.as_str(), fresh[0].is_dir), (file.as_str(), false));
+
+        assert!(connect_folder(&format!("{}:{}", authority, root.join("gone").display())).is_err());
+    }
+
+    #[test]
+    fn test_remote_terminal_keeps_directory() {
+        let authority = local_host("terminal");
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let project = uri(&authority, &root);
+
+        let first = run_command(&project, "cd sub && echo \"it's here\"").unwrap();
+        assert_eq!((first.output.as_str(), first.exit_code), ("it's here\n", 0));
+        assert_eq!(first.cwd, uri(&authority, &root.join("sub")));
+        assert_eq!(current_dir(&project).unwrap(), first.cwd);
+
+        let failed = run_command(&project, "pwd; ls missing-file").unwrap();
+        assert!(failed.output.starts_with(&format!("{}\n", root.join("sub").display())));
+        assert!(failed.output.contains("missing-file"));
+        assert_ne!(failed.exit_code, 0);
+    }
+
+    #[test]
+    fn test_search_results_use_remote_paths() {
+        let uri = RemoteUri::parse("ssh://devbox/srv/app").unwrap();
+        let output = r#"{"type":"match","data":{"path":{"text":"/srv/app/main.rs"},"lines":{"text":"fn main() {}\n"},"line_number":3,"submatches":[{"match":{"text":"main"},"start":3,"end":7}]}}"#;
+        let results = with_host(&uri, search_commands::parse_ripgrep_json(output).unwrap());
+        assert_eq!(results[0].path, "ssh://devbox/srv/app/main.rs");
+        assert_eq!((results[0].line_number, results[0].column), (3, 4));
+
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(split_cwd("out\n\u{1e}/srv"), ("out\n", Some("/srv")));
+        assert_eq!(split_cwd("exited early"), ("exited early", None));
+    }
+}
@@ -0,0 +1,134 @@
+//! `~/.ssh/config` lookup
+//!
+//! Only the settings needed to open a connection are read. As in OpenSSH the
+//! first value found for a setting wins, except `IdentityFile`, which
+//! accumulates across matching `Host` blocks.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostConfig {
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_files: Vec<PathBuf>,
+}
+
+/// `*` and `?` glob match
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => wildcard(&pattern[1..], text) || (!text.is_empty() && wildcard(pattern, &text[1..])),
+        (Some(b'?'), Some(_)) => wildcard(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p.eq_ignore_ascii_case(t) => wildcard(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+/// Whether a `Host` line's patterns select `host`; a matching `!pattern` excludes it
+fn host_matches(patterns: &str, host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns.split([' ', '\t', ',']).filter(|p| !p.is_empty()) {
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard(negated.as_bytes(), host.as_bytes()) => return false,
+            Some(_) => {}
+            None => matched |= wildcard(pattern.as_bytes(), host.as_bytes()),
+        }
+    }
+    matched
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Settings of `host` in the text of an ssh config file
+pub fn resolve(config: &str, host: &str) -> HostConfig {
+    let mut result = HostConfig::default();
+    // Settings before the first `Host` line apply to every host
+    let mut active = true;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((key, value)) => (key, value.trim_start_matches(|c: char| c.is_whitespace() || c == '=').trim()),
+            None => continue,
+        };
+        let value = value.trim_matches('"');
+        match key.to_ascii_lowercase().as_str() {
+            "host" => active = host_matches(value, host),
+            // `Match` blocks need criteria we do not evaluate; skip them
+            "match" => active = false,
+            _ if !active => {}
+            "hostname" => {
+                result.host_name.get_or_insert_with(|| value.replace("%h", host));
+            }
+            "user" => {
+                result.user.get_or_insert_with(|| value.to_string());
+            }
+            "port" => {
+                if result.port.is_none() {
+                    result.port = value.parse().ok();
+                }
+            }
+            "identityfile" => result.identity_files.push(expand_home(value)),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Settings of `host` in the user's `~/.ssh/config` (empty if there is none)
+pub fn load(host: &str) -> HostConfig {
+    let config = dirs::home_dir()
+        .map(|home| home.join(".ssh").join("config"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    resolve(&config, host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+IdentityFile /keys/global
+
+Host devbox build-*
+    HostName %h.internal.example.com
+    User dev
+    Port=2222
+    IdentityFile /keys/dev
+
+Host build-* !build-legacy
+    User ci
+
+Host *
+    User fallback
+    Port 22
+";
+
+    #[test]
+    fn test_resolve_ssh_config() {
+        let devbox = resolve(CONFIG, "devbox");
+        assert_eq!(devbox.host_name.as_deref(), Some("devbox.internal.example.com"));
+        assert_eq!((devbox.user.as_deref(), devbox.port), (Some("dev"), Some(2222)));
+        assert_eq!(devbox.identity_files, vec![PathBuf::from("/keys/global"), PathBuf::from("/keys/dev")]);
+
+        // The first value wins, even when a later block matches more specifically
+        assert_eq!(resolve(CONFIG, "build-7").user.as_deref(), Some("dev"));
+
+        let other = resolve(CONFIG, "example.org");
+        assert_eq!(other.host_name, None);
+        assert_eq!((other.user.as_deref(), other.port), (Some("fallback"), Some(22)));
+
+        assert!(host_matches("build-* !build-legacy", "BUILD-1"));
+        assert!(!host_matches("build-* !build-legacy", "build-legacy"));
+        assert!(host_matches("db?", "db1") && !host_matches("db?", "db10"));
+    }
+}
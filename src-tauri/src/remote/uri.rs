@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Result};
+
+const SCHEME: &str = "ssh://";
+
+/// Whether `path` names a file on a remote host
+pub fn is_remote(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// A path on a remote host: `ssh://[user@]host[:port]/path`. The host may be
+/// an alias from `~/.ssh/config`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUri {
+    /// `[user@]host[:port]`, also the key of the host's connection
+    pub authority: String,
+    /// Absolute path on the host
+    pub path: String,
+}
+
+impl RemoteUri {
+    pub fn parse(uri: &str) -> Result<Self> {
+        let rest = uri
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| anyhow!("Not a remote path: {}", uri))?;
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+        if authority.is_empty() {
+            return Err(anyhow!("No host in remote path: {}", uri));
+        }
+        Ok(Self {
+            authority: authority.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    /// Parse what a user types to open a remote folder: an `ssh://` URI or
+    /// scp-style `[user@]host:path`. A relative path is kept as typed and
+    /// resolved against the home directory once connected.
+    pub fn from_target(target: &str) -> Result<Self> {
+        let target = target.trim();
+        if is_remote(target) {
+            return Self::parse(target);
+        }
+        let (authority, path) = target
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected [user@]host:path, got {}", target))?;
+        if authority.is_empty() {
+            return Err(anyhow!("No host in {}", target));
+        }
+        Ok(Self {
+            authority: authority.to_string(),
+            path: if path.is_empty() { ".".to_string() } else { path.to_string() },
+        })
+    }
+
+    /// The user, host and port given in the authority
+    pub fn user_host_port(&self) -> Result<(Option<&str>, &str, Option<u16>)> {
+        let (user, host_port) = match self.authority.split_once('@') {
+            Some((user, rest)) => (Some(user), rest),
+            None => (None, self.authority.as_str()),
+        };
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| anyhow!("Invalid port in {}", self.authority))?;
+                Ok((user, host, Some(port)))
+            }
+            None => Ok((user, host_port, None)),
+        }
+    }
+
+    /// URI of another path on the same host
+    pub fn with_path(&self, path: &str) -> String {
+        format!("{}{}{}", SCHEME, self.authority, path)
+    }
+
+    pub fn to_uri(&self) -> String {
+        self.with_path(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_uri() {
+        let uri = RemoteUri::parse("ssh://dev@build-box:2222/home/dev/api/src/main.rs").unwrap();
+        assert_eq!(uri.authority, "dev@build-box:2222");
+        assert_eq!(uri.path, "/home/dev/api/src/main.rs");
+        assert_eq!(uri.user_host_port().unwrap(), (Some("dev"), "build-box", Some(2222)));
+        assert_eq!(uri.with_path("/etc"), "ssh://dev@build-box:2222/etc");
+
+        let alias = RemoteUri::parse("ssh://devbox").unwrap();
+        assert_eq!((alias.path.as_str(), alias.user_host_port().unwrap()), ("/", (None, "devbox", None)));
+
+        let scp = RemoteUri::from_target("devbox:projects/api").unwrap();
+        assert_eq!((scp.authority.as_str(), scp.path.as_str()), ("devbox", "projects/api"));
+        assert_eq!(RemoteUri::from_target("ssh://devbox/srv").unwrap().to_uri(), "ssh://devbox/srv");
+
+        assert!(RemoteUri::parse("/local/path").is_err());
+        assert!(RemoteUri::from_target("no-path").is_err());
+        assert!(RemoteUri::parse("ssh://host:ssh/x").unwrap().user_host_port().is_err());
+        assert!(is_remote("ssh://devbox/x") && !is_remote("/home/dev"));
+    }
+}
@@ -1,6 +1,7 @@
 //! Search commands for project-wide search functionality
 //! Integrates with grep/ripgrep for fast searching
 
+use crate::remote;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Command;
@@ -47,6 +48,14 @@ pub async fn search_in_files(
     options: Option<SearchOptions>,
 ) -> Result<Vec<SearchResult>, String> {
     let opts = options.unwrap_or_default();
+    if remote::is_remote(&root_path) {
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+        return tokio::task::spawn_blocking(move || remote::search(&query, &root_path, &opts))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
     let root = PathBuf::from(&root_path);
 
     if !root.exists() {
@@ -74,65 +83,72 @@ fn search_with_ripgrep(
     opts: &SearchOptions,
 ) -> Result<Vec<SearchResult>, String> {
     let mut cmd = Command::new("rg");
+    cmd.args(ripgrep_args(opts));
+
+    // Query and path
+    cmd.arg(query).arg(root);
 
+    // Execute
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to execute ripgrep: {}", e))?;
+
+    if !output.status.success() {
+        // Exit code 1 means no matches, which is OK
+        if output.status.code() == Some(1) {
+            return Ok(vec![]);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Ripgrep failed: {}", stderr));
+    }
+
+    // Parse JSON output
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_ripgrep_json(&stdout)
+}
+
+/// ripgrep options for a search, without the query and path
+pub(crate) fn ripgrep_args(opts: &SearchOptions) -> Vec<String> {
     // Basic options
-    cmd.arg("--json") // JSON output for parsing
-        .arg("--line-number")
-        .arg("--column");
+    let mut args: Vec<String> = ["--json", "--line-number", "--column"] // JSON output for parsing
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
 
     // Case sensitivity
     if !opts.case_sensitive {
-        cmd.arg("--ignore-case");
+        args.push("--ignore-case".to_string());
     }
 
     // Regex mode
     if !opts.use_regex {
-        cmd.arg("--fixed-strings");
+        args.push("--fixed-strings".to_string());
     }
 
     // Whole word
     if opts.whole_word {
-        cmd.arg("--word-regexp");
+        args.push("--word-regexp".to_string());
     }
 
     // Include/exclude patterns
     if let Some(ref include) = opts.include_pattern {
-        cmd.arg("--glob").arg(include);
+        args.extend(["--glob".to_string(), include.clone()]);
     }
 
     if let Some(ref exclude) = opts.exclude_pattern {
-        cmd.arg("--glob").arg(format!("!{}", exclude));
+        args.extend(["--glob".to_string(), format!("!{}", exclude)]);
     }
 
     // Max results (ripgrep uses --max-count per file)
     if let Some(max) = opts.max_results {
-        cmd.arg("--max-count").arg(max.to_string());
-    }
-
-    // Query and path
-    cmd.arg(query).arg(root);
-
-    // Execute
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to execute ripgrep: {}", e))?;
-
-    if !output.status.success() {
-        // Exit code 1 means no matches, which is OK
-        if output.status.code() == Some(1) {
-            return Ok(vec![]);
-        }
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Ripgrep failed: {}", stderr));
+        args.extend(["--max-count".to_string(), max.to_string()]);
     }
 
-    // Parse JSON output
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_ripgrep_json(&stdout)
+    args
 }
 
 /// Parse ripgrep's JSON output
-fn parse_ripgrep_json(output: &str) -> Result<Vec<SearchResult>, String> {
+pub(crate) fn parse_ripgrep_json(output: &str) -> Result<Vec<SearchResult>, String> {
     let mut results = Vec::new();
 
     for line in output.lines() {
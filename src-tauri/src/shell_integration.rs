@@ -0,0 +1,339 @@
+//! Shell Integration
+//!
+//! FinalTerm / VS Code style prompt markers (OSC 133 and OSC 633) injected into
+//! bash and zsh. With the markers in place the terminal backend knows exactly
+//! where each command's output starts and ends, its exit code and the shell's
+//! working directory, instead of guessing from the prompt text.
+//!
+//! Sequences understood by [`OscParser`]:
+//! - `OSC 133;A` / `OSC 633;A` - prompt start
+//! - `OSC 133;B` / `OSC 633;B` - prompt end, command input starts
+//! - `OSC 133;C` / `OSC 633;C` - command executed, output starts
+//! - `OSC 133;D;<exit>` / `OSC 633;D;<exit>` - command finished
+//! - `OSC 633;P;Cwd=<path>` - current working directory
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Unterminated OSC sequences longer than this are treated as plain output
+const MAX_OSC_LEN: usize = 4096;
+
+/// Shell flavour used for a persistent terminal session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+}
+
+impl ShellKind {
+    /// Detect the user's shell from `$SHELL`, falling back to bash
+    pub fn detect() -> Self {
+        std::env::var("SHELL")
+            .ok()
+            .and_then(|shell| Self::from_program(&shell))
+            .unwrap_or(ShellKind::Bash)
+    }
+
+    /// Map a shell program path (e.g. `/bin/zsh`) to a supported shell
+    pub fn from_program(program: &str) -> Option<Self> {
+        let name = Path::new(program).file_name()?.to_str()?;
+        match name {
+            "bash" => Some(ShellKind::Bash),
+            "zsh" => Some(ShellKind::Zsh),
+            _ => None,
+        }
+    }
+
+    /// Executable name
+    pub fn program(&self) -> &'static str {
+        match self {
+            ShellKind::Bash => "bash",
+            ShellKind::Zsh => "zsh",
+        }
+    }
+
+    /// Write the init script into `dir` and return the shell arguments and
+    /// environment variables needed to start an interactive shell with it.
+    pub fn prepare_launch(&self, dir: &Path) -> Result<ShellLaunch> {
+        std::fs::create_dir_all(dir)?;
+
+        match self {
+            ShellKind::Bash => {
+                let rc_file = dir.join("bashrc");
+                std::fs::write(&rc_file, BASH_INIT_SCRIPT)?;
+                Ok(ShellLaunch {
+                    program: self.program(),
+                    args: vec![
+                        "--noprofile".to_string(),
+                        "--rcfile".to_string(),
+                        rc_file.to_string_lossy().to_string(),
+                        "-i".to_string(),
+                    ],
+                    env: Vec::new(),
+                    script_dir: dir.to_path_buf(),
+                })
+            }
+            ShellKind::Zsh => {
+                // zsh has no --rcfile; point ZDOTDIR at a directory holding our .zshrc
+                std::fs::write(dir.join(".zshrc"), ZSH_INIT_SCRIPT)?;
+                Ok(ShellLaunch {
+                    program: self.program(),
+                    args: vec!["-i".to_string()],
+                    env: vec![("ZDOTDIR".to_string(), dir.to_string_lossy().to_string())],
+                    script_dir: dir.to_path_buf(),
+                })
+            }
+        }
+    }
+}
+
+/// How to spawn a shell with integration enabled
+#[derive(Debug, Clone)]
+pub struct ShellLaunch {
+    pub program: &'static str,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// Directory holding the generated init script (removed on terminate)
+    pub script_dir: PathBuf,
+}
+
+/// bash: PS0 fires after a command line is read, PROMPT_COMMAND before each prompt.
+/// The cwd is reported before the finish marker so it is current once `D` arrives.
+const BASH_INIT_SCRIPT: &str = r#"# BerryEditor shell integration
+bind 'set enable-bracketed-paste off' 2>/dev/null
+__berry_prompt_command() {
+    local __berry_status=$?
+    printf '\033]633;P;Cwd=%s\007\033]133;D;%s\007' "$PWD" "$__berry_status"
+}
+PROMPT_COMMAND=__berry_prompt_command
+PS0=$'\033]133;C\007'
+PS1='\[\033]133;A\007\]$ \[\033]133;B\007\]'
+PS2='> '
+"#;
+
+/// zsh: precmd/preexec hooks, ZLE disabled so input is plain line-based text
+const ZSH_INIT_SCRIPT: &str = r#"# BerryEditor shell integration
+unsetopt zle prompt_sp
+autoload -Uz add-zsh-hook
+__berry_precmd() {
+    local __berry_status=$?
+    print -n "\033]633;P;Cwd=${PWD}\007\033]133;D;${__berry_status}\007"
+}
+__berry_preexec() {
+    print -n "\033]133;C\007"
+}
+add-zsh-hook precmd __berry_precmd
+add-zsh-hook preexec __berry_preexec
+PS1=$'%{\033]133;A\007%}$ %{\033]133;B\007%}'
+PS2='> '
+"#;
+
+/// Event decoded from the PTY byte stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellEvent {
+    /// Plain terminal output (markers stripped)
+    Output(String),
+    PromptStart,
+    CommandStart,
+    OutputStart,
+    CommandFinished { exit_code: Option<i32> },
+    Cwd(String),
+}
+
+/// Incremental parser turning raw PTY bytes into [`ShellEvent`]s
+///
+/// Sequences and UTF-8 characters split across reads are buffered until the
+/// next call to [`OscParser::feed`].
+#[derive(Debug, Default)]
+pub struct OscParser {
+    pending: Vec<u8>,
+}
+
+impl OscParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of PTY output and return the decoded events
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<ShellEvent> {
+        self.pending.extend_from_slice(bytes);
+        let data = std::mem::take(&mut self.pending);
+
+        let mut events = Vec::new();
+        let mut text = Vec::new();
+        let mut i = 0;
+
+        while i < data.len() {
+            if data[i] != ESC {
+                text.push(data[i]);
+                i += 1;
+                continue;
+            }
+
+            // Need at least one more byte to know whether this is an OSC
+            let Some(&next) = data.get(i + 1) else {
+                self.pending.extend_from_slice(&data[i..]);
+                break;
+            };
+            if next != b']' {
+                text.push(data[i]);
+                i += 1;
+                continue;
+            }
+
+            // OSC: ESC ] <payload> (BEL | ESC \)
+            let body_start = i + 2;
+            let Some((body_end, seq_end)) = find_osc_terminator(&data, body_start) else {
+                if data.len() - i > MAX_OSC_LEN {
+                    text.push(data[i]);
+                    i += 1;
+                    continue;
+                }
+                self.pending.extend_from_slice(&data[i..]);
+                break;
+            };
+
+            let payload = String::from_utf8_lossy(&data[body_start..body_end]);
+            if let Some(event) = parse_osc_payload(&payload) {
+                flush_text(&mut text, &mut events);
+                events.push(event);
+            }
+            i = seq_end;
+        }
+
+        // Keep an incomplete trailing UTF-8 character for the next chunk
+        let valid_len = utf8_complete_prefix(&text);
+        if valid_len < text.len() {
+            let tail = text.split_off(valid_len);
+            let mut rest = tail;
+            rest.extend_from_slice(&self.pending);
+            self.pending = rest;
+        }
+        flush_text(&mut text, &mut events);
+
+        events
+    }
+}
+
+fn flush_text(text: &mut Vec<u8>, events: &mut Vec<ShellEvent>) {
+    if !text.is_empty() {
+        events.push(ShellEvent::Output(
+            String::from_utf8_lossy(text).to_string(),
+        ));
+        text.clear();
+    }
+}
+
+/// Returns (payload end, index after terminator)
+fn find_osc_terminator(data: &[u8], start: usize) -> Option<(usize, usize)> {
+    let mut j = start;
+    while j < data.len() {
+        match data[j] {
+            BEL => return Some((j, j + 1)),
+            ESC => {
+                let next = *data.get(j + 1)?;
+                if next == b'\\' {
+                    return Some((j, j + 2));
+                }
+            }
+            _ => {}
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Length of the longest prefix that does not end in a truncated UTF-8 sequence
+fn utf8_complete_prefix(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+/// Decode an OSC payload (without the ESC ] prefix and terminator)
+fn parse_osc_payload(payload: &str) -> Option<ShellEvent> {
+    let mut parts = payload.splitn(3, ';');
+    let code = parts.next()?;
+    if code != "133" && code != "633" {
+        return None;
+    }
+
+    match parts.next()? {
+        "A" => Some(ShellEvent::PromptStart),
+        "B" => Some(ShellEvent::CommandStart),
+        "C" => Some(ShellEvent::OutputStart),
+        "D" => Some(ShellEvent::CommandFinished {
+            exit_code: parts.next().and_then(|s| s.trim().parse().ok()),
+        }),
+        "P" if code == "633" => parts
+            .next()
+            .and_then(|prop| prop.strip_prefix("Cwd="))
+            .map(|cwd| ShellEvent::Cwd(cwd.to_string())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_command_cycle() {
+        let mut parser = OscParser::new();
+        let events = parser.feed(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.txt\r\n\x1b]133;D;0\x07\x1b]633;P;Cwd=/tmp\x07",
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                ShellEvent::PromptStart,
+                ShellEvent::Output("$ ".to_string()),
+                ShellEvent::CommandStart,
+                ShellEvent::Output("ls\r\n".to_string()),
+                ShellEvent::OutputStart,
+                ShellEvent::Output("a.txt\r\n".to_string()),
+                ShellEvent::CommandFinished { exit_code: Some(0) },
+                ShellEvent::Cwd("/tmp".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sequence_split_across_reads() {
+        let mut parser = OscParser::new();
+        assert_eq!(parser.feed(b"out\x1b]13"), vec![ShellEvent::Output("out".to_string())]);
+        assert_eq!(
+            parser.feed(b"3;D;127\x1b\\"),
+            vec![ShellEvent::CommandFinished { exit_code: Some(127) }]
+        );
+    }
+
+    #[test]
+    fn test_utf8_split_across_reads() {
+        let mut parser = OscParser::new();
+        let bytes = "日本".as_bytes();
+        let first = parser.feed(&bytes[..2]);
+        assert!(first.is_empty());
+        assert_eq!(parser.feed(&bytes[2..]), vec![ShellEvent::Output("日本".to_string())]);
+    }
+
+    #[test]
+    fn test_unknown_osc_is_dropped_and_csi_kept() {
+        let mut parser = OscParser::new();
+        let events = parser.feed(b"\x1b]0;title\x07\x1b[31mred");
+        assert_eq!(events, vec![ShellEvent::Output("\x1b[31mred".to_string())]);
+    }
+
+    #[test]
+    fn test_shell_kind_from_program() {
+        assert_eq!(ShellKind::from_program("/bin/bash"), Some(ShellKind::Bash));
+        assert_eq!(ShellKind::from_program("/usr/local/bin/zsh"), Some(ShellKind::Zsh));
+        assert_eq!(ShellKind::from_program("/usr/bin/fish"), None);
+    }
+}
@@ -0,0 +1,42 @@
+use super::detection::detect_tasks;
+use super::types::{TaskDefinition, TaskRunInfo};
+use super::TaskManager;
+use std::path::PathBuf;
+use tauri::State;
+
+/// List detected and user-defined tasks for a project
+#[tauri::command]
+pub async fn task_list(project_path: String) -> Result<Vec<TaskDefinition>, String> {
+    detect_tasks(&PathBuf::from(project_path)).map_err(|e| e.to_string())
+}
+
+/// Run a task and its dependencies; returns the run ID
+#[tauri::command]
+pub async fn task_run(
+    project_path: String,
+    label: String,
+    manager: State<'_, TaskManager>,
+) -> Result<String, String> {
+    manager
+        .run_task(&PathBuf::from(project_path), &label)
+        .await
+        .map_err(|e| format!("Failed to run task: {}", e))
+}
+
+#[tauri::command]
+pub async fn task_get_run(
+    run_id: String,
+    manager: State<'_, TaskManager>,
+) -> Result<Option<TaskRunInfo>, String> {
+    Ok(manager.get_run(&run_id).await)
+}
+
+#[tauri::command]
+pub async fn task_list_runs(manager: State<'_, TaskManager>) -> Result<Vec<TaskRunInfo>, String> {
+    Ok(manager.list_runs().await)
+}
+
+#[tauri::command]
+pub async fn task_cancel(run_id: String, manager: State<'_, TaskManager>) -> Result<(), String> {
+    manager.cancel(&run_id).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,329 @@
+//! Task auto-detection
+//!
+//! Builds task definitions from the project's Cargo.toml, package.json scripts,
+//! Makefile, justfile and pytest configuration, then merges in user-defined
+//! tasks from `.berry/tasks.json` (user tasks replace detected ones with the
+//! same label).
+
+use super::types::*;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// User task definitions, relative to the project root
+pub const USER_TASKS_FILE: &str = ".berry/tasks.json";
+
+/// Detect all tasks for a project
+pub fn detect_tasks(root: &Path) -> Result<Vec<TaskDefinition>> {
+    let mut tasks = Vec::new();
+    tasks.extend(detect_cargo_tasks(root));
+    tasks.extend(detect_npm_tasks(root));
+    tasks.extend(detect_make_tasks(root));
+    tasks.extend(detect_just_tasks(root));
+    tasks.extend(detect_pytest_tasks(root));
+
+    for user_task in load_user_tasks(root)? {
+        tasks.retain(|t| t.label != user_task.label);
+        tasks.push(user_task);
+    }
+
+    Ok(tasks)
+}
+
+/// Load `.berry/tasks.json` (empty when the file does not exist)
+pub fn load_user_tasks(root: &Path) -> Result<Vec<TaskDefinition>> {
+    let path = root.join(USER_TASKS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: TasksFile = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid task definitions in {}", path.display()))?;
+
+    Ok(file
+        .tasks
+        .into_iter()
+        .map(|mut task| {
+            task.source = TaskSource::User;
+            task
+        })
+        .collect())
+}
+
+fn task(label: String, command: &str, args: Vec<String>, source: TaskSource) -> TaskDefinition {
+    TaskDefinition {
+        label,
+        command: command.to_string(),
+        args,
+        cwd: None,
+        env: HashMap::new(),
+        group: TaskGroup::None,
+        is_default: false,
+        depends_on: Vec::new(),
+        is_background: false,
+        problem_matcher: None,
+        source,
+    }
+}
+
+fn detect_cargo_tasks(root: &Path) -> Vec<TaskDefinition> {
+    if !root.join("Cargo.toml").exists() {
+        return Vec::new();
+    }
+
+    [
+        ("build", TaskGroup::Build, true),
+        ("check", TaskGroup::Build, false),
+        ("clippy", TaskGroup::Build, false),
+        ("test", TaskGroup::Test, true),
+        ("run", TaskGroup::Run, false),
+    ]
+    .into_iter()
+    .map(|(subcommand, group, is_default)| {
+        let mut t = task(
+            format!("cargo {}", subcommand),
+            "cargo",
+            vec![subcommand.to_string(), "--message-format=json".to_string()],
+            TaskSource::Cargo,
+        );
+        t.group = group;
+        t.is_default = is_default;
+        t.problem_matcher = Some(ProblemMatcherKind::CargoJson);
+        t
+    })
+    .collect()
+}
+
+fn detect_npm_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let Ok(content) = std::fs::read_to_string(root.join("package.json")) else {
+        return Vec::new();
+    };
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+    let Some(scripts) = package.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+
+    let runner = if root.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if root.join("yarn.lock").exists() {
+        "yarn"
+    } else {
+        "npm"
+    };
+
+    scripts
+        .iter()
+        .map(|(name, script)| {
+            let script = script.as_str().unwrap_or_default();
+            let mut t = task(
+                format!("{} {}", runner, name),
+                runner,
+                vec!["run".to_string(), name.clone()],
+                TaskSource::Npm,
+            );
+
+            t.group = match name.as_str() {
+                "build" => TaskGroup::Build,
+                "test" => TaskGroup::Test,
+                "start" | "dev" | "serve" => TaskGroup::Run,
+                _ => TaskGroup::None,
+            };
+            t.is_default = matches!(name.as_str(), "build" | "test");
+            t.is_background = ["watch", "dev", "serve", "start"]
+                .iter()
+                .any(|w| name.contains(w))
+                || script.contains("--watch");
+            t.problem_matcher = if script.contains("tsc") {
+                Some(ProblemMatcherKind::Tsc)
+            } else if script.contains("eslint") {
+                Some(ProblemMatcherKind::Eslint)
+            } else if script.contains("pytest") {
+                Some(ProblemMatcherKind::Pytest)
+            } else {
+                None
+            };
+            t
+        })
+        .collect()
+}
+
+fn detect_make_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let content = ["Makefile", "makefile", "GNUmakefile"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(root.join(name)).ok());
+    let Some(content) = content else {
+        return Vec::new();
+    };
+
+    parse_make_targets(&content)
+        .into_iter()
+        .map(|target| {
+            let mut t = task(format!("make {}", target), "make", vec![target.clone()], TaskSource::Make);
+            t.group = group_for_target(&target);
+            t
+        })
+        .collect()
+}
+
+fn detect_just_tasks(root: &Path) -> Vec<TaskDefinition> {
+    let content = ["justfile", "Justfile", ".justfile"]
+        .iter()
+        .find_map(|name| std::fs::read_to_string(root.join(name)).ok());
+    let Some(content) = content else {
+        return Vec::new();
+    };
+
+    parse_just_recipes(&content)
+        .into_iter()
+        .map(|recipe| {
+            let mut t = task(format!("just {}", recipe), "just", vec![recipe.clone()], TaskSource::Just);
+            t.group = group_for_target(&recipe);
+            t
+        })
+        .collect()
+}
+
+/// Whether the project has a pytest configuration
+pub fn has_pytest_config(root: &Path) -> bool {
+    root.join("pytest.ini").exists()
+        || root.join("conftest.py").exists()
+        || std::fs::read_to_string(root.join("pyproject.toml"))
+            .map(|c| c.contains("[tool.pytest"))
+            .unwrap_or(false)
+}
+
+fn detect_pytest_tasks(root: &Path) -> Vec<TaskDefinition> {
+    if !has_pytest_config(root) {
+        return Vec::new();
+    }
+
+    let mut t = task("pytest".to_string(), "pytest", Vec::new(), TaskSource::Pytest);
+    t.group = TaskGroup::Test;
+    t.problem_matcher = Some(ProblemMatcherKind::Pytest);
+    vec![t]
+}
+
+fn group_for_target(name: &str) -> TaskGroup {
+    match name {
+        "build" | "all" => TaskGroup::Build,
+        "test" | "check" => TaskGroup::Test,
+        "run" | "serve" | "dev" => TaskGroup::Run,
+        _ => TaskGroup::None,
+    }
+}
+
+/// Explicit targets from a Makefile (no pattern rules, variables or special targets)
+pub fn parse_make_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `VAR := value` / `VAR ::= value`
+        if rest.starts_with('=') || rest.starts_with(":=") || names.contains('=') {
+            continue;
+        }
+        for name in names.split_whitespace() {
+            if name.contains(['%', '$', '/']) || targets.iter().any(|t| t == name) {
+                continue;
+            }
+            targets.push(name.to_string());
+        }
+    }
+    targets
+}
+
+/// Recipe names from a justfile
+pub fn parse_just_recipes(content: &str) -> Vec<String> {
+    let mut recipes = Vec::new();
+    for line in content.lines() {
+        if line.starts_with([' ', '\t', '#', '[']) || line.trim().is_empty() {
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `name := value` assignments and `set shell := [...]` settings
+        if rest.starts_with('=') || head.starts_with("set ") || head.starts_with("alias ") {
+            continue;
+        }
+        let Some(name) = head.split_whitespace().next() else {
+            continue;
+        };
+        let name = name.trim_start_matches('@');
+        if name.is_empty() || name.starts_with('_') || name == "export" || name == "import" {
+            continue;
+        }
+        recipes.push(name.to_string());
+    }
+    recipes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_make_targets() {
+        let makefile = ".PHONY: build test\nCC := gcc\nbuild: main.o\n\t$(CC) -o app main.o\n%.o: %.c\n\tcc -c $<\ntest lint:\n\t./run-tests\n";
+        assert_eq!(parse_make_targets(makefile), vec!["build", "test", "lint"]);
+    }
+
+    #[test]
+    fn test_parse_just_recipes() {
+        let justfile = "set shell := [\"bash\", \"-c\"]\nversion := \"1.0\"\n\n# Build it\nbuild:\n    cargo build\n\n@test filter='': build\n    cargo test {{filter}}\n\n_helper:\n    echo hidden\n";
+        assert_eq!(parse_just_recipes(justfile), vec!["build", "test"]);
+    }
+
+    #[test]
+    fn test_detect_npm_and_user_tasks() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "tsc -p .", "watch": "tsc --watch", "lint": "eslint src"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join(".berry")).unwrap();
+        std::fs::write(
+            dir.path().join(USER_TASKS_FILE),
+            r#"{"tasks": [{"label": "npm lint", "command": "eslint", "args": ["--fix", "src"], "problem_matcher": "eslint"},
+                          {"label": "release", "command": "./release.sh", "depends_on": ["npm build"]}]}"#,
+        )
+        .unwrap();
+
+        let tasks = detect_tasks(dir.path()).unwrap();
+        let find = |label: &str| tasks.iter().find(|t| t.label == label).unwrap();
+
+        let build = find("npm build");
+        assert_eq!(build.group, TaskGroup::Build);
+        assert_eq!(build.problem_matcher, Some(ProblemMatcherKind::Tsc));
+        assert!(find("npm watch").is_background);
+
+        let lint = find("npm lint");
+        assert_eq!(lint.source, TaskSource::User);
+        assert_eq!(lint.args, vec!["--fix", "src"]);
+        assert_eq!(tasks.iter().filter(|t| t.label == "npm lint").count(), 1);
+
+        assert_eq!(find("release").depends_on, vec!["npm build"]);
+    }
+
+    #[test]
+    fn test_detect_cargo_tasks() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+
+        let tasks = detect_tasks(dir.path()).unwrap();
+        let build = tasks.iter().find(|t| t.label == "cargo build").unwrap();
+        assert!(build.is_default);
+        assert_eq!(build.problem_matcher, Some(ProblemMatcherKind::CargoJson));
+        assert!(tasks.iter().any(|t| t.label == "cargo test" && t.group == TaskGroup::Test));
+    }
+}
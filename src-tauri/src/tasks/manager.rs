@@ -0,0 +1,589 @@
+//! Task execution
+//!
+//! Runs task definitions as child processes, resolving `depends_on` first.
+//! Foreground dependencies must succeed before the task starts; background
+//! (watch) dependencies are started and left running. Output is collected per
+//! run and fed through the task's problem matcher.
+
+use super::detection::detect_tasks;
+use super::problem_matchers::{LineDisplay, ProblemCollector};
+use super::types::*;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, Notify, RwLock};
+
+/// Output kept per run; older output is dropped from the front
+const MAX_OUTPUT_BYTES: usize = 1_000_000;
+
+/// Finished runs kept for inspection
+const MAX_FINISHED_RUNS: usize = 50;
+
+struct RunState {
+    label: String,
+    status: TaskStatus,
+    exit_code: Option<i32>,
+    output: String,
+    collector: Option<ProblemCollector>,
+    is_background: bool,
+    started_at: i64,
+    finished_at: Option<i64>,
+}
+
+struct TaskRun {
+    id: String,
+    state: Mutex<RunState>,
+    cancel: Notify,
+}
+
+impl TaskRun {
+    fn new(task: &TaskDefinition, cwd: &Path) -> Arc<Self> {
+        Arc::new(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            state: Mutex::new(RunState {
+                label: task.label.clone(),
+                status: TaskStatus::Running,
+                exit_code: None,
+                output: String::new(),
+                collector: task.problem_matcher.map(|kind| ProblemCollector::new(kind, cwd)),
+                is_background: task.is_background,
+                started_at: chrono::Utc::now().timestamp_millis(),
+                finished_at: None,
+            }),
+            cancel: Notify::new(),
+        })
+    }
+
+    fn push_output(&self, line: &str) {
+        let mut state = self.state.lock().unwrap();
+        let display = match state.collector.as_mut() {
+            Some(collector) => {
+                if collector.is_cycle_start(line) {
+                    collector.reset();
+                }
+                collector.push_line(line)
+            }
+            None => LineDisplay::Show,
+        };
+
+        match display {
+            LineDisplay::Show => append_line(&mut state.output, line),
+            LineDisplay::Replace(text) => append_line(&mut state.output, &text),
+            LineDisplay::Hide => {}
+        }
+    }
+
+    fn finish(&self, status: TaskStatus, exit_code: Option<i32>) {
+        let mut state = self.state.lock().unwrap();
+        state.status = status;
+        state.exit_code = exit_code;
+        state.finished_at = Some(chrono::Utc::now().timestamp_millis());
+    }
+
+    fn status(&self) -> TaskStatus {
+        self.state.lock().unwrap().status
+    }
+
+    fn snapshot(&self) -> TaskRunInfo {
+        let state = self.state.lock().unwrap();
+        TaskRunInfo {
+            run_id: self.id.clone(),
+            label: state.label.clone(),
+            status: state.status,
+            exit_code: state.exit_code,
+            output: state.output.clone(),
+            diagnostics: state
+                .collector
+                .as_ref()
+                .map(|c| c.diagnostics().to_vec())
+                .unwrap_or_default(),
+            is_background: state.is_background,
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+        }
+    }
+}
+
+pub(crate) fn append_line(output: &mut String, line: &str) {
+    output.push_str(line);
+    output.push('\n');
+
+    if output.len() > MAX_OUTPUT_BYTES {
+        let excess = output.len() - MAX_OUTPUT_BYTES;
+        let cut = output[excess..]
+            .find('\n')
+            .map(|i| excess + i + 1)
+            .unwrap_or(output.len());
+        output.drain(..cut);
+    }
+}
+
+/// Order in which `label` and its dependencies must run (dependencies first)
+pub fn resolve_order(tasks: &[TaskDefinition], label: &str) -> Result<Vec<TaskDefinition>> {
+    fn visit(
+        tasks: &[TaskDefinition],
+        label: &str,
+        stack: &mut Vec<String>,
+        order: &mut Vec<TaskDefinition>,
+    ) -> Result<()> {
+        if order.iter().any(|t| t.label == label) {
+            return Ok(());
+        }
+        if stack.iter().any(|l| l == label) {
+            return Err(anyhow!("Task dependency cycle: {} -> {}", stack.join(" -> "), label));
+        }
+        let task = tasks
+            .iter()
+            .find(|t| t.label == label)
+            .ok_or_else(|| anyhow!("Unknown task: {}", label))?;
+
+        stack.push(label.to_string());
+        for dependency in &task.depends_on {
+            visit(tasks, dependency, stack, order)?;
+        }
+        stack.pop();
+
+        order.push(task.clone());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(tasks, label, &mut Vec::new(), &mut order)?;
+    Ok(order)
+}
+
+/// Tracks task runs for the desktop app
+#[derive(Clone, Default)]
+pub struct TaskManager {
+    runs: Arc<RwLock<HashMap<String, Arc<TaskRun>>>>,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a task (and its dependencies); returns the run ID of the task itself
+    pub async fn run_task(&self, root: &Path, label: &str) -> Result<String> {
+        let tasks = detect_tasks(root)?;
+        self.run_with_tasks(root, &tasks, label).await
+    }
+
+    /// Start `label` from an explicit task list
+    pub async fn run_with_tasks(&self, root: &Path, tasks: &[TaskDefinition], label: &str) -> Result<String> {
+        let mut order = resolve_order(tasks, label)?;
+        let task = order.pop().expect("resolve_order includes the task itself");
+
+        let cwd = task_cwd(root, &task);
+        let run = TaskRun::new(&task, &cwd);
+        let run_id = run.id.clone();
+        self.insert(run.clone()).await;
+
+        let manager = self.clone();
+        let root = root.to_path_buf();
+        tokio::spawn(async move {
+            for dependency in order {
+                if dependency.is_background {
+                    if !manager.is_running(&dependency.label).await {
+                        let dep_run = TaskRun::new(&dependency, &task_cwd(&root, &dependency));
+                        manager.insert(dep_run.clone()).await;
+                        tokio::spawn(execute(dep_run, dependency, root.clone()));
+                    }
+                    continue;
+                }
+
+                let dep_run = TaskRun::new(&dependency, &task_cwd(&root, &dependency));
+                manager.insert(dep_run.clone()).await;
+                let label = dependency.label.clone();
+                let finished = execute(dep_run.clone(), dependency, root.clone());
+                tokio::pin!(finished);
+                tokio::select! {
+                    _ = &mut finished => {}
+                    _ = run.cancel.notified() => {
+                        // Cancelling the task stops the dependency it is waiting for
+                        dep_run.cancel.notify_one();
+                        finished.await;
+                        run.push_output(&format!("Task cancelled while '{}' was running", label));
+                        run.finish(TaskStatus::Cancelled, None);
+                        return;
+                    }
+                }
+
+                if dep_run.status() != TaskStatus::Succeeded {
+                    run.push_output(&format!("Dependency '{}' did not succeed; not starting '{}'", label, task.label));
+                    run.finish(TaskStatus::Failed, None);
+                    return;
+                }
+            }
+
+            execute(run, task, root).await;
+        });
+
+        Ok(run_id)
+    }
+
+    async fn insert(&self, run: Arc<TaskRun>) {
+        let mut runs = self.runs.write().await;
+
+        // Drop the oldest finished runs
+        let mut finished: Vec<(i64, String)> = runs
+            .values()
+            .filter(|r| r.status() != TaskStatus::Running)
+            .map(|r| (r.state.lock().unwrap().started_at, r.id.clone()))
+            .collect();
+        if finished.len() >= MAX_FINISHED_RUNS {
+            finished.sort();
+            for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_RUNS) {
+                runs.remove(id);
+            }
+        }
+
+        runs.insert(run.id.clone(), run);
+    }
+
+    async fn is_running(&self, label: &str) -> bool {
+        self.runs
+            .read()
+            .await
+            .values()
+            .any(|r| r.status() == TaskStatus::Running && r.state.lock().unwrap().label == label)
+    }
+
+    pub async fn get_run(&self, run_id: &str) -> Option<TaskRunInfo> {
+        self.runs.read().await.get(run_id).map(|r| r.snapshot())
+    }
+
+    /// All known runs, newest first
+    pub async fn list_runs(&self) -> Vec<TaskRunInfo> {
+        let mut runs: Vec<TaskRunInfo> = self.runs.read().await.values().map(|r| r.snapshot()).collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        runs
+    }
+
+    /// Stop a running task
+    pub async fn cancel(&self, run_id: &str) -> Result<()> {
+        let runs = self.runs.read().await;
+        let run = runs.get(run_id).ok_or_else(|| anyhow!("Task run not found: {}", run_id))?;
+        run.cancel.notify_one();
+        Ok(())
+    }
+}
+
+fn task_cwd(root: &Path, task: &TaskDefinition) -> PathBuf {
+    match &task.cwd {
+        Some(cwd) => root.join(cwd),
+        None => root.to_path_buf(),
+    }
+}
+
+fn build_command(task: &TaskDefinition, root: &Path) -> Command {
+    // A bare command line without args runs through the shell
+    let mut command = if task.args.is_empty() && task.command.contains(char::is_whitespace) {
+        if cfg!(windows) {
+            let mut c = Command::new("cmd");
+            c.arg("/C").arg(&task.command);
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c").arg(&task.command);
+            c
+        }
+    } else {
+        let mut c = Command::new(&task.command);
+        c.args(&task.args);
+        c
+    };
+
+    command
+        .current_dir(task_cwd(root, task))
+        .envs(&task.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Lead a process group so cancelling also stops what the shell started
+    #[cfg(unix)]
+    command.process_group(0);
+    command
+}
+
+/// Kill the task process and every process in its group
+fn kill_task(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: plain syscall; the group was created by `build_command`
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    let _ = child.start_kill();
+}
+
+async fn cancel_run(run: &TaskRun, child: &mut Child) {
+    kill_task(child);
+    let _ = child.wait().await;
+    run.push_output("Task cancelled");
+    run.finish(TaskStatus::Cancelled, None);
+}
+
+/// Run a single task to completion, recording output and final status
+async fn execute(run: Arc<TaskRun>, task: TaskDefinition, root: PathBuf) {
+    let mut child = match build_command(&task, &root).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            run.push_output(&format!("Failed to start '{}': {}", task.command, e));
+            run.finish(TaskStatus::Failed, None);
+            return;
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    if let Some(stdout) = child.stdout.take() {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = tx.send(line);
+            }
+        });
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = tx.send(line);
+            }
+        });
+    }
+    drop(tx);
+
+    loop {
+        tokio::select! {
+            line = rx.recv() => match line {
+                Some(line) => run.push_output(&line),
+                None => break,
+            },
+            _ = run.cancel.notified() => {
+                cancel_run(&run, &mut child).await;
+                return;
+            }
+        }
+    }
+
+    // Output can close before the process exits; cancel still applies
+    let status = tokio::select! {
+        status = child.wait() => status,
+        _ = run.cancel.notified() => {
+            cancel_run(&run, &mut child).await;
+            return;
+        }
+    };
+    match status {
+        Ok(status) => {
+            let result = if status.success() {
+                TaskStatus::Succeeded
+            } else {
+                TaskStatus::Failed
+            };
+            run.finish(result, status.code());
+        }
+        Err(e) => {
+            run.push_output(&format!("Failed to wait for task: {}", e));
+            run.finish(TaskStatus::Failed, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn shell_task(label: &str, command: &str, depends_on: &[&str]) -> TaskDefinition {
+        TaskDefinition {
+            label: label.to_string(),
+            command: command.to_string(),
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            group: TaskGroup::None,
+            is_default: false,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            is_background: false,
+            problem_matcher: None,
+            source: TaskSource::User,
+        }
+    }
+
+    async fn wait_finished(manager: &TaskManager, run_id: &str) -> TaskRunInfo {
+        for _ in 0..100 {
+            let info = manager.get_run(run_id).await.unwrap();
+            if info.status != TaskStatus::Running {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("task did not finish");
+    }
+
+    #[test]
+    fn test_resolve_order_and_cycles() {
+        let tasks = vec![
+            shell_task("deploy", "true", &["build", "test"]),
+            shell_task("test", "true", &["build"]),
+            shell_task("build", "true", &[]),
+            shell_task("a", "true", &["b"]),
+            shell_task("b", "true", &["a"]),
+        ];
+
+        let order: Vec<String> = resolve_order(&tasks, "deploy")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.label)
+            .collect();
+        assert_eq!(order, vec!["build", "test", "deploy"]);
+
+        assert!(resolve_order(&tasks, "a").unwrap_err().to_string().contains("cycle"));
+        assert!(resolve_order(&tasks, "missing").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_task_with_dependency() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tasks = vec![
+            shell_task("prepare", "echo preparing && touch prepared", &[]),
+            shell_task("main", "test -f prepared && echo done", &["prepare"]),
+        ];
+
+        let manager = TaskManager::new();
+        let run_id = manager.run_with_tasks(dir.path(), &tasks, "main").await.unwrap();
+        let info = wait_finished(&manager, &run_id).await;
+
+        assert_eq!(info.status, TaskStatus::Succeeded);
+        assert_eq!(info.exit_code, Some(0));
+        assert_eq!(info.output, "done\n");
+        assert_eq!(manager.list_runs().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_dependency_stops_task() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tasks = vec![
+            shell_task("broken", "exit 3", &[]),
+            shell_task("main", "echo should-not-run", &["broken"]),
+        ];
+
+        let manager = TaskManager::new();
+        let run_id = manager.run_with_tasks(dir.path(), &tasks, "main").await.unwrap();
+        let info = wait_finished(&manager, &run_id).await;
+
+        assert_eq!(info.status, TaskStatus::Failed);
+        assert!(!info.output.contains("should-not-run"));
+    }
+
+    #[tokio::test]
+    async fn test_problem_matcher_and_cancel() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut task = shell_task(
+            "tsc",
+            "echo \"src/a.ts(2,5): error TS2304: Cannot find name 'x'.\" && sleep 30",
+            &[],
+        );
+        task.problem_matcher = Some(ProblemMatcherKind::Tsc);
+        task.is_background = true;
+
+        let manager = TaskManager::new();
+        let run_id = manager.run_with_tasks(dir.path(), &[task], "tsc").await.unwrap();
+
+        let mut diagnostics = Vec::new();
+        for _ in 0..100 {
+            diagnostics = manager.get_run(&run_id).await.unwrap().diagnostics;
+            if !diagnostics.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+
+        manager.cancel(&run_id).await.unwrap();
+        let info = wait_finished(&manager, &run_id).await;
+        assert_eq!(info.status, TaskStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_running_dependency() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tasks = vec![
+            shell_task("slow", "echo started && sleep 30", &[]),
+            shell_task("main", "echo should-not-run", &["slow"]),
+        ];
+
+        let manager = TaskManager::new();
+        let run_id = manager.run_with_tasks(dir.path(), &tasks, "main").await.unwrap();
+        let mut dependency = None;
+        for _ in 0..100 {
+            dependency = manager
+                .list_runs()
+                .await
+                .into_iter()
+                .find(|r| r.label == "slow" && r.output.contains("started"));
+            if dependency.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let dependency = dependency.expect("dependency started");
+
+        manager.cancel(&run_id).await.unwrap();
+        let info = wait_finished(&manager, &run_id).await;
+        assert_eq!(info.status, TaskStatus::Cancelled);
+        assert!(!info.output.contains("should-not-run"));
+        let dependency = wait_finished(&manager, &dependency.run_id).await;
+        assert_eq!(dependency.status, TaskStatus::Cancelled);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_kills_process_group() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_file = dir.path().join("child.pid");
+        // The grandchild keeps the pipes open after the shell is killed
+        let tasks = vec![
+            shell_task("tree", "sleep 30 & echo $! > child.pid; echo started; wait", &[]),
+            // Output closes early; the final wait must still be cancellable
+            shell_task("quiet", "echo started && exec sleep 30 >/dev/null 2>&1", &[]),
+        ];
+
+        let manager = TaskManager::new();
+        for label in ["tree", "quiet"] {
+            let run_id = manager.run_with_tasks(dir.path(), &tasks, label).await.unwrap();
+            for _ in 0..100 {
+                let info = manager.get_run(&run_id).await.unwrap();
+                if info.output.contains("started") {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            manager.cancel(&run_id).await.unwrap();
+            let info = wait_finished(&manager, &run_id).await;
+            assert_eq!(info.status, TaskStatus::Cancelled, "{}", label);
+        }
+
+        // An orphan may linger as a zombie until it is reaped; that still counts as stopped
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let output = std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", pid.trim()])
+            .output()
+            .unwrap();
+        let stat = String::from_utf8_lossy(&output.stdout);
+        assert!(stat.trim().is_empty() || stat.starts_with('Z'), "grandchild {} still running", pid.trim());
+    }
+}
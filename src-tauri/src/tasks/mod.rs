@@ -0,0 +1,8 @@
+pub mod commands;
+pub mod detection;
+pub mod manager;
+pub mod problem_matchers;
+pub mod types;
+
+pub use manager::TaskManager;
+pub use types::*;
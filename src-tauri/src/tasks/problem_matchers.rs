@@ -0,0 +1,380 @@
+//! Problem Matchers
+//!
+//! Turn task output into [`TaskDiagnostic`]s. Output is fed line by line into a
+//! [`ProblemCollector`], which keeps whatever state a format needs across lines
+//! (rustc's `-->` location line, eslint's per-file blocks, pytest's `E` lines).
+
+use super::types::{DiagnosticSeverity, ProblemMatcherKind, TaskDiagnostic};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+static RUSTC_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap());
+static RUSTC_LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)$").unwrap());
+/// `src/a.ts(3,7): error TS2322: ...` and `src/a.ts:3:7 - error TS2322: ...` (--pretty)
+static TSC_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.+?)(?:\((\d+),(\d+)\)|:(\d+):(\d+)) ?[:\-] (error|warning|message) (TS\d+): (.+)$").unwrap()
+});
+static ESLINT_PROBLEM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s+(\d+):(\d+)\s+(error|warning)\s+(.+?)(?:\s{2,}(\S+))?$").unwrap());
+static PYTEST_LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(.+\.py):(\d+): (\w+)$").unwrap());
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap());
+
+/// What to do with an output line after matching
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineDisplay {
+    /// Show the line as-is
+    Show,
+    /// Show this text instead (e.g. the rendered message of a cargo JSON line)
+    Replace(String),
+    /// Machine-readable noise (e.g. cargo artifact notifications)
+    Hide,
+}
+
+/// Stateful line parser for one task run
+#[derive(Debug)]
+pub struct ProblemCollector {
+    kind: ProblemMatcherKind,
+    cwd: PathBuf,
+    diagnostics: Vec<TaskDiagnostic>,
+    /// rustc: header waiting for its `-->` line
+    pending_rustc: Option<(DiagnosticSeverity, Option<String>, String)>,
+    /// eslint: file of the current block
+    eslint_file: Option<String>,
+    /// pytest: first `E` line of the current failure
+    pytest_error: Option<String>,
+}
+
+impl ProblemCollector {
+    /// `cwd` is used to resolve relative paths in the output
+    pub fn new(kind: ProblemMatcherKind, cwd: &Path) -> Self {
+        Self {
+            kind,
+            cwd: cwd.to_path_buf(),
+            diagnostics: Vec::new(),
+            pending_rustc: None,
+            eslint_file: None,
+            pytest_error: None,
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[TaskDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Whether the line starts a new compile cycle of a watch task, after which
+    /// previously reported problems are stale
+    pub fn is_cycle_start(&self, line: &str) -> bool {
+        match self.kind {
+            ProblemMatcherKind::Tsc => {
+                line.contains("File change detected") || line.contains("Starting compilation")
+            }
+            ProblemMatcherKind::CargoJson | ProblemMatcherKind::Rustc => {
+                line.starts_with("[Running") || line.starts_with("[Finished running")
+            }
+            ProblemMatcherKind::Eslint | ProblemMatcherKind::Pytest => false,
+        }
+    }
+
+    /// Forget all problems (start of a new watch cycle)
+    pub fn reset(&mut self) {
+        self.diagnostics.clear();
+        self.pending_rustc = None;
+        self.eslint_file = None;
+        self.pytest_error = None;
+    }
+
+    /// Feed one output line (without trailing newline)
+    pub fn push_line(&mut self, raw_line: &str) -> LineDisplay {
+        let line = ANSI_ESCAPE.replace_all(raw_line, "");
+        match self.kind {
+            ProblemMatcherKind::CargoJson => return self.push_cargo_json(&line),
+            ProblemMatcherKind::Rustc => self.push_rustc(&line),
+            ProblemMatcherKind::Tsc => self.push_tsc(&line),
+            ProblemMatcherKind::Eslint => self.push_eslint(&line),
+            ProblemMatcherKind::Pytest => self.push_pytest(&line),
+        }
+        LineDisplay::Show
+    }
+
+    fn resolve(&self, file: &str) -> String {
+        let path = Path::new(file);
+        if path.is_absolute() {
+            file.to_string()
+        } else {
+            self.cwd.join(path).to_string_lossy().to_string()
+        }
+    }
+
+    fn push_cargo_json(&mut self, line: &str) -> LineDisplay {
+        if !line.starts_with('{') {
+            // Plain stderr lines ("Compiling ...") and test output, which may
+            // also contain human-readable compiler messages
+            self.push_rustc(line);
+            return LineDisplay::Show;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            return LineDisplay::Show;
+        };
+
+        if value["reason"] != "compiler-message" {
+            return LineDisplay::Hide;
+        }
+
+        let message = &value["message"];
+        let rendered = message["rendered"].as_str().unwrap_or_default().trim_end().to_string();
+        let severity = match message["level"].as_str() {
+            Some("error") | Some("error: internal compiler error") => DiagnosticSeverity::Error,
+            Some("warning") => DiagnosticSeverity::Warning,
+            Some("note") | Some("help") => DiagnosticSeverity::Info,
+            _ => DiagnosticSeverity::Hint,
+        };
+
+        let primary_span = message["spans"]
+            .as_array()
+            .and_then(|spans| spans.iter().find(|s| s["is_primary"] == true));
+
+        if let Some(span) = primary_span {
+            let as_usize = |v: &serde_json::Value| v.as_u64().map(|n| n as usize);
+            let file = span["file_name"].as_str().unwrap_or_default();
+            let manifest_dir = value["manifest_path"]
+                .as_str()
+                .and_then(|p| Path::new(p).parent())
+                .map(Path::to_path_buf);
+            let file = match manifest_dir {
+                Some(dir) if !Path::new(file).is_absolute() => dir.join(file).to_string_lossy().to_string(),
+                _ => self.resolve(file),
+            };
+
+            self.diagnostics.push(TaskDiagnostic {
+                file,
+                line: as_usize(&span["line_start"]).unwrap_or(1),
+                column: as_usize(&span["column_start"]).unwrap_or(1),
+                end_line: as_usize(&span["line_end"]),
+                end_column: as_usize(&span["column_end"]),
+                severity,
+                message: message["message"].as_str().unwrap_or_default().to_string(),
+                code: message["code"]["code"].as_str().map(|s| s.to_string()),
+                source: "cargo".to_string(),
+            });
+        }
+
+        if rendered.is_empty() {
+            LineDisplay::Hide
+        } else {
+            LineDisplay::Replace(rendered)
+        }
+    }
+
+    fn push_rustc(&mut self, line: &str) {
+        if let Some(caps) = RUSTC_HEADER.captures(line) {
+            let severity = if &caps[1] == "error" {
+                DiagnosticSeverity::Error
+            } else {
+                DiagnosticSeverity::Warning
+            };
+            let message = caps[3].to_string();
+            // Summary lines such as "aborting due to 2 previous errors" have no location
+            self.pending_rustc = Some((severity, caps.get(2).map(|m| m.as_str().to_string()), message));
+            return;
+        }
+
+        if let Some(caps) = RUSTC_LOCATION.captures(line) {
+            if let Some((severity, code, message)) = self.pending_rustc.take() {
+                let file = self.resolve(&caps[1]);
+                self.diagnostics.push(TaskDiagnostic {
+                    file,
+                    line: caps[2].parse().unwrap_or(1),
+                    column: caps[3].parse().unwrap_or(1),
+                    end_line: None,
+                    end_column: None,
+                    severity,
+                    message,
+                    code,
+                    source: "rustc".to_string(),
+                });
+            }
+        }
+    }
+
+    fn push_tsc(&mut self, line: &str) {
+        let Some(caps) = TSC_LINE.captures(line) else {
+            return;
+        };
+        let number = |a: usize, b: usize| {
+            caps.get(a)
+                .or_else(|| caps.get(b))
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(1)
+        };
+        let severity = match &caps[6] {
+            "error" => DiagnosticSeverity::Error,
+            "warning" => DiagnosticSeverity::Warning,
+            _ => DiagnosticSeverity::Info,
+        };
+
+        self.diagnostics.push(TaskDiagnostic {
+            file: self.resolve(caps[1].trim()),
+            line: number(2, 4),
+            column: number(3, 5),
+            end_line: None,
+            end_column: None,
+            severity,
+            message: caps[8].to_string(),
+            code: Some(caps[7].to_string()),
+            source: "tsc".to_string(),
+        });
+    }
+
+    fn push_eslint(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            self.eslint_file = None;
+            return;
+        }
+
+        if let Some(caps) = ESLINT_PROBLEM.captures(line) {
+            let Some(file) = self.eslint_file.clone() else {
+                return;
+            };
+            let severity = if &caps[3] == "error" {
+                DiagnosticSeverity::Error
+            } else {
+                DiagnosticSeverity::Warning
+            };
+            self.diagnostics.push(TaskDiagnostic {
+                file,
+                line: caps[1].parse().unwrap_or(1),
+                column: caps[2].parse().unwrap_or(1),
+                end_line: None,
+                end_column: None,
+                severity,
+                message: caps[4].to_string(),
+                code: caps.get(5).map(|m| m.as_str().to_string()),
+                source: "eslint".to_string(),
+            });
+            return;
+        }
+
+        // The stylish formatter prints the file path unindented before its problems
+        if !line.starts_with(char::is_whitespace) && !line.starts_with('✖') {
+            self.eslint_file = Some(self.resolve(line.trim()));
+        }
+    }
+
+    fn push_pytest(&mut self, line: &str) {
+        if let Some(error) = line.strip_prefix('E') {
+            if error.starts_with(char::is_whitespace) && self.pytest_error.is_none() {
+                self.pytest_error = Some(error.trim().to_string());
+            }
+            return;
+        }
+
+        if let Some(caps) = PYTEST_LOCATION.captures(line) {
+            let message = match self.pytest_error.take() {
+                Some(detail) => format!("{}: {}", &caps[3], detail),
+                None => caps[3].to_string(),
+            };
+            self.diagnostics.push(TaskDiagnostic {
+                file: self.resolve(&caps[1]),
+                line: caps[2].parse().unwrap_or(1),
+                column: 1,
+                end_line: None,
+                end_column: None,
+                severity: DiagnosticSeverity::Error,
+                message,
+                code: None,
+                source: "pytest".to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(kind: ProblemMatcherKind, output: &str) -> Vec<TaskDiagnostic> {
+        let mut collector = ProblemCollector::new(kind, Path::new("/proj"));
+        for line in output.lines() {
+            collector.push_line(line);
+        }
+        collector.diagnostics().to_vec()
+    }
+
+    #[test]
+    fn test_cargo_json() {
+        let line = r#"{"reason":"compiler-message","manifest_path":"/proj/Cargo.toml","message":{"level":"error","message":"mismatched types","code":{"code":"E0308"},"rendered":"error[E0308]: mismatched types\n","spans":[{"file_name":"src/main.rs","line_start":4,"line_end":4,"column_start":18,"column_end":25,"is_primary":true}]}}"#;
+        let mut collector = ProblemCollector::new(ProblemMatcherKind::CargoJson, Path::new("/proj"));
+
+        assert_eq!(
+            collector.push_line(line),
+            LineDisplay::Replace("error[E0308]: mismatched types".to_string())
+        );
+        assert_eq!(
+            collector.push_line(r#"{"reason":"compiler-artifact","target":{}}"#),
+            LineDisplay::Hide
+        );
+        assert_eq!(collector.push_line("   Compiling demo v0.1.0"), LineDisplay::Show);
+
+        let diag = &collector.diagnostics()[0];
+        assert_eq!(diag.file, "/proj/src/main.rs");
+        assert_eq!((diag.line, diag.column, diag.end_column), (4, 18, Some(25)));
+        assert_eq!(diag.severity, DiagnosticSeverity::Error);
+        assert_eq!(diag.code.as_deref(), Some("E0308"));
+    }
+
+    #[test]
+    fn test_rustc_human() {
+        let output = "warning: unused variable: `x`\n --> src/lib.rs:2:9\n  |\nerror: aborting due to 1 previous error\n";
+        let diags = collect(ProblemMatcherKind::Rustc, output);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].file, "/proj/src/lib.rs");
+        assert_eq!(diags[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diags[0].message, "unused variable: `x`");
+    }
+
+    #[test]
+    fn test_tsc_both_formats() {
+        let output = "src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\nsrc/b.ts:10:1 - error TS1005: ';' expected.\n";
+        let diags = collect(ProblemMatcherKind::Tsc, output);
+        assert_eq!(diags.len(), 2);
+        assert_eq!((diags[0].line, diags[0].column), (3, 7));
+        assert_eq!(diags[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(diags[1].file, "/proj/src/b.ts");
+        assert_eq!((diags[1].line, diags[1].column), (10, 1));
+    }
+
+    #[test]
+    fn test_eslint_stylish() {
+        let output = "\n/proj/src/app.js\n  1:10  error    'foo' is defined but never used  no-unused-vars\n  3:1   warning  Unexpected console statement     no-console\n\n✖ 2 problems (1 error, 1 warning)\n";
+        let diags = collect(ProblemMatcherKind::Eslint, output);
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].file, "/proj/src/app.js");
+        assert_eq!(diags[0].message, "'foo' is defined but never used");
+        assert_eq!(diags[0].code.as_deref(), Some("no-unused-vars"));
+        assert_eq!(diags[1].severity, DiagnosticSeverity::Warning);
+    }
+
+    #[test]
+    fn test_pytest_failure() {
+        let output = "    def test_add():\n>       assert add(1, 2) == 4\nE       assert 3 == 4\nE        +  where 3 = add(1, 2)\n\ntests/test_math.py:5: AssertionError\n";
+        let diags = collect(ProblemMatcherKind::Pytest, output);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].file, "/proj/tests/test_math.py");
+        assert_eq!(diags[0].line, 5);
+        assert_eq!(diags[0].message, "AssertionError: assert 3 == 4");
+    }
+
+    #[test]
+    fn test_watch_cycle_reset() {
+        let mut collector = ProblemCollector::new(ProblemMatcherKind::Tsc, Path::new("/proj"));
+        collector.push_line("src/a.ts(1,1): error TS1005: ';' expected.");
+        let line = "[10:00:00 AM] File change detected. Starting incremental compilation...";
+        assert!(collector.is_cycle_start(line));
+        collector.reset();
+        assert!(collector.diagnostics().is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where a task definition came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSource {
+    Cargo,
+    Npm,
+    Make,
+    Just,
+    Pytest,
+    User,
+}
+
+/// Task group used for keyboard invocation (run build task / run test task)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskGroup {
+    Build,
+    Test,
+    Run,
+    #[default]
+    None,
+}
+
+/// Output parser applied to a task's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProblemMatcherKind {
+    /// `cargo --message-format=json`
+    CargoJson,
+    /// Human-readable rustc / cargo output
+    Rustc,
+    Tsc,
+    Eslint,
+    Pytest,
+}
+
+/// Task definition (auto-detected or from `.berry/tasks.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDefinition {
+    pub label: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory, relative to the project root
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub group: TaskGroup,
+    /// Default task of its group (run by the keyboard shortcut)
+    #[serde(default)]
+    pub is_default: bool,
+    /// Labels of tasks that must run before this one
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Long-running / watch task; dependents do not wait for it
+    #[serde(default)]
+    pub is_background: bool,
+    #[serde(default)]
+    pub problem_matcher: Option<ProblemMatcherKind>,
+    #[serde(default = "default_source")]
+    pub source: TaskSource,
+}
+
+fn default_source() -> TaskSource {
+    TaskSource::User
+}
+
+/// `.berry/tasks.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TasksFile {
+    #[serde(default)]
+    pub tasks: Vec<TaskDefinition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+/// Problem found in task output (1-based line and column)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDiagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    pub code: Option<String>,
+    /// Matcher that produced it (e.g. "cargo", "tsc")
+    pub source: String,
+}
+
+/// Snapshot of a task run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRunInfo {
+    pub run_id: String,
+    pub label: String,
+    pub status: TaskStatus,
+    pub exit_code: Option<i32>,
+    pub output: String,
+    pub diagnostics: Vec<TaskDiagnostic>,
+    pub is_background: bool,
+    /// Unix timestamp (ms)
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
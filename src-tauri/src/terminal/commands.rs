@@ -22,31 +22,100 @@ pub async fn terminal_execute_command(
         .map_err(|e| format!("Failed to get terminal: {}", e))?;
 
     // Execute command
-    let result = if background.unwrap_or(false) {
+    if background.unwrap_or(false) {
         // Execute in background
-        terminal
+        let process_id = terminal
             .execute_background(&command)
             .await
-            .map_err(|e| format!("Failed to execute background command: {}", e))?
-    } else {
-        // Execute normally
-        terminal
-            .execute(&command)
-            .await
-            .map_err(|e| format!("Failed to execute command: {}", e))?
-    };
-
-    let process_id = if background.unwrap_or(false) {
-        Some(result.clone())
-    } else {
-        None
-    };
-
-    Ok(TerminalCommandResponse {
-        output: result,
-        success: true,
-        process_id,
-    })
+            .map_err(|e| format!("Failed to execute background command: {}", e))?;
+
+        return Ok(TerminalCommandResponse {
+            output: process_id.clone(),
+            success: true,
+            process_id: Some(process_id),
+            command_id: None,
+            exit_code: None,
+            duration_ms: None,
+            cwd: None,
+        });
+    }
+
+    let record = terminal
+        .run_command(&command)
+        .await
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    Ok(command_response(record.into()))
+}
+
+/// Build the command response from a finished command record. A command
+/// that timed out or was killed has no exit code and did not succeed.
+fn command_response(record: CommandRecordInfo) -> TerminalCommandResponse {
+    TerminalCommandResponse {
+        success: record.exit_code == Some(0),
+        output: record.output,
+        process_id: None,
+        command_id: Some(record.id),
+        exit_code: record.exit_code,
+        duration_ms: Some(record.duration_ms),
+        cwd: record.cwd,
+    }
+}
+
+/// List finished commands with exit codes and durations
+#[tauri::command]
+pub async fn terminal_list_commands(
+    project_path: String,
+    terminal_manager: State<'_, TerminalManagerState>,
+) -> Result<Vec<CommandRecordInfo>, String> {
+    let project_path_buf = PathBuf::from(project_path);
+
+    let terminal = terminal_manager
+        .get_or_create_terminal(project_path_buf)
+        .await
+        .map_err(|e| format!("Failed to get terminal: {}", e))?;
+
+    let records = terminal.get_command_records().await;
+
+    Ok(records.into_iter().map(CommandRecordInfo::from).collect())
+}
+
+/// Get the most recently finished command (for "copy output of last command")
+#[tauri::command]
+pub async fn terminal_get_last_command(
+    project_path: String,
+    terminal_manager: State<'_, TerminalManagerState>,
+) -> Result<Option<CommandRecordInfo>, String> {
+    let project_path_buf = PathBuf::from(project_path);
+
+    let terminal = terminal_manager
+        .get_or_create_terminal(project_path_buf)
+        .await
+        .map_err(|e| format!("Failed to get terminal: {}", e))?;
+
+    Ok(terminal.last_command_record().await.map(CommandRecordInfo::from))
+}
+
+/// Re-run a previously executed command
+#[tauri::command]
+pub async fn terminal_rerun_command(
+    project_path: String,
+    command_id: String,
+    terminal_manager: State<'_, TerminalManagerState>,
+) -> Result<TerminalCommandResponse, String> {
+    let project_path_buf = PathBuf::from(project_path);
+
+    let terminal = terminal_manager
+        .get_or_create_terminal(project_path_buf)
+        .await
+        .map_err(|e| format!("Failed to get terminal: {}", e))?;
+
+    let record = terminal
+        .rerun_command(&command_id)
+        .await
+        .map_err(|e| format!("Failed to re-run command: {}", e))?;
+
+    Ok(command_response(record.into()))
 }
 
 /// Get command history
@@ -164,3 +233,30 @@ pub async fn terminal_get_current_directory(
 
     Ok(cwd.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(exit_code: Option<i32>) -> CommandRecordInfo {
+        CommandRecordInfo {
+            id: "1".to_string(),
+            command: "cargo build".to_string(),
+            cwd: Some("/p".to_string()),
+            output: String::new(),
+            exit_code,
+            started_at: 0,
+            duration_ms: 30_000,
+        }
+    }
+
+    #[test]
+    fn test_command_response_success() {
+        assert!(command_response(record(Some(0))).success);
+        assert!(!command_response(record(Some(101))).success);
+        // Timed out or killed before reporting an exit code
+        let timed_out = command_response(record(None));
+        assert!(!timed_out.success);
+        assert_eq!(timed_out.exit_code, None);
+    }
+}
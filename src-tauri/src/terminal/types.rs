@@ -13,6 +13,36 @@ pub struct TerminalCommandResponse {
     pub output: String,
     pub success: bool,
     pub process_id: Option<String>, // For background processes
+    pub command_id: Option<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    pub cwd: Option<String>,
+}
+
+/// Finished command info (from shell integration markers)
+#[derive(Debug, Serialize, Clone)]
+pub struct CommandRecordInfo {
+    pub id: String,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub output: String,
+    pub exit_code: Option<i32>,
+    pub started_at: i64,
+    pub duration_ms: u64,
+}
+
+impl From<crate::persistent_terminal::CommandRecord> for CommandRecordInfo {
+    fn from(record: crate::persistent_terminal::CommandRecord) -> Self {
+        Self {
+            id: record.id,
+            command: record.command,
+            cwd: record.cwd.map(|dir| dir.to_string_lossy().to_string()),
+            output: record.output,
+            exit_code: record.exit_code,
+            started_at: record.started_at,
+            duration_ms: record.duration_ms,
+        }
+    }
 }
 
 /// Background process info
@@ -0,0 +1,59 @@
+use super::discovery::{detect_frameworks, discover_tests};
+use super::manager::debug_target;
+use super::types::{DebugTarget, RunTestsRequest, TestCase, TestRunInfo};
+use super::TestManager;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Discover the tests of every framework configured in a project
+#[tauri::command]
+pub async fn test_discover(project_path: String) -> Result<Vec<TestCase>, String> {
+    let root = PathBuf::from(project_path);
+    let mut tests = Vec::new();
+    for framework in detect_frameworks(&root) {
+        let discovered = discover_tests(&root, framework)
+            .await
+            .map_err(|e| format!("Failed to discover {:?} tests: {}", framework, e))?;
+        tests.extend(discovered);
+    }
+    Ok(tests)
+}
+
+/// Run tests; returns the run ID
+#[tauri::command]
+pub async fn test_run(
+    project_path: String,
+    request: RunTestsRequest,
+    manager: State<'_, TestManager>,
+) -> Result<String, String> {
+    manager
+        .run_tests(&PathBuf::from(project_path), request)
+        .await
+        .map_err(|e| format!("Failed to run tests: {}", e))
+}
+
+#[tauri::command]
+pub async fn test_get_run(
+    run_id: String,
+    manager: State<'_, TestManager>,
+) -> Result<Option<TestRunInfo>, String> {
+    Ok(manager.get_run(&run_id).await)
+}
+
+#[tauri::command]
+pub async fn test_list_runs(manager: State<'_, TestManager>) -> Result<Vec<TestRunInfo>, String> {
+    Ok(manager.list_runs().await)
+}
+
+#[tauri::command]
+pub async fn test_cancel(run_id: String, manager: State<'_, TestManager>) -> Result<(), String> {
+    manager.cancel(&run_id).await.map_err(|e| e.to_string())
+}
+
+/// Program and arguments for debugging a single test
+#[tauri::command]
+pub async fn test_debug_target(project_path: String, test_id: String) -> Result<DebugTarget, String> {
+    debug_target(&PathBuf::from(project_path), &test_id)
+        .await
+        .map_err(|e| format!("Failed to prepare test for debugging: {}", e))
+}
@@ -0,0 +1,291 @@
+//! Test discovery
+//!
+//! - cargo: `cargo test -- --list --format terse`, then the test functions are
+//!   located in the sources to get file and line
+//! - pytest: `pytest --collect-only -q` node IDs
+//! - jest: `jest --listTests` files, scanned for `describe` / `it` / `test` calls
+
+use super::types::*;
+use crate::tasks::detection::has_pytest_config;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+static JEST_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(\s*)(describe|it|test)(?:\.(?:only|skip|each\([^)]*\)))?\(\s*(['"`])(.+?)['"`]"#).unwrap()
+});
+
+/// Directories never scanned for test sources
+const SKIP_DIRS: &[&str] = &["target", "node_modules", ".git", "dist", "build"];
+
+/// Test frameworks configured in the project
+pub fn detect_frameworks(root: &Path) -> Vec<TestFramework> {
+    let mut frameworks = Vec::new();
+    if root.join("Cargo.toml").exists() {
+        frameworks.push(TestFramework::Cargo);
+    }
+    if has_pytest_config(root) {
+        frameworks.push(TestFramework::Pytest);
+    }
+    let uses_jest = std::fs::read_to_string(root.join("package.json"))
+        .map(|c| c.contains("\"jest\""))
+        .unwrap_or(false);
+    if uses_jest {
+        frameworks.push(TestFramework::Jest);
+    }
+    frameworks
+}
+
+/// Discover all tests of one framework
+pub async fn discover_tests(root: &Path, framework: TestFramework) -> Result<Vec<TestCase>> {
+    match framework {
+        TestFramework::Cargo => {
+            let output = command_stdout(root, "cargo", &["test", "--", "--list", "--format", "terse"]).await?;
+            Ok(locate_rust_tests(root, &parse_cargo_list(&output)))
+        }
+        TestFramework::Pytest => {
+            let output = command_stdout(root, "python", &["-m", "pytest", "--collect-only", "-q"]).await?;
+            Ok(parse_pytest_collection(&output)
+                .into_iter()
+                .map(|node_id| pytest_case(root, &node_id))
+                .collect())
+        }
+        TestFramework::Jest => {
+            let output = command_stdout(root, "npx", &["jest", "--listTests"]).await?;
+            let mut tests = Vec::new();
+            for file in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                let Ok(content) = std::fs::read_to_string(file) else {
+                    continue;
+                };
+                tests.extend(jest_cases(root, file, &content));
+            }
+            Ok(tests)
+        }
+    }
+}
+
+async fn command_stdout(root: &Path, program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run {}: {}", program, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() && stdout.trim().is_empty() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(stdout)
+}
+
+/// Test names from `cargo test -- --list --format terse` (`name: test` lines)
+pub fn parse_cargo_list(output: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for line in output.lines() {
+        if let Some(name) = line.strip_suffix(": test") {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Map libtest names (`module::tests::name`) to test cases with source locations
+pub fn locate_rust_tests(root: &Path, names: &[String]) -> Vec<TestCase> {
+    // fn name -> [(file, line)]
+    let mut functions: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+    let fn_re = Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?fn\s+(\w+)").unwrap();
+
+    let walker = WalkDir::new(root).into_iter().filter_entry(|e| {
+        !e.file_type().is_dir() || !SKIP_DIRS.contains(&e.file_name().to_str().unwrap_or_default())
+    });
+    for entry in walker.flatten() {
+        if entry.path().extension().and_then(|e| e.to_str()) != Some("rs") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let file = entry.path().to_string_lossy().to_string();
+        for (i, line) in content.lines().enumerate() {
+            if let Some(caps) = fn_re.captures(line) {
+                functions
+                    .entry(caps[1].to_string())
+                    .or_default()
+                    .push((file.clone(), i + 1));
+            }
+        }
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            let mut segments: Vec<String> = name.split("::").map(str::to_string).collect();
+            let leaf = segments.pop().unwrap_or_default();
+
+            // Prefer the candidate whose path mentions the most module segments
+            let location = functions.get(&leaf).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .max_by_key(|(file, _)| segments.iter().filter(|s| file.contains(s.as_str())).count())
+                    .cloned()
+            });
+
+            TestCase {
+                id: format!("cargo::{}", name),
+                framework: TestFramework::Cargo,
+                name: leaf,
+                path: segments,
+                file: location.as_ref().map(|(f, _)| f.clone()),
+                line: location.map(|(_, l)| l),
+            }
+        })
+        .collect()
+}
+
+/// Node IDs from `pytest --collect-only -q`
+pub fn parse_pytest_collection(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.contains("::") && !l.contains(' '))
+        .map(str::to_string)
+        .collect()
+}
+
+fn pytest_case(root: &Path, node_id: &str) -> TestCase {
+    let mut parts: Vec<String> = node_id.split("::").map(str::to_string).collect();
+    let leaf = parts.pop().unwrap_or_default();
+    let file = root.join(&parts[0]);
+
+    // `test_x[param]` is defined as `def test_x(`
+    let function = leaf.split('[').next().unwrap_or(&leaf);
+    let line = std::fs::read_to_string(&file).ok().and_then(|content| {
+        let needle = format!("def {}(", function);
+        content
+            .lines()
+            .position(|l| l.trim_start().trim_start_matches("async ").starts_with(&needle))
+            .map(|i| i + 1)
+    });
+
+    TestCase {
+        id: format!("pytest::{}", node_id),
+        framework: TestFramework::Pytest,
+        name: leaf,
+        path: parts,
+        file: Some(file.to_string_lossy().to_string()),
+        line,
+    }
+}
+
+/// Tests in a jest file; the ID is `jest::<file>::<describe> <name>` (jest's full name)
+pub fn jest_cases(root: &Path, file: &str, content: &str) -> Vec<TestCase> {
+    let relative = Path::new(file)
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| file.to_string());
+
+    let mut tests = Vec::new();
+    // (indent, describe name)
+    let mut describes: Vec<(usize, String)> = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let Some(caps) = JEST_CALL.captures(line) else {
+            continue;
+        };
+        let indent = caps[1].len();
+        while describes.last().is_some_and(|(d, _)| *d >= indent) {
+            describes.pop();
+        }
+
+        let name = caps[4].to_string();
+        if &caps[2] == "describe" {
+            describes.push((indent, name));
+            continue;
+        }
+
+        let mut full_name: Vec<&str> = describes.iter().map(|(_, d)| d.as_str()).collect();
+        full_name.push(&name);
+
+        let mut path = vec![relative.clone()];
+        path.extend(describes.iter().map(|(_, d)| d.clone()));
+
+        tests.push(TestCase {
+            id: format!("jest::{}::{}", file, full_name.join(" ")),
+            framework: TestFramework::Jest,
+            name,
+            path,
+            file: Some(file.to_string()),
+            line: Some(i + 1),
+        });
+    }
+    tests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_cargo_list() {
+        let output = "tests::it_works: test\ntests::bench_x: benchmark\nparser::tests::parses: test\ntests::it_works: test\n";
+        assert_eq!(parse_cargo_list(output), vec!["tests::it_works", "parser::tests::parses"]);
+    }
+
+    #[test]
+    fn test_locate_rust_tests() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "#[test]\nfn parses() {}\n").unwrap();
+        std::fs::write(
+            dir.path().join("src/parser.rs"),
+            "mod tests {\n    #[test]\n    fn parses() {}\n}\n",
+        )
+        .unwrap();
+
+        let tests = locate_rust_tests(dir.path(), &["parser::tests::parses".to_string()]);
+        assert_eq!(tests[0].id, "cargo::parser::tests::parses");
+        assert_eq!(tests[0].path, vec!["parser", "tests"]);
+        assert!(tests[0].file.as_ref().unwrap().ends_with("parser.rs"));
+        assert_eq!(tests[0].line, Some(3));
+    }
+
+    #[test]
+    fn test_parse_pytest_collection() {
+        let output = "tests/test_math.py::test_add\ntests/test_math.py::TestDiv::test_zero[1]\n\n2 tests collected in 0.01s\n";
+        assert_eq!(
+            parse_pytest_collection(output),
+            vec!["tests/test_math.py::test_add", "tests/test_math.py::TestDiv::test_zero[1]"]
+        );
+    }
+
+    #[test]
+    fn test_jest_cases() {
+        let content = "describe('math', () => {\n  it('adds', () => {});\n  describe('div', () => {\n    test('by zero', () => {});\n  });\n  it.skip('subtracts', () => {});\n});\ntest('top level', () => {});\n";
+        let tests = jest_cases(Path::new("/p"), "/p/src/math.test.js", content);
+        let ids: Vec<&str> = tests.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "jest::/p/src/math.test.js::math adds",
+                "jest::/p/src/math.test.js::math div by zero",
+                "jest::/p/src/math.test.js::math subtracts",
+                "jest::/p/src/math.test.js::top level",
+            ]
+        );
+        assert_eq!(tests[1].path, vec!["src/math.test.js", "math", "div"]);
+        assert_eq!(tests[1].line, Some(4));
+    }
+}
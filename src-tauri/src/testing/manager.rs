@@ -0,0 +1,591 @@
+//! Test execution
+//!
+//! Runs a framework's test command for the whole suite or selected test IDs,
+//! decoding per-test results from the output while the run is in progress.
+//! Cargo test binaries are built first and then run directly, so the
+//! environment libtest's JSON output needs never reaches the compiler.
+
+use super::results::{ResultParser, TestEvent};
+use super::types::*;
+use crate::tasks::manager::append_line;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, Notify, RwLock};
+
+/// Finished runs kept for inspection
+const MAX_FINISHED_RUNS: usize = 20;
+
+struct RunState {
+    framework: TestFramework,
+    status: TestRunStatus,
+    results: Vec<TestResult>,
+    output: String,
+    parser: ResultParser,
+    started_at: i64,
+    finished_at: Option<i64>,
+}
+
+impl RunState {
+    fn apply(&mut self, event: TestEvent) {
+        let (id, update) = match event {
+            TestEvent::Started(id) => (id.clone(), TestResult::new(id, TestOutcome::Running)),
+            TestEvent::Finished(result) => (result.id.clone(), result),
+        };
+
+        match self.results.iter_mut().find(|r| r.id == id) {
+            Some(result) => {
+                result.outcome = update.outcome;
+                result.duration_ms = update.duration_ms.or(result.duration_ms);
+                result.message = update.message.or(result.message.take());
+                result.failure_file = update.failure_file.or(result.failure_file.take());
+                result.failure_line = update.failure_line.or(result.failure_line);
+            }
+            None => self.results.push(update),
+        }
+    }
+}
+
+struct TestRun {
+    id: String,
+    state: Mutex<RunState>,
+    cancel: Notify,
+}
+
+impl TestRun {
+    fn new(root: &Path, framework: TestFramework, test_ids: &[String]) -> Arc<Self> {
+        Arc::new(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            state: Mutex::new(RunState {
+                framework,
+                status: TestRunStatus::Running,
+                results: test_ids
+                    .iter()
+                    .map(|id| TestResult::new(id.clone(), TestOutcome::Queued))
+                    .collect(),
+                output: String::new(),
+                parser: ResultParser::new(framework, root),
+                started_at: chrono::Utc::now().timestamp_millis(),
+                finished_at: None,
+            }),
+            cancel: Notify::new(),
+        })
+    }
+
+    fn push_output(&self, line: &str) {
+        let mut state = self.state.lock().unwrap();
+        for event in state.parser.push_line(line) {
+            state.apply(event);
+        }
+        // The jest report is a single huge JSON line; it is not useful as output
+        if !(state.framework == TestFramework::Jest && line.starts_with('{')) {
+            append_line(&mut state.output, line);
+        }
+    }
+
+    fn finish(&self, status: TestRunStatus) {
+        let mut state = self.state.lock().unwrap();
+        for event in state.parser.finish() {
+            state.apply(event);
+        }
+        // Tests that never reported (build failure, cancellation) did not run
+        for result in &mut state.results {
+            if matches!(result.outcome, TestOutcome::Queued | TestOutcome::Running) {
+                result.outcome = TestOutcome::Skipped;
+            }
+        }
+        state.status = status;
+        state.finished_at = Some(chrono::Utc::now().timestamp_millis());
+    }
+
+    fn status(&self) -> TestRunStatus {
+        self.state.lock().unwrap().status
+    }
+
+    fn snapshot(&self) -> TestRunInfo {
+        let state = self.state.lock().unwrap();
+        TestRunInfo {
+            run_id: self.id.clone(),
+            framework: state.framework,
+            status: state.status,
+            results: state.results.clone(),
+            output: state.output.clone(),
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+        }
+    }
+}
+
+/// Split a test ID into its framework and framework-specific name
+pub fn parse_test_id(test_id: &str) -> Result<(TestFramework, &str)> {
+    let (prefix, name) = test_id
+        .split_once("::")
+        .ok_or_else(|| anyhow!("Invalid test ID: {}", test_id))?;
+    let framework = [TestFramework::Cargo, TestFramework::Pytest, TestFramework::Jest]
+        .into_iter()
+        .find(|f| f.id_prefix() == prefix)
+        .ok_or_else(|| anyhow!("Unknown test framework: {}", prefix))?;
+    Ok((framework, name))
+}
+
+/// Jest names are `<file>::<full name>`
+fn split_jest_name(name: &str) -> (&str, &str) {
+    name.split_once("::").unwrap_or((name, ""))
+}
+
+/// Anchored `-t` pattern matching exactly the given full names
+fn jest_name_pattern<'a>(names: impl IntoIterator<Item = &'a str>) -> String {
+    let names: Vec<String> = names.into_iter().map(regex::escape).collect();
+    format!("^(?:{})$", names.join("|"))
+}
+
+/// Framework-specific names of `test_ids`, which must all belong to `framework`
+fn test_names(framework: TestFramework, test_ids: &[String]) -> Result<Vec<&str>> {
+    let mut names = Vec::new();
+    for id in test_ids {
+        let (id_framework, name) = parse_test_id(id)?;
+        if id_framework != framework {
+            return Err(anyhow!("Test {} does not belong to {:?}", id, framework));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Program, arguments and environment for running tests; for cargo this
+/// builds the test binaries, which are then run with `libtest_args`
+pub fn test_command(framework: TestFramework, test_ids: &[String]) -> Result<(String, Vec<String>, HashMap<String, String>)> {
+    let names = test_names(framework, test_ids)?;
+
+    let (program, args): (&str, Vec<String>) = match framework {
+        TestFramework::Cargo => (
+            "cargo",
+            ["test", "--no-run", "--message-format=json"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        ),
+        TestFramework::Pytest => {
+            let mut args: Vec<String> = ["-m", "pytest", "-v", "--tb=short", "-p", "no:cacheprovider", "--durations=0", "--durations-min=0"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            args.extend(names.iter().map(|n| n.to_string()));
+            ("python", args)
+        }
+        TestFramework::Jest => {
+            let mut args: Vec<String> = vec!["jest".to_string(), "--json".to_string(), "--testLocationInResults".to_string()];
+            if !names.is_empty() {
+                let files: BTreeSet<&str> = names.iter().map(|n| split_jest_name(n).0).collect();
+                args.extend(files.into_iter().map(str::to_string));
+                args.push("-t".to_string());
+                args.push(jest_name_pattern(names.iter().map(|n| split_jest_name(n).1)));
+            }
+            ("npx", args)
+        }
+    };
+    Ok((program.to_string(), args, HashMap::new()))
+}
+
+/// Arguments for a cargo test binary to report `names` (all tests when empty) as JSON
+pub fn libtest_args(names: &[&str]) -> Vec<String> {
+    let mut args: Vec<String> = ["-Z", "unstable-options", "--format", "json", "--report-time"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    if !names.is_empty() {
+        args.extend(names.iter().map(|n| n.to_string()));
+        args.push("--exact".to_string());
+    }
+    args
+}
+
+/// What a run executes
+enum Job {
+    /// A command whose output carries the results
+    Single(Command),
+    /// Build cargo test binaries, then run each with the given libtest arguments
+    CargoBinaries { build: Command, test_args: Vec<String> },
+}
+
+/// Tracks test runs for the desktop app
+#[derive(Clone, Default)]
+pub struct TestManager {
+    runs: Arc<RwLock<HashMap<String, Arc<TestRun>>>>,
+}
+
+impl TestManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a test run; returns the run ID
+    pub async fn run_tests(&self, root: &Path, request: RunTestsRequest) -> Result<String> {
+        let (program, args, env) = test_command(request.framework, &request.test_ids)?;
+        let mut command = Command::new(program);
+        command.args(args).envs(env).current_dir(root);
+        let job = match request.framework {
+            TestFramework::Cargo => Job::CargoBinaries {
+                build: command,
+                test_args: libtest_args(&test_names(request.framework, &request.test_ids)?),
+            },
+            _ => Job::Single(command),
+        };
+        self.run_job(root, request.framework, &request.test_ids, job).await
+    }
+
+    async fn run_job(&self, root: &Path, framework: TestFramework, test_ids: &[String], job: Job) -> Result<String> {
+        let run = TestRun::new(root, framework, test_ids);
+        let run_id = run.id.clone();
+        self.insert(run.clone()).await;
+
+        tokio::spawn(execute(run, job, root.to_path_buf()));
+        Ok(run_id)
+    }
+
+    async fn insert(&self, run: Arc<TestRun>) {
+        let mut runs = self.runs.write().await;
+
+        // Drop the oldest finished runs
+        let mut finished: Vec<(i64, String)> = runs
+            .values()
+            .filter(|r| r.status() != TestRunStatus::Running)
+            .map(|r| (r.state.lock().unwrap().started_at, r.id.clone()))
+            .collect();
+        if finished.len() >= MAX_FINISHED_RUNS {
+            finished.sort();
+            for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_RUNS) {
+                runs.remove(id);
+            }
+        }
+
+        runs.insert(run.id.clone(), run);
+    }
+
+    pub async fn get_run(&self, run_id: &str) -> Option<TestRunInfo> {
+        self.runs.read().await.get(run_id).map(|r| r.snapshot())
+    }
+
+    /// All known runs, newest first
+    pub async fn list_runs(&self) -> Vec<TestRunInfo> {
+        let mut runs: Vec<TestRunInfo> = self.runs.read().await.values().map(|r| r.snapshot()).collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+        runs
+    }
+
+    /// Stop a running test run
+    pub async fn cancel(&self, run_id: &str) -> Result<()> {
+        let runs = self.runs.read().await;
+        let run = runs.get(run_id).ok_or_else(|| anyhow!("Test run not found: {}", run_id))?;
+        run.cancel.notify_one();
+        Ok(())
+    }
+}
+
+/// Run the job to completion, feeding output to the result parser
+async fn execute(run: Arc<TestRun>, job: Job, root: PathBuf) {
+    let completed = match job {
+        Job::Single(command) => stream(&run, command, |line| run.push_output(&line)).await,
+        Job::CargoBinaries { build, test_args } => run_test_binaries(&run, build, &test_args, &root).await,
+    };
+    match completed {
+        Ok(true) => run.finish(TestRunStatus::Finished),
+        Ok(false) => {
+            run.push_output("Test run cancelled");
+            run.finish(TestRunStatus::Cancelled);
+        }
+        Err(e) => {
+            run.push_output(&format!("Failed to start tests: {}", e));
+            run.finish(TestRunStatus::Finished);
+        }
+    }
+}
+
+/// Build the test binaries, then run each one; false when cancelled
+async fn run_test_binaries(run: &TestRun, build: Command, test_args: &[String], root: &Path) -> Result<bool> {
+    let mut binaries = Vec::new();
+    let built = stream(run, build, |line| match serde_json::from_str::<serde_json::Value>(&line) {
+        Ok(message) => {
+            if let Some(executable) = test_executable(&message) {
+                // Cargo runs each binary from its package directory
+                let dir = message["manifest_path"]
+                    .as_str()
+                    .and_then(|path| Path::new(path).parent())
+                    .map_or_else(|| root.to_path_buf(), Path::to_path_buf);
+                binaries.push((executable, dir));
+            } else if let Some(rendered) = message["message"]["rendered"].as_str() {
+                for line in rendered.lines() {
+                    run.push_output(line);
+                }
+            }
+        }
+        Err(_) => run.push_output(&line),
+    })
+    .await?;
+    if !built {
+        return Ok(false);
+    }
+
+    for (executable, dir) in binaries {
+        let mut command = Command::new(executable);
+        // libtest's JSON format is unstable; RUSTC_BOOTSTRAP enables it on stable
+        // toolchains. It is set on the test binary only, never on the build.
+        command.args(test_args).env("RUSTC_BOOTSTRAP", "1").current_dir(dir);
+        if !stream(run, command, |line| run.push_output(&line)).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Run a process, handing each output line to `on_line`; false when cancelled
+async fn stream(run: &TestRun, mut command: Command, mut on_line: impl FnMut(String)) -> Result<bool> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = command.spawn()?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    if let Some(stdout) = child.stdout.take() {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = tx.send(line);
+            }
+        });
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = tx.send(line);
+            }
+        });
+    }
+    drop(tx);
+
+    loop {
+        tokio::select! {
+            line = rx.recv() => match line {
+                Some(line) => on_line(line),
+                None => break,
+            },
+            _ = run.cancel.notified() => {
+                let _ = child.start_kill();
+                let _ = child.wait().await;
+                return Ok(false);
+            }
+        }
+    }
+
+    let _ = child.wait().await;
+    Ok(true)
+}
+
+/// How to launch a single test under the debugger
+pub async fn debug_target(root: &Path, test_id: &str) -> Result<DebugTarget> {
+    let (framework, name) = parse_test_id(test_id)?;
+    let cwd = root.to_string_lossy().to_string();
+
+    let (program, args) = match framework {
+        TestFramework::Cargo => {
+            let program = find_test_binary(root, name).await?;
+            let args = vec![name.to_string(), "--exact".to_string(), "--nocapture".to_string()];
+            (program.to_string_lossy().to_string(), args)
+        }
+        TestFramework::Pytest => (
+            "python".to_string(),
+            vec!["-m".to_string(), "pytest".to_string(), name.to_string(), "-s".to_string()],
+        ),
+        TestFramework::Jest => {
+            let (file, full_name) = split_jest_name(name);
+            (
+                "node".to_string(),
+                vec![
+                    "--inspect-brk".to_string(),
+                    "node_modules/.bin/jest".to_string(),
+                    "--runInBand".to_string(),
+                    file.to_string(),
+                    "-t".to_string(),
+                    jest_name_pattern([full_name]),
+                ],
+            )
+        }
+    };
+
+    Ok(DebugTarget {
+        program,
+        args,
+        cwd,
+        env: HashMap::new(),
+    })
+}
+
+/// Build the test binaries and return the one that contains `test_name`
+async fn find_test_binary(root: &Path, test_name: &str) -> Result<PathBuf> {
+    let output = Command::new("cargo")
+        .args(["test", "--no-run", "--message-format=json"])
+        .current_dir(root)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run cargo: {}", e))?;
+
+    for executable in test_executables(&String::from_utf8_lossy(&output.stdout)) {
+        let listing = Command::new(&executable)
+            .args(["--list", "--format", "terse"])
+            .current_dir(root)
+            .output()
+            .await;
+        let Ok(listing) = listing else {
+            continue;
+        };
+        let needle = format!("{}: test", test_name);
+        if String::from_utf8_lossy(&listing.stdout).lines().any(|l| l == needle) {
+            return Ok(executable);
+        }
+    }
+
+    Err(anyhow!("No test binary contains {}", test_name))
+}
+
+/// Test executables from `cargo test --no-run --message-format=json`
+pub fn test_executables(messages: &str) -> Vec<PathBuf> {
+    messages
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|m| test_executable(&m))
+        .collect()
+}
+
+/// The executable of a cargo message announcing a built test binary
+fn test_executable(message: &serde_json::Value) -> Option<PathBuf> {
+    if message["reason"] == "compiler-artifact" && message["profile"]["test"] == true {
+        message["executable"].as_str().map(PathBuf::from)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_finished(manager: &TestManager, run_id: &str) -> TestRunInfo {
+        for _ in 0..100 {
+            let info = manager.get_run(run_id).await.unwrap();
+            if info.status != TestRunStatus::Running {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("test run did not finish");
+    }
+
+    #[test]
+    fn test_commands_for_selected_tests() {
+        let (program, args, env) = test_command(TestFramework::Cargo, &["cargo::tests::adds".to_string()]).unwrap();
+        assert_eq!(program, "cargo");
+        assert_eq!(args, vec!["test", "--no-run", "--message-format=json"]);
+        assert!(env.is_empty());
+        assert!(libtest_args(&["tests::adds"]).ends_with(&["tests::adds".to_string(), "--exact".to_string()]));
+        assert!(!libtest_args(&[]).contains(&"--exact".to_string()));
+
+        let (_, args, _) = test_command(
+            TestFramework::Jest,
+            &["jest::/p/a.test.js::math adds (1 + 1)".to_string()],
+        )
+        .unwrap();
+        assert!(args.ends_with(&[
+            "/p/a.test.js".to_string(),
+            "-t".to_string(),
+            r"^(?:math adds \(1 \+ 1\))$".to_string(),
+        ]));
+
+        assert!(test_command(TestFramework::Pytest, &["cargo::tests::adds".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_test_executables() {
+        let messages = r#"{"reason":"compiler-artifact","profile":{"test":true},"executable":"/p/target/debug/deps/app-123"}
+{"reason":"compiler-artifact","profile":{"test":false},"executable":"/p/target/debug/app"}
+{"reason":"build-finished","success":true}"#;
+        assert_eq!(test_executables(messages), vec![PathBuf::from("/p/target/debug/deps/app-123")]);
+    }
+
+    #[tokio::test]
+    async fn test_streams_results_and_marks_unreported_tests() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = r#"echo '{ "type": "test", "event": "started", "name": "a" }'
+echo '{ "type": "test", "name": "a", "event": "ok", "exec_time": 0.5 }'
+echo 'error: could not compile'"#;
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+
+        let manager = TestManager::new();
+        let ids = vec!["cargo::a".to_string(), "cargo::b".to_string()];
+        let run_id = manager
+            .run_job(dir.path(), TestFramework::Cargo, &ids, Job::Single(command))
+            .await
+            .unwrap();
+        let info = wait_finished(&manager, &run_id).await;
+
+        assert_eq!(info.status, TestRunStatus::Finished);
+        assert_eq!(info.results[0].outcome, TestOutcome::Passed);
+        assert_eq!(info.results[0].duration_ms, Some(500));
+        assert_eq!(info.results[1].outcome, TestOutcome::Skipped);
+        assert!(info.output.contains("could not compile"));
+    }
+
+    #[tokio::test]
+    async fn test_cargo_runs_binaries_with_bootstrap_env_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"sample\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            r#"#[test]
+fn bootstrap_only_at_runtime() {
+    assert!(option_env!("RUSTC_BOOTSTRAP").is_none());
+    assert_eq!(std::env::var("RUSTC_BOOTSTRAP").as_deref(), Ok("1"));
+}
+
+#[test]
+fn skipped() {}
+"#,
+        )
+        .unwrap();
+
+        let manager = TestManager::new();
+        let request = RunTestsRequest {
+            framework: TestFramework::Cargo,
+            test_ids: vec!["cargo::bootstrap_only_at_runtime".to_string()],
+        };
+        let run_id = manager.run_tests(dir.path(), request).await.unwrap();
+        // Building takes longer than `wait_finished` allows
+        let mut info = manager.get_run(&run_id).await.unwrap();
+        for _ in 0..1200 {
+            if info.status != TestRunStatus::Running {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            info = manager.get_run(&run_id).await.unwrap();
+        }
+
+        assert_eq!(info.status, TestRunStatus::Finished, "{}", info.output);
+        assert_eq!(info.results.len(), 1);
+        assert_eq!(info.results[0].outcome, TestOutcome::Passed, "{}", info.output);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod commands;
+pub mod discovery;
+pub mod manager;
+pub mod results;
+pub mod types;
+
+pub use manager::TestManager;
+pub use types::*;
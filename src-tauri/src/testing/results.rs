@@ -0,0 +1,377 @@
+//! Test result parsers
+//!
+//! - cargo: libtest JSON events (`--format json --report-time`), streamed per test
+//! - pytest: `-v` result lines, streamed per test; failure details and durations
+//!   from the `--tb=short` and `--durations=0` sections once the run finishes
+//! - jest: the `--json` report printed when the run finishes
+
+use super::types::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+static RUST_PANIC_LOCATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"panicked at (?:'(?s:(.*?))', )?([^\s:']+\.rs):(\d+):(\d+)").unwrap()
+});
+static PYTEST_RESULT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\S+::\S+) (PASSED|FAILED|SKIPPED|ERROR|XFAIL|XPASS)\b").unwrap()
+});
+static PYTEST_SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").unwrap());
+static PYTEST_LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\S+\.py):(\d+): ").unwrap());
+static PYTEST_DURATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*([\d.]+)s (?:call|setup|teardown)\s+(\S+::\S+)$").unwrap());
+static STACK_LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"([^\s()]+):(\d+):(\d+)").unwrap());
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap());
+
+/// Progress decoded from test output
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestEvent {
+    Started(String),
+    /// Fields that are `None` leave earlier information in place
+    Finished(TestResult),
+}
+
+/// Failure section of pytest's `--tb=short` report
+#[derive(Debug, Default)]
+struct PytestFailure {
+    location: Option<(String, usize)>,
+    message: Option<String>,
+}
+
+/// Stateful parser for one test run's output
+#[derive(Debug)]
+pub struct ResultParser {
+    framework: TestFramework,
+    root: PathBuf,
+    pytest_failed: Vec<String>,
+    pytest_section: Option<String>,
+    pytest_failures: HashMap<String, PytestFailure>,
+    pytest_durations: HashMap<String, f64>,
+}
+
+impl ResultParser {
+    pub fn new(framework: TestFramework, root: &Path) -> Self {
+        Self {
+            framework,
+            root: root.to_path_buf(),
+            pytest_failed: Vec::new(),
+            pytest_section: None,
+            pytest_failures: HashMap::new(),
+            pytest_durations: HashMap::new(),
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) -> Vec<TestEvent> {
+        match self.framework {
+            TestFramework::Cargo => self.push_libtest_json(line),
+            TestFramework::Pytest => self.push_pytest(line),
+            TestFramework::Jest => self.push_jest_json(line),
+        }
+    }
+
+    /// Events that can only be produced once all output has been seen
+    pub fn finish(&mut self) -> Vec<TestEvent> {
+        if self.framework != TestFramework::Pytest {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        for node_id in &self.pytest_failed {
+            let id = format!("pytest::{}", node_id);
+            let mut result = TestResult::new(id, TestOutcome::Failed);
+
+            // Section headers use the function name, with `.` between class and method
+            let failure = self.pytest_failures.iter().find(|(section, _)| {
+                node_id.ends_with(&format!("::{}", section.replace('.', "::")))
+            });
+            if let Some((_, failure)) = failure {
+                result.message = failure.message.clone();
+                if let Some((file, line)) = &failure.location {
+                    result.failure_file = Some(self.resolve(file));
+                    result.failure_line = Some(*line);
+                }
+            }
+            events.push(TestEvent::Finished(result));
+        }
+
+        for (node_id, seconds) in &self.pytest_durations {
+            let mut result = TestResult::new(format!("pytest::{}", node_id), TestOutcome::Passed);
+            result.duration_ms = Some((seconds * 1000.0).round() as u64);
+            // Outcome is already known; only the duration is new
+            if self.pytest_failed.contains(node_id) {
+                result.outcome = TestOutcome::Failed;
+            }
+            events.push(TestEvent::Finished(result));
+        }
+
+        events
+    }
+
+    fn resolve(&self, file: &str) -> String {
+        let path = Path::new(file);
+        if path.is_absolute() {
+            file.to_string()
+        } else {
+            self.root.join(path).to_string_lossy().to_string()
+        }
+    }
+
+    fn push_libtest_json(&mut self, line: &str) -> Vec<TestEvent> {
+        if !line.starts_with('{') {
+            return Vec::new();
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            return Vec::new();
+        };
+        if value["type"] != "test" {
+            return Vec::new();
+        }
+        let Some(name) = value["name"].as_str() else {
+            return Vec::new();
+        };
+        let id = format!("cargo::{}", name);
+
+        let outcome = match value["event"].as_str() {
+            Some("started") => return vec![TestEvent::Started(id)],
+            Some("ok") => TestOutcome::Passed,
+            Some("failed") | Some("timeout") => TestOutcome::Failed,
+            Some("ignored") => TestOutcome::Skipped,
+            _ => return Vec::new(),
+        };
+
+        let mut result = TestResult::new(id, outcome);
+        result.duration_ms = value["exec_time"].as_f64().map(|s| (s * 1000.0).round() as u64);
+
+        if outcome == TestOutcome::Failed {
+            let stdout = value["stdout"].as_str().unwrap_or_default();
+            let (message, location) = parse_rust_panic(stdout);
+            result.message = message;
+            if let Some((file, line)) = location {
+                result.failure_file = Some(self.resolve(&file));
+                result.failure_line = Some(line);
+            }
+        }
+
+        vec![TestEvent::Finished(result)]
+    }
+
+    fn push_pytest(&mut self, line: &str) -> Vec<TestEvent> {
+        if let Some(caps) = PYTEST_RESULT.captures(line) {
+            let node_id = caps[1].to_string();
+            let outcome = match &caps[2] {
+                "PASSED" | "XFAIL" => TestOutcome::Passed,
+                "SKIPPED" => TestOutcome::Skipped,
+                _ => TestOutcome::Failed,
+            };
+            if outcome == TestOutcome::Failed && !self.pytest_failed.contains(&node_id) {
+                self.pytest_failed.push(node_id.clone());
+            }
+            return vec![TestEvent::Finished(TestResult::new(format!("pytest::{}", node_id), outcome))];
+        }
+
+        if let Some(caps) = PYTEST_SECTION.captures(line) {
+            self.pytest_section = Some(caps[1].to_string());
+            return Vec::new();
+        }
+        if line.starts_with("====") {
+            self.pytest_section = None;
+        }
+
+        if let Some(caps) = PYTEST_DURATION.captures(line) {
+            let seconds: f64 = caps[1].parse().unwrap_or(0.0);
+            *self.pytest_durations.entry(caps[2].to_string()).or_default() += seconds;
+            return Vec::new();
+        }
+
+        if let Some(section) = self.pytest_section.clone() {
+            let failure = self.pytest_failures.entry(section).or_default();
+            if let Some(caps) = PYTEST_LOCATION.captures(line) {
+                // The innermost frame is the last one printed
+                failure.location = Some((caps[1].to_string(), caps[2].parse().unwrap_or(1)));
+            } else if let Some(message) = line.strip_prefix('E') {
+                if failure.message.is_none() && message.starts_with(char::is_whitespace) {
+                    failure.message = Some(message.trim().to_string());
+                }
+            }
+        }
+
+        Vec::new()
+    }
+
+    fn push_jest_json(&mut self, line: &str) -> Vec<TestEvent> {
+        if !line.starts_with('{') || !line.contains("\"testResults\"") {
+            return Vec::new();
+        }
+        let Ok(report) = serde_json::from_str::<serde_json::Value>(line) else {
+            return Vec::new();
+        };
+
+        let mut events = Vec::new();
+        for file_result in report["testResults"].as_array().into_iter().flatten() {
+            let file = file_result["name"].as_str().unwrap_or_default();
+            for assertion in file_result["assertionResults"].as_array().into_iter().flatten() {
+                let full_name = assertion["fullName"].as_str().unwrap_or_default();
+                let outcome = match assertion["status"].as_str() {
+                    Some("passed") => TestOutcome::Passed,
+                    Some("failed") => TestOutcome::Failed,
+                    _ => TestOutcome::Skipped,
+                };
+
+                let mut result = TestResult::new(format!("jest::{}::{}", file, full_name), outcome);
+                result.duration_ms = assertion["duration"].as_u64();
+
+                if outcome == TestOutcome::Failed {
+                    let failure = assertion["failureMessages"][0].as_str().unwrap_or_default();
+                    let failure = ANSI_ESCAPE.replace_all(failure, "");
+                    result.message = failure
+                        .lines()
+                        .map(str::trim)
+                        .find(|l| !l.is_empty())
+                        .map(str::to_string);
+
+                    // First stack frame inside the test file
+                    let location = STACK_LOCATION
+                        .captures_iter(&failure)
+                        .find(|caps| &caps[1] == file)
+                        .map(|caps| caps[2].parse().unwrap_or(1))
+                        .or_else(|| assertion["location"]["line"].as_u64().map(|l| l as usize));
+                    if let Some(line) = location {
+                        result.failure_file = Some(file.to_string());
+                        result.failure_line = Some(line);
+                    }
+                }
+
+                events.push(TestEvent::Finished(result));
+            }
+        }
+        events
+    }
+}
+
+/// Message and location from a Rust panic (`thread 'x' panicked at src/lib.rs:5:9:`)
+pub fn parse_rust_panic(stdout: &str) -> (Option<String>, Option<(String, usize)>) {
+    let Some(caps) = RUST_PANIC_LOCATION.captures(stdout) else {
+        let message = stdout.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string);
+        return (message, None);
+    };
+
+    let location = Some((caps[2].to_string(), caps[3].parse().unwrap_or(1)));
+
+    // Before Rust 1.73 the message was quoted inline; now it follows on the next lines
+    let message = match caps.get(1) {
+        Some(inline) => inline.as_str().to_string(),
+        None => stdout[caps.get(0).unwrap().end()..]
+            .trim_start_matches(':')
+            .lines()
+            .take_while(|l| !l.starts_with("note:") && !l.starts_with("stack backtrace:"))
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string(),
+    };
+
+    ((!message.is_empty()).then_some(message), location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(events: Vec<TestEvent>) -> Vec<TestResult> {
+        events
+            .into_iter()
+            .filter_map(|e| match e {
+                TestEvent::Finished(r) => Some(r),
+                TestEvent::Started(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_libtest_json() {
+        let mut parser = ResultParser::new(TestFramework::Cargo, Path::new("/proj"));
+        assert_eq!(
+            parser.push_line(r#"{ "type": "test", "event": "started", "name": "tests::adds" }"#),
+            vec![TestEvent::Started("cargo::tests::adds".to_string())]
+        );
+
+        let ok = finished(parser.push_line(r#"{ "type": "test", "name": "tests::adds", "event": "ok", "exec_time": 0.012 }"#));
+        assert_eq!(ok[0].outcome, TestOutcome::Passed);
+        assert_eq!(ok[0].duration_ms, Some(12));
+
+        let failed = finished(parser.push_line(
+            r#"{ "type": "test", "name": "tests::subs", "event": "failed", "exec_time": 0.001, "stdout": "\nthread 'tests::subs' panicked at src/lib.rs:14:9:\nassertion `left == right` failed\n  left: 1\n right: 2\nnote: run with `RUST_BACKTRACE=1`\n" }"#,
+        ));
+        assert_eq!(failed[0].outcome, TestOutcome::Failed);
+        assert_eq!(failed[0].failure_file.as_deref(), Some("/proj/src/lib.rs"));
+        assert_eq!(failed[0].failure_line, Some(14));
+        assert_eq!(
+            failed[0].message.as_deref(),
+            Some("assertion `left == right` failed\n  left: 1\n right: 2")
+        );
+
+        assert!(parser.push_line(r#"{ "type": "suite", "event": "ok", "passed": 1 }"#).is_empty());
+    }
+
+    #[test]
+    fn test_parse_old_rust_panic_format() {
+        let (message, location) = parse_rust_panic("thread 'x' panicked at 'boom', src/main.rs:3:5\n");
+        assert_eq!(message.as_deref(), Some("boom"));
+        assert_eq!(location, Some(("src/main.rs".to_string(), 3)));
+    }
+
+    #[test]
+    fn test_pytest_output() {
+        let output = "\
+tests/test_math.py::test_add PASSED                                      [ 50%]
+tests/test_math.py::TestDiv::test_zero FAILED                            [100%]
+
+=================================== FAILURES ===================================
+______________________________ TestDiv.test_zero _______________________________
+tests/test_math.py:12: in test_zero
+    assert div(1, 0) == 0
+tests/math.py:3: in div
+    return a / b
+E   ZeroDivisionError: division by zero
+============================= slowest durations ==============================
+0.02s call     tests/test_math.py::TestDiv::test_zero
+0.01s call     tests/test_math.py::test_add
+";
+        let mut parser = ResultParser::new(TestFramework::Pytest, Path::new("/proj"));
+        let streamed: Vec<TestResult> = output.lines().flat_map(|l| finished(parser.push_line(l))).collect();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[0].outcome, TestOutcome::Passed);
+        assert_eq!(streamed[1].outcome, TestOutcome::Failed);
+
+        let final_results = finished(parser.finish());
+        let failure = final_results
+            .iter()
+            .find(|r| r.id == "pytest::tests/test_math.py::TestDiv::test_zero" && r.message.is_some())
+            .unwrap();
+        assert_eq!(failure.message.as_deref(), Some("ZeroDivisionError: division by zero"));
+        assert_eq!(failure.failure_file.as_deref(), Some("/proj/tests/math.py"));
+        assert_eq!(failure.failure_line, Some(3));
+
+        let add = final_results
+            .iter()
+            .find(|r| r.id == "pytest::tests/test_math.py::test_add")
+            .unwrap();
+        assert_eq!(add.duration_ms, Some(10));
+    }
+
+    #[test]
+    fn test_jest_report() {
+        let report = r#"{"numFailedTests":1,"testResults":[{"name":"/p/math.test.js","assertionResults":[{"fullName":"math adds","status":"passed","duration":3,"failureMessages":[]},{"fullName":"math divides","status":"failed","duration":5,"failureMessages":["Error: expect(received).toBe(expected)\n    at Object.<anonymous> (/p/math.test.js:9:17)"],"location":{"line":8,"column":3}}]}]}"#;
+        let mut parser = ResultParser::new(TestFramework::Jest, Path::new("/p"));
+        let results = finished(parser.push_line(report));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "jest::/p/math.test.js::math adds");
+        assert_eq!(results[0].duration_ms, Some(3));
+        assert_eq!(results[1].outcome, TestOutcome::Failed);
+        assert_eq!(results[1].failure_line, Some(9));
+        assert_eq!(results[1].message.as_deref(), Some("Error: expect(received).toBe(expected)"));
+    }
+}
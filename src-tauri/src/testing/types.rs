@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFramework {
+    Cargo,
+    Pytest,
+    Jest,
+}
+
+impl TestFramework {
+    /// Prefix used in test IDs (`cargo::tests::it_works`)
+    pub fn id_prefix(&self) -> &'static str {
+        match self {
+            TestFramework::Cargo => "cargo",
+            TestFramework::Pytest => "pytest",
+            TestFramework::Jest => "jest",
+        }
+    }
+}
+
+/// A discovered test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// `<framework>::<framework-specific name>`
+    pub id: String,
+    pub framework: TestFramework,
+    /// Leaf name shown in the tree
+    pub name: String,
+    /// Parent nodes in the tree (modules, files, classes, describe blocks)
+    pub path: Vec<String>,
+    pub file: Option<String>,
+    /// 1-based line of the test definition
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Queued,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result of one test in a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+    pub id: String,
+    pub outcome: TestOutcome,
+    pub duration_ms: Option<u64>,
+    /// Failure message (assertion text)
+    pub message: Option<String>,
+    /// Where the failure happened (1-based line)
+    pub failure_file: Option<String>,
+    pub failure_line: Option<usize>,
+}
+
+impl TestResult {
+    pub fn new(id: String, outcome: TestOutcome) -> Self {
+        Self {
+            id,
+            outcome,
+            duration_ms: None,
+            message: None,
+            failure_file: None,
+            failure_line: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunStatus {
+    Running,
+    Finished,
+    Cancelled,
+}
+
+/// Snapshot of a test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRunInfo {
+    pub run_id: String,
+    pub framework: TestFramework,
+    pub status: TestRunStatus,
+    pub results: Vec<TestResult>,
+    pub output: String,
+    /// Unix timestamp (ms)
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// Run a framework's tests, optionally limited to some test IDs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTestsRequest {
+    pub framework: TestFramework,
+    /// Empty = whole suite
+    #[serde(default)]
+    pub test_ids: Vec<String>,
+}
+
+/// Program and arguments that run a single test under a debugger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebugTarget {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+}
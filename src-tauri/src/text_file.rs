@@ -0,0 +1,289 @@
+//! Text file decoding and safe writes
+//!
+//! Files are decoded with their detected (or a requested) encoding and keep
+//! their BOM and line endings when written back. Writes go to a temp file in
+//! the same folder that is renamed over the target, so a crash mid-save never
+//! leaves a truncated file.
+
+use anyhow::{anyhow, Context, Result};
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Bytes inspected to tell text from binary
+const SNIFF_LEN: usize = 8192;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    #[serde(rename = "LF")]
+    Lf,
+    #[serde(rename = "CRLF")]
+    Crlf,
+}
+
+/// How a text file is stored on disk
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TextFormat {
+    /// encoding_rs name, e.g. "UTF-8", "UTF-16LE", "Shift_JIS", "windows-1252"
+    pub encoding: String,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+}
+
+/// Contents of a file opened in the editor; text always uses `\n`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FileContents {
+    Text { content: String, format: TextFormat },
+    Binary { size: u64 },
+}
+
+fn encoding_for(name: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(name.as_bytes()).ok_or_else(|| anyhow!("Unknown encoding: {}", name))
+}
+
+/// UTF-16 without a BOM: most ASCII characters leave a zero in every other byte
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    if odd_zeros * 10 >= pairs * 4 && even_zeros == 0 {
+        Some(UTF_16LE)
+    } else if even_zeros * 10 >= pairs * 4 && odd_zeros == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// NUL bytes or many control characters in the first few KB
+pub fn is_binary(bytes: &[u8]) -> bool {
+    if utf16_without_bom(bytes).is_some() || Encoding::for_bom(bytes).is_some() {
+        return false;
+    }
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if sample.contains(&0) {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|b| b.is_ascii_control() && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b))
+        .count();
+    control * 10 > sample.len()
+}
+
+/// Shift-JIS that decodes cleanly and contains kana; otherwise Latin-1 text
+/// such as "é" followed by a letter would also pass as Shift-JIS
+fn looks_like_shift_jis(bytes: &[u8]) -> bool {
+    SHIFT_JIS
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .is_some_and(|text| text.chars().any(|c| matches!(c, '\u{3040}'..='\u{30FF}')))
+}
+
+/// Encoding and the length of its BOM at the start of the bytes (0 if none)
+pub fn detect_encoding(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some(found) = Encoding::for_bom(bytes) {
+        return found;
+    }
+    if let Some(encoding) = utf16_without_bom(bytes) {
+        return (encoding, 0);
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return (UTF_8, 0);
+    }
+    if looks_like_shift_jis(bytes) {
+        return (SHIFT_JIS, 0);
+    }
+    (WINDOWS_1252, 0)
+}
+
+/// The line ending used by most lines; LF when there are none
+pub fn detect_line_ending(text: &str) -> LineEnding {
+    let lines = text.matches('\n').count();
+    let crlf = text.matches("\r\n").count();
+    if lines > 0 && crlf * 2 > lines {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    }
+}
+
+/// Decode with the detected encoding, or `encoding` when given. Line endings
+/// are kept as they are.
+pub fn decode_raw(bytes: &[u8], encoding: Option<&str>) -> Result<(String, &'static Encoding, bool)> {
+    let (detected, bom_len) = detect_encoding(bytes);
+    let encoding = match encoding {
+        Some(name) => encoding_for(name)?,
+        None => detected,
+    };
+    let bom = bom_len > 0 && detected == encoding;
+    let body = if bom { &bytes[bom_len..] } else { bytes };
+    let (text, _) = encoding.decode_without_bom_handling(body);
+    Ok((text.into_owned(), encoding, bom))
+}
+
+/// File contents for the editor, with `\r\n` normalized to `\n`
+pub fn decode(bytes: &[u8], encoding: Option<&str>) -> Result<FileContents> {
+    if encoding.is_none() && is_binary(bytes) {
+        return Ok(FileContents::Binary {
+            size: bytes.len() as u64,
+        });
+    }
+    let (text, encoding, bom) = decode_raw(bytes, encoding)?;
+    let line_ending = detect_line_ending(&text);
+    Ok(FileContents::Text {
+        content: text.replace("\r\n", "\n"),
+        format: TextFormat {
+            encoding: encoding.name().to_string(),
+            bom,
+            line_ending,
+        },
+    })
+}
+
+/// Bytes to write for `text` (which uses `\n`) in `format`
+pub fn encode(text: &str, format: &TextFormat) -> Result<Vec<u8>> {
+    let encoding = encoding_for(&format.encoding)?;
+    let text = match format.line_ending {
+        LineEnding::Lf => text.replace("\r\n", "\n"),
+        LineEnding::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+    };
+
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    // encoding_rs only encodes to UTF-8 for the UTF-16 encodings
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        if format.bom {
+            bytes.extend_from_slice(if encoding == UTF_16LE { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&if encoding == UTF_16LE {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(bytes);
+    }
+
+    if format.bom && encoding == UTF_8 {
+        bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    let (encoded, _, unmappable) = encoding.encode(&text);
+    if unmappable {
+        return Err(anyhow!(
+            "The text contains characters that cannot be saved as {}",
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+/// Replace `path` with `bytes` through a temp file in the same folder, keeping
+/// the permissions of the existing file. Symlinks are written through.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+
+    let mut temp = tempfile::Builder::new()
+        .prefix(".berry-save-")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create a temp file in {}", dir.display()))?;
+    temp.write_all(bytes)?;
+    temp.as_file().sync_all()?;
+    if let Some(permissions) = permissions {
+        fs::set_permissions(temp.path(), permissions)?;
+    }
+    temp.persist(&target)
+        .map_err(|e| anyhow!("Failed to replace {}: {}", target.display(), e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(contents: FileContents) -> (String, TextFormat) {
+        match contents {
+            FileContents::Text { content, format } => (content, format),
+            FileContents::Binary { .. } => panic!("decoded as binary"),
+        }
+    }
+
+    #[test]
+    fn test_detects_encodings() {
+        let (content, format) = text(decode("a\r\nb\r\n".as_bytes(), None).unwrap());
+        assert_eq!(content, "a\nb\n");
+        assert_eq!((format.encoding.as_str(), format.bom, format.line_ending), ("UTF-8", false, LineEnding::Crlf));
+
+        let utf16: Vec<u8> = "hé\n".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let (content, format) = text(decode(&utf16, None).unwrap());
+        assert_eq!((content.as_str(), format.encoding.as_str()), ("hé\n", "UTF-16LE"));
+
+        let (sjis, _, _) = SHIFT_JIS.encode("こんにちは\n");
+        let (content, format) = text(decode(&sjis, None).unwrap());
+        assert_eq!((content.as_str(), format.encoding.as_str()), ("こんにちは\n", "Shift_JIS"));
+
+        let (content, format) = text(decode(b"caf\xe9s\n", None).unwrap());
+        assert_eq!((content.as_str(), format.encoding.as_str()), ("cafés\n", "windows-1252"));
+
+        // An explicit encoding overrides detection
+        let (content, _) = text(decode(b"caf\xe9s\n", Some("Shift_JIS")).unwrap());
+        assert_ne!(content, "cafés\n");
+
+        assert_eq!(decode(b"\x7fELF\x02\x01\x00\x00", None).unwrap(), FileContents::Binary { size: 8 });
+    }
+
+    #[test]
+    fn test_round_trips_format() {
+        for bytes in [
+            b"\xef\xbb\xbfone\r\ntwo\r\n".to_vec(),
+            b"\xff\xfeo\x00k\x00\n\x00".to_vec(),
+            b"\xfe\xff\x00o\x00k\x00\r\x00\n".to_vec(),
+            b"na\xefve\n".to_vec(),
+        ] {
+            let (content, format) = text(decode(&bytes, None).unwrap());
+            assert_eq!(encode(&content, &format).unwrap(), bytes, "{:?}", format);
+        }
+
+        let latin1 = TextFormat {
+            encoding: "windows-1252".to_string(),
+            bom: false,
+            line_ending: LineEnding::Lf,
+        };
+        assert!(encode("日本", &latin1).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o755);
+        // No temp files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let link = dir.path().join("link.sh");
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+        write_atomic(&link, b"through link").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "through link");
+    }
+}
@@ -0,0 +1,52 @@
+use super::merge::merge_text;
+use super::types::*;
+use super::WorkspaceWatcher;
+use crate::local_history::{LocalHistory, RevisionSource};
+use crate::remote;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Watch the workspace folders and emit `FS_CHANGED_EVENT` with debounced changes
+#[tauri::command]
+pub async fn watch_workspace(
+    roots: Vec<String>,
+    app: AppHandle,
+    watcher: State<'_, WorkspaceWatcher>,
+    history: State<'_, LocalHistory>,
+) -> Result<(), String> {
+    // Remote folders are not watched
+    let roots: Vec<PathBuf> = roots.into_iter().filter(|root| !remote::is_remote(root)).map(PathBuf::from).collect();
+    history.set_roots(roots.clone());
+    watcher
+        .watch_roots(roots, move |batch| {
+            if let Err(e) = app.emit(FS_CHANGED_EVENT, &batch) {
+                tracing::warn!("Failed to emit file changes: {}", e);
+            }
+            // Changes made outside the editor go to local history; saves are
+            // already recorded and skipped as unchanged
+            let history = app.state::<LocalHistory>();
+            for change in &batch.changes {
+                let path = match change {
+                    FsChange::Created { path, is_dir: false } | FsChange::Modified { path } => path,
+                    FsChange::Renamed { to, .. } => to,
+                    _ => continue,
+                };
+                if let Err(e) = history.record_in(Path::new(&batch.root), Path::new(path), RevisionSource::External) {
+                    tracing::warn!("Failed to record local history of {}: {}", path, e);
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to watch workspace: {}", e))
+}
+
+#[tauri::command]
+pub async fn unwatch_workspace(watcher: State<'_, WorkspaceWatcher>) -> Result<(), String> {
+    watcher.unwatch();
+    Ok(())
+}
+
+/// Merge a tab's unsaved text (`ours`) with the changed file (`theirs`)
+#[tauri::command]
+pub async fn merge_external_change(base: String, ours: String, theirs: String) -> Result<TextMerge, String> {
+    Ok(merge_text(&base, &ours, &theirs))
+}
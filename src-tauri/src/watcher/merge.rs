@@ -0,0 +1,153 @@
+//! Line-based three-way merge of a tab's unsaved text with the file on disk,
+//! using the last loaded or saved text as the common base
+
+use super::types::TextMerge;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// A base range replaced by one side
+struct Change<'a> {
+    start: usize,
+    end: usize,
+    lines: &'a [&'a str],
+}
+
+fn changes<'a>(base: &[&'a str], other: &'a [&'a str]) -> Vec<Change<'a>> {
+    capture_diff_slices(Algorithm::Myers, base, other)
+        .into_iter()
+        .filter_map(|op| match op {
+            DiffOp::Equal { .. } => None,
+            DiffOp::Delete { old_index, old_len, .. } => Some(Change {
+                start: old_index,
+                end: old_index + old_len,
+                lines: &[],
+            }),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => Some(Change {
+                start: old_index,
+                end: old_index,
+                lines: &other[new_index..new_index + new_len],
+            }),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => Some(Change {
+                start: old_index,
+                end: old_index + old_len,
+                lines: &other[new_index..new_index + new_len],
+            }),
+        })
+        .collect()
+}
+
+/// One side's text for base lines `start..end`
+fn side_text(base: &[&str], changes: &[&Change], start: usize, end: usize) -> String {
+    let mut text = String::new();
+    let mut pos = start;
+    for change in changes {
+        text.push_str(&base[pos..change.start].concat());
+        text.push_str(&change.lines.concat());
+        pos = change.end;
+    }
+    text.push_str(&base[pos..end].concat());
+    text
+}
+
+fn push_section(out: &mut String, marker: &str, text: &str) {
+    out.push_str(marker);
+    out.push('\n');
+    out.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Merge `ours` (the editor) and `theirs` (the disk) against `base`. Regions
+/// changed on both sides differently become diff3-style conflict blocks.
+pub fn merge_text(base: &str, ours: &str, theirs: &str) -> TextMerge {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let our_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let their_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+
+    // (change, is_ours) ordered by position in the base
+    let mut all: Vec<(Change, bool)> = changes(&base_lines, &our_lines)
+        .into_iter()
+        .map(|c| (c, true))
+        .chain(changes(&base_lines, &their_lines).into_iter().map(|c| (c, false)))
+        .collect();
+    all.sort_by_key(|(c, is_ours)| (c.start, c.end, !is_ours));
+
+    let mut result = String::new();
+    let mut conflicts = 0;
+    let mut pos = 0;
+    let mut idx = 0;
+    while idx < all.len() {
+        // Group changes whose base ranges overlap or touch
+        let start = all[idx].0.start;
+        let mut end = all[idx].0.end;
+        let mut group = idx + 1;
+        while group < all.len() && all[group].0.start <= end {
+            end = end.max(all[group].0.end);
+            group += 1;
+        }
+        let chunk = &all[idx..group];
+        idx = group;
+
+        result.push_str(&base_lines[pos..start].concat());
+        pos = end;
+
+        let ours_in: Vec<&Change> = chunk.iter().filter(|(_, o)| *o).map(|(c, _)| c).collect();
+        let theirs_in: Vec<&Change> = chunk.iter().filter(|(_, o)| !*o).map(|(c, _)| c).collect();
+        let our_text = side_text(&base_lines, &ours_in, start, end);
+        let their_text = side_text(&base_lines, &theirs_in, start, end);
+
+        if theirs_in.is_empty() || our_text == their_text {
+            result.push_str(&our_text);
+        } else if ours_in.is_empty() {
+            result.push_str(&their_text);
+        } else {
+            conflicts += 1;
+            push_section(&mut result, "<<<<<<< Editor", &our_text);
+            push_section(&mut result, "||||||| Saved", &base_lines[start..end].concat());
+            push_section(&mut result, "=======", &their_text);
+            result.push_str(">>>>>>> Disk\n");
+        }
+    }
+    result.push_str(&base_lines[pos..].concat());
+
+    TextMerge { result, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_separate_edits() {
+        let base = "one\ntwo\nthree\nfour\nfive\n";
+        let ours = "ONE\ntwo\nthree\nfour\nfive\n";
+        let theirs = "one\ntwo\nthree\nfour\nFIVE\nsix\n";
+        let merged = merge_text(base, ours, theirs);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.result, "ONE\ntwo\nthree\nfour\nFIVE\nsix\n");
+
+        // The same edit on both sides is not a conflict
+        let same = merge_text(base, theirs, theirs);
+        assert_eq!((same.result.as_str(), same.conflicts), (theirs, 0));
+    }
+
+    #[test]
+    fn test_conflicting_edits() {
+        let base = "a\nb\nc";
+        let merged = merge_text(base, "a\nmine\nc", "a\ndisk\nc");
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.result,
+            "a\n<<<<<<< Editor\nmine\n||||||| Saved\nb\n=======\ndisk\n>>>>>>> Disk\nc"
+        );
+    }
+}
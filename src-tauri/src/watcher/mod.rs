@@ -0,0 +1,13 @@
+//! Workspace file watcher
+//!
+//! Watches the open workspace with `notify`, debounces bursts of raw events,
+//! drops paths ignored by `.gitignore` and emits the coalesced changes to the
+//! frontend so open tabs and the file tree follow the disk.
+
+pub mod commands;
+pub mod merge;
+pub mod service;
+pub mod types;
+
+pub use service::{IgnoreRules, WorkspaceWatcher};
+pub use types::*;
@@ -0,0 +1,485 @@
+use super::types::*;
+use anyhow::{anyhow, Result};
+use ignore::gitignore::Gitignore;
+use ignore::{Match, WalkBuilder};
+use notify::event::{CreateKind, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Quiet period that ends a burst of events
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Longest a burst is held back before it is emitted anyway
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// `.gitignore` rules of a workspace; `.git` itself is always ignored
+pub struct IgnoreRules {
+    root: PathBuf,
+    /// One matcher per `.gitignore` (plus `.git/info/exclude`), deepest first
+    matchers: Vec<Gitignore>,
+}
+
+impl IgnoreRules {
+    pub fn load(root: &Path) -> Self {
+        let files: Vec<PathBuf> = WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_name() == ".gitignore")
+            .map(|entry| entry.into_path())
+            .collect();
+        Self::from_files(root, files)
+    }
+
+    /// Only the rules that can apply to entries of `dir`: the `.gitignore`
+    /// files of `dir` and its ancestors up to `root`
+    pub fn for_dir(root: &Path, dir: &Path) -> Self {
+        let files: Vec<PathBuf> = dir
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(root))
+            .map(|ancestor| ancestor.join(".gitignore"))
+            .filter(|path| path.is_file())
+            .collect();
+        Self::from_files(root, files)
+    }
+
+    fn from_files(root: &Path, mut files: Vec<PathBuf>) -> Self {
+        files.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+
+        let mut matchers: Vec<Gitignore> = files.iter().map(|path| Gitignore::new(path).0).collect();
+        let exclude = root.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            let mut builder = ignore::gitignore::GitignoreBuilder::new(root);
+            builder.add(exclude);
+            if let Ok(matcher) = builder.build() {
+                matchers.push(matcher);
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            matchers,
+        }
+    }
+
+    /// Whether `path` is ignored; paths outside the workspace count as ignored
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        if relative.components().next().is_some_and(|c| c.as_os_str() == ".git") {
+            return true;
+        }
+        for matcher in &self.matchers {
+            if !path.starts_with(matcher.path()) {
+                continue;
+            }
+            match matcher.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// The change as seen from the workspace: a rename across the ignore
+    /// boundary becomes a create or delete
+    pub fn filter(&self, change: FsChange) -> Option<FsChange> {
+        let ignored = |path: &str| {
+            let path = Path::new(path);
+            self.is_ignored(path, path.is_dir())
+        };
+        match change {
+            FsChange::Renamed { from, to } => match (ignored(&from), ignored(&to)) {
+                (true, true) => None,
+                (true, false) => Some(FsChange::Created {
+                    is_dir: Path::new(&to).is_dir(),
+                    path: to,
+                }),
+                (false, true) => Some(FsChange::Deleted { path: from }),
+                (false, false) => Some(FsChange::Renamed { from, to }),
+            },
+            FsChange::Created { ref path, is_dir } => (!self.is_ignored(Path::new(path), is_dir)).then_some(change),
+            other => (!ignored(other.path())).then_some(other),
+        }
+    }
+}
+
+/// Changes described by a raw notify event. `workspace` maps the reported
+/// (canonical) path onto the workspace path the frontend uses.
+fn raw_changes(event: &Event, workspace: &impl Fn(&Path) -> String) -> Vec<FsChange> {
+    let paths = &event.paths;
+    match event.kind {
+        EventKind::Create(kind) => paths
+            .iter()
+            .map(|path| FsChange::Created {
+                path: workspace(path),
+                is_dir: kind == CreateKind::Folder || path.is_dir(),
+            })
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => vec![FsChange::Renamed {
+            from: workspace(&paths[0]),
+            to: workspace(&paths[1]),
+        }],
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths
+            .iter()
+            .map(|path| FsChange::Deleted { path: workspace(path) })
+            .collect(),
+        // The other half of a rename, or a platform that doesn't say which half
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .iter()
+            .map(|path| match path.exists() {
+                true => FsChange::Created {
+                    path: workspace(path),
+                    is_dir: path.is_dir(),
+                },
+                false => FsChange::Deleted { path: workspace(path) },
+            })
+            .collect(),
+        // Permission and timestamp changes don't touch content
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Modify(_) => paths
+            .iter()
+            .filter(|path| !path.is_dir())
+            .map(|path| FsChange::Modified { path: workspace(path) })
+            .collect(),
+        EventKind::Remove(_) => paths
+            .iter()
+            .map(|path| FsChange::Deleted { path: workspace(path) })
+            .collect(),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => Vec::new(),
+    }
+}
+
+/// Collapse a burst to at most one change per path, e.g. create + modify is a
+/// create, create + delete is nothing, and a temp file renamed over a file
+/// (atomic save) is a modification of that file
+pub fn coalesce(changes: Vec<FsChange>) -> Vec<FsChange> {
+    let mut merged: Vec<Option<FsChange>> = Vec::new();
+    let mut by_path: HashMap<String, usize> = HashMap::new();
+
+    for change in changes {
+        let key = change.path().to_string();
+        let source = match &change {
+            FsChange::Renamed { from, .. } => from.clone(),
+            _ => key.clone(),
+        };
+        let previous = by_path.remove(&source).and_then(|idx| merged[idx].take());
+        if let Some(idx) = by_path.remove(&key) {
+            merged[idx] = None;
+        }
+
+        let next = match (previous, change) {
+            (Some(FsChange::Created { .. }), FsChange::Deleted { .. }) => None,
+            (Some(FsChange::Renamed { from, .. }), FsChange::Deleted { .. }) => Some(FsChange::Deleted { path: from }),
+            (Some(FsChange::Deleted { .. }), FsChange::Created { path, is_dir: false }) => Some(FsChange::Modified { path }),
+            (Some(previous @ (FsChange::Created { .. } | FsChange::Renamed { .. })), FsChange::Modified { .. }) => {
+                Some(previous)
+            }
+            (Some(FsChange::Created { is_dir: false, .. }), FsChange::Renamed { to, .. }) => {
+                Some(FsChange::Modified { path: to })
+            }
+            (Some(FsChange::Created { is_dir: true, .. }), FsChange::Renamed { to, .. }) => {
+                Some(FsChange::Created { path: to, is_dir: true })
+            }
+            (Some(FsChange::Renamed { from, .. }), FsChange::Renamed { to, .. }) => Some(FsChange::Renamed { from, to }),
+            (_, change) => Some(change),
+        };
+        if let Some(next) = next {
+            by_path.insert(key, merged.len());
+            merged.push(Some(next));
+        }
+    }
+    merged.into_iter().flatten().collect()
+}
+
+/// Debounce raw events from `events` into batches for `emit`; returns when
+/// the sending watcher is dropped
+fn debounce_loop(
+    root: &Path,
+    canonical_root: &Path,
+    events: Receiver<notify::Result<Event>>,
+    emit: impl Fn(FsChangeBatch),
+) {
+    let workspace = |path: &Path| match path.strip_prefix(canonical_root) {
+        Ok(relative) => root.join(relative).to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    };
+    let mut rules = IgnoreRules::load(root);
+
+    while let Ok(first) = events.recv() {
+        let started = Instant::now();
+        let mut raw = Vec::new();
+        let mut collect = |event: notify::Result<Event>| match event {
+            Ok(event) => raw.extend(raw_changes(&event, &workspace)),
+            Err(e) => tracing::warn!("File watcher error: {}", e),
+        };
+        collect(first);
+
+        let mut disconnected = false;
+        while started.elapsed() < MAX_DELAY {
+            match events.recv_timeout(DEBOUNCE.min(MAX_DELAY.saturating_sub(started.elapsed()))) {
+                Ok(event) => collect(event),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+
+        if raw.iter().any(|c| Path::new(c.path()).file_name().is_some_and(|n| n == ".gitignore")) {
+            rules = IgnoreRules::load(root);
+        }
+        let changes: Vec<FsChange> = coalesce(raw).into_iter().filter_map(|c| rules.filter(c)).collect();
+        if !changes.is_empty() {
+            emit(FsChangeBatch {
+                root: root.to_string_lossy().to_string(),
+                changes,
+            });
+        }
+        if disconnected {
+            return;
+        }
+    }
+}
+
+/// Watchers of the open workspace folders; watching other folders replaces them
+#[derive(Default)]
+pub struct WorkspaceWatcher {
+    active: Mutex<Vec<(PathBuf, RecommendedWatcher)>>,
+}
+
+impl WorkspaceWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch `root` recursively and pass debounced changes to `emit`
+    pub fn watch(&self, root: PathBuf, emit: impl Fn(FsChangeBatch) + Clone + Send + 'static) -> Result<()> {
+        self.watch_roots(vec![root], emit)
+    }
+
+    /// Watch every folder of a multi-root workspace. Folders that are already
+    /// watched keep their watcher; folders no longer listed are dropped. A
+    /// folder that cannot be watched is skipped; the error names it only when
+    /// no folder is watched at all.
+    pub fn watch_roots(
+        &self,
+        roots: Vec<PathBuf>,
+        emit: impl Fn(FsChangeBatch) + Clone + Send + 'static,
+    ) -> Result<()> {
+        let mut active = self.active.lock().unwrap();
+        // Dropping a watcher ends its debounce thread
+        active.retain(|(root, _)| roots.contains(root));
+        let mut failure = None;
+        for root in roots {
+            if active.iter().any(|(watched, _)| *watched == root) {
+                continue;
+            }
+            match Self::start(&root, emit.clone()) {
+                Ok(watcher) => active.push((root, watcher)),
+                Err(e) => {
+                    tracing::warn!("Not watching {}: {}", root.display(), e);
+                    failure = Some(e);
+                }
+            }
+        }
+        match failure {
+            Some(e) if active.is_empty() => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn start(root: &Path, emit: impl Fn(FsChangeBatch) + Send + 'static) -> Result<RecommendedWatcher> {
+        let canonical_root = root
+            .canonicalize()
+            .map_err(|e| anyhow!("Cannot watch {}: {}", root.display(), e))?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        watcher.watch(&canonical_root, RecursiveMode::Recursive)?;
+
+        let loop_root = root.to_path_buf();
+        std::thread::spawn(move || debounce_loop(&loop_root, &canonical_root, events, emit));
+        Ok(watcher)
+    }
+
+    pub fn unwatch(&self) {
+        self.active.lock().unwrap().clear();
+    }
+
+    /// The first watched folder
+    pub fn root(&self) -> Option<PathBuf> {
+        self.roots().into_iter().next()
+    }
+
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.active.lock().unwrap().iter().map(|(root, _)| root.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{DataChange, RemoveKind};
+    use tempfile::TempDir;
+
+    fn created(path: &str) -> FsChange {
+        FsChange::Created {
+            path: path.to_string(),
+            is_dir: false,
+        }
+    }
+
+    fn modified(path: &str) -> FsChange {
+        FsChange::Modified { path: path.to_string() }
+    }
+
+    fn deleted(path: &str) -> FsChange {
+        FsChange::Deleted { path: path.to_string() }
+    }
+
+    fn renamed(from: &str, to: &str) -> FsChange {
+        FsChange::Renamed {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        assert_eq!(coalesce(vec![created("a"), modified("a"), modified("a")]), vec![created("a")]);
+        assert_eq!(coalesce(vec![created("tmp"), deleted("tmp"), modified("b")]), vec![modified("b")]);
+        // Delete-and-recreate and atomic saves through a temp file are edits
+        assert_eq!(coalesce(vec![deleted("a"), created("a")]), vec![modified("a")]);
+        assert_eq!(
+            coalesce(vec![created("a.tmp"), modified("a.tmp"), renamed("a.tmp", "a")]),
+            vec![modified("a")]
+        );
+        assert_eq!(coalesce(vec![renamed("a", "b"), renamed("b", "c")]), vec![renamed("a", "c")]);
+        assert_eq!(coalesce(vec![renamed("a", "b"), deleted("b")]), vec![deleted("a")]);
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("sub/build")).unwrap();
+        std::fs::create_dir_all(root.join(".git/info")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        std::fs::write(root.join("sub/.gitignore"), "build/\n!keep.log\n").unwrap();
+        std::fs::write(root.join(".git/info/exclude"), "scratch.txt\n").unwrap();
+
+        let rules = IgnoreRules::load(root);
+        assert!(rules.is_ignored(&root.join("target/debug/app"), false));
+        assert!(rules.is_ignored(&root.join("err.log"), false));
+        assert!(rules.is_ignored(&root.join("sub/build/out.o"), false));
+        assert!(rules.is_ignored(&root.join(".git/HEAD"), false));
+        assert!(rules.is_ignored(&root.join("scratch.txt"), false));
+        assert!(!rules.is_ignored(&root.join("sub/keep.log"), false));
+        assert!(!rules.is_ignored(&root.join("src/main.rs"), false));
+        assert!(rules.is_ignored(Path::new("/elsewhere/file"), false));
+
+        // Rules for one folder only need its own and its ancestors' files
+        let sub = IgnoreRules::for_dir(root, &root.join("sub"));
+        assert!(sub.is_ignored(&root.join("sub/build"), true));
+        assert!(sub.is_ignored(&root.join("sub/err.log"), false));
+        assert!(!sub.is_ignored(&root.join("sub/keep.log"), false));
+
+        let path = |p: &str| root.join(p).to_string_lossy().to_string();
+        assert_eq!(
+            rules.filter(renamed(&path("err.log"), &path("err.txt"))),
+            Some(FsChange::Created {
+                path: path("err.txt"),
+                is_dir: false
+            })
+        );
+        assert_eq!(rules.filter(modified(&path("target/x"))), None);
+    }
+
+    #[test]
+    fn test_debounce_loop() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+
+        let (sender, events) = mpsc::channel();
+        let event = |kind: EventKind, name: &str| Ok(Event::new(kind).add_path(root.join(name)));
+        sender
+            .send(event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), "main.rs"))
+            .unwrap();
+        sender.send(event(EventKind::Create(CreateKind::File), "out.log")).unwrap();
+        sender.send(event(EventKind::Create(CreateKind::File), "gone.rs")).unwrap();
+        sender.send(event(EventKind::Remove(RemoveKind::File), "gone.rs")).unwrap();
+        drop(sender);
+
+        let batches = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let collected = batches.clone();
+        debounce_loop(&root, &root, events, move |batch| collected.lock().unwrap().push(batch));
+
+        let batches = batches.lock().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(
+            batches[0].changes,
+            vec![modified(&root.join("main.rs").to_string_lossy())]
+        );
+    }
+
+    #[test]
+    fn test_watch_reports_changes() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let watcher = WorkspaceWatcher::new();
+        let (sender, batches) = mpsc::channel();
+        let batches_sender = sender.clone();
+        watcher
+            .watch(root.clone(), move |batch| {
+                let _ = sender.send(batch);
+            })
+            .unwrap();
+        assert_eq!(watcher.root(), Some(root.clone()));
+
+        std::fs::write(root.join("new.txt"), "hello").unwrap();
+        let batch = batches.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(batch
+            .changes
+            .iter()
+            .any(|c| c.path() == root.join("new.txt").to_string_lossy()));
+
+        // A second folder is added without restarting the first
+        let other = TempDir::new().unwrap();
+        let sender = batches_sender.clone();
+        watcher
+            .watch_roots(vec![root.clone(), other.path().to_path_buf()], move |batch| {
+                let _ = sender.send(batch);
+            })
+            .unwrap();
+        assert_eq!(watcher.roots(), vec![root.clone(), other.path().to_path_buf()]);
+        std::fs::write(other.path().join("other.txt"), "hello").unwrap();
+        let other_root = other.path().to_string_lossy().to_string();
+        let batch = std::iter::from_fn(|| batches.recv_timeout(Duration::from_secs(10)).ok())
+            .find(|batch| batch.root == other_root)
+            .unwrap();
+        assert!(batch.changes.iter().any(|c| c.path().ends_with("other.txt")));
+
+        // A folder that cannot be watched does not stop the ones after it
+        let missing = root.join("missing");
+        watcher
+            .watch_roots(vec![missing.clone(), root.clone(), other.path().to_path_buf()], |_| {})
+            .unwrap();
+        assert_eq!(watcher.roots(), vec![root.clone(), other.path().to_path_buf()]);
+        assert!(watcher.watch(missing, |_| {}).is_err());
+
+        watcher.unwatch();
+        assert_eq!(watcher.root(), None);
+    }
+}
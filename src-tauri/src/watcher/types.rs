@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Event carrying a `FsChangeBatch`
+pub const FS_CHANGED_EVENT: &str = "workspace-changed";
+
+/// A change on disk, after debouncing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FsChange {
+    Created { path: String, is_dir: bool },
+    Modified { path: String },
+    Deleted { path: String },
+    Renamed { from: String, to: String },
+}
+
+impl FsChange {
+    /// Path the change leaves behind (the target of a rename)
+    pub fn path(&self) -> &str {
+        match self {
+            FsChange::Created { path, .. } | FsChange::Modified { path } | FsChange::Deleted { path } => path,
+            FsChange::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Changes of one debounce window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsChangeBatch {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+}
+
+/// Line-based three-way merge of a tab's text with the file on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextMerge {
+    /// Merged text; conflicting regions are wrapped in conflict markers
+    pub result: String,
+    pub conflicts: usize,
+}
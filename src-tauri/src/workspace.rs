@@ -0,0 +1,125 @@
+//! Multi-root workspaces
+//!
+//! A workspace file (JSON, `*.berry-workspace`) lists the folders opened in
+//! one window, with settings overrides for the whole workspace and for each
+//! folder. Folder paths below the workspace file are stored relative to it so
+//! the file can be committed and shared.
+
+use crate::remote;
+use crate::text_file;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceFolder {
+    pub path: String,
+    /// Shown instead of the folder name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Settings overrides for files in this folder
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub folders: Vec<WorkspaceFolder>,
+    /// Settings overrides for all folders
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+}
+
+fn base_dir(file: &Path) -> &Path {
+    file.parent().unwrap_or(Path::new("."))
+}
+
+/// Read a workspace file; folder paths are returned absolute. Remote
+/// (`ssh://`) folders are kept as they are.
+pub fn load(file: &Path) -> Result<Workspace> {
+    let json = fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let mut workspace: Workspace =
+        serde_json::from_slice(&json).map_err(|e| anyhow!("Invalid workspace file {}: {}", file.display(), e))?;
+    for folder in &mut workspace.folders {
+        let path = Path::new(&folder.path);
+        if path.is_relative() && !remote::is_remote(&folder.path) {
+            let absolute: PathBuf = base_dir(file).join(path).components().collect();
+            folder.path = absolute.to_string_lossy().to_string();
+        }
+    }
+    Ok(workspace)
+}
+
+/// Write a workspace file, storing folders below it as relative paths
+pub fn save(file: &Path, workspace: &Workspace) -> Result<()> {
+    let mut stored = workspace.clone();
+    for folder in &mut stored.folders {
+        if let Ok(relative) = Path::new(&folder.path).strip_prefix(base_dir(file)) {
+            folder.path = if relative.as_os_str().is_empty() {
+                ".".to_string()
+            } else {
+                relative.to_string_lossy().to_string()
+            };
+        }
+    }
+    let json = serde_json::to_vec_pretty(&stored)?;
+    text_file::write_atomic(file, &json)
+}
+
+/// Open a workspace file
+#[tauri::command]
+pub async fn open_workspace(path: String) -> Result<Workspace, String> {
+    load(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+/// Save the open folders and their settings to a workspace file
+#[tauri::command]
+pub async fn save_workspace(path: String, workspace: Workspace) -> Result<(), String> {
+    save(Path::new(&path), &workspace).map_err(|e| format!("Failed to save workspace: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("project.berry-workspace");
+        let outside = TempDir::new().unwrap();
+        let folder = |path: &Path| WorkspaceFolder {
+            path: path.to_string_lossy().to_string(),
+            name: None,
+            settings: Map::new(),
+        };
+
+        let mut api = folder(&dir.path().join("api"));
+        api.settings.insert("tab_size".to_string(), Value::from(2));
+        let workspace = Workspace {
+            folders: vec![
+                folder(dir.path()),
+                api,
+                folder(outside.path()),
+                folder(Path::new("ssh://devbox/srv/app")),
+            ],
+            settings: Map::new(),
+        };
+        save(&file, &workspace).unwrap();
+
+        // Folders below the file are stored relative to it
+        let stored: Value = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        assert_eq!(stored["folders"][0]["path"], ".");
+        assert_eq!(stored["folders"][1]["path"], "api");
+        assert_eq!(stored["folders"][2]["path"], outside.path().to_string_lossy().as_ref());
+        assert_eq!(stored["folders"][3]["path"], "ssh://devbox/srv/app");
+        assert!(stored.get("settings").is_none());
+
+        assert_eq!(load(&file).unwrap(), workspace);
+
+        fs::write(&file, "{ \"folders\": 3 }").unwrap();
+        assert!(load(&file).is_err());
+    }
+}
@@ -3,6 +3,14 @@
 
 use ropey::Rope;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of buffer revisions, shared by all buffers so that none repeats
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
 
 /// ✅ IntelliJ Design: TextBuffer with syntax highlighting cache
 /// Cache stores pre-rendered HTML for visible lines only
@@ -17,6 +25,8 @@ pub struct TextBuffer {
     syntax_cache: HashMap<usize, String>,
     /// Version counter - incremented on every edit to invalidate cache
     version: u64,
+    /// Stamp of the current text, unique across buffers (clones share it)
+    revision: u64,
 }
 
 impl TextBuffer {
@@ -28,6 +38,7 @@ impl TextBuffer {
             language: String::from("plaintext"),
             syntax_cache: HashMap::new(),
             version: 0,
+            revision: next_revision(),
         }
     }
 
@@ -39,6 +50,7 @@ impl TextBuffer {
             language: String::from("plaintext"),
             syntax_cache: HashMap::new(),
             version: 0,
+            revision: next_revision(),
         }
     }
 
@@ -61,6 +73,7 @@ impl TextBuffer {
         self.rope.insert(safe_idx, text);
         self.modified = true;
         self.version += 1;
+        self.revision = next_revision();
 
         // ✅ IntelliJ Pro: Smart cache invalidation
         // Only clear lines that were actually modified + surrounding context
@@ -85,6 +98,7 @@ impl TextBuffer {
         self.rope.remove(safe_start..safe_end);
         self.modified = true;
         self.version += 1;
+        self.revision = next_revision();
 
         // ✅ IntelliJ Pro: Only invalidate affected range
         self.invalidate_cache_range(start_line, end_line + 2); // +2 for context
@@ -157,6 +171,13 @@ impl TextBuffer {
         self.version
     }
 
+    /// Stamp of the current text. Unlike `version`, it never repeats, also
+    /// not after a buffer is replaced by an older clone (undo), so equal
+    /// revisions always mean equal text.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     /// ✅ IntelliJ Design: Cache highlighted HTML for a line (on-demand)
     /// Returns cached HTML if available, None if not cached yet
     pub fn get_cached_highlight(&self, line_idx: usize) -> Option<&str> {
@@ -665,6 +686,21 @@ mod tests {
         assert_eq!(buffer.version(), v2);
     }
 
+    #[wasm_bindgen_test]
+    fn test_revision_never_repeats() {
+        let mut buffer = TextBuffer::from_str("A");
+        let before = buffer.clone();
+        buffer.insert(1, "B");
+        let edited = buffer.revision();
+
+        // Undo で古い複製に戻した後の編集も別のリビジョンになる
+        buffer = before.clone();
+        assert_eq!(buffer.revision(), before.revision());
+        buffer.insert(1, "C");
+        assert_ne!(buffer.revision(), edited);
+        assert_ne!(buffer.revision(), before.revision());
+    }
+
     #[wasm_bindgen_test]
     fn test_multiline_insert_cache_invalidation() {
         let mut buffer = TextBuffer::from_str("Line1\nLine2\nLine3");
@@ -1,15 +1,25 @@
 //! UI Components for BerryEditor - Tauri Version
 //! Uses native file system access
 
-use crate::core::virtual_editor::VirtualEditorPanel;
+use crate::core::virtual_editor::{FormatChange, VirtualEditorPanel};
 use crate::file_tree_tauri::FileTreePanelTauri;
 use crate::search_panel::SearchPanel;
 use crate::database_panel::DatabasePanel;
 use crate::workflow_panel::WorkflowPanel;
 use crate::terminal_panel::TerminalPanel;
+use crate::tasks_panel::{TaskRunner, TasksPanel};
+use crate::test_explorer_panel::{TestExplorer, TestExplorerPanel};
+use crate::git_ui::{CommitHistoryPanel, GitGutter, HunkPeek, InteractiveRebase, SourceControlPanel};
+use crate::tauri_bindings_tasks::TaskGroup;
 use crate::berrycode_panel::BerryCodePanel;
 use crate::settings::EditorSettings;
-use crate::tauri_bindings;
+use crate::hot_exit::HotExit;
+use crate::local_history_panel::LocalHistoryPanel;
+use crate::workspace::{WorkspaceState, WorkspaceToolbar};
+use crate::tauri_bindings::{self, LineEnding, TextFormat};
+use crate::tauri_bindings_hot_exit;
+use crate::tauri_bindings_remote;
+use crate::tauri_bindings_watcher::{self, FsChangeBatch};
 use leptos::prelude::*;
 
 /// Active panel in the sidebar
@@ -17,17 +27,106 @@ use leptos::prelude::*;
 enum ActivePanel {
     Explorer,
     Search,
+    SourceControl,
     Chat,
     Database,
     Workflow,
+    Tasks,
+    Testing,
+    History,
     Terminal,
     VirtualOffice,
     Settings,
 }
 
+impl ActivePanel {
+    const ALL: [ActivePanel; 12] = [
+        ActivePanel::Explorer,
+        ActivePanel::Search,
+        ActivePanel::SourceControl,
+        ActivePanel::Chat,
+        ActivePanel::Database,
+        ActivePanel::Workflow,
+        ActivePanel::Tasks,
+        ActivePanel::Testing,
+        ActivePanel::History,
+        ActivePanel::Terminal,
+        ActivePanel::VirtualOffice,
+        ActivePanel::Settings,
+    ];
+
+    /// Name stored in the hot exit session
+    fn id(self) -> &'static str {
+        match self {
+            ActivePanel::Explorer => "explorer",
+            ActivePanel::Search => "search",
+            ActivePanel::SourceControl => "source-control",
+            ActivePanel::Chat => "chat",
+            ActivePanel::Database => "database",
+            ActivePanel::Workflow => "workflow",
+            ActivePanel::Tasks => "tasks",
+            ActivePanel::Testing => "testing",
+            ActivePanel::History => "history",
+            ActivePanel::Terminal => "terminal",
+            ActivePanel::VirtualOffice => "virtual-office",
+            ActivePanel::Settings => "settings",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|panel| panel.id() == id)
+    }
+}
+
+/// View shown in the source control sidebar
+#[derive(Clone, Copy, PartialEq)]
+enum GitView {
+    Changes,
+    History,
+    Rebase,
+}
+
 /// Status Bar component with branding
 #[component]
-pub fn StatusBar() -> impl IntoView {
+pub fn StatusBar(
+    /// On-disk format of the active editor tab
+    #[prop(optional)]
+    file_format: Option<Signal<Option<TextFormat>>>,
+    /// Called when the encoding or line ending of the active tab is switched
+    #[prop(optional)]
+    on_format_change: Option<Callback<FormatChange>>,
+) -> impl IntoView {
+    let show_encodings = RwSignal::new(false);
+    let format = move || file_format.and_then(|f| f.get());
+    let change = move |change: FormatChange| {
+        show_encodings.set(false);
+        if let Some(callback) = on_format_change {
+            callback.run(change);
+        }
+    };
+    let item_style = "padding: 3px 12px; cursor: pointer; white-space: nowrap;";
+    let encoding_items = move |reopen: bool| {
+        TextFormat::ENCODINGS
+            .iter()
+            .map(|(name, label)| {
+                let name = name.to_string();
+                view! {
+                    <div
+                        class="berry-status-menu-item"
+                        style=item_style
+                        on:click=move |_| change(if reopen {
+                            FormatChange::ReopenWithEncoding(name.clone())
+                        } else {
+                            FormatChange::SaveWithEncoding(name.clone())
+                        })
+                    >
+                        {*label}
+                    </div>
+                }
+            })
+            .collect_view()
+    };
+
     view! {
         <div class="berry-editor-status-bar" style="
             display: flex;
@@ -39,14 +138,46 @@ pub fn StatusBar() -> impl IntoView {
             font-size: 12px;
             padding: 0 12px;
             border-top: 1px solid #1E1E1E;
+            position: relative;
         ">
             <div class="berry-editor-status-left" style="display: flex; gap: 12px; align-items: center;">
                 <span style="font-weight: bold; color: #E0E0E0;">"BerryEditor"</span>
                 <span style="color: #858585;">"100% Rust"</span>
             </div>
-            <div class="berry-editor-status-right" style="color: #858585;">
+            <div class="berry-editor-status-right" style="color: #858585; display: flex; gap: 12px; align-items: center;">
+                {move || format().map(|format| {
+                    let line_ending = format.line_ending;
+                    view! {
+                        <span
+                            style="cursor: pointer; color: #CCCCCC;"
+                            title="Select Encoding"
+                            on:click=move |_| show_encodings.update(|v| *v = !*v)
+                        >
+                            {format.encoding_label()}
+                        </span>
+                        <span
+                            style="cursor: pointer; color: #CCCCCC;"
+                            title="Toggle End of Line Sequence"
+                            on:click=move |_| change(FormatChange::LineEnding(match line_ending {
+                                LineEnding::Lf => LineEnding::Crlf,
+                                LineEnding::Crlf => LineEnding::Lf,
+                            }))
+                        >
+                            {line_ending.label()}
+                        </span>
+                    }
+                })}
                 <span>"WASM"</span>
             </div>
+            {move || (show_encodings.get() && format().is_some()).then(|| view! {
+                <div class="berry-status-menu" style="position: absolute; right: 12px; bottom: 24px; z-index: 1000; max-height: 420px; overflow-y: auto; background: #2B2D30; border: 1px solid #43454A; border-radius: 4px; box-shadow: 0 4px 12px rgba(0, 0, 0, 0.4); color: #DFE1E5; padding: 4px 0;">
+                    <div style="padding: 3px 12px; color: #858585; font-size: 11px;">"Reopen with Encoding"</div>
+                    {encoding_items(true)}
+                    <div style="border-top: 1px solid #43454A; margin: 4px 0;"></div>
+                    <div style="padding: 3px 12px; color: #858585; font-size: 11px;">"Save with Encoding"</div>
+                    {encoding_items(false)}
+                </div>
+            })}
         </div>
     }
 }
@@ -56,8 +187,63 @@ pub fn EditorAppTauri() -> impl IntoView {
     // File selection state (shared between FileTree and Editor)
     let selected_file = RwSignal::new(Option::<(String, String)>::None); // (path, content)
 
+    // Encoding and line ending of the active editor tab, and changes requested from the status bar
+    let active_format = RwSignal::new(Option::<TextFormat>::None);
+    let format_request = RwSignal::new(Option::<FormatChange>::None);
+
+    // Position to reveal in the editor: (path, line, column), 0-based
+    let reveal_position = RwSignal::new(Option::<(String, usize, usize)>::None);
+
     // Active panel state (Explorer or Search)
     let active_panel = RwSignal::new(ActivePanel::Explorer);
+    let git_view = RwSignal::new(GitView::Changes);
+
+    // Open a file at a 1-based line/column (e.g. a `path:line:col` link in the terminal)
+    let open_location = Callback::new(move |(path, line, column): (String, usize, usize)| {
+        leptos::task::spawn_local(async move {
+            match tauri_bindings::read_file(&path).await {
+                Ok(content) => {
+                    reveal_position.set(Some((path.clone(), line.saturating_sub(1), column.saturating_sub(1))));
+                    selected_file.set(Some((path, content)));
+                    // The terminal replaces the editor; other sidebar panels can stay open
+                    if active_panel.get_untracked() == ActivePanel::Terminal {
+                        active_panel.set(ActivePanel::Explorer);
+                    }
+                }
+                Err(e) => {
+                    leptos::logging::error!("❌ Failed to open {}: {}", path, e);
+                }
+            }
+        });
+    });
+
+    // Task runner state (shared by the Tasks panel, shortcuts and the Problems panel)
+    let task_runner = TaskRunner::new();
+
+    // Test explorer state (shared by the Testing panel and the editor gutter)
+    let test_explorer = TestExplorer::new();
+
+    // Git change markers and hunk peek for the file in the editor
+    let git_gutter = GitGutter::new();
+    let file_reloaded = RwSignal::new(Option::<(String, String)>::None);
+    let reload_file = Callback::new(move |path: String| {
+        leptos::task::spawn_local(async move {
+            if let Ok(content) = tauri_bindings::read_file(&path).await {
+                file_reloaded.set(Some((path, content)));
+            }
+        });
+    });
+
+    // Changes on disk reported by the workspace watcher
+    let fs_changes = RwSignal::new(Option::<FsChangeBatch>::None);
+    leptos::task::spawn_local(async move {
+        if let Err(e) = tauri_bindings_watcher::listen_fs_changes(move |batch| fs_changes.set(Some(batch))).await {
+            leptos::logging::warn!("File watcher events unavailable: {}", e);
+        }
+    });
+
+    // Session and unsaved buffers restored at startup and backed up while running
+    let hot_exit = HotExit::new();
 
     // Search panel state
     let search_is_open = RwSignal::new(true); // Always open when Search is active
@@ -66,27 +252,127 @@ pub fn EditorAppTauri() -> impl IntoView {
     let sidebar_width = RwSignal::new(300.0); // Default width in pixels
     let is_resizing = RwSignal::new(false);
 
-    // Get current directory dynamically from Tauri
-    // ✅ Start with empty path - will be populated by Effect
+    // Open folders; the first one is the project of the terminal, tasks and tests
+    // ✅ Start empty - populated from the last session or the current directory
     // In test environment, get_current_dir() will return "." due to is_tauri_context() check
-    let root_path = RwSignal::new(String::new());
+    let workspace = WorkspaceState::new();
+    let root_path = Memo::new(move |_| workspace.roots().into_iter().next().unwrap_or_default());
 
-    // Load current directory on mount
+    // Watch the workspace so open tabs and the file tree follow the disk
     Effect::new(move |_| {
+        let roots = workspace.roots();
+        if roots.is_empty() {
+            return;
+        }
         leptos::task::spawn_local(async move {
+            if let Err(e) = tauri_bindings_watcher::watch_workspace(roots).await {
+                leptos::logging::warn!("Failed to watch workspace: {}", e);
+            }
+        });
+    });
+
+    // Refresh git change markers when a file is opened (local folders only)
+    Effect::new(move |_| {
+        workspace.workspace.track();
+        if let Some((path, _)) = selected_file.get() {
+            if let Some(root) = workspace.root_for(&path).filter(|root| !tauri_bindings_remote::is_remote(root)) {
+                git_gutter.refresh(root, path);
+            }
+        }
+    });
+
+    // Folder the terminal runs in; the backend keeps one terminal per folder
+    let terminal_root = RwSignal::new(Option::<String>::None);
+    let terminal_path = Signal::derive(move || {
+        let roots = workspace.roots();
+        terminal_root
+            .get()
+            .filter(|root| roots.contains(root))
+            .unwrap_or_else(|| root_path.get())
+    });
+
+    // The terminal replaces the editor area once a workspace is open
+    let show_terminal = Memo::new(move |_| active_panel.get() == ActivePanel::Terminal && !root_path.get().is_empty());
+
+    // Keep the hot exit session's layout up to date
+    Effect::new(move |_| {
+        let root = root_path.get();
+        let workspace_file = workspace.file.get();
+        let folders = workspace.roots();
+        let panel = active_panel.get().id().to_string();
+        let width = sidebar_width.get();
+        hot_exit.layout.update(|layout| {
+            layout.root_path = root;
+            layout.workspace_file = workspace_file;
+            layout.folders = folders;
+            layout.active_panel = panel;
+            layout.sidebar_width = width;
+        });
+    });
+
+    // Restore the last session (layout, tabs and unsaved edits), else open the current directory
+    Effect::new(move |_| {
+        leptos::task::spawn_local(async move {
+            match tauri_bindings_hot_exit::restore_session().await {
+                Ok(Some(restored)) => {
+                    let layout = &restored.session;
+                    if let Some(panel) = ActivePanel::from_id(&layout.active_panel) {
+                        active_panel.set(panel);
+                    }
+                    if layout.sidebar_width > 0.0 {
+                        sidebar_width.set(layout.sidebar_width);
+                    }
+                    if let Some(file) = layout.workspace_file.clone() {
+                        workspace.open_file(file).await;
+                    }
+                    // Plain folders when there is no workspace file or it is gone
+                    if root_path.get_untracked().is_empty() {
+                        if !layout.folders.is_empty() {
+                            workspace.open_folders(layout.folders.clone());
+                        } else if !layout.root_path.is_empty() {
+                            workspace.open_folder(layout.root_path.clone());
+                        }
+                    }
+                    hot_exit.restored.set(Some(restored));
+                }
+                Ok(None) => {}
+                Err(e) => leptos::logging::warn!("Failed to restore session: {}", e),
+            }
+            hot_exit.ready.set(true);
+            if !root_path.get_untracked().is_empty() {
+                return;
+            }
+
             match tauri_bindings::get_current_dir().await {
                 Ok(path) => {
-                    root_path.set(path);
+                    workspace.open_folder(path);
                 }
                 Err(_e) => {
                     // Fallback to default path
                     leptos::logging::warn!("Failed to get current directory: {}", _e);
-                    root_path.set("/Users/kyosukeishizu/oracleberry/berrcode/gui-editor".to_string());
+                    workspace.open_folder("/Users/kyosukeishizu/oracleberry/berrcode/gui-editor".to_string());
                 }
             }
         });
     });
 
+    // ✅ Tasks: Ctrl/Cmd+Shift+B runs the default build task, Ctrl/Cmd+Shift+T the default test task
+    let _task_shortcuts = window_event_listener(leptos::ev::keydown, move |ev| {
+        if !(ev.ctrl_key() || ev.meta_key()) || !ev.shift_key() {
+            return;
+        }
+        let group = match ev.key().to_lowercase().as_str() {
+            "b" => TaskGroup::Build,
+            "t" => TaskGroup::Test,
+            _ => return,
+        };
+        ev.prevent_default();
+        let path = root_path.get_untracked();
+        if !path.is_empty() {
+            task_runner.run_default(path, group);
+        }
+    });
+
     // Resize handlers
     let is_hovering_resize = RwSignal::new(false);
 
@@ -167,6 +453,18 @@ pub fn EditorAppTauri() -> impl IntoView {
                         <i class="codicon codicon-search" style="font-size: 25px !important;"></i>
                     </div>
 
+                    // Source control icon
+                    <div
+                        on:click=move |_| active_panel.set(ActivePanel::SourceControl)
+                        style=move || format!(
+                            "cursor: pointer; font-size: 25px !important; color: {}; transition: color 0.2s; line-height: 25px;",
+                            if active_panel.get() == ActivePanel::SourceControl { "#FFFFFF" } else { "#858585" }
+                        )
+                        title="Source Control"
+                    >
+                        <i class="codicon codicon-source-control" style="font-size: 25px !important;"></i>
+                    </div>
+
                     // Chat (BerryCode AI) icon
                     <div
                         on:click=move |_| active_panel.set(ActivePanel::Chat)
@@ -203,6 +501,42 @@ pub fn EditorAppTauri() -> impl IntoView {
                         <i class="codicon codicon-symbol-event" style="font-size: 25px !important;"></i>
                     </div>
 
+                    // Tasks icon
+                    <div
+                        on:click=move |_| active_panel.set(ActivePanel::Tasks)
+                        style=move || format!(
+                            "cursor: pointer; font-size: 25px !important; color: {}; transition: color 0.2s; line-height: 25px;",
+                            if active_panel.get() == ActivePanel::Tasks { "#FFFFFF" } else { "#858585" }
+                        )
+                        title="Tasks"
+                    >
+                        <i class="codicon codicon-tools" style="font-size: 25px !important;"></i>
+                    </div>
+
+                    // Testing icon
+                    <div
+                        on:click=move |_| active_panel.set(ActivePanel::Testing)
+                        style=move || format!(
+                            "cursor: pointer; font-size: 25px !important; color: {}; transition: color 0.2s; line-height: 25px;",
+                            if active_panel.get() == ActivePanel::Testing { "#FFFFFF" } else { "#858585" }
+                        )
+                        title="Testing"
+                    >
+                        <i class="codicon codicon-beaker" style="font-size: 25px !important;"></i>
+                    </div>
+
+                    // Local history icon
+                    <div
+                        on:click=move |_| active_panel.set(ActivePanel::History)
+                        style=move || format!(
+                            "cursor: pointer; font-size: 25px !important; color: {}; transition: color 0.2s; line-height: 25px;",
+                            if active_panel.get() == ActivePanel::History { "#FFFFFF" } else { "#858585" }
+                        )
+                        title="Local History"
+                    >
+                        <i class="codicon codicon-history" style="font-size: 25px !important;"></i>
+                    </div>
+
                     // Terminal icon
                     <div
                         on:click=move |_| active_panel.set(ActivePanel::Terminal)
@@ -251,8 +585,16 @@ pub fn EditorAppTauri() -> impl IntoView {
                         match active_panel.get() {
                         ActivePanel::Explorer => {
                             if !path.is_empty() {
+                                let remove_folder = Callback::new(move |folder: String| workspace.remove_folder(&folder));
                                 view! {
-                                    <FileTreePanelTauri on_file_select=selected_file root_path=path.clone() />
+                                    <WorkspaceToolbar workspace=workspace />
+                                    <FileTreePanelTauri
+                                        on_file_select=selected_file
+                                        workspace=workspace.workspace.get()
+                                        on_remove_folder=remove_folder
+                                        fs_changes=Signal::from(fs_changes)
+                                        diagnostics=Signal::from(task_runner.diagnostics)
+                                    />
                                 }.into_any()
                             } else {
                                 view! {
@@ -266,14 +608,12 @@ pub fn EditorAppTauri() -> impl IntoView {
                         },
                         ActivePanel::Search => {
                             if !path.is_empty() {
-                                let path_clone = path.clone();
                                 view! {
                                     <SearchPanel
                                         is_open=search_is_open
-                                        root_path=path_clone
-                                        on_result_click=move |file_path: String, line: usize| {
-                                            leptos::logging::log!("Search result clicked: {} at line {}", file_path, line);
-                                            // TODO: Open file and jump to line
+                                        workspace=workspace.workspace.get()
+                                        on_result_click=move |file_path: String, line: usize, column: usize| {
+                                            open_location.run((file_path, line, column));
                                         }
                                     />
                                 }.into_any()
@@ -287,6 +627,40 @@ pub fn EditorAppTauri() -> impl IntoView {
                                 }.into_any()
                             }
                         },
+                        ActivePanel::SourceControl => {
+                            let tab = move |view: GitView, label: &'static str| view! {
+                                <button
+                                    class=move || if git_view.get() == view {
+                                        "berry-git-view-tab berry-git-view-tab-active"
+                                    } else {
+                                        "berry-git-view-tab"
+                                    }
+                                    on:click=move |_| git_view.set(view)
+                                >
+                                    {label}
+                                </button>
+                            };
+                            view! {
+                                <div class="berry-editor-sidebar" style="background: #252526; height: 100%; display: flex; flex-direction: column;">
+                                    <div class="berry-git-view-tabs">
+                                        {tab(GitView::Changes, "Changes")}
+                                        {tab(GitView::History, "History")}
+                                        {tab(GitView::Rebase, "Rebase")}
+                                    </div>
+                                    <div style="flex: 1; overflow-y: auto;">
+                                        {move || match git_view.get() {
+                                            GitView::Changes => view! {
+                                                <SourceControlPanel roots=Signal::derive(move || workspace.roots()) />
+                                            }.into_any(),
+                                            GitView::History => view! { <CommitHistoryPanel /> }.into_any(),
+                                            GitView::Rebase => view! {
+                                                <InteractiveRebase on_close=Callback::new(move |_| git_view.set(GitView::Changes)) />
+                                            }.into_any(),
+                                        }}
+                                    </div>
+                                </div>
+                            }.into_any()
+                        },
                         ActivePanel::Chat => {
                             view! {
                                 <BerryCodePanel project_path=Signal::derive(move || root_path.get()) />
@@ -302,6 +676,29 @@ pub fn EditorAppTauri() -> impl IntoView {
                                 <WorkflowPanel is_active=Signal::derive(move || active_panel.get() == ActivePanel::Workflow) />
                             }.into_any()
                         },
+                        ActivePanel::Tasks => {
+                            view! {
+                                <TasksPanel project_path=Signal::derive(move || root_path.get()) runner=task_runner />
+                            }.into_any()
+                        },
+                        ActivePanel::Testing => {
+                            view! {
+                                <TestExplorerPanel
+                                    project_path=Signal::derive(move || root_path.get())
+                                    explorer=test_explorer
+                                    on_open_location=open_location
+                                />
+                            }.into_any()
+                        },
+                        ActivePanel::History => {
+                            view! {
+                                <LocalHistoryPanel
+                                    workspace=workspace
+                                    active_file=Signal::derive(move || selected_file.with(|file| file.as_ref().map(|(path, _)| path.clone())))
+                                    on_open_location=open_location
+                                />
+                            }.into_any()
+                        },
                         ActivePanel::Terminal => {
                             // Terminal is shown in main area, hide sidebar
                             view! {
@@ -472,6 +869,33 @@ pub fn EditorAppTauri() -> impl IntoView {
                                             </div>
                                         </div>
 
+                                        // Files Settings
+                                        <div style="margin-bottom: 24px;">
+                                            <div style="font-weight: 600; margin-bottom: 12px; color: #FFFFFF;">
+                                                "Files"
+                                            </div>
+                                            <div style="display: flex; flex-direction: column; gap: 12px;">
+                                                // Exclude patterns
+                                                <div style="display: flex; justify-content: space-between; align-items: center; gap: 12px;">
+                                                    <span style="color: #BCBEC4;" title="Comma-separated, gitignore syntax">"Exclude"</span>
+                                                    <input
+                                                        type="text"
+                                                        prop:value=settings_store.get_value().files_exclude.join(", ")
+                                                        on:change=move |ev| {
+                                                            let patterns: Vec<String> = event_target_value(&ev)
+                                                                .split(',')
+                                                                .map(|p| p.trim().to_string())
+                                                                .filter(|p| !p.is_empty())
+                                                                .collect();
+                                                            settings_store.update_value(|s| s.files_exclude = patterns);
+                                                            save_settings();
+                                                        }
+                                                        style="flex: 1; min-width: 0; background: #3C3F41; border: 1px solid #555; color: #BCBEC4; padding: 4px; border-radius: 3px; font-size: 11px;"
+                                                    />
+                                                </div>
+                                            </div>
+                                        </div>
+
                                         // Theme Settings
                                         <div style="margin-bottom: 24px;">
                                             <div style="font-weight: 600; margin-bottom: 12px; color: #FFFFFF;">
@@ -625,28 +1049,66 @@ pub fn EditorAppTauri() -> impl IntoView {
 
                 // Main Editor Area with Virtual Scrolling (flex to fill remaining space)
                 <div style="display: flex; flex-direction: column; flex: 1; min-width: 0; min-height: 0; overflow: hidden;">
-                    {move || {
-                        let path = root_path.get();
-                        if active_panel.get() == ActivePanel::Terminal && !path.is_empty() {
-                            view! {
-                                <div style="display: flex; flex-direction: column; height: 100%;">
-                                    <TerminalPanel project_path=Signal::derive(move || root_path.get()) />
-                                </div>
-                            }.into_any()
-                        } else {
-                            view! {
-                                <VirtualEditorPanel
-                                    selected_file=selected_file
-                                    is_active=Signal::derive(move || active_panel.get() != ActivePanel::Terminal)
-                                />
-                            }.into_any()
-                        }
-                    }}
+                    {move || show_terminal.get().then(|| view! {
+                        <div style="display: flex; flex-direction: column; height: 100%;">
+                            {move || {
+                                let folders = workspace.folders();
+                                (folders.len() > 1).then(|| view! {
+                                    <select
+                                        class="berry-terminal-root"
+                                        title="Terminal Folder"
+                                        on:change=move |ev| terminal_root.set(Some(event_target_value(&ev)))
+                                    >
+                                        {folders.into_iter().map(|folder| {
+                                            let selected = folder.path == terminal_path.get_untracked();
+                                            view! {
+                                                <option value=folder.path.clone() selected=selected>{folder.display_name()}</option>
+                                            }
+                                        }).collect_view()}
+                                    </select>
+                                })
+                            }}
+                            <TerminalPanel
+                                project_path=terminal_path
+                                on_open_location=open_location
+                            />
+                        </div>
+                    })}
+                    // The editor stays mounted behind the terminal so its tabs survive
+                    <div style=move || if show_terminal.get() { "display: none;" } else { "display: contents;" }>
+                        <HunkPeek gutter=git_gutter on_reverted=reload_file />
+                        <VirtualEditorPanel
+                            selected_file=selected_file
+                            is_active=Signal::derive(move || !show_terminal.get())
+                            reveal_position=reveal_position
+                            task_diagnostics=task_runner.diagnostics
+                            on_open_location=open_location
+                            line_decorations=Signal::derive(move || {
+                                let mut decorations = git_gutter.line_decorations();
+                                decorations.extend(test_explorer.line_decorations());
+                                decorations
+                            })
+                            on_gutter_click=Callback::new(move |(file, line): (String, usize)| {
+                                if !test_explorer.run_at_line(root_path.get_untracked(), &file, line) {
+                                    git_gutter.toggle_peek(&file, line);
+                                }
+                            })
+                            file_reloaded=Signal::from(file_reloaded)
+                            fs_changes=Signal::from(fs_changes)
+                            active_format=active_format
+                            format_request=Signal::from(format_request)
+                            hot_exit=hot_exit
+                            workspace=workspace
+                        />
+                    </div>
                 </div>
             </div>
 
             // Status Bar at bottom
-            <StatusBar />
+            <StatusBar
+                file_format=Signal::from(active_format)
+                on_format_change=Callback::new(move |change| format_request.set(Some(change)))
+            />
         </div>
     }
 }
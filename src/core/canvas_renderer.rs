@@ -497,6 +497,21 @@ impl CanvasRenderer {
         let _ = self.context.fill_text(text, x_rounded, y_rounded);
     }
 
+    /// ガター左端に行デコレーションのアイコンを描画
+    pub fn draw_gutter_glyph(&self, y_offset: f64, glyph: &str, color: &str) {
+        self.context.set_fill_style(&color.into());
+        let _ = self.context.fill_text(glyph, 4.0, (y_offset + 15.0).round());
+    }
+
+    /// 行末の後ろにインラインメッセージを描画（テスト失敗メッセージなど）
+    pub fn draw_inline_message(&self, y_offset: f64, line_text: &str, message: &str, color: &str) {
+        let x = (self.gutter_width + 15.0 + self.calculate_x_offset_from_text(line_text, line_text.chars().count()) + 24.0).round();
+        // 複数行メッセージは1行目のみ表示
+        let first_line = message.lines().next().unwrap_or_default();
+        self.context.set_fill_style(&color.into());
+        let _ = self.context.fill_text(first_line, x, (y_offset + 15.0).round());
+    }
+
     /// カーソルを描画（縦線）
     /// line_text: カーソルがある行のテキスト全体
     pub fn draw_cursor(&self, line: usize, col: usize, scroll_top: f64, line_text: &str) {
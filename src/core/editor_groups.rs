@@ -0,0 +1,525 @@
+//! Editor groups: split panes with tabs of their own
+//!
+//! The editor area is a tree of horizontal and vertical splits whose leaves
+//! are groups. Each group has its own tabs and, for each tab, its own cursor,
+//! selection and scroll position. Documents are not owned by groups: a file
+//! open in two groups is one buffer, so edits in one pane show up in the other.
+
+use crate::tauri_bindings_hot_exit::{SessionGroup, SessionGroups, SessionTab};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub type GroupId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Groups side by side
+    Horizontal,
+    /// Groups stacked on top of each other
+    Vertical,
+}
+
+/// Arrangement of the groups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EditorLayout {
+    Group { id: GroupId },
+    Split { orientation: Orientation, children: Vec<EditorLayout> },
+}
+
+impl EditorLayout {
+    /// Group IDs in reading order (left to right, top to bottom)
+    pub fn group_ids(&self) -> Vec<GroupId> {
+        match self {
+            EditorLayout::Group { id } => vec![*id],
+            EditorLayout::Split { children, .. } => children.iter().flat_map(EditorLayout::group_ids).collect(),
+        }
+    }
+
+    /// Put group `new` after `target`. A split of the same orientation gets
+    /// another child instead of nesting a new split.
+    fn split(&mut self, target: GroupId, orientation: Orientation, new: GroupId) -> bool {
+        match self {
+            EditorLayout::Group { id } if *id == target => {
+                *self = EditorLayout::Split {
+                    orientation,
+                    children: vec![EditorLayout::Group { id: target }, EditorLayout::Group { id: new }],
+                };
+                true
+            }
+            EditorLayout::Group { .. } => false,
+            EditorLayout::Split { orientation: own, children } => {
+                if *own == orientation {
+                    if let Some(i) = children.iter().position(|c| *c == EditorLayout::Group { id: target }) {
+                        children.insert(i + 1, EditorLayout::Group { id: new });
+                        return true;
+                    }
+                }
+                children.iter_mut().any(|child| child.split(target, orientation, new))
+            }
+        }
+    }
+
+    /// Remove a group; splits left with one child are replaced by it
+    fn remove(&mut self, target: GroupId) {
+        let EditorLayout::Split { orientation, children } = self else {
+            return;
+        };
+        children.retain(|child| *child != EditorLayout::Group { id: target });
+        for child in children.iter_mut() {
+            child.remove(target);
+        }
+        children.retain(|child| !matches!(child, EditorLayout::Split { children, .. } if children.is_empty()));
+        // A child split of the same orientation merges into this one
+        let mut merged = Vec::new();
+        for child in children.drain(..) {
+            match child {
+                EditorLayout::Split { orientation: inner, children: inner_children } if inner == *orientation => {
+                    merged.extend(inner_children)
+                }
+                child => merged.push(child),
+            }
+        }
+        *children = merged;
+        if children.len() == 1 {
+            *self = children.remove(0);
+        }
+    }
+}
+
+/// Cursor, selection and scroll position of a tab in one group
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TabView {
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub selection_start: Option<(usize, usize)>,
+    pub selection_end: Option<(usize, usize)>,
+    pub scroll_top: f64,
+}
+
+impl TabView {
+    fn from_session(tab: &SessionTab) -> Self {
+        Self {
+            cursor_line: tab.cursor_line,
+            cursor_col: tab.cursor_col,
+            scroll_top: tab.scroll_top,
+            ..Self::default()
+        }
+    }
+
+    fn to_session(&self, path: &str) -> SessionTab {
+        SessionTab {
+            path: path.to_string(),
+            cursor_line: self.cursor_line,
+            cursor_col: self.cursor_col,
+            scroll_top: self.scroll_top,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditorGroup {
+    pub id: GroupId,
+    /// Paths of the open tabs, in tab order
+    pub tabs: Vec<String>,
+    pub active: Option<String>,
+    pub views: HashMap<String, TabView>,
+}
+
+impl EditorGroup {
+    fn new(id: GroupId) -> Self {
+        Self {
+            id,
+            ..Self::default()
+        }
+    }
+
+    fn add_tab(&mut self, path: &str) {
+        if !self.tabs.iter().any(|tab| tab == path) {
+            // New tabs open next to the active one
+            let index = self
+                .active
+                .as_ref()
+                .and_then(|active| self.tabs.iter().position(|tab| tab == active))
+                .map_or(self.tabs.len(), |i| i + 1);
+            self.tabs.insert(index, path.to_string());
+        }
+    }
+
+    /// Close a tab; the tab before it (or the new first tab) becomes active
+    fn remove_tab(&mut self, path: &str) -> Option<TabView> {
+        let index = self.tabs.iter().position(|tab| tab == path)?;
+        self.tabs.remove(index);
+        if self.active.as_deref() == Some(path) {
+            self.active = self.tabs.get(index.saturating_sub(1)).cloned();
+        }
+        Some(self.views.remove(path).unwrap_or_default())
+    }
+}
+
+/// The groups of the editor area and which one has the keyboard focus
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorGroups {
+    pub layout: EditorLayout,
+    pub groups: Vec<EditorGroup>,
+    pub focused: GroupId,
+    next_id: GroupId,
+}
+
+impl Default for EditorGroups {
+    fn default() -> Self {
+        Self {
+            layout: EditorLayout::Group { id: 0 },
+            groups: vec![EditorGroup::new(0)],
+            focused: 0,
+            next_id: 1,
+        }
+    }
+}
+
+impl EditorGroups {
+    pub fn group(&self, id: GroupId) -> Option<&EditorGroup> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    fn group_mut(&mut self, id: GroupId) -> Option<&mut EditorGroup> {
+        self.groups.iter_mut().find(|group| group.id == id)
+    }
+
+    /// Active tab of the focused group
+    pub fn active_path(&self) -> Option<&str> {
+        self.group(self.focused)?.active.as_deref()
+    }
+
+    /// Whether any group has a tab for `path`
+    pub fn contains(&self, path: &str) -> bool {
+        self.groups.iter().any(|group| group.tabs.iter().any(|tab| tab == path))
+    }
+
+    /// The group to show `path` in: the focused one if it has the tab, else
+    /// the first group that has it, else the focused one
+    pub fn group_for(&self, path: &str) -> GroupId {
+        let has = |id: GroupId| self.group(id).is_some_and(|g| g.tabs.iter().any(|tab| tab == path));
+        if has(self.focused) {
+            return self.focused;
+        }
+        self.layout.group_ids().into_iter().find(|id| has(*id)).unwrap_or(self.focused)
+    }
+
+    pub fn view(&self, group: GroupId, path: &str) -> Option<&TabView> {
+        self.group(group)?.views.get(path)
+    }
+
+    /// View of a tab in a group, created at the top of the file if missing
+    pub fn view_mut(&mut self, group: GroupId, path: &str) -> Option<&mut TabView> {
+        Some(self.group_mut(group)?.views.entry(path.to_string()).or_default())
+    }
+
+    pub fn set_view(&mut self, group: GroupId, path: &str, view: TabView) {
+        if let Some(group) = self.group_mut(group) {
+            group.views.insert(path.to_string(), view);
+        }
+    }
+
+    pub fn focus(&mut self, id: GroupId) -> bool {
+        if self.group(id).is_none() {
+            return false;
+        }
+        self.focused = id;
+        true
+    }
+
+    /// Focus the `n`th group in reading order (0-based)
+    pub fn focus_nth(&mut self, n: usize) -> bool {
+        match self.layout.group_ids().get(n) {
+            Some(&id) => self.focus(id),
+            None => false,
+        }
+    }
+
+    /// Open `path` in a group (as a new tab if needed) and make it active
+    pub fn open(&mut self, group: GroupId, path: &str) {
+        if let Some(group) = self.group_mut(group) {
+            group.add_tab(path);
+            group.active = Some(path.to_string());
+        }
+    }
+
+    /// Add tabs for paths no group shows, e.g. tabs restored without a layout
+    pub fn adopt<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let missing: Vec<&str> = paths.into_iter().filter(|path| !self.contains(path)).collect();
+        let focused = self.focused;
+        if let Some(group) = self.group_mut(focused) {
+            for path in missing {
+                group.tabs.push(path.to_string());
+            }
+            if group.active.is_none() {
+                group.active = group.tabs.first().cloned();
+            }
+        }
+    }
+
+    /// Split the focused group. The new group opens the same tab with the
+    /// same view, gets the focus and its ID is returned. A group without
+    /// tabs is not split.
+    pub fn split(&mut self, orientation: Orientation) -> Option<GroupId> {
+        let source = self.group(self.focused)?;
+        let path = source.active.clone()?;
+        let view = source.views.get(&path).cloned().unwrap_or_default();
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut group = EditorGroup::new(id);
+        group.tabs.push(path.clone());
+        group.views.insert(path.clone(), view);
+        group.active = Some(path);
+        let index = self
+            .groups
+            .iter()
+            .position(|g| g.id == self.focused)
+            .map_or(self.groups.len(), |i| i + 1);
+        self.groups.insert(index, group);
+        self.layout.split(self.focused, orientation, id);
+        self.focused = id;
+        Some(id)
+    }
+
+    /// Close a tab of a group. An emptied group is removed unless it is the last one.
+    pub fn close_tab(&mut self, group: GroupId, path: &str) {
+        let Some(target) = self.group_mut(group) else {
+            return;
+        };
+        target.remove_tab(path);
+        if target.tabs.is_empty() {
+            self.remove_group(group);
+        }
+    }
+
+    /// Move a tab to another group (e.g. dropped on it), keeping its view
+    pub fn move_tab(&mut self, path: &str, from: GroupId, to: GroupId) {
+        if from == to || self.group(to).is_none() {
+            return;
+        }
+        let Some(view) = self.group_mut(from).and_then(|group| group.remove_tab(path)) else {
+            return;
+        };
+        if let Some(target) = self.group_mut(to) {
+            target.add_tab(path);
+            target.views.entry(path.to_string()).or_insert(view);
+            target.active = Some(path.to_string());
+        }
+        self.focused = to;
+        if self.group(from).is_some_and(|group| group.tabs.is_empty()) {
+            self.remove_group(from);
+        }
+    }
+
+    fn remove_group(&mut self, id: GroupId) {
+        if self.groups.len() <= 1 {
+            return;
+        }
+        // Focus moves to the group before the removed one in reading order
+        let order = self.layout.group_ids();
+        let position = order.iter().position(|g| *g == id).unwrap_or(0);
+        self.groups.retain(|group| group.id != id);
+        self.layout.remove(id);
+        if self.focused == id {
+            let order = self.layout.group_ids();
+            self.focused = order[position.saturating_sub(1).min(order.len() - 1)];
+        }
+    }
+
+    /// A tab was saved under a new path (e.g. an untitled tab)
+    pub fn rename(&mut self, old: &str, new: &str) {
+        for group in &mut self.groups {
+            for tab in group.tabs.iter_mut().filter(|tab| *tab == old) {
+                *tab = new.to_string();
+            }
+            if group.active.as_deref() == Some(old) {
+                group.active = Some(new.to_string());
+            }
+            if let Some(view) = group.views.remove(old) {
+                group.views.insert(new.to_string(), view);
+            }
+        }
+    }
+
+    /// Layout and tabs for the session. `live` is the view of the focused
+    /// tab, which the editor keeps up to date outside of the groups.
+    pub fn to_session(&self, live: Option<&TabView>) -> SessionGroups {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| SessionGroup {
+                id: group.id,
+                tabs: group
+                    .tabs
+                    .iter()
+                    .map(|path| {
+                        let is_live = group.id == self.focused && group.active.as_ref() == Some(path);
+                        let view = live.filter(|_| is_live).or_else(|| group.views.get(path));
+                        view.cloned().unwrap_or_default().to_session(path)
+                    })
+                    .collect(),
+                active: group.active.clone(),
+            })
+            .collect();
+        SessionGroups {
+            layout: self.layout.clone(),
+            groups,
+            focused: self.focused,
+        }
+    }
+
+    /// Groups of a saved session, without tabs for which `is_open` is false.
+    /// Groups left without tabs are dropped.
+    pub fn from_session(session: &SessionGroups, is_open: impl Fn(&str) -> bool) -> Self {
+        let mut groups: Vec<EditorGroup> = session
+            .groups
+            .iter()
+            .map(|saved| {
+                let mut group = EditorGroup::new(saved.id);
+                for tab in saved.tabs.iter().filter(|tab| is_open(&tab.path)) {
+                    group.add_tab(&tab.path);
+                    group.views.insert(tab.path.clone(), TabView::from_session(tab));
+                }
+                group.active = saved
+                    .active
+                    .clone()
+                    .filter(|path| group.tabs.contains(path))
+                    .or_else(|| group.tabs.first().cloned());
+                group
+            })
+            .collect();
+        let in_layout = session.layout.group_ids();
+        groups.retain(|group| !group.tabs.is_empty() && in_layout.contains(&group.id));
+        if groups.is_empty() {
+            return Self::default();
+        }
+
+        let mut layout = session.layout.clone();
+        for id in in_layout {
+            if !groups.iter().any(|group| group.id == id) {
+                layout.remove(id);
+            }
+        }
+        let next_id = groups.iter().map(|group| group.id).max().unwrap_or(0) + 1;
+        let focused = if groups.iter().any(|group| group.id == session.focused) {
+            session.focused
+        } else {
+            layout.group_ids()[0]
+        };
+        Self {
+            layout,
+            groups,
+            focused,
+            next_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tabs(groups: &EditorGroups, id: GroupId) -> Vec<&str> {
+        groups.group(id).unwrap().tabs.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_split_and_close() {
+        let mut groups = EditorGroups::default();
+        assert_eq!(groups.split(Orientation::Horizontal), None);
+        groups.open(0, "/a.rs");
+        groups.open(0, "/b.rs");
+        groups.set_view(0, "/b.rs", TabView { cursor_line: 7, ..TabView::default() });
+
+        let right = groups.split(Orientation::Horizontal).unwrap();
+        assert_eq!(groups.focused, right);
+        assert_eq!(tabs(&groups, right), vec!["/b.rs"]);
+        assert_eq!(groups.view(right, "/b.rs").unwrap().cursor_line, 7);
+
+        let below = groups.split(Orientation::Vertical).unwrap();
+        groups.focus(0);
+        let far_right = groups.split(Orientation::Horizontal).unwrap();
+        assert_eq!(groups.layout.group_ids(), vec![0, far_right, right, below]);
+        assert_eq!(
+            groups.layout,
+            EditorLayout::Split {
+                orientation: Orientation::Horizontal,
+                children: vec![
+                    EditorLayout::Group { id: 0 },
+                    EditorLayout::Group { id: far_right },
+                    EditorLayout::Split {
+                        orientation: Orientation::Vertical,
+                        children: vec![EditorLayout::Group { id: right }, EditorLayout::Group { id: below }],
+                    },
+                ],
+            }
+        );
+
+        // Closing the last tab of a group removes it and focuses the previous group
+        groups.focus(below);
+        groups.close_tab(below, "/b.rs");
+        assert!(groups.group(below).is_none());
+        assert_eq!(groups.focused, right);
+        assert_eq!(groups.layout.group_ids(), vec![0, far_right, right]);
+        assert!(matches!(&groups.layout, EditorLayout::Split { children, .. } if children.len() == 3));
+
+        groups.close_tab(0, "/b.rs");
+        assert_eq!(groups.group(0).unwrap().active.as_deref(), Some("/a.rs"));
+        assert!(groups.contains("/b.rs"));
+        assert!(groups.focus_nth(1));
+        assert_eq!(groups.focused, far_right);
+    }
+
+    #[test]
+    fn test_move_tab_between_groups() {
+        let mut groups = EditorGroups::default();
+        groups.open(0, "/a.rs");
+        groups.open(0, "/b.rs");
+        groups.set_view(0, "/b.rs", TabView { cursor_line: 3, ..TabView::default() });
+        let right = groups.split(Orientation::Horizontal).unwrap();
+        groups.open(right, "/c.rs");
+        assert_eq!(tabs(&groups, right), vec!["/b.rs", "/c.rs"]);
+
+        groups.move_tab("/a.rs", 0, right);
+        assert_eq!(tabs(&groups, 0), vec!["/b.rs"]);
+        assert_eq!(tabs(&groups, right), vec!["/b.rs", "/c.rs", "/a.rs"]);
+        assert_eq!((groups.focused, groups.active_path()), (right, Some("/a.rs")));
+
+        // The last tab moving out removes its group
+        groups.move_tab("/b.rs", 0, right);
+        assert_eq!(groups.layout, EditorLayout::Group { id: right });
+        assert_eq!(groups.group_for("/b.rs"), right);
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        let mut groups = EditorGroups::default();
+        groups.open(0, "/a.rs");
+        let right = groups.split(Orientation::Vertical).unwrap();
+        groups.open(right, "/gone.rs");
+        groups.open(right, "/b.rs");
+        groups.rename("/b.rs", "/c.rs");
+        let live = TabView { cursor_line: 4, scroll_top: 20.0, ..TabView::default() };
+
+        let session = groups.to_session(Some(&live));
+        let json = serde_json::to_string(&session).unwrap();
+        let session: SessionGroups = serde_json::from_str(&json).unwrap();
+        let restored = EditorGroups::from_session(&session, |path| path != "/gone.rs");
+        assert_eq!(restored.layout, groups.layout);
+        assert_eq!(tabs(&restored, right), vec!["/a.rs", "/c.rs"]);
+        assert_eq!(restored.active_path(), Some("/c.rs"));
+        assert_eq!(restored.view(right, "/c.rs").unwrap().cursor_line, 4);
+
+        // Groups without open tabs disappear
+        let restored = EditorGroups::from_session(&session, |_| false);
+        assert_eq!(restored, EditorGroups::default());
+        let only_first = EditorGroups::from_session(&session, |path| path == "/a.rs");
+        assert_eq!(only_first.layout.group_ids().len(), 2);
+        let mut adopted = EditorGroups::default();
+        adopted.adopt(["/x.rs", "/y.rs"]);
+        assert_eq!(adopted.active_path(), Some("/x.rs"));
+    }
+}
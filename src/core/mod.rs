@@ -1,3 +1,5 @@
 pub mod virtual_editor;
 pub mod bridge;
 pub mod canvas_renderer;
+pub mod editor_groups;
+pub mod smart_edit;
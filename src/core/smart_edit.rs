@@ -0,0 +1,496 @@
+//! Syntax-aware editing commands
+//!
+//! Text transforms behind the editor's smart editing: comment toggling,
+//! moving lines and statements, auto-closed pairs, surrounding a selection
+//! and indentation on Enter and Tab. Each returns a [`TextChange`] that the
+//! editor applies to its buffer; the structure comes from
+//! [`crate::tree_sitter_engine`].
+
+use crate::tree_sitter_engine::{LanguageSyntax, ParsedText};
+
+/// Replacement of the chars `start..end` and the selection afterwards as
+/// (anchor, head). Line-local commands use offsets from the line start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChange {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub selection: (usize, usize),
+}
+
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+fn closer_for(open: char, syntax: &LanguageSyntax) -> Option<char> {
+    PAIRS
+        .iter()
+        .find(|(o, _)| *o == open)
+        .map(|(_, c)| *c)
+        .or_else(|| syntax.quotes.contains(&open).then_some(open))
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Indentation used by the file: a tab, or the smallest step of spaces;
+/// `fallback` (from the settings) when no line is indented
+pub fn indent_unit(text: &str, fallback: &str) -> String {
+    let mut smallest = None;
+    for line in text.lines().filter(|l| !l.trim().is_empty()).take(500) {
+        if line.starts_with('\t') {
+            return "\t".to_string();
+        }
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        if spaces >= 2 {
+            smallest = Some(smallest.map_or(spaces, |s: usize| s.min(spaces)));
+        }
+    }
+    smallest.map_or_else(|| fallback.to_string(), |spaces| " ".repeat(spaces.min(8)))
+}
+
+/// Chars Shift+Tab removes from the start of `line`
+pub fn outdent_width(line: &str, unit: &str) -> usize {
+    if line.starts_with('\t') {
+        return 1;
+    }
+    let spaces = line.chars().take_while(|&c| c == ' ').count();
+    spaces.min(unit.chars().count().max(1))
+}
+
+/// Whitespace Tab inserts at `col`: up to the next indent stop
+pub fn tab_text(col: usize, unit: &str) -> String {
+    if unit == "\t" {
+        return unit.to_string();
+    }
+    let width = unit.len().max(1);
+    " ".repeat(width - col % width)
+}
+
+/// Enter at `col` of `line`: keeps the indentation, indents after an opening
+/// bracket (or `:` in Python) and puts a closing bracket right after the
+/// cursor on its own line
+pub fn newline(line: &str, col: usize, syntax: &LanguageSyntax, unit: &str) -> TextChange {
+    let byte = line.char_indices().nth(col).map_or(line.len(), |(i, _)| i);
+    let (before, after) = line.split_at(byte);
+    let indent = leading_whitespace(before).to_string();
+    let before_code = before.trim_end();
+
+    let opens = before_code.chars().last().is_some_and(|c| PAIRS.iter().any(|(o, _)| *o == c))
+        || (syntax.colon_blocks && before_code.ends_with(':'));
+    let closes = match (before_code.chars().last(), after.trim_start().chars().next()) {
+        (Some(open), Some(close)) => PAIRS.contains(&(open, close)),
+        _ => false,
+    };
+    let ends_block = syntax.colon_blocks
+        && ["return", "pass", "break", "continue", "raise"]
+            .iter()
+            .any(|kw| before_code.split_whitespace().next() == Some(kw));
+
+    let inner = if opens {
+        format!("{}{}", indent, unit)
+    } else if ends_block {
+        indent.strip_suffix(unit).unwrap_or(&indent).to_string()
+    } else {
+        indent.clone()
+    };
+    let mut text = format!("\n{}", inner);
+    let cursor = col + text.chars().count();
+    if closes {
+        text.push('\n');
+        text.push_str(&indent);
+    }
+    // Whitespace after the cursor would end up before the closing bracket
+    let skipped = after.chars().count() - after.trim_start().chars().count();
+    TextChange {
+        start: col,
+        end: col + skipped,
+        text,
+        selection: (cursor, cursor),
+    }
+}
+
+/// Typing `ch` at `col` of `line` with nothing selected: closes brackets and
+/// quotes, steps over a closing char that is already there and outdents a
+/// closing bracket typed on a blank line. `None` inserts `ch` as is.
+pub fn type_char(line: &str, col: usize, ch: char, syntax: &LanguageSyntax, unit: &str) -> Option<TextChange> {
+    let chars: Vec<char> = line.chars().collect();
+    let next = chars.get(col).copied();
+    let prev = col.checked_sub(1).and_then(|i| chars.get(i)).copied();
+    let is_closer = PAIRS.iter().any(|(_, c)| *c == ch);
+
+    if (is_closer || syntax.quotes.contains(&ch)) && next == Some(ch) {
+        return Some(TextChange {
+            start: col,
+            end: col,
+            text: String::new(),
+            selection: (col + 1, col + 1),
+        });
+    }
+
+    if is_closer && chars[..col].iter().all(|c| c.is_whitespace()) && col > 0 {
+        let indent: String = chars[..col].iter().collect();
+        let outdented = indent.strip_suffix(unit).unwrap_or(&indent).to_string();
+        let cursor = outdented.chars().count() + 1;
+        return Some(TextChange {
+            start: 0,
+            end: col,
+            text: format!("{}{}", outdented, ch),
+            selection: (cursor, cursor),
+        });
+    }
+
+    let close = closer_for(ch, syntax)?;
+    // Only before whitespace, a closing char or the line end
+    let free_after = next.is_none_or(|c| c.is_whitespace() || PAIRS.iter().any(|(_, cl)| *cl == c) || c == ',' || c == ';');
+    if !free_after {
+        return None;
+    }
+    // A quote right after a word is an apostrophe or a closing quote
+    if syntax.quotes.contains(&ch) && prev.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == ch) {
+        return None;
+    }
+    Some(TextChange {
+        start: col,
+        end: col,
+        text: format!("{}{}", ch, close),
+        selection: (col + 1, col + 1),
+    })
+}
+
+/// Typing an opening bracket or quote over a selection wraps it
+pub fn surround(selected: &str, ch: char, syntax: &LanguageSyntax) -> Option<String> {
+    if PAIRS.iter().any(|(_, c)| *c == ch) {
+        return None;
+    }
+    let close = closer_for(ch, syntax)?;
+    Some(format!("{}{}{}", ch, selected, close))
+}
+
+/// Backspace at `col`: removes an empty pair around the cursor, or one
+/// indent step inside leading whitespace
+pub fn backspace(line: &str, col: usize, syntax: &LanguageSyntax, unit: &str) -> Option<TextChange> {
+    let chars: Vec<char> = line.chars().collect();
+    let prev = *chars.get(col.checked_sub(1)?)?;
+    if let (Some(close), Some(&next)) = (closer_for(prev, syntax), chars.get(col)) {
+        if close == next {
+            return Some(TextChange {
+                start: col - 1,
+                end: col + 1,
+                text: String::new(),
+                selection: (col - 1, col - 1),
+            });
+        }
+    }
+    if unit != "\t" && col > 1 && chars[..col].iter().all(|&c| c == ' ') {
+        let width = unit.len();
+        let remove = match col % width {
+            0 => width,
+            rest => rest,
+        };
+        return Some(TextChange {
+            start: col - remove,
+            end: col,
+            text: String::new(),
+            selection: (col - remove, col - remove),
+        });
+    }
+    None
+}
+
+/// Line index and line start of every line
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.chars().count() + 1;
+            (start, line)
+        })
+        .collect()
+}
+
+/// Lines touched by `start..end`; a selection ending at a line start leaves
+/// that line out
+fn selected_lines(lines: &[(usize, &str)], start: usize, end: usize) -> (usize, usize) {
+    let line_of = |offset: usize| lines.partition_point(|(s, _)| *s <= offset).saturating_sub(1);
+    let first = line_of(start);
+    let mut last = line_of(end);
+    if end > start && last > first && lines[last].0 == end {
+        last -= 1;
+    }
+    (first, last)
+}
+
+/// Toggle the line comment token on the lines of `start..end` (char
+/// offsets in `text`); languages without line comments use block comments
+pub fn toggle_line_comment(text: &str, syntax: &LanguageSyntax, start: usize, end: usize) -> Option<TextChange> {
+    let Some(token) = syntax.line_comment else {
+        let lines = lines_with_offsets(text);
+        let (first, last) = selected_lines(&lines, start, end);
+        let (first_start, first_line) = lines[first];
+        let (last_start, last_line) = lines[last];
+        let from = first_start + leading_whitespace(first_line).chars().count();
+        let to = last_start + last_line.trim_end().chars().count();
+        return toggle_block_comment(text, syntax, from, to.max(from));
+    };
+    let token_len = token.chars().count();
+    let lines = lines_with_offsets(text);
+    let (first, last) = selected_lines(&lines, start, end);
+    let range = &lines[first..=last];
+    let code_lines = || range.iter().filter(|(_, l)| !l.trim().is_empty());
+    let commented = code_lines().count() > 0 && code_lines().all(|(_, l)| l.trim_start().starts_with(token));
+    let column = code_lines()
+        .map(|(_, l)| leading_whitespace(l).chars().count())
+        .min()
+        .unwrap_or(0);
+
+    // Per line: (column of the edit, chars inserted or removed there)
+    let mut edits: Vec<(usize, isize)> = Vec::new();
+    let mut new_lines = Vec::new();
+    for (_, line) in range {
+        if line.trim().is_empty() {
+            edits.push((0, 0));
+            new_lines.push(line.to_string());
+        } else if commented {
+            let indent = leading_whitespace(line);
+            let rest = &line[indent.len() + token.len()..];
+            let removed = token_len + usize::from(rest.starts_with(' '));
+            edits.push((indent.chars().count(), -(removed as isize)));
+            new_lines.push(format!("{}{}", indent, rest.strip_prefix(' ').unwrap_or(rest)));
+        } else {
+            let split = line.char_indices().nth(column).map_or(line.len(), |(i, _)| i);
+            edits.push((column, token_len as isize + 1));
+            new_lines.push(format!("{}{} {}", &line[..split], token, &line[split..]));
+        }
+    }
+
+    let map = |offset: usize| -> usize {
+        let mut shift = 0isize;
+        for ((line_start, line), (col, delta)) in range.iter().zip(&edits) {
+            let line_end = line_start + line.chars().count();
+            if offset > line_end {
+                shift += delta;
+                continue;
+            }
+            let in_line = offset.saturating_sub(*line_start);
+            if *delta > 0 && in_line >= *col {
+                shift += delta;
+            } else if *delta < 0 && in_line > *col {
+                shift -= (in_line - col).min(delta.unsigned_abs()) as isize;
+            }
+            break;
+        }
+        (offset as isize + shift).max(0) as usize
+    };
+
+    let from = range[0].0;
+    let to = range[range.len() - 1].0 + range[range.len() - 1].1.chars().count();
+    Some(TextChange {
+        start: from,
+        end: to,
+        text: new_lines.join("\n"),
+        selection: (map(start), map(end)),
+    })
+}
+
+/// Wrap `start..end` in the block comment tokens, or unwrap it. Without a
+/// selection the cursor's line is used; languages without block comments
+/// comment out the lines instead.
+pub fn toggle_block_comment(text: &str, syntax: &LanguageSyntax, start: usize, end: usize) -> Option<TextChange> {
+    let Some((open, close)) = syntax.block_comment else {
+        return syntax.line_comment.and_then(|_| toggle_line_comment(text, syntax, start, end));
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let (mut start, mut end) = (start, end);
+    if start == end {
+        let line_start = chars[..start].iter().rposition(|&c| c == '\n').map_or(0, |p| p + 1);
+        let line_end = chars[start..].iter().position(|&c| c == '\n').map_or(chars.len(), |p| start + p);
+        start = line_start + chars[line_start..line_end].iter().take_while(|c| c.is_whitespace()).count();
+        end = line_end - chars[start..line_end].iter().rev().take_while(|c| c.is_whitespace()).count();
+    }
+    let selected: String = chars[start..end].iter().collect();
+    let (open_len, close_len) = (open.chars().count(), close.chars().count());
+
+    if let Some(body) = selected.strip_prefix(open).and_then(|s| s.strip_suffix(close)) {
+        let body = body.strip_prefix(' ').unwrap_or(body);
+        let body = body.strip_suffix(' ').unwrap_or(body);
+        let body_len = body.chars().count();
+        return Some(TextChange {
+            start,
+            end,
+            text: body.to_string(),
+            selection: (start, start + body_len),
+        });
+    }
+    let wrapped = format!("{} {} {}", open, selected, close);
+    let inner_start = start + open_len + 1;
+    let inner_end = inner_start + end - start;
+    debug_assert_eq!(wrapped.chars().count(), inner_end + 1 + close_len - start);
+    Some(TextChange {
+        start,
+        end,
+        text: wrapped,
+        selection: (inner_start, inner_end),
+    })
+}
+
+/// Move the lines of `start..end` one statement up or down. A line that
+/// opens a bracket moves together with the lines up to its closing line,
+/// and steps over whole statements of the same kind.
+pub fn move_lines(parsed: &ParsedText, start: usize, end: usize, up: bool) -> Option<TextChange> {
+    let lines = lines_with_offsets(parsed.text());
+    let (first, selected_last) = selected_lines(&lines, start, end);
+    let last = (first..=selected_last)
+        .map(|line| parsed.statement_lines(line).1)
+        .fold(selected_last, usize::max)
+        .min(lines.len() - 1);
+
+    let (neighbor_first, neighbor_last) = if up {
+        if first == 0 {
+            return None;
+        }
+        parsed.statement_ending_at(first - 1)
+    } else {
+        if last + 1 >= lines.len() {
+            return None;
+        }
+        let (from, to) = parsed.statement_lines(last + 1);
+        (from, to.min(lines.len() - 1))
+    };
+
+    let joined = |from: usize, to: usize| -> String {
+        lines[from..=to].iter().map(|(_, l)| *l).collect::<Vec<_>>().join("\n")
+    };
+    let block = joined(first, last);
+    let neighbor = joined(neighbor_first, neighbor_last);
+    let neighbor_len = neighbor.chars().count() + 1;
+
+    let (region_first, region_last, moved, shift) = if up {
+        (neighbor_first, last, format!("{}\n{}", block, neighbor), -(neighbor_len as isize))
+    } else {
+        (first, neighbor_last, format!("{}\n{}", neighbor, block), neighbor_len as isize)
+    };
+    let region_start = lines[region_first].0;
+    let region_end = lines[region_last].0 + lines[region_last].1.chars().count();
+    let shifted = |offset: usize| (offset as isize + shift) as usize;
+    Some(TextChange {
+        start: region_start,
+        end: region_end,
+        text: moved,
+        selection: (shifted(start), shifted(end)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(text: &str, change: &TextChange) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out: String = chars[..change.start].iter().collect();
+        out.push_str(&change.text);
+        out.extend(&chars[change.end..]);
+        out
+    }
+
+    #[test]
+    fn test_newline_indent() {
+        let rust = LanguageSyntax::for_path("a.rs");
+        let change = newline("    fn a() {}", 12, &rust, "    ");
+        assert_eq!(apply("    fn a() {}", &change), "    fn a() {\n        \n    }");
+        assert_eq!(change.selection, (21, 21));
+
+        let plain = newline("    let x = 1;", 14, &rust, "    ");
+        assert_eq!(plain.text, "\n    ");
+
+        let python = LanguageSyntax::for_path("a.py");
+        assert_eq!(newline("    if x:", 9, &python, "    ").text, "\n        ");
+        assert_eq!(newline("        return x", 16, &python, "    ").text, "\n    ");
+    }
+
+    #[test]
+    fn test_type_char_pairs() {
+        let rust = LanguageSyntax::for_path("a.rs");
+        let pair = type_char("foo", 3, '(', &rust, "    ").unwrap();
+        assert_eq!((pair.text.as_str(), pair.selection), ("()", (4, 4)));
+        // Step over the closing char typed next
+        let skip = type_char("foo()", 4, ')', &rust, "    ").unwrap();
+        assert_eq!((skip.text.as_str(), skip.selection), ("", (5, 5)));
+        // Not before a word, and no apostrophes after one
+        assert!(type_char("foo", 0, '(', &rust, "    ").is_none());
+        let js = LanguageSyntax::for_path("a.js");
+        assert!(type_char("don", 3, '\'', &js, "    ").is_none());
+        assert_eq!(type_char("x = ", 4, '\'', &js, "    ").unwrap().text, "''");
+        // A closing bracket on a blank line outdents
+        let outdent = type_char("        ", 8, '}', &rust, "    ").unwrap();
+        assert_eq!(apply("        ", &outdent), "    }");
+
+        assert_eq!(surround("a + b", '(', &rust).as_deref(), Some("(a + b)"));
+        assert_eq!(surround("name", '"', &rust).as_deref(), Some("\"name\""));
+        assert_eq!(surround("x", ')', &rust), None);
+    }
+
+    #[test]
+    fn test_backspace_and_indent() {
+        let rust = LanguageSyntax::for_path("a.rs");
+        assert_eq!(apply("f()", &backspace("f()", 2, &rust, "    ").unwrap()), "f");
+        assert_eq!(apply("      x", &backspace("      ", 6, &rust, "    ").unwrap()), "    x");
+        assert!(backspace("ab", 1, &rust, "    ").is_none());
+
+        assert_eq!(indent_unit("fn a() {\n  b();\n    c();\n}", "\t"), "  ");
+        assert_eq!(indent_unit("fn a() {\n\tb();\n}", "  "), "\t");
+        assert_eq!(indent_unit("plain", "    "), "    ");
+        assert_eq!(indent_unit("plain", "\t"), "\t");
+        assert_eq!(tab_text(2, "    "), "  ");
+        assert_eq!(outdent_width("  x", "    "), 2);
+        assert_eq!(outdent_width("\t\tx", "\t"), 1);
+    }
+
+    #[test]
+    fn test_toggle_comments() {
+        let rust = LanguageSyntax::for_path("a.rs");
+        let text = "fn a() {\n    b();\n\n        c();\n}";
+        let (start, end) = (text.find("b()").unwrap(), text.find("c()").unwrap() + 2);
+        let change = toggle_line_comment(text, &rust, start, end).unwrap();
+        let commented = apply(text, &change);
+        assert_eq!(commented, "fn a() {\n    // b();\n\n    //     c();\n}");
+        assert_eq!(change.selection, (start + 3, end + 6));
+        let change = toggle_line_comment(&commented, &rust, change.selection.0, change.selection.1).unwrap();
+        assert_eq!(apply(&commented, &change), text);
+        assert_eq!(change.selection, (start, end));
+
+        let block = toggle_block_comment("let x = a + b;", &rust, 8, 13).unwrap();
+        let wrapped = apply("let x = a + b;", &block);
+        assert_eq!(wrapped, "let x = /* a + b */;");
+        assert_eq!(block.selection, (11, 16));
+        let unwrap = toggle_block_comment(&wrapped, &rust, 8, 19).unwrap();
+        assert_eq!(apply(&wrapped, &unwrap), "let x = a + b;");
+
+        let html = LanguageSyntax::for_path("a.html");
+        let change = toggle_line_comment("  <p>hi</p>", &html, 4, 4).unwrap();
+        assert_eq!(apply("  <p>hi</p>", &change), "  <!-- <p>hi</p> -->");
+    }
+
+    #[test]
+    fn test_move_lines() {
+        let rust = LanguageSyntax::for_path("a.rs");
+        let text = "let a = 1;\nfn f() {\n    g();\n}\nlet b = 2;";
+        let parsed = ParsedText::new(text.to_string(), &rust);
+        // The function moves as a whole, past the statement below it
+        let cursor = text.find("fn").unwrap();
+        let change = move_lines(&parsed, cursor, cursor, false).unwrap();
+        let moved = apply(text, &change);
+        assert_eq!(moved, "let a = 1;\nlet b = 2;\nfn f() {\n    g();\n}");
+        assert_eq!(moved.find("fn").unwrap(), change.selection.0);
+
+        // `let a` moves down over the whole function
+        let change = move_lines(&parsed, 0, 0, false).unwrap();
+        assert_eq!(apply(text, &change), "fn f() {\n    g();\n}\nlet a = 1;\nlet b = 2;");
+        // `let b` moves up over it
+        let cursor = text.find("let b").unwrap();
+        let change = move_lines(&parsed, cursor, cursor, true).unwrap();
+        assert_eq!(apply(text, &change), "let a = 1;\nlet b = 2;\nfn f() {\n    g();\n}");
+
+        assert!(move_lines(&parsed, 0, 0, true).is_none());
+    }
+}
@@ -8,18 +8,66 @@
 use crate::buffer::TextBuffer;
 use crate::completion_widget::CompletionWidget;
 use crate::core::canvas_renderer::{CanvasRenderer, LINE_HEIGHT};
-use crate::diagnostics_panel::DiagnosticsPanel;
+use crate::core::editor_groups::{EditorGroups, EditorLayout, GroupId, Orientation, TabView};
+use crate::core::smart_edit::{self, indent_unit, outdent_width, tab_text, TextChange};
+use crate::diagnostics_panel::{DiagnosticsPanel, FileDiagnostic};
+use crate::git_ui::merge_editor::{MergeEditor, ThreeWayMerge};
 use crate::hover_tooltip::HoverTooltip;
 use crate::lsp_ui::{CompletionItem, Diagnostic, HoverInfo, LspIntegration};
+use crate::hex_view::HexView;
+use crate::markdown_preview::{is_markdown_path, MarkdownPreview};
 use crate::syntax::SyntaxHighlighter;
+use crate::hot_exit::HotExit;
+use crate::settings::EditorSettings;
+use crate::workspace::WorkspaceState;
+use crate::tauri_bindings::{FileContents, LineEnding, TextFormat};
+use crate::tauri_bindings_hot_exit::{Backup, RestoredBackup, RestoredSession, Session, SessionTab, UNTITLED_PREFIX};
+use crate::tauri_bindings_watcher::FsChangeBatch;
+use crate::tree_sitter_engine::{matching_bracket, LanguageSyntax, ParsedText};
 use crate::theme::EditorTheme;
 use crate::types::Position;
+use crate::virtual_scroll::VirtualScroll;
 use leptos::html::Canvas;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use wasm_bindgen::JsCast;
 use web_sys::HtmlCanvasElement;
 
+/// Marker shown for a line of a file: a glyph in the gutter and/or a message after the line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineDecoration {
+    pub file: String,
+    /// 0-based
+    pub line: usize,
+    pub gutter_glyph: Option<String>,
+    pub message: Option<String>,
+    pub color: String,
+}
+
+/// Result of comparing a tab with its file's new content on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskSync {
+    /// Nothing to do: the tab already matches the disk
+    Unchanged,
+    /// The tab had no unsaved edits and now shows the new content
+    Reloaded,
+    /// The tab has unsaved edits; the new content waits in `disk_conflict`
+    Conflict,
+}
+
+/// Change to the active tab's on-disk format, requested from the status bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatChange {
+    LineEnding(LineEnding),
+    /// Convert to this encoding on the next save
+    SaveWithEncoding(String),
+    /// Decode the file again with this encoding
+    ReopenWithEncoding(String),
+}
+
 // Undo/Redo用の状態スナップショット
 #[derive(Clone)]
 struct EditorSnapshot {
@@ -47,6 +95,26 @@ pub struct EditorTab {
     syntax_highlighter: SyntaxHighlighter,
     // ファイルの言語（拡張子から判定）
     language: Option<String>,
+    // 最後に読み込み/保存した時点のディスク上の内容（未保存判定とマージの基準）
+    disk_snapshot: ropey::Rope,
+    /// Content on disk that changed while the tab had unsaved edits
+    pub disk_conflict: Option<String>,
+    /// Encoding, BOM and line ending used when saving
+    pub format: TextFormat,
+    // 最後に読み込み/保存した時点の形式（変更すると未保存扱い）
+    saved_format: TextFormat,
+    /// Size of a binary file, shown in the hex view instead of the text
+    pub binary_size: Option<u64>,
+    /// Why the last save failed
+    pub save_error: Option<String>,
+    // 構文単位の選択拡大の履歴 (拡大前, 拡大後)：縮小で戻る
+    expand_history: Vec<((usize, usize), (usize, usize))>,
+    /// Indentation from the settings of the file's folder, used until the
+    /// file itself is indented
+    default_indent: String,
+    // バッファのリビジョンごとのインデント単位と構文解析の結果（次の編集まで再利用）
+    indent_cache: Option<(u64, String)>,
+    parsed_cache: Option<(u64, Arc<ParsedText>)>,
 }
 
 impl EditorTab {
@@ -72,9 +140,25 @@ impl EditorTab {
             None // サポートされていない拡張子
         };
 
+        // 改行コードは読み込み時に LF へ統一し、保存時に元へ戻す
+        let format = TextFormat {
+            line_ending: LineEnding::detect(&content),
+            ..TextFormat::default()
+        };
+        let buffer = TextBuffer::from_str(&content.replace("\r\n", "\n"));
         Self {
             file_path,
-            buffer: TextBuffer::from_str(&content),
+            disk_snapshot: buffer.snapshot(),
+            disk_conflict: None,
+            saved_format: format.clone(),
+            format,
+            binary_size: None,
+            save_error: None,
+            expand_history: Vec::new(),
+            default_indent: "    ".to_string(),
+            indent_cache: None,
+            parsed_cache: None,
+            buffer,
             cursor_line: 0,
             cursor_col: 0,
             scroll_top: 0.0,
@@ -207,6 +291,262 @@ impl EditorTab {
         self.clear_selection();
     }
 
+    // (line, col) → 文字オフセット
+    fn offset_of(&self, (line, col): (usize, usize)) -> usize {
+        self.buffer.line_to_char(line) + col
+    }
+
+    // 文字オフセット → (line, col)
+    fn position_of(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.buffer.len_chars());
+        let line = self.buffer.char_to_line(offset);
+        (line, offset - self.buffer.line_to_char(line))
+    }
+
+    /// Selection as (anchor, head) char offsets; both at the cursor without one
+    fn selection_offsets(&self) -> (usize, usize) {
+        let cursor = self.offset_of((self.cursor_line, self.cursor_col));
+        match (self.selection_start, self.selection_end) {
+            (Some(anchor), Some(head)) => (self.offset_of(anchor), self.offset_of(head)),
+            _ => (cursor, cursor),
+        }
+    }
+
+    // anchor..head を選択（同じならカーソルだけ置く）
+    fn select_offsets(&mut self, (anchor, head): (usize, usize)) {
+        let (line, col) = self.position_of(head);
+        self.cursor_line = line;
+        self.cursor_col = col;
+        if anchor == head {
+            self.clear_selection();
+        } else {
+            self.selection_start = Some(self.position_of(anchor));
+            self.selection_end = Some((line, col));
+        }
+    }
+
+    /// Apply a smart editing change whose offsets are relative to `base`
+    fn apply_change(&mut self, base: usize, change: &TextChange) {
+        self.buffer.remove(base + change.start, base + change.end);
+        if !change.text.is_empty() {
+            self.buffer.insert(base + change.start, &change.text);
+        }
+        self.select_offsets((base + change.selection.0, base + change.selection.1));
+    }
+
+    // 改行を除いた行の内容
+    fn line_text(&self, line: usize) -> String {
+        self.buffer
+            .line(line)
+            .map(|s| s.trim_end_matches('\n').to_string())
+            .unwrap_or_default()
+    }
+
+    /// Use the indentation of the file's folder settings until the file is indented
+    pub fn set_default_indent(&mut self, unit: String) {
+        self.default_indent = unit;
+        self.indent_cache = None;
+    }
+
+    // ファイルのインデント単位（先頭の行から推定）
+    fn indent_unit(&mut self) -> String {
+        let revision = self.buffer.revision();
+        if let Some((cached, unit)) = &self.indent_cache {
+            if *cached == revision {
+                return unit.clone();
+            }
+        }
+        let sample: String = (0..self.buffer.len_lines().min(500))
+            .filter_map(|line| self.buffer.line(line))
+            .collect();
+        let unit = indent_unit(&sample, &self.default_indent);
+        self.indent_cache = Some((revision, unit.clone()));
+        unit
+    }
+
+    fn syntax(&self) -> LanguageSyntax {
+        LanguageSyntax::for_path(&self.file_path)
+    }
+
+    // 構文解析の結果（テキストか言語が変わるまで再利用）
+    fn parsed(&mut self) -> Arc<ParsedText> {
+        let revision = self.buffer.revision();
+        let syntax = self.syntax();
+        if let Some((cached, parsed)) = &self.parsed_cache {
+            if *cached == revision && parsed.syntax == syntax {
+                return parsed.clone();
+            }
+        }
+        let parsed = Arc::new(ParsedText::new(self.buffer.to_string(), &syntax));
+        self.parsed_cache = Some((revision, parsed.clone()));
+        parsed
+    }
+
+    /// Grow the selection to the enclosing syntax node
+    pub fn expand_selection(&mut self) -> bool {
+        let (anchor, head) = self.selection_offsets();
+        let (start, end) = (anchor.min(head), anchor.max(head));
+        let Some(expanded) = self.parsed().expand_selection(start, end) else {
+            return false;
+        };
+        // 別の操作で選択が変わっていたら履歴は無効
+        if self.expand_history.last().is_some_and(|(_, after)| *after != (start, end)) {
+            self.expand_history.clear();
+        }
+        self.expand_history.push(((anchor, head), expanded));
+        self.select_offsets(expanded);
+        true
+    }
+
+    /// Undo the last expansion of the selection
+    pub fn shrink_selection(&mut self) -> bool {
+        let (anchor, head) = self.selection_offsets();
+        let current = (anchor.min(head), anchor.max(head));
+        match self.expand_history.pop() {
+            Some((before, after)) if after == current => {
+                self.select_offsets(before);
+                true
+            }
+            _ => {
+                self.expand_history.clear();
+                false
+            }
+        }
+    }
+
+    /// Move the cursor to the bracket matching the one next to it
+    pub fn jump_to_matching_bracket(&mut self) -> bool {
+        let parsed = self.parsed();
+        let cursor = self.offset_of((self.cursor_line, self.cursor_col));
+        let Some(target) = matching_bracket(&parsed.tree, cursor) else {
+            return false;
+        };
+        self.select_offsets((target, target));
+        true
+    }
+
+    /// Apply a command working on the whole text and the selection
+    fn apply_text_command(
+        &mut self,
+        command: impl FnOnce(&ParsedText, &LanguageSyntax, usize, usize) -> Option<TextChange>,
+    ) -> bool {
+        let (anchor, head) = self.selection_offsets();
+        let parsed = self.parsed();
+        let Some(mut change) = command(&parsed, &self.syntax(), anchor.min(head), anchor.max(head)) else {
+            return false;
+        };
+        // 選択の向きを保つ
+        if head < anchor {
+            change.selection = (change.selection.1, change.selection.0);
+        }
+        self.save_undo_state();
+        self.apply_change(0, &change);
+        true
+    }
+
+    /// Enter with the indentation of the line, indented after an opening bracket
+    pub fn smart_newline(&mut self) {
+        self.save_undo_state();
+        if self.has_selection() {
+            self.delete_selection();
+        }
+        let line = self.line_text(self.cursor_line);
+        let unit = self.indent_unit();
+        let change = smart_edit::newline(&line, self.cursor_col, &self.syntax(), &unit);
+        self.apply_change(self.buffer.line_to_char(self.cursor_line), &change);
+    }
+
+    /// Type a character with auto-closed pairs and surround; false to insert it as is
+    pub fn smart_type(&mut self, ch: char) -> bool {
+        let syntax = self.syntax();
+        if let Some(selected) = self.get_selected_text().filter(|s| !s.is_empty()) {
+            let Some(wrapped) = smart_edit::surround(&selected, ch, &syntax) else {
+                return false;
+            };
+            let (anchor, head) = self.selection_offsets();
+            let start = anchor.min(head);
+            let selected_len = selected.chars().count();
+            self.save_undo_state();
+            self.apply_change(
+                start,
+                &TextChange {
+                    start: 0,
+                    end: selected_len,
+                    text: wrapped,
+                    // 囲んだ中身を選択したまま
+                    selection: (1, selected_len + 1),
+                },
+            );
+            return true;
+        }
+        let line = self.line_text(self.cursor_line);
+        let unit = self.indent_unit();
+        let Some(change) = smart_edit::type_char(&line, self.cursor_col, ch, &syntax, &unit) else {
+            return false;
+        };
+        self.save_undo_state();
+        self.apply_change(self.buffer.line_to_char(self.cursor_line), &change);
+        true
+    }
+
+    /// Backspace that removes empty pairs and whole indent steps; false for a plain backspace
+    pub fn smart_backspace(&mut self) -> bool {
+        if self.has_selection() {
+            return false;
+        }
+        let line = self.line_text(self.cursor_line);
+        let unit = self.indent_unit();
+        let Some(change) = smart_edit::backspace(&line, self.cursor_col, &self.syntax(), &unit) else {
+            return false;
+        };
+        self.save_undo_state();
+        self.apply_change(self.buffer.line_to_char(self.cursor_line), &change);
+        true
+    }
+
+    /// Tab: indent the selected lines, or insert whitespace to the next indent stop.
+    /// With `outdent`, remove one indent step from the lines instead.
+    pub fn indent(&mut self, outdent: bool) {
+        let unit = self.indent_unit();
+        let (anchor, head) = self.selection_offsets();
+        if !outdent && anchor == head {
+            self.save_undo_state();
+            let text = tab_text(self.cursor_col, &unit);
+            let cursor = self.cursor_col + text.chars().count();
+            let change = TextChange { start: self.cursor_col, end: self.cursor_col, text, selection: (cursor, cursor) };
+            self.apply_change(self.buffer.line_to_char(self.cursor_line), &change);
+            return;
+        }
+
+        let (anchor_pos, head_pos) = (self.position_of(anchor), self.position_of(head));
+        let first = anchor_pos.min(head_pos).0;
+        let end = anchor_pos.max(head_pos);
+        // 行頭で終わる選択はその行を含めない
+        let last = if end.1 == 0 && end.0 > first { end.0 - 1 } else { end.0 };
+
+        self.save_undo_state();
+        // 行ごとの桁の変化（選択位置を合わせるため）
+        let mut deltas = Vec::new();
+        for line in first..=last {
+            let line_start = self.buffer.line_to_char(line);
+            let content = self.line_text(line);
+            if outdent {
+                let width = outdent_width(&content, &unit);
+                self.buffer.remove(line_start, line_start + width);
+                deltas.push((line, -(width as isize)));
+            } else if !content.trim().is_empty() {
+                self.buffer.insert(line_start, &unit);
+                deltas.push((line, unit.chars().count() as isize));
+            }
+        }
+        let moved = |(line, col): (usize, usize)| {
+            let delta = deltas.iter().find(|(l, _)| *l == line).map_or(0, |(_, d)| *d);
+            (line, (col as isize + delta).max(0) as usize)
+        };
+        let (anchor, head) = (self.offset_of(moved(anchor_pos)), self.offset_of(moved(head_pos)));
+        self.select_offsets((anchor, head));
+    }
+
     // カーソルが見える範囲にスクロールを調整
     pub fn scroll_into_view(&mut self, canvas_height: f64) {
         let line_height = 20.0; // LINE_HEIGHT
@@ -231,6 +571,247 @@ impl EditorTab {
         self.scroll_top = self.scroll_top.max(0.0).min(max_scroll);
     }
 
+    // 指定位置（0始まり）にカーソルを移動してスクロール
+    pub fn reveal_position(&mut self, line: usize, col: usize, canvas_height: f64) {
+        let last_line = self.buffer.len_lines().saturating_sub(1);
+        self.cursor_line = line.min(last_line);
+        let line_len = self
+            .buffer
+            .line(self.cursor_line)
+            .map(|l| l.trim_end_matches(['\n', '\r']).chars().count())
+            .unwrap_or(0);
+        self.cursor_col = col.min(line_len);
+        self.clear_selection();
+
+        // 移動先が画面中央付近に来るようにしてから範囲内に収める
+        self.scroll_top = self.cursor_line as f64 * 20.0 - canvas_height / 2.0;
+        self.scroll_into_view(canvas_height);
+    }
+
+    /// 外部で変更されたファイル内容に置き換える（カーソル位置は可能な範囲で維持）
+    pub fn reload_content(&mut self, content: &str) {
+        self.buffer = TextBuffer::from_str(content);
+        let last_line = self.buffer.len_lines().saturating_sub(1);
+        self.cursor_line = self.cursor_line.min(last_line);
+        let line_len = self
+            .buffer
+            .line(self.cursor_line)
+            .map(|l| l.trim_end_matches(['\n', '\r']).chars().count())
+            .unwrap_or(0);
+        self.cursor_col = self.cursor_col.min(line_len);
+        self.clear_selection();
+        self.disk_snapshot = self.buffer.snapshot();
+        self.disk_conflict = None;
+    }
+
+    /// 未保存の編集があるか（最後に読み込み/保存した内容と異なるか）
+    pub fn is_dirty(&self) -> bool {
+        self.buffer.snapshot() != self.disk_snapshot || self.format != self.saved_format
+    }
+
+    /// 保存した内容をディスク上の内容として記録
+    pub fn mark_saved(&mut self) {
+        self.buffer.mark_saved();
+        self.disk_snapshot = self.buffer.snapshot();
+        self.saved_format = self.format.clone();
+        self.disk_conflict = None;
+        self.save_error = None;
+    }
+
+    /// 保存前の状態（保存に失敗したら戻す）
+    pub fn saved_state(&self) -> (ropey::Rope, TextFormat) {
+        (self.disk_snapshot.clone(), self.saved_format.clone())
+    }
+
+    /// 保存に失敗：未保存の状態に戻してエラーを表示
+    pub fn save_failed(&mut self, state: (ropey::Rope, TextFormat), error: String) {
+        (self.disk_snapshot, self.saved_format) = state;
+        self.save_error = Some(error);
+    }
+
+    /// ディスクから読み込んだ内容と形式を反映（バイナリなら16進表示に切り替え）。
+    /// 未保存の編集があれば形式だけを更新
+    pub fn apply_file_contents(&mut self, contents: FileContents) {
+        match contents {
+            FileContents::Binary { size } => self.binary_size = Some(size),
+            FileContents::Text { content, format } => {
+                self.binary_size = None;
+                if !self.is_dirty() && self.buffer.snapshot() != content.as_str() {
+                    self.reload_content(&content);
+                }
+                self.format = format.clone();
+                self.saved_format = format;
+            }
+        }
+    }
+
+    /// 別のエンコーディングで読み直した内容に置き換える（Undo可能）
+    pub fn reopen_with(&mut self, content: &str, format: TextFormat) {
+        if self.buffer.snapshot() != content {
+            self.save_undo_state();
+            self.reload_content(content);
+        }
+        self.format = format.clone();
+        self.saved_format = format;
+    }
+
+    /// ステータスバーからの改行コード/エンコーディング変更（保存時に反映）
+    pub fn change_format(&mut self, change: &FormatChange) {
+        match change {
+            FormatChange::LineEnding(line_ending) => self.format.line_ending = *line_ending,
+            FormatChange::SaveWithEncoding(encoding) => {
+                self.format.bom = self.format.bom && encoding.starts_with("UTF");
+                self.format.encoding = encoding.clone();
+            }
+            FormatChange::ReopenWithEncoding(_) => {}
+        }
+    }
+
+    /// Last loaded or saved text, the base for merging a conflict
+    pub fn disk_text(&self) -> String {
+        self.disk_snapshot.to_string()
+    }
+
+    /// 外部で変更された内容と同期：未保存の編集がなければ再読み込み、あれば衝突として保持
+    pub fn sync_with_disk(&mut self, content: &str) -> DiskSync {
+        let content = content.replace("\r\n", "\n");
+        let content = content.as_str();
+        if self.buffer.snapshot() == content {
+            // 保存の反映：保留中の形式変更はそのまま
+            self.buffer.mark_saved();
+            self.disk_snapshot = self.buffer.snapshot();
+            self.disk_conflict = None;
+            return DiskSync::Unchanged;
+        }
+        if self.disk_snapshot == content {
+            // 衝突後にディスクが元に戻った
+            self.disk_conflict = None;
+            return DiskSync::Unchanged;
+        }
+        if !self.is_dirty() {
+            self.reload_content(content);
+            return DiskSync::Reloaded;
+        }
+        self.disk_conflict = Some(content.to_string());
+        DiskSync::Conflict
+    }
+
+    /// 衝突を解消：`text`（ディスクの内容、自分の編集、またはマージ結果）をバッファにする。
+    /// ディスク上の内容が以後の基準になり、ディスクと異なれば未保存のまま残る
+    pub fn resolve_disk_conflict(&mut self, text: &str) {
+        let Some(disk) = self.disk_conflict.take() else {
+            return;
+        };
+        if self.buffer.snapshot() != text {
+            self.save_undo_state();
+            self.reload_content(text);
+        }
+        self.disk_snapshot = ropey::Rope::from_str(&disk);
+    }
+
+    /// ディスク上にまだファイルがない新規タブ（"untitled:Untitled-1" など）
+    pub fn untitled(name: &str) -> Self {
+        Self::new(format!("{}{}", UNTITLED_PREFIX, name), String::new())
+    }
+
+    pub fn is_untitled(&self) -> bool {
+        self.file_path.starts_with(UNTITLED_PREFIX)
+    }
+
+    /// タブに表示する名前
+    pub fn title(&self) -> &str {
+        tab_title(&self.file_path)
+    }
+
+    /// 新規タブを`path`へ保存する前に、パスと言語を切り替える（編集履歴は維持）
+    pub fn save_as(&mut self, path: String) {
+        let mut tab = Self::new(path, self.buffer.to_string());
+        tab.disk_snapshot = self.disk_snapshot.clone();
+        tab.format = self.format.clone();
+        tab.saved_format = self.saved_format.clone();
+        tab.cursor_line = self.cursor_line;
+        tab.cursor_col = self.cursor_col;
+        tab.scroll_top = self.scroll_top;
+        tab.undo_stack = std::mem::take(&mut self.undo_stack);
+        tab.redo_stack = std::mem::take(&mut self.redo_stack);
+        *self = tab;
+    }
+
+    /// セッションに記録するタブの状態
+    pub fn session_tab(&self) -> SessionTab {
+        SessionTab {
+            path: self.file_path.clone(),
+            cursor_line: self.cursor_line,
+            cursor_col: self.cursor_col,
+            scroll_top: self.scroll_top,
+        }
+    }
+
+    /// セッションのカーソル位置とスクロール位置を復元（内容に収まる範囲で）
+    pub fn restore_position(&mut self, state: &SessionTab) {
+        self.cursor_line = state.cursor_line.min(self.buffer.len_lines().saturating_sub(1));
+        let line_len = self
+            .buffer
+            .line(self.cursor_line)
+            .map(|l| l.trim_end_matches(['\n', '\r']).chars().count())
+            .unwrap_or(0);
+        self.cursor_col = state.cursor_col.min(line_len);
+        self.scroll_top = state.scroll_top.max(0.0);
+    }
+
+    /// カーソル・選択範囲・スクロール位置（エディタグループごとに保持）
+    pub fn view(&self) -> TabView {
+        TabView {
+            cursor_line: self.cursor_line,
+            cursor_col: self.cursor_col,
+            selection_start: self.selection_start,
+            selection_end: self.selection_end,
+            scroll_top: self.scroll_top,
+        }
+    }
+
+    /// 別のグループで保持していた表示状態に切り替える。
+    /// その後の編集で範囲外になった位置は内容に収める
+    pub fn set_view(&mut self, view: &TabView) {
+        let line_len = |line: usize| {
+            self.buffer
+                .line(line)
+                .map(|l| l.trim_end_matches(['\n', '\r']).chars().count())
+                .unwrap_or(0)
+        };
+        let in_bounds = |(line, col): (usize, usize)| line < self.buffer.len_lines() && col <= line_len(line);
+        let selection = match (view.selection_start, view.selection_end) {
+            (Some(start), Some(end)) if in_bounds(start) && in_bounds(end) => (Some(start), Some(end)),
+            _ => (None, None),
+        };
+        (self.selection_start, self.selection_end) = selection;
+        self.cursor_line = view.cursor_line.min(self.buffer.len_lines().saturating_sub(1));
+        self.cursor_col = view.cursor_col.min(line_len(self.cursor_line));
+        self.scroll_top = view.scroll_top.max(0.0);
+    }
+
+    /// 未保存の内容のバックアップ
+    pub fn backup(&self) -> Backup {
+        Backup {
+            path: self.file_path.clone(),
+            base: (!self.is_untitled()).then(|| self.disk_text()),
+            content: self.buffer.to_string(),
+        }
+    }
+
+    /// バックアップから未保存のタブを復元。ディスク上で変更されていれば衝突として保持
+    pub fn from_backup(restored: &RestoredBackup) -> Self {
+        let backup = &restored.backup;
+        let mut tab = Self::new(backup.path.clone(), backup.base.clone().unwrap_or_default());
+        if tab.buffer.snapshot() != backup.content.as_str() {
+            tab.buffer = TextBuffer::from_str(&backup.content);
+        }
+        if let Some(disk) = &restored.disk {
+            tab.sync_with_disk(disk);
+        }
+        tab
+    }
+
     // 指定位置の単語の境界を取得
     fn get_word_bounds(&self, line: usize, col: usize) -> (usize, usize) {
         let line_text = self.buffer.line(line).unwrap_or_default();
@@ -361,23 +942,386 @@ fn lsp_position_to_canvas_pixel(
     (x, y)
 }
 
+/// パスのタブに表示する名前（新規タブは "Untitled-N"、それ以外はファイル名）
+fn tab_title(path: &str) -> &str {
+    path.strip_prefix(UNTITLED_PREFIX)
+        .unwrap_or_else(|| path.rsplit(['/', '\\']).next().unwrap_or(path))
+}
+
+/// 次の新規タブの名前（"Untitled-N"）
+fn next_untitled_name(tabs: &[EditorTab]) -> String {
+    let last = tabs
+        .iter()
+        .filter_map(|t| t.file_path.strip_prefix(UNTITLED_PREFIX)?.strip_prefix("Untitled-")?.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("Untitled-{}", last + 1)
+}
+
+/// 前回のバックアップ以降に変わった未保存タブのバックアップと、
+/// 未保存の編集がなくなった（保存・閉じた）タブのパス
+fn pending_backups(tabs: &[EditorTab], backed_up: &HashMap<String, String>) -> (Vec<Backup>, Vec<String>) {
+    let backups = tabs
+        .iter()
+        .filter(|tab| tab.is_dirty() && tab.binary_size.is_none())
+        .filter(|tab| backed_up.get(&tab.file_path).is_none_or(|text| tab.buffer.snapshot() != text.as_str()))
+        .map(EditorTab::backup)
+        .collect();
+    let discards = backed_up
+        .keys()
+        .filter(|path| !tabs.iter().any(|tab| &tab.file_path == *path && tab.is_dirty()))
+        .cloned()
+        .collect();
+    (backups, discards)
+}
+
+/// 前回のセッションのタブを開き直す。未保存の編集はバックアップから戻し、
+/// 削除されたファイルのタブは閉じる。アクティブだったタブの位置も返す
+async fn restore_tabs(restored: RestoredSession) -> (Vec<EditorTab>, Option<usize>) {
+    let RestoredSession { session, mut backups } = restored;
+    let mut states = session.tabs;
+    // セッションの保存前に終了した場合でもバックアップは開く
+    for backup in &backups {
+        if !states.iter().any(|s| s.path == backup.backup.path) {
+            states.push(SessionTab {
+                path: backup.backup.path.clone(),
+                cursor_line: 0,
+                cursor_col: 0,
+                scroll_top: 0.0,
+            });
+        }
+    }
+
+    let mut restored_tabs = Vec::new();
+    let mut active = None;
+    for (index, state) in states.iter().enumerate() {
+        let backup = backups
+            .iter()
+            .position(|b| b.backup.path == state.path)
+            .map(|i| backups.swap_remove(i));
+        let contents = if state.path.starts_with(UNTITLED_PREFIX) {
+            None
+        } else {
+            crate::tauri_bindings::read_text_file(&state.path, None).await.ok()
+        };
+        let mut tab = match (backup, contents) {
+            (Some(backup), contents) => {
+                let mut tab = EditorTab::from_backup(&backup);
+                // 未保存なので形式だけが反映される
+                if let Some(contents) = contents {
+                    tab.apply_file_contents(contents);
+                }
+                tab
+            }
+            (None, Some(contents)) => {
+                let mut tab = EditorTab::new(state.path.clone(), String::new());
+                tab.apply_file_contents(contents);
+                tab
+            }
+            (None, None) => continue,
+        };
+        tab.restore_position(state);
+        if session.active_tab == Some(index) {
+            active = Some(restored_tabs.len());
+        }
+        restored_tabs.push(tab);
+    }
+    if active.is_none() && !restored_tabs.is_empty() {
+        active = Some(0);
+    }
+    (restored_tabs, active)
+}
+
+/// Switches tabs and groups. The open tab of the focused group keeps its
+/// cursor in the `EditorTab`; the other groups' cursors wait in `groups`.
+#[derive(Clone, Copy)]
+struct GroupControl {
+    tabs: RwSignal<Vec<EditorTab>>,
+    groups: RwSignal<EditorGroups>,
+    active_tab_index: RwSignal<Option<usize>>,
+}
+
+impl GroupControl {
+    /// Hand the focused tab's cursor back to its group
+    fn store_live(&self) {
+        let Some(index) = self.active_tab_index.get_untracked() else {
+            return;
+        };
+        let Some((path, view)) = self
+            .tabs
+            .with_untracked(|tabs_vec| tabs_vec.get(index).map(|tab| (tab.file_path.clone(), tab.view())))
+        else {
+            return;
+        };
+        self.groups.update_untracked(|groups| {
+            if groups.active_path() == Some(path.as_str()) {
+                groups.set_view(groups.focused, &path, view);
+            }
+        });
+    }
+
+    /// Show the focused group's tab with that group's cursor. A tab new to the
+    /// group keeps the cursor it has.
+    fn load_live(&self) {
+        let active = self.groups.with_untracked(|groups| {
+            let path = groups.active_path()?;
+            Some((path.to_string(), groups.view(groups.focused, path).cloned()))
+        });
+        let index = active.as_ref().and_then(|(path, _)| {
+            self.tabs.with_untracked(|tabs_vec| tabs_vec.iter().position(|t| &t.file_path == path))
+        });
+        if let (Some(index), Some((_, Some(view)))) = (index, &active) {
+            self.tabs.update(|tabs_vec| tabs_vec[index].set_view(view));
+        }
+        self.active_tab_index.set(index);
+    }
+
+    fn change(&self, change: impl FnOnce(&mut EditorGroups)) {
+        self.store_live();
+        self.groups.update(change);
+        self.load_live();
+    }
+
+    /// Show `path` in `group` and focus it
+    fn activate(&self, group: GroupId, path: &str) {
+        self.change(|groups| {
+            groups.focus(group);
+            groups.open(group, path);
+        });
+    }
+
+    fn focus(&self, group: GroupId) {
+        if self.groups.with_untracked(|groups| groups.focused != group) {
+            self.change(|groups| {
+                groups.focus(group);
+            });
+        }
+    }
+
+    /// Close a tab of a group; the document closes with its last tab
+    fn close(&self, group: GroupId, path: &str) {
+        self.change(|groups| groups.close_tab(group, path));
+        if !self.groups.with_untracked(|groups| groups.contains(path)) {
+            self.tabs.update(|tabs_vec| tabs_vec.retain(|t| t.file_path != path));
+            self.load_live();
+        }
+    }
+}
+
+/// The canvas of the focused group, for handlers written for a single canvas
+#[derive(Clone, Copy)]
+struct FocusedCanvas {
+    canvases: StoredValue<HashMap<GroupId, NodeRef<Canvas>>>,
+    groups: RwSignal<EditorGroups>,
+}
+
+impl FocusedCanvas {
+    fn get(&self) -> Option<HtmlCanvasElement> {
+        let focused = self.groups.with(|groups| groups.focused);
+        self.canvases.with_value(|canvases| canvases.get(&focused).copied())?.get()
+    }
+
+    fn get_untracked(&self) -> Option<HtmlCanvasElement> {
+        let focused = self.groups.with_untracked(|groups| groups.focused);
+        self.canvases.with_value(|canvases| canvases.get(&focused).copied())?.get_untracked()
+    }
+}
+
+/// Draw a tab as seen from one group. The cursor (and IME text being
+/// composed) is only drawn when `composing` is given, i.e. in the focused
+/// group; its pixel position is returned.
+fn draw_pane(
+    renderer: &CanvasRenderer,
+    scroll: &VirtualScroll,
+    tab: &EditorTab,
+    view: &TabView,
+    decorations: &[LineDecoration],
+    composing: Option<&str>,
+    (width, height): (f64, f64),
+) -> Option<(f64, f64)> {
+    renderer.clear(width, height);
+
+    // 可視範囲の行（スクロール位置は VirtualScroll で内容に収める）
+    let start_line = scroll.line_at_y(0.0);
+    let scroll_top = scroll.get_line_offset(start_line);
+    let end_line = (scroll.line_at_y(height) + 1).min(tab.buffer.len_lines());
+    let line_text = |line: usize| {
+        tab.buffer
+            .line(line)
+            .map(|s| s.trim_end_matches('\n').to_string())
+            .unwrap_or_default()
+    };
+
+    renderer.draw_gutter(start_line, end_line, height);
+
+    // 選択範囲を描画（逆方向選択は正規化）
+    if let (Some(start), Some(end)) = (view.selection_start, view.selection_end) {
+        let (start, end) = if start <= end { (start, end) } else { (end, start) };
+        renderer.draw_selection(start.0, start.1, end.0, end.1, scroll_top, line_text);
+    }
+
+    // テキスト行を描画（シンタックスハイライト付き）
+    let theme = EditorTheme::current();
+    let language = tab.language.as_deref();
+    for line_num in start_line..end_line {
+        let y_offset = (line_num - start_line) as f64 * LINE_HEIGHT;
+        renderer.draw_line_highlighted(y_offset, &line_text(line_num), theme, language);
+    }
+
+    // 行デコレーション（ガターのアイコンと行末のメッセージ）
+    for decoration in decorations
+        .iter()
+        .filter(|d| d.file == tab.file_path && d.line >= start_line && d.line < end_line)
+    {
+        let y_offset = (decoration.line - start_line) as f64 * LINE_HEIGHT;
+        if let Some(glyph) = &decoration.gutter_glyph {
+            renderer.draw_gutter_glyph(y_offset, glyph, &decoration.color);
+        }
+        if let Some(message) = &decoration.message {
+            renderer.draw_inline_message(y_offset, &line_text(decoration.line), message, &decoration.color);
+        }
+    }
+
+    let composing = composing?;
+
+    // IME組成中は、確定文字+未確定文字の仮想テキストでカーソル位置を計算
+    let cursor_line_text = line_text(view.cursor_line);
+    let (virtual_line_text, cursor_col_display) = if composing.is_empty() {
+        (cursor_line_text.clone(), view.cursor_col)
+    } else {
+        let before: String = cursor_line_text.chars().take(view.cursor_col).collect();
+        let after: String = cursor_line_text.chars().skip(view.cursor_col).collect();
+        (format!("{}{}{}", before, composing, after), view.cursor_col + composing.chars().count())
+    };
+    renderer.draw_cursor(view.cursor_line, cursor_col_display, scroll_top, &virtual_line_text);
+
+    // IME未確定文字列をカーソル位置から描画（灰色）
+    if !composing.is_empty() {
+        let text_before_cursor: String = cursor_line_text.chars().take(view.cursor_col).collect();
+        let x = renderer.gutter_width() + 15.0 + renderer.measure_text(&text_before_cursor);
+        let y = view.cursor_line as f64 * LINE_HEIGHT - scroll_top + 15.0;
+        renderer.draw_text_at(x, y, composing, "#808080");
+    }
+
+    // カーソル位置（IME用）- 全角文字対応、composing中は未確定文字列の後ろ
+    let text_before_cursor_display: String = virtual_line_text.chars().take(cursor_col_display).collect();
+    let x = renderer.gutter_width() + 15.0 + renderer.measure_text(&text_before_cursor_display);
+    let y = view.cursor_line as f64 * LINE_HEIGHT - scroll_top;
+    Some((x, y))
+}
+
+/// Splits as nested flex rows/columns with a pane per group
+fn layout_view<F>(layout: EditorLayout, pane: F) -> AnyView
+where
+    F: Fn(GroupId) -> AnyView + Copy + Send + Sync + 'static,
+{
+    match layout {
+        EditorLayout::Group { id } => pane(id),
+        EditorLayout::Split { orientation, children } => {
+            let class = match orientation {
+                Orientation::Horizontal => "berry-editor-split berry-editor-split-horizontal",
+                Orientation::Vertical => "berry-editor-split berry-editor-split-vertical",
+            };
+            view! {
+                <div class=class>
+                    {children.into_iter().map(|child| layout_view(child, pane)).collect_view()}
+                </div>
+            }
+            .into_any()
+        }
+    }
+}
+
 #[component]
 pub fn VirtualEditorPanel(
     #[prop(into)] selected_file: Signal<Option<(String, String)>>,
     /// Whether this editor panel is currently active (visible). Defaults to true for backwards compatibility.
     #[prop(into, default = Signal::derive(|| true))]
     is_active: Signal<bool>,
+    /// Position to reveal as (path, line, column), 0-based. Applied when the file's tab is open.
+    #[prop(into, default = Signal::derive(|| None))]
+    reveal_position: Signal<Option<(String, usize, usize)>>,
+    /// Problems reported by tasks, shown in the diagnostics panel
+    #[prop(optional)]
+    task_diagnostics: Option<RwSignal<Vec<FileDiagnostic>>>,
+    /// Opens a file at (path, line, column), 1-based
+    #[prop(optional)]
+    on_open_location: Option<Callback<(String, usize, usize)>>,
+    /// Gutter markers and inline messages (e.g. test results)
+    #[prop(optional)]
+    line_decorations: Option<Signal<Vec<LineDecoration>>>,
+    /// Called with (path, line) when the gutter is clicked, 0-based
+    #[prop(optional)]
+    on_gutter_click: Option<Callback<(String, usize)>>,
+    /// File content changed on disk as (path, content); reloads the open tab unless it has unsaved edits
+    #[prop(optional)]
+    file_reloaded: Option<Signal<Option<(String, String)>>>,
+    /// Changes reported by the workspace watcher; open tabs are re-read from disk
+    #[prop(optional)]
+    fs_changes: Option<Signal<Option<FsChangeBatch>>>,
+    /// Receives the on-disk format of the active tab (for the status bar)
+    #[prop(optional)]
+    active_format: Option<RwSignal<Option<TextFormat>>>,
+    /// Encoding or line ending change for the active tab
+    #[prop(optional)]
+    format_request: Option<Signal<Option<FormatChange>>>,
+    /// Restores the last session's tabs and backs up unsaved buffers
+    #[prop(optional)]
+    hot_exit: Option<HotExit>,
+    /// Workspace folders; a file's folder is the root of its language server
+    #[prop(optional)]
+    workspace: Option<WorkspaceState>,
 ) -> impl IntoView {
-    let canvas_ref = NodeRef::<Canvas>::new();
     let container_ref = NodeRef::<leptos::html::Div>::new();
+    let panes_ref = NodeRef::<leptos::html::Div>::new();
 
-    // タブ管理（複数タブ対応）
+    // タブ管理（複数タブ対応）: 同じファイルは全グループで1つのバッファを共有
     let tabs = RwSignal::new(Vec::<EditorTab>::new());
+    // ファイルのフォルダの設定（ワークスペース・フォルダごとの上書きを含む）によるインデント
+    let default_indent = move |path: &str| {
+        workspace
+            .map(|w| w.settings_for(path))
+            .unwrap_or_else(EditorSettings::load)
+            .indent_unit()
+    };
+    // フォーカス中のグループで開いているタブ
     let active_tab_index = RwSignal::new(Option::<usize>::None);
 
+    // 分割したエディタグループ（グループごとのタブとカーソル位置）
+    let groups = RwSignal::new(EditorGroups::default());
+    let group_control = GroupControl {
+        tabs,
+        groups,
+        active_tab_index,
+    };
+    let canvases = StoredValue::new(HashMap::<GroupId, NodeRef<Canvas>>::new());
+    let canvas_ref = FocusedCanvas { canvases, groups };
+    // 閉じたグループのキャンバスを破棄
+    Effect::new(move |_| {
+        groups.with(|g| {
+            canvases.update_value(|map| map.retain(|id, _| g.group(*id).is_some()));
+        });
+    });
+    // ドラッグ中のタブ (group, path)
+    let dragged_tab = RwSignal::new(Option::<(GroupId, String)>::None);
+    // フォーカス中のキャンバスの位置（IME入力とポップアップの配置用）
+    let pane_offset = RwSignal::new((0.0, 0.0));
+
     // 再描画トリガー用
     let render_trigger = RwSignal::new(0u32);
 
+    // Markdownプレビューの表示（フォーカス中のグループの文書を表示）
+    let preview_open = RwSignal::new(false);
+    let toggle_preview = move || {
+        preview_open.update(|open| *open = !*open);
+        // プレビューの表示・非表示でグループの幅が変わるので、レイアウト後に再描画
+        let callback = wasm_bindgen::closure::Closure::once(move || render_trigger.update(|v| *v += 1));
+        if let Some(window) = web_sys::window() {
+            let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+        }
+        callback.forget();
+    };
+
     // IME状態管理
     let is_composing = RwSignal::new(false);
     let composing_text = RwSignal::new(String::new());
@@ -420,59 +1364,428 @@ pub fn VirtualEditorPanel(
         leptos::logging::log!("🔍 DEBUG: Effect triggered, current_file={:?}",
             current_file.as_ref().map(|(p, _)| p));
 
-        if let Some((path, content)) = current_file {
-            leptos::logging::log!("🔍 DEBUG: Opening file: {}", path);
+        if let Some((path, content)) = current_file {
+            leptos::logging::log!("🔍 DEBUG: Opening file: {}", path);
+            // 既存のタブがなければ新しいタブを追加
+            if !tabs.with_untracked(|tabs_vec| tabs_vec.iter().any(|t| t.file_path == path)) {
+                let mut tab = EditorTab::new(path.clone(), content.clone());
+                tab.set_default_indent(default_indent(&path));
+                tabs.update(|tabs_vec| tabs_vec.push(tab));
+
+                // エンコーディング・BOM・改行コードを取得（バイナリなら16進表示）
+                let path = path.clone();
+                spawn_local(async move {
+                    if let Ok(contents) = crate::tauri_bindings::read_text_file(&path, None).await {
+                        tabs.update(|tabs_vec| {
+                            if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                                tab.apply_file_contents(contents);
+                            }
+                        });
+                        render_trigger.update(|v| *v += 1);
+                    }
+                });
+            }
+
+            // ファイルを開いているグループ（なければフォーカス中のグループ）でアクティブにする
+            let group = groups.with_untracked(|g| g.group_for(&path));
+            group_control.activate(group, &path);
+
+            // 表示位置の指定があればカーソルを移動
+            if let Some((reveal_path, line, col)) = reveal_position.get_untracked() {
+                if reveal_path == path {
+                    let height = canvas_ref
+                        .get_untracked()
+                        .map(|c| c.height() as f64)
+                        .unwrap_or(600.0);
+                    tabs.update(|tabs_vec| {
+                        if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                            tab.reveal_position(line, col, height);
+                        }
+                    });
+                }
+            }
+
+            // ✅ LSP: Initialize LSP for the file and request diagnostics
+            let lsp_client = lsp.get_untracked();
+
+            spawn_local(async move {
+                leptos::logging::log!("🔍 LSP: Initializing for file: {}", path);
+
+                // Workspace folder of the file (parent directory for files outside the workspace)
+                let root_uri = match workspace.and_then(|w| w.root_for(&path)) {
+                    Some(root) => root,
+                    None => std::path::Path::new(&path)
+                        .parent()
+                        .map(|parent| parent.to_string_lossy().to_string())
+                        .unwrap_or_else(|| ".".to_string()),
+                };
+
+                // Initialize LSP server
+                match lsp_client.initialize(path.clone(), root_uri).await {
+                    Ok(_) => {
+                        leptos::logging::log!("✅ LSP: Initialized successfully");
+
+                        // Request initial diagnostics
+                        match lsp_client.request_diagnostics().await {
+                            Ok(diags) => {
+                                let count = diags.len();
+                                diagnostics.set(diags);
+                                leptos::logging::log!("✅ LSP: Diagnostics loaded: {} items", count);
+                            }
+                            Err(e) => {
+                                leptos::logging::log!("❌ LSP: Diagnostics error: {:?}", e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        leptos::logging::log!("❌ LSP: Initialization error: {:?}", e);
+                    }
+                }
+            });
+
+            render_trigger.set(0);
+        }
+    });
+
+    // 既に開いているファイルへの位置指定（ターミナルのリンクなど）
+    Effect::new(move |_| {
+        let Some((path, line, col)) = reveal_position.get() else {
+            return;
+        };
+        let height = canvas_ref
+            .get_untracked()
+            .map(|c| c.height() as f64)
+            .unwrap_or(600.0);
+
+        if !tabs.with_untracked(|tabs_vec| tabs_vec.iter().any(|t| t.file_path == path)) {
+            return;
+        }
+        let group = groups.with_untracked(|g| g.group_for(&path));
+        group_control.activate(group, &path);
+        tabs.update(|tabs_vec| {
+            if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                tab.reveal_position(line, col, height);
+            }
+        });
+        render_trigger.update(|v| *v += 1);
+    });
+
+    // 外部変更と未保存の編集をマージするビュー
+    let merge_view = RwSignal::new(Option::<ThreeWayMerge>::None);
+
+    // ディスク上で変更されたファイルを開いているタブに反映（未保存の編集があれば衝突として保持）
+    let apply_disk_content = move |path: String, content: String| {
+        let mut changed = false;
+        tabs.update(|tabs_vec| {
+            if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                changed = tab.sync_with_disk(&content) != DiskSync::Unchanged;
+            }
+        });
+        if changed {
+            render_trigger.update(|v| *v += 1);
+        }
+    };
+
+    Effect::new(move |_| {
+        let Some((path, content)) = file_reloaded.and_then(|f| f.get()) else {
+            return;
+        };
+        apply_disk_content(path, content);
+    });
+
+    // ウォッチャーが報告した変更のうち、開いているファイルを読み直す
+    Effect::new(move |_| {
+        let Some(batch) = fs_changes.and_then(|f| f.get()) else {
+            return;
+        };
+        let open_paths: Vec<String> = tabs.with_untracked(|tabs_vec| {
+            batch
+                .changes
+                .iter()
+                .filter_map(|change| change.changed_file())
+                .filter(|path| tabs_vec.iter().any(|t| t.file_path == *path))
+                .map(String::from)
+                .collect()
+        });
+        for path in open_paths {
+            spawn_local(async move {
+                if let Ok(FileContents::Text { content, .. }) = crate::tauri_bindings::read_text_file(&path, None).await {
+                    apply_disk_content(path, content);
+                }
+            });
+        }
+    });
+
+    // 衝突したタブを `text` で解消（None なら自分の編集を維持）
+    let resolve_conflict = move |path: String, text: Option<String>| {
+        tabs.update(|tabs_vec| {
+            if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                let text = text.unwrap_or_else(|| tab.buffer.to_string());
+                tab.resolve_disk_conflict(&text);
+            }
+        });
+        merge_view.set(None);
+        render_trigger.update(|v| *v += 1);
+    };
+
+    let open_merge = move |path: String| {
+        let versions = tabs.with_untracked(|tabs_vec| {
+            tabs_vec.iter().find(|t| t.file_path == path).and_then(|tab| {
+                tab.disk_conflict
+                    .clone()
+                    .map(|disk| (tab.disk_text(), tab.buffer.to_string(), disk))
+            })
+        });
+        let Some((base, ours, theirs)) = versions else {
+            return;
+        };
+        spawn_local(async move {
+            match crate::tauri_bindings_watcher::merge_external_change(base.clone(), ours.clone(), theirs.clone()).await {
+                Ok(merged) => merge_view.set(Some(ThreeWayMerge {
+                    file_path: path,
+                    base: Some(base),
+                    ours: Some(ours),
+                    theirs: Some(theirs),
+                    result: merged.result,
+                })),
+                Err(e) => leptos::logging::error!("❌ Failed to merge {}: {}", path, e),
+            }
+        });
+    };
+
+    // アクティブなタブが外部変更と衝突していればそのパス
+    let active_conflict = Memo::new(move |_| {
+        let index = active_tab_index.get()?;
+        tabs.with(|tabs_vec| {
+            tabs_vec
+                .get(index)
+                .filter(|tab| tab.disk_conflict.is_some())
+                .map(|tab| tab.file_path.clone())
+        })
+    });
+
+    // 保存に失敗したタブ (path, error)
+    let active_save_error = Memo::new(move |_| {
+        let index = active_tab_index.get()?;
+        tabs.with(|tabs_vec| {
+            let tab = tabs_vec.get(index)?;
+            tab.save_error.clone().map(|error| (tab.file_path.clone(), error))
+        })
+    });
+
+    // アクティブなタブの形式をステータスバーへ
+    let current_format = Memo::new(move |_| {
+        let index = active_tab_index.get()?;
+        tabs.with(|tabs_vec| {
+            tabs_vec
+                .get(index)
+                .filter(|tab| tab.binary_size.is_none())
+                .map(|tab| tab.format.clone())
+        })
+    });
+    if let Some(active_format) = active_format {
+        Effect::new(move |_| active_format.set(current_format.get()));
+        on_cleanup(move || active_format.set(None));
+    }
+
+    // ステータスバーからの形式変更
+    Effect::new(move |_| {
+        let Some(change) = format_request.and_then(|r| r.get()) else {
+            return;
+        };
+        let Some(path) = active_tab_index
+            .get_untracked()
+            .and_then(|index| tabs.with_untracked(|tabs_vec| tabs_vec.get(index).map(|t| t.file_path.clone())))
+        else {
+            return;
+        };
+        let update_tab = move |path: &str, apply: &dyn Fn(&mut EditorTab)| {
             tabs.update(|tabs_vec| {
-                // 既存のタブを探す
-                if let Some(existing_index) = tabs_vec.iter().position(|t| &t.file_path == &path) {
-                    // 既存のタブをアクティブにする
-                    active_tab_index.set(Some(existing_index));
-                } else {
-                    // 新しいタブを追加
-                    tabs_vec.push(EditorTab::new(path.clone(), content.clone()));
-                    active_tab_index.set(Some(tabs_vec.len() - 1));
+                if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == path) {
+                    apply(tab);
                 }
             });
+            render_trigger.update(|v| *v += 1);
+        };
+        match change {
+            FormatChange::ReopenWithEncoding(encoding) => {
+                spawn_local(async move {
+                    match crate::tauri_bindings::read_text_file(&path, Some(&encoding)).await {
+                        Ok(FileContents::Text { content, format }) => {
+                            update_tab(&path, &|tab| tab.reopen_with(&content, format.clone()));
+                        }
+                        Ok(FileContents::Binary { .. }) => {}
+                        Err(e) => leptos::logging::error!("❌ Failed to reopen {} as {}: {}", path, encoding, e),
+                    }
+                });
+            }
+            change => update_tab(&path, &|tab| tab.change_format(&change)),
+        }
+    });
 
-            // ✅ LSP: Initialize LSP for the file and request diagnostics
-            let lsp_client = lsp.get_untracked();
+    // Ctrl/Cmd+N: 新規タブ（保存時に保存先を指定）
+    let _new_file_shortcut = window_event_listener(leptos::ev::keydown, move |ev| {
+        if !(ev.ctrl_key() || ev.meta_key()) || ev.shift_key() || ev.key().to_lowercase() != "n" {
+            return;
+        }
+        if !is_active.get_untracked() {
+            return;
+        }
+        ev.prevent_default();
+        let tab = tabs.with_untracked(|tabs_vec| EditorTab::untitled(&next_untitled_name(tabs_vec)));
+        let path = tab.file_path.clone();
+        tabs.update(|tabs_vec| tabs_vec.push(tab));
+        group_control.activate(groups.with_untracked(|g| g.focused), &path);
+        render_trigger.update(|v| *v += 1);
+    });
 
-            spawn_local(async move {
-                leptos::logging::log!("🔍 LSP: Initializing for file: {}", path);
+    // Ctrl/Cmd+\: 右に分割、Ctrl/Cmd+Shift+\: 下に分割、Ctrl/Cmd+1..9: N番目のグループへ、
+    // Ctrl/Cmd+Shift+V: Markdownプレビュー
+    let _group_shortcuts = window_event_listener(leptos::ev::keydown, move |ev| {
+        if !(ev.ctrl_key() || ev.meta_key()) || ev.alt_key() || !is_active.get_untracked() {
+            return;
+        }
+        if ev.code() == "Backslash" {
+            ev.prevent_default();
+            let orientation = if ev.shift_key() { Orientation::Vertical } else { Orientation::Horizontal };
+            group_control.change(|g| {
+                g.split(orientation);
+            });
+            render_trigger.update(|v| *v += 1);
+            return;
+        }
+        if ev.shift_key() && ev.code() == "KeyV" {
+            ev.prevent_default();
+            toggle_preview();
+            return;
+        }
+        let Some(n) = ev.code().strip_prefix("Digit").and_then(|d| d.parse::<usize>().ok()) else {
+            return;
+        };
+        if ev.shift_key() || n == 0 {
+            return;
+        }
+        ev.prevent_default();
+        group_control.change(|g| {
+            g.focus_nth(n - 1);
+        });
+        render_trigger.update(|v| *v += 1);
+    });
 
-                // Extract workspace root from file path (parent directory)
-                let root_uri = if let Some(parent) = std::path::Path::new(&path).parent() {
-                    parent.to_string_lossy().to_string()
-                } else {
-                    ".".to_string()
+    // ✅ Hot exit: 前回のセッションを復元し、未保存の編集を定期的にバックアップ
+    if let Some(hot_exit) = hot_exit {
+        let restoring = RwSignal::new(false);
+        Effect::new(move |_| {
+            let Some(restored) = hot_exit.restored.get() else {
+                return;
+            };
+            hot_exit.restored.set(None);
+            restoring.set(true);
+            spawn_local(async move {
+                let saved_groups = restored.session.editor_groups.clone();
+                let (mut restored_tabs, active) = restore_tabs(restored).await;
+                for tab in &mut restored_tabs {
+                    let unit = default_indent(&tab.file_path);
+                    tab.set_default_indent(unit);
+                }
+                // 復元中に開いたファイルはアクティブのまま後ろに残す
+                let opened_path = active_tab_index.get_untracked().and_then(|index| {
+                    tabs.with_untracked(|tabs_vec| tabs_vec.get(index).map(|t| t.file_path.clone()))
+                });
+                tabs.update(|tabs_vec| {
+                    tabs_vec.retain(|t| !restored_tabs.iter().any(|r| r.file_path == t.file_path));
+                    let opened = std::mem::replace(tabs_vec, restored_tabs);
+                    tabs_vec.extend(opened);
+                });
+
+                // 分割レイアウトを復元（分割前のセッションは1グループにまとめる）
+                let mut restored_groups = tabs.with_untracked(|tabs_vec| {
+                    let is_open = |path: &str| tabs_vec.iter().any(|t| t.file_path == path);
+                    let mut restored_groups = saved_groups
+                        .as_ref()
+                        .map(|saved| EditorGroups::from_session(saved, is_open))
+                        .unwrap_or_default();
+                    restored_groups.adopt(tabs_vec.iter().map(|t| t.file_path.as_str()));
+                    restored_groups
+                });
+                let active_path = opened_path.or_else(|| {
+                    let index = active.filter(|_| saved_groups.is_none())?;
+                    tabs.with_untracked(|tabs_vec| tabs_vec.get(index).map(|t| t.file_path.clone()))
+                });
+                if let Some(path) = active_path {
+                    let group = restored_groups.group_for(&path);
+                    restored_groups.focus(group);
+                    restored_groups.open(group, &path);
+                }
+                groups.set(restored_groups);
+                group_control.load_live();
+                restoring.set(false);
+                render_trigger.update(|v| *v += 1);
+            });
+        });
+
+        // 最後にバックアップした内容（パスごと）と保存したセッション
+        let backed_up = StoredValue::new(HashMap::<String, String>::new());
+        let saved_session = StoredValue::new(Option::<Session>::None);
+        let backup_tick = move || {
+            if !hot_exit.ready.get_untracked()
+                || restoring.get_untracked()
+                || hot_exit.restored.with_untracked(Option::is_some)
+            {
+                return;
+            }
+            let (session, backups, discards) = tabs.with_untracked(|tabs_vec| {
+                let active_tab = active_tab_index.get_untracked();
+                let live = active_tab.and_then(|index| tabs_vec.get(index)).map(EditorTab::view);
+                let session = Session {
+                    tabs: tabs_vec.iter().map(EditorTab::session_tab).collect(),
+                    active_tab,
+                    editor_groups: Some(groups.with_untracked(|g| g.to_session(live.as_ref()))),
+                    ..hot_exit.layout.get_untracked()
                 };
+                let (backups, discards) = backed_up.with_value(|map| pending_backups(tabs_vec, map));
+                (session, backups, discards)
+            });
+            backed_up.update_value(|map| {
+                for backup in &backups {
+                    map.insert(backup.path.clone(), backup.content.clone());
+                }
+                for path in &discards {
+                    map.remove(path);
+                }
+            });
+            let session_changed = saved_session.with_value(|saved| saved.as_ref() != Some(&session));
+            if session_changed {
+                saved_session.set_value(Some(session.clone()));
+            }
+            if backups.is_empty() && discards.is_empty() && !session_changed {
+                return;
+            }
 
-                // Initialize LSP server
-                match lsp_client.initialize(path.clone(), root_uri).await {
-                    Ok(_) => {
-                        leptos::logging::log!("✅ LSP: Initialized successfully");
-
-                        // Request initial diagnostics
-                        match lsp_client.request_diagnostics().await {
-                            Ok(diags) => {
-                                let count = diags.len();
-                                diagnostics.set(diags);
-                                leptos::logging::log!("✅ LSP: Diagnostics loaded: {} items", count);
-                            }
-                            Err(e) => {
-                                leptos::logging::log!("❌ LSP: Diagnostics error: {:?}", e);
-                            }
-                        }
+            use crate::tauri_bindings_hot_exit as bindings;
+            spawn_local(async move {
+                for backup in &backups {
+                    if let Err(e) = bindings::backup_buffer(backup).await {
+                        leptos::logging::warn!("Failed to back up {}: {}", backup.path, e);
                     }
-                    Err(e) => {
-                        leptos::logging::log!("❌ LSP: Initialization error: {:?}", e);
+                }
+                for path in &discards {
+                    let _ = bindings::discard_backup(path).await;
+                }
+                if session_changed {
+                    if let Err(e) = bindings::save_session(&session).await {
+                        leptos::logging::warn!("Failed to save session: {}", e);
                     }
                 }
             });
-
-            render_trigger.set(0);
+        };
+        if let Ok(handle) = set_interval_with_handle(
+            backup_tick,
+            Duration::from_secs(crate::hot_exit::BACKUP_INTERVAL_SECS),
+        ) {
+            on_cleanup(move || handle.clear());
         }
-    });
+    }
 
     // ⚠️ LSP: Buffer change detection temporarily disabled
     // This Effect was causing memory issues by creating too many spawn_local tasks
@@ -540,6 +1853,11 @@ pub fn VirtualEditorPanel(
             return;
         };
 
+        // バイナリファイルは読み取り専用
+        if tab.binary_size.is_some() {
+            return;
+        }
+
         let key = ev.key();
         let mut buffer_changed = false;
 
@@ -661,33 +1979,50 @@ pub fn VirtualEditorPanel(
 
         // Ctrl/Cmd + S (Save)
         if (ev.ctrl_key() || ev.meta_key()) && key.as_str() == "s" {
+            // 新規タブは保存先を尋ねる
+            if tab.is_untitled() {
+                let root = hot_exit
+                    .map(|h| h.layout.with_untracked(|s| s.root_path.clone()))
+                    .unwrap_or_default();
+                let suggested = if root.is_empty() {
+                    tab.title().to_string()
+                } else {
+                    format!("{}/{}", root, tab.title())
+                };
+                let Some(path) = web_sys::window()
+                    .and_then(|w| w.prompt_with_message_and_default("Save as", &suggested).ok().flatten())
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                else {
+                    return;
+                };
+                let untitled_path = tab.file_path.clone();
+                tab.save_as(path);
+                groups.update(|g| g.rename(&untitled_path, &tab.file_path));
+            }
+
             let file_path = tab.file_path.clone();
             let content = tab.buffer.to_string();
+            let format = tab.format.clone();
+            let previous = tab.saved_state();
 
-            // Tauri commandを使ってファイル保存
-            wasm_bindgen_futures::spawn_local(async move {
-                #[cfg(target_arch = "wasm32")]
-                {
-                    use wasm_bindgen::prelude::*;
-                    #[wasm_bindgen]
-                    extern "C" {
-                        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-                        async fn invoke(cmd: &str, args: JsValue) -> JsValue;
-                    }
-
-                    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
-                        "path": file_path,
-                        "contents": content,
-                    })).unwrap();
-
-                    match invoke("write_file", args).await {
-                        _ => {
-                            leptos::logging::log!("File saved: {}", file_path);
-                        }
+            // 元のエンコーディングと改行コードで保存（一時ファイル経由で置き換え）
+            spawn_local(async move {
+                match crate::tauri_bindings::write_text_file(&file_path, &content, &format).await {
+                    Ok(()) => leptos::logging::log!("File saved: {}", file_path),
+                    Err(e) => {
+                        leptos::logging::error!("❌ Failed to save {}: {}", file_path, e);
+                        tabs.update(|tabs_vec| {
+                            if let Some(tab) = tabs_vec.iter_mut().find(|t| t.file_path == file_path) {
+                                tab.save_failed(previous, e);
+                            }
+                        });
                     }
                 }
             });
 
+            // 保存した内容が新しい基準（外部変更との衝突も上書きで解消）
+            tab.mark_saved();
             current_tab.set(Some(tab));
             return;
         }
@@ -766,6 +2101,59 @@ pub fn VirtualEditorPanel(
             return;
         }
 
+        // 構文を使った編集コマンド
+        let ctrl = ev.ctrl_key() || ev.meta_key();
+        let handled = match (key.as_str(), ev.code().as_str()) {
+            // Alt+Shift+→/←: 構文単位で選択を拡大/縮小
+            ("ArrowRight", _) if ev.alt_key() && ev.shift_key() => tab.expand_selection() || true,
+            ("ArrowLeft", _) if ev.alt_key() && ev.shift_key() => tab.shrink_selection() || true,
+            // Alt+↑/↓: 行（ブロックを開く行はブロックごと）を移動
+            ("ArrowUp" | "ArrowDown", _) if ev.alt_key() && !ev.shift_key() => {
+                let up = key == "ArrowUp";
+                tab.apply_text_command(|parsed, _, start, end| smart_edit::move_lines(parsed, start, end, up));
+                true
+            }
+            // Ctrl/Cmd+Shift+M: 対応する括弧へ
+            (_, "KeyM") if ctrl && ev.shift_key() => tab.jump_to_matching_bracket() || true,
+            // Ctrl/Cmd+/: 行コメント、Ctrl/Cmd+Shift+/: ブロックコメント
+            (_, "Slash") if ctrl => {
+                let block = ev.shift_key();
+                tab.apply_text_command(|parsed, syntax, start, end| {
+                    if block {
+                        smart_edit::toggle_block_comment(parsed.text(), syntax, start, end)
+                    } else {
+                        smart_edit::toggle_line_comment(parsed.text(), syntax, start, end)
+                    }
+                });
+                true
+            }
+            // Tab/Shift+Tab: インデント
+            ("Tab", _) if !ctrl && !ev.alt_key() => {
+                tab.indent(ev.shift_key());
+                true
+            }
+            // Enter: 言語に合わせたインデント
+            ("Enter", _) if !ctrl => {
+                tab.smart_newline();
+                true
+            }
+            // Backspace: 空の括弧ペアとインデントをまとめて削除
+            ("Backspace", _) if !ctrl => tab.smart_backspace(),
+            // 括弧・引用符の自動クローズと選択範囲の囲み
+            (k, _) if !ctrl && !ev.alt_key() && k.chars().count() == 1 => {
+                tab.smart_type(k.chars().next().unwrap_or_default())
+            }
+            _ => false,
+        };
+        if handled {
+            if let Some(canvas) = canvas_ref.get() {
+                tab.scroll_into_view(canvas.height() as f64);
+            }
+            current_tab.set(Some(tab));
+            render_trigger.update(|v| *v += 1);
+            return;
+        }
+
         match key.as_str() {
             // 英数字・記号の入力
             k if k.len() == 1 && !ev.ctrl_key() && !ev.meta_key() => {
@@ -1086,7 +2474,7 @@ pub fn VirtualEditorPanel(
 
         // 確定文字をバッファに挿入
         if !data.is_empty() {
-            if let Some(mut tab) = current_tab.get() {
+            if let Some(mut tab) = current_tab.get().filter(|t| t.binary_size.is_none()) {
                 let old_col = tab.cursor_col;
                 let char_idx = tab.buffer.line_to_char(tab.cursor_line) + tab.cursor_col;
                 tab.buffer.insert(char_idx, &data);
@@ -1137,6 +2525,17 @@ pub fn VirtualEditorPanel(
 
         // カーソル位置を計算
         if let Ok(renderer) = CanvasRenderer::new((*canvas).clone().unchecked_into()) {
+            // Gutter clicks act on line decorations (e.g. run the test on that line)
+            if x <= renderer.gutter_width() {
+                if let Some(callback) = on_gutter_click {
+                    let line = ((y + tab.scroll_top) / LINE_HEIGHT).floor() as usize;
+                    if line < tab.buffer.len_lines() {
+                        callback.run((tab.file_path.clone(), line));
+                    }
+                }
+                return;
+            }
+
             // ガター幅を超えているか確認
             if x > renderer.gutter_width() {
                 let text_x = x - renderer.gutter_width() - 15.0;
@@ -1287,8 +2686,9 @@ pub fn VirtualEditorPanel(
 
                         match lsp_client.request_hover(position).await {
                             Ok(Some(info)) => {
+                                let (offset_x, offset_y) = pane_offset.get_untracked();
                                 hover_info.set(Some(info));
-                                hover_pixel_position.set(Some((x, y)));
+                                hover_pixel_position.set(Some((x + offset_x, y + offset_y)));
                                 leptos::logging::log!("✅ LSP: Hover info received");
                             }
                             _ => {
@@ -1432,378 +2832,376 @@ pub fn VirtualEditorPanel(
         render_trigger.update(|v| *v += 1);
     };
 
-    // Canvasのリサイズとレンダリング
-    Effect::new(move |_| {
-        // render_triggerに依存して、変更時に再描画
-        let _ = render_trigger.get();
-
-        let Some(canvas) = canvas_ref.get() else {
-            leptos::logging::log!("❌ Canvas ref not available");
-            return;
-        };
-
-        // Canvas の親要素(.berry-editor-pane)のサイズを取得
-        let Some(parent) = canvas.parent_element() else {
-            leptos::logging::log!("❌ Canvas parent not available");
-            return;
-        };
-
-        let rect = parent.get_bounding_client_rect();
-        let mut width = rect.width();
-        let mut height = rect.height();
-
-        // ✅ 高さが0の場合は、フォールバックとして親要素から取得を試みる
-        if height <= 0.0 {
-            leptos::logging::log!("⚠️ Parent height is 0, trying grandparent...");
+    // 分割したグループのレイアウト（分割・結合したときだけペインを作り直す）
+    let layout = Memo::new(move |_| groups.with(|g| g.layout.clone()));
 
-            if let Some(grandparent) = parent.parent_element() {
-                let gp_rect = grandparent.get_bounding_client_rect();
-                leptos::logging::log!(
-                    "📏 Grandparent (.berry-editor-main): {}x{}, class: {}",
-                    gp_rect.width(),
-                    gp_rect.height(),
-                    grandparent.class_name()
-                );
-
-                if gp_rect.height() > 0.0 {
-                    height = gp_rect.height();
-                    leptos::logging::log!("✅ Using grandparent height: {}", height);
-                }
-            }
+    // プレビューする文書 (path, text): フォーカス中のグループのMarkdownファイル
+    let preview_source = Memo::new(move |_| {
+        let path = groups.with(|g| g.active_path().map(str::to_string)).filter(|p| is_markdown_path(p))?;
+        let text = tabs.with(|tabs_vec| tabs_vec.iter().find(|t| t.file_path == path).map(|t| t.buffer.to_string()))?;
+        Some((path, text))
+    });
+    let show_preview = Memo::new(move |_| preview_open.get() && preview_source.with(Option::is_some));
+    // エディタの先頭行（プレビューのスクロール同期用）
+    let preview_top_line = Memo::new(move |_| {
+        let index = active_tab_index.get()?;
+        tabs.with(|tabs_vec| tabs_vec.get(index).map(|t| (t.scroll_top / LINE_HEIGHT) as usize))
+    });
 
-            // それでも0なら、最低限の高さを確保
-            if height <= 0.0 {
-                height = 500.0; // フォールバック高さ
-                leptos::logging::log!("⚠️ Using fallback height: {}", height);
+    // 各グループのペイン：タブバーとキャンバス（スクロール位置とカーソルはグループごと）
+    let group_pane = move |id: GroupId| -> AnyView {
+        let pane_canvas = NodeRef::<Canvas>::new();
+        canvases.update_value(|map| {
+            map.insert(id, pane_canvas);
+        });
+        let scroll = StoredValue::new(VirtualScroll::new(0, 0.0, LINE_HEIGHT));
+        let is_focused = Memo::new(move |_| groups.with(|g| g.focused == id));
+        let pane_path = Memo::new(move |_| groups.with(|g| g.group(id).and_then(|group| group.active.clone())));
+
+        // 16進表示するバイナリファイル (path, size)
+        let pane_binary = Memo::new(move |_| {
+            let path = pane_path.get()?;
+            let size = tabs.with(|tabs_vec| tabs_vec.iter().find(|t| t.file_path == path)?.binary_size)?;
+            Some((path, size))
+        });
+
+        // Canvasのリサイズとレンダリング
+        Effect::new(move |_| {
+            // render_triggerに依存して、変更時に再描画
+            render_trigger.track();
+
+            let Some(canvas) = pane_canvas.get() else {
+                return;
+            };
+            // Canvas の親要素(.berry-editor-group-body)のサイズ
+            let Some(parent) = canvas.parent_element() else {
+                return;
+            };
+            let rect = parent.get_bounding_client_rect();
+            let width = rect.width();
+            // レイアウト前で高さが0なら、最低限の高さを確保
+            let height = if rect.height() > 0.0 { rect.height() } else { 500.0 };
+            if width <= 0.0 {
+                return;
             }
-        }
-
-        leptos::logging::log!(
-            "📏 Final canvas size: {}x{}",
-            width,
-            height
-        );
-
-        // サイズチェック（フォールバック後もまだ無効なら）
-        if width <= 0.0 || height <= 0.0 {
-            leptos::logging::log!("❌ Invalid canvas size after fallback: {}x{}", width, height);
-            return;
-        }
-
-        // Retinaディスプレイ対応: devicePixelRatioを取得
-        let window = web_sys::window().expect("no global window");
-        let dpr = window.device_pixel_ratio();
-
-        leptos::logging::log!(
-            "✅ Canvas resize: parent={}x{}, dpr={}, physical={}x{}",
-            width,
-            height,
-            dpr,
-            (width * dpr) as u32,
-            (height * dpr) as u32
-        );
-
-        // ✅ Canvas要素をHtmlCanvasElementにキャスト
-        let canvas_el: HtmlCanvasElement = (*canvas).clone().unchecked_into();
-
-        // ✅ CSSサイズを明示的に設定（論理ピクセル）
-        // web_sys::HtmlElement の style() を使用するために、明示的にキャストしてアクセス
-        use wasm_bindgen::JsCast;
-        let html_el: &web_sys::HtmlElement = canvas_el.as_ref();
-        let _ = html_el
-            .style()
-            .set_property("width", &format!("{}px", width));
-        let _ = html_el
-            .style()
-            .set_property("height", &format!("{}px", height));
-
-        // ✅ 物理ピクセルサイズを設定（Retina対応）
-        canvas_el.set_width((width * dpr) as u32);
-        canvas_el.set_height((height * dpr) as u32);
-
-        // Note: CSSサイズ（論理ピクセル）と物理サイズ（実ピクセル）を分離することで、
-        // Retinaディスプレイで高解像度レンダリングを実現し、ぼやけを完全に解消
-
-        // レンダリング
-        let tab_data = current_tab.get();
-        if tab_data.is_none() {
-            leptos::logging::log!("⚠️ No tab data available for rendering");
-            return;
-        }
-
-        if let Some(tab) = tab_data {
-            leptos::logging::log!(
-                "🎨 Rendering tab: {} lines, cursor at ({}, {})",
-                tab.buffer.len_lines(),
-                tab.cursor_line,
-                tab.cursor_col
-            );
-
-            if let Ok(renderer) = CanvasRenderer::new(canvas_el) {
-                // Canvas全体をクリア
-                renderer.clear(width as f64, height as f64);
 
-                // 可視範囲の行を計算
-                let start_line = (tab.scroll_top / LINE_HEIGHT).floor() as usize;
-                let visible_lines = (height as f64 / LINE_HEIGHT).ceil() as usize + 1;
-                let end_line = (start_line + visible_lines).min(tab.buffer.len_lines());
+            // Retinaディスプレイ対応: CSSサイズ（論理ピクセル）と物理サイズ（実ピクセル）を分離
+            let dpr = web_sys::window().map(|w| w.device_pixel_ratio()).unwrap_or(1.0);
+            let html_el: &web_sys::HtmlElement = canvas.as_ref();
+            let _ = html_el.style().set_property("width", &format!("{}px", width));
+            let _ = html_el.style().set_property("height", &format!("{}px", height));
+            canvas.set_width((width * dpr) as u32);
+            canvas.set_height((height * dpr) as u32);
 
-                // 行番号ガターを描画
-                renderer.draw_gutter(start_line, end_line, height as f64);
+            let Some(path) = pane_path.get() else {
+                return;
+            };
+            let focused = is_focused.get();
+            let composing = composing_text.get();
+            let decorations = line_decorations.map(|d| d.get()).unwrap_or_default();
+            let stored_view = groups.with(|g| g.view(id, &path).cloned()).unwrap_or_default();
 
-                // 選択範囲を描画（テキストの背景として）
-                if tab.has_selection() {
-                    if let (Some((start_line, start_col)), Some((end_line, end_col))) =
-                        (tab.selection_start, tab.selection_end) {
-                        leptos::logging::log!("🎨 Drawing selection: ({}, {}) to ({}, {})", start_line, start_col, end_line, end_col);
-
-                        // 行番号から行のテキストを取得するクロージャを作成
-                        let buffer = &tab.buffer;
-                        let get_line_text = |line_num: usize| -> String {
-                            buffer
-                                .line(line_num)
-                                .map(|s| s.trim_end_matches('\n').to_string())
-                                .unwrap_or_default()
-                        };
-
-                        // 選択範囲の正規化（逆方向選択に対応）
-                        let (norm_start_line, norm_start_col, norm_end_line, norm_end_col) =
-                            if start_line > end_line || (start_line == end_line && start_col > end_col) {
-                                // 逆方向選択 - 座標を入れ替える
-                                (end_line, end_col, start_line, start_col)
-                            } else {
-                                // 順方向選択 - そのまま
-                                (start_line, start_col, end_line, end_col)
-                            };
-
-                        renderer.draw_selection(
-                            norm_start_line,
-                            norm_start_col,
-                            norm_end_line,
-                            norm_end_col,
-                            tab.scroll_top,
-                            get_line_text,
-                        );
+            tabs.with(|tabs_vec| {
+                let Some(tab) = tabs_vec.iter().find(|t| t.file_path == path) else {
+                    return;
+                };
+                // フォーカス中のグループのカーソルはタブ側が最新
+                let view = if focused { tab.view() } else { stored_view };
+                scroll.update_value(|scroll| {
+                    scroll.set_viewport_height(height);
+                    scroll.set_total_lines(tab.buffer.len_lines());
+                    scroll.set_scroll_top(view.scroll_top);
+                });
+                let Ok(renderer) = CanvasRenderer::new(canvas.clone()) else {
+                    return;
+                };
+                let composing = focused.then_some(composing.as_str());
+                let cursor = scroll.with_value(|scroll| {
+                    draw_pane(&renderer, scroll, tab, &view, &decorations, composing, (width, height))
+                });
+
+                if let Some((pixel_x, pixel_y)) = cursor {
+                    cursor_x.set(pixel_x);
+                    cursor_y.set(pixel_y);
+                    if let Some(panes) = panes_ref.get_untracked() {
+                        let area = panes.get_bounding_client_rect();
+                        let canvas_rect = canvas.get_bounding_client_rect();
+                        pane_offset.set((canvas_rect.left() - area.left(), canvas_rect.top() - area.top()));
                     }
                 }
+            });
+        });
 
-                // テキスト行を描画（シンタックスハイライト付き）
-                let theme = EditorTheme::current();
-                let language = tab.language.as_deref(); // Option<String> -> Option<&str>
-                for line_num in start_line..end_line {
-                    // Ropeから行のテキストを取得（改行を除く）
-                    let line_text = tab
-                        .buffer
-                        .line(line_num)
-                        .map(|s| s.trim_end_matches('\n').to_string())
-                        .unwrap_or_default();
-
-                    let y_offset = (line_num - start_line) as f64 * LINE_HEIGHT;
-                    renderer.draw_line_highlighted(y_offset, &line_text, theme, language);
+        // フォーカスしていないグループはそのグループのスクロール位置だけを動かす
+        let on_pane_wheel = move |ev: leptos::ev::WheelEvent| {
+            if is_focused.get_untracked() {
+                on_wheel(ev);
+                return;
+            }
+            ev.prevent_default();
+            let Some(path) = pane_path.get_untracked() else {
+                return;
+            };
+            let delta = (ev.delta_y() / LINE_HEIGHT).round() * LINE_HEIGHT;
+            groups.update(|g| {
+                if let Some(view) = g.view_mut(id, &path) {
+                    scroll.update_value(|scroll| scroll.set_scroll_top(view.scroll_top + delta));
+                    view.scroll_top = scroll.with_value(VirtualScroll::scroll_top);
                 }
+            });
+        };
 
-                // カーソルを描画（現在行のテキストを渡す）
-                // ✅ FIX: 改行を除いたテキストを渡す（改行があると文字数計算がずれる）
-                let cursor_line_text = tab.buffer.line(tab.cursor_line)
-                    .map(|s| s.trim_end_matches('\n').to_string())
-                    .unwrap_or_default();
-
-                // IME未確定文字列を取得
-                let composing = composing_text.get();
-
-                // IME組成中は、仮想的なテキスト（確定文字+未確定文字）を作成してカーソル位置を計算
-                let (virtual_line_text, cursor_col_display) = if !composing.is_empty() {
-                    // 未確定文字列がある場合、カーソル位置に挿入した仮想テキストを作る
-                    let before: String = cursor_line_text.chars().take(tab.cursor_col).collect();
-                    let after: String = cursor_line_text.chars().skip(tab.cursor_col).collect();
-                    let virtual_text = format!("{}{}{}", before, composing, after);
-                    let virtual_col = tab.cursor_col + composing.chars().count();
-                    (virtual_text, virtual_col)
-                } else {
-                    (cursor_line_text.clone(), tab.cursor_col)
-                };
-
-                leptos::logging::log!(
-                    "🎯 Drawing cursor: line={}, col={} (display_col={}), composing='{}', line_text='{}' (len={})",
-                    tab.cursor_line,
-                    tab.cursor_col,
-                    cursor_col_display,
-                    &composing,
-                    &cursor_line_text,
-                    cursor_line_text.chars().count()
-                );
-
-                // カーソルを描画（composing中は未確定文字列の後ろに表示）
-                renderer.draw_cursor(tab.cursor_line, cursor_col_display, tab.scroll_top, &virtual_line_text);
-
-                // IME未確定文字列を描画（あれば）
-                if !composing.is_empty() {
-                    // 全角文字を考慮してカーソル位置までの実際の幅を測定
-                    let text_before_cursor: String = cursor_line_text
-                        .chars()
-                        .take(tab.cursor_col)
-                        .collect();
-                    let x = renderer.gutter_width() + 15.0
-                        + renderer.measure_text(&text_before_cursor);
-                    let y = tab.cursor_line as f64 * LINE_HEIGHT - tab.scroll_top + 15.0;
-
-                    // 未確定文字列をカーソル位置から描画（灰色）
-                    renderer.draw_text_at(x, y, &composing, "#808080");
+        let split = move |orientation: Orientation| {
+            group_control.change(|g| {
+                g.focus(id);
+                g.split(orientation);
+            });
+            render_trigger.update(|v| *v += 1);
+        };
 
-                    leptos::logging::log!("Drew composing text '{}' at ({}, {})", composing, x, y);
+        view! {
+            <div
+                class="berry-editor-group"
+                class:focused=move || is_focused.get() && groups.with(|g| g.groups.len() > 1)
+                on:dragover=move |ev: leptos::ev::DragEvent| {
+                    // グループ間のタブ移動だけを受け付ける
+                    if dragged_tab.with_untracked(Option::is_some) {
+                        ev.prevent_default();
+                    }
                 }
+                on:drop=move |ev: leptos::ev::DragEvent| {
+                    let Some((from, path)) = dragged_tab.get_untracked() else {
+                        return;
+                    };
+                    ev.prevent_default();
+                    dragged_tab.set(None);
+                    group_control.change(|g| g.move_tab(&path, from, id));
+                    render_trigger.update(|v| *v += 1);
+                }
+            >
+                <div class="berry-editor-group-header" style="display: flex; background: #313335; border-bottom: 1px solid #1E1F22; min-height: 35px;">
+                    // タブバー
+                    <div class="berry-editor-tabs" style="display: flex; flex: 1; min-width: 0; overflow-x: auto; scrollbar-width: thin; scrollbar-color: #4C4C4C #2B2B2B;">
+                        {move || {
+                            let (paths, active) = groups.with(|g| {
+                                g.group(id)
+                                    .map(|group| (group.tabs.clone(), group.active.clone()))
+                                    .unwrap_or_default()
+                            });
+
+                            if paths.is_empty() {
+                                return view! {
+                                    <div style="padding: 8px 16px; color: #606366; font-size: 13px;">
+                                        "No file open"
+                                    </div>
+                                }.into_any();
+                            }
 
-                // カーソル位置を計算（IME用）- 全角文字対応
-                // composing中は未確定文字列の後ろに配置
-                let text_before_cursor_display: String = virtual_line_text
-                    .chars()
-                    .take(cursor_col_display)
-                    .collect();
-                let cursor_pixel_x = renderer.gutter_width() + 15.0
-                    + renderer.measure_text(&text_before_cursor_display);
-                let cursor_pixel_y = tab.cursor_line as f64 * LINE_HEIGHT - tab.scroll_top;
+                            paths.into_iter().map(|path| {
+                                let is_active = active.as_ref() == Some(&path);
+                                let file_name = tab_title(&path).to_string();
 
-                cursor_x.set(cursor_pixel_x);
-                cursor_y.set(cursor_pixel_y);
+                                let tab_class = if is_active { "berry-tab active" } else { "berry-tab" };
+                                let bg_color = if is_active { "#1E1E1E" } else { "#2B2B2B" };
 
-                leptos::logging::log!(
-                    "Rendered {} lines ({}..{}), cursor at ({}, {})",
-                    end_line - start_line,
-                    start_line,
-                    end_line,
-                    cursor_pixel_x,
-                    cursor_pixel_y
-                );
-            }
-        }
-    });
+                                let (click_path, drag_path, close_path) = (path.clone(), path.clone(), path);
 
-    view! {
-        <div
-            node_ref=container_ref
-            class="berry-editor-main"
-            style="display: flex; flex-direction: column; flex: 1; min-width: 0; min-height: 0;"
-        >
-            // タブバー
-            <div class="berry-editor-tabs" style="display: flex; background: #313335; border-bottom: 1px solid #1E1F22; min-height: 35px; overflow-x: auto; scrollbar-width: thin; scrollbar-color: #4C4C4C #2B2B2B;">
-                {move || {
-                    let tabs_vec = current_tab.tabs.get();
-                    let active_index = current_tab.active_index.get();
-
-                    if tabs_vec.is_empty() {
-                        view! {
-                            <div style="padding: 8px 16px; color: #606366; font-size: 13px;">
-                                "No file open"
-                            </div>
-                        }.into_any()
-                    } else {
-                        // 全てのタブを表示
-                        tabs_vec.into_iter().enumerate().map(|(index, tab)| {
-                            let is_active = Some(index) == active_index;
-                            let file_name = tab.file_path
-                                .split('/')
-                                .last()
-                                .unwrap_or(&tab.file_path)
-                                .to_string();
-
-                            let tab_class = if is_active { "berry-tab active" } else { "berry-tab" };
-                            let bg_color = if is_active { "#1E1E1E" } else { "#2B2B2B" };
-
-                            // file_pathをクローンしてクロージャーで使う（indexは古くなる可能性があるため）
-                            let tab_path = tab.file_path.clone();
-                            let tab_path_for_close = tab_path.clone();
-
-                            view! {
-                                <div
-                                    class=tab_class
-                                    on:click=move |_| {
-                                        // クリック時に最新のindexを検索
-                                        let tabs_vec = current_tab.tabs.get();
-                                        if let Some(idx) = tabs_vec.iter().position(|t| t.file_path == tab_path) {
-                                            current_tab.active_index.set(Some(idx));
+                                view! {
+                                    <div
+                                        class=tab_class
+                                        draggable="true"
+                                        on:click=move |_| {
+                                            group_control.activate(id, &click_path);
+                                            render_trigger.update(|v| *v += 1);
                                         }
-                                    }
-                                    style=format!("
-                                        display: flex;
-                                        align-items: center;
-                                        padding: 8px 12px 8px 16px;
-                                        background: {};
-                                        border-right: 1px solid #323232;
-                                        color: #A9B7C6;
-                                        font-size: 13px;
-                                        font-family: 'JetBrains Mono', monospace;
-                                        gap: 8px;
-                                        cursor: pointer;
-                                        flex-shrink: 0;
-                                        white-space: nowrap;
-                                    ", bg_color)
-                                >
-                                    <span>{file_name}</span>
-                                    <button
-                                        on:click=move |ev| {
-                                            ev.stop_propagation();
-                                            // タブを閉じる（file_pathで検索して削除）
-                                            let mut tabs_vec = current_tab.tabs.get();
-                                            if let Some(close_index) = tabs_vec.iter().position(|t| t.file_path == tab_path_for_close) {
-                                                tabs_vec.remove(close_index);
-                                                current_tab.tabs.set(tabs_vec.clone());
-
-                                                // アクティブタブのインデックスを調整
-                                                if tabs_vec.is_empty() {
-                                                    // 全てのタブが閉じられた場合
-                                                    current_tab.active_index.set(None);
-                                                } else if Some(close_index) == current_tab.active_index.get() {
-                                                    // 閉じたタブがアクティブだった場合、前のタブか次のタブをアクティブにする
-                                                    let new_index = if close_index > 0 {
-                                                        close_index - 1 // 前のタブ
-                                                    } else {
-                                                        0 // 最初のタブが閉じられた場合は新しい最初のタブ
-                                                    };
-                                                    // tabs_vec.len() は少なくとも 1 なので、安全に -1 できる
-                                                    current_tab.active_index.set(Some(new_index.min(tabs_vec.len() - 1)));
-                                                } else if let Some(active_idx) = current_tab.active_index.get() {
-                                                    // 閉じたタブがアクティブタブより前にあった場合、インデックスを調整
-                                                    if close_index < active_idx {
-                                                        current_tab.active_index.set(Some(active_idx - 1));
-                                                    }
-                                                    // 閉じたタブがアクティブタブより後ろにある場合は調整不要
-                                                }
+                                        on:dragstart=move |ev: leptos::ev::DragEvent| {
+                                            if let Some(data) = ev.data_transfer() {
+                                                let _ = data.set_data("application/x-berry-tab", &drag_path);
                                             }
+                                            dragged_tab.set(Some((id, drag_path.clone())));
                                         }
-                                        style="
-                                            background: transparent;
-                                            border: none;
-                                            color: #606366;
-                                            cursor: pointer;
-                                            padding: 2px 4px;
-                                            font-size: 16px;
-                                            line-height: 1;
+                                        on:dragend=move |_| dragged_tab.set(None)
+                                        style=format!("
                                             display: flex;
                                             align-items: center;
-                                            justify-content: center;
-                                            border-radius: 2px;
-                                        "
-                                        onmouseover="this.style.background='#4E5157'; this.style.color='#A9B7C6';"
-                                        onmouseout="this.style.background='transparent'; this.style.color='#606366';"
+                                            padding: 8px 12px 8px 16px;
+                                            background: {};
+                                            border-right: 1px solid #323232;
+                                            color: #A9B7C6;
+                                            font-size: 13px;
+                                            font-family: 'JetBrains Mono', monospace;
+                                            gap: 8px;
+                                            cursor: pointer;
+                                            flex-shrink: 0;
+                                            white-space: nowrap;
+                                        ", bg_color)
                                     >
-                                        "×"
-                                    </button>
-                                </div>
+                                        <span>{file_name}</span>
+                                        <button
+                                            on:click=move |ev| {
+                                                ev.stop_propagation();
+                                                // グループのタブを閉じる（最後のタブならファイルも閉じる）
+                                                group_control.close(id, &close_path);
+                                                render_trigger.update(|v| *v += 1);
+                                            }
+                                            style="
+                                                background: transparent;
+                                                border: none;
+                                                color: #606366;
+                                                cursor: pointer;
+                                                padding: 2px 4px;
+                                                font-size: 16px;
+                                                line-height: 1;
+                                                display: flex;
+                                                align-items: center;
+                                                justify-content: center;
+                                                border-radius: 2px;
+                                            "
+                                            onmouseover="this.style.background='#4E5157'; this.style.color='#A9B7C6';"
+                                            onmouseout="this.style.background='transparent'; this.style.color='#606366';"
+                                        >
+                                            "×"
+                                        </button>
+                                    </div>
+                                }
+                            }).collect_view().into_any()
+                        }}
+                    </div>
+                    <div class="berry-editor-group-actions">
+                        {move || pane_path.get().filter(|p| is_markdown_path(p)).map(|_| view! {
+                            <i
+                                class="codicon codicon-open-preview"
+                                class:active=move || preview_open.get()
+                                title="Toggle Markdown Preview (Ctrl+Shift+V)"
+                                on:click=move |_| {
+                                    group_control.focus(id);
+                                    toggle_preview();
+                                }
+                            ></i>
+                        })}
+                        <i
+                            class="codicon codicon-split-horizontal"
+                            title="Split Right (Ctrl+\\)"
+                            on:click=move |_| split(Orientation::Horizontal)
+                        ></i>
+                        <i
+                            class="codicon codicon-split-vertical"
+                            title="Split Down (Ctrl+Shift+\\)"
+                            on:click=move |_| split(Orientation::Vertical)
+                        ></i>
+                    </div>
+                </div>
+
+                <div class="berry-editor-group-body">
+                    // バイナリファイルはテキストの代わりに16進表示
+                    {move || pane_binary.get().map(|(path, size)| view! {
+                        <div
+                            style="position: absolute; inset: 0; z-index: 5; display: flex;"
+                            on:mousedown=move |_| group_control.focus(id)
+                        >
+                            <HexView path=path size=size />
+                        </div>
+                    })}
+                    <canvas
+                        node_ref=pane_canvas
+                        on:mousedown=move |ev| {
+                            group_control.focus(id);
+                            on_mousedown(ev);
+                        }
+                        on:mousemove=move |ev| {
+                            if is_focused.get_untracked() {
+                                on_mousemove(ev);
                             }
-                        }).collect_view().into_any()
-                    }
-                }}
+                        }
+                        on:mouseup=on_mouseup
+                        on:dblclick=on_dblclick
+                        on:wheel=on_pane_wheel
+                    />
+                </div>
             </div>
+        }
+        .into_any()
+    };
 
-            <div class="berry-editor-pane" style="flex: 1; min-height: 0; display: flex; background: #1E1F22;">
-                <canvas
-                    node_ref=canvas_ref
-                    on:mousedown=on_mousedown
-                    on:mousemove=on_mousemove
-                    on:mouseup=on_mouseup
-                    on:dblclick=on_dblclick
-                    on:wheel=on_wheel
-                />
+    view! {
+        <div
+            node_ref=container_ref
+            class="berry-editor-main"
+            style="display: flex; flex-direction: column; flex: 1; min-width: 0; min-height: 0;"
+        >
+            // 外部変更と未保存の編集の衝突
+            {move || active_conflict.get().map(|path| {
+                let (compare_path, disk_path, keep_path) = (path.clone(), path.clone(), path);
+                let button_style = "background: #4E5157; border: none; color: #DFE1E5; padding: 2px 8px; cursor: pointer; font-size: 12px; border-radius: 3px;";
+                view! {
+                    <div class="berry-editor-conflict-bar" style="display: flex; align-items: center; gap: 8px; padding: 4px 10px; background: #49412C; color: #DFE1E5; font-size: 12px; border-bottom: 1px solid #1E1F22;">
+                        <i class="codicon codicon-warning" style="color: #E2C08D;"></i>
+                        <span style="flex: 1;">"The file changed on disk and this tab has unsaved edits."</span>
+                        <button style=button_style on:click=move |_| open_merge(compare_path.clone())>"Compare…"</button>
+                        <button
+                            style=button_style
+                            on:click=move |_| {
+                                let disk = tabs.with_untracked(|tabs_vec| {
+                                    tabs_vec.iter().find(|t| t.file_path == disk_path).and_then(|t| t.disk_conflict.clone())
+                                });
+                                if disk.is_some() {
+                                    resolve_conflict(disk_path.clone(), disk);
+                                }
+                            }
+                        >
+                            "Use Disk Version"
+                        </button>
+                        <button style=button_style on:click=move |_| resolve_conflict(keep_path.clone(), None)>"Keep My Edits"</button>
+                    </div>
+                }
+            })}
+
+            // 保存の失敗
+            {move || active_save_error.get().map(|(_, error)| view! {
+                <div class="berry-editor-save-error-bar" style="display: flex; align-items: center; gap: 8px; padding: 4px 10px; background: #5A1D1D; color: #DFE1E5; font-size: 12px; border-bottom: 1px solid #1E1F22;">
+                    <i class="codicon codicon-error" style="color: #F14C4C;"></i>
+                    <span style="flex: 1;">{format!("Save failed: {}", error)}</span>
+                </div>
+            })}
+
+            {move || merge_view.get().map(|versions| {
+                let path = versions.file_path.clone();
+                view! {
+                    <div class="berry-editor-merge-overlay" style="position: fixed; inset: 5%; z-index: 2000; background: #2B2D30; border: 1px solid #1E1F22; box-shadow: 0 8px 24px rgba(0, 0, 0, 0.5); overflow: auto;">
+                        <MergeEditor
+                            file_path=path.clone()
+                            versions=versions
+                            side_labels=("Editor", "Disk")
+                            on_resolved=Callback::new(move |text: String| resolve_conflict(path.clone(), Some(text)))
+                            on_close=Callback::new(move |_| merge_view.set(None))
+                        />
+                    </div>
+                }
+            })}
+
+            <div
+                node_ref=panes_ref
+                class="berry-editor-pane"
+                style="flex: 1; min-height: 0; display: flex; position: relative; background: #1E1F22;"
+            >
+                // 分割したエディタグループ
+                <div class="berry-editor-groups">
+                    {move || layout_view(layout.get(), group_pane)}
+                </div>
+
+                // フォーカス中のグループのMarkdownプレビュー
+                {move || show_preview.get().then(|| view! {
+                    <MarkdownPreview
+                        source=preview_source
+                        top_line=Signal::derive(move || preview_top_line.get().unwrap_or(0))
+                        on_open_location=Callback::new(move |location: (String, usize, usize)| {
+                            if let Some(callback) = on_open_location {
+                                callback.run(location);
+                            }
+                        })
+                        on_close=Callback::new(move |_| toggle_preview())
+                    />
+                })}
 
                 // 隠しinput要素（IME候補ウィンドウの位置制御用）
                 <input
@@ -1819,7 +3217,7 @@ pub fn VirtualEditorPanel(
                     on:blur=move |ev: leptos::ev::FocusEvent| {
                         leptos::logging::log!("❌ IME input BLURRED");
                         // 即座に再フォーカス（ただしIME composing中、ドラッグ中、またはエディタが非アクティブの場合は除く）
-                        if !is_composing.get() && !is_dragging.get() && is_active.get() {
+                        if !is_composing.get() && !is_dragging.get() && is_active.get() && merge_view.get_untracked().is_none() {
                             leptos::logging::log!("🔄 Editor is active, re-focusing...");
                             if let Some(input) = ime_input_ref.get() {
                                 // Use requestAnimationFrame to avoid immediate blur loop
@@ -1852,12 +3250,11 @@ pub fn VirtualEditorPanel(
                          padding: 0; \
                          margin: 0; \
                          caret-color: transparent;",
-                        cursor_x.get(),
-                        cursor_y.get(),
+                        pane_offset.get().0 + cursor_x.get(),
+                        pane_offset.get().1 + cursor_y.get(),
                         LINE_HEIGHT
                     )
                 />
-
                 // ✅ LSP: Completion Widget Overlay
                 {move || {
                     if show_completion.get() {
@@ -1875,10 +3272,11 @@ pub fn VirtualEditorPanel(
                                         &tab.buffer,
                                     );
 
+                                    let (offset_x, offset_y) = pane_offset.get();
                                     return view! {
                                         <CompletionWidget
                                             items=completion_items
-                                            position=Position::new(pixel_x as usize, (pixel_y + 20.0) as usize)
+                                            position=Position::new((pixel_x + offset_x) as usize, (pixel_y + offset_y + 20.0) as usize)
                                             on_select=move |item: CompletionItem| {
                                                 // Insert completion into buffer
                                                 tabs.update(|tabs_vec| {
@@ -1933,6 +3331,14 @@ pub fn VirtualEditorPanel(
                         });
                         render_trigger.update(|v| *v += 1);
                     }
+                    file_diagnostics=Signal::derive(move || {
+                        task_diagnostics.map(|d| d.get()).unwrap_or_default()
+                    })
+                    on_open_location=Callback::new(move |location: (String, usize, usize)| {
+                        if let Some(callback) = on_open_location {
+                            callback.run(location);
+                        }
+                    })
                 />
             </div>
         </div>
@@ -1965,3 +3371,163 @@ mod lsp_tests {
         assert_eq!(position.column, col);
     }
 }
+
+#[cfg(test)]
+mod disk_sync_tests {
+    use super::*;
+
+    fn tab(content: &str) -> EditorTab {
+        EditorTab::new("/ws/notes.txt".to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_clean_tab_reloads() {
+        let mut tab = tab("one\n");
+        assert!(!tab.is_dirty());
+        assert_eq!(tab.sync_with_disk("one\n"), DiskSync::Unchanged);
+        assert_eq!(tab.sync_with_disk("two\n"), DiskSync::Reloaded);
+        assert_eq!(tab.buffer.to_string(), "two\n");
+        assert!(!tab.is_dirty());
+    }
+
+    #[test]
+    fn test_dirty_tab_conflicts() {
+        let mut tab = tab("one\n");
+        tab.buffer.insert(0, "edited ");
+        assert!(tab.is_dirty());
+
+        // Our own save echoed back by the watcher is not a conflict
+        let mut saved = tab.clone();
+        assert_eq!(saved.sync_with_disk("edited one\n"), DiskSync::Unchanged);
+        assert!(!saved.is_dirty());
+
+        assert_eq!(tab.sync_with_disk("disk\n"), DiskSync::Conflict);
+        assert_eq!(tab.buffer.to_string(), "edited one\n");
+        assert_eq!(tab.disk_conflict.as_deref(), Some("disk\n"));
+        assert_eq!(tab.disk_text(), "one\n");
+
+        // Keeping the edits makes the disk content the new base
+        tab.resolve_disk_conflict("edited one\n");
+        assert_eq!(tab.disk_conflict, None);
+        assert_eq!(tab.disk_text(), "disk\n");
+        assert!(tab.is_dirty());
+
+        assert_eq!(tab.sync_with_disk("disk 2\n"), DiskSync::Conflict);
+        tab.resolve_disk_conflict("disk 2\n");
+        assert!(!tab.is_dirty());
+    }
+
+    #[test]
+    fn test_file_format() {
+        // CRLF files are edited with LF and remember their line ending
+        let mut tab = tab("one\r\ntwo\r\n");
+        assert_eq!(tab.buffer.to_string(), "one\ntwo\n");
+        assert_eq!(tab.format.line_ending, LineEnding::Crlf);
+        assert_eq!(tab.sync_with_disk("one\r\ntwo\r\n"), DiskSync::Unchanged);
+
+        // Switching the format is an unsaved change
+        tab.change_format(&FormatChange::SaveWithEncoding("Shift_JIS".to_string()));
+        assert!(tab.is_dirty());
+        let before = tab.saved_state();
+        tab.mark_saved();
+        assert!(!tab.is_dirty());
+        tab.save_failed(before, "cannot be saved as Shift_JIS".to_string());
+        assert!(tab.is_dirty());
+        assert!(tab.save_error.is_some());
+
+        let mut binary = EditorTab::new("/ws/logo.png".to_string(), String::new());
+        binary.apply_file_contents(FileContents::Binary { size: 42 });
+        assert_eq!(binary.binary_size, Some(42));
+
+        // The detected format replaces the provisional one from a clean tab
+        let mut latin1 = EditorTab::new("/ws/old.txt".to_string(), "caf\u{fffd}".to_string());
+        let format = TextFormat {
+            encoding: "windows-1252".to_string(),
+            ..TextFormat::default()
+        };
+        latin1.apply_file_contents(FileContents::Text {
+            content: "caf\u{e9}".to_string(),
+            format: format.clone(),
+        });
+        assert_eq!(latin1.buffer.to_string(), "caf\u{e9}");
+        assert_eq!(latin1.format, format);
+        assert!(!latin1.is_dirty());
+    }
+}
+
+#[cfg(test)]
+mod hot_exit_tests {
+    use super::*;
+
+    #[test]
+    fn test_untitled_tabs() {
+        let mut tabs = vec![EditorTab::new("/ws/main.rs".to_string(), String::new())];
+        assert_eq!(next_untitled_name(&tabs), "Untitled-1");
+        tabs.push(EditorTab::untitled("Untitled-3"));
+        assert_eq!(next_untitled_name(&tabs), "Untitled-4");
+
+        let mut tab = EditorTab::untitled("Untitled-1");
+        assert_eq!((tab.title(), tab.is_untitled(), tab.is_dirty()), ("Untitled-1", true, false));
+        tab.buffer.insert(0, "fn main() {}\n");
+        assert!(tab.is_dirty());
+        assert_eq!(tab.backup().base, None);
+
+        tab.save_as("/ws/scratch.rs".to_string());
+        assert_eq!((tab.title(), tab.is_untitled()), ("scratch.rs", false));
+        assert_eq!(tab.language.as_deref(), Some("rust"));
+        assert!(tab.is_dirty());
+    }
+
+    #[test]
+    fn test_pending_backups() {
+        let mut dirty = EditorTab::new("/ws/a.txt".to_string(), "one\n".to_string());
+        dirty.buffer.insert(0, "zero\n");
+        let clean = EditorTab::new("/ws/b.txt".to_string(), "two\n".to_string());
+        let mut tabs = vec![dirty, clean];
+
+        let mut backed_up = HashMap::new();
+        let (backups, discards) = pending_backups(&tabs, &backed_up);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].base.as_deref(), Some("one\n"));
+        assert_eq!(backups[0].content, "zero\none\n");
+        assert!(discards.is_empty());
+
+        // Unchanged since the last backup: nothing to do
+        backed_up.insert("/ws/a.txt".to_string(), "zero\none\n".to_string());
+        assert_eq!(pending_backups(&tabs, &backed_up), (vec![], vec![]));
+
+        // Saved: the backup is dropped
+        tabs[0].mark_saved();
+        assert_eq!(pending_backups(&tabs, &backed_up), (vec![], vec!["/ws/a.txt".to_string()]));
+    }
+
+    #[test]
+    fn test_restore_from_backup() {
+        let restored = |disk: Option<&str>| RestoredBackup {
+            backup: Backup {
+                path: "/ws/a.txt".to_string(),
+                base: Some("one\ntwo\n".to_string()),
+                content: "one\nmine\n".to_string(),
+            },
+            disk: disk.map(str::to_string),
+        };
+
+        let mut tab = EditorTab::from_backup(&restored(None));
+        assert_eq!(tab.buffer.to_string(), "one\nmine\n");
+        assert!(tab.is_dirty());
+        assert_eq!(tab.disk_conflict, None);
+        tab.restore_position(&SessionTab {
+            path: tab.file_path.clone(),
+            cursor_line: 9,
+            cursor_col: 9,
+            scroll_top: 40.0,
+        });
+        assert_eq!((tab.cursor_line, tab.cursor_col, tab.scroll_top), (2, 0, 40.0));
+
+        // The file changed on disk: keep the edits and offer a merge
+        let tab = EditorTab::from_backup(&restored(Some("one\ntheirs\n")));
+        assert_eq!(tab.buffer.to_string(), "one\nmine\n");
+        assert_eq!(tab.disk_conflict.as_deref(), Some("one\ntheirs\n"));
+        assert_eq!(tab.disk_text(), "one\ntwo\n");
+    }
+}
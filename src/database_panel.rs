@@ -2,7 +2,18 @@ use leptos::prelude::*;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // Re-export backend types for frontend use
-pub use crate::tauri_bindings_database::{DbConnection, DbType, ConnectionTestResult};
+pub use crate::tauri_bindings_database::{DbConnection, DbType, ConnectionTestResult, PasswordSource};
+use crate::common::dialogs::ConfirmDialog;
+use crate::sql_completion::{self, SqlCompletions};
+use crate::tauri_bindings_database::{
+    ColumnInfo, ColumnValue, DatabaseSchema, ExportFormat, PlanNode, QueryHistoryEntry, QueryPlan, QueryResult, RowChange,
+    TableEdit, TableInfo, TableKind,
+};
+
+/// Rows fetched per page of a result
+const PAGE_SIZE: usize = 200;
+/// Pause in typing before the query history is searched
+const HISTORY_SEARCH_DELAY_MS: u32 = 250;
 
 #[component]
 pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
@@ -20,6 +31,10 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
     let form_database = RwSignal::new(String::new());
     let form_username = RwSignal::new(String::new());
     let form_password = RwSignal::new(String::new());
+    // Password source kind ("stored", "env", "pgpass") and its variable or file
+    let form_password_kind = RwSignal::new(String::from("stored"));
+    let form_password_ref = RwSignal::new(String::new());
+    let save_error = RwSignal::new(None::<String>);
     let form_ssl = RwSignal::new(false);
     let test_result = RwSignal::new(None::<ConnectionTestResult>);
     let is_testing = RwSignal::new(false);
@@ -76,6 +91,8 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                 </button>
             </div>
 
+            <CredentialStore on_import=Callback::new(move |_| reload_connections()) />
+
             <div class="db-connection-list" style="
                 flex: 1;
                 overflow-y: auto;
@@ -202,6 +219,13 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                 }}
             </div>
 
+            // Schema browser and query console for the selected connection
+            {move || {
+                let selected = selected_connection.get()?;
+                let connection = connections.get().into_iter().find(|c| c.id == selected)?;
+                Some(view! { <ConnectionWorkspace connection=connection /> })
+            }}
+
             // Add/Edit Dialog (Modal)
             {move || {
                 if show_add_dialog.get() || show_edit_dialog.get() {
@@ -214,7 +238,11 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                             form_port.set(conn.port.map(|p| p.to_string()).unwrap_or_default());
                             form_database.set(conn.database.clone());
                             form_username.set(conn.username.clone().unwrap_or_default());
-                            form_password.set(conn.password.clone().unwrap_or_default());
+                            // Saved passwords stay in the backend; empty keeps them
+                            form_password.set(String::new());
+                            let (kind, reference) = password_source_parts(&conn.password_source);
+                            form_password_kind.set(kind.to_string());
+                            form_password_ref.set(reference);
                             form_ssl.set(conn.ssl);
                         }
                     } else {
@@ -226,11 +254,18 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                         form_database.set(String::new());
                         form_username.set(String::new());
                         form_password.set(String::new());
+                        form_password_kind.set(String::from("stored"));
+                        form_password_ref.set(String::new());
                         form_ssl.set(false);
                         test_result.set(None);
                     }
 
                     let is_sqlite = move || matches!(form_db_type.get(), DbType::SQLite);
+                    let form_source = move || password_source(&form_password_kind.get(), &form_password_ref.get());
+                    let form_password_value = move || {
+                        let password = form_password.get();
+                        (!is_sqlite() && form_source() == PasswordSource::Stored && !password.is_empty()).then_some(password)
+                    };
 
                     view! {
                         <div style="
@@ -439,26 +474,50 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                     // Password (not for SQLite)
                                     {move || {
                                         if !is_sqlite() {
+                                            let field_style = "
+                                                width: 100%;
+                                                padding: 6px 8px;
+                                                background: #1e1e1e;
+                                                border: 1px solid #3e3e3e;
+                                                border-radius: 4px;
+                                                color: #cccccc;
+                                                font-size: 12px;
+                                            ";
                                             view! {
                                                 <div>
                                                     <label style="display: block; margin-bottom: 6px; font-size: 12px; color: #cccccc;">
                                                         "Password"
                                                     </label>
-                                                    <input
-                                                        type="password"
-                                                        prop:value=move || form_password.get()
-                                                        on:input=move |ev| form_password.set(event_target_value(&ev))
-                                                        placeholder="••••••••"
-                                                        style="
-                                                            width: 100%;
-                                                            padding: 6px 8px;
-                                                            background: #1e1e1e;
-                                                            border: 1px solid #3e3e3e;
-                                                            border-radius: 4px;
-                                                            color: #cccccc;
-                                                            font-size: 12px;
-                                                        "
-                                                    />
+                                                    <div style="display: flex; gap: 8px;">
+                                                        <select
+                                                            on:change=move |ev| form_password_kind.set(event_target_value(&ev))
+                                                            style=field_style
+                                                        >
+                                                            <option value="stored" selected=move || form_password_kind.get() == "stored">"Saved (encrypted)"</option>
+                                                            <option value="env" selected=move || form_password_kind.get() == "env">"Environment variable"</option>
+                                                            <option value="pgpass" selected=move || form_password_kind.get() == "pgpass">".pgpass file"</option>
+                                                        </select>
+                                                        {move || match form_password_kind.get().as_str() {
+                                                            "stored" => view! {
+                                                                <input
+                                                                    type="password"
+                                                                    prop:value=move || form_password.get()
+                                                                    on:input=move |ev| form_password.set(event_target_value(&ev))
+                                                                    placeholder=move || if show_edit_dialog.get() { "Unchanged" } else { "••••••••" }
+                                                                    style=field_style
+                                                                />
+                                                            }.into_any(),
+                                                            kind => view! {
+                                                                <input
+                                                                    type="text"
+                                                                    prop:value=move || form_password_ref.get()
+                                                                    on:input=move |ev| form_password_ref.set(event_target_value(&ev))
+                                                                    placeholder=if kind == "env" { "DB_PASSWORD" } else { "~/.pgpass" }
+                                                                    style=field_style
+                                                                />
+                                                            }.into_any(),
+                                                        }}
+                                                    </div>
                                                 </div>
                                             }.into_any()
                                         } else {
@@ -487,6 +546,10 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                         }
                                     }}
 
+                                    {move || save_error.get().map(|e| view! {
+                                        <div style="color: #f48771; font-size: 12px;">{e}</div>
+                                    })}
+
                                     // Test Result Display
                                     {move || {
                                         if let Some(result) = test_result.get() {
@@ -535,14 +598,20 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                             test_result.set(None);
 
                                             let conn = DbConnection {
-                                                id: String::new(),
+                                                // Lets the backend use the saved password of an edited connection
+                                                id: if show_edit_dialog.get() {
+                                                    edit_connection.get().map(|c| c.id).unwrap_or_default()
+                                                } else {
+                                                    String::new()
+                                                },
                                                 name: form_name.get(),
                                                 db_type: form_db_type.get(),
                                                 host: if is_sqlite() { None } else { Some(form_host.get()) },
                                                 port: if is_sqlite() { None } else { form_port.get().parse().ok() },
                                                 database: form_database.get(),
                                                 username: if is_sqlite() { None } else { Some(form_username.get()) },
-                                                password: if is_sqlite() { None } else { Some(form_password.get()) },
+                                                password: form_password_value(),
+                                                password_source: form_source(),
                                                 ssl: form_ssl.get(),
                                                 created_at: 0,
                                                 last_used: None,
@@ -586,6 +655,7 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                                 show_add_dialog.set(false);
                                                 show_edit_dialog.set(false);
                                                 test_result.set(None);
+                                                save_error.set(None);
                                             }
                                             style="
                                                 background: #3c3c3c;
@@ -623,7 +693,8 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                                     port: if is_sqlite() { None } else { form_port.get().parse().ok() },
                                                     database: form_database.get(),
                                                     username: if is_sqlite() { None } else { Some(form_username.get()) },
-                                                    password: if is_sqlite() { None } else { Some(form_password.get()) },
+                                                    password: form_password_value(),
+                                                    password_source: form_source(),
                                                     ssl: form_ssl.get(),
                                                     created_at: SystemTime::now()
                                                         .duration_since(UNIX_EPOCH)
@@ -645,9 +716,10 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
                                                             show_add_dialog.set(false);
                                                             show_edit_dialog.set(false);
                                                             test_result.set(None);
+                                                            save_error.set(None);
                                                             reload_connections();
                                                         }
-                                                        Err(e) => leptos::logging::error!("Save error: {}", e),
+                                                        Err(e) => save_error.set(Some(e)),
                                                     }
                                                 });
                                             }
@@ -675,3 +747,1864 @@ pub fn DatabasePanel(is_active: Signal<bool>) -> impl IntoView {
         </div>
     }
 }
+
+/// Password source from the form's kind select and reference field
+fn password_source(kind: &str, reference: &str) -> PasswordSource {
+    let reference = reference.trim();
+    match kind {
+        "env" => PasswordSource::Env {
+            variable: reference.to_string(),
+        },
+        "pgpass" => PasswordSource::PgPass {
+            path: (!reference.is_empty()).then(|| reference.to_string()),
+        },
+        _ => PasswordSource::Stored,
+    }
+}
+
+fn password_source_parts(source: &PasswordSource) -> (&'static str, String) {
+    match source {
+        PasswordSource::Stored => ("stored", String::new()),
+        PasswordSource::Env { variable } => ("env", variable.clone()),
+        PasswordSource::PgPass { path } => ("pgpass", path.clone().unwrap_or_default()),
+    }
+}
+
+/// Lock state of the encrypted password store, and connection export/import
+#[component]
+fn CredentialStore(
+    /// Called after connections were imported
+    on_import: Callback<()>,
+) -> impl IntoView {
+    use crate::tauri_bindings_database as db;
+
+    let status = RwSignal::new(None::<db::VaultStatus>);
+    let secret = RwSignal::new(String::new());
+    let use_key_file = RwSignal::new(false);
+    let transfer_path = RwSignal::new(String::new());
+    let message = RwSignal::new(None::<String>);
+    let error = RwSignal::new(None::<String>);
+
+    let refresh = move || {
+        leptos::task::spawn_local(async move {
+            match db::db_vault_status().await {
+                Ok(s) => status.set(Some(s)),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+    refresh();
+
+    let unlock = move || {
+        let value = secret.get_untracked();
+        if value.is_empty() {
+            return;
+        }
+        let (passphrase, key_file) = if use_key_file.get_untracked() { (None, Some(value)) } else { (Some(value), None) };
+        leptos::task::spawn_local(async move {
+            match db::db_unlock_vault(passphrase, key_file).await {
+                Ok(s) => {
+                    status.set(Some(s));
+                    secret.set(String::new());
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let lock = move |_| {
+        leptos::task::spawn_local(async move {
+            db::db_lock_vault().await.ok();
+            refresh();
+        });
+    };
+
+    let transfer = move |import: bool| {
+        let path = transfer_path.get_untracked();
+        if path.trim().is_empty() {
+            return;
+        }
+        leptos::task::spawn_local(async move {
+            let result = if import {
+                db::db_import_connections(path.clone()).await
+            } else {
+                db::db_export_connections(path.clone()).await
+            };
+            match result {
+                Ok(count) if import => {
+                    message.set(Some(format!("Imported {} connection(s)", count)));
+                    on_import.run(());
+                }
+                Ok(count) => message.set(Some(format!("Exported {} connection(s) without passwords", count))),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let button_style = "background: #3c3c3c; border: 1px solid #3e3e3e; color: #cccccc; padding: 2px 8px; cursor: pointer; font-size: 11px; border-radius: 4px;";
+    let input_style = "flex: 1; min-width: 0; padding: 2px 6px; background: #1e1e1e; border: 1px solid #3e3e3e; border-radius: 4px; color: #cccccc; font-size: 11px;";
+
+    view! {
+        <div class="db-credential-store" style="padding: 6px 12px; border-bottom: 1px solid #1e1e1e; display: flex; flex-direction: column; gap: 4px; font-size: 11px; color: #858585;">
+            {move || status.get().map(|s| if s.unlocked {
+                view! {
+                    <div style="display: flex; align-items: center; gap: 6px;">
+                        <i class="codicon codicon-unlock"></i>
+                        <span style="flex: 1;">{format!("Saved passwords unlocked ({})", s.stored_secrets)}</span>
+                        <button style=button_style on:click=lock>"Lock"</button>
+                    </div>
+                }.into_any()
+            } else {
+                let hint = match (s.initialized, s.plaintext_secrets) {
+                    (false, 0) => "Set a master passphrase or key file to save passwords".to_string(),
+                    (false, n) => format!("{} password(s) stored in plain text; set a master passphrase to encrypt them", n),
+                    (true, _) => "Saved passwords are locked".to_string(),
+                };
+                view! {
+                    <div style="display: flex; flex-direction: column; gap: 4px;">
+                        <div style="display: flex; align-items: center; gap: 6px;">
+                            <i class="codicon codicon-lock"></i>
+                            <span>{hint}</span>
+                        </div>
+                        <div style="display: flex; align-items: center; gap: 6px;">
+                            <input
+                                type=move || if use_key_file.get() { "text" } else { "password" }
+                                style=input_style
+                                placeholder=move || if use_key_file.get() { "/path/to/key file" } else { "Master passphrase" }
+                                prop:value=move || secret.get()
+                                on:input=move |ev| secret.set(event_target_value(&ev))
+                                on:keydown=move |ev| if ev.key() == "Enter" { unlock() }
+                            />
+                            <label style="display: flex; align-items: center; gap: 2px; white-space: nowrap;">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || use_key_file.get()
+                                    on:change=move |ev| use_key_file.set(event_target_checked(&ev))
+                                />
+                                "Key file"
+                            </label>
+                            <button style=button_style on:click=move |_| unlock()>"Unlock"</button>
+                        </div>
+                    </div>
+                }.into_any()
+            })}
+            <div style="display: flex; align-items: center; gap: 6px;">
+                <input
+                    type="text"
+                    style=input_style
+                    placeholder="/path/to/connections.json"
+                    prop:value=move || transfer_path.get()
+                    on:input=move |ev| transfer_path.set(event_target_value(&ev))
+                />
+                <button style=button_style on:click=move |_| transfer(false) title="Export connections without passwords">"Export"</button>
+                <button style=button_style on:click=move |_| transfer(true) title="Import connections">"Import"</button>
+            </div>
+            {move || error.get().map(|e| view! { <div style="color: #f48771;">{e}</div> })}
+            {move || message.get().map(|m| view! { <div style="color: #89d185;">{m}</div> })}
+        </div>
+    }
+}
+
+/// Milliseconds with a precision that suits their size
+fn format_duration_ms(ms: f64) -> String {
+    if ms >= 1000.0 {
+        format!("{:.2} s", ms / 1000.0)
+    } else if ms >= 10.0 {
+        format!("{:.0} ms", ms)
+    } else {
+        format!("{:.2} ms", ms)
+    }
+}
+
+/// Hotspot color for a node's share of the plan
+fn heat_color(weight: f64) -> &'static str {
+    if weight >= 0.5 {
+        "#f14c4c"
+    } else if weight >= 0.2 {
+        "#cca700"
+    } else {
+        "#3794ff"
+    }
+}
+
+/// One plan node with its metrics and a bar for its share of the plan
+#[component]
+fn PlanNodeView(node: PlanNode) -> impl IntoView {
+    let expanded = RwSignal::new(true);
+    let has_children = !node.children.is_empty();
+    let mut metrics = Vec::new();
+    if let Some(cost) = node.total_cost {
+        metrics.push(match node.startup_cost {
+            Some(startup) => format!("cost {:.2}..{:.2}", startup, cost),
+            None => format!("cost {:.2}", cost),
+        });
+    }
+    match (node.estimated_rows, node.actual_rows) {
+        (Some(estimated), Some(actual)) => metrics.push(format!("rows {:.0} (est. {:.0})", actual, estimated)),
+        (Some(rows), None) | (None, Some(rows)) => metrics.push(format!("rows {:.0}", rows)),
+        _ => {}
+    }
+    if let Some(ms) = node.actual_time_ms {
+        metrics.push(format_duration_ms(ms));
+    }
+    if let Some(loops) = node.loops.filter(|l| *l > 1) {
+        metrics.push(format!("{} loops", loops));
+    }
+    let weight = node.weight;
+    let children = StoredValue::new(node.children);
+
+    view! {
+        <div style="padding-left: 12px; font-size: 12px;">
+            <div
+                style=format!("display: flex; align-items: center; gap: 6px; white-space: nowrap; cursor: {};", if has_children { "pointer" } else { "default" })
+                on:click=move |_| expanded.update(|e| *e = !*e)
+            >
+                <i
+                    class=move || if expanded.get() { "codicon codicon-chevron-down" } else { "codicon codicon-chevron-right" }
+                    style=if has_children { "font-size: 11px;" } else { "font-size: 11px; visibility: hidden;" }
+                ></i>
+                <span style="display: inline-block; width: 40px; height: 6px; background: #3c3c3c; border-radius: 3px;" title=format!("{:.0}% of the plan", weight * 100.0)>
+                    <span style=format!("display: block; height: 100%; width: {:.0}%; background: {}; border-radius: 3px;", weight * 100.0, heat_color(weight))></span>
+                </span>
+                <span style=format!("color: {};", if weight >= 0.2 { heat_color(weight) } else { "#cccccc" })>{node.label}</span>
+                <span style="color: #858585; font-size: 11px;">{metrics.join(" · ")}</span>
+            </div>
+            {node.detail.map(|detail| view! {
+                <div style="padding-left: 64px; color: #858585; font-size: 11px; white-space: pre-wrap;">{detail}</div>
+            })}
+            {move || expanded.get().then(|| {
+                // Boxed: the view type would otherwise contain itself
+                children.get_value().into_iter().map(|child| view! { <PlanNodeView node=child /> }.into_any()).collect_view()
+            })}
+        </div>
+    }
+}
+
+/// Plan tree with timing summary and the raw EXPLAIN output
+#[component]
+fn PlanView(plan: QueryPlan, on_close: Callback<()>) -> impl IntoView {
+    let show_raw = RwSignal::new(false);
+    let mut summary = vec![if plan.analyzed { "Analyzed plan".to_string() } else { "Estimated plan".to_string() }];
+    if let Some(ms) = plan.planning_time_ms {
+        summary.push(format!("planning {}", format_duration_ms(ms)));
+    }
+    if let Some(ms) = plan.execution_time_ms {
+        summary.push(format!("execution {}", format_duration_ms(ms)));
+    }
+    let raw = plan.raw.clone();
+
+    view! {
+        <div class="db-query-plan" style="overflow: auto; max-height: 50%; border: 1px solid #3e3e3e; border-radius: 4px; padding: 4px; font-family: monospace; color: #cccccc;">
+            <div style="display: flex; align-items: center; gap: 8px; font-size: 11px; color: #858585;">
+                <span style="flex: 1;">{summary.join(" · ")}</span>
+                <button
+                    on:click=move |_| show_raw.update(|s| *s = !*s)
+                    style="background: none; border: none; color: #858585; cursor: pointer; font-size: 11px;"
+                >
+                    {move || if show_raw.get() { "Tree" } else { "Raw" }}
+                </button>
+                <button
+                    on:click=move |_| on_close.run(())
+                    style="background: none; border: none; color: #858585; cursor: pointer; padding: 2px;"
+                    title="Close plan"
+                >
+                    <i class="codicon codicon-close"></i>
+                </button>
+            </div>
+            {move || if show_raw.get() {
+                view! { <pre style="font-size: 11px; margin: 0; white-space: pre-wrap;">{raw.clone()}</pre> }.into_any()
+            } else {
+                plan.nodes.clone().into_iter().map(|node| view! { <PlanNodeView node=node /> }).collect_view().into_any()
+            }}
+        </div>
+    }
+}
+
+/// Searchable list of executed queries; entries can be loaded or re-run
+#[component]
+fn QueryHistory(
+    connection_id: String,
+    /// Changes whenever a query was run
+    version: RwSignal<u32>,
+    on_select: Callback<String>,
+    on_run: Callback<String>,
+) -> impl IntoView {
+    use crate::tauri_bindings_database as db;
+
+    let entries = RwSignal::new(Vec::<QueryHistoryEntry>::new());
+    let search = RwSignal::new(String::new());
+    let all_connections = RwSignal::new(false);
+    let connection_id = StoredValue::new(connection_id);
+
+    // Responses arriving out of order must not replace a newer search
+    let generation = StoredValue::new(0u32);
+
+    let scope = move || (!all_connections.get_untracked()).then(|| connection_id.get_value());
+    let load_after = move |delay_ms: u32| {
+        generation.update_value(|g| *g += 1);
+        let current = generation.get_value();
+        let text = search.get_untracked();
+        let scope = scope();
+        leptos::task::spawn_local(async move {
+            if delay_ms > 0 {
+                gloo_timers::future::TimeoutFuture::new(delay_ms).await;
+                if generation.get_value() != current {
+                    return;
+                }
+            }
+            let result = db::db_query_history((!text.is_empty()).then_some(text), scope).await;
+            if generation.get_value() != current {
+                return;
+            }
+            match result {
+                Ok(loaded) => entries.set(loaded),
+                Err(e) => leptos::logging::error!("Failed to load query history: {}", e),
+            }
+        });
+    };
+    let load = move || load_after(0);
+    Effect::new(move |_| {
+        version.track();
+        load();
+    });
+
+    let delete = move |id: i64| {
+        leptos::task::spawn_local(async move {
+            db::db_delete_history_entry(id).await.ok();
+            load();
+        });
+    };
+    let clear = move |_| {
+        let scope = scope();
+        leptos::task::spawn_local(async move {
+            db::db_clear_query_history(scope).await.ok();
+            load();
+        });
+    };
+
+    let input_style = "flex: 1; padding: 2px 6px; background: #1e1e1e; border: 1px solid #3e3e3e; border-radius: 4px; color: #cccccc; font-size: 11px;";
+    let icon_style = "background: none; border: none; color: #858585; cursor: pointer; padding: 1px;";
+
+    view! {
+        <div class="db-query-history" style="overflow: auto; max-height: 40%; border: 1px solid #3e3e3e; border-radius: 4px; padding: 4px;">
+            <div style="display: flex; align-items: center; gap: 6px; font-size: 11px; color: #858585;">
+                <input
+                    type="text"
+                    style=input_style
+                    placeholder="Search history"
+                    prop:value=move || search.get()
+                    on:input=move |ev| {
+                        search.set(event_target_value(&ev));
+                        load_after(HISTORY_SEARCH_DELAY_MS);
+                    }
+                />
+                <label style="display: flex; align-items: center; gap: 2px; white-space: nowrap;">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || all_connections.get()
+                        on:change=move |ev| {
+                            all_connections.set(event_target_checked(&ev));
+                            load();
+                        }
+                    />
+                    "All connections"
+                </label>
+                <button style=icon_style on:click=clear title="Clear history">
+                    <i class="codicon codicon-clear-all"></i>
+                </button>
+            </div>
+            {move || entries.get().into_iter().map(|entry| {
+                let text = entry.query.clone();
+                let run_text = entry.query.clone();
+                let id = entry.id;
+                let outcome = match (&entry.error_message, entry.row_count, entry.affected_rows) {
+                    (Some(_), _, _) => "failed".to_string(),
+                    (None, Some(rows), _) => format!("{}{} rows", rows, if entry.more_rows { "+" } else { "" }),
+                    (None, None, Some(affected)) => format!("{} affected", affected),
+                    _ => "ok".to_string(),
+                };
+                let time = entry.executed_at.get(..19).unwrap_or(&entry.executed_at).replace('T', " ");
+                view! {
+                    <div
+                        style="display: flex; align-items: flex-start; gap: 6px; padding: 2px 0; border-bottom: 1px solid #2D2D30; cursor: pointer;"
+                        title=entry.error_message.clone().unwrap_or_else(|| "Load into the editor".to_string())
+                        on:click=move |_| on_select.run(text.clone())
+                    >
+                        <div style="flex: 1; min-width: 0;">
+                            <div style="font-family: monospace; font-size: 11px; color: #cccccc; white-space: nowrap; overflow: hidden; text-overflow: ellipsis;">
+                                {entry.query.clone()}
+                            </div>
+                            <div style=format!("font-size: 10px; color: {};", if entry.error_message.is_some() { "#f48771" } else { "#858585" })>
+                                {format!("{} · {} · {} · {}", time, entry.connection_name, format_duration_ms(entry.duration_ms as f64), outcome)}
+                            </div>
+                        </div>
+                        <button
+                            style=icon_style
+                            title="Run again"
+                            on:click=move |ev| {
+                                ev.stop_propagation();
+                                on_run.run(run_text.clone());
+                            }
+                        >
+                            <i class="codicon codicon-play"></i>
+                        </button>
+                        <button
+                            style=icon_style
+                            title="Remove from history"
+                            on:click=move |ev| {
+                                ev.stop_propagation();
+                                delete(id);
+                            }
+                        >
+                            <i class="codicon codicon-trash"></i>
+                        </button>
+                    </div>
+                }
+            }).collect_view()}
+        </div>
+    }
+}
+
+/// Text shown in a result cell
+fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Numeric columns are right-aligned
+fn is_numeric_column(column: &ColumnInfo) -> bool {
+    let type_name = column.type_name.to_lowercase();
+    ["int", "float", "double", "real", "numeric", "decimal", "oid", "year"]
+        .iter()
+        .any(|t| type_name.contains(t))
+}
+
+/// Schema browser and query console sharing the connection's schema metadata
+#[component]
+fn ConnectionWorkspace(connection: DbConnection) -> impl IntoView {
+    if connection.db_type == DbType::MongoDB {
+        return view! { <MongoWorkspace connection=connection /> }.into_any();
+    }
+
+    let schema = RwSignal::new(None::<DatabaseSchema>);
+    let schema_error = RwSignal::new(None::<String>);
+    let open_table = RwSignal::new(None::<TableInfo>);
+    let connection = StoredValue::new(connection);
+
+    let load_schema = move |refresh: bool| {
+        leptos::task::spawn_local(async move {
+            match crate::tauri_bindings_database::db_get_schema(connection.get_value(), refresh).await {
+                Ok(loaded) => {
+                    schema.set(Some(loaded));
+                    schema_error.set(None);
+                }
+                Err(e) => schema_error.set(Some(e)),
+            }
+        });
+    };
+    load_schema(false);
+
+    view! {
+        <SchemaBrowser
+            schema=schema
+            schema_error=schema_error
+            on_refresh=Callback::new(move |_| load_schema(true))
+            on_open_table=Callback::new(move |table: TableInfo| open_table.set(Some(table)))
+        />
+        {move || open_table.get().map(|table| view! {
+            <TableEditor
+                connection=connection.get_value()
+                table=table
+                on_close=Callback::new(move |_| open_table.set(None))
+            />
+        })}
+        <QueryConsole
+            connection=connection.get_value()
+            schema=schema
+            on_schema_change=Callback::new(move |_| load_schema(false))
+        />
+    }
+    .into_any()
+}
+
+/// Tree of schemas, tables and views with their columns, indexes and foreign keys
+#[component]
+fn SchemaBrowser(
+    schema: RwSignal<Option<DatabaseSchema>>,
+    schema_error: RwSignal<Option<String>>,
+    on_refresh: Callback<()>,
+    /// Opens a table or view in the data editor
+    on_open_table: Callback<TableInfo>,
+) -> impl IntoView {
+    let expanded = RwSignal::new(std::collections::HashSet::<String>::new());
+    let toggle = move |key: String| {
+        expanded.update(|set| {
+            if !set.remove(&key) {
+                set.insert(key);
+            }
+        })
+    };
+    let row_style = "display: flex; align-items: center; gap: 4px; padding: 1px 4px; cursor: pointer; font-size: 12px; color: #cccccc; white-space: nowrap;";
+
+    let table_view = move |table: TableInfo| {
+        let key = format!("{}.{}", table.schema, table.name);
+        let is_open = {
+            let key = key.clone();
+            move || expanded.with(|set| set.contains(&key))
+        };
+        let icon = match table.kind {
+            TableKind::Table => "codicon-table",
+            TableKind::View => "codicon-eye",
+        };
+        let name = table.name.clone();
+        let opened = table.clone();
+        view! {
+            <div style="padding-left: 12px;">
+                <div style=row_style on:click=move |_| toggle(key.clone())>
+                    <i class=format!("codicon {}", icon)></i>
+                    <span style="flex: 1;">{name}</span>
+                    <button
+                        on:click=move |ev| {
+                            ev.stop_propagation();
+                            on_open_table.run(opened.clone());
+                        }
+                        style="background: none; border: none; color: #858585; cursor: pointer; padding: 0 2px;"
+                        title="Edit data"
+                    >
+                        <i class="codicon codicon-edit"></i>
+                    </button>
+                </div>
+                {move || is_open().then(|| {
+                    let columns = table.columns.iter().map(|column| {
+                        let detail = format!(
+                            "{}{}{}",
+                            column.data_type,
+                            if column.nullable { "" } else { " not null" },
+                            column.default.as_ref().map(|d| format!(" = {}", d)).unwrap_or_default()
+                        );
+                        view! {
+                            <div style="padding-left: 16px; font-size: 11px; color: #bbbbbb; white-space: nowrap;">
+                                {column.primary_key.then(|| view! { <i class="codicon codicon-key" title="Primary key"></i> })}
+                                {format!(" {} ", column.name)}
+                                <span style="color: #858585;">{detail}</span>
+                            </div>
+                        }
+                    }).collect_view();
+                    let indexes = table.indexes.iter().map(|index| view! {
+                        <div style="padding-left: 16px; font-size: 11px; color: #858585; white-space: nowrap;">
+                            {format!("{} {} ({})", if index.unique { "unique index" } else { "index" }, index.name, index.columns.join(", "))}
+                        </div>
+                    }).collect_view();
+                    let foreign_keys = table.foreign_keys.iter().map(|key| view! {
+                        <div style="padding-left: 16px; font-size: 11px; color: #858585; white-space: nowrap;">
+                            {format!(
+                                "fk ({}) → {}.{} ({})",
+                                key.columns.join(", "),
+                                key.referenced_schema,
+                                key.referenced_table,
+                                key.referenced_columns.join(", ")
+                            )}
+                        </div>
+                    }).collect_view();
+                    view! { <div>{columns}{indexes}{foreign_keys}</div> }
+                })}
+            </div>
+        }
+    };
+
+    view! {
+        <div class="db-schema-browser" style="border-top: 1px solid #1e1e1e; padding: 4px 8px; max-height: 35%; overflow: auto;">
+            <div style="display: flex; align-items: center; justify-content: space-between; font-size: 11px; font-weight: 600; color: #858585;">
+                <span>"SCHEMA"</span>
+                <button
+                    on:click=move |_| on_refresh.run(())
+                    style="background: none; border: none; color: #858585; cursor: pointer; padding: 2px;"
+                    title="Refresh schema"
+                >
+                    <i class="codicon codicon-refresh"></i>
+                </button>
+            </div>
+            {move || schema_error.get().map(|e| view! {
+                <div style="color: #f48771; font-size: 12px;">{e}</div>
+            })}
+            {move || schema.get().map(|loaded| loaded.schemas.into_iter().map(|s| {
+                let key = format!("schema:{}", s.name);
+                // The default schema starts expanded; toggling flips it
+                let default_open = s.name == "main" || s.name == "public";
+                let is_open = {
+                    let key = key.clone();
+                    move || expanded.with(|set| set.contains(&key)) != default_open
+                };
+                let name = s.name.clone();
+                let tables = s.tables.clone();
+                let functions = s.functions.clone();
+                view! {
+                    <div>
+                        <div style=row_style on:click=move |_| toggle(key.clone())>
+                            <i class="codicon codicon-symbol-namespace"></i>
+                            <span>{name}</span>
+                            <span style="color: #858585; font-size: 11px;">{format!("{} tables", tables.len())}</span>
+                        </div>
+                        {move || is_open().then(|| {
+                            let tables = tables.clone().into_iter().map(table_view).collect_view();
+                            let functions = functions.iter().map(|f| view! {
+                                <div style="padding-left: 12px; font-size: 11px; color: #bbbbbb; white-space: nowrap;">
+                                    <i class="codicon codicon-symbol-method"></i>
+                                    {format!(" {}({}) → {}", f.name, f.arguments, f.return_type)}
+                                </div>
+                            }).collect_view();
+                            view! { <div>{tables}{functions}</div> }
+                        })}
+                    </div>
+                }
+            }).collect_view())}
+        </div>
+    }
+}
+
+/// A row of the table editor; `original` is `None` for an inserted row
+#[derive(Debug, Clone, PartialEq)]
+struct EditRow {
+    original: Option<Vec<serde_json::Value>>,
+    values: Vec<serde_json::Value>,
+    deleted: bool,
+}
+
+impl EditRow {
+    fn loaded(values: Vec<serde_json::Value>) -> Self {
+        Self {
+            original: Some(values.clone()),
+            values,
+            deleted: false,
+        }
+    }
+
+    fn is_changed(&self, idx: usize) -> bool {
+        self.original.as_ref().is_none_or(|original| original.get(idx) != self.values.get(idx))
+    }
+}
+
+/// Result columns holding the table's primary key; `None` when the table is read-only
+fn key_indices(table: &TableInfo, columns: &[ColumnInfo]) -> Option<Vec<usize>> {
+    let key: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| c.name.as_str())
+        .collect();
+    if table.kind != TableKind::Table || key.is_empty() {
+        return None;
+    }
+    key.iter()
+        .map(|name| columns.iter().position(|c| c.name == *name))
+        .collect()
+}
+
+/// Value typed into a cell: a number or boolean when the column's type calls for one, else
+/// the text itself. NULL is set with the cell's NULL button, so the text `NULL` stays text.
+fn parse_cell(text: &str, column: &ColumnInfo) -> serde_json::Value {
+    let trimmed = text.trim();
+    if is_numeric_column(column) {
+        if let Ok(n) = trimmed.parse::<i64>() {
+            return n.into();
+        }
+        if let Some(n) = trimmed.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+            return serde_json::Value::Number(n);
+        }
+    }
+    if column.type_name.to_lowercase().contains("bool") {
+        match trimmed.to_lowercase().as_str() {
+            "true" | "t" | "1" => return true.into(),
+            "false" | "f" | "0" => return false.into(),
+            _ => {}
+        }
+    }
+    serde_json::Value::String(text.to_string())
+}
+
+/// Changes for the editor's rows in row order, keyed by the loaded primary key values at `key`.
+/// Inserts leave NULL cells out so column defaults apply.
+fn pending_changes(columns: &[ColumnInfo], key: &[usize], rows: &[EditRow]) -> Vec<RowChange> {
+    let value = |idx: usize, value: &serde_json::Value| ColumnValue {
+        column: columns[idx].name.clone(),
+        value: value.clone(),
+    };
+    rows.iter()
+        .filter_map(|row| match &row.original {
+            None if row.deleted => None,
+            None => Some(RowChange::Insert {
+                values: row
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(idx, v)| value(idx, v))
+                    .collect(),
+            }),
+            Some(original) => {
+                let row_key: Vec<ColumnValue> = key.iter().map(|&idx| value(idx, &original[idx])).collect();
+                if row.deleted {
+                    return Some(RowChange::Delete { key: row_key });
+                }
+                let changed: Vec<ColumnValue> = (0..row.values.len())
+                    .filter(|&idx| row.is_changed(idx))
+                    .map(|idx| value(idx, &row.values[idx]))
+                    .collect();
+                (!changed.is_empty()).then_some(RowChange::Update {
+                    key: row_key,
+                    values: changed,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Grid editor for a table's rows. Edits stay pending until committed in one
+/// transaction; tables and views without a primary key are read-only.
+#[component]
+fn TableEditor(connection: DbConnection, table: TableInfo, on_close: Callback<()>) -> impl IntoView {
+    use crate::tauri_bindings_database as db;
+
+    let connection = StoredValue::new(connection);
+    let table = StoredValue::new(table);
+    let columns = RwSignal::new(Vec::<ColumnInfo>::new());
+    let rows = RwSignal::new(Vec::<EditRow>::new());
+    let cursor = RwSignal::new(None::<String>);
+    let editing = RwSignal::new(None::<(usize, usize)>);
+    let preview = RwSignal::new(None::<Vec<String>>);
+    let error = RwSignal::new(None::<String>);
+    let message = RwSignal::new(None::<String>);
+    let busy = RwSignal::new(false);
+
+    let connection_id = move || connection.with_value(|c| c.id.clone());
+    let key = Memo::new(move |_| columns.with(|c| table.with_value(|t| key_indices(t, c))));
+    let changes = Memo::new(move |_| {
+        key.get()
+            .map(|key| columns.with(|c| rows.with(|r| pending_changes(c, &key, r))))
+            .unwrap_or_default()
+    });
+    let table_edit = move || {
+        table.with_value(|t| TableEdit {
+            schema: t.schema.clone(),
+            table: t.name.clone(),
+            changes: changes.get_untracked(),
+        })
+    };
+
+    let load = move || {
+        let previous = cursor.get_untracked();
+        let (schema, name) = table.with_value(|t| (t.schema.clone(), t.name.clone()));
+        busy.set(true);
+        leptos::task::spawn_local(async move {
+            if let Some(previous) = previous {
+                db::db_close_cursor(connection_id(), previous).await.ok();
+            }
+            match db::db_open_table(connection.get_value(), schema, name, Some(PAGE_SIZE)).await {
+                Ok(result) => {
+                    columns.set(result.columns);
+                    rows.set(result.rows.into_iter().map(EditRow::loaded).collect());
+                    cursor.set(result.cursor);
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            editing.set(None);
+            preview.set(None);
+            busy.set(false);
+        });
+    };
+    load();
+
+    let load_more = move |_| {
+        let Some(id) = cursor.get_untracked() else { return };
+        busy.set(true);
+        leptos::task::spawn_local(async move {
+            match db::db_fetch_rows(connection_id(), id, Some(PAGE_SIZE), None).await {
+                Ok(result) => {
+                    rows.update(|r| r.extend(result.rows.into_iter().map(EditRow::loaded)));
+                    cursor.set(result.cursor);
+                }
+                Err(e) => {
+                    cursor.set(None);
+                    error.set(Some(e));
+                }
+            }
+            busy.set(false);
+        });
+    };
+
+    // `None` sets the cell to NULL
+    let set_cell = move |row: usize, col: usize, text: Option<String>| {
+        // The input's blur after the NULL button or Escape must not apply it again
+        if editing.get_untracked() != Some((row, col)) {
+            return;
+        }
+        let Some(column) = columns.with_untracked(|c| c.get(col).cloned()) else { return };
+        rows.update(|r| {
+            if let Some(cell) = r.get_mut(row).and_then(|row| row.values.get_mut(col)) {
+                *cell = text.map_or(serde_json::Value::Null, |text| parse_cell(&text, &column));
+            }
+        });
+        editing.set(None);
+        preview.set(None);
+    };
+
+    let add_row = move |_| {
+        let width = columns.with_untracked(|c| c.len());
+        rows.update(|r| {
+            r.push(EditRow {
+                original: None,
+                values: vec![serde_json::Value::Null; width],
+                deleted: false,
+            })
+        });
+        preview.set(None);
+    };
+
+    // Inserted rows are dropped; loaded rows are marked and can be restored
+    let toggle_delete = move |idx: usize| {
+        rows.update(|r| match r.get(idx).map(|row| row.original.is_some()) {
+            Some(true) => r[idx].deleted = !r[idx].deleted,
+            Some(false) => {
+                r.remove(idx);
+            }
+            None => {}
+        });
+        editing.set(None);
+        preview.set(None);
+    };
+
+    let discard = move |_| {
+        rows.update(|r| {
+            r.retain(|row| row.original.is_some());
+            for row in r.iter_mut() {
+                row.values = row.original.clone().unwrap_or_default();
+                row.deleted = false;
+            }
+        });
+        editing.set(None);
+        preview.set(None);
+        error.set(None);
+    };
+
+    let show_preview = move |_| {
+        let edit = table_edit();
+        leptos::task::spawn_local(async move {
+            match db::db_preview_changes(connection.get_value(), edit).await {
+                Ok(sql) => {
+                    preview.set(Some(sql));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let commit = move |_| {
+        let edit = table_edit();
+        if edit.changes.is_empty() {
+            return;
+        }
+        // Reloaded after the commit; close the open result first
+        let previous = cursor.get_untracked();
+        cursor.set(None);
+        busy.set(true);
+        leptos::task::spawn_local(async move {
+            if let Some(previous) = previous {
+                db::db_close_cursor(connection_id(), previous).await.ok();
+            }
+            match db::db_apply_changes(connection.get_value(), edit).await {
+                Ok(affected) => {
+                    message.set(Some(format!("Committed, {} row(s) affected", affected)));
+                    load();
+                }
+                Err(e) => {
+                    error.set(Some(e));
+                    busy.set(false);
+                }
+            }
+        });
+    };
+
+    let button_style = "background: #0e639c; border: none; color: #ffffff; padding: 4px 10px; cursor: pointer; font-size: 12px; border-radius: 4px;";
+    let cell_style = "padding: 2px 8px; border-bottom: 1px solid #2D2D30; white-space: nowrap;";
+    let title = table.with_value(|t| format!("{}.{}", t.schema, t.name));
+
+    view! {
+        <div class="db-table-editor" style="
+            display: flex;
+            flex-direction: column;
+            gap: 6px;
+            padding: 8px;
+            border-top: 1px solid #1e1e1e;
+            max-height: 50%;
+            min-height: 0;
+        ">
+            <div style="display: flex; gap: 6px; align-items: center;">
+                <i class="codicon codicon-table"></i>
+                <span style="font-size: 12px; font-weight: 600; color: #cccccc;">{title}</span>
+                <span style="font-size: 11px; color: #858585;">
+                    {move || match changes.with(|c| c.len()) {
+                        0 => String::new(),
+                        n => format!("{} pending change(s)", n),
+                    }}
+                </span>
+                <div style="flex: 1;"></div>
+                <button
+                    on:click=move |_| on_close.run(())
+                    style="background: none; border: none; color: #858585; cursor: pointer; padding: 2px;"
+                    title="Close"
+                >
+                    <i class="codicon codicon-close"></i>
+                </button>
+            </div>
+
+            {move || match key.get() {
+                Some(_) => view! {
+                    <div style="display: flex; gap: 6px; align-items: center;">
+                        <button style=button_style on:click=add_row disabled=move || busy.get()>"Add row"</button>
+                        <button style=button_style on:click=show_preview disabled=move || changes.with(|c| c.is_empty())>
+                            "Preview SQL"
+                        </button>
+                        <button style=button_style on:click=commit disabled=move || busy.get() || changes.with(|c| c.is_empty())>
+                            "Commit"
+                        </button>
+                        <button style=button_style on:click=discard disabled=move || changes.with(|c| c.is_empty())>
+                            "Discard"
+                        </button>
+                        <span style="font-size: 11px; color: #858585;">"Double-click a cell to edit; type NULL for null"</span>
+                    </div>
+                }.into_any(),
+                None => view! {
+                    <div style="font-size: 12px; color: #cca700;">
+                        <i class="codicon codicon-lock"></i>
+                        " Read-only: no primary key identifies the rows to change"
+                    </div>
+                }.into_any(),
+            }}
+
+            {move || error.get().map(|e| view! {
+                <div style="color: #f48771; font-size: 12px; white-space: pre-wrap;">{e}</div>
+            })}
+            {move || message.get().map(|m| view! {
+                <div style="color: #89d185; font-size: 12px;">{m}</div>
+            })}
+            {move || preview.get().map(|sql| view! {
+                <pre style="margin: 0; padding: 6px; background: #1e1e1e; border: 1px solid #3e3e3e; border-radius: 4px; font-size: 11px; color: #cccccc; white-space: pre-wrap; max-height: 120px; overflow: auto;">
+                    {sql.iter().map(|s| format!("{};", s)).collect::<Vec<_>>().join("\n")}
+                </pre>
+            })}
+
+            <div style="overflow: auto; min-height: 0; flex: 1;">
+                <table style="border-collapse: collapse; font-size: 12px; font-family: monospace; color: #cccccc;">
+                    <thead>
+                        <tr>
+                            <th style="position: sticky; top: 0; background: #2D2D30;"></th>
+                            {move || columns.get().into_iter().map(|column| view! {
+                                <th style="position: sticky; top: 0; background: #2D2D30; text-align: left; padding: 2px 8px; border-bottom: 1px solid #3e3e3e; white-space: nowrap;">
+                                    {column.name.clone()}
+                                    <div style="font-weight: normal; font-size: 10px; color: #858585;">{column.type_name.clone()}</div>
+                                </th>
+                            }).collect_view()}
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {move || {
+                            let editable = key.get().is_some();
+                            let numeric: Vec<bool> = columns.with(|c| c.iter().map(is_numeric_column).collect());
+                            let current = editing.get();
+                            rows.get().into_iter().enumerate().map(|(row_idx, row)| {
+                                let row_style = if row.deleted {
+                                    "background: #4b1818; text-decoration: line-through;"
+                                } else if row.original.is_none() {
+                                    "background: #1e3a1e;"
+                                } else {
+                                    ""
+                                };
+                                let cells = row.values.iter().enumerate().map(|(col_idx, value)| {
+                                    let text = cell_text(value);
+                                    if current == Some((row_idx, col_idx)) {
+                                        let input = NodeRef::<leptos::html::Input>::new();
+                                        input.on_load(|el| {
+                                            let _ = el.focus();
+                                        });
+                                        // An untouched input leaves the cell as it was, NULL included
+                                        let touched = StoredValue::new(false);
+                                        let commit = move |text: String| {
+                                            if touched.get_value() {
+                                                set_cell(row_idx, col_idx, Some(text));
+                                            } else {
+                                                editing.set(None);
+                                            }
+                                        };
+                                        let initial = if value.is_null() { String::new() } else { text };
+                                        return view! {
+                                            <td style=format!("{} display: flex; gap: 4px;", cell_style)>
+                                                <input
+                                                    node_ref=input
+                                                    type="text"
+                                                    value=initial
+                                                    placeholder="NULL"
+                                                    style="flex: 1; min-width: 80px; background: #1e1e1e; border: 1px solid #007acc; color: #cccccc; font-family: monospace; font-size: 12px;"
+                                                    on:input=move |_| touched.set_value(true)
+                                                    on:blur=move |ev| commit(event_target_value(&ev))
+                                                    on:keydown=move |ev| match ev.key().as_str() {
+                                                        "Enter" => commit(event_target_value(&ev)),
+                                                        "Escape" => editing.set(None),
+                                                        _ => {}
+                                                    }
+                                                />
+                                                <button
+                                                    title="Set NULL"
+                                                    style="background: #3c3c3c; border: none; color: #cccccc; font-size: 11px; cursor: pointer; padding: 0 6px;"
+                                                    // Before the input's blur, which would keep the typed text
+                                                    on:mousedown=move |ev| {
+                                                        ev.prevent_default();
+                                                        set_cell(row_idx, col_idx, None);
+                                                    }
+                                                >
+                                                    "NULL"
+                                                </button>
+                                            </td>
+                                        }.into_any();
+                                    }
+                                    let align = if numeric.get(col_idx).copied().unwrap_or(false) { "right" } else { "left" };
+                                    let color = if value.is_null() { "#858585" } else { "#cccccc" };
+                                    let background = if row.original.is_some() && row.is_changed(col_idx) { "background: #4d4a1e;" } else { "" };
+                                    let deleted = row.deleted;
+                                    view! {
+                                        <td
+                                            style=format!("{} text-align: {}; color: {}; {}", cell_style, align, color, background)
+                                            on:dblclick=move |_| {
+                                                if editable && !deleted {
+                                                    editing.set(Some((row_idx, col_idx)));
+                                                }
+                                            }
+                                        >
+                                            {text}
+                                        </td>
+                                    }.into_any()
+                                }).collect_view();
+                                view! {
+                                    <tr style=row_style>
+                                        <td style=cell_style>
+                                            {editable.then(|| view! {
+                                                <button
+                                                    on:click=move |_| toggle_delete(row_idx)
+                                                    style="background: none; border: none; color: #858585; cursor: pointer; padding: 0;"
+                                                    title=if row.deleted { "Restore row" } else { "Delete row" }
+                                                >
+                                                    <i class=if row.deleted { "codicon codicon-discard" } else { "codicon codicon-trash" }></i>
+                                                </button>
+                                            })}
+                                        </td>
+                                        {cells}
+                                    </tr>
+                                }
+                            }).collect_view()
+                        }}
+                    </tbody>
+                </table>
+            </div>
+
+            {move || cursor.get().is_some().then(|| view! {
+                <button style=button_style on:click=load_more disabled=move || busy.get()>
+                    {format!("Load {} more", PAGE_SIZE)}
+                </button>
+            })}
+        </div>
+    }
+}
+
+/// Editor, paged result grid and export for one connection
+#[component]
+fn QueryConsole(
+    connection: DbConnection,
+    /// Schema metadata driving completion
+    schema: RwSignal<Option<DatabaseSchema>>,
+    /// Called after a statement that may have changed the schema
+    on_schema_change: Callback<()>,
+) -> impl IntoView {
+    use crate::tauri_bindings_database as db;
+
+    let connection = StoredValue::new(connection);
+    let query = RwSignal::new(String::new());
+    let columns = RwSignal::new(Vec::<ColumnInfo>::new());
+    let rows = RwSignal::new(Vec::<Vec<serde_json::Value>>::new());
+    let cursor = RwSignal::new(None::<String>);
+    let message = RwSignal::new(None::<String>);
+    let error = RwSignal::new(None::<String>);
+    let running = RwSignal::new(false);
+    // Query whose rows are shown; exports re-run it
+    let shown_query = RwSignal::new(String::new());
+    let export_format = RwSignal::new(ExportFormat::Csv);
+    let export_path = RwSignal::new(String::new());
+    let textarea = NodeRef::<leptos::html::Textarea>::new();
+    let completions = RwSignal::new(SqlCompletions::default());
+    let selected_completion = RwSignal::new(0usize);
+    let plan = RwSignal::new(None::<QueryPlan>);
+    let show_history = RwSignal::new(false);
+    // Bumped after each run so the history list reloads
+    let history_version = RwSignal::new(0u32);
+
+    // Completions at the caret; `explicit` (Ctrl+Space) also lists them for an empty word
+    let update_completions = move |explicit: bool| {
+        let Some(el) = textarea.get_untracked() else { return };
+        let text = el.value();
+        let caret = el.selection_start().ok().flatten().unwrap_or(0) as usize;
+        let caret = sql_completion::utf16_to_byte(&text, caret);
+        let found = schema.with_untracked(|schema| {
+            schema
+                .as_ref()
+                .map(|schema| sql_completion::complete(&text, caret, schema))
+                .unwrap_or_default()
+        });
+        let show = explicit || !found.prefix.is_empty() || found.qualified;
+        selected_completion.set(0);
+        completions.set(if show { found } else { SqlCompletions::default() });
+    };
+
+    let accept_completion = move |index: usize| {
+        let Some(el) = textarea.get_untracked() else { return };
+        let Some(item) = completions.with_untracked(|c| c.items.get(index).cloned()) else { return };
+        let text = el.value();
+        let start = completions.with_untracked(|c| c.start).min(text.len());
+        let caret = sql_completion::utf16_to_byte(&text, el.selection_start().ok().flatten().unwrap_or(0) as usize);
+        let updated = format!("{}{}{}", &text[..start], item.label, &text[caret.max(start)..]);
+        let new_caret = sql_completion::byte_to_utf16(&updated, start + item.label.len()) as u32;
+        el.set_value(&updated);
+        el.set_selection_range(new_caret, new_caret).ok();
+        query.set(updated);
+        completions.set(SqlCompletions::default());
+    };
+
+    let connection_id = move || connection.with_value(|c| c.id.clone());
+
+    let show_result = move |result: QueryResult, append: bool| {
+        if append {
+            rows.update(|r| r.extend(result.rows));
+        } else {
+            columns.set(result.columns);
+            rows.set(result.rows);
+        }
+        cursor.set(result.cursor);
+        message.set(match (result.affected_rows, columns.with(|c| c.is_empty())) {
+            (Some(n), true) => Some(format!("{} row(s) affected", n)),
+            (None, true) => Some("Statement executed".to_string()),
+            _ => None,
+        });
+    };
+
+    let run_query = move || {
+        let sql = query.get_untracked();
+        if sql.trim().is_empty() || running.get_untracked() {
+            return;
+        }
+        let previous = cursor.get_untracked();
+        running.set(true);
+        error.set(None);
+        leptos::task::spawn_local(async move {
+            // A result that was not read to the end still holds a server-side cursor
+            if let Some(previous) = previous {
+                db::db_close_cursor(connection_id(), previous).await.ok();
+            }
+            match db::db_execute_query(connection.get_value(), sql.clone(), None, Some(PAGE_SIZE)).await {
+                Ok(result) => {
+                    let statement = result.columns.is_empty();
+                    shown_query.set(sql);
+                    plan.set(None);
+                    show_result(result, false);
+                    // DDL drops the backend's schema cache; reload for completion
+                    if statement {
+                        on_schema_change.run(());
+                    }
+                }
+                Err(e) => {
+                    cursor.set(None);
+                    error.set(Some(e));
+                }
+            }
+            running.set(false);
+            history_version.update(|v| *v += 1);
+        });
+    };
+
+    let explain = move |analyze: bool| {
+        let sql = query.get_untracked();
+        if sql.trim().is_empty() || running.get_untracked() {
+            return;
+        }
+        running.set(true);
+        error.set(None);
+        leptos::task::spawn_local(async move {
+            match db::db_explain_query(connection.get_value(), sql, analyze).await {
+                Ok(loaded) => plan.set(Some(loaded)),
+                Err(e) => error.set(Some(e)),
+            }
+            running.set(false);
+        });
+    };
+    let is_postgres = connection.with_value(|c| c.db_type == DbType::PostgreSQL);
+
+    let load_more = move |_| {
+        let Some(id) = cursor.get_untracked() else { return };
+        running.set(true);
+        leptos::task::spawn_local(async move {
+            match db::db_fetch_rows(connection_id(), id, Some(PAGE_SIZE), None).await {
+                Ok(result) => show_result(result, true),
+                Err(e) => {
+                    cursor.set(None);
+                    error.set(Some(e));
+                }
+            }
+            running.set(false);
+        });
+    };
+
+    let cancel = move |_| {
+        leptos::task::spawn_local(async move {
+            if let Err(e) = db::db_cancel_query(connection_id()).await {
+                error.set(Some(e));
+            }
+        });
+    };
+
+    let export = move |_| {
+        let path = export_path.get_untracked();
+        let sql = shown_query.get_untracked();
+        if path.trim().is_empty() || sql.is_empty() {
+            return;
+        }
+        let format = export_format.get_untracked();
+        leptos::task::spawn_local(async move {
+            match db::db_export_query(connection.get_value(), sql, format, path.clone(), None).await {
+                Ok(count) => message.set(Some(format!("Exported {} row(s) to {}", count, path))),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let button_style = "background: #0e639c; border: none; color: #ffffff; padding: 4px 10px; cursor: pointer; font-size: 12px; border-radius: 4px;";
+    let input_style = "padding: 4px 6px; background: #1e1e1e; border: 1px solid #3e3e3e; border-radius: 4px; color: #cccccc; font-size: 12px;";
+
+    view! {
+        <div class="db-query-console" style="
+            display: flex;
+            flex-direction: column;
+            gap: 6px;
+            padding: 8px;
+            border-top: 1px solid #1e1e1e;
+            max-height: 60%;
+            min-height: 0;
+        ">
+            <div style="position: relative; display: flex; flex-direction: column;">
+                <textarea
+                    node_ref=textarea
+                    prop:value=move || query.get()
+                    on:input=move |ev| {
+                        query.set(event_target_value(&ev));
+                        update_completions(false);
+                    }
+                    on:keydown=move |ev| {
+                        let open = completions.with(|c| !c.items.is_empty());
+                        let count = completions.with(|c| c.items.len());
+                        match ev.key().as_str() {
+                            "Enter" if ev.ctrl_key() || ev.meta_key() => {
+                                ev.prevent_default();
+                                completions.set(SqlCompletions::default());
+                                run_query();
+                            }
+                            " " if ev.ctrl_key() => {
+                                ev.prevent_default();
+                                update_completions(true);
+                            }
+                            "ArrowDown" if open => {
+                                ev.prevent_default();
+                                selected_completion.update(|i| *i = (*i + 1).min(count.saturating_sub(1)));
+                            }
+                            "ArrowUp" if open => {
+                                ev.prevent_default();
+                                selected_completion.update(|i| *i = i.saturating_sub(1));
+                            }
+                            "Enter" | "Tab" if open => {
+                                ev.prevent_default();
+                                accept_completion(selected_completion.get_untracked());
+                            }
+                            "Escape" if open => {
+                                ev.prevent_default();
+                                completions.set(SqlCompletions::default());
+                            }
+                            _ => {}
+                        }
+                    }
+                    on:blur=move |_| completions.set(SqlCompletions::default())
+                    placeholder="SELECT ... (Ctrl+Enter to run, Ctrl+Space to complete)"
+                    rows="4"
+                    style=format!("{} font-family: monospace; resize: vertical;", input_style)
+                ></textarea>
+                {move || completions.with(|c| !c.items.is_empty()).then(|| view! {
+                    <div class="berry-completion-widget" style="position: absolute; top: 100%; left: 0; z-index: 1000; max-height: 200px; overflow-y: auto;">
+                        <div class="berry-completion-list">
+                            {move || {
+                                let selected = selected_completion.get();
+                                completions.get().items.into_iter().enumerate().map(|(idx, item)| view! {
+                                    <div
+                                        class=if idx == selected { "berry-completion-item berry-completion-item-selected" } else { "berry-completion-item" }
+                                        // mousedown fires before the textarea blurs
+                                        on:mousedown=move |ev| {
+                                            ev.prevent_default();
+                                            accept_completion(idx);
+                                        }
+                                    >
+                                        <span class="berry-completion-kind">{item.kind.badge()}</span>
+                                        <span class="berry-completion-label">{item.label.clone()}</span>
+                                        <span class="berry-completion-detail">{item.detail.clone()}</span>
+                                    </div>
+                                }).collect_view()
+                            }}
+                        </div>
+                    </div>
+                })}
+            </div>
+            <div style="display: flex; gap: 6px; align-items: center;">
+                <button style=button_style on:click=move |_| run_query() disabled=move || running.get()>
+                    "Run"
+                </button>
+                <button style=button_style on:click=cancel disabled=move || !running.get()>
+                    "Cancel"
+                </button>
+                <button style=button_style on:click=move |_| explain(false) disabled=move || running.get() title="Show the query plan">
+                    "Explain"
+                </button>
+                {is_postgres.then(|| view! {
+                    <button
+                        style=button_style
+                        on:click=move |_| explain(true)
+                        disabled=move || running.get()
+                        title="Run the query and show actual rows and timings (writes are rolled back)"
+                    >
+                        "Analyze"
+                    </button>
+                })}
+                <button
+                    style=button_style
+                    on:click=move |_| show_history.update(|s| *s = !*s)
+                    title="Query history"
+                >
+                    <i class="codicon codicon-history"></i>
+                </button>
+                <span style="font-size: 11px; color: #858585;">
+                    {move || {
+                        let count = rows.with(|r| r.len());
+                        if columns.with(|c| c.is_empty()) {
+                            String::new()
+                        } else if cursor.with(|c| c.is_some()) {
+                            format!("{} rows loaded, more available", count)
+                        } else {
+                            format!("{} rows", count)
+                        }
+                    }}
+                </span>
+            </div>
+
+            {move || error.get().map(|e| view! {
+                <div style="color: #f48771; font-size: 12px; white-space: pre-wrap;">{e}</div>
+            })}
+            {move || message.get().map(|m| view! {
+                <div style="color: #89d185; font-size: 12px;">{m}</div>
+            })}
+
+            {move || show_history.get().then(|| view! {
+                <QueryHistory
+                    connection_id=connection_id()
+                    version=history_version
+                    on_select=Callback::new(move |text: String| query.set(text))
+                    on_run=Callback::new(move |text: String| {
+                        query.set(text);
+                        run_query();
+                    })
+                />
+            })}
+
+            {move || plan.get().map(|loaded| view! {
+                <PlanView plan=loaded on_close=Callback::new(move |_| plan.set(None)) />
+            })}
+
+            <div style="overflow: auto; min-height: 0; flex: 1;">
+                <table style="border-collapse: collapse; font-size: 12px; font-family: monospace; color: #cccccc;">
+                    <thead>
+                        <tr>
+                            {move || columns.get().into_iter().map(|column| view! {
+                                <th style="position: sticky; top: 0; background: #2D2D30; text-align: left; padding: 2px 8px; border-bottom: 1px solid #3e3e3e; white-space: nowrap;">
+                                    {column.name.clone()}
+                                    <div style="font-weight: normal; font-size: 10px; color: #858585;">{column.type_name.clone()}</div>
+                                </th>
+                            }).collect_view()}
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {move || {
+                            let numeric: Vec<bool> = columns.with(|c| c.iter().map(is_numeric_column).collect());
+                            rows.get().into_iter().map(|row| {
+                                let cells = row.iter().enumerate().map(|(idx, value)| {
+                                    let align = if numeric.get(idx).copied().unwrap_or(false) { "right" } else { "left" };
+                                    let color = if value.is_null() { "#858585" } else { "#cccccc" };
+                                    view! {
+                                        <td style=format!("padding: 2px 8px; border-bottom: 1px solid #2D2D30; white-space: nowrap; text-align: {}; color: {};", align, color)>
+                                            {cell_text(value)}
+                                        </td>
+                                    }
+                                }).collect_view();
+                                view! { <tr>{cells}</tr> }
+                            }).collect_view()
+                        }}
+                    </tbody>
+                </table>
+            </div>
+
+            {move || cursor.get().is_some().then(|| view! {
+                <button style=button_style on:click=load_more disabled=move || running.get()>
+                    {format!("Load {} more", PAGE_SIZE)}
+                </button>
+            })}
+
+            {move || (!columns.with(|c| c.is_empty())).then(|| view! {
+                <div style="display: flex; gap: 6px; align-items: center;">
+                    <select
+                        style=input_style
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            if let Some(format) = ExportFormat::ALL.into_iter().find(|f| f.extension() == value) {
+                                export_format.set(format);
+                            }
+                        }
+                    >
+                        {ExportFormat::ALL.into_iter().map(|format| view! {
+                            <option value=format.extension() selected=move || export_format.get() == format>
+                                {format.label()}
+                            </option>
+                        }).collect_view()}
+                    </select>
+                    <input
+                        type="text"
+                        style=format!("{} flex: 1;", input_style)
+                        placeholder=move || format!("/path/to/result.{}", export_format.get().extension())
+                        prop:value=move || export_path.get()
+                        on:input=move |ev| export_path.set(event_target_value(&ev))
+                    />
+                    <button style=button_style on:click=export>"Export"</button>
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Collapsed form of a document or array, e.g. `{ _id, name, … }`
+fn document_summary(value: &serde_json::Value) -> String {
+    const SHOWN_KEYS: usize = 4;
+    match value {
+        serde_json::Value::Object(map) => {
+            // Extended JSON wrappers such as {"$oid": "..."} read better inline
+            if map.len() == 1 && map.keys().all(|k| k.starts_with('$')) {
+                return value.to_string();
+            }
+            let mut keys: Vec<&str> = map.keys().take(SHOWN_KEYS).map(String::as_str).collect();
+            if map.len() > SHOWN_KEYS {
+                keys.push("…");
+            }
+            format!("{{ {} }}", keys.join(", "))
+        }
+        serde_json::Value::Array(items) => format!("[ {} items ]", items.len()),
+        other => cell_text(other),
+    }
+}
+
+/// `find()` for a collection; names that aren't identifiers go through getCollection
+fn mongo_find_command(collection: &str) -> String {
+    let identifier = !collection.is_empty()
+        && !collection.starts_with(|c: char| c.is_ascii_digit())
+        && collection.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        format!("db.{}.find({{}})", collection)
+    } else {
+        format!("db.getCollection({}).find({{}})", serde_json::Value::String(collection.to_string()))
+    }
+}
+
+/// A value that expands into its fields
+fn is_expandable(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => !(map.len() == 1 && map.keys().all(|k| k.starts_with('$'))),
+        serde_json::Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Expandable tree view of a JSON document
+#[component]
+fn DocumentView(
+    value: serde_json::Value,
+    /// Field name or array index; None for a top-level document
+    #[prop(optional)]
+    label: Option<String>,
+    #[prop(optional)] open: bool,
+) -> impl IntoView {
+    let expanded = RwSignal::new(open);
+    let expandable = is_expandable(&value);
+    let summary = document_summary(&value);
+    let color = match &value {
+        serde_json::Value::String(_) => "#ce9178",
+        serde_json::Value::Number(_) => "#b5cea8",
+        serde_json::Value::Bool(_) | serde_json::Value::Null => "#569cd6",
+        _ => "#cccccc",
+    };
+    let value = StoredValue::new(value);
+
+    view! {
+        <div style="font-family: monospace; font-size: 12px; padding-left: 12px;">
+            <div
+                style=format!("white-space: nowrap; cursor: {};", if expandable { "pointer" } else { "default" })
+                on:click=move |_| if expandable { expanded.update(|e| *e = !*e) }
+            >
+                <i
+                    class=move || if expanded.get() { "codicon codicon-chevron-down" } else { "codicon codicon-chevron-right" }
+                    style=if expandable { "font-size: 11px;" } else { "font-size: 11px; visibility: hidden;" }
+                ></i>
+                {label.map(|label| view! { <span style="color: #9cdcfe;">{format!("{}: ", label)}</span> })}
+                <span style=format!("color: {};", color)>{summary}</span>
+            </div>
+            {move || (expandable && expanded.get()).then(|| {
+                let children: Vec<(String, serde_json::Value)> = value.with_value(|value| match value {
+                    serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                    serde_json::Value::Array(items) => items.iter().enumerate().map(|(i, v)| (i.to_string(), v.clone())).collect(),
+                    _ => Vec::new(),
+                });
+                // Boxed: the view type would otherwise contain itself
+                children.into_iter().map(|(label, child)| view! {
+                    <DocumentView value=child label=label />
+                }.into_any()).collect_view()
+            })}
+        </div>
+    }
+}
+
+/// Collection list, shell-syntax console and document results for a MongoDB connection
+#[component]
+fn MongoWorkspace(connection: DbConnection) -> impl IntoView {
+    use crate::tauri_bindings_database as db;
+
+    let connection = StoredValue::new(connection);
+    let collections = RwSignal::new(Vec::<String>::new());
+    let command = RwSignal::new(String::new());
+    let documents = RwSignal::new(Vec::<serde_json::Value>::new());
+    let message = RwSignal::new(None::<String>);
+    let error = RwSignal::new(None::<String>);
+    let running = RwSignal::new(false);
+    // Description of a write waiting for confirmation
+    let pending = RwSignal::new(None::<String>);
+    let confirm_open = RwSignal::new(false);
+
+    let load_collections = move || {
+        leptos::task::spawn_local(async move {
+            match db::db_mongo_collections(connection.get_value()).await {
+                Ok(names) => collections.set(names),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+    load_collections();
+
+    let run = move |confirmed: bool| {
+        let text = command.get_untracked();
+        if text.trim().is_empty() || running.get_untracked() {
+            return;
+        }
+        running.set(true);
+        error.set(None);
+        leptos::task::spawn_local(async move {
+            match db::db_mongo_execute(connection.get_value(), text, confirmed).await {
+                Ok(result) if result.needs_confirmation => {
+                    pending.set(result.message);
+                    confirm_open.set(true);
+                }
+                Ok(result) => {
+                    documents.set(result.documents);
+                    message.set(result.message);
+                    // Inserts can create collections
+                    if confirmed {
+                        load_collections();
+                    }
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            running.set(false);
+        });
+    };
+
+    let button_style = "background: #0e639c; border: none; color: #ffffff; padding: 4px 10px; cursor: pointer; font-size: 12px; border-radius: 4px;";
+    let input_style = "padding: 4px 6px; background: #1e1e1e; border: 1px solid #3e3e3e; border-radius: 4px; color: #cccccc; font-size: 12px;";
+
+    view! {
+        <div class="db-mongo-collections" style="border-top: 1px solid #1e1e1e; padding: 4px 8px; max-height: 30%; overflow: auto;">
+            <div style="display: flex; align-items: center; justify-content: space-between; font-size: 11px; font-weight: 600; color: #858585;">
+                <span>"COLLECTIONS"</span>
+                <button
+                    on:click=move |_| load_collections()
+                    style="background: none; border: none; color: #858585; cursor: pointer; padding: 2px;"
+                    title="Refresh collections"
+                >
+                    <i class="codicon codicon-refresh"></i>
+                </button>
+            </div>
+            {move || collections.get().into_iter().map(|name| {
+                let query = mongo_find_command(&name);
+                view! {
+                    <div
+                        style="display: flex; align-items: center; gap: 4px; padding: 1px 4px; cursor: pointer; font-size: 12px; color: #cccccc; white-space: nowrap;"
+                        title="Insert a find() for this collection"
+                        on:click=move |_| command.set(query.clone())
+                    >
+                        <i class="codicon codicon-folder"></i>
+                        <span>{name}</span>
+                    </div>
+                }
+            }).collect_view()}
+        </div>
+
+        <div class="db-mongo-console" style="
+            display: flex;
+            flex-direction: column;
+            gap: 6px;
+            padding: 8px;
+            border-top: 1px solid #1e1e1e;
+            max-height: 60%;
+            min-height: 0;
+        ">
+            <textarea
+                prop:value=move || command.get()
+                on:input=move |ev| command.set(event_target_value(&ev))
+                on:keydown=move |ev| {
+                    if ev.key() == "Enter" && (ev.ctrl_key() || ev.meta_key()) {
+                        ev.prevent_default();
+                        run(false);
+                    }
+                }
+                placeholder="db.users.find({age: {$gt: 30}}).sort({name: 1}).limit(10) (Ctrl+Enter to run)"
+                rows="4"
+                style=format!("{} font-family: monospace; resize: vertical;", input_style)
+            ></textarea>
+            <div style="display: flex; gap: 6px; align-items: center;">
+                <button style=button_style on:click=move |_| run(false) disabled=move || running.get()>
+                    "Run"
+                </button>
+                <span style="font-size: 11px; color: #858585;">
+                    {move || {
+                        let count = documents.with(|d| d.len());
+                        if count == 0 { String::new() } else { format!("{} documents", count) }
+                    }}
+                </span>
+            </div>
+
+            {move || error.get().map(|e| view! {
+                <div style="color: #f48771; font-size: 12px; white-space: pre-wrap;">{e}</div>
+            })}
+            {move || message.get().map(|m| view! {
+                <div style="color: #89d185; font-size: 12px;">{m}</div>
+            })}
+
+            <div style="overflow: auto; min-height: 0; flex: 1; color: #cccccc;">
+                {move || documents.get().into_iter().map(|document| view! {
+                    <div style="border-bottom: 1px solid #2D2D30; padding: 2px 0;">
+                        <DocumentView value=document />
+                    </div>
+                }).collect_view()}
+            </div>
+        </div>
+
+        {move || pending.get().map(|description| view! {
+            <ConfirmDialog
+                is_open=confirm_open
+                title="Modify documents?".to_string()
+                message=description
+                on_confirm=move || {
+                    pending.set(None);
+                    run(true);
+                }
+                on_cancel=move || pending.set(None)
+            />
+        })}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cell_text() {
+        assert_eq!(cell_text(&json!(null)), "NULL");
+        assert_eq!(cell_text(&json!("2024-01-02T03:04:05")), "2024-01-02T03:04:05");
+        assert_eq!(cell_text(&json!(12)), "12");
+        assert_eq!(cell_text(&json!({"a": [1]})), "{\"a\":[1]}");
+    }
+
+    fn result_column(name: &str, type_name: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_cell() {
+        let int = result_column("id", "INT4");
+        let flag = result_column("active", "BOOL");
+        let text = result_column("name", "TEXT");
+        assert_eq!(parse_cell("42", &int), json!(42));
+        assert_eq!(parse_cell("1.5", &int), json!(1.5));
+        assert_eq!(parse_cell("NULL", &int), json!("NULL"));
+        assert_eq!(parse_cell("t", &flag), json!(true));
+        assert_eq!(parse_cell("42", &text), json!("42"));
+        assert_eq!(parse_cell("null", &text), json!("null"));
+        assert_eq!(parse_cell("NULL", &text), json!("NULL"));
+    }
+
+    #[test]
+    fn test_pending_changes() {
+        let columns = vec![result_column("id", "INT4"), result_column("name", "TEXT")];
+        let loaded = |id: i64, name: &str| EditRow::loaded(vec![json!(id), json!(name)]);
+        let value = |column: &str, value: serde_json::Value| ColumnValue {
+            column: column.to_string(),
+            value,
+        };
+
+        let mut renamed = loaded(1, "a");
+        renamed.values[1] = json!("b");
+        let mut rekeyed = loaded(2, "c");
+        rekeyed.values[0] = json!(20);
+        let mut deleted = loaded(3, "d");
+        deleted.deleted = true;
+        let inserted = EditRow {
+            original: None,
+            values: vec![json!(null), json!("e")],
+            deleted: false,
+        };
+        let dropped = EditRow {
+            deleted: true,
+            ..inserted.clone()
+        };
+        let rows = vec![renamed, loaded(4, "same"), rekeyed, deleted, inserted, dropped];
+
+        assert_eq!(
+            pending_changes(&columns, &[0], &rows),
+            vec![
+                RowChange::Update {
+                    key: vec![value("id", json!(1))],
+                    values: vec![value("name", json!("b"))],
+                },
+                RowChange::Update {
+                    key: vec![value("id", json!(2))],
+                    values: vec![value("id", json!(20))],
+                },
+                RowChange::Delete {
+                    key: vec![value("id", json!(3))],
+                },
+                RowChange::Insert {
+                    values: vec![value("name", json!("e"))],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_indices() {
+        let column = |name: &str, primary_key: bool| crate::tauri_bindings_database::ColumnMeta {
+            name: name.to_string(),
+            data_type: "int".to_string(),
+            nullable: false,
+            default: None,
+            primary_key,
+        };
+        let mut table = TableInfo {
+            schema: "public".to_string(),
+            name: "t".to_string(),
+            kind: TableKind::Table,
+            columns: vec![column("a", false), column("b", true)],
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        };
+        let columns = vec![result_column("a", "INT4"), result_column("b", "INT4")];
+        assert_eq!(key_indices(&table, &columns), Some(vec![1]));
+        assert_eq!(key_indices(&table, &columns[..1]), None);
+
+        table.kind = TableKind::View;
+        assert_eq!(key_indices(&table, &columns), None);
+        table.kind = TableKind::Table;
+        table.columns[1].primary_key = false;
+        assert_eq!(key_indices(&table, &columns), None);
+    }
+
+    #[test]
+    fn test_document_summary() {
+        assert_eq!(document_summary(&json!({"_id": 1, "name": "a"})), "{ _id, name }");
+        assert_eq!(
+            document_summary(&json!({"a": 1, "b": 2, "c": 3, "d": 4, "e": 5})),
+            "{ a, b, c, d, … }"
+        );
+        assert_eq!(document_summary(&json!([1, 2, 3])), "[ 3 items ]");
+        assert_eq!(document_summary(&json!({"$oid": "65a1"})), "{\"$oid\":\"65a1\"}");
+        assert!(!is_expandable(&json!({"$date": "2024-01-01T00:00:00Z"})));
+        assert!(is_expandable(&json!({"tags": []})));
+        assert!(!is_expandable(&json!([])));
+    }
+
+    #[test]
+    fn test_mongo_find_command() {
+        assert_eq!(mongo_find_command("users"), "db.users.find({})");
+        assert_eq!(mongo_find_command("audit.log"), "db.getCollection(\"audit.log\").find({})");
+    }
+
+    #[test]
+    fn test_password_source_form() {
+        assert_eq!(password_source("stored", "ignored"), PasswordSource::Stored);
+        assert_eq!(
+            password_source("env", " DB_PASSWORD "),
+            PasswordSource::Env {
+                variable: "DB_PASSWORD".to_string()
+            }
+        );
+        assert_eq!(password_source("pgpass", ""), PasswordSource::PgPass { path: None });
+
+        let source = password_source("pgpass", "/home/me/.pgpass");
+        let (kind, reference) = password_source_parts(&source);
+        assert_eq!(password_source(kind, &reference), source);
+    }
+
+    #[test]
+    fn test_plan_formatting() {
+        assert_eq!(format_duration_ms(0.123), "0.12 ms");
+        assert_eq!(format_duration_ms(42.4), "42 ms");
+        assert_eq!(format_duration_ms(1500.0), "1.50 s");
+        assert_eq!(heat_color(0.6), "#f14c4c");
+        assert_eq!(heat_color(0.2), "#cca700");
+        assert_eq!(heat_color(0.05), "#3794ff");
+    }
+
+    #[test]
+    fn test_numeric_columns() {
+        let column = |type_name: &str| ColumnInfo {
+            name: "c".to_string(),
+            type_name: type_name.to_string(),
+        };
+        assert!(is_numeric_column(&column("int8")));
+        assert!(is_numeric_column(&column("NEWDECIMAL")));
+        assert!(is_numeric_column(&column("numeric")));
+        assert!(!is_numeric_column(&column("timestamptz")));
+        assert!(!is_numeric_column(&column("TEXT")));
+    }
+}
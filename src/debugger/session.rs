@@ -98,6 +98,35 @@ impl DebugSession {
         Ok(session_id)
     }
 
+    /// Start a debug session for a program with arguments (e.g. a single test)
+    pub async fn start_with_args(
+        &self,
+        program_path: String,
+        args: Vec<String>,
+        cwd: String,
+        env: HashMap<String, String>,
+    ) -> Result<String, String> {
+        #[derive(Serialize)]
+        struct StartArgs {
+            program_path: String,
+            args: Vec<String>,
+            cwd: String,
+            env: HashMap<String, String>,
+        }
+
+        let session_id: String = TauriBridge::invoke(
+            "debug_start_session",
+            StartArgs { program_path, args, cwd, env },
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        self.session_id.set(Some(session_id.clone()));
+        self.state.set(DebugState::Running);
+
+        Ok(session_id)
+    }
+
     /// Stop the debug session
     pub async fn stop(&self) -> Result<(), String> {
         if let Some(session_id) = self.session_id.get_untracked() {
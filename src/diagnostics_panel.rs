@@ -1,11 +1,18 @@
 //! Diagnostics Panel
 //!
-//! Displays errors, warnings, and information from LSP.
+//! Displays errors, warnings, and information from LSP and from task problem matchers.
 
 use leptos::prelude::*;
 use crate::lsp_ui::Diagnostic;
 use crate::common::ui_components::Panel;
 
+/// Diagnostic reported for a specific file (e.g. by a task's problem matcher)
+#[derive(Debug, Clone)]
+pub struct FileDiagnostic {
+    pub file: String,
+    pub diagnostic: Diagnostic,
+}
+
 /// Diagnostics panel component
 #[component]
 pub fn DiagnosticsPanel(
@@ -13,21 +20,28 @@ pub fn DiagnosticsPanel(
     diagnostics: RwSignal<Vec<Diagnostic>>,
     /// Callback when a diagnostic is clicked (to jump to location)
     on_click: impl Fn(u32, u32) + 'static + Clone + Send,
+    /// Diagnostics in other files (task output)
+    #[prop(optional)]
+    file_diagnostics: Option<Signal<Vec<FileDiagnostic>>>,
+    /// Called with (path, line, column), 1-based, when a file diagnostic is clicked
+    #[prop(optional)]
+    on_open_location: Option<Callback<(String, usize, usize)>>,
 ) -> impl IntoView {
     view! {
         <Panel title="Problems">
             <div class="berry-diagnostics-list">
                 {move || {
                     let diags = diagnostics.get();
+                    let file_diags = file_diagnostics.map(|d| d.get()).unwrap_or_default();
 
-                    if diags.is_empty() {
+                    if diags.is_empty() && file_diags.is_empty() {
                         view! {
                             <div class="berry-diagnostics-empty">
                                 "No problems detected"
                             </div>
                         }.into_any()
                     } else {
-                        diags.iter().map(|diagnostic| {
+                        let current_file = diags.iter().map(|diagnostic| {
                             let on_click_clone = on_click.clone();
                             let line = diagnostic.range.start.line;
                             let character = diagnostic.range.start.character;
@@ -38,7 +52,35 @@ pub fn DiagnosticsPanel(
                                     on_click=move || on_click_clone(line, character)
                                 />
                             }
-                        }).collect::<Vec<_>>().into_any()
+                        }).collect::<Vec<_>>();
+
+                        let other_files = group_by_file(file_diags).into_iter().map(|(file, items)| {
+                            let items = items.into_iter().map(|diagnostic| {
+                                let path = file.clone();
+                                let line = diagnostic.range.start.line as usize + 1;
+                                let column = diagnostic.range.start.character as usize + 1;
+
+                                view! {
+                                    <DiagnosticItem
+                                        diagnostic=diagnostic
+                                        on_click=move || {
+                                            if let Some(callback) = on_open_location {
+                                                callback.run((path.clone(), line, column));
+                                            }
+                                        }
+                                    />
+                                }
+                            }).collect::<Vec<_>>();
+
+                            view! {
+                                <div class="berry-diagnostics-file">
+                                    <div class="berry-diagnostics-file-name">{file}</div>
+                                    {items}
+                                </div>
+                            }
+                        }).collect::<Vec<_>>();
+
+                        view! { {current_file} {other_files} }.into_any()
                     }
                 }}
             </div>
@@ -86,6 +128,18 @@ fn DiagnosticItem(
     }
 }
 
+/// Group file diagnostics by path, keeping first-seen file order
+pub fn group_by_file(diagnostics: Vec<FileDiagnostic>) -> Vec<(String, Vec<Diagnostic>)> {
+    let mut groups: Vec<(String, Vec<Diagnostic>)> = Vec::new();
+    for FileDiagnostic { file, diagnostic } in diagnostics {
+        match groups.iter_mut().find(|(f, _)| *f == file) {
+            Some((_, items)) => items.push(diagnostic),
+            None => groups.push((file, vec![diagnostic])),
+        }
+    }
+    groups
+}
+
 /// Group diagnostics by severity for summary
 pub fn diagnostics_summary(diagnostics: &[Diagnostic]) -> (usize, usize, usize) {
     let errors = diagnostics.iter().filter(|d| d.severity == 1).count();
@@ -138,6 +192,29 @@ mod tests {
         assert_eq!(info, 1);
     }
 
+    #[test]
+    fn test_group_by_file() {
+        let diagnostic = |line: u32| Diagnostic {
+            range: DiagnosticRange {
+                start: DiagnosticPosition { line, character: 0 },
+                end: DiagnosticPosition { line, character: 0 },
+            },
+            severity: 1,
+            message: format!("line {}", line),
+            source: Some("tsc".to_string()),
+        };
+        let groups = group_by_file(vec![
+            FileDiagnostic { file: "b.ts".to_string(), diagnostic: diagnostic(1) },
+            FileDiagnostic { file: "a.ts".to_string(), diagnostic: diagnostic(2) },
+            FileDiagnostic { file: "b.ts".to_string(), diagnostic: diagnostic(3) },
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "b.ts");
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[1].0, "a.ts");
+    }
+
     #[test]
     fn test_empty_diagnostics() {
         let (errors, warnings, info) = diagnostics_summary(&[]);
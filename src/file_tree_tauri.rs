@@ -2,8 +2,14 @@
 //! Uses native file system access via Tauri commands
 
 use leptos::prelude::*;
-use crate::tauri_bindings::{self, FileNode};
+use crate::diagnostics_panel::FileDiagnostic;
+use crate::settings::EditorSettings;
+use crate::tauri_bindings::{self, TreeEntry};
+use crate::tauri_bindings_watcher::{parent, FsChange, FsChangeBatch};
+use crate::tauri_bindings_workspace::{Workspace, WorkspaceFolder};
+use crate::workspace::settings_for;
 use leptos::task::spawn_local;
+use std::collections::HashMap;
 use crate::web_worker::{IndexerWorker, ProgressData};
 
 /// ✅ VS Code Pattern: Codicon-based file/folder icon component
@@ -47,53 +53,203 @@ fn FileIcon(is_dir: bool, expanded: bool, name: String) -> impl IntoView {
     }
 }
 
-#[component]
-pub fn FileTreePanelTauri(
-    on_file_select: RwSignal<Option<(String, String)>>,
-    root_path: String,
-) -> impl IntoView {
-    let tree = RwSignal::new(Vec::<FileNode>::new());
-    let is_loading = RwSignal::new(true);
+/// Loaded folders of the explorer, keyed by folder path. Watcher batches are
+/// applied in place so expanded folders keep their state.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TreeModel {
+    folders: HashMap<String, Vec<TreeEntry>>,
+}
 
-    // ✅ IntelliJ Pro: Symbol indexing state
-    let is_indexing = RwSignal::new(false);
-    let symbol_count = RwSignal::new(0_usize);
+/// Folders first, then case-insensitive by name (same order as the backend)
+fn entry_order(a: &TreeEntry, b: &TreeEntry) -> std::cmp::Ordering {
+    b.is_dir
+        .cmp(&a.is_dir)
+        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+}
 
+impl TreeModel {
+    pub fn children(&self, dir: &str) -> Option<&[TreeEntry]> {
+        self.folders.get(dir).map(Vec::as_slice)
+    }
 
-    // CRITICAL: Load immediately in component body, not in Effect
+    pub fn entry(&self, path: &str) -> Option<&TreeEntry> {
+        self.children(parent(path)?)?.iter().find(|e| e.path == path)
+    }
 
-    // ✅ In test environment, skip Tauri backend calls and show empty tree
-    #[cfg(test)]
-    {
-        tree.set(Vec::new());
-        is_loading.set(false);
+    pub fn set_children(&mut self, dir: &str, entries: Vec<TreeEntry>) {
+        // Folders that disappeared take their loaded subtrees with them
+        if let Some(old) = self.folders.get(dir) {
+            let gone: Vec<String> = old
+                .iter()
+                .filter(|e| e.is_dir && !entries.iter().any(|n| n.path == e.path))
+                .map(|e| e.path.clone())
+                .collect();
+            for path in gone {
+                self.forget_folder(&path);
+            }
+        }
+        self.folders.insert(dir.to_string(), entries);
     }
 
-    // ✅ Only call Tauri backend in non-test environment
-    #[cfg(not(test))]
-    {
-        let root_for_tree = root_path.clone();
-        spawn_local(async move {
-            // ✅ IntelliJ Design: Lazy Loading - load only first level initially
-            // Further levels are loaded on-demand when folders are expanded
-            match tauri_bindings::read_dir(&root_for_tree, Some(1)).await {
-                Ok(nodes) => {
-                    // ✅ Safe: Use .set() to trigger reactivity and update UI
-                    tree.set(nodes);
-                    is_loading.set(false);
-                }
-                Err(e) => {
-                    // ✅ Safe: set empty on error
-                    tree.set(Vec::new());
-                    is_loading.set(false);
+    fn forget_folder(&mut self, dir: &str) {
+        self.folders
+            .retain(|folder, _| folder != dir && parent_chain(folder).all(|p| p != dir));
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entries) = parent(path).and_then(|dir| self.folders.get_mut(dir)) {
+            entries.retain(|e| e.path != path);
+        }
+        self.forget_folder(path);
+    }
+
+    fn upsert(&mut self, entry: TreeEntry) {
+        let Some(entries) = parent(&entry.path).and_then(|dir| self.folders.get_mut(dir)) else {
+            return;
+        };
+        match entries.iter_mut().find(|e| e.path == entry.path) {
+            Some(existing) => *existing = entry,
+            None => {
+                let at = entries.partition_point(|e| entry_order(e, &entry).is_lt());
+                entries.insert(at, entry);
+            }
+        }
+    }
+
+    /// Paths to re-read for `changes`: new and modified entries plus every
+    /// folder above them, whose git status may have changed. Only entries
+    /// inside loaded folders are returned.
+    pub fn paths_to_refresh(&self, changes: &[FsChange]) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        for change in changes {
+            let (target, above) = match change {
+                FsChange::Created { path, .. } | FsChange::Modified { path } => (Some(path), path),
+                FsChange::Renamed { from, to } => {
+                    paths.extend(parent_chain(from).map(str::to_string));
+                    (Some(to), to)
                 }
+                FsChange::Deleted { path } => (None, path),
+            };
+            paths.extend(target.cloned());
+            paths.extend(parent_chain(above).map(str::to_string));
+        }
+        paths.sort();
+        paths.dedup();
+        paths.retain(|path| parent(path).is_some_and(|dir| self.folders.contains_key(dir)));
+        paths
+    }
+
+    /// Drop deleted and renamed-away entries, then merge the re-read ones
+    pub fn apply(&mut self, changes: &[FsChange], refreshed: Vec<TreeEntry>) {
+        for change in changes {
+            match change {
+                FsChange::Deleted { path } | FsChange::Renamed { from: path, .. } => self.remove(path),
+                _ => {}
             }
-        });
+        }
+        for entry in refreshed {
+            self.upsert(entry);
+        }
+    }
+}
+
+/// Ancestors of `path`, nearest first
+fn parent_chain(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(parent(path), |p| parent(p))
+}
+
+/// (errors, warnings) per file and, summed up, per folder below `root`
+pub fn diagnostic_counts(root: &str, diagnostics: &[FileDiagnostic]) -> HashMap<String, (usize, usize)> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for FileDiagnostic { file, diagnostic } in diagnostics {
+        let (errors, warnings) = match diagnostic.severity {
+            1 => (1, 0),
+            2 => (0, 1),
+            _ => continue,
+        };
+        let folders = parent_chain(file).take_while(|dir| dir.len() > root.len() && dir.starts_with(root));
+        for path in std::iter::once(file.as_str()).chain(folders) {
+            let count = counts.entry(path.to_string()).or_default();
+            count.0 += errors;
+            count.1 += warnings;
+        }
     }
+    counts
+}
+
+fn git_class(status: &str) -> &'static str {
+    match status {
+        "A" | "U" => "berry-git-added",
+        "D" => "berry-git-deleted",
+        _ => "berry-git-modified",
+    }
+}
+
+/// Shared by every node of one explorer tree
+#[derive(Clone, Copy)]
+struct TreeContext {
+    model: RwSignal<TreeModel>,
+    exclude: StoredValue<Vec<String>>,
+    root: StoredValue<String>,
+    diagnostics: Memo<HashMap<String, (usize, usize)>>,
+    on_file_select: RwSignal<Option<(String, String)>>,
+}
+
+impl TreeContext {
+    /// (Re-)read one folder into the model
+    async fn load(self, dir: String) -> Result<(), String> {
+        let root = self.root.get_value();
+        let entries = tauri_bindings::list_dir(&root, &dir, &self.exclude.get_value()).await?;
+        self.model.update(|m| m.set_children(&dir, entries));
+        Ok(())
+    }
+}
+
+#[component]
+pub fn FileTreePanelTauri(
+    on_file_select: RwSignal<Option<(String, String)>>,
+    /// Open folders; each gets its own root node when there are several
+    workspace: Workspace,
+    /// Called with the path of a folder removed from the workspace
+    #[prop(optional)]
+    on_remove_folder: Option<Callback<String>>,
+    /// Changes reported by the workspace watcher; applied to the loaded folders in place
+    #[prop(optional)]
+    fs_changes: Option<Signal<Option<FsChangeBatch>>>,
+    /// Diagnostics shown as per-file and per-folder counts
+    #[prop(optional)]
+    diagnostics: Option<Signal<Vec<FileDiagnostic>>>,
+) -> impl IntoView {
+    // ✅ IntelliJ Pro: Symbol indexing state
+    let is_indexing = RwSignal::new(false);
+    let symbol_count = RwSignal::new(0_usize);
+
+    // Folder settings may exclude more files than the user settings
+    let settings = EditorSettings::load();
+    let multi_root = workspace.folders.len() > 1;
+    let roots = workspace
+        .folders
+        .iter()
+        .map(|folder| {
+            let exclude = settings_for(&settings, &workspace, &folder.path).files_exclude;
+            view! {
+                <FileTreeRoot
+                    folder=folder.clone()
+                    exclude=exclude
+                    show_header=multi_root
+                    on_remove_folder=on_remove_folder
+                    on_file_select=on_file_select
+                    fs_changes=fs_changes
+                    diagnostics=diagnostics
+                />
+            }
+        })
+        .collect_view();
 
     // ✅ IntelliJ Pro: Index workspace on button click
+    let primary_root = workspace.folders.first().map(|f| f.path.clone()).unwrap_or_default();
     let on_index_click = move |_| {
-        let root = root_path.clone();
+        let root = primary_root.clone();
         is_indexing.set(true);
 
         spawn_local(async move {
@@ -158,83 +314,195 @@ pub fn FileTreePanelTauri(
                 }}
             </div>
             <div class="berry-editor-file-tree">
-                {move || {
-                    if is_loading.get() {
-                        view! {
-                            <div style="padding: 10px; color: #858585;">
-                                "Loading files..."
-                            </div>
-                        }.into_any()
-                    } else {
-                        let nodes = tree.get();
-                        if nodes.is_empty() {
-                            view! {
-                                <div style="padding: 10px; color: #858585;">
-                                    "No files found"
-                                </div>
-                            }.into_any()
-                        } else {
-                            nodes.iter().map(|node| {
-                                view! {
-                                    <FileTreeNodeTauri node=node.clone() level=0 on_file_select=on_file_select />
-                                }
-                            }).collect_view().into_any()
-                        }
-                    }
-                }}
+                {roots}
             </div>
         </div>
     }
 }
 
+/// The tree of one workspace folder
 #[component]
-fn FileTreeNodeTauri(
-    node: FileNode,
-    level: usize,
+fn FileTreeRoot(
+    folder: WorkspaceFolder,
+    exclude: Vec<String>,
+    /// Show the folder itself as a collapsible node (multi-root workspaces)
+    show_header: bool,
+    on_remove_folder: Option<Callback<String>>,
     on_file_select: RwSignal<Option<(String, String)>>,
+    fs_changes: Option<Signal<Option<FsChangeBatch>>>,
+    diagnostics: Option<Signal<Vec<FileDiagnostic>>>,
 ) -> impl IntoView {
-    // ✅ Make node reactive to update when children are loaded
-    let node_signal = RwSignal::new(node);
+    let is_loading = RwSignal::new(true);
+    let expanded = RwSignal::new(true);
+    let root_path = folder.path.clone();
+
+    let root_for_counts = root_path.clone();
+    let tree = TreeContext {
+        model: RwSignal::new(TreeModel::default()),
+        exclude: StoredValue::new(exclude),
+        root: StoredValue::new(root_path.clone()),
+        diagnostics: Memo::new(move |_| {
+            diagnostics
+                .map(|d| d.with(|d| diagnostic_counts(&root_for_counts, d)))
+                .unwrap_or_default()
+        }),
+        on_file_select,
+    };
+
+    // CRITICAL: Load immediately in component body, not in Effect
+
+    // ✅ In test environment, skip Tauri backend calls and show empty tree
+    #[cfg(test)]
+    {
+        is_loading.set(false);
+    }
+
+    // ✅ Only call Tauri backend in non-test environment
+    #[cfg(not(test))]
+    {
+        let root_for_tree = root_path.clone();
+        spawn_local(async move {
+            // ✅ IntelliJ Design: Lazy Loading - load only first level initially
+            // Further levels are loaded on-demand when folders are expanded
+            if tree.load(root_for_tree.clone()).await.is_err() {
+                tree.model.update(|m| m.set_children(&root_for_tree, Vec::new()));
+            }
+            is_loading.set(false);
+        });
+    }
+
+    // Apply watcher batches of this folder to the loaded folders instead of re-reading them
+    Effect::new(move |_| {
+        let Some(batch) = fs_changes.and_then(|f| f.get()) else {
+            return;
+        };
+        if tree.root.with_value(|root| *root != batch.root) {
+            return;
+        }
+        let paths = tree.model.with_untracked(|m| m.paths_to_refresh(&batch.changes));
+        spawn_local(async move {
+            let refreshed = if paths.is_empty() {
+                Vec::new()
+            } else {
+                tauri_bindings::stat_entries(&tree.root.get_value(), &paths, &tree.exclude.get_value())
+                    .await
+                    .unwrap_or_default()
+            };
+            tree.model.update(|m| m.apply(&batch.changes, refreshed));
+        });
+    });
+
+    let header = show_header.then(|| {
+        let name = folder.display_name();
+        let remove_path = root_path.clone();
+        view! {
+            <div
+                class="berry-editor-file-item berry-workspace-root"
+                style:padding-left="8px"
+                title=root_path.clone()
+                on:click=move |_| expanded.update(|e| *e = !*e)
+            >
+                {
+                    let name = name.clone();
+                    move || view! { <FileIcon is_dir=true expanded=expanded.get() name=name.clone() /> }
+                }
+                <span style="font-weight: 600;">{name}</span>
+                {on_remove_folder.map(|remove| view! {
+                    <span
+                        class="berry-editor-file-decorations"
+                        title="Remove Folder from Workspace"
+                        on:click=move |ev| {
+                            ev.stop_propagation();
+                            remove.run(remove_path.clone());
+                        }
+                    >
+                        <i class="codicon codicon-close"></i>
+                    </span>
+                })}
+            </div>
+        }
+    });
+    let level = usize::from(show_header);
+    let root = root_path;
+
+    view! {
+        {header}
+        {move || {
+            if !expanded.get() {
+                ().into_any()
+            } else if is_loading.get() {
+                view! {
+                    <div style="padding: 10px; color: #858585;">
+                        "Loading files..."
+                    </div>
+                }.into_any()
+            } else if tree.model.with(|m| m.children(&root).is_none_or(|c| c.is_empty())) {
+                view! {
+                    <div style="padding: 10px; color: #858585;">
+                        "No files found"
+                    </div>
+                }.into_any()
+            } else {
+                let root = root.clone();
+                // Keyed by path so expanded folders survive updates
+                view! {
+                    <For
+                        each=move || tree.model.with(|m| m.children(&root).map(|c| c.to_vec()).unwrap_or_default())
+                        key=|entry| entry.path.clone()
+                        let:entry
+                    >
+                        <FileTreeNodeTauri path=entry.path level=level tree=tree />
+                    </For>
+                }.into_any()
+            }
+        }}
+    }
+}
+
+#[component]
+fn FileTreeNodeTauri(path: String, level: usize, tree: TreeContext) -> impl IntoView {
+    // ✅ Read the entry from the model so decorations follow watcher updates
+    let path = StoredValue::new(path);
+    let entry = Memo::new(move |_| tree.model.with(|m| path.with_value(|p| m.entry(p).cloned())));
+    let loaded = Memo::new(move |_| tree.model.with(|m| path.with_value(|p| m.children(p).is_some())));
+    let counts = Memo::new(move |_| tree.diagnostics.with(|d| path.with_value(|p| d.get(p).copied())));
     let expanded = RwSignal::new(false);
     let is_loading_children = RwSignal::new(false);
     let indent = (level * 16) + 8;
+    let on_file_select = tree.on_file_select;
 
     view! {
         <div>
             <div
-                class="berry-editor-file-item"
+                class=move || {
+                    let ignored = entry.with(|e| e.as_ref().is_some_and(|e| e.ignored));
+                    if ignored { "berry-editor-file-item ignored" } else { "berry-editor-file-item" }
+                }
                 style:padding-left=format!("{}px", indent)
                 on:click=move |_| {
-                    let current_node = node_signal.get_untracked();
+                    let Some(current_node) = entry.get_untracked() else {
+                        return;
+                    };
 
                     if current_node.is_dir {
                         // Toggle folder expansion
                         if !expanded.get_untracked() {
-                            // Opening folder - check if we need to load children
-                            if current_node.children.is_none() {
-                                // ✅ IntelliJ Design: On-demand loading
+                            // ✅ IntelliJ Design: On-demand loading. Loaded folders are
+                            // shown at once and re-read in the background, since changes
+                            // inside ignored folders are not reported by the watcher
+                            if !loaded.get_untracked() {
                                 is_loading_children.set(true);
-                                let path = current_node.path.clone();
-
-                                spawn_local(async move {
-                                    // Load only first level (depth=1) for memory efficiency
-                                    match tauri_bindings::read_dir(&path, Some(1)).await {
-                                        Ok(children) => {
-                                            // ✅ Safe: Update node and UI
-                                            node_signal.update(|n| n.children = Some(children));
-                                            is_loading_children.set(false);
-                                            expanded.set(true);
-                                        }
-                                        Err(_) => {
-                                            // ✅ Safe: set on error
-                                            is_loading_children.set(false);
-                                        }
-                                    }
-                                });
                             } else {
-                                // Children already loaded, just expand
                                 expanded.set(true);
                             }
+                            let dir = current_node.path.clone();
+                            spawn_local(async move {
+                                let ok = tree.load(dir).await.is_ok();
+                                is_loading_children.set(false);
+                                if ok {
+                                    expanded.set(true);
+                                }
+                            });
                         } else {
                             // Closing folder
                             expanded.set(false);
@@ -312,35 +580,53 @@ fn FileTreeNodeTauri(
                 }
             >
                 {move || {
-                    let current_node = node_signal.get();
-                    view! {
-                        <FileIcon
-                            is_dir=current_node.is_dir
-                            expanded=expanded.get()
-                            name=current_node.name.clone()
-                        />
-                        <span>{current_node.name.clone()}</span>
-                    }
+                    entry.get().map(|current_node| {
+                        let name_class = current_node.git_status.as_deref().map(git_class).unwrap_or("");
+                        let git_badge = current_node.git_status.clone().map(|status| {
+                            // Folders get a dot; files show the status letter
+                            let text = if current_node.is_dir { "●".to_string() } else { status.clone() };
+                            view! { <span class=git_class(&status)>{text}</span> }
+                        });
+                        let problems = counts.get().map(|(errors, warnings)| {
+                            if errors > 0 {
+                                view! { <span class="berry-file-errors" title="Errors">{errors}</span> }.into_any()
+                            } else {
+                                view! { <span class="berry-file-warnings" title="Warnings">{warnings}</span> }.into_any()
+                            }
+                        });
+                        view! {
+                            <FileIcon
+                                is_dir=current_node.is_dir
+                                expanded=expanded.get()
+                                name=current_node.name.clone()
+                            />
+                            <span class=name_class>{current_node.name.clone()}</span>
+                            <span class="berry-editor-file-decorations">
+                                {problems}
+                                {git_badge}
+                            </span>
+                        }
+                    })
                 }}
             </div>
             {move || {
-                let current_node = node_signal.get();
-                if current_node.is_dir && expanded.get() {
-                    if let Some(ref children) = current_node.children {
-                        children.iter().map(|child| {
-                            view! {
-                                <FileTreeNodeTauri node=child.clone() level=level + 1 on_file_select=on_file_select />
-                            }
-                        }).collect_view().into_any()
-                    } else if is_loading_children.get() {
-                        view! {
-                            <div style=format!("padding-left: {}px; color: #858585; font-size: 11px;", indent + 16)>
-                                "Loading..."
-                            </div>
-                        }.into_any()
-                    } else {
-                        view! { <></> }.into_any()
-                    }
+                let is_dir = entry.with(|e| e.as_ref().is_some_and(|e| e.is_dir));
+                if is_dir && expanded.get() && loaded.get() {
+                    view! {
+                        <For
+                            each=move || tree.model.with(|m| path.with_value(|p| m.children(p).map(|c| c.to_vec()).unwrap_or_default()))
+                            key=|child| child.path.clone()
+                            let:child
+                        >
+                            <FileTreeNodeTauri path=child.path level=level + 1 tree=tree />
+                        </For>
+                    }.into_any()
+                } else if is_loading_children.get() {
+                    view! {
+                        <div style=format!("padding-left: {}px; color: #858585; font-size: 11px;", indent + 16)>
+                            "Loading..."
+                        </div>
+                    }.into_any()
                 } else {
                     view! { <></> }.into_any()
                 }
@@ -348,3 +634,85 @@ fn FileTreeNodeTauri(
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp_ui::{Diagnostic, DiagnosticPosition, DiagnosticRange};
+
+    fn entry(path: &str, is_dir: bool) -> TreeEntry {
+        TreeEntry {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            is_dir,
+            ignored: false,
+            git_status: None,
+        }
+    }
+
+    fn names(model: &TreeModel, dir: &str) -> Vec<String> {
+        model.children(dir).unwrap().iter().map(|e| e.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_tree_model_applies_changes() {
+        let mut model = TreeModel::default();
+        model.set_children("/ws", vec![entry("/ws/src", true), entry("/ws/a.txt", false)]);
+        model.set_children("/ws/src", vec![entry("/ws/src/lib.rs", false)]);
+
+        let changes = vec![
+            FsChange::Created { path: "/ws/src/main.rs".into(), is_dir: false },
+            FsChange::Created { path: "/ws/target/x".into(), is_dir: false },
+            FsChange::Renamed { from: "/ws/a.txt".into(), to: "/ws/B.txt".into() },
+        ];
+        // Unloaded folders are not re-read; ancestors are, for their git status
+        assert_eq!(
+            model.paths_to_refresh(&changes),
+            vec!["/ws/B.txt", "/ws/src", "/ws/src/main.rs", "/ws/target"]
+        );
+
+        let mut modified = entry("/ws/src", true);
+        modified.git_status = Some("U".into());
+        model.apply(
+            &changes,
+            vec![entry("/ws/src/main.rs", false), entry("/ws/B.txt", false), modified],
+        );
+        assert_eq!(names(&model, "/ws"), vec!["src", "B.txt"]);
+        assert_eq!(names(&model, "/ws/src"), vec!["lib.rs", "main.rs"]);
+        assert_eq!(model.entry("/ws/src").unwrap().git_status.as_deref(), Some("U"));
+
+        // Deleting a folder forgets its loaded children
+        model.apply(&[FsChange::Deleted { path: "/ws/src".into() }], Vec::new());
+        assert_eq!(names(&model, "/ws"), vec!["B.txt"]);
+        assert!(model.children("/ws/src").is_none());
+    }
+
+    #[test]
+    fn test_diagnostic_counts() {
+        let diagnostic = |file: &str, severity: u32| FileDiagnostic {
+            file: file.to_string(),
+            diagnostic: Diagnostic {
+                range: DiagnosticRange {
+                    start: DiagnosticPosition { line: 0, character: 0 },
+                    end: DiagnosticPosition { line: 0, character: 0 },
+                },
+                severity,
+                message: String::new(),
+                source: None,
+            },
+        };
+        let counts = diagnostic_counts(
+            "/ws",
+            &[
+                diagnostic("/ws/src/a.rs", 1),
+                diagnostic("/ws/src/a.rs", 2),
+                diagnostic("/ws/src/b.rs", 2),
+                diagnostic("/ws/src/b.rs", 4),
+            ],
+        );
+        assert_eq!(counts.get("/ws/src/a.rs"), Some(&(1, 1)));
+        assert_eq!(counts.get("/ws/src/b.rs"), Some(&(0, 1)));
+        assert_eq!(counts.get("/ws/src"), Some(&(1, 2)));
+        assert_eq!(counts.get("/ws"), None);
+    }
+}
@@ -84,6 +84,75 @@ impl FileDiff {
     }
 }
 
+/// A diff hunk between two versions of a file (mirrors the backend `DiffHunk`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HunkLine {
+    /// "add", "delete" or "context"
+    pub line_type: String,
+    pub content: String,
+    pub old_line_no: Option<u32>,
+    pub new_line_no: Option<u32>,
+}
+
+impl Hunk {
+    /// Text removed by this hunk
+    pub fn old_text(&self) -> String {
+        self.lines
+            .iter()
+            .filter(|l| l.line_type == "delete")
+            .map(|l| l.content.as_str())
+            .collect()
+    }
+
+    /// Text added by this hunk
+    pub fn new_text(&self) -> String {
+        self.lines
+            .iter()
+            .filter(|l| l.line_type == "add")
+            .map(|l| l.content.as_str())
+            .collect()
+    }
+}
+
+/// Hunks (and optionally some of their lines) to stage, unstage or discard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkSelection {
+    pub hunk_index: usize,
+    /// Indices into the hunk's lines; None selects the whole hunk
+    pub lines: Option<Vec<usize>>,
+}
+
+impl HunkSelection {
+    pub fn hunk(hunk_index: usize) -> Self {
+        Self { hunk_index, lines: None }
+    }
+}
+
+async fn invoke_git<T: serde::de::DeserializeOwned>(cmd: &str, args: serde_json::Value) -> Result<T, String> {
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+        async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+    }
+
+    let args = serde_wasm_bindgen::to_value(&args).map_err(|e| format!("Failed to serialize args: {}", e))?;
+    let result = tauri_invoke(cmd, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{} failed", cmd)))?;
+
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize {}: {}", cmd, e))
+}
+
 pub struct GitDiffTracker {
     current_file: Option<String>,
     diff_cache: HashMap<String, FileDiff>,
@@ -193,6 +262,111 @@ impl GitDiffTracker {
         file_diff
     }
 
+    /// Gutter changes for hunks, by new (1-based) line number
+    ///
+    /// Deletions followed by additions mark the added lines as modified; the
+    /// first of them carries the replaced text. Pure deletions mark the line
+    /// after them.
+    pub fn changes_from_hunks(file_path: String, hunks: &[Hunk]) -> FileDiff {
+        let mut file_diff = FileDiff::new(file_path);
+        for hunk in hunks {
+            for change in Self::hunk_changes(hunk) {
+                file_diff.add_change(change);
+            }
+        }
+        file_diff
+    }
+
+    fn hunk_changes(hunk: &Hunk) -> Vec<LineChange> {
+        let mut changes = Vec::new();
+        let mut deleted = String::new();
+        let mut next_new_line = hunk.new_start.max(1) as usize;
+
+        let flush_deleted = |changes: &mut Vec<LineChange>, deleted: &mut String, line: usize| {
+            if !deleted.is_empty() {
+                changes.push(LineChange::with_old_content(line, ChangeType::Deleted, std::mem::take(deleted)));
+            }
+        };
+
+        for line in &hunk.lines {
+            match line.line_type.as_str() {
+                "delete" => deleted.push_str(&line.content),
+                "add" => {
+                    let line_number = line.new_line_no.map(|n| n as usize).unwrap_or(next_new_line);
+                    if deleted.is_empty() {
+                        let modified = changes
+                            .last()
+                            .is_some_and(|c: &LineChange| c.change_type == ChangeType::Modified && c.line_number + 1 == line_number);
+                        let change_type = if modified { ChangeType::Modified } else { ChangeType::Added };
+                        changes.push(LineChange::new(line_number, change_type));
+                    } else {
+                        changes.push(LineChange::with_old_content(
+                            line_number,
+                            ChangeType::Modified,
+                            std::mem::take(&mut deleted),
+                        ));
+                    }
+                    next_new_line = line_number + 1;
+                }
+                _ => {
+                    flush_deleted(&mut changes, &mut deleted, next_new_line);
+                    next_new_line = line.new_line_no.map(|n| n as usize + 1).unwrap_or(next_new_line);
+                }
+            }
+        }
+        flush_deleted(&mut changes, &mut deleted, next_new_line);
+
+        changes
+    }
+
+    /// Index of the hunk with a change on a (1-based) line
+    pub fn hunk_at_line(hunks: &[Hunk], line: usize) -> Option<usize> {
+        hunks
+            .iter()
+            .position(|hunk| Self::hunk_changes(hunk).iter().any(|c| c.line_number == line))
+    }
+
+    /// Point the backend at the repository containing the project
+    pub async fn set_repo_path(path: String) -> Result<(), String> {
+        invoke_git("git_set_repo_path", serde_json::json!({ "path": path })).await
+    }
+
+    /// Load a file's hunks: unstaged (work tree vs index) or staged (index vs HEAD)
+    pub async fn load_hunks(file_path: String, staged: bool) -> Result<Vec<Hunk>, String> {
+        invoke_git("git_file_hunks", serde_json::json!({
+            "filePath": file_path,
+            "staged": staged,
+        }))
+        .await
+    }
+
+    /// Stage hunks or lines of a file
+    pub async fn stage_hunks(file_path: String, selections: Vec<HunkSelection>) -> Result<(), String> {
+        invoke_git("git_stage_hunks", serde_json::json!({
+            "filePath": file_path,
+            "selections": selections,
+        }))
+        .await
+    }
+
+    /// Unstage hunks or lines of a file
+    pub async fn unstage_hunks(file_path: String, selections: Vec<HunkSelection>) -> Result<(), String> {
+        invoke_git("git_unstage_hunks", serde_json::json!({
+            "filePath": file_path,
+            "selections": selections,
+        }))
+        .await
+    }
+
+    /// Revert hunks or lines of a file in the work tree
+    pub async fn discard_hunks(file_path: String, selections: Vec<HunkSelection>) -> Result<(), String> {
+        invoke_git("git_discard_hunks", serde_json::json!({
+            "filePath": file_path,
+            "selections": selections,
+        }))
+        .await
+    }
+
     /// Get all files with changes
     pub fn get_changed_files(&self) -> Vec<String> {
         self.diff_cache.keys().cloned().collect()
@@ -346,4 +520,45 @@ mod tests {
         let no_change = diff.get_change_at_line(20);
         assert!(no_change.is_none());
     }
+
+    fn hunk_line(line_type: &str, content: &str, old: Option<u32>, new: Option<u32>) -> HunkLine {
+        HunkLine {
+            line_type: line_type.to_string(),
+            content: content.to_string(),
+            old_line_no: old,
+            new_line_no: new,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_changes_from_hunks() {
+        let hunk = Hunk {
+            old_start: 1,
+            old_lines: 5,
+            new_start: 1,
+            new_lines: 5,
+            lines: vec![
+                hunk_line("context", "a\n", Some(1), Some(1)),
+                hunk_line("delete", "b\n", Some(2), None),
+                hunk_line("add", "B\n", None, Some(2)),
+                hunk_line("add", "B2\n", None, Some(3)),
+                hunk_line("context", "c\n", Some(3), Some(4)),
+                hunk_line("delete", "d\n", Some(4), None),
+                hunk_line("context", "e\n", Some(5), Some(5)),
+            ],
+        };
+
+        let diff = GitDiffTracker::changes_from_hunks("f.txt".to_string(), &[hunk.clone()]);
+        let modified = diff.get_change_at_line(2).unwrap();
+        assert_eq!(modified.change_type, ChangeType::Modified);
+        assert_eq!(modified.old_content.as_deref(), Some("b\n"));
+        assert_eq!(diff.get_change_at_line(3).unwrap().change_type, ChangeType::Modified);
+        assert_eq!(diff.get_change_at_line(5).unwrap().change_type, ChangeType::Deleted);
+        assert!(diff.get_change_at_line(4).is_none());
+
+        assert_eq!(GitDiffTracker::hunk_at_line(&[hunk.clone()], 3), Some(0));
+        assert_eq!(GitDiffTracker::hunk_at_line(&[hunk.clone()], 1), None);
+        assert_eq!(hunk.old_text(), "b\nd\n");
+        assert_eq!(hunk.new_text(), "B\nB2\n");
+    }
 }
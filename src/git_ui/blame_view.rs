@@ -141,7 +141,7 @@ pub fn BlameGutter(
     blame_info: Option<BlameLineInfo>,
 ) -> impl IntoView {
     view! {
-        <div class="berry-blame-gutter-inline">
+        <div class="berry-blame-gutter-inline" data-line=line_number>
             {move || {
                 if let Some(info) = blame_info.clone() {
                     let short_commit = info.commit_id.chars().take(7).collect::<String>();
@@ -242,13 +242,26 @@ async fn load_blame(file_path: &str, file_lines: &[String]) -> anyhow::Result<Ve
     Ok(blame_info)
 }
 
-fn format_relative_time(timestamp: i64) -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
+/// Current Unix time in seconds (`SystemTime::now()` panics in the browser)
+fn now_secs() -> i64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Date::now() / 1000.0) as i64
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+}
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
+fn format_relative_time(timestamp: i64) -> String {
+    let now = now_secs();
 
     let diff = now - timestamp;
 
@@ -269,11 +282,8 @@ fn format_relative_time(timestamp: i64) -> String {
 
 fn format_timestamp(timestamp: i64) -> String {
     use chrono::{DateTime, Utc};
-    use std::time::{SystemTime, UNIX_EPOCH};
 
-    let duration = std::time::Duration::from_secs(timestamp as u64);
-    let system_time = UNIX_EPOCH + duration;
-    let datetime: DateTime<Utc> = system_time.into();
+    let datetime: DateTime<Utc> = DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
 
     datetime.format("%Y-%m-%d %H:%M:%S UTC").to_string()
 }
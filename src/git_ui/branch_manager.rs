@@ -6,7 +6,7 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use serde::{Deserialize, Serialize};
 use crate::common::async_bridge::TauriBridge;
-use crate::common::ui_components::{Panel, Button};
+use crate::common::ui_components::Panel;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BranchInfo {
@@ -152,7 +152,7 @@ pub fn BranchManagerPanel() -> impl IntoView {
                             </div>
                         }.into_any()
                     } else {
-                        view! { <></> }.into_any()
+                        ().into_any()
                     }
                 }}
 
@@ -211,7 +211,7 @@ pub fn BranchManagerPanel() -> impl IntoView {
                                                     </>
                                                 }.into_any()
                                             } else {
-                                                view! { <></> }.into_any()
+                                                ().into_any()
                                             }}
                                         </div>
                                     }
@@ -1,12 +1,25 @@
 //! Commit History View
 //!
-//! Display commit log with details
+//! Commit graph with paging and filters, commit details, ref comparison and
+//! per-file history
 
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use serde::{Deserialize, Serialize};
 use crate::common::async_bridge::TauriBridge;
 use crate::common::ui_components::Panel;
+use crate::git::Hunk;
+
+const PAGE_SIZE: usize = 100;
+
+/// Graph geometry (px)
+const LANE_WIDTH: f64 = 14.0;
+const ROW_HEIGHT: f64 = 56.0;
+const NODE_RADIUS: f64 = 4.0;
+
+const LANE_COLORS: [&str; 8] = [
+    "#4ec9b0", "#569cd6", "#c586c0", "#dcdcaa", "#ce9178", "#9cdcfe", "#d16969", "#b5cea8",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitInfo {
@@ -19,75 +32,348 @@ pub struct CommitInfo {
     pub parents: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphRow {
+    pub commit: CommitInfo,
+    pub lane: usize,
+    pub incoming: Vec<GraphEdge>,
+    pub outgoing: Vec<GraphEdge>,
+    pub refs: Vec<String>,
+}
+
+/// Log filters (mirrors the backend `LogQuery`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQuery {
+    pub cursor: Option<LogCursor>,
+    pub limit: usize,
+    pub author: Option<String>,
+    pub path: Option<String>,
+    pub message: Option<String>,
+    pub all_branches: bool,
+}
+
+/// Where the next page of a history walk starts (mirrors the backend `LogCursor`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogCursor {
+    pub pending: Vec<String>,
+    pub lanes: Vec<Option<String>>,
+    pub path: Option<String>,
+}
+
+/// One page of commits or file history; `cursor` is `None` after the last page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage<T> {
+    pub items: Vec<T>,
+    pub cursor: Option<LogCursor>,
+}
+
+/// A changed file in a commit or comparison (mirrors the backend `FileDiff`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: String,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitDetail {
+    pub info: CommitInfo,
+    pub files: Vec<ChangedFile>,
+    pub stats: DiffStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefComparison {
+    pub base: String,
+    pub target: String,
+    pub ahead: Vec<CommitInfo>,
+    pub behind: usize,
+    pub files: Vec<ChangedFile>,
+    pub stats: DiffStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: String,
+}
+
+pub fn lane_color(lane: usize) -> &'static str {
+    LANE_COLORS[lane % LANE_COLORS.len()]
+}
+
+fn lane_x(lane: usize) -> f64 {
+    LANE_WIDTH * lane as f64 + LANE_WIDTH / 2.0
+}
+
+/// SVG path for a segment between two lanes, from height `y1` to `y2`
+fn segment_path(from: usize, to: usize, y1: f64, y2: f64) -> String {
+    let (x1, x2) = (lane_x(from), lane_x(to));
+    if from == to {
+        format!("M{} {} L{} {}", x1, y1, x2, y2)
+    } else {
+        let ym = (y1 + y2) / 2.0;
+        format!("M{} {} C{} {} {} {} {} {}", x1, y1, x1, ym, x2, ym, x2, y2)
+    }
+}
+
+/// SVG paths (with their lane color) for the graph cell of a row
+pub fn graph_paths(row: &GraphRow) -> Vec<(String, &'static str)> {
+    let mid = ROW_HEIGHT / 2.0;
+    let incoming = row
+        .incoming
+        .iter()
+        .map(|e| (segment_path(e.from, e.to, 0.0, mid), lane_color(e.from)));
+    // Branch-outs take the color of the new lane, merges keep their own
+    let outgoing = row
+        .outgoing
+        .iter()
+        .map(|e| (segment_path(e.from, e.to, mid, ROW_HEIGHT), lane_color(e.from.max(e.to))));
+    incoming.chain(outgoing).collect()
+}
+
+/// Width of the graph column for a set of rows
+pub fn graph_width(rows: &[GraphRow]) -> f64 {
+    let lanes = rows
+        .iter()
+        .flat_map(|row| {
+            std::iter::once(row.lane)
+                .chain(row.incoming.iter().chain(&row.outgoing).flat_map(|e| [e.from, e.to]))
+        })
+        .max()
+        .map_or(1, |max| max + 1);
+    LANE_WIDTH * lanes as f64
+}
+
+fn format_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn first_line(message: &str) -> String {
+    message.lines().next().unwrap_or("").to_string()
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
 /// Commit History Panel
 #[component]
 pub fn CommitHistoryPanel() -> impl IntoView {
-    let commits = RwSignal::new(Vec::<CommitInfo>::new());
+    let rows = RwSignal::new(Vec::<GraphRow>::new());
     let selected_commit = RwSignal::new(Option::<String>::None);
     let loading = RwSignal::new(false);
+    let cursor = RwSignal::new(None::<LogCursor>);
+    let has_more = Memo::new(move |_| cursor.with(Option::is_some));
     let error = RwSignal::new(None::<String>);
 
-    // Load commits on mount
-    Effect::new(move || {
+    let author = RwSignal::new(String::new());
+    let path = RwSignal::new(String::new());
+    let message = RwSignal::new(String::new());
+    let all_branches = RwSignal::new(true);
+
+    let compare_base = RwSignal::new(String::new());
+    let compare_target = RwSignal::new(String::from("HEAD"));
+    let comparison = RwSignal::new(None::<RefComparison>);
+
+    // Load the first page (reset = true) or append the next one
+    let load = move |reset: bool| {
+        let query = LogQuery {
+            cursor: if reset { None } else { cursor.get_untracked() },
+            limit: PAGE_SIZE,
+            author: non_empty(author.get_untracked()),
+            path: non_empty(path.get_untracked()),
+            message: non_empty(message.get_untracked()),
+            all_branches: all_branches.get_untracked(),
+        };
+        loading.set(true);
         spawn_local(async move {
-            loading.set(true);
-            match load_commits().await {
-                Ok(commit_list) => {
-                    commits.set(commit_list);
+            match load_graph(query).await {
+                Ok(page) => {
+                    cursor.set(page.cursor);
+                    if reset {
+                        rows.set(page.items);
+                    } else {
+                        rows.update(|rows| rows.extend(page.items));
+                    }
                     error.set(None);
                 }
-                Err(e) => {
-                    error.set(Some(format!("Failed to load commits: {}", e)));
-                }
+                Err(e) => error.set(Some(format!("Failed to load commits: {}", e))),
             }
             loading.set(false);
         });
-    });
+    };
+
+    // Load commits on mount
+    Effect::new(move || load(true));
+
+    let compare = move || {
+        let (Some(base), Some(target)) = (
+            non_empty(compare_base.get_untracked()),
+            non_empty(compare_target.get_untracked()),
+        ) else {
+            return;
+        };
+        spawn_local(async move {
+            match load_comparison(&base, &target).await {
+                Ok(result) => {
+                    comparison.set(Some(result));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Failed to compare: {}", e))),
+            }
+        });
+    };
+
+    let on_filter_key = move |ev: leptos::ev::KeyboardEvent| {
+        if ev.key() == "Enter" {
+            load(true);
+        }
+    };
 
     view! {
         <Panel title="Commit History">
             <div class="berry-commit-history">
-                {move || {
-                    if loading.get() {
-                        view! {
-                            <div class="berry-git-loading">"Loading..."</div>
-                        }.into_any()
-                    } else if let Some(err) = error.get() {
-                        view! {
-                            <div class="berry-git-error">{err}</div>
-                        }.into_any()
-                    } else {
-                        let commit_list = commits.get();
+                <div class="berry-commit-filters">
+                    <input
+                        type="text"
+                        placeholder="Author"
+                        prop:value=move || author.get()
+                        on:input=move |ev| author.set(event_target_value(&ev))
+                        on:keydown=on_filter_key
+                    />
+                    <input
+                        type="text"
+                        placeholder="Path"
+                        prop:value=move || path.get()
+                        on:input=move |ev| path.set(event_target_value(&ev))
+                        on:keydown=on_filter_key
+                    />
+                    <input
+                        type="text"
+                        placeholder="Message"
+                        prop:value=move || message.get()
+                        on:input=move |ev| message.set(event_target_value(&ev))
+                        on:keydown=on_filter_key
+                    />
+                    <label title="Include all local and remote branches">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || all_branches.get()
+                            on:change=move |ev| {
+                                all_branches.set(event_target_checked(&ev));
+                                load(true);
+                            }
+                        />
+                        "All branches"
+                    </label>
+                    <button on:click=move |_| load(true)>"Filter"</button>
+                </div>
 
-                        if commit_list.is_empty() {
-                            view! {
-                                <div class="berry-git-empty">"No commits"</div>
-                            }.into_any()
-                        } else {
-                            commit_list.iter().map(|commit| {
-                                let hash = commit.hash.clone();
-                                let is_selected = selected_commit.get().as_ref() == Some(&hash);
-
-                                view! {
-                                    <CommitItem
-                                        commit=commit.clone()
-                                        selected=is_selected
-                                        on_select=move || selected_commit.set(Some(hash.clone()))
-                                    />
-                                }
-                            }).collect::<Vec<_>>().into_any()
+                <div class="berry-commit-compare">
+                    <input
+                        type="text"
+                        placeholder="Base ref"
+                        prop:value=move || compare_base.get()
+                        on:input=move |ev| compare_base.set(event_target_value(&ev))
+                    />
+                    <span>".."</span>
+                    <input
+                        type="text"
+                        placeholder="Target ref"
+                        prop:value=move || compare_target.get()
+                        on:input=move |ev| compare_target.set(event_target_value(&ev))
+                    />
+                    <button on:click=move |_| compare()>"Compare"</button>
+                </div>
+
+                {move || error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+
+                {move || comparison.get().map(|result| view! {
+                    <ComparisonView
+                        comparison=result
+                        on_close=Callback::new(move |_| comparison.set(None))
+                    />
+                })}
+
+                <div class="berry-commit-list">
+                    {move || {
+                        let row_list = rows.get();
+                        if row_list.is_empty() {
+                            return if loading.get() {
+                                view! { <div class="berry-git-loading">"Loading..."</div> }.into_any()
+                            } else {
+                                view! { <div class="berry-git-empty">"No commits"</div> }.into_any()
+                            };
                         }
-                    }
-                }}
+
+                        let width = graph_width(&row_list);
+                        row_list.into_iter().map(|row| {
+                            let hash = row.commit.hash.clone();
+                            let is_selected = selected_commit.get().as_ref() == Some(&hash);
+                            view! {
+                                <CommitItem
+                                    row=row
+                                    graph_width=width
+                                    selected=is_selected
+                                    on_select=move || {
+                                        selected_commit.update(|current| {
+                                            *current = if current.as_ref() == Some(&hash) {
+                                                None
+                                            } else {
+                                                Some(hash.clone())
+                                            };
+                                        })
+                                    }
+                                />
+                            }
+                        }).collect::<Vec<_>>().into_any()
+                    }}
+                </div>
+
+                {move || has_more.get().then(|| view! {
+                    <button
+                        class="berry-commit-load-more"
+                        disabled=move || loading.get()
+                        on:click=move |_| load(false)
+                    >
+                        {move || if loading.get() { "Loading..." } else { "Load more" }}
+                    </button>
+                })}
+
+                {move || selected_commit.get().map(|hash| view! { <CommitDetailView commit=hash /> })}
             </div>
         </Panel>
     }
 }
 
-/// Single commit item
+/// Single commit row with its graph cell
 #[component]
 fn CommitItem(
-    commit: CommitInfo,
+    row: GraphRow,
+    graph_width: f64,
     selected: bool,
     on_select: impl Fn() + 'static,
 ) -> impl IntoView {
@@ -97,47 +383,342 @@ fn CommitItem(
         "berry-commit-item"
     };
 
-    // Format timestamp
-    let timestamp = commit.timestamp;
-    let datetime = chrono::DateTime::from_timestamp(timestamp, 0)
-        .unwrap_or_else(|| chrono::Utc::now().into());
-    let time_str = datetime.format("%Y-%m-%d %H:%M").to_string();
-
-    // Extract first line of commit message
-    let first_line = commit.message.lines().next().unwrap_or("").to_string();
+    let paths = graph_paths(&row)
+        .into_iter()
+        .map(|(d, color)| view! { <path d=d stroke=color stroke-width="2" fill="none" /> })
+        .collect::<Vec<_>>();
+    let node_x = lane_x(row.lane);
+    let node_color = lane_color(row.lane);
+    let commit = row.commit;
+    let time_str = format_time(commit.timestamp);
+    let message = first_line(&commit.message);
 
     view! {
         <div class=class on:click=move |_| on_select()>
-            <div class="berry-commit-header">
-                <span class="berry-commit-hash">{commit.short_hash}</span>
-                <span class="berry-commit-time">{time_str}</span>
+            <svg
+                class="berry-commit-graph"
+                width=graph_width
+                height=ROW_HEIGHT
+                viewBox=format!("0 0 {} {}", graph_width, ROW_HEIGHT)
+            >
+                {paths}
+                <circle cx=node_x cy=ROW_HEIGHT / 2.0 r=NODE_RADIUS fill=node_color />
+            </svg>
+            <div class="berry-commit-body">
+                <div class="berry-commit-header">
+                    <span class="berry-commit-hash">{commit.short_hash}</span>
+                    <span class="berry-commit-time">{time_str}</span>
+                </div>
+                <div class="berry-commit-message">
+                    {row.refs.into_iter().map(|name| view! {
+                        <span class="berry-commit-ref">{name}</span>
+                    }).collect::<Vec<_>>()}
+                    {message}
+                </div>
+                <div class="berry-commit-author">{commit.author}</div>
             </div>
-            <div class="berry-commit-message">{first_line}</div>
-            <div class="berry-commit-author">{commit.author}</div>
         </div>
     }
 }
 
-async fn load_commits() -> anyhow::Result<Vec<CommitInfo>> {
+/// Full change set of one commit
+#[component]
+fn CommitDetailView(
+    commit: String,
+    /// File to expand initially
+    #[prop(optional)]
+    expanded: Option<String>,
+) -> impl IntoView {
+    let detail = RwSignal::new(None::<CommitDetail>);
+    let error = RwSignal::new(None::<String>);
+
+    Effect::new(move || {
+        let commit = commit.clone();
+        spawn_local(async move {
+            match load_commit_detail(&commit).await {
+                Ok(loaded) => detail.set(Some(loaded)),
+                Err(e) => error.set(Some(format!("Failed to load commit: {}", e))),
+            }
+        });
+    });
+
+    view! {
+        <div class="berry-commit-detail">
+            {move || error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+            {move || detail.get().map(|detail| {
+                let info = detail.info;
+                view! {
+                    <div class="berry-commit-detail-header">
+                        <div class="berry-commit-hash">{info.hash}</div>
+                        <div class="berry-commit-author">
+                            {format!("{} <{}> · {}", info.author, info.email, format_time(info.timestamp))}
+                        </div>
+                        <pre class="berry-commit-detail-message">{info.message}</pre>
+                    </div>
+                    <ChangeSet files=detail.files stats=detail.stats expanded=expanded.clone() />
+                }
+            })}
+        </div>
+    }
+}
+
+/// Commits and changes between two refs
+#[component]
+fn ComparisonView(comparison: RefComparison, on_close: Callback<()>) -> impl IntoView {
+    let summary = format!(
+        "{}..{}: {} ahead, {} behind",
+        comparison.base,
+        comparison.target,
+        comparison.ahead.len(),
+        comparison.behind
+    );
+
+    view! {
+        <div class="berry-commit-detail">
+            <div class="berry-commit-detail-header">
+                <span>{summary}</span>
+                <button title="Close" on:click=move |_| on_close.run(())>"×"</button>
+            </div>
+            {comparison.ahead.into_iter().map(|commit| view! {
+                <div class="berry-commit-compare-item">
+                    <span class="berry-commit-hash">{commit.short_hash}</span>
+                    {format!(" {}", first_line(&commit.message))}
+                </div>
+            }).collect::<Vec<_>>()}
+            <ChangeSet files=comparison.files stats=comparison.stats />
+        </div>
+    }
+}
+
+/// Changed files with expandable per-file diffs
+#[component]
+fn ChangeSet(
+    files: Vec<ChangedFile>,
+    stats: DiffStats,
+    #[prop(default = None)]
+    expanded: Option<String>,
+) -> impl IntoView {
+    let expanded = RwSignal::new(expanded);
+    let summary = format!(
+        "{} files changed, +{} -{}",
+        stats.files_changed, stats.insertions, stats.deletions
+    );
+
+    view! {
+        <div class="berry-changeset">
+            <div class="berry-changeset-summary">{summary}</div>
+            {files.into_iter().map(|file| {
+                let path = file.path.clone();
+                let toggle_path = path.clone();
+                let is_open = move || expanded.get().as_deref() == Some(path.as_str());
+                let label = match &file.old_path {
+                    Some(old) => format!("{} → {}", old, file.path),
+                    None => file.path.clone(),
+                };
+                let hunks = file.hunks;
+                view! {
+                    <div class="berry-changeset-file">
+                        <div
+                            class="berry-changeset-file-header"
+                            on:click=move |_| expanded.update(|current| {
+                                *current = if current.as_deref() == Some(toggle_path.as_str()) {
+                                    None
+                                } else {
+                                    Some(toggle_path.clone())
+                                };
+                            })
+                        >
+                            <span class=format!("berry-git-status berry-git-status-{}", file.status.to_lowercase())>
+                                {file.status.clone()}
+                            </span>
+                            <span>{label}</span>
+                        </div>
+                        {move || is_open().then(|| {
+                            if hunks.is_empty() {
+                                view! { <div class="berry-git-empty">"Binary or empty change"</div> }.into_any()
+                            } else {
+                                hunks.iter().map(|hunk| view! { <HunkLines hunk=hunk.clone() /> })
+                                    .collect::<Vec<_>>()
+                                    .into_any()
+                            }
+                        })}
+                    </div>
+                }
+            }).collect::<Vec<_>>()}
+        </div>
+    }
+}
+
+#[component]
+fn HunkLines(hunk: Hunk) -> impl IntoView {
+    let header = format!(
+        "@@ -{},{} +{},{} @@",
+        hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+    );
+
+    view! {
+        <div class="berry-diff-hunk">
+            <div class="berry-diff-hunk-header">{header}</div>
+            {hunk.lines.into_iter().map(|line| {
+                let (class, prefix) = match line.line_type.as_str() {
+                    "add" => ("berry-diff-line berry-diff-add", "+"),
+                    "delete" => ("berry-diff-line berry-diff-delete", "-"),
+                    _ => ("berry-diff-line", " "),
+                };
+                view! {
+                    <div class=class>
+                        <span class="berry-diff-content">
+                            {format!("{}{}", prefix, line.content.trim_end_matches(['\n', '\r']))}
+                        </span>
+                    </div>
+                }
+            }).collect::<Vec<_>>()}
+        </div>
+    }
+}
+
+/// History of one file, following renames
+#[component]
+pub fn FileHistoryPanel(file_path: String) -> impl IntoView {
+    let entries = RwSignal::new(Vec::<FileHistoryEntry>::new());
+    let selected = RwSignal::new(None::<(String, String)>);
+    let loading = RwSignal::new(false);
+    let cursor = RwSignal::new(None::<LogCursor>);
+    let has_more = Memo::new(move |_| cursor.with(Option::is_some));
+    let error = RwSignal::new(None::<String>);
+
+    let path_for_load = file_path.clone();
+    let load = move |reset: bool| {
+        let path = path_for_load.clone();
+        let from = if reset { None } else { cursor.get_untracked() };
+        loading.set(true);
+        spawn_local(async move {
+            match load_file_history(&path, from).await {
+                Ok(page) => {
+                    cursor.set(page.cursor);
+                    if reset {
+                        entries.set(page.items);
+                    } else {
+                        entries.update(|entries| entries.extend(page.items));
+                    }
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Failed to load history: {}", e))),
+            }
+            loading.set(false);
+        });
+    };
+
+    let load_first = load.clone();
+    Effect::new(move || load_first(true));
+
+    view! {
+        <Panel title="File History">
+            <div class="berry-commit-history">
+                <div class="berry-commit-history-path">{file_path.clone()}</div>
+                {move || error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+                {move || {
+                    let list = entries.get();
+                    if list.is_empty() && !loading.get() {
+                        return view! { <div class="berry-git-empty">"No history"</div> }.into_any();
+                    }
+                    list.into_iter().map(|entry| {
+                        let key = (entry.commit.hash.clone(), entry.path.clone());
+                        let is_selected = selected.get().as_ref() == Some(&key);
+                        let class = if is_selected {
+                            "berry-commit-item berry-commit-item-selected"
+                        } else {
+                            "berry-commit-item"
+                        };
+                        let path_label = match &entry.old_path {
+                            Some(old) => format!("{} → {}", old, entry.path),
+                            None => entry.path.clone(),
+                        };
+                        view! {
+                            <div class=class on:click=move |_| selected.set(Some(key.clone()))>
+                                <div class="berry-commit-header">
+                                    <span class="berry-commit-hash">{entry.commit.short_hash.clone()}</span>
+                                    <span class="berry-commit-time">{format_time(entry.commit.timestamp)}</span>
+                                </div>
+                                <div class="berry-commit-message">{first_line(&entry.commit.message)}</div>
+                                <div class="berry-commit-author">
+                                    {format!("{} · {} {}", entry.commit.author, entry.status, path_label)}
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Vec<_>>().into_any()
+                }}
+                {move || {
+                    let load = load.clone();
+                    has_more.get().then(|| view! {
+                        <button class="berry-commit-load-more" on:click=move |_| load(false)>"Load more"</button>
+                    })
+                }}
+                {move || selected.get().map(|(commit, path)| view! {
+                    <CommitDetailView commit=commit expanded=path />
+                })}
+            </div>
+        </Panel>
+    }
+}
+
+// Helper functions
+
+async fn load_graph(query: LogQuery) -> anyhow::Result<LogPage<GraphRow>> {
     #[derive(Serialize)]
-    struct LogRequest {
-        limit: Option<usize>,
+    struct GraphRequest {
+        query: LogQuery,
     }
 
-    let commits: Vec<CommitInfo> = TauriBridge::invoke("git_log", LogRequest {
-        limit: Some(100),
-    }).await?;
+    TauriBridge::invoke("git_log_graph", GraphRequest { query }).await
+}
+
+async fn load_commit_detail(commit: &str) -> anyhow::Result<CommitDetail> {
+    #[derive(Serialize)]
+    struct DetailRequest {
+        commit: String,
+    }
 
-    Ok(commits)
+    TauriBridge::invoke("git_commit_detail", DetailRequest {
+        commit: commit.to_string(),
+    }).await
+}
+
+async fn load_comparison(base: &str, target: &str) -> anyhow::Result<RefComparison> {
+    #[derive(Serialize)]
+    struct CompareRequest {
+        base: String,
+        target: String,
+    }
+
+    TauriBridge::invoke("git_compare_refs", CompareRequest {
+        base: base.to_string(),
+        target: target.to_string(),
+    }).await
+}
+
+async fn load_file_history(file_path: &str, cursor: Option<LogCursor>) -> anyhow::Result<LogPage<FileHistoryEntry>> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct HistoryRequest {
+        file_path: String,
+        cursor: Option<LogCursor>,
+        limit: usize,
+    }
+
+    TauriBridge::invoke("git_file_history", HistoryRequest {
+        file_path: file_path.to_string(),
+        cursor,
+        limit: PAGE_SIZE,
+    }).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_commit_info_creation() {
-        let commit = CommitInfo {
+    fn commit() -> CommitInfo {
+        CommitInfo {
             hash: "abc123".to_string(),
             short_hash: "abc".to_string(),
             message: "Test commit".to_string(),
@@ -145,9 +726,33 @@ mod tests {
             email: "test@example.com".to_string(),
             timestamp: 1234567890,
             parents: vec![],
-        };
+        }
+    }
+
+    #[test]
+    fn test_commit_info_creation() {
+        let commit = commit();
 
         assert_eq!(commit.hash, "abc123");
         assert_eq!(commit.short_hash, "abc");
     }
+
+    #[test]
+    fn test_graph_paths() {
+        let row = GraphRow {
+            commit: commit(),
+            lane: 0,
+            incoming: vec![GraphEdge { from: 0, to: 0 }],
+            outgoing: vec![GraphEdge { from: 0, to: 0 }, GraphEdge { from: 0, to: 2 }],
+            refs: vec![],
+        };
+
+        let paths = graph_paths(&row);
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0].0, "M7 0 L7 28");
+        assert!(paths[2].0.starts_with("M7 28 C"));
+        assert!(paths[2].0.ends_with("35 56"));
+        assert_eq!(paths[2].1, lane_color(2));
+        assert_eq!(graph_width(&[row]), 3.0 * LANE_WIDTH);
+    }
 }
@@ -5,7 +5,8 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use serde::{Deserialize, Serialize};
-use crate::common::async_bridge::TauriBridge;
+use std::collections::BTreeSet;
+use crate::git::{GitDiffTracker, Hunk, HunkSelection};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffLine {
@@ -33,6 +34,61 @@ pub struct FileDiff {
     pub hunks: Vec<DiffHunk>,
 }
 
+impl DiffHunk {
+    fn from_hunk(hunk: &Hunk) -> Self {
+        Self {
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            header: format!(
+                "@@ -{},{} +{},{} @@",
+                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+            ),
+            lines: hunk
+                .lines
+                .iter()
+                .map(|line| DiffLine {
+                    origin: match line.line_type.as_str() {
+                        "add" => '+',
+                        "delete" => '-',
+                        _ => ' ',
+                    },
+                    content: line.content.trim_end_matches(['\n', '\r']).to_string(),
+                    old_lineno: line.old_line_no,
+                    new_lineno: line.new_line_no,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Hunk operation triggered from the diff view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HunkAction {
+    /// Stage (or, for staged diffs, unstage)
+    Toggle,
+    /// Revert in the work tree
+    Discard,
+}
+
+/// Selected (hunk, line) pairs grouped per hunk
+pub fn selections_from_lines(selected: &BTreeSet<(usize, usize)>) -> Vec<HunkSelection> {
+    let mut selections: Vec<HunkSelection> = Vec::new();
+    for &(hunk_index, line_index) in selected {
+        match selections.last_mut() {
+            Some(last) if last.hunk_index == hunk_index => {
+                last.lines.get_or_insert_with(Vec::new).push(line_index);
+            }
+            _ => selections.push(HunkSelection {
+                hunk_index,
+                lines: Some(vec![line_index]),
+            }),
+        }
+    }
+    selections
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DiffViewMode {
     SideBySide,
@@ -47,21 +103,32 @@ pub fn DiffView(
     /// Initial view mode
     #[prop(default = DiffViewMode::SideBySide)]
     mode: DiffViewMode,
+    /// Show staged changes (HEAD vs index) instead of unstaged ones
+    #[prop(optional)]
+    staged: bool,
+    /// Called after hunks were staged, unstaged or discarded
+    #[prop(optional)]
+    on_change: Option<Callback<()>>,
 ) -> impl IntoView {
     let diff = RwSignal::new(None::<FileDiff>);
     let view_mode = RwSignal::new(mode);
     let loading = RwSignal::new(false);
     let error = RwSignal::new(None::<String>);
+    let reload = RwSignal::new(0u32);
+    // Changed lines picked for line-level operations, as (hunk, line) indices
+    let selected = RwSignal::new(BTreeSet::<(usize, usize)>::new());
 
-    // Load diff on mount
+    // Load diff on mount (and after hunk operations)
     let file_path_clone = file_path.clone();
     Effect::new(move || {
+        reload.track();
         let path = file_path_clone.clone();
         loading.set(true);
         spawn_local(async move {
-            match load_diff(&path).await {
+            match load_diff(&path, staged).await {
                 Ok(file_diff) => {
                     diff.set(Some(file_diff));
+                    selected.set(BTreeSet::new());
                     error.set(None);
                     loading.set(false);
                 }
@@ -73,6 +140,37 @@ pub fn DiffView(
         });
     });
 
+    let path_for_ops = file_path.clone();
+    let apply = Callback::new(move |(action, selections): (HunkAction, Vec<HunkSelection>)| {
+        if selections.is_empty() {
+            return;
+        }
+        let path = path_for_ops.clone();
+        spawn_local(async move {
+            let result = match (action, staged) {
+                (HunkAction::Toggle, false) => GitDiffTracker::stage_hunks(path, selections).await,
+                (HunkAction::Toggle, true) => GitDiffTracker::unstage_hunks(path, selections).await,
+                (HunkAction::Discard, _) => GitDiffTracker::discard_hunks(path, selections).await,
+            };
+            match result {
+                Ok(()) => {
+                    if let Some(on_change) = on_change {
+                        on_change.run(());
+                    }
+                }
+                Err(e) => error.set(Some(e)),
+            }
+            reload.update(|n| *n += 1);
+        });
+    });
+    let on_hunk = Callback::new(move |(hunk_index, action): (usize, HunkAction)| {
+        apply.run((action, vec![HunkSelection::hunk(hunk_index)]));
+    });
+    let apply_selected = move |action: HunkAction| {
+        apply.run((action, selections_from_lines(&selected.get_untracked())));
+    };
+    let toggle_label = if staged { "Unstage" } else { "Stage" };
+
     view! {
         <div class="berry-diff-view">
             // Header with mode toggle
@@ -87,6 +185,22 @@ pub fn DiffView(
                     }
                 </div>
 
+                {move || {
+                    let count = selected.get().len();
+                    (count > 0).then(|| view! {
+                        <div class="berry-diff-line-actions">
+                            <button on:click=move |_| apply_selected(HunkAction::Toggle)>
+                                {format!("{} {} Selected Line(s)", toggle_label, count)}
+                            </button>
+                            {(!staged).then(|| view! {
+                                <button on:click=move |_| apply_selected(HunkAction::Discard)>
+                                    "Discard Selected"
+                                </button>
+                            })}
+                        </div>
+                    })
+                }}
+
                 <div class="berry-diff-mode-toggle">
                     <button
                         class=move || if view_mode.get() == DiffViewMode::SideBySide {
@@ -125,11 +239,11 @@ pub fn DiffView(
                     } else if let Some(file_diff) = diff.get() {
                         if view_mode.get() == DiffViewMode::SideBySide {
                             view! {
-                                <DiffSideBySide diff=file_diff />
+                                <DiffSideBySide diff=file_diff staged=staged on_hunk=on_hunk />
                             }.into_any()
                         } else {
                             view! {
-                                <DiffUnified diff=file_diff />
+                                <DiffUnified diff=file_diff staged=staged on_hunk=on_hunk selected=selected />
                             }.into_any()
                         }
                     } else {
@@ -143,18 +257,51 @@ pub fn DiffView(
     }
 }
 
+/// Hunk header with stage/unstage and discard buttons
+#[component]
+fn HunkHeader(
+    header: String,
+    hunk_index: usize,
+    staged: bool,
+    on_hunk: Callback<(usize, HunkAction)>,
+) -> impl IntoView {
+    view! {
+        <div class="berry-diff-hunk-header">
+            <span>{header}</span>
+            <button
+                class="berry-diff-hunk-btn"
+                on:click=move |_| on_hunk.run((hunk_index, HunkAction::Toggle))
+            >
+                {if staged { "Unstage Hunk" } else { "Stage Hunk" }}
+            </button>
+            {(!staged).then(|| view! {
+                <button
+                    class="berry-diff-hunk-btn"
+                    on:click=move |_| on_hunk.run((hunk_index, HunkAction::Discard))
+                >
+                    "Discard Hunk"
+                </button>
+            })}
+        </div>
+    }
+}
+
 /// Side-by-side diff view
 #[component]
-fn DiffSideBySide(diff: FileDiff) -> impl IntoView {
+fn DiffSideBySide(
+    diff: FileDiff,
+    staged: bool,
+    on_hunk: Callback<(usize, HunkAction)>,
+) -> impl IntoView {
     let hunks = diff.hunks;
     view! {
         <div class="berry-diff-side-by-side">
-            {hunks.into_iter().map(|hunk| {
+            {hunks.into_iter().enumerate().map(|(hunk_index, hunk)| {
                 let lines_old = hunk.lines.clone();
                 let lines_new = hunk.lines;
                 view! {
                     <div class="berry-diff-hunk">
-                        <div class="berry-diff-hunk-header">{hunk.header.clone()}</div>
+                        <HunkHeader header=hunk.header.clone() hunk_index=hunk_index staged=staged on_hunk=on_hunk />
 
                         <div class="berry-diff-hunk-content">
                             <div class="berry-diff-column berry-diff-old">
@@ -210,18 +357,24 @@ fn DiffSideBySide(diff: FileDiff) -> impl IntoView {
 
 /// Unified diff view
 #[component]
-fn DiffUnified(diff: FileDiff) -> impl IntoView {
+fn DiffUnified(
+    diff: FileDiff,
+    staged: bool,
+    on_hunk: Callback<(usize, HunkAction)>,
+    /// Changed lines picked for line-level operations
+    selected: RwSignal<BTreeSet<(usize, usize)>>,
+) -> impl IntoView {
     let hunks = diff.hunks;
     view! {
         <div class="berry-diff-unified">
-            {hunks.into_iter().map(|hunk| {
+            {hunks.into_iter().enumerate().map(|(hunk_index, hunk)| {
                 let lines = hunk.lines;
                 view! {
                     <div class="berry-diff-hunk">
-                        <div class="berry-diff-hunk-header">{hunk.header.clone()}</div>
+                        <HunkHeader header=hunk.header.clone() hunk_index=hunk_index staged=staged on_hunk=on_hunk />
 
                         <div class="berry-diff-hunk-content">
-                            {lines.into_iter().map(|line| {
+                            {lines.into_iter().enumerate().map(|(line_index, line)| {
                                 let class_suffix = match line.origin {
                                     '+' => "added",
                                     '-' => "removed",
@@ -231,9 +384,29 @@ fn DiffUnified(diff: FileDiff) -> impl IntoView {
                                 let old_lineno = line.old_lineno;
                                 let new_lineno = line.new_lineno;
                                 let content = line.content;
+                                let key = (hunk_index, line_index);
+                                let selectable = origin != ' ';
 
                                 view! {
-                                    <div class=format!("berry-diff-line berry-diff-line-{}", class_suffix)>
+                                    <div
+                                        class=move || {
+                                            let picked = selected.with(|s| s.contains(&key));
+                                            format!(
+                                                "berry-diff-line berry-diff-line-{}{}",
+                                                class_suffix,
+                                                if picked { " selected" } else { "" }
+                                            )
+                                        }
+                                        on:click=move |_| {
+                                            if selectable {
+                                                selected.update(|s| {
+                                                    if !s.remove(&key) {
+                                                        s.insert(key);
+                                                    }
+                                                });
+                                            }
+                                        }
+                                    >
                                         <span class="berry-diff-lineno berry-diff-lineno-old">
                                             {old_lineno.map(|n| n.to_string()).unwrap_or_default()}
                                         </span>
@@ -255,15 +428,15 @@ fn DiffUnified(diff: FileDiff) -> impl IntoView {
 
 // Helper functions
 
-async fn load_diff(file_path: &str) -> anyhow::Result<FileDiff> {
-    #[derive(Serialize)]
-    struct DiffRequest {
-        file_path: String,
-    }
+async fn load_diff(file_path: &str, staged: bool) -> Result<FileDiff, String> {
+    let hunks = GitDiffTracker::load_hunks(file_path.to_string(), staged).await?;
 
-    TauriBridge::invoke("git_diff", DiffRequest {
-        file_path: file_path.to_string(),
-    }).await
+    Ok(FileDiff {
+        old_path: Some(file_path.to_string()),
+        new_path: Some(file_path.to_string()),
+        status: if staged { "staged" } else { "modified" }.to_string(),
+        hunks: hunks.iter().map(DiffHunk::from_hunk).collect(),
+    })
 }
 
 #[cfg(test)]
@@ -309,4 +482,16 @@ mod tests {
 
         assert_eq!(diff.status, "modified");
     }
+
+    #[test]
+    fn test_selections_from_lines() {
+        let selected: BTreeSet<(usize, usize)> = [(0, 2), (0, 3), (2, 1)].into_iter().collect();
+        let selections = selections_from_lines(&selected);
+
+        assert_eq!(selections.len(), 2);
+        assert_eq!(selections[0].hunk_index, 0);
+        assert_eq!(selections[0].lines, Some(vec![2, 3]));
+        assert_eq!(selections[1].hunk_index, 2);
+        assert_eq!(selections[1].lines, Some(vec![1]));
+    }
 }
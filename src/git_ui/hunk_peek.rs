@@ -0,0 +1,199 @@
+//! Git Gutter and Hunk Peek
+//!
+//! Change markers for the open file and an inline peek of the original text
+//! of a hunk, with stage/revert actions
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use crate::core::virtual_editor::LineDecoration;
+use crate::git::{ChangeType, GitDiffTracker, Hunk, HunkSelection};
+
+/// Git change state for the file in the editor (shared by the gutter and the peek widget)
+#[derive(Clone, Copy)]
+pub struct GitGutter {
+    pub root: RwSignal<String>,
+    pub file: RwSignal<Option<String>>,
+    pub hunks: RwSignal<Vec<Hunk>>,
+    /// Hunk shown in the peek widget
+    pub peek: RwSignal<Option<usize>>,
+    pub error: RwSignal<Option<String>>,
+}
+
+impl GitGutter {
+    pub fn new() -> Self {
+        Self {
+            root: RwSignal::new(String::new()),
+            file: RwSignal::new(None),
+            hunks: RwSignal::new(Vec::new()),
+            peek: RwSignal::new(None),
+            error: RwSignal::new(None),
+        }
+    }
+
+    /// Load the unstaged hunks of a file
+    pub fn refresh(&self, root: String, file: String) {
+        let this = *self;
+        if this.file.get_untracked().as_deref() != Some(file.as_str()) {
+            this.peek.set(None);
+        }
+        this.root.set(root.clone());
+        this.file.set(Some(file.clone()));
+
+        spawn_local(async move {
+            if GitDiffTracker::set_repo_path(root).await.is_err() {
+                return;
+            }
+            match GitDiffTracker::load_hunks(file.clone(), false).await {
+                Ok(hunks) => {
+                    // Ignore results for a file that is no longer shown
+                    if this.file.get_untracked().as_deref() == Some(file.as_str()) {
+                        if this.peek.get_untracked().is_some_and(|i| i >= hunks.len()) {
+                            this.peek.set(None);
+                        }
+                        this.hunks.set(hunks);
+                        this.error.set(None);
+                    }
+                }
+                // Not a repository, untracked directory, binary file...
+                Err(_) => this.hunks.set(Vec::new()),
+            }
+        });
+    }
+
+    fn reload(&self) {
+        if let Some(file) = self.file.get_untracked() {
+            self.refresh(self.root.get_untracked(), file);
+        }
+    }
+
+    /// Open/close the peek for the hunk on a (0-based) line; false when there is none
+    pub fn toggle_peek(&self, file: &str, line: usize) -> bool {
+        if self.file.get_untracked().as_deref() != Some(file) {
+            return false;
+        }
+        let Some(index) = GitDiffTracker::hunk_at_line(&self.hunks.get_untracked(), line + 1) else {
+            return false;
+        };
+        self.peek.update(|peek| {
+            *peek = if *peek == Some(index) { None } else { Some(index) };
+        });
+        true
+    }
+
+    /// Stage one hunk
+    pub fn stage(&self, hunk_index: usize) {
+        self.run(hunk_index, false, None);
+    }
+
+    /// Revert one hunk in the work tree; `on_reverted` gets the file path to reload
+    pub fn revert(&self, hunk_index: usize, on_reverted: Option<Callback<String>>) {
+        self.run(hunk_index, true, on_reverted);
+    }
+
+    fn run(&self, hunk_index: usize, discard: bool, on_done: Option<Callback<String>>) {
+        let Some(file) = self.file.get_untracked() else {
+            return;
+        };
+        let this = *self;
+        this.peek.set(None);
+        spawn_local(async move {
+            let selections = vec![HunkSelection::hunk(hunk_index)];
+            let result = if discard {
+                GitDiffTracker::discard_hunks(file.clone(), selections).await
+            } else {
+                GitDiffTracker::stage_hunks(file.clone(), selections).await
+            };
+            match result {
+                Ok(()) => {
+                    if let Some(on_done) = on_done {
+                        on_done.run(file);
+                    }
+                }
+                Err(e) => this.error.set(Some(e)),
+            }
+            this.reload();
+        });
+    }
+
+    /// Gutter markers for the current file
+    pub fn line_decorations(&self) -> Vec<LineDecoration> {
+        let Some(file) = self.file.get() else {
+            return Vec::new();
+        };
+        GitDiffTracker::changes_from_hunks(file.clone(), &self.hunks.get())
+            .changes
+            .into_iter()
+            .map(|change| LineDecoration {
+                file: file.clone(),
+                line: change.line_number.saturating_sub(1),
+                gutter_glyph: Some(change.change_type.gutter_indicator().to_string()),
+                message: None,
+                color: change.change_type.gutter_color().to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Default for GitGutter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inline peek of a hunk: original text, new text and actions
+#[component]
+pub fn HunkPeek(
+    gutter: GitGutter,
+    /// Called with the file path after a revert changed it on disk
+    #[prop(optional)]
+    on_reverted: Option<Callback<String>>,
+) -> impl IntoView {
+    let close = move |_| gutter.peek.set(None);
+
+    move || {
+        let index = gutter.peek.get()?;
+        let hunks = gutter.hunks.get();
+        let hunk = hunks.get(index)?.clone();
+        let count = hunks.len();
+
+        let old_text = hunk.old_text();
+        let new_text = hunk.new_text();
+        let change = GitDiffTracker::changes_from_hunks(String::new(), std::slice::from_ref(&hunk))
+            .changes
+            .first()
+            .map(|c| c.change_type)
+            .unwrap_or(ChangeType::Modified);
+
+        Some(view! {
+            <div class="berry-hunk-peek">
+                <div class="berry-hunk-peek-header">
+                    <span>{format!("Change {} of {} (line {})", index + 1, count, hunk.new_start)}</span>
+                    <button
+                        title="Previous change"
+                        disabled=index == 0
+                        on:click=move |_| gutter.peek.set(Some(index.saturating_sub(1)))
+                    >
+                        "↑"
+                    </button>
+                    <button
+                        title="Next change"
+                        disabled=index + 1 >= count
+                        on:click=move |_| gutter.peek.set(Some(index + 1))
+                    >
+                        "↓"
+                    </button>
+                    <button title="Stage this change" on:click=move |_| gutter.stage(index)>"Stage"</button>
+                    <button title="Revert this change" on:click=move |_| gutter.revert(index, on_reverted)>"Revert"</button>
+                    <button title="Close" on:click=close>"×"</button>
+                </div>
+                {(change != ChangeType::Added).then(|| view! {
+                    <pre class="berry-hunk-peek-old">{old_text}</pre>
+                })}
+                {(!new_text.is_empty()).then(|| view! {
+                    <pre class="berry-hunk-peek-new">{new_text}</pre>
+                })}
+                {move || gutter.error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+            </div>
+        })
+    }
+}
@@ -0,0 +1,412 @@
+//! Interactive Rebase Editor
+//!
+//! Plan a rebase of the commits after a base: reorder, pick, reword, squash,
+//! fixup or drop them, then run it with continue/skip/abort on conflicts
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
+use crate::common::async_bridge::TauriBridge;
+use super::commit_history::CommitInfo;
+use super::source_control_panel::OperationResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [RebaseAction; 5] = [
+        RebaseAction::Pick,
+        RebaseAction::Reword,
+        RebaseAction::Squash,
+        RebaseAction::Fixup,
+        RebaseAction::Drop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Whether the step takes a new commit message
+    pub fn edits_message(self) -> bool {
+        matches!(self, RebaseAction::Reword | RebaseAction::Squash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebaseStep {
+    pub commit: String,
+    pub action: RebaseAction,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractiveRebaseState {
+    pub onto: String,
+    pub orig_head: String,
+    pub head_name: Option<String>,
+    pub steps: Vec<RebaseStep>,
+    pub next: usize,
+    pub pending: bool,
+}
+
+/// A commit in the plan being edited
+#[derive(Clone)]
+struct PlanStep {
+    commit: CommitInfo,
+    action: RwSignal<RebaseAction>,
+    message: RwSignal<String>,
+}
+
+/// Check that squash/fixup steps have a commit to meld into
+pub fn validate_plan(actions: &[RebaseAction]) -> Result<(), String> {
+    match actions.iter().find(|action| **action != RebaseAction::Drop) {
+        Some(RebaseAction::Squash | RebaseAction::Fixup) => {
+            Err("The first commit cannot be squashed or fixed up".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Default message of a squash step: the previous message followed by its own
+pub fn squash_message(previous: &str, own: &str) -> String {
+    format!("{}\n\n{}", previous.trim_end(), own.trim_end())
+}
+
+/// Move an item one position up or down; returns false at the ends
+pub fn move_item<T>(items: &mut [T], index: usize, up: bool) -> bool {
+    let target = if up { index.checked_sub(1) } else { Some(index + 1) };
+    match target {
+        Some(target) if target < items.len() && index < items.len() => {
+            items.swap(index, target);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Interactive Rebase Editor
+#[component]
+pub fn InteractiveRebase(
+    /// Called when the editor is closed
+    #[prop(optional)]
+    on_close: Option<Callback<()>>,
+) -> impl IntoView {
+    let base = RwSignal::new(String::from("HEAD~5"));
+    let onto = RwSignal::new(String::new());
+    let steps = RwSignal::new(Vec::<PlanStep>::new());
+    let in_progress = RwSignal::new(None::<InteractiveRebaseState>);
+    let result = RwSignal::new(None::<OperationResult>);
+    let error = RwSignal::new(None::<String>);
+    let busy = RwSignal::new(false);
+
+    let refresh_state = move || {
+        spawn_local(async move {
+            match TauriBridge::invoke::<_, Option<InteractiveRebaseState>>("git_interactive_rebase_state", ()).await {
+                Ok(state) => in_progress.set(state),
+                Err(e) => error.set(Some(format!("Failed to read rebase state: {}", e))),
+            }
+        });
+    };
+
+    // Pick up a rebase that stopped earlier
+    Effect::new(refresh_state);
+
+    let load_commits = move || {
+        let base_ref = base.get_untracked().trim().to_string();
+        if base_ref.is_empty() {
+            return;
+        }
+        spawn_local(async move {
+            match load_rebase_commits(&base_ref).await {
+                Ok(commits) => {
+                    onto.set(base_ref);
+                    steps.set(
+                        commits
+                            .into_iter()
+                            .map(|commit| PlanStep {
+                                message: RwSignal::new(commit.message.trim_end().to_string()),
+                                action: RwSignal::new(RebaseAction::Pick),
+                                commit,
+                            })
+                            .collect(),
+                    );
+                    result.set(None);
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Failed to load commits: {}", e))),
+            }
+        });
+    };
+
+    let set_action = move |hash: String, action: RebaseAction| {
+        let plan = steps.get_untracked();
+        let Some(index) = plan.iter().position(|s| s.commit.hash == hash) else {
+            return;
+        };
+        let step = &plan[index];
+        if action == RebaseAction::Squash && step.action.get_untracked() != RebaseAction::Squash {
+            let previous = plan[..index]
+                .iter()
+                .rev()
+                .find(|s| s.action.get_untracked() != RebaseAction::Drop)
+                .map(|s| s.message.get_untracked())
+                .unwrap_or_default();
+            step.message.set(squash_message(&previous, &step.commit.message));
+        } else if action == RebaseAction::Pick {
+            step.message.set(step.commit.message.trim_end().to_string());
+        }
+        step.action.set(action);
+    };
+
+    let move_step = move |hash: String, up: bool| {
+        steps.update(|plan| {
+            if let Some(index) = plan.iter().position(|s| s.commit.hash == hash) {
+                move_item(plan, index, up);
+            }
+        });
+    };
+
+    let handle_result = move |outcome: anyhow::Result<OperationResult>, action: &str| {
+        match outcome {
+            Ok(outcome) => {
+                if outcome.status != "conflicts" {
+                    steps.set(Vec::new());
+                }
+                result.set(Some(outcome));
+                error.set(None);
+            }
+            Err(e) => error.set(Some(format!("Failed to {}: {}", action, e))),
+        }
+        busy.set(false);
+        refresh_state();
+    };
+
+    let start = move || {
+        let plan = steps.get_untracked();
+        let actions: Vec<RebaseAction> = plan.iter().map(|s| s.action.get_untracked()).collect();
+        if let Err(e) = validate_plan(&actions) {
+            error.set(Some(e));
+            return;
+        }
+        let rebase_steps = plan
+            .iter()
+            .map(|s| {
+                let action = s.action.get_untracked();
+                RebaseStep {
+                    commit: s.commit.hash.clone(),
+                    action,
+                    message: action.edits_message().then(|| s.message.get_untracked()),
+                }
+            })
+            .collect();
+        let onto_ref = onto.get_untracked();
+        busy.set(true);
+        spawn_local(async move {
+            handle_result(run_interactive_rebase(&onto_ref, rebase_steps).await, "rebase");
+        });
+    };
+
+    let control = move |command: &'static str| {
+        busy.set(true);
+        spawn_local(async move {
+            let outcome = if command == "git_abort_operation" {
+                TauriBridge::invoke::<_, ()>(command, ()).await.map(|_| OperationResult {
+                    status: "completed".to_string(),
+                    message: "Rebase aborted".to_string(),
+                    commit: None,
+                    conflicts: Vec::new(),
+                })
+            } else {
+                TauriBridge::invoke::<_, OperationResult>(command, ()).await
+            };
+            handle_result(outcome, "update the rebase");
+        });
+    };
+
+    view! {
+        <div class="berry-rebase-editor">
+            <div class="berry-rebase-header">
+                <span>"Interactive Rebase"</span>
+                <input
+                    type="text"
+                    placeholder="Base (branch, tag or commit)"
+                    prop:value=move || base.get()
+                    on:input=move |ev| base.set(event_target_value(&ev))
+                    on:keydown=move |ev| {
+                        if ev.key() == "Enter" {
+                            load_commits();
+                        }
+                    }
+                />
+                <button disabled=move || in_progress.get().is_some() on:click=move |_| load_commits()>
+                    "Load"
+                </button>
+                {on_close.map(|on_close| view! {
+                    <button title="Close" on:click=move |_| on_close.run(())>"×"</button>
+                })}
+            </div>
+
+            {move || error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+
+            // Rebase stopped on conflicts
+            {move || in_progress.get().map(|state| view! {
+                <div class="berry-git-state-banner">
+                    <span>
+                        {format!(
+                            "Rebase stopped at step {} of {}. Resolve conflicts in Source Control, then continue.",
+                            state.next + 1,
+                            state.steps.len()
+                        )}
+                    </span>
+                    <button disabled=move || busy.get() on:click=move |_| control("git_rebase_continue")>"Continue"</button>
+                    <button disabled=move || busy.get() on:click=move |_| control("git_rebase_skip")>"Skip"</button>
+                    <button disabled=move || busy.get() on:click=move |_| control("git_abort_operation")>"Abort"</button>
+                </div>
+            })}
+
+            {move || result.get().map(|outcome| view! {
+                <div class="berry-git-info">
+                    {outcome.message}
+                    {outcome.conflicts.into_iter().map(|path| view! {
+                        <div class="berry-git-conflict-path">{path}</div>
+                    }).collect::<Vec<_>>()}
+                </div>
+            })}
+
+            <div class="berry-rebase-steps">
+                <For
+                    each=move || steps.get()
+                    key=|step| step.commit.hash.clone()
+                    children=move |step| {
+                        let hash = step.commit.hash.clone();
+                        let (hash_up, hash_down, hash_action) = (hash.clone(), hash.clone(), hash.clone());
+                        let action = step.action;
+                        let message = step.message;
+                        let subject = step.commit.message.lines().next().unwrap_or("").to_string();
+                        view! {
+                            <div class=move || format!("berry-rebase-step berry-rebase-{}", action.get().name())>
+                                <div class="berry-rebase-step-row">
+                                    <select on:change=move |ev| {
+                                        if let Some(new_action) = RebaseAction::from_name(&event_target_value(&ev)) {
+                                            set_action(hash_action.clone(), new_action);
+                                        }
+                                    }>
+                                        {RebaseAction::ALL.into_iter().map(|option| view! {
+                                            <option value=option.name() selected=move || action.get() == option>
+                                                {option.name()}
+                                            </option>
+                                        }).collect::<Vec<_>>()}
+                                    </select>
+                                    <span class="berry-commit-hash">{step.commit.short_hash.clone()}</span>
+                                    <span class="berry-rebase-subject">{subject}</span>
+                                    <button title="Move up" on:click=move |_| move_step(hash_up.clone(), true)>"↑"</button>
+                                    <button title="Move down" on:click=move |_| move_step(hash_down.clone(), false)>"↓"</button>
+                                </div>
+                                {move || action.get().edits_message().then(|| view! {
+                                    <textarea
+                                        class="berry-rebase-message"
+                                        prop:value=move || message.get()
+                                        on:input=move |ev| message.set(event_target_value(&ev))
+                                    />
+                                })}
+                            </div>
+                        }
+                    }
+                />
+            </div>
+
+            {move || (!steps.get().is_empty()).then(|| view! {
+                <div class="berry-rebase-footer">
+                    <span>{move || format!("{} commit(s) onto {}", steps.get().len(), onto.get())}</span>
+                    <button
+                        class="berry-rebase-start"
+                        disabled=move || busy.get() || in_progress.get().is_some()
+                        on:click=move |_| start()
+                    >
+                        "Start Rebase"
+                    </button>
+                </div>
+            })}
+        </div>
+    }
+}
+
+// Helper functions
+
+async fn load_rebase_commits(base: &str) -> anyhow::Result<Vec<CommitInfo>> {
+    #[derive(Serialize)]
+    struct CommitsRequest {
+        base: String,
+    }
+
+    TauriBridge::invoke("git_rebase_commits", CommitsRequest {
+        base: base.to_string(),
+    }).await
+}
+
+async fn run_interactive_rebase(onto: &str, steps: Vec<RebaseStep>) -> anyhow::Result<OperationResult> {
+    #[derive(Serialize)]
+    struct RebaseRequest {
+        onto: String,
+        steps: Vec<RebaseStep>,
+    }
+
+    TauriBridge::invoke("git_interactive_rebase", RebaseRequest {
+        onto: onto.to_string(),
+        steps,
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_plan() {
+        use RebaseAction::*;
+        assert!(validate_plan(&[Pick, Squash, Fixup]).is_ok());
+        assert!(validate_plan(&[Drop, Reword, Fixup]).is_ok());
+        assert!(validate_plan(&[Drop, Squash, Pick]).is_err());
+        assert!(validate_plan(&[Fixup]).is_err());
+        assert!(validate_plan(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_move_item() {
+        let mut items = vec![1, 2, 3];
+        assert!(move_item(&mut items, 2, true));
+        assert_eq!(items, vec![1, 3, 2]);
+        assert!(move_item(&mut items, 0, false));
+        assert_eq!(items, vec![3, 1, 2]);
+        assert!(!move_item(&mut items, 0, true));
+        assert!(!move_item(&mut items, 2, false));
+    }
+
+    #[test]
+    fn test_action_names_and_messages() {
+        for action in RebaseAction::ALL {
+            assert_eq!(RebaseAction::from_name(action.name()), Some(action));
+            assert_eq!(serde_json::to_string(&action).unwrap(), format!("\"{}\"", action.name()));
+        }
+        assert!(RebaseAction::Reword.edits_message());
+        assert!(!RebaseAction::Fixup.edits_message());
+        assert_eq!(squash_message("first\n", "second\n"), "first\n\nsecond");
+    }
+}
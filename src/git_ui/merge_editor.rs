@@ -0,0 +1,404 @@
+//! Merge Editor Component
+//!
+//! Three-way merge editor for resolving conflicts: base/ours/theirs panes,
+//! per-conflict accept buttons and an editable result pane
+
+use leptos::html::Textarea;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
+use crate::common::async_bridge::TauriBridge;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreeWayMerge {
+    pub file_path: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub result: String,
+}
+
+/// Which side of a conflict to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkSide {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// A conflict block (`<<<<<<<` … `>>>>>>>`) in the result text
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictHunk {
+    /// Line of the `<<<<<<<` marker (0-based)
+    pub start_line: usize,
+    /// Line of the `>>>>>>>` marker (0-based)
+    pub end_line: usize,
+    pub ours: String,
+    /// Base section of diff3-style markers
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+/// Find the conflict blocks in a marked-up file
+pub fn parse_conflicts(text: &str) -> Vec<ConflictHunk> {
+    #[derive(PartialEq)]
+    enum Section {
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<(ConflictHunk, Section)> = None;
+
+    for (line_no, line) in text.split_inclusive('\n').enumerate() {
+        if line.starts_with("<<<<<<<") {
+            current = Some((
+                ConflictHunk {
+                    start_line: line_no,
+                    end_line: line_no,
+                    ours: String::new(),
+                    base: None,
+                    theirs: String::new(),
+                },
+                Section::Ours,
+            ));
+            continue;
+        }
+
+        let Some((hunk, section)) = current.as_mut() else {
+            continue;
+        };
+
+        if line.starts_with("|||||||") && *section == Section::Ours {
+            hunk.base = Some(String::new());
+            *section = Section::Base;
+        } else if line.starts_with("=======") && *section != Section::Theirs {
+            *section = Section::Theirs;
+        } else if line.starts_with(">>>>>>>") && *section == Section::Theirs {
+            let (mut hunk, _) = current.take().unwrap();
+            hunk.end_line = line_no;
+            hunks.push(hunk);
+        } else {
+            match section {
+                Section::Ours => hunk.ours.push_str(line),
+                Section::Base => hunk.base.get_or_insert_with(String::new).push_str(line),
+                Section::Theirs => hunk.theirs.push_str(line),
+            }
+        }
+    }
+
+    hunks
+}
+
+/// Replace one conflict block of `text` by the chosen side
+pub fn resolve_hunk(text: &str, index: usize, side: HunkSide) -> String {
+    let Some(hunk) = parse_conflicts(text).into_iter().nth(index) else {
+        return text.to_string();
+    };
+
+    let replacement = match side {
+        HunkSide::Ours => hunk.ours.clone(),
+        HunkSide::Theirs => hunk.theirs.clone(),
+        HunkSide::Both => {
+            let mut both = hunk.ours.clone();
+            if !both.is_empty() && !both.ends_with('\n') {
+                both.push('\n');
+            }
+            both.push_str(&hunk.theirs);
+            both
+        }
+    };
+
+    let mut result = String::with_capacity(text.len());
+    for (line_no, line) in text.split_inclusive('\n').enumerate() {
+        if line_no == hunk.start_line {
+            result.push_str(&replacement);
+        } else if line_no < hunk.start_line || line_no > hunk.end_line {
+            result.push_str(line);
+        }
+    }
+    result
+}
+
+/// Character offset of the start of a line (for moving the result cursor)
+pub fn line_offset(text: &str, line: usize) -> usize {
+    text.split_inclusive('\n')
+        .take(line)
+        .map(|l| l.chars().count())
+        .sum()
+}
+
+/// Merge Editor Component
+#[component]
+pub fn MergeEditor(
+    /// Conflicted file (relative to the repository root)
+    file_path: String,
+    /// Called after the file is marked resolved (or the editor is closed)
+    #[prop(optional)]
+    on_close: Option<Callback<()>>,
+    /// Versions to merge instead of the file's index conflict stages
+    #[prop(optional)]
+    versions: Option<ThreeWayMerge>,
+    /// Receives the result instead of writing and staging it
+    #[prop(optional)]
+    on_resolved: Option<Callback<String>>,
+    /// Names of the two sides, ("Ours", "Theirs") by default
+    #[prop(optional)]
+    side_labels: Option<(&'static str, &'static str)>,
+) -> impl IntoView {
+    let (ours_label, theirs_label) = side_labels.unwrap_or(("Ours", "Theirs"));
+    let preloaded = versions.is_some();
+    let merge = RwSignal::new(versions.clone());
+    let result = RwSignal::new(versions.map(|v| v.result).unwrap_or_default());
+    let current = RwSignal::new(0usize);
+    let error = RwSignal::new(None::<String>);
+    let saving = RwSignal::new(false);
+    let result_ref = NodeRef::<Textarea>::new();
+
+    let conflicts = Memo::new(move |_| parse_conflicts(&result.get()));
+
+    // Load the three versions on mount
+    let path_for_load = file_path.clone();
+    Effect::new(move || {
+        if preloaded {
+            return;
+        }
+        let path = path_for_load.clone();
+        spawn_local(async move {
+            match load_three_way_merge(&path).await {
+                Ok(loaded) => {
+                    result.set(loaded.result.clone());
+                    merge.set(Some(loaded));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Failed to load conflict: {}", e))),
+            }
+        });
+    });
+
+    // Keep the selected conflict in range as conflicts get resolved
+    Effect::new(move || {
+        let count = conflicts.get().len();
+        if current.get_untracked() >= count && count > 0 {
+            current.set(count - 1);
+        }
+    });
+
+    let reveal = move |index: usize| {
+        current.set(index);
+        let Some(hunk) = conflicts.get_untracked().get(index).cloned() else {
+            return;
+        };
+        if let Some(textarea) = result_ref.get_untracked() {
+            let text = result.get_untracked();
+            let start = line_offset(&text, hunk.start_line) as u32;
+            let end = line_offset(&text, hunk.end_line + 1) as u32;
+            let _ = textarea.focus();
+            let _ = textarea.set_selection_range(start, end);
+        }
+    };
+
+    let accept = move |side: HunkSide| {
+        let index = current.get_untracked();
+        result.update(|text| *text = resolve_hunk(text, index, side));
+    };
+
+    let on_close_clone = on_close;
+    let path_for_save = file_path.clone();
+    let mark_resolved = move || {
+        let path = path_for_save.clone();
+        let content = result.get_untracked();
+        if let Some(on_resolved) = on_resolved {
+            on_resolved.run(content);
+            return;
+        }
+        saving.set(true);
+        spawn_local(async move {
+            match resolve_conflict(&path, &content).await {
+                Ok(_) => {
+                    error.set(None);
+                    if let Some(on_close) = on_close_clone {
+                        on_close.run(());
+                    }
+                }
+                Err(e) => error.set(Some(format!("Failed to mark resolved: {}", e))),
+            }
+            saving.set(false);
+        });
+    };
+
+    let pane = move |title: &'static str, text: Option<String>| {
+        view! {
+            <div class="berry-merge-pane">
+                <div class="berry-merge-pane-title">{title}</div>
+                <pre class="berry-merge-pane-content">
+                    {text.unwrap_or_else(|| "(deleted)".to_string())}
+                </pre>
+            </div>
+        }
+    };
+
+    view! {
+        <div class="berry-merge-editor">
+            <div class="berry-merge-header">
+                <span class="berry-merge-file-path">{file_path.clone()}</span>
+                <span class="berry-merge-count">
+                    {move || {
+                        let count = conflicts.get().len();
+                        if count == 0 {
+                            "No conflicts remaining".to_string()
+                        } else {
+                            format!("Conflict {} of {}", current.get() + 1, count)
+                        }
+                    }}
+                </span>
+                <button
+                    title="Previous conflict"
+                    disabled=move || current.get() == 0
+                    on:click=move |_| reveal(current.get_untracked().saturating_sub(1))
+                >
+                    "↑"
+                </button>
+                <button
+                    title="Next conflict"
+                    disabled=move || current.get() + 1 >= conflicts.get().len()
+                    on:click=move |_| reveal(current.get_untracked() + 1)
+                >
+                    "↓"
+                </button>
+                <button disabled=move || conflicts.get().is_empty() on:click=move |_| accept(HunkSide::Ours)>
+                    {format!("Accept {}", ours_label)}
+                </button>
+                <button disabled=move || conflicts.get().is_empty() on:click=move |_| accept(HunkSide::Theirs)>
+                    {format!("Accept {}", theirs_label)}
+                </button>
+                <button disabled=move || conflicts.get().is_empty() on:click=move |_| accept(HunkSide::Both)>
+                    "Accept Both"
+                </button>
+                <button
+                    class="berry-merge-resolve-btn"
+                    title=if on_resolved.is_some() { "Use the merged text" } else { "Save the result and stage the file" }
+                    disabled=move || saving.get() || !conflicts.get().is_empty()
+                    on:click=move |_| mark_resolved()
+                >
+                    {if on_resolved.is_some() { "Apply Result" } else { "Mark Resolved" }}
+                </button>
+                {on_close.map(|on_close| view! {
+                    <button title="Close" on:click=move |_| on_close.run(())>"×"</button>
+                })}
+            </div>
+
+            {move || error.get().map(|err| view! { <div class="berry-git-error">{err}</div> })}
+
+            // Current conflict, side by side
+            {move || {
+                conflicts.get().get(current.get()).cloned().map(|hunk| view! {
+                    <div class="berry-merge-hunk">
+                        <div class="berry-merge-hunk-side berry-merge-ours">
+                            <div class="berry-merge-pane-title">{ours_label}</div>
+                            <pre>{hunk.ours}</pre>
+                        </div>
+                        {hunk.base.map(|base| view! {
+                            <div class="berry-merge-hunk-side berry-merge-base">
+                                <div class="berry-merge-pane-title">"Base"</div>
+                                <pre>{base}</pre>
+                            </div>
+                        })}
+                        <div class="berry-merge-hunk-side berry-merge-theirs">
+                            <div class="berry-merge-pane-title">{theirs_label}</div>
+                            <pre>{hunk.theirs}</pre>
+                        </div>
+                    </div>
+                })
+            }}
+
+            // Full versions
+            {move || {
+                merge.get().map(|m| view! {
+                    <div class="berry-merge-panes">
+                        {pane("Base", m.base)}
+                        {pane(ours_label, m.ours)}
+                        {pane(theirs_label, m.theirs)}
+                    </div>
+                })
+            }}
+
+            // Result
+            <div class="berry-merge-result">
+                <div class="berry-merge-pane-title">"Result"</div>
+                <textarea
+                    node_ref=result_ref
+                    class="berry-merge-result-content"
+                    spellcheck="false"
+                    prop:value=move || result.get()
+                    on:input=move |ev| result.set(event_target_value(&ev))
+                />
+            </div>
+        </div>
+    }
+}
+
+// Helper functions
+
+async fn load_three_way_merge(file_path: &str) -> anyhow::Result<ThreeWayMerge> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct MergeRequest {
+        file_path: String,
+    }
+
+    TauriBridge::invoke("git_three_way_merge", MergeRequest {
+        file_path: file_path.to_string(),
+    }).await
+}
+
+async fn resolve_conflict(file_path: &str, content: &str) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ResolveRequest {
+        file_path: String,
+        content: String,
+    }
+
+    TauriBridge::invoke("git_resolve_conflict", ResolveRequest {
+        file_path: file_path.to_string(),
+        content: content.to_string(),
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKED: &str = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\nb\n<<<<<<< HEAD\nx\n||||||| base\nw\n=======\ny\n>>>>>>> feature\n";
+
+    #[test]
+    fn test_parse_conflicts() {
+        let hunks = parse_conflicts(MARKED);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].start_line, 1);
+        assert_eq!(hunks[0].end_line, 5);
+        assert_eq!(hunks[0].ours, "ours\n");
+        assert_eq!(hunks[0].theirs, "theirs\n");
+        assert_eq!(hunks[0].base, None);
+        assert_eq!(hunks[1].base.as_deref(), Some("w\n"));
+    }
+
+    #[test]
+    fn test_resolve_hunk() {
+        let once = resolve_hunk(MARKED, 0, HunkSide::Theirs);
+        assert!(once.starts_with("a\ntheirs\nb\n<<<<<<<"));
+        assert_eq!(parse_conflicts(&once).len(), 1);
+
+        let done = resolve_hunk(&once, 0, HunkSide::Both);
+        assert_eq!(done, "a\ntheirs\nb\nx\ny\n");
+    }
+
+    #[test]
+    fn test_line_offset() {
+        assert_eq!(line_offset("ab\ncd\n", 0), 0);
+        assert_eq!(line_offset("ab\ncd\n", 1), 3);
+        assert_eq!(line_offset("ab\ncd\n", 5), 6);
+    }
+}
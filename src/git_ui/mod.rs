@@ -7,9 +7,15 @@ pub mod diff_view;
 pub mod blame_view;
 pub mod commit_history;
 pub mod branch_manager;
+pub mod merge_editor;
+pub mod hunk_peek;
+pub mod interactive_rebase;
 
 pub use source_control_panel::SourceControlPanel;
 pub use diff_view::DiffView;
 pub use blame_view::BlameView;
-pub use commit_history::CommitHistoryPanel;
+pub use commit_history::{CommitHistoryPanel, FileHistoryPanel};
 pub use branch_manager::BranchManagerPanel;
+pub use merge_editor::MergeEditor;
+pub use hunk_peek::{GitGutter, HunkPeek};
+pub use interactive_rebase::InteractiveRebase;
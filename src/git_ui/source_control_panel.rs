@@ -7,6 +7,10 @@ use leptos::task::spawn_local;
 use serde::{Deserialize, Serialize};
 use crate::common::async_bridge::TauriBridge;
 use crate::common::ui_components::Panel;
+use super::merge_editor::MergeEditor;
+
+/// Progress polling interval while a fetch/pull/push runs
+const PROGRESS_POLL_MS: u32 = 200;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileStatus {
@@ -22,30 +26,286 @@ pub struct BranchInfo {
     pub upstream: Option<String>,
 }
 
+/// Progress of a network operation (mirrors the backend `GitProgress`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitProgress {
+    pub operation: String,
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+    pub bytes: usize,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationResult {
+    pub status: String,
+    pub message: String,
+    pub commit: Option<String>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictFile {
+    pub path: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoStateInfo {
+    pub state: String,
+    pub conflicts: Vec<ConflictFile>,
+}
+
+/// Git summary of one workspace folder (mirrors the backend `RootStatus`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootStatus {
+    pub root: String,
+    pub repo_path: Option<String>,
+    pub branch: Option<String>,
+    pub changes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub commit: String,
+}
+
+/// One-line description of a network operation's progress
+pub fn progress_label(progress: &GitProgress) -> String {
+    let operation = match progress.operation.as_str() {
+        "fetch" => "Fetching",
+        "pull" => "Pulling",
+        "push" => "Pushing",
+        other => other,
+    };
+
+    if progress.stage == "done" {
+        return format!("{}: done", operation);
+    }
+
+    let mut label = format!("{}: {}", operation, progress.stage);
+    if let Some(percent) = (progress.current * 100).checked_div(progress.total) {
+        label.push_str(&format!(" {}% ({}/{})", percent, progress.current, progress.total));
+    }
+    if progress.bytes >= 1024 {
+        label.push_str(&format!(", {} KiB", progress.bytes / 1024));
+    }
+    if let Some(message) = &progress.message {
+        label.push_str(&format!(" — {}", message));
+    }
+    label
+}
+
+/// Banner text for an operation in progress, None when the repository is clean
+pub fn repo_state_label(state: &str) -> Option<&'static str> {
+    match state {
+        "merge" => Some("Merge in progress"),
+        "rebase" => Some("Rebase in progress"),
+        "cherry-pick" => Some("Cherry-pick in progress"),
+        "revert" => Some("Revert in progress"),
+        "bisect" => Some("Bisect in progress"),
+        _ => None,
+    }
+}
+
+/// Repository to show when `current` is not among the folders' repositories
+pub fn default_repo(statuses: &[RootStatus], current: Option<&str>) -> Option<String> {
+    let repos = || statuses.iter().filter_map(|s| s.repo_path.as_deref());
+    match current {
+        Some(current) if repos().any(|repo| repo == current) => Some(current.to_string()),
+        _ => repos().next().map(str::to_string),
+    }
+}
+
 /// Source Control Panel Component
 #[component]
-pub fn SourceControlPanel() -> impl IntoView {
+pub fn SourceControlPanel(
+    /// Workspace folders; with several, their repositories are listed and one is shown at a time
+    #[prop(optional, into)]
+    roots: Option<Signal<Vec<String>>>,
+) -> impl IntoView {
     let files = RwSignal::new(Vec::<FileStatus>::new());
     let branches = RwSignal::new(Vec::<BranchInfo>::new());
     let commit_message = RwSignal::new(String::new());
     let current_branch = RwSignal::new(String::from("main"));
     let loading = RwSignal::new(false);
     let error = RwSignal::new(None::<String>);
+    let repo_state = RwSignal::new(RepoStateInfo::default());
+    let stashes = RwSignal::new(Vec::<StashEntry>::new());
+    let stash_message = RwSignal::new(String::new());
+    let remote_busy = RwSignal::new(false);
+    let progress = RwSignal::new(None::<GitProgress>);
+    let info = RwSignal::new(None::<String>);
+    let merge_file = RwSignal::new(None::<String>);
+    let root_statuses = RwSignal::new(Vec::<RootStatus>::new());
+    let selected_repo = RwSignal::new(None::<String>);
+
+    // Status, repository state and stashes
+    let refresh_all = move || async move {
+        refresh_status(files, branches, current_branch).await?;
+        repo_state.set(TauriBridge::invoke("git_repo_state", ()).await?);
+        stashes.set(TauriBridge::invoke("git_stash_list", ()).await?);
+        Ok::<(), anyhow::Error>(())
+    };
+
+    // Show the repository of a workspace folder
+    let select_repo = move |repo: String| {
+        selected_repo.set(Some(repo.clone()));
+        spawn_local(async move {
+            let result = async {
+                set_repo_path(&repo).await?;
+                refresh_all().await
+            };
+            match result.await {
+                Ok(_) => error.set(None),
+                Err(e) => error.set(Some(format!("Failed to open repository {}: {}", repo, e))),
+            }
+        });
+    };
+
+    // Repositories of the workspace folders
+    Effect::new(move || {
+        let Some(roots) = roots.map(|r| r.get()).filter(|r| r.len() > 1) else {
+            return;
+        };
+        spawn_local(async move {
+            match root_statuses_of(roots).await {
+                Ok(statuses) => {
+                    let repo = default_repo(&statuses, selected_repo.get_untracked().as_deref());
+                    root_statuses.set(statuses);
+                    if repo != selected_repo.get_untracked() {
+                        if let Some(repo) = repo {
+                            select_repo(repo);
+                        }
+                    }
+                }
+                Err(e) => error.set(Some(format!("Failed to load repositories: {}", e))),
+            }
+        });
+    });
 
     // Load initial status
     Effect::new(move || {
         spawn_local(async move {
-            if let Err(e) = refresh_status(files, branches, current_branch).await {
+            if let Err(e) = refresh_all().await {
                 error.set(Some(format!("Failed to load Git status: {}", e)));
             }
         });
     });
 
+    // Fetch / pull / push: poll progress until the operation returns
+    let run_remote = move |command: &'static str| {
+        if remote_busy.get_untracked() {
+            return;
+        }
+        remote_busy.set(true);
+        progress.set(None);
+        info.set(None);
+
+        spawn_local(async move {
+            while remote_busy.get_untracked() {
+                if let Ok(current) = TauriBridge::invoke::<_, Option<GitProgress>>("git_progress", ()).await {
+                    progress.set(current);
+                }
+                gloo_timers::future::TimeoutFuture::new(PROGRESS_POLL_MS).await;
+            }
+        });
+
+        spawn_local(async move {
+            let result = match command {
+                "git_pull" => TauriBridge::invoke::<_, OperationResult>(command, ())
+                    .await
+                    .map(|r| r.message),
+                "git_push" => TauriBridge::invoke::<_, String>(command, ()).await,
+                _ => TauriBridge::invoke::<_, ()>(command, ())
+                    .await
+                    .map(|_| "Fetched".to_string()),
+            };
+            remote_busy.set(false);
+            progress.set(None);
+
+            match result {
+                Ok(message) => {
+                    info.set(Some(message));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("{} failed: {}", command.trim_start_matches("git_"), e))),
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    // Merge/rebase/cherry-pick controls
+    let handle_abort = move || {
+        spawn_local(async move {
+            if let Err(e) = TauriBridge::invoke::<_, ()>("git_abort_operation", ()).await {
+                error.set(Some(format!("Failed to abort: {}", e)));
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    let handle_rebase_continue = move || {
+        spawn_local(async move {
+            match TauriBridge::invoke::<_, OperationResult>("git_rebase_continue", ()).await {
+                Ok(result) => info.set(Some(result.message)),
+                Err(e) => error.set(Some(format!("Failed to continue rebase: {}", e))),
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    let handle_rebase_skip = move || {
+        spawn_local(async move {
+            match TauriBridge::invoke::<_, OperationResult>("git_rebase_skip", ()).await {
+                Ok(result) => info.set(Some(result.message)),
+                Err(e) => error.set(Some(format!("Failed to skip commit: {}", e))),
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    let handle_accept = move |path: String, side: &'static str| {
+        spawn_local(async move {
+            if let Err(e) = accept_conflict_side(&path, side).await {
+                error.set(Some(format!("Failed to resolve {}: {}", path, e)));
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    // Stash handlers
+    let handle_stash_save = move || {
+        let message = stash_message.get_untracked();
+        spawn_local(async move {
+            match stash_save(&message).await {
+                Ok(_) => {
+                    stash_message.set(String::new());
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(format!("Failed to stash: {}", e))),
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
+    let handle_stash_action = move |command: &'static str, index: usize| {
+        spawn_local(async move {
+            if let Err(e) = stash_action(command, index).await {
+                error.set(Some(format!("Stash operation failed: {}", e)));
+            }
+            let _ = refresh_all().await;
+        });
+    };
+
     // Refresh handler
     let handle_refresh = move || {
         loading.set(true);
         spawn_local(async move {
-            match refresh_status(files, branches, current_branch).await {
+            match refresh_all().await {
                 Ok(_) => {
                     error.set(None);
                     loading.set(false);
@@ -131,6 +391,40 @@ pub fn SourceControlPanel() -> impl IntoView {
     view! {
         <Panel title="Source Control">
             <div class="berry-git-panel">
+                // Repositories of a multi-root workspace
+                {move || {
+                    let statuses = root_statuses.get();
+                    (!statuses.is_empty()).then(|| view! {
+                        <div class="berry-git-repositories">
+                            <div class="berry-git-section-title">"REPOSITORIES"</div>
+                            {statuses.into_iter().map(|status| {
+                                let name = status.root.rsplit(['/', '\\']).next().unwrap_or(&status.root).to_string();
+                                let repo = status.repo_path.clone();
+                                let is_selected = repo.is_some() && repo == selected_repo.get();
+                                let detail = match &status.branch {
+                                    Some(branch) => format!("{} · {} changes", branch, status.changes),
+                                    None if repo.is_some() => format!("{} changes", status.changes),
+                                    None => "No repository".to_string(),
+                                };
+                                view! {
+                                    <div
+                                        class=if is_selected { "berry-git-file berry-git-repo selected" } else { "berry-git-file berry-git-repo" }
+                                        title=status.root.clone()
+                                        on:click=move |_| {
+                                            if let Some(repo) = repo.clone() {
+                                                select_repo(repo);
+                                            }
+                                        }
+                                    >
+                                        <span class="berry-git-file-path">{name}</span>
+                                        <span class="berry-git-file-status">{detail}</span>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    })
+                }}
+
                 // Header with branch selector and refresh
                 <div class="berry-git-header">
                     <select
@@ -162,6 +456,21 @@ pub fn SourceControlPanel() -> impl IntoView {
                     </button>
                 </div>
 
+                // Fetch / pull / push
+                <div class="berry-git-remote-actions">
+                    <button on:click=move |_| run_remote("git_fetch") disabled=move || remote_busy.get()>"Fetch"</button>
+                    <button on:click=move |_| run_remote("git_pull") disabled=move || remote_busy.get()>"Pull"</button>
+                    <button on:click=move |_| run_remote("git_push") disabled=move || remote_busy.get()>"Push"</button>
+                </div>
+                {move || {
+                    remote_busy.get().then(|| {
+                        let label = progress.get()
+                            .map(|p| progress_label(&p))
+                            .unwrap_or_else(|| "Connecting...".to_string());
+                        view! { <div class="berry-git-progress">{label}</div> }
+                    })
+                }}
+
                 // Error display
                 {move || {
                     error.get().map(|err| {
@@ -172,6 +481,64 @@ pub fn SourceControlPanel() -> impl IntoView {
                         }
                     })
                 }}
+                {move || info.get().map(|message| view! { <div class="berry-git-info">{message}</div> })}
+
+                // Merge / rebase / cherry-pick in progress
+                {move || {
+                    let state = repo_state.get().state;
+                    repo_state_label(&state).map(|label| {
+                        let is_rebase = state == "rebase";
+                        view! {
+                            <div class="berry-git-state-banner">
+                                <span>{label}</span>
+                                {is_rebase.then(|| view! {
+                                    <button on:click=move |_| handle_rebase_continue()>"Continue"</button>
+                                    <button title="Skip this commit" on:click=move |_| handle_rebase_skip()>"Skip"</button>
+                                })}
+                                <button on:click=move |_| handle_abort()>"Abort"</button>
+                            </div>
+                        }
+                    })
+                }}
+
+                // Three-way merge editor for the selected conflict
+                {move || {
+                    merge_file.get().map(|path| {
+                        let on_close = Callback::new(move |_| {
+                            merge_file.set(None);
+                            spawn_local(async move {
+                                let _ = refresh_all().await;
+                            });
+                        });
+                        view! { <MergeEditor file_path=path on_close=on_close /> }
+                    })
+                }}
+
+                // Merge conflicts
+                {move || {
+                    let conflicts = repo_state.get().conflicts;
+                    (!conflicts.is_empty()).then(|| view! {
+                        <div class="berry-git-conflicts">
+                            <div class="berry-git-section-title">"MERGE CONFLICTS"</div>
+                            {conflicts.into_iter().map(|conflict| {
+                                let ours = conflict.path.clone();
+                                let theirs = conflict.path.clone();
+                                let both = conflict.path.clone();
+                                let open = conflict.path.clone();
+                                view! {
+                                    <div class="berry-git-file berry-git-conflict">
+                                        <span class="berry-git-file-status">{conflict.kind}</span>
+                                        <span class="berry-git-file-path">{conflict.path}</span>
+                                        <button title="Accept ours" on:click=move |_| handle_accept(ours.clone(), "ours")>"Ours"</button>
+                                        <button title="Accept theirs" on:click=move |_| handle_accept(theirs.clone(), "theirs")>"Theirs"</button>
+                                        <button title="Accept both" on:click=move |_| handle_accept(both.clone(), "both")>"Both"</button>
+                                        <button title="Open in merge editor" on:click=move |_| merge_file.set(Some(open.clone()))>"Merge"</button>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    })
+                }}
 
                 // Changes section
                 <div class="berry-git-changes">
@@ -268,6 +635,33 @@ pub fn SourceControlPanel() -> impl IntoView {
                         "Commit"
                     </button>
                 </div>
+
+                // Stashes
+                <div class="berry-git-stashes">
+                    <div class="berry-git-section-title">"STASHES"</div>
+                    <div class="berry-git-stash-save">
+                        <input
+                            type="text"
+                            placeholder="Stash message (optional)"
+                            prop:value=move || stash_message.get()
+                            on:input=move |ev| stash_message.set(event_target_value(&ev))
+                        />
+                        <button on:click=move |_| handle_stash_save()>"Stash"</button>
+                    </div>
+                    {move || {
+                        stashes.get().into_iter().map(|stash| {
+                            let index = stash.index;
+                            view! {
+                                <div class="berry-git-file">
+                                    <span class="berry-git-file-path">{stash.message}</span>
+                                    <button title="Apply" on:click=move |_| handle_stash_action("git_stash_apply", index)>"Apply"</button>
+                                    <button title="Pop" on:click=move |_| handle_stash_action("git_stash_pop", index)>"Pop"</button>
+                                    <button title="Drop" on:click=move |_| handle_stash_action("git_stash_drop", index)>"Drop"</button>
+                                </div>
+                            }
+                        }).collect::<Vec<_>>()
+                    }}
+                </div>
             </div>
         </Panel>
     }
@@ -296,6 +690,26 @@ async fn refresh_status(
     Ok(())
 }
 
+async fn root_statuses_of(roots: Vec<String>) -> anyhow::Result<Vec<RootStatus>> {
+    #[derive(Serialize)]
+    struct RootsRequest {
+        roots: Vec<String>,
+    }
+
+    TauriBridge::invoke("git_root_statuses", RootsRequest { roots }).await
+}
+
+async fn set_repo_path(path: &str) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct RepoPathRequest {
+        path: String,
+    }
+
+    TauriBridge::invoke("git_set_repo_path", RepoPathRequest {
+        path: path.to_string(),
+    }).await
+}
+
 async fn stage_file(path: &str) -> anyhow::Result<()> {
     #[derive(Serialize)]
     struct StageRequest {
@@ -340,6 +754,43 @@ async fn switch_branch(branch_name: &str) -> anyhow::Result<()> {
     }).await
 }
 
+async fn accept_conflict_side(path: &str, side: &str) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct AcceptRequest {
+        file_path: String,
+        side: String,
+    }
+
+    TauriBridge::invoke("git_accept_conflict_side", AcceptRequest {
+        file_path: path.to_string(),
+        side: side.to_string(),
+    }).await
+}
+
+async fn stash_save(message: &str) -> anyhow::Result<String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct StashRequest {
+        message: Option<String>,
+        include_untracked: bool,
+    }
+
+    TauriBridge::invoke("git_stash_save", StashRequest {
+        message: (!message.trim().is_empty()).then(|| message.trim().to_string()),
+        include_untracked: true,
+    }).await
+}
+
+async fn stash_action(command: &str, index: usize) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct StashIndexRequest {
+        index: usize,
+    }
+
+    TauriBridge::invoke(command, StashIndexRequest { index }).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,4 +818,45 @@ mod tests {
         assert!(branch.is_head);
         assert_eq!(branch.name, "main");
     }
+
+    #[test]
+    fn test_progress_label() {
+        let progress = GitProgress {
+            operation: "fetch".to_string(),
+            stage: "receiving".to_string(),
+            current: 25,
+            total: 100,
+            bytes: 4096,
+            message: None,
+        };
+        assert_eq!(progress_label(&progress), "Fetching: receiving 25% (25/100), 4 KiB");
+
+        let done = GitProgress {
+            operation: "push".to_string(),
+            stage: "done".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(progress_label(&done), "Pushing: done");
+    }
+
+    #[test]
+    fn test_default_repo() {
+        let status = |root: &str, repo: Option<&str>| RootStatus {
+            root: root.to_string(),
+            repo_path: repo.map(str::to_string),
+            branch: None,
+            changes: 0,
+        };
+        let statuses = vec![status("/ws/docs", None), status("/ws/api", Some("/ws/api")), status("/ws/web", Some("/ws"))];
+        assert_eq!(default_repo(&statuses, None).as_deref(), Some("/ws/api"));
+        assert_eq!(default_repo(&statuses, Some("/ws")).as_deref(), Some("/ws"));
+        assert_eq!(default_repo(&statuses, Some("/other")).as_deref(), Some("/ws/api"));
+        assert_eq!(default_repo(&statuses[..1], None), None);
+    }
+
+    #[test]
+    fn test_repo_state_label() {
+        assert_eq!(repo_state_label("merge"), Some("Merge in progress"));
+        assert_eq!(repo_state_label("clean"), None);
+    }
 }
@@ -0,0 +1,115 @@
+//! Hex View
+//!
+//! Read-only view for binary files: offset, hex bytes and printable ASCII,
+//! loaded a page at a time.
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+const BYTES_PER_ROW: usize = 16;
+const PAGE_SIZE: usize = 64 * 1024;
+
+/// One row of the hex view
+#[derive(Debug, Clone, PartialEq)]
+pub struct HexRow {
+    pub offset: String,
+    pub hex: String,
+    pub ascii: String,
+}
+
+/// Rows for `bytes` starting at file offset `offset`
+pub fn hex_rows(offset: u64, bytes: &[u8]) -> Vec<HexRow> {
+    bytes
+        .chunks(BYTES_PER_ROW)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = (0..BYTES_PER_ROW)
+                .map(|j| chunk.get(j).map(|b| format!("{:02x}", b)).unwrap_or_else(|| "  ".to_string()))
+                .collect();
+            HexRow {
+                offset: format!("{:08x}", offset + (i * BYTES_PER_ROW) as u64),
+                // Extra gap after the eighth byte
+                hex: format!("{}  {}", hex[..8].join(" "), hex[8..].join(" ")),
+                ascii: chunk
+                    .iter()
+                    .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Hex dump of a binary file
+#[component]
+pub fn HexView(
+    /// File to show
+    path: String,
+    /// File size in bytes
+    size: u64,
+) -> impl IntoView {
+    let rows = RwSignal::new(Vec::<HexRow>::new());
+    let loaded = RwSignal::new(0_u64);
+    let error = RwSignal::new(Option::<String>::None);
+    let path = StoredValue::new(path);
+
+    let load_more = move || {
+        let offset = loaded.get_untracked();
+        spawn_local(async move {
+            match crate::tauri_bindings::read_file_bytes(&path.get_value(), offset, PAGE_SIZE).await {
+                Ok(bytes) => {
+                    rows.update(|r| r.extend(hex_rows(offset, &bytes)));
+                    loaded.set(offset + bytes.len() as u64);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+    load_more();
+
+    view! {
+        <div class="berry-hex-view" style="flex: 1; overflow: auto; padding: 8px 12px; font-family: 'JetBrains Mono', monospace; font-size: 12px; line-height: 18px; color: #BCBEC4; background: #1E1F22;">
+            <div style="color: #858585; margin-bottom: 8px; font-family: sans-serif;">
+                {format!("Binary file ({} bytes), shown read-only", size)}
+            </div>
+            <For each=move || rows.get() key=|row| row.offset.clone() let:row>
+                <div style="white-space: pre;">
+                    <span style="color: #6F737A;">{row.offset}</span>
+                    "  "
+                    <span>{row.hex}</span>
+                    "  "
+                    <span style="color: #6A8759;">{row.ascii}</span>
+                </div>
+            </For>
+            {move || error.get().map(|e| view! { <div style="color: #F14C4C; margin-top: 8px;">{e}</div> })}
+            {move || (loaded.get() < size && error.get().is_none()).then(|| view! {
+                <button
+                    on:click=move |_| load_more()
+                    style="margin-top: 8px; background: #4E5157; border: none; color: #DFE1E5; padding: 2px 8px; cursor: pointer; font-size: 12px; border-radius: 3px;"
+                >
+                    {move || format!("Load more ({} of {} bytes)", loaded.get(), size)}
+                </button>
+            })}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_rows() {
+        let bytes: Vec<u8> = (0x3e..0x50).collect();
+        let rows = hex_rows(0x100, &bytes);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].offset, "00000100");
+        assert_eq!(rows[0].hex, "3e 3f 40 41 42 43 44 45  46 47 48 49 4a 4b 4c 4d");
+        assert_eq!(rows[0].ascii, ">?@ABCDEFGHIJKLM");
+
+        // A short last row is padded so the ASCII column lines up
+        assert_eq!(rows[1].offset, "00000110");
+        assert_eq!(rows[1].hex.trim_end(), "4e 4f");
+        assert_eq!(rows[1].hex.len(), rows[0].hex.len());
+        assert_eq!(hex_rows(0, &[0, b'a', 0x7f])[0].ascii, ".a.");
+    }
+}
@@ -0,0 +1,35 @@
+//! Hot exit: the last session is restored at startup and kept saved while
+//! the app runs, so a crash or quit never loses unsaved edits
+
+use crate::tauri_bindings_hot_exit::{RestoredSession, Session};
+use leptos::prelude::*;
+
+/// Seconds between backups of dirty buffers and session saves
+pub const BACKUP_INTERVAL_SECS: u64 = 2;
+
+/// Session state shared by the app (layout) and the editor (tabs and buffers)
+#[derive(Clone, Copy)]
+pub struct HotExit {
+    /// Session read at startup; the editor takes it to reopen its tabs
+    pub restored: RwSignal<Option<RestoredSession>>,
+    /// Set once the startup restore finished; nothing is saved before that
+    pub ready: RwSignal<bool>,
+    /// Layout kept up to date by the app; its tabs are filled in by the editor
+    pub layout: RwSignal<Session>,
+}
+
+impl HotExit {
+    pub fn new() -> Self {
+        Self {
+            restored: RwSignal::new(None),
+            ready: RwSignal::new(false),
+            layout: RwSignal::new(Session::default()),
+        }
+    }
+}
+
+impl Default for HotExit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -29,7 +29,15 @@ pub mod tauri_bindings_search;
 pub mod tauri_bindings_database;
 pub mod tauri_bindings_workflow;
 pub mod tauri_bindings_terminal;
+pub mod tauri_bindings_tasks;
+pub mod tauri_bindings_testing;
 pub mod tauri_bindings_berrycode;
+pub mod tauri_bindings_watcher;
+pub mod tauri_bindings_hot_exit;
+pub mod tauri_bindings_workspace;
+pub mod tauri_bindings_remote;
+pub mod tauri_bindings_local_history;
+pub mod tauri_bindings_markdown;
 
 // ✅ Web Workers for background processing
 pub mod syntax_worker; // ✅ Strategy 1: Non-blocking syntax analysis
@@ -45,6 +53,7 @@ pub mod search_panel;
 
 // Database Tools
 pub mod database_panel;
+pub mod sql_completion;
 
 // Workflow Automation
 pub mod workflow_panel;
@@ -52,18 +61,37 @@ pub mod workflow_panel;
 // Terminal
 pub mod terminal_panel;
 
+// Task runner
+pub mod tasks_panel;
+
+// Test explorer
+pub mod test_explorer_panel;
+
 // BerryCode AI Assistant
 pub mod berrycode_panel;
 
 // Settings management
 pub mod settings;
 
+// Hot exit and crash recovery
+pub mod hot_exit;
+
+// Multi-root workspaces
+pub mod workspace;
+
+// Local history of project files
+pub mod local_history_panel;
+
+// Markdown preview beside the editor
+pub mod markdown_preview;
+
 // Common types
 pub mod types;
 
 // Phase 1: High-performance rendering
 pub mod highlight_job;
 pub mod virtual_scroll; // ✅ IntelliJ Pro: Async syntax highlighting
+pub mod hex_view; // Binary files
 
 // Phase 1: LSP UI Integration
 pub mod completion_widget;
@@ -80,8 +108,7 @@ pub mod debugger;
 // Phase 3: Refactoring Integration
 pub mod refactoring;
 
-// Phase 4: Git UI Integration (disabled in WASM - requires std::time)
-#[cfg(not(target_arch = "wasm32"))]
+// Phase 4: Git UI Integration
 pub mod git_ui;
 
 use components_tauri::EditorAppTauri;
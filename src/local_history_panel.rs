@@ -0,0 +1,330 @@
+//! Local History Panel
+//!
+//! Lists the recorded revisions of the file in the editor, its folder or the
+//! whole project, together with labelled checkpoints. Selecting a revision
+//! shows what changed since then; restoring writes it back to disk, where the
+//! workspace watcher picks it up like any other change.
+
+use crate::common::dialogs::InputDialog;
+use crate::tauri_bindings_local_history::*;
+use crate::tauri_bindings_remote;
+use crate::workspace::WorkspaceState;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+/// Which revisions are listed, relative to the file in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryScope {
+    File,
+    Folder,
+    Project,
+}
+
+impl HistoryScope {
+    const ALL: [HistoryScope; 3] = [HistoryScope::File, HistoryScope::Folder, HistoryScope::Project];
+
+    fn label(self) -> &'static str {
+        match self {
+            HistoryScope::File => "File",
+            HistoryScope::Folder => "Folder",
+            HistoryScope::Project => "Project",
+        }
+    }
+}
+
+/// The path whose history is listed; the project when no file is open
+pub fn scope_path(scope: HistoryScope, root: &str, file: Option<&str>) -> String {
+    match (scope, file) {
+        (HistoryScope::File, Some(file)) => file.to_string(),
+        (HistoryScope::Folder, Some(file)) => match file.rfind('/') {
+            Some(i) if i >= root.len() => file[..i].to_string(),
+            _ => root.to_string(),
+        },
+        _ => root.to_string(),
+    }
+}
+
+fn format_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn source_icon(source: RevisionSource) -> &'static str {
+    match source {
+        RevisionSource::Save => "codicon-save",
+        RevisionSource::External => "codicon-file",
+        RevisionSource::Agent => "codicon-sparkle",
+        RevisionSource::Restore => "codicon-discard",
+        RevisionSource::Checkpoint => "codicon-bookmark",
+    }
+}
+
+#[component]
+pub fn LocalHistoryPanel(
+    workspace: WorkspaceState,
+    /// Path of the file in the editor
+    #[prop(into)]
+    active_file: Signal<Option<String>>,
+    /// Open a restored file at a 1-based line/column
+    on_open_location: Callback<(String, usize, usize)>,
+) -> impl IntoView {
+    let scope = RwSignal::new(HistoryScope::File);
+    let listing = RwSignal::new(HistoryListing::default());
+    let selected = RwSignal::new(Option::<String>::None);
+    let diff = RwSignal::new(Option::<RevisionDiff>::None);
+    let error = RwSignal::new(Option::<String>::None);
+    let label_dialog_open = RwSignal::new(false);
+    // Bumped to reload the list after restoring or labelling
+    let refresh = RwSignal::new(0u32);
+
+    // Project of the file in the editor (remote folders have no local history)
+    let root = Memo::new(move |_| {
+        workspace.workspace.track();
+        active_file
+            .get()
+            .and_then(|file| workspace.root_for(&file))
+            .or_else(|| workspace.roots().into_iter().next())
+            .filter(|root| !tauri_bindings_remote::is_remote(root))
+    });
+    let target = Memo::new(move |_| {
+        let root = root.get()?;
+        let file = active_file.get().filter(|file| file.starts_with(&root));
+        Some(scope_path(scope.get(), &root, file.as_deref()))
+    });
+
+    Effect::new(move |_| {
+        refresh.track();
+        let (Some(root), Some(path)) = (root.get(), target.get()) else {
+            listing.set(HistoryListing::default());
+            return;
+        };
+        spawn_local(async move {
+            match local_history_list(&root, &path).await {
+                Ok(result) => {
+                    listing.set(result);
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    });
+
+    // Compare the selected revision with the file on disk
+    Effect::new(move |_| {
+        refresh.track();
+        let (Some(root), Some(id)) = (root.get_untracked(), selected.get()) else {
+            diff.set(None);
+            return;
+        };
+        spawn_local(async move {
+            match local_history_diff(&root, &id).await {
+                Ok(result) => diff.set(Some(result)),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    });
+
+    let restore = move |id: String| {
+        let Some(root) = root.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            match local_history_restore(&root, &id).await {
+                Ok(revision) => {
+                    selected.set(None);
+                    refresh.update(|n| *n += 1);
+                    on_open_location.run((revision.path, 1, 1));
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let put_label = move |label: String| {
+        let Some(root) = root.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            match local_history_put_label(&root, label.trim()).await {
+                Ok(_) => refresh.update(|n| *n += 1),
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let relative = move |path: &str| {
+        let root = root.get_untracked().unwrap_or_default();
+        path.strip_prefix(&root)
+            .map(|p| p.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| path.to_string())
+    };
+
+    view! {
+        <div class="berry-editor-sidebar berry-history-panel" style="background: #252526; display: flex; flex-direction: column; height: 100%;">
+            <div class="berry-editor-sidebar-header" style="
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                padding: 8px 12px;
+                background: #2D2D30;
+                border-bottom: 1px solid #1e1e1e;
+                font-size: 12px;
+                font-weight: 600;
+                color: #cccccc;
+            ">
+                <span>"LOCAL HISTORY"</span>
+                <span style="display: flex; gap: 8px;">
+                    <i
+                        class="codicon codicon-tag"
+                        title="Put Label"
+                        style="cursor: pointer;"
+                        on:click=move |_| label_dialog_open.set(true)
+                    ></i>
+                    <i
+                        class="codicon codicon-refresh"
+                        title="Refresh"
+                        style="cursor: pointer;"
+                        on:click=move |_| refresh.update(|n| *n += 1)
+                    ></i>
+                </span>
+            </div>
+
+            <div class="berry-history-scopes">
+                {HistoryScope::ALL.into_iter().map(|s| view! {
+                    <button
+                        class="berry-history-scope"
+                        class:active=move || scope.get() == s
+                        on:click=move |_| {
+                            selected.set(None);
+                            scope.set(s);
+                        }
+                    >
+                        {s.label()}
+                    </button>
+                }).collect_view()}
+            </div>
+
+            {move || error.get().map(|e| view! {
+                <div style="padding: 8px 12px; color: #f14c4c; font-size: 12px;">{e}</div>
+            })}
+
+            <div class="berry-history-list" style="overflow-y: auto; flex: 1;">
+                {move || {
+                    if root.get().is_none() {
+                        return view! {
+                            <div style="padding: 16px; color: #858585; font-size: 12px;">
+                                "Open a local folder to see its history."
+                            </div>
+                        }.into_any();
+                    }
+                    let entries = listing.get().entries();
+                    if entries.is_empty() {
+                        return view! {
+                            <div style="padding: 16px; color: #858585; font-size: 12px;">
+                                "No revisions recorded yet. Saves and changes on disk appear here."
+                            </div>
+                        }.into_any();
+                    }
+                    let show_path = scope.get() != HistoryScope::File;
+                    entries.into_iter().map(|entry| match entry {
+                        HistoryEntry::Checkpoint(checkpoint) => view! {
+                            <div class="berry-history-checkpoint" title=format_time(checkpoint.timestamp)>
+                                <i class="codicon codicon-tag"></i>
+                                <span>{checkpoint.label}</span>
+                            </div>
+                        }.into_any(),
+                        HistoryEntry::Revision(revision) => {
+                            let id = revision.id.clone();
+                            let is_selected = {
+                                let id = id.clone();
+                                move || selected.get().as_deref() == Some(id.as_str())
+                            };
+                            let path = show_path.then(|| relative(&revision.path));
+                            view! {
+                                <div
+                                    class="berry-history-revision"
+                                    class:selected=is_selected
+                                    title=revision.path.clone()
+                                    on:click=move |_| selected.set(Some(id.clone()))
+                                >
+                                    <i class=format!("codicon {}", source_icon(revision.source))></i>
+                                    <span class="berry-history-time">{format_time(revision.timestamp)}</span>
+                                    <span class="berry-history-source">{revision.source.label()}</span>
+                                    {path.map(|path| view! { <span class="berry-history-path">{path}</span> })}
+                                </div>
+                            }.into_any()
+                        }
+                    }).collect_view().into_any()
+                }}
+            </div>
+
+            {move || diff.get().map(|diff| {
+                let id = diff.revision.id.clone();
+                let summary = if diff.deleted {
+                    "File deleted since this revision".to_string()
+                } else if diff.binary {
+                    "Binary content".to_string()
+                } else if diff.hunks.is_empty() {
+                    "Same as the current content".to_string()
+                } else {
+                    format!("Changes since {}", format_time(diff.revision.timestamp))
+                };
+                view! {
+                    <div class="berry-history-diff">
+                        <div class="berry-history-diff-header">
+                            <span>{summary}</span>
+                            <button class="berry-history-restore" on:click=move |_| restore(id.clone())>
+                                "Restore"
+                            </button>
+                        </div>
+                        <div class="berry-history-diff-body">
+                            {diff.hunks.into_iter().map(|hunk| view! {
+                                <div class="berry-history-hunk-header">
+                                    {format!("@@ -{},{} +{},{} @@", hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines)}
+                                </div>
+                                {hunk.lines.into_iter().map(|line| {
+                                    let prefix = match line.line_type.as_str() {
+                                        "add" => "+",
+                                        "delete" => "-",
+                                        _ => " ",
+                                    };
+                                    view! {
+                                        <div class=format!("berry-history-line berry-history-line-{}", line.line_type)>
+                                            {format!("{}{}", prefix, line.content)}
+                                        </div>
+                                    }
+                                }).collect_view()}
+                            }).collect_view()}
+                        </div>
+                    </div>
+                }
+            })}
+
+            <InputDialog
+                is_open=label_dialog_open
+                title="Put Label".to_string()
+                placeholder="Label, e.g. Before refactoring".to_string()
+                initial_value=String::new()
+                on_submit=put_label
+                on_cancel=|| {}
+            />
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_path() {
+        let file = Some("/p/src/main.rs");
+        assert_eq!(scope_path(HistoryScope::File, "/p", file), "/p/src/main.rs");
+        assert_eq!(scope_path(HistoryScope::Folder, "/p", file), "/p/src");
+        assert_eq!(scope_path(HistoryScope::Folder, "/p", Some("/p/Cargo.toml")), "/p");
+        assert_eq!(scope_path(HistoryScope::Project, "/p", file), "/p");
+        assert_eq!(scope_path(HistoryScope::File, "/p", None), "/p");
+    }
+}
@@ -0,0 +1,270 @@
+//! Markdown Preview
+//!
+//! Renders the Markdown document in the focused editor group beside it.
+//! The preview re-renders shortly after typing stops and follows the editor's
+//! scroll position through the `data-sourcepos` line ranges the renderer puts
+//! on block elements. Links to other files open them in the editor.
+
+use crate::tauri_bindings_markdown::*;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::JsCast;
+
+/// Typing pause before the document is rendered again
+const RENDER_DELAY_MS: u32 = 150;
+
+/// Whether the preview can show `path`
+pub fn is_markdown_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".md", ".markdown", ".mdown", ".mkd"].iter().any(|ext| lower.ends_with(ext))
+}
+
+/// First and last source line (1-based) of a `data-sourcepos` such as `3:1-5:12`
+pub fn parse_sourcepos(value: &str) -> Option<(usize, usize)> {
+    let (start, end) = value.split_once('-')?;
+    let line = |pos: &str| pos.split(':').next()?.parse::<usize>().ok();
+    Some((line(start)?, line(end)?))
+}
+
+/// What a click on a link in the preview does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkAction {
+    /// Scroll to the element with this id
+    Anchor(String),
+    /// Open in the browser
+    External(String),
+    /// Open a file in the editor; relative links are resolved by the renderer
+    File(String),
+}
+
+pub fn link_action(href: &str) -> Option<LinkAction> {
+    if let Some(id) = href.strip_prefix('#') {
+        return Some(LinkAction::Anchor(id.to_string()));
+    }
+    let lower = href.to_lowercase();
+    if ["http://", "https://", "mailto:"].iter().any(|scheme| lower.starts_with(scheme)) {
+        return Some(LinkAction::External(href.to_string()));
+    }
+    let path = href.split('#').next().unwrap_or_default();
+    (!path.is_empty() && !lower.starts_with("javascript:")).then(|| LinkAction::File(path.to_string()))
+}
+
+/// Suggested export target next to the document
+fn html_path(path: &str) -> String {
+    match path.rfind('.') {
+        Some(dot) if dot > path.rfind('/').unwrap_or(0) => format!("{}.html", &path[..dot]),
+        _ => format!("{}.html", path),
+    }
+}
+
+/// Scroll `body` so the block holding `line` (0-based) is at the top,
+/// interpolating within blocks that span several lines
+fn scroll_to_line(body: &web_sys::HtmlElement, line: usize) {
+    let line = line + 1;
+    let Ok(blocks) = body.query_selector_all("[data-sourcepos]") else {
+        return;
+    };
+    // Blocks come in document order; the last one starting at or above the
+    // line is the innermost block that holds it
+    let mut target = None;
+    for i in 0..blocks.length() {
+        let Some(element) = blocks.item(i).and_then(|n| n.dyn_into::<web_sys::Element>().ok()) else {
+            continue;
+        };
+        let Some((start, end)) = element.get_attribute("data-sourcepos").as_deref().and_then(parse_sourcepos) else {
+            continue;
+        };
+        if start > line {
+            break;
+        }
+        target = Some((element, start, end));
+    }
+
+    let Some((element, start, end)) = target else {
+        body.set_scroll_top(0);
+        return;
+    };
+    let rect = element.get_bounding_client_rect();
+    let top = rect.top() - body.get_bounding_client_rect().top() + body.scroll_top() as f64;
+    let fraction = if line <= end {
+        (line - start) as f64 / (end - start + 1) as f64
+    } else {
+        // Past the block's end: blank lines between blocks
+        1.0
+    };
+    body.set_scroll_top((top + fraction * rect.height()) as i32);
+}
+
+#[component]
+pub fn MarkdownPreview(
+    /// Path and current text of the document
+    #[prop(into)]
+    source: Signal<Option<(String, String)>>,
+    /// First line shown in the editor, 0-based
+    #[prop(into)]
+    top_line: Signal<usize>,
+    /// Opens a file at (path, line, column), 1-based
+    #[prop(optional)]
+    on_open_location: Option<Callback<(String, usize, usize)>>,
+    on_close: Callback<()>,
+) -> impl IntoView {
+    let body_ref = NodeRef::<leptos::html::Div>::new();
+    let rendered = RwSignal::new(Option::<RenderedMarkdown>::None);
+    let error = RwSignal::new(Option::<String>::None);
+    let status = RwSignal::new(Option::<String>::None);
+    // Renders finishing out of order must not replace a newer one
+    let generation = StoredValue::new(0u32);
+
+    Effect::new(move |_| {
+        let Some((path, text)) = source.get() else {
+            rendered.set(None);
+            return;
+        };
+        generation.update_value(|g| *g += 1);
+        let current = generation.get_value();
+        spawn_local(async move {
+            gloo_timers::future::TimeoutFuture::new(RENDER_DELAY_MS).await;
+            if generation.get_value() != current {
+                return;
+            }
+            let result = markdown_render(&path, &text).await;
+            if generation.get_value() != current {
+                return;
+            }
+            match result {
+                Ok(result) => {
+                    rendered.set(Some(result));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    });
+
+    // Follow the editor after scrolling and after each render
+    Effect::new(move |_| {
+        rendered.track();
+        let line = top_line.get();
+        if let Some(body) = body_ref.get() {
+            scroll_to_line(&body, line);
+        }
+    });
+
+    let on_click = move |ev: leptos::ev::MouseEvent| {
+        let Some(link) = ev
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            .and_then(|el| el.closest("a[href]").ok().flatten())
+        else {
+            return;
+        };
+        ev.prevent_default();
+        let href = link.get_attribute("href").unwrap_or_default();
+        match link_action(&href) {
+            Some(LinkAction::Anchor(id)) => {
+                let target = body_ref
+                    .get_untracked()
+                    .and_then(|body| body.query_selector(&format!("[id=\"{}\"]", id.replace('"', ""))).ok().flatten());
+                if let Some(target) = target {
+                    target.scroll_into_view();
+                }
+            }
+            Some(LinkAction::External(url)) => {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.open_with_url_and_target(&url, "_blank");
+                }
+            }
+            Some(LinkAction::File(path)) => {
+                if let Some(callback) = on_open_location {
+                    callback.run((path, 1, 1));
+                }
+            }
+            None => {}
+        }
+    };
+
+    let export = move |_| {
+        let Some((path, text)) = source.get_untracked() else {
+            return;
+        };
+        let target = web_sys::window()
+            .and_then(|w| w.prompt_with_message_and_default("Export HTML to", &html_path(&path)).ok().flatten())
+            .filter(|target| !target.trim().is_empty());
+        let Some(target) = target else {
+            return;
+        };
+        spawn_local(async move {
+            match markdown_export_html(&path, &text, target.trim()).await {
+                Ok(()) => {
+                    status.set(Some(format!("Exported to {}", target.trim())));
+                    error.set(None);
+                }
+                Err(e) => error.set(Some(e)),
+            }
+        });
+    };
+
+    let title = move || {
+        source.with(|s| {
+            s.as_ref()
+                .map(|(path, _)| path.rsplit(['/', '\\']).next().unwrap_or(path).to_string())
+                .unwrap_or_default()
+        })
+    };
+
+    view! {
+        <div class="berry-markdown-preview">
+            <div class="berry-markdown-header">
+                <i class="codicon codicon-open-preview"></i>
+                <span class="berry-markdown-title">{move || format!("Preview {}", title())}</span>
+                <i class="codicon codicon-export" title="Export HTML" on:click=export></i>
+                <i class="codicon codicon-close" title="Close Preview" on:click=move |_| on_close.run(())></i>
+            </div>
+            {move || error.get().map(|e| view! { <div class="berry-markdown-error">{e}</div> })}
+            {move || status.get().map(|s| view! {
+                <div class="berry-markdown-status" on:click=move |_| status.set(None)>{s}</div>
+            })}
+            <div
+                node_ref=body_ref
+                class="berry-markdown-body"
+                on:click=on_click
+                inner_html=move || rendered.with(|r| r.as_ref().map(|r| r.html.clone()).unwrap_or_default())
+            ></div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sourcepos() {
+        assert_eq!(parse_sourcepos("3:1-5:12"), Some((3, 5)));
+        assert_eq!(parse_sourcepos("1:1-1:1"), Some((1, 1)));
+        assert_eq!(parse_sourcepos("bad"), None);
+    }
+
+    #[test]
+    fn test_link_action() {
+        assert_eq!(link_action("#usage"), Some(LinkAction::Anchor("usage".to_string())));
+        assert_eq!(
+            link_action("https://x.dev/a"),
+            Some(LinkAction::External("https://x.dev/a".to_string()))
+        );
+        assert_eq!(
+            link_action("/p/docs/api.md#usage"),
+            Some(LinkAction::File("/p/docs/api.md".to_string()))
+        );
+        assert_eq!(link_action("javascript:alert(1)"), None);
+    }
+
+    #[test]
+    fn test_paths() {
+        assert!(is_markdown_path("/p/README.md"));
+        assert!(is_markdown_path("/p/notes.Markdown"));
+        assert!(!is_markdown_path("/p/main.rs"));
+        assert_eq!(html_path("/p/docs/guide.md"), "/p/docs/guide.html");
+        assert_eq!(html_path("/p.d/README"), "/p.d/README.html");
+    }
+}
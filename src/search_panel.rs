@@ -1,24 +1,55 @@
 //! Search Panel Component
 //! Project-wide search functionality
 
+use crate::settings::EditorSettings;
+use crate::tauri_bindings_search::search_in_files;
+use crate::tauri_bindings_workspace::{Workspace, WorkspaceFolder};
+use crate::workspace::settings_for;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
 
 // Re-export search types from tauri_bindings_search
 pub use crate::tauri_bindings_search::{SearchOptions, SearchResult};
 
+/// Ripgrep glob matching any of the exclude patterns
+pub fn exclude_glob(patterns: &[String]) -> Option<String> {
+    match patterns {
+        [] => None,
+        [pattern] => Some(pattern.clone()),
+        _ => Some(format!("{{{}}}", patterns.join(","))),
+    }
+}
+
+/// Results grouped by file, in the order the files were first matched
+pub fn group_by_file(results: Vec<SearchResult>) -> Vec<(String, Vec<SearchResult>)> {
+    let mut groups: Vec<(String, Vec<SearchResult>)> = Vec::new();
+    for result in results {
+        match groups.iter_mut().find(|(path, _)| *path == result.path) {
+            Some((_, group)) => group.push(result),
+            None => groups.push((result.path.clone(), vec![result])),
+        }
+    }
+    groups
+}
+
 #[component]
 pub fn SearchPanel(
     is_open: RwSignal<bool>,
-    root_path: String,
-    on_result_click: impl Fn(String, usize) + 'static + Clone + Send + Sync,
+    /// Every folder is searched with its own exclude settings
+    workspace: Workspace,
+    /// Called with the file, 1-based line and 1-based column of a result
+    on_result_click: impl Fn(String, usize, usize) + 'static + Clone + Send + Sync,
 ) -> impl IntoView {
     let on_result_click = StoredValue::new(on_result_click);
     let search_query = RwSignal::new(String::new());
-    let search_results = RwSignal::new(Vec::<SearchResult>::new());
+    // Results of each folder that had matches
+    let search_results = RwSignal::new(Vec::<(WorkspaceFolder, Vec<SearchResult>)>::new());
     let is_searching = RwSignal::new(false);
     let case_sensitive = RwSignal::new(false);
     let use_regex = RwSignal::new(false);
     let error_message = RwSignal::new(None::<String>);
+    let multi_root = workspace.folders.len() > 1;
+    let workspace = StoredValue::new(workspace);
 
     // Perform search function - stored as a signal to allow multiple uses
     let perform_search = StoredValue::new(move || {
@@ -31,40 +62,77 @@ pub fn SearchPanel(
         is_searching.set(true);
         error_message.set(None);
 
-        let root = root_path.clone();
-        let options = SearchOptions {
-            case_sensitive: case_sensitive.get(),
-            use_regex: use_regex.get(),
-            ..Default::default()
-        };
-
-        // In a real implementation, this would call the Tauri search command
-        // For now, we just log it
-
-        // Simulated search results for demonstration
-        let demo_results = vec![
-            SearchResult {
-                path: format!("{}/src/main.rs", root),
-                line_number: 10,
-                column: 5,
-                line_text: "fn main() {".to_string(),
-                match_start: 3,
-                match_end: 7,
-            },
-            SearchResult {
-                path: format!("{}/src/lib.rs", root),
-                line_number: 25,
-                column: 12,
-                line_text: "    // Main module".to_string(),
-                match_start: 7,
-                match_end: 11,
-            },
-        ];
-
-        search_results.set(demo_results);
-        is_searching.set(false);
+        let settings = EditorSettings::load();
+        let searches: Vec<(WorkspaceFolder, SearchOptions)> = workspace.with_value(|workspace| {
+            workspace
+                .folders
+                .iter()
+                .map(|folder| {
+                    let exclude = settings_for(&settings, workspace, &folder.path).files_exclude;
+                    let options = SearchOptions {
+                        case_sensitive: case_sensitive.get(),
+                        use_regex: use_regex.get(),
+                        exclude_pattern: exclude_glob(&exclude),
+                        ..Default::default()
+                    };
+                    (folder.clone(), options)
+                })
+                .collect()
+        });
+
+        spawn_local(async move {
+            let mut results = Vec::new();
+            for (folder, options) in searches {
+                match search_in_files(&query, &folder.path, Some(options)).await {
+                    Ok(found) if !found.is_empty() => results.push((folder, found)),
+                    Ok(_) => {}
+                    Err(e) => error_message.set(Some(e)),
+                }
+            }
+            search_results.set(results);
+            is_searching.set(false);
+        });
     });
 
+    let file_group = move |root: String, path: String, results: Vec<SearchResult>| {
+        let filename = path
+            .strip_prefix(root.as_str())
+            .map(|rest| rest.trim_start_matches(['/', '\\']))
+            .unwrap_or(&path)
+            .to_string();
+        let result_count = results.len();
+
+        view! {
+            <div class="berry-search-file-group">
+                <div class="berry-search-file-header" title=path.clone()>
+                    <i class="codicon codicon-file"></i>
+                    " " {filename} " (" {result_count} " results)"
+                </div>
+                <div class="berry-search-file-results">
+                    {results.into_iter().map(|result| {
+                        let path_clone = result.path.clone();
+                        let line_num = result.line_number;
+                        let column = result.column;
+
+                        view! {
+                            <div
+                                class="berry-search-result-item"
+                                on:click=move |_| on_result_click.with_value(|f| f(path_clone.clone(), line_num, column))
+                            >
+                                <span class="berry-search-result-line-num">
+                                    {result.line_number}":"
+                                </span>
+                                <span class="berry-search-result-text">
+                                    {result.line_text.clone()}
+                                </span>
+                            </div>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+            </div>
+        }
+    };
+
     view! {
         {move || {
             if is_open.get() {
@@ -136,51 +204,27 @@ pub fn SearchPanel(
                         <div class="berry-search-results">
                             {move || {
                                 let results = search_results.get();
-                                if results.is_empty() && !search_query.get().is_empty() {
+                                if results.is_empty() && !search_query.get().is_empty() && !is_searching.get() {
                                     view! {
                                         <div class="berry-search-no-results">"No results found"</div>
                                     }.into_any()
                                 } else {
-                                    // Group results by file
-                                    let mut grouped: std::collections::HashMap<String, Vec<SearchResult>> = std::collections::HashMap::new();
-                                    for result in results {
-                                        grouped.entry(result.path.clone()).or_insert_with(Vec::new).push(result);
-                                    }
-
+                                    // Group results by folder, then by file
                                     view! {
                                         <div>
-                                            {grouped.into_iter().map(|(path, results)| {
-                                                let filename = path.split('/').last().unwrap_or(&path).to_string();
-                                                let result_count = results.len();
-
-                                                view! {
-                                                    <div class="berry-search-file-group">
-                                                        <div class="berry-search-file-header">
-                                                            <i class="codicon codicon-file"></i>
-                                                            " " {filename.clone()} " (" {result_count} " results)"
-                                                        </div>
-                                                        <div class="berry-search-file-results">
-                                                            {results.into_iter().map(|result| {
-                                                                let path_clone = result.path.clone();
-                                                                let line_num = result.line_number;
-
-                                                                view! {
-                                                                    <div
-                                                                        class="berry-search-result-item"
-                                                                        on:click=move |_| on_result_click.with_value(|f| f(path_clone.clone(), line_num))
-                                                                    >
-                                                                        <span class="berry-search-result-line-num">
-                                                                            {result.line_number}":"
-                                                                        </span>
-                                                                        <span class="berry-search-result-text">
-                                                                            {result.line_text.clone()}
-                                                                        </span>
-                                                                    </div>
-                                                                }
-                                                            }).collect::<Vec<_>>()}
-                                                        </div>
+                                            {results.into_iter().map(|(folder, results)| {
+                                                let root = folder.path.clone();
+                                                let header = multi_root.then(|| view! {
+                                                    <div class="berry-search-root-header" title=root.clone()>
+                                                        <i class="codicon codicon-root-folder"></i>
+                                                        " " {folder.display_name()}
                                                     </div>
-                                                }
+                                                });
+                                                let files = group_by_file(results)
+                                                    .into_iter()
+                                                    .map(|(path, results)| file_group(root.clone(), path, results))
+                                                    .collect::<Vec<_>>();
+                                                view! { {header} {files} }
                                             }).collect::<Vec<_>>()}
                                         </div>
                                     }.into_any()
@@ -195,3 +239,39 @@ pub fn SearchPanel(
         }}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, line_number: usize) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            line_number,
+            column: 1,
+            line_text: String::new(),
+            match_start: 0,
+            match_end: 0,
+        }
+    }
+
+    #[test]
+    fn test_group_by_file_keeps_order() {
+        let groups = group_by_file(vec![result("/ws/b.rs", 3), result("/ws/a.rs", 1), result("/ws/b.rs", 7)]);
+        let summary: Vec<(&str, Vec<usize>)> = groups
+            .iter()
+            .map(|(path, results)| (path.as_str(), results.iter().map(|r| r.line_number).collect()))
+            .collect();
+        assert_eq!(summary, vec![("/ws/b.rs", vec![3, 7]), ("/ws/a.rs", vec![1])]);
+    }
+
+    #[test]
+    fn test_exclude_glob() {
+        assert_eq!(exclude_glob(&[]), None);
+        assert_eq!(exclude_glob(&["target".to_string()]).as_deref(), Some("target"));
+        assert_eq!(
+            exclude_glob(&["target".to_string(), ".git".to_string()]).as_deref(),
+            Some("{target,.git}")
+        );
+    }
+}
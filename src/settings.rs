@@ -16,6 +16,11 @@ pub struct EditorSettings {
     pub insert_spaces: bool,
     pub word_wrap: bool,
 
+    // Files
+    /// Gitignore-style patterns hidden from the explorer
+    #[serde(default = "default_files_exclude")]
+    pub files_exclude: Vec<String>,
+
     // Theme
     pub color_theme: String,
     pub icon_theme: String,
@@ -37,6 +42,9 @@ impl Default for EditorSettings {
             insert_spaces: true,
             word_wrap: false,
 
+            // Files defaults
+            files_exclude: default_files_exclude(),
+
             // Theme defaults
             color_theme: "RustRover Darcula".to_string(),
             icon_theme: "Codicons".to_string(),
@@ -49,9 +57,22 @@ impl Default for EditorSettings {
     }
 }
 
+fn default_files_exclude() -> Vec<String> {
+    vec![".git".to_string(), ".DS_Store".to_string(), "Thumbs.db".to_string()]
+}
+
 impl EditorSettings {
     const STORAGE_KEY: &'static str = "berry-editor-settings";
 
+    /// What Tab inserts in files that are not indented yet
+    pub fn indent_unit(&self) -> String {
+        if self.insert_spaces {
+            " ".repeat(self.tab_size.clamp(1, 8) as usize)
+        } else {
+            "\t".to_string()
+        }
+    }
+
     /// Load settings from localStorage
     pub fn load() -> Self {
         if let Some(window) = web_sys::window() {
@@ -78,6 +99,21 @@ impl EditorSettings {
         Ok(())
     }
 
+    /// These settings with `overrides` applied; values of the wrong type are ignored
+    pub fn with_overrides(&self, overrides: &serde_json::Map<String, serde_json::Value>) -> Self {
+        let mut settings = self.clone();
+        for (key, value) in overrides {
+            let Ok(serde_json::Value::Object(mut fields)) = serde_json::to_value(&settings) else {
+                break;
+            };
+            fields.insert(key.clone(), value.clone());
+            if let Ok(merged) = serde_json::from_value(serde_json::Value::Object(fields)) {
+                settings = merged;
+            }
+        }
+        settings
+    }
+
     /// Get available font families
     pub fn available_fonts() -> Vec<&'static str> {
         vec![
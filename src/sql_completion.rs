@@ -0,0 +1,509 @@
+//! SQL Completion
+//!
+//! Context-aware completion for the query console, driven by the cached
+//! schema metadata: tables after `FROM`/`JOIN`, columns after `alias.`,
+//! columns of the referenced tables elsewhere.
+
+use crate::tauri_bindings_database::{DatabaseSchema, TableInfo, TableKind};
+
+/// Maximum number of suggestions shown
+const MAX_COMPLETIONS: usize = 100;
+
+const KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "JOIN", "LEFT", "RIGHT", "INNER", "OUTER", "FULL", "CROSS", "ON",
+    "USING", "GROUP", "ORDER", "BY", "HAVING", "LIMIT", "OFFSET", "INSERT", "INTO", "VALUES",
+    "UPDATE", "SET", "DELETE", "CREATE", "ALTER", "DROP", "TABLE", "VIEW", "INDEX", "AND", "OR",
+    "NOT", "NULL", "IS", "IN", "EXISTS", "BETWEEN", "LIKE", "AS", "DISTINCT", "UNION", "ALL",
+    "CASE", "WHEN", "THEN", "ELSE", "END", "WITH", "RETURNING", "ASC", "DESC", "COUNT", "SUM",
+    "AVG", "MIN", "MAX", "COALESCE", "BEGIN", "COMMIT", "ROLLBACK",
+];
+
+/// Keywords after which a table name is expected
+const TABLE_KEYWORDS: &[&str] = &["FROM", "JOIN", "UPDATE", "INTO", "TABLE"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SqlCompletionKind {
+    Column,
+    Table,
+    View,
+    Schema,
+    Function,
+    Keyword,
+}
+
+impl SqlCompletionKind {
+    /// Letter shown in the completion list
+    pub fn badge(self) -> &'static str {
+        match self {
+            SqlCompletionKind::Column => "C",
+            SqlCompletionKind::Table => "T",
+            SqlCompletionKind::View => "V",
+            SqlCompletionKind::Schema => "S",
+            SqlCompletionKind::Function => "F",
+            SqlCompletionKind::Keyword => "K",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlCompletion {
+    pub label: String,
+    pub kind: SqlCompletionKind,
+    pub detail: String,
+}
+
+/// Suggestions for the word at `cursor` (a byte offset into `sql`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SqlCompletions {
+    /// Byte offset where the word being completed starts
+    pub start: usize,
+    /// The word typed so far
+    pub prefix: String,
+    /// The word follows `qualifier.`
+    pub qualified: bool,
+    pub items: Vec<SqlCompletion>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// Double-quoted or backquoted identifier
+    Quoted(String),
+    Dot,
+    Comma,
+    Semicolon,
+    Other,
+}
+
+impl Token {
+    fn identifier(&self) -> Option<&str> {
+        match self {
+            Token::Word(w) | Token::Quoted(w) => Some(w),
+            _ => None,
+        }
+    }
+
+    fn is_keyword(&self, keywords: &[&str]) -> bool {
+        matches!(self, Token::Word(w) if keywords.iter().any(|k| w.eq_ignore_ascii_case(k)))
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Tokens with their byte offsets; string literals and comments are skipped
+fn tokenize(sql: &str) -> Vec<(usize, Token)> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i].1 == '*' && chars.get(i + 1).map(|(_, c)| *c) == Some('/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' || c == '"' || c == '`' {
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i].1 == c {
+                    // A doubled quote is an escaped quote
+                    if chars.get(i + 1).map(|(_, ch)| *ch) == Some(c) {
+                        text.push(c);
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                text.push(chars[i].1);
+                i += 1;
+            }
+            i += 1;
+            tokens.push((start, if c == '\'' { Token::Other } else { Token::Quoted(text) }));
+        } else if is_word_char(c) {
+            let mut word = String::new();
+            while i < chars.len() && is_word_char(chars[i].1) {
+                word.push(chars[i].1);
+                i += 1;
+            }
+            tokens.push((start, Token::Word(word)));
+        } else {
+            tokens.push((
+                start,
+                match c {
+                    '.' => Token::Dot,
+                    ',' => Token::Comma,
+                    ';' => Token::Semicolon,
+                    _ => Token::Other,
+                },
+            ));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// A table referenced by the statement, with its alias
+#[derive(Debug, Clone, PartialEq)]
+struct TableRef {
+    schema: Option<String>,
+    name: String,
+    alias: Option<String>,
+}
+
+/// Tables named after FROM/JOIN/UPDATE/INTO (and commas in a FROM list)
+fn table_refs(tokens: &[Token]) -> Vec<TableRef> {
+    let mut refs = Vec::new();
+    let mut in_from = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let starts_ref = tokens[i].is_keyword(TABLE_KEYWORDS) || (in_from && tokens[i] == Token::Comma);
+        if tokens[i].is_keyword(&["FROM"]) {
+            in_from = true;
+        } else if tokens[i].is_keyword(&["WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "ON", "SET", "UNION"]) {
+            in_from = false;
+        }
+        i += 1;
+        if !starts_ref {
+            continue;
+        }
+
+        let Some(first) = tokens.get(i).and_then(Token::identifier) else {
+            continue;
+        };
+        if tokens[i].is_keyword(KEYWORDS) && !matches!(tokens[i], Token::Quoted(_)) {
+            continue;
+        }
+        let (schema, name) = match (tokens.get(i + 1), tokens.get(i + 2).and_then(Token::identifier)) {
+            (Some(Token::Dot), Some(table)) => {
+                i += 3;
+                (Some(first.to_string()), table.to_string())
+            }
+            _ => {
+                i += 1;
+                (None, first.to_string())
+            }
+        };
+        if tokens.get(i).is_some_and(|t| t.is_keyword(&["AS"])) {
+            i += 1;
+        }
+        let alias = match tokens.get(i) {
+            Some(token @ Token::Word(word)) if !token.is_keyword(KEYWORDS) => Some(word.clone()),
+            Some(Token::Quoted(word)) => Some(word.clone()),
+            _ => None,
+        };
+        if alias.is_some() {
+            i += 1;
+        }
+        refs.push(TableRef { schema, name, alias });
+    }
+    refs
+}
+
+fn find_tables<'a>(schema: &'a DatabaseSchema, schema_name: Option<&str>, name: &str) -> Vec<&'a TableInfo> {
+    schema
+        .schemas
+        .iter()
+        .filter(|s| schema_name.is_none_or(|n| s.name.eq_ignore_ascii_case(n)))
+        .flat_map(|s| s.tables.iter())
+        .filter(|t| t.name.eq_ignore_ascii_case(name))
+        .collect()
+}
+
+fn column_items(table: &TableInfo, items: &mut Vec<SqlCompletion>) {
+    for column in &table.columns {
+        items.push(SqlCompletion {
+            label: column.name.clone(),
+            kind: SqlCompletionKind::Column,
+            detail: format!("{} · {}", table.name, column.data_type),
+        });
+    }
+}
+
+fn table_items(schema: &DatabaseSchema, schema_name: Option<&str>, items: &mut Vec<SqlCompletion>) {
+    for s in schema
+        .schemas
+        .iter()
+        .filter(|s| schema_name.is_none_or(|n| s.name.eq_ignore_ascii_case(n)))
+    {
+        for table in &s.tables {
+            items.push(SqlCompletion {
+                label: table.name.clone(),
+                kind: match table.kind {
+                    TableKind::Table => SqlCompletionKind::Table,
+                    TableKind::View => SqlCompletionKind::View,
+                },
+                detail: s.name.clone(),
+            });
+        }
+    }
+}
+
+fn function_items(schema: &DatabaseSchema, schema_name: Option<&str>, items: &mut Vec<SqlCompletion>) {
+    for s in schema
+        .schemas
+        .iter()
+        .filter(|s| schema_name.is_none_or(|n| s.name.eq_ignore_ascii_case(n)))
+    {
+        for function in &s.functions {
+            items.push(SqlCompletion {
+                label: function.name.clone(),
+                kind: SqlCompletionKind::Function,
+                detail: format!("({}) → {}", function.arguments, function.return_type),
+            });
+        }
+    }
+}
+
+/// Completion candidates for the word at `cursor`
+pub fn complete(sql: &str, cursor: usize, schema: &DatabaseSchema) -> SqlCompletions {
+    let mut cursor = cursor.min(sql.len());
+    while !sql.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+    let before = &sql[..cursor];
+    let prefix_len: usize = before
+        .chars()
+        .rev()
+        .take_while(|c| is_word_char(*c))
+        .map(char::len_utf8)
+        .sum();
+    let start = cursor - prefix_len;
+    let prefix = before[start..].to_string();
+
+    // Tokens of the current statement, split at the word being completed
+    let tokens = tokenize(sql);
+    let statement_start = tokens
+        .iter()
+        .rposition(|(at, t)| *at < start && *t == Token::Semicolon)
+        .map_or(0, |i| i + 1);
+    let statement_end = tokens
+        .iter()
+        .position(|(at, t)| *at >= cursor && *t == Token::Semicolon)
+        .unwrap_or(tokens.len());
+    let statement: Vec<Token> = tokens[statement_start..statement_end.max(statement_start)]
+        .iter()
+        .filter(|(at, _)| *at < start || *at >= cursor)
+        .map(|(_, t)| t.clone())
+        .collect();
+    let preceding: Vec<&Token> = tokens[statement_start..]
+        .iter()
+        .take_while(|(at, _)| *at < start)
+        .map(|(_, t)| t)
+        .collect();
+
+    let refs = table_refs(&statement);
+    let mut items = Vec::new();
+    let qualifier = match preceding.as_slice() {
+        [.., qualifier, Token::Dot] => qualifier.identifier().map(str::to_string),
+        _ => None,
+    };
+
+    if let Some(qualifier) = &qualifier {
+        // alias. → columns; table. → columns; schema. → tables and functions
+        let aliased = refs.iter().find(|r| {
+            r.alias.as_deref().is_some_and(|a| a.eq_ignore_ascii_case(qualifier))
+                || (r.alias.is_none() && r.name.eq_ignore_ascii_case(qualifier))
+        });
+        let tables = match aliased {
+            Some(r) => find_tables(schema, r.schema.as_deref(), &r.name),
+            None => find_tables(schema, None, qualifier),
+        };
+        for table in tables {
+            column_items(table, &mut items);
+        }
+        if items.is_empty() {
+            table_items(schema, Some(qualifier), &mut items);
+            function_items(schema, Some(qualifier), &mut items);
+        }
+    } else {
+        let clause = preceding
+            .iter()
+            .rev()
+            .find(|t| t.is_keyword(&["SELECT", "FROM", "JOIN", "WHERE", "ON", "SET", "BY", "HAVING", "UPDATE", "INTO", "TABLE"]));
+        let table_position = match preceding.last() {
+            Some(last) if last.is_keyword(TABLE_KEYWORDS) => true,
+            Some(Token::Comma) => clause.is_some_and(|c| c.is_keyword(&["FROM"])),
+            _ => false,
+        };
+
+        if table_position {
+            table_items(schema, None, &mut items);
+            for s in &schema.schemas {
+                items.push(SqlCompletion {
+                    label: s.name.clone(),
+                    kind: SqlCompletionKind::Schema,
+                    detail: "schema".to_string(),
+                });
+            }
+        } else {
+            for r in &refs {
+                for table in find_tables(schema, r.schema.as_deref(), &r.name) {
+                    column_items(table, &mut items);
+                }
+            }
+            if refs.is_empty() {
+                table_items(schema, None, &mut items);
+            }
+            function_items(schema, None, &mut items);
+            for keyword in KEYWORDS {
+                items.push(SqlCompletion {
+                    label: keyword.to_string(),
+                    kind: SqlCompletionKind::Keyword,
+                    detail: String::new(),
+                });
+            }
+        }
+    }
+
+    let lower = prefix.to_lowercase();
+    items.retain(|item| item.label.to_lowercase().starts_with(&lower) && item.label != prefix);
+    items.sort_by_key(|item| (item.kind, item.label.to_lowercase()));
+    items.dedup_by(|a, b| a.kind == b.kind && a.label == b.label);
+    items.truncate(MAX_COMPLETIONS);
+
+    SqlCompletions {
+        start,
+        prefix,
+        qualified: qualifier.is_some(),
+        items,
+    }
+}
+
+/// Byte offset of a UTF-16 offset (as used by DOM selection APIs)
+pub fn utf16_to_byte(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (byte, c) in text.char_indices() {
+        if units >= offset {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// UTF-16 offset of a byte offset
+pub fn byte_to_utf16(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tauri_bindings_database::{ColumnMeta, FunctionInfo, SchemaInfo};
+
+    fn table(schema: &str, name: &str, kind: TableKind, columns: &[&str]) -> TableInfo {
+        TableInfo {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            kind,
+            columns: columns
+                .iter()
+                .map(|c| ColumnMeta {
+                    name: c.to_string(),
+                    data_type: "text".to_string(),
+                    nullable: true,
+                    default: None,
+                    primary_key: false,
+                })
+                .collect(),
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    fn schema() -> DatabaseSchema {
+        DatabaseSchema {
+            schemas: vec![
+                SchemaInfo {
+                    name: "public".to_string(),
+                    tables: vec![
+                        table("public", "users", TableKind::Table, &["id", "email", "name"]),
+                        table("public", "orders", TableKind::Table, &["id", "user_id", "total"]),
+                        table("public", "user_stats", TableKind::View, &["user_id", "order_count"]),
+                    ],
+                    functions: vec![FunctionInfo {
+                        schema: "public".to_string(),
+                        name: "user_score".to_string(),
+                        arguments: "user_id integer".to_string(),
+                        return_type: "numeric".to_string(),
+                    }],
+                },
+                SchemaInfo {
+                    name: "audit".to_string(),
+                    tables: vec![table("audit", "events", TableKind::Table, &["at", "payload"])],
+                    functions: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    fn labels(sql: &str) -> Vec<String> {
+        let cursor = sql.find('|').unwrap();
+        let sql = sql.replace('|', "");
+        complete(&sql, cursor, &schema()).items.into_iter().map(|i| i.label).collect()
+    }
+
+    #[test]
+    fn test_tables_after_from_and_join() {
+        assert_eq!(labels("SELECT * FROM us|"), vec!["users", "user_stats"]);
+        assert_eq!(labels("select * from orders o join |").len(), 6);
+        assert_eq!(labels("SELECT * FROM orders, u|"), vec!["users", "user_stats"]);
+        assert_eq!(labels("SELECT * FROM a|"), vec!["audit"]);
+    }
+
+    #[test]
+    fn test_columns_after_alias() {
+        assert_eq!(
+            labels("SELECT u.| FROM users u JOIN orders o ON o.user_id = u.id"),
+            vec!["email", "id", "name"]
+        );
+        assert_eq!(labels("SELECT o.t| FROM users AS u, orders o"), vec!["total"]);
+        assert_eq!(labels("SELECT users.e| FROM users"), vec!["email"]);
+        assert_eq!(labels("SELECT * FROM audit.|"), vec!["events"]);
+        assert_eq!(labels("SELECT \"x\".| FROM audit.events \"x\""), vec!["at", "payload"]);
+    }
+
+    #[test]
+    fn test_columns_of_referenced_tables() {
+        let items = labels("SELECT * FROM orders WHERE us|");
+        assert_eq!(items, vec!["user_id", "user_score", "USING"]);
+        // Only the current statement counts
+        assert_eq!(labels("SELECT * FROM users; SELECT em| FROM orders"), Vec::<String>::new());
+        assert!(labels("SELECT * FROM orders WHERE |").contains(&"WHERE".to_string()));
+    }
+
+    #[test]
+    fn test_literals_and_comments_are_ignored() {
+        assert_eq!(labels("SELECT 'from' -- from x\n, e| FROM users"), vec!["email", "ELSE", "END", "EXISTS"]);
+    }
+
+    #[test]
+    fn test_replacement_range() {
+        let result = complete("SELECT na FROM users", 9, &schema());
+        assert_eq!(result.start, 7);
+        assert_eq!(result.prefix, "na");
+        assert!(!result.qualified);
+        assert_eq!(result.items[0].label, "name");
+    }
+
+    #[test]
+    fn test_utf16_offsets() {
+        let text = "SELECT '日本' AS x";
+        let byte = text.find("AS").unwrap();
+        let utf16 = byte_to_utf16(text, byte);
+        assert_eq!(utf16, 12);
+        assert_eq!(utf16_to_byte(text, utf16), byte);
+        assert_eq!(utf16_to_byte(text, 1000), text.len());
+    }
+}
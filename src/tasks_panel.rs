@@ -0,0 +1,391 @@
+//! Tasks Panel
+//!
+//! Lists auto-detected and user-defined tasks, runs them and shows their
+//! output. Problems found by the tasks' problem matchers are published through
+//! [`TaskRunner::diagnostics`] for the diagnostics panel.
+
+use crate::diagnostics_panel::FileDiagnostic;
+use crate::tauri_bindings_tasks::*;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+/// Polling interval while any task is running
+const POLL_INTERVAL_MS: u32 = 500;
+
+/// Task state shared between the tasks panel, keyboard shortcuts and the diagnostics panel
+#[derive(Clone, Copy)]
+pub struct TaskRunner {
+    pub tasks: RwSignal<Vec<TaskDefinition>>,
+    /// Newest first
+    pub runs: RwSignal<Vec<TaskRunInfo>>,
+    pub selected_run: RwSignal<Option<String>>,
+    /// Problems from the latest run of each task
+    pub diagnostics: RwSignal<Vec<FileDiagnostic>>,
+    polling: RwSignal<bool>,
+}
+
+impl TaskRunner {
+    pub fn new() -> Self {
+        Self {
+            tasks: RwSignal::new(Vec::new()),
+            runs: RwSignal::new(Vec::new()),
+            selected_run: RwSignal::new(None),
+            diagnostics: RwSignal::new(Vec::new()),
+            polling: RwSignal::new(false),
+        }
+    }
+
+    /// Reload task definitions for the project
+    pub fn refresh_tasks(&self, project_path: String) {
+        let tasks = self.tasks;
+        spawn_local(async move {
+            match task_list(project_path).await {
+                Ok(list) => tasks.set(list),
+                Err(e) => leptos::logging::error!("❌ Failed to load tasks: {}", e),
+            }
+        });
+    }
+
+    /// Run a task by label
+    pub fn run(&self, project_path: String, label: String) {
+        let runner = *self;
+        spawn_local(async move {
+            leptos::logging::log!("▶️ Running task: {}", label);
+            match task_run(project_path, label).await {
+                Ok(run_id) => {
+                    runner.selected_run.set(Some(run_id));
+                    runner.start_polling();
+                }
+                Err(e) => leptos::logging::error!("❌ Failed to run task: {}", e),
+            }
+        });
+    }
+
+    /// Run the default task of a group (keyboard shortcut), loading tasks first if needed
+    pub fn run_default(&self, project_path: String, group: TaskGroup) {
+        let runner = *self;
+        spawn_local(async move {
+            if runner.tasks.get_untracked().is_empty() {
+                if let Ok(list) = task_list(project_path.clone()).await {
+                    runner.tasks.set(list);
+                }
+            }
+
+            match default_task(&runner.tasks.get_untracked(), group) {
+                Some(label) => runner.run(project_path, label),
+                None => leptos::logging::warn!("⚠️ No {:?} task defined", group),
+            }
+        });
+    }
+
+    pub fn cancel(&self, run_id: String) {
+        spawn_local(async move {
+            if let Err(e) = task_cancel(run_id).await {
+                leptos::logging::error!("❌ Failed to cancel task: {}", e);
+            }
+        });
+    }
+
+    /// Poll run state until no task is running
+    fn start_polling(&self) {
+        if self.polling.get_untracked() {
+            return;
+        }
+        self.polling.set(true);
+
+        let runner = *self;
+        spawn_local(async move {
+            loop {
+                match task_list_runs().await {
+                    Ok(runs) => {
+                        let any_running = runs.iter().any(|r| r.status == TaskStatus::Running);
+                        runner.diagnostics.set(latest_diagnostics(&runs));
+                        runner.runs.set(runs);
+                        if !any_running {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        leptos::logging::error!("❌ Failed to poll task runs: {}", e);
+                        break;
+                    }
+                }
+                gloo_timers::future::TimeoutFuture::new(POLL_INTERVAL_MS).await;
+            }
+            runner.polling.set(false);
+        });
+    }
+}
+
+impl Default for TaskRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Label of the default task in a group (explicit default, else the first one)
+pub fn default_task(tasks: &[TaskDefinition], group: TaskGroup) -> Option<String> {
+    tasks
+        .iter()
+        .filter(|t| t.group == group)
+        .find(|t| t.is_default)
+        .or_else(|| tasks.iter().find(|t| t.group == group))
+        .map(|t| t.label.clone())
+}
+
+/// Diagnostics of the newest run of each task (runs are newest first)
+pub fn latest_diagnostics(runs: &[TaskRunInfo]) -> Vec<FileDiagnostic> {
+    let mut seen = Vec::new();
+    let mut diagnostics = Vec::new();
+    for run in runs {
+        if seen.contains(&run.label) {
+            continue;
+        }
+        seen.push(run.label.clone());
+        diagnostics.extend(run.diagnostics.iter().cloned().map(FileDiagnostic::from));
+    }
+    diagnostics
+}
+
+fn status_icon(status: TaskStatus) -> (&'static str, &'static str) {
+    match status {
+        TaskStatus::Running => ("codicon-loading codicon-modifier-spin", "#cccccc"),
+        TaskStatus::Succeeded => ("codicon-check", "#4ec9b0"),
+        TaskStatus::Failed => ("codicon-error", "#f14c4c"),
+        TaskStatus::Cancelled => ("codicon-circle-slash", "#858585"),
+    }
+}
+
+#[component]
+pub fn TasksPanel(
+    /// Project root path
+    #[prop(into)]
+    project_path: Signal<String>,
+    runner: TaskRunner,
+) -> impl IntoView {
+    // Load tasks when the project changes
+    Effect::new(move |_| {
+        let path = project_path.get();
+        if !path.is_empty() {
+            runner.refresh_tasks(path);
+        }
+    });
+
+    let selected_output = move || {
+        let selected = runner.selected_run.get()?;
+        runner.runs.get().into_iter().find(|r| r.run_id == selected)
+    };
+
+    view! {
+        <div class="berry-editor-sidebar tasks-panel" style="background: #252526; display: flex; flex-direction: column;">
+            <div class="berry-editor-sidebar-header" style="
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                padding: 8px 12px;
+                background: #2D2D30;
+                border-bottom: 1px solid #1e1e1e;
+                font-size: 12px;
+                font-weight: 600;
+                color: #cccccc;
+            ">
+                <span>"TASKS"</span>
+                <i
+                    class="codicon codicon-refresh"
+                    title="Reload tasks"
+                    style="cursor: pointer;"
+                    on:click=move |_| runner.refresh_tasks(project_path.get_untracked())
+                ></i>
+            </div>
+
+            <div class="tasks-list" style="overflow-y: auto; padding: 4px 0; flex: 1;">
+                {move || {
+                    let tasks = runner.tasks.get();
+                    if tasks.is_empty() {
+                        return view! {
+                            <div style="padding: 16px; color: #858585; font-size: 12px;">
+                                "No tasks found. Add a Cargo.toml, package.json, Makefile or justfile, or define tasks in .berry/tasks.json."
+                            </div>
+                        }.into_any();
+                    }
+
+                    let runs = runner.runs.get();
+                    tasks.into_iter().map(|task| {
+                        let running_run = runs
+                            .iter()
+                            .find(|r| r.label == task.label && r.status == TaskStatus::Running)
+                            .map(|r| r.run_id.clone());
+                        let label = task.label.clone();
+                        let details = match task.group {
+                            TaskGroup::None => task.source.clone(),
+                            group => format!("{} · {:?}", task.source, group).to_lowercase(),
+                        };
+
+                        view! {
+                            <div
+                                class="tasks-item"
+                                style="display: flex; align-items: center; gap: 6px; padding: 4px 12px; font-size: 13px; color: #cccccc;"
+                            >
+                                {match running_run {
+                                    Some(run_id) => view! {
+                                        <i
+                                            class="codicon codicon-debug-stop"
+                                            title="Stop task"
+                                            style="cursor: pointer; color: #f14c4c;"
+                                            on:click=move |_| runner.cancel(run_id.clone())
+                                        ></i>
+                                    }.into_any(),
+                                    None => view! {
+                                        <i
+                                            class="codicon codicon-play"
+                                            title="Run task"
+                                            style="cursor: pointer; color: #89d185;"
+                                            on:click=move |_| runner.run(project_path.get_untracked(), label.clone())
+                                        ></i>
+                                    }.into_any(),
+                                }}
+                                <span style="flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                    {task.label.clone()}
+                                </span>
+                                {task.is_background.then(|| view! {
+                                    <i class="codicon codicon-eye" title="Background task" style="color: #858585;"></i>
+                                })}
+                                {task.is_default.then(|| view! {
+                                    <i class="codicon codicon-star-full" title="Default task of its group" style="color: #858585;"></i>
+                                })}
+                                <span style="font-size: 11px; color: #858585;">{details}</span>
+                            </div>
+                        }
+                    }).collect_view().into_any()
+                }}
+
+                {move || {
+                    let runs = runner.runs.get();
+                    (!runs.is_empty()).then(|| view! {
+                        <div style="padding: 8px 12px 4px; font-size: 11px; font-weight: 600; color: #858585;">
+                            "RECENT RUNS"
+                        </div>
+                        {runs.into_iter().map(|run| {
+                            let (icon, color) = status_icon(run.status);
+                            let run_id = run.run_id.clone();
+                            let is_selected = runner.selected_run.get().as_ref() == Some(&run.run_id);
+                            let problems = run.diagnostics.len();
+
+                            view! {
+                                <div
+                                    class="tasks-run"
+                                    style=format!(
+                                        "display: flex; align-items: center; gap: 6px; padding: 3px 12px; font-size: 12px; cursor: pointer; color: #cccccc; background: {};",
+                                        if is_selected { "#37373d" } else { "transparent" }
+                                    )
+                                    on:click=move |_| runner.selected_run.set(Some(run_id.clone()))
+                                >
+                                    <i class=format!("codicon {}", icon) style=format!("color: {};", color)></i>
+                                    <span style="flex: 1;">{run.label.clone()}</span>
+                                    {(problems > 0).then(|| view! {
+                                        <span style="color: #f14c4c;">{format!("{} problems", problems)}</span>
+                                    })}
+                                    {run.exit_code.map(|code| view! {
+                                        <span style="color: #858585;">{format!("exit {}", code)}</span>
+                                    })}
+                                </div>
+                            }
+                        }).collect_view()}
+                    })
+                }}
+            </div>
+
+            {move || selected_output().map(|run| view! {
+                <pre class="tasks-output" style="
+                    margin: 0;
+                    max-height: 40%;
+                    overflow: auto;
+                    padding: 8px 12px;
+                    background: #1e1e1e;
+                    border-top: 1px solid #3e3e42;
+                    color: #d4d4d4;
+                    font-size: 12px;
+                    white-space: pre-wrap;
+                ">
+                    {run.output}
+                </pre>
+            })}
+
+            <div style="padding: 6px 12px; font-size: 11px; color: #858585; border-top: 1px solid #1e1e1e;">
+                "Ctrl+Shift+B: build task · Ctrl+Shift+T: test task"
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn task(label: &str, group: TaskGroup, is_default: bool) -> TaskDefinition {
+        TaskDefinition {
+            label: label.to_string(),
+            command: "true".to_string(),
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            group,
+            is_default,
+            depends_on: Vec::new(),
+            is_background: false,
+            problem_matcher: None,
+            source: "user".to_string(),
+        }
+    }
+
+    fn run(label: &str, file: &str) -> TaskRunInfo {
+        TaskRunInfo {
+            run_id: format!("{}-{}", label, file),
+            label: label.to_string(),
+            status: TaskStatus::Failed,
+            exit_code: Some(1),
+            output: String::new(),
+            diagnostics: vec![TaskDiagnostic {
+                file: file.to_string(),
+                line: 3,
+                column: 2,
+                end_line: None,
+                end_column: None,
+                severity: "error".to_string(),
+                message: "oops".to_string(),
+                code: Some("E1".to_string()),
+                source: "tsc".to_string(),
+            }],
+            is_background: false,
+            started_at: 0,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn test_default_task() {
+        let tasks = vec![
+            task("lint", TaskGroup::None, false),
+            task("cargo check", TaskGroup::Build, false),
+            task("cargo build", TaskGroup::Build, true),
+            task("unit", TaskGroup::Test, false),
+        ];
+        assert_eq!(default_task(&tasks, TaskGroup::Build).as_deref(), Some("cargo build"));
+        assert_eq!(default_task(&tasks, TaskGroup::Test).as_deref(), Some("unit"));
+        assert_eq!(default_task(&tasks, TaskGroup::Run), None);
+    }
+
+    #[test]
+    fn test_latest_diagnostics_per_task() {
+        // Newest first: the older "build" run's problems are stale
+        let runs = vec![run("build", "new.ts"), run("lint", "a.js"), run("build", "old.ts")];
+        let files: Vec<String> = latest_diagnostics(&runs).into_iter().map(|d| d.file).collect();
+        assert_eq!(files, vec!["new.ts", "a.js"]);
+
+        let diagnostic = &latest_diagnostics(&runs)[0].diagnostic;
+        assert_eq!(diagnostic.range.start.line, 2);
+        assert_eq!(diagnostic.range.start.character, 1);
+        assert_eq!(diagnostic.message, "oops [E1]");
+    }
+}
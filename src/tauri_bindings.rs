@@ -14,6 +14,98 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
 }
 
+/// One explorer entry, listed lazily per folder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TreeEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    /// Matched by `.gitignore`; shown greyed out
+    pub ignored: bool,
+    /// Git status letter; for folders, the most significant status below it
+    pub git_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LineEnding {
+    #[serde(rename = "LF")]
+    Lf,
+    #[serde(rename = "CRLF")]
+    Crlf,
+}
+
+impl LineEnding {
+    /// The line ending used by most lines; LF when there are none
+    pub fn detect(text: &str) -> Self {
+        let lines = text.matches('\n').count();
+        let crlf = text.matches("\r\n").count();
+        if lines > 0 && crlf * 2 > lines {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+        }
+    }
+}
+
+/// How a text file is stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextFormat {
+    /// encoding_rs name, e.g. "UTF-8", "UTF-16LE", "Shift_JIS", "windows-1252"
+    pub encoding: String,
+    pub bom: bool,
+    pub line_ending: LineEnding,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        Self {
+            encoding: "UTF-8".to_string(),
+            bom: false,
+            line_ending: LineEnding::Lf,
+        }
+    }
+}
+
+impl TextFormat {
+    /// Encodings offered when switching; (encoding_rs name, label)
+    pub const ENCODINGS: &'static [(&'static str, &'static str)] = &[
+        ("UTF-8", "UTF-8"),
+        ("UTF-16LE", "UTF-16 LE"),
+        ("UTF-16BE", "UTF-16 BE"),
+        ("windows-1252", "Western (Windows 1252 / Latin-1)"),
+        ("ISO-8859-15", "Western (ISO 8859-15)"),
+        ("Shift_JIS", "Japanese (Shift JIS)"),
+        ("EUC-JP", "Japanese (EUC-JP)"),
+        ("GBK", "Simplified Chinese (GBK)"),
+        ("Big5", "Traditional Chinese (Big5)"),
+        ("EUC-KR", "Korean (EUC-KR)"),
+    ];
+
+    /// Status bar text, e.g. "UTF-8 with BOM"
+    pub fn encoding_label(&self) -> String {
+        if self.bom {
+            format!("{} with BOM", self.encoding)
+        } else {
+            self.encoding.clone()
+        }
+    }
+}
+
+/// A file opened in the editor; text always uses `\n`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FileContents {
+    Text { content: String, format: TextFormat },
+    Binary { size: u64 },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub size: u64,
@@ -254,6 +346,84 @@ pub async fn write_file(_path: &str, _contents: &str) -> Result<(), String> {
     Err("write_file only available in WASM context".to_string())
 }
 
+/// Read a file with its on-disk format; `encoding` overrides detection
+#[cfg(target_arch = "wasm32")]
+pub async fn read_text_file(path: &str, encoding: Option<&str>) -> Result<FileContents, String> {
+    if !is_tauri_context() {
+        return Err("read_text_file requires Tauri".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "path": path,
+        "encoding": encoding
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("read_text_file", args)
+        .await
+        .map_err(|e| format!("Failed to read file: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn read_text_file(_path: &str, _encoding: Option<&str>) -> Result<FileContents, String> {
+    Err("read_text_file only available in WASM context".to_string())
+}
+
+/// Save editor text in the given format (atomic on the backend)
+#[cfg(target_arch = "wasm32")]
+pub async fn write_text_file(path: &str, contents: &str, format: &TextFormat) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "path": path,
+        "contents": contents,
+        "format": format
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    tauri_invoke("write_text_file", args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("Failed to write file: {:?}", e)))?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn write_text_file(_path: &str, _contents: &str, _format: &TextFormat) -> Result<(), String> {
+    Err("write_text_file only available in WASM context".to_string())
+}
+
+/// Raw bytes of a file range
+#[cfg(target_arch = "wasm32")]
+pub async fn read_file_bytes(path: &str, offset: u64, length: usize) -> Result<Vec<u8>, String> {
+    if !is_tauri_context() {
+        return Ok(Vec::new());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "path": path,
+        "offset": offset,
+        "length": length
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("read_file_bytes", args)
+        .await
+        .map_err(|e| format!("Failed to read bytes: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn read_file_bytes(_path: &str, _offset: u64, _length: usize) -> Result<Vec<u8>, String> {
+    Err("read_file_bytes only available in WASM context".to_string())
+}
+
 /// Read directory contents
 #[cfg(target_arch = "wasm32")]
 pub async fn read_dir(path: &str, max_depth: Option<usize>) -> Result<Vec<FileNode>, String> {
@@ -281,6 +451,60 @@ pub async fn read_dir(_path: &str, _max_depth: Option<usize>) -> Result<Vec<File
     Err("read_dir only available in WASM context".to_string())
 }
 
+/// List the direct children of one folder of the explorer tree
+#[cfg(target_arch = "wasm32")]
+pub async fn list_dir(root: &str, path: &str, exclude: &[String]) -> Result<Vec<TreeEntry>, String> {
+    if !is_tauri_context() {
+        return Ok(Vec::new());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "root": root,
+        "path": path,
+        "exclude": exclude
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("list_dir", args)
+        .await
+        .map_err(|e| format!("Failed to list directory: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn list_dir(_root: &str, _path: &str, _exclude: &[String]) -> Result<Vec<TreeEntry>, String> {
+    Err("list_dir only available in WASM context".to_string())
+}
+
+/// Re-read individual explorer entries; missing or excluded paths are left out
+#[cfg(target_arch = "wasm32")]
+pub async fn stat_entries(root: &str, paths: &[String], exclude: &[String]) -> Result<Vec<TreeEntry>, String> {
+    if !is_tauri_context() {
+        return Ok(Vec::new());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "root": root,
+        "paths": paths,
+        "exclude": exclude
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("stat_entries", args)
+        .await
+        .map_err(|e| format!("Failed to read entries: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn stat_entries(_root: &str, _paths: &[String], _exclude: &[String]) -> Result<Vec<TreeEntry>, String> {
+    Err("stat_entries only available in WASM context".to_string())
+}
+
 /// Create a new file
 #[cfg(target_arch = "wasm32")]
 pub async fn create_file(path: &str, contents: Option<String>) -> Result<(), String> {
@@ -18,12 +18,27 @@ pub struct DbConnection {
     pub port: Option<u16>,
     pub database: String,
     pub username: Option<String>,
+    /// Only sent when entered; saved passwords never leave the backend
     pub password: Option<String>,
+    #[serde(default)]
+    pub password_source: PasswordSource,
     pub ssl: bool,
     pub created_at: i64,
     pub last_used: Option<i64>,
 }
 
+/// Where a connection's password comes from
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PasswordSource {
+    /// Encrypted in the credential store
+    #[default]
+    Stored,
+    Env { variable: String },
+    /// `.pgpass`-style file; `PGPASSFILE` or `~/.pgpass` when unset
+    PgPass { path: Option<String> },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionTestResult {
     pub success: bool,
@@ -53,67 +68,538 @@ pub async fn db_list_connections() -> Result<Vec<DbConnection>, String> {
 }
 
 pub async fn db_add_connection(connection: DbConnection) -> Result<(), String> {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let args = serde_wasm_bindgen::to_value(&connection)
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
-        tauri_invoke("db_add_connection", args)
-            .await
-            .map_err(|e| format!("Failed to add DB connection: {:?}", e))?;
-        Ok(())
-    }
-    #[cfg(not(target_arch = "wasm32"))]
-    Ok(())
+    invoke_db("db_add_connection", &ConnectionArgs { connection }).await
 }
 
 pub async fn db_update_connection(connection: DbConnection) -> Result<(), String> {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let args = serde_wasm_bindgen::to_value(&connection)
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
-        tauri_invoke("db_update_connection", args)
-            .await
-            .map_err(|e| format!("Failed to update DB connection: {:?}", e))?;
-        Ok(())
-    }
-    #[cfg(not(target_arch = "wasm32"))]
-    Ok(())
+    invoke_db("db_update_connection", &ConnectionArgs { connection }).await
 }
 
 pub async fn db_delete_connection(connection_id: String) -> Result<(), String> {
-    #[cfg(target_arch = "wasm32")]
-    {
-        #[derive(Serialize)]
-        struct Args {
-            connection_id: String,
+    invoke_db("db_delete_connection", &ConnectionIdArgs { connection_id }).await
+}
+
+pub async fn db_test_connection(connection: DbConnection) -> Result<ConnectionTestResult, String> {
+    invoke_db("db_test_connection", &ConnectionArgs { connection }).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// Rows are in column order; `cursor` is set while more rows can be fetched
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryResult {
+    #[serde(default)]
+    pub columns: Vec<ColumnInfo>,
+    #[serde(default)]
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub affected_rows: Option<u64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Sql,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Sql];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Sql => "SQL",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Sql => "sql",
         }
-        let args = serde_wasm_bindgen::to_value(&Args { connection_id })
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
-        tauri_invoke("db_delete_connection", args)
-            .await
-            .map_err(|e| format!("Failed to delete DB connection: {:?}", e))?;
-        Ok(())
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    Ok(())
 }
 
-pub async fn db_test_connection(connection: DbConnection) -> Result<ConnectionTestResult, String> {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let args = serde_wasm_bindgen::to_value(&connection)
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
-        let result = tauri_invoke("db_test_connection", args)
-            .await
-            .map_err(|e| format!("Failed to test DB connection: {:?}", e))?;
-        serde_wasm_bindgen::from_value(result)
-            .map_err(|e| format!("Failed to deserialize: {}", e))
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub connection_id: String,
+    pub connected: bool,
+    pub in_transaction: bool,
+    pub running: bool,
+    pub idle_sessions: usize,
+}
+
+#[derive(Serialize)]
+struct NoArgs {}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionArgs {
+    connection: DbConnection,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionIdArgs {
+    connection_id: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn invoke_db<A: Serialize, R: serde::de::DeserializeOwned>(cmd: &str, args: &A) -> Result<R, String> {
+    let args = serde_wasm_bindgen::to_value(args).map_err(|e| format!("Failed to serialize: {}", e))?;
+    let result = tauri_invoke(cmd, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn invoke_db<A: Serialize, R: serde::de::DeserializeOwned>(_cmd: &str, _args: &A) -> Result<R, String> {
+    Err("Not in WASM environment".to_string())
+}
+
+/// Run a query on the connection's persistent session (cancelled after `timeout_ms`)
+/// and return its first `page_size` rows
+pub async fn db_execute_query(
+    connection: DbConnection,
+    query: String,
+    timeout_ms: Option<u64>,
+    page_size: Option<usize>,
+) -> Result<QueryResult, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        query: String,
+        timeout_ms: Option<u64>,
+        page_size: Option<usize>,
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    Ok(ConnectionTestResult {
-        success: false,
-        message: "Not in WASM environment".to_string(),
-        latency_ms: None,
-        server_version: None,
-    })
+    invoke_db(
+        "db_execute_query",
+        &Args {
+            connection,
+            query,
+            timeout_ms,
+            page_size,
+        },
+    )
+    .await
+}
+
+/// Next page of a result opened by `db_execute_query`
+pub async fn db_fetch_rows(
+    connection_id: String,
+    cursor: String,
+    count: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> Result<QueryResult, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection_id: String,
+        cursor: String,
+        count: Option<usize>,
+        timeout_ms: Option<u64>,
+    }
+    invoke_db(
+        "db_fetch_rows",
+        &Args {
+            connection_id,
+            cursor,
+            count,
+            timeout_ms,
+        },
+    )
+    .await
+}
+
+pub async fn db_close_cursor(connection_id: String, cursor: String) -> Result<(), String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection_id: String,
+        cursor: String,
+    }
+    invoke_db("db_close_cursor", &Args { connection_id, cursor }).await
+}
+
+/// Write every row of a query to `path`; returns the number of rows written
+pub async fn db_export_query(
+    connection: DbConnection,
+    query: String,
+    format: ExportFormat,
+    path: String,
+    table_name: Option<String>,
+) -> Result<usize, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        query: String,
+        format: ExportFormat,
+        path: String,
+        table_name: Option<String>,
+    }
+    invoke_db(
+        "db_export_query",
+        &Args {
+            connection,
+            query,
+            format,
+            path,
+            table_name,
+        },
+    )
+    .await
+}
+
+pub async fn db_begin_transaction(connection: DbConnection) -> Result<(), String> {
+    invoke_db("db_begin_transaction", &ConnectionArgs { connection }).await
+}
+
+pub async fn db_commit_transaction(connection_id: String) -> Result<(), String> {
+    invoke_db("db_commit_transaction", &ConnectionIdArgs { connection_id }).await
+}
+
+pub async fn db_rollback_transaction(connection_id: String) -> Result<(), String> {
+    invoke_db("db_rollback_transaction", &ConnectionIdArgs { connection_id }).await
+}
+
+/// Cancel the running statement; false when nothing was running
+pub async fn db_cancel_query(connection_id: String) -> Result<bool, String> {
+    invoke_db("db_cancel_query", &ConnectionIdArgs { connection_id }).await
+}
+
+pub async fn db_session_status(connection_id: String) -> Result<SessionStatus, String> {
+    invoke_db("db_session_status", &ConnectionIdArgs { connection_id }).await
+}
+
+pub async fn db_health_check(connection: DbConnection) -> Result<ConnectionTestResult, String> {
+    invoke_db("db_health_check", &ConnectionArgs { connection }).await
+}
+
+pub async fn db_close_session(connection_id: String) -> Result<(), String> {
+    invoke_db("db_close_session", &ConnectionIdArgs { connection_id }).await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseSchema {
+    pub schemas: Vec<SchemaInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    pub name: String,
+    pub tables: Vec<TableInfo>,
+    pub functions: Vec<FunctionInfo>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableKind {
+    Table,
+    View,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableInfo {
+    pub schema: String,
+    pub name: String,
+    pub kind: TableKind,
+    pub columns: Vec<ColumnMeta>,
+    pub indexes: Vec<IndexInfo>,
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMeta {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub primary_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyInfo {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionInfo {
+    pub schema: String,
+    pub name: String,
+    pub arguments: String,
+    pub return_type: String,
+}
+
+/// Schema metadata (cached by the backend until `refresh` or a DDL statement)
+pub async fn db_get_schema(connection: DbConnection, refresh: bool) -> Result<DatabaseSchema, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        refresh: bool,
+    }
+    invoke_db("db_get_schema", &Args { connection, refresh }).await
+}
+
+/// Result of a MongoDB shell command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MongoResult {
+    /// Documents as relaxed Extended JSON
+    pub documents: Vec<serde_json::Value>,
+    pub message: Option<String>,
+    /// A write that must be confirmed before it runs; `message` describes it
+    pub needs_confirmation: bool,
+    /// More documents matched than were returned
+    pub truncated: bool,
+}
+
+pub async fn db_mongo_collections(connection: DbConnection) -> Result<Vec<String>, String> {
+    invoke_db("db_mongo_collections", &ConnectionArgs { connection }).await
+}
+
+/// Run a shell command such as `db.users.find({})`
+pub async fn db_mongo_execute(
+    connection: DbConnection,
+    command: String,
+    confirmed: bool,
+) -> Result<MongoResult, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        command: String,
+        confirmed: bool,
+    }
+    invoke_db(
+        "db_mongo_execute",
+        &Args {
+            connection,
+            command,
+            confirmed,
+        },
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultStatus {
+    pub initialized: bool,
+    pub unlocked: bool,
+    pub stored_secrets: usize,
+    /// Passwords still in plain text from older versions
+    pub plaintext_secrets: usize,
+}
+
+pub async fn db_vault_status() -> Result<VaultStatus, String> {
+    invoke_db("db_vault_status", &NoArgs {}).await
+}
+
+/// Unlock (or set up) the credential store with a passphrase or key file
+pub async fn db_unlock_vault(passphrase: Option<String>, key_file: Option<String>) -> Result<VaultStatus, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        passphrase: Option<String>,
+        key_file: Option<String>,
+    }
+    invoke_db("db_unlock_vault", &Args { passphrase, key_file }).await
+}
+
+pub async fn db_lock_vault() -> Result<(), String> {
+    invoke_db("db_lock_vault", &NoArgs {}).await
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PathArgs {
+    path: String,
+}
+
+/// Export saved connections without passwords; returns the number written
+pub async fn db_export_connections(path: String) -> Result<usize, String> {
+    invoke_db("db_export_connections", &PathArgs { path }).await
+}
+
+/// Import connections from an exported file; returns the number added
+pub async fn db_import_connections(path: String) -> Result<usize, String> {
+    invoke_db("db_import_connections", &PathArgs { path }).await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanNode {
+    pub label: String,
+    pub detail: Option<String>,
+    pub startup_cost: Option<f64>,
+    pub total_cost: Option<f64>,
+    pub estimated_rows: Option<f64>,
+    pub actual_rows: Option<f64>,
+    pub actual_time_ms: Option<f64>,
+    pub loops: Option<u64>,
+    /// Share of the plan's exclusive time or cost (0..1)
+    pub weight: f64,
+    #[serde(default)]
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub nodes: Vec<PlanNode>,
+    pub planning_time_ms: Option<f64>,
+    pub execution_time_ms: Option<f64>,
+    pub analyzed: bool,
+    pub raw: String,
+}
+
+/// EXPLAIN a query; `analyze` runs it on PostgreSQL for actual timings
+pub async fn db_explain_query(connection: DbConnection, query: String, analyze: bool) -> Result<QueryPlan, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        query: String,
+        analyze: bool,
+    }
+    invoke_db(
+        "db_explain_query",
+        &Args {
+            connection,
+            query,
+            analyze,
+        },
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnValue {
+    pub column: String,
+    pub value: serde_json::Value,
+}
+
+/// A pending edit of one row; `key` holds the primary key as loaded
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RowChange {
+    Insert { values: Vec<ColumnValue> },
+    Update { key: Vec<ColumnValue>, values: Vec<ColumnValue> },
+    Delete { key: Vec<ColumnValue> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableEdit {
+    pub schema: String,
+    pub table: String,
+    pub changes: Vec<RowChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TableEditArgs {
+    connection: DbConnection,
+    edit: TableEdit,
+}
+
+/// First page of a table's rows, ordered by primary key
+pub async fn db_open_table(
+    connection: DbConnection,
+    schema: String,
+    table: String,
+    page_size: Option<usize>,
+) -> Result<QueryResult, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection: DbConnection,
+        schema: String,
+        table: String,
+        page_size: Option<usize>,
+    }
+    invoke_db(
+        "db_open_table",
+        &Args {
+            connection,
+            schema,
+            table,
+            page_size,
+        },
+    )
+    .await
+}
+
+/// SQL the backend would run for `edit`
+pub async fn db_preview_changes(connection: DbConnection, edit: TableEdit) -> Result<Vec<String>, String> {
+    invoke_db("db_preview_changes", &TableEditArgs { connection, edit }).await
+}
+
+/// Apply `edit` in one transaction; returns the number of affected rows
+pub async fn db_apply_changes(connection: DbConnection, edit: TableEdit) -> Result<u64, String> {
+    invoke_db("db_apply_changes", &TableEditArgs { connection, edit }).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryHistoryEntry {
+    pub id: i64,
+    pub connection_id: String,
+    pub connection_name: String,
+    pub query: String,
+    /// RFC 3339 timestamp
+    pub executed_at: String,
+    pub duration_ms: i64,
+    pub row_count: Option<i64>,
+    pub more_rows: bool,
+    pub affected_rows: Option<i64>,
+    pub error_message: Option<String>,
+}
+
+/// Executed queries, newest first; `search` matches query text or connection name
+pub async fn db_query_history(
+    search: Option<String>,
+    connection_id: Option<String>,
+) -> Result<Vec<QueryHistoryEntry>, String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        search: Option<String>,
+        connection_id: Option<String>,
+    }
+    invoke_db("db_query_history", &Args { search, connection_id }).await
+}
+
+pub async fn db_delete_history_entry(id: i64) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Args {
+        id: i64,
+    }
+    invoke_db("db_delete_history_entry", &Args { id }).await
+}
+
+pub async fn db_clear_query_history(connection_id: Option<String>) -> Result<(), String> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Args {
+        connection_id: Option<String>,
+    }
+    invoke_db("db_clear_query_history", &Args { connection_id }).await
 }
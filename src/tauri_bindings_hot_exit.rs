@@ -0,0 +1,147 @@
+//! Tauri bindings for hot exit: session layout and unsaved buffer backups
+
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+use crate::core::editor_groups::{EditorLayout, GroupId};
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Tab paths starting with this prefix have no file on disk yet
+pub const UNTITLED_PREFIX: &str = "untitled:";
+
+/// An open editor tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub path: String,
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub scroll_top: f64,
+}
+
+/// Window layout and open tabs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub root_path: String,
+    /// Workspace file of a multi-root workspace
+    pub workspace_file: Option<String>,
+    /// Open folders when there is no workspace file
+    pub folders: Vec<String>,
+    pub active_panel: String,
+    pub sidebar_width: f64,
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: Option<usize>,
+    /// Split panes; `None` in sessions saved before editor groups
+    pub editor_groups: Option<SessionGroups>,
+}
+
+/// Split layout of the editor area
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionGroups {
+    pub layout: EditorLayout,
+    pub groups: Vec<SessionGroup>,
+    pub focused: GroupId,
+}
+
+/// Tabs of one editor group, with the cursor and scroll position in that group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionGroup {
+    pub id: GroupId,
+    pub tabs: Vec<SessionTab>,
+    pub active: Option<String>,
+}
+
+/// Unsaved contents of a tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub path: String,
+    /// Text the edits started from; `None` for untitled tabs
+    pub base: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoredBackup {
+    #[serde(flatten)]
+    pub backup: Backup,
+    /// Current file content when the file changed on disk since `base`
+    pub disk: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestoredSession {
+    pub session: Session,
+    pub backups: Vec<RestoredBackup>,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn invoke(cmd: &str, args: serde_json::Value) -> Result<JsValue, String> {
+    let args = serde_wasm_bindgen::to_value(&args).map_err(|e| format!("Failed to serialize args: {}", e))?;
+    tauri_invoke(cmd, args).await.map_err(|e| format!("{:?}", e))
+}
+
+/// The previous session and unsaved buffers, or `None` on the first start
+#[cfg(target_arch = "wasm32")]
+pub async fn restore_session() -> Result<Option<RestoredSession>, String> {
+    if !is_tauri_context() {
+        return Ok(None);
+    }
+    let result = invoke("restore_session", serde_json::json!({})).await?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn restore_session() -> Result<Option<RestoredSession>, String> {
+    Ok(None)
+}
+
+/// Save the layout and open tabs (backups of closed tabs are dropped)
+#[cfg(target_arch = "wasm32")]
+pub async fn save_session(session: &Session) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+    invoke("save_session", serde_json::json!({ "session": session })).await?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn save_session(_session: &Session) -> Result<(), String> {
+    Err("Hot exit only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn backup_buffer(backup: &Backup) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+    invoke("backup_buffer", serde_json::json!({ "backup": backup })).await?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn backup_buffer(_backup: &Backup) -> Result<(), String> {
+    Err("Hot exit only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn discard_backup(path: &str) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+    invoke("discard_backup", serde_json::json!({ "path": path })).await?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn discard_backup(_path: &str) -> Result<(), String> {
+    Err("Hot exit only available in WASM context".to_string())
+}
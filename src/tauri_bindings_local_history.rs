@@ -0,0 +1,184 @@
+//! Tauri bindings for local history: recorded revisions of project files
+
+use crate::git::Hunk;
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// What produced a revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionSource {
+    Save,
+    External,
+    Agent,
+    Restore,
+    Checkpoint,
+}
+
+impl RevisionSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            RevisionSource::Save => "Saved",
+            RevisionSource::External => "Changed on disk",
+            RevisionSource::Agent => "Agent edit",
+            RevisionSource::Restore => "Restored",
+            RevisionSource::Checkpoint => "Checkpoint",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: String,
+    pub path: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: i64,
+    pub source: RevisionSource,
+    pub size: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+/// History of a file or folder, newest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryListing {
+    pub revisions: Vec<Revision>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// A revision compared with the current file content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevisionDiff {
+    pub revision: Revision,
+    pub hunks: Vec<Hunk>,
+    pub binary: bool,
+    pub deleted: bool,
+}
+
+/// A row of the history list
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryEntry {
+    Revision(Revision),
+    Checkpoint(Checkpoint),
+}
+
+impl HistoryListing {
+    /// Revisions and checkpoints merged by time, newest first
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let mut entries: Vec<(i64, HistoryEntry)> = self
+            .revisions
+            .iter()
+            .map(|r| (r.timestamp, HistoryEntry::Revision(r.clone())))
+            .chain(
+                self.checkpoints
+                    .iter()
+                    .map(|c| (c.timestamp, HistoryEntry::Checkpoint(c.clone()))),
+            )
+            .collect();
+        entries.sort_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn invoke<T: serde::de::DeserializeOwned>(cmd: &str, args: serde_json::Value) -> Result<T, String> {
+    if !is_tauri_context() {
+        return Err("Not running in Tauri context".to_string());
+    }
+    let args = serde_wasm_bindgen::to_value(&args).map_err(|e| format!("Failed to serialize args: {}", e))?;
+    let result = tauri_invoke(cmd, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+/// Revisions of a file, or of every file below a folder, in the project `root`
+#[cfg(target_arch = "wasm32")]
+pub async fn local_history_list(root: &str, path: &str) -> Result<HistoryListing, String> {
+    invoke("local_history_list", serde_json::json!({ "root": root, "path": path })).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn local_history_list(_root: &str, _path: &str) -> Result<HistoryListing, String> {
+    Err("Local history only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn local_history_diff(root: &str, revision_id: &str) -> Result<RevisionDiff, String> {
+    invoke("local_history_diff", serde_json::json!({ "root": root, "revisionId": revision_id })).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn local_history_diff(_root: &str, _revision_id: &str) -> Result<RevisionDiff, String> {
+    Err("Local history only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn local_history_restore(root: &str, revision_id: &str) -> Result<Revision, String> {
+    invoke("local_history_restore", serde_json::json!({ "root": root, "revisionId": revision_id })).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn local_history_restore(_root: &str, _revision_id: &str) -> Result<Revision, String> {
+    Err("Local history only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn local_history_put_label(root: &str, label: &str) -> Result<Checkpoint, String> {
+    invoke("local_history_put_label", serde_json::json!({ "root": root, "label": label })).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn local_history_put_label(_root: &str, _label: &str) -> Result<Checkpoint, String> {
+    Err("Local history only available in WASM context".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_merge_checkpoints_by_time() {
+        let revision = |id: &str, timestamp| Revision {
+            id: id.to_string(),
+            path: "/p/a.rs".to_string(),
+            timestamp,
+            source: RevisionSource::Save,
+            size: 1,
+            hash: id.to_string(),
+        };
+        let listing = HistoryListing {
+            revisions: vec![revision("new", 30), revision("old", 10)],
+            checkpoints: vec![Checkpoint {
+                id: "c".to_string(),
+                label: "Before agent edit".to_string(),
+                timestamp: 20,
+            }],
+        };
+        let ids: Vec<_> = listing
+            .entries()
+            .into_iter()
+            .map(|entry| match entry {
+                HistoryEntry::Revision(r) => r.id,
+                HistoryEntry::Checkpoint(c) => c.label,
+            })
+            .collect();
+        assert_eq!(ids, vec!["new", "Before agent edit", "old"]);
+    }
+}
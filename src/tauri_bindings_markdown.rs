@@ -0,0 +1,60 @@
+//! Tauri bindings for the Markdown preview
+
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// HTML of a rendered document; block elements carry `data-sourcepos`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    /// Text of the first heading
+    pub title: Option<String>,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn invoke<T: serde::de::DeserializeOwned>(cmd: &str, args: serde_json::Value) -> Result<T, String> {
+    if !is_tauri_context() {
+        return Err("Not running in Tauri context".to_string());
+    }
+    let args = serde_wasm_bindgen::to_value(&args).map_err(|e| format!("Failed to serialize args: {}", e))?;
+    let result = tauri_invoke(cmd, args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+/// Render `content`, the possibly unsaved text of the Markdown file at `path`
+#[cfg(target_arch = "wasm32")]
+pub async fn markdown_render(path: &str, content: &str) -> Result<RenderedMarkdown, String> {
+    invoke("markdown_render", serde_json::json!({ "path": path, "content": content })).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn markdown_render(_path: &str, _content: &str) -> Result<RenderedMarkdown, String> {
+    Err("Markdown preview only available in WASM context".to_string())
+}
+
+/// Export the rendered document to `target` as a standalone HTML page
+#[cfg(target_arch = "wasm32")]
+pub async fn markdown_export_html(path: &str, content: &str, target: &str) -> Result<(), String> {
+    invoke(
+        "markdown_export_html",
+        serde_json::json!({ "path": path, "content": content, "target": target }),
+    )
+    .await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn markdown_export_html(_path: &str, _content: &str, _target: &str) -> Result<(), String> {
+    Err("Markdown preview only available in WASM context".to_string())
+}
@@ -0,0 +1,53 @@
+//! Tauri bindings for remote development over SSH
+//!
+//! Remote folders are `ssh://[user@]host[:port]/path` paths; the backend
+//! routes file, explorer, search and LSP commands for them over SSH.
+
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Whether `path` is a folder or file on a remote host
+pub fn is_remote(path: &str) -> bool {
+    path.starts_with("ssh://")
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Connect to `[user@]host:path` (resolved through `~/.ssh/config`) and
+/// return the folder's `ssh://` path
+#[cfg(target_arch = "wasm32")]
+pub async fn remote_connect(target: &str) -> Result<String, String> {
+    if !is_tauri_context() {
+        return Err("Not running in Tauri context".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "target": target }))
+        .map_err(|e| format!("Failed to serialize args: {}", e))?;
+    let result = tauri_invoke("remote_connect", args)
+        .await
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn remote_connect(_target: &str) -> Result<String, String> {
+    Err("Remote folders only available in WASM context".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote() {
+        assert!(is_remote("ssh://devbox/srv/app"));
+        assert!(!is_remote("/home/dev/ssh://"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Tauri bindings for the task runner
+
+use crate::diagnostics_panel::FileDiagnostic;
+use crate::lsp_ui::{Diagnostic, DiagnosticPosition, DiagnosticRange};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskGroup {
+    Build,
+    Test,
+    Run,
+    #[default]
+    None,
+}
+
+/// Task definition (auto-detected or from `.berry/tasks.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDefinition {
+    pub label: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub group: TaskGroup,
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub is_background: bool,
+    #[serde(default)]
+    pub problem_matcher: Option<String>,
+    /// "cargo", "npm", "make", "just", "pytest" or "user"
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// Problem found in task output (1-based line and column)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDiagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    /// "error", "warning", "info" or "hint"
+    pub severity: String,
+    pub message: String,
+    pub code: Option<String>,
+    pub source: String,
+}
+
+impl From<TaskDiagnostic> for FileDiagnostic {
+    fn from(d: TaskDiagnostic) -> Self {
+        let severity = match d.severity.as_str() {
+            "error" => 1,
+            "warning" => 2,
+            "info" => 3,
+            _ => 4,
+        };
+        let start_line = d.line.saturating_sub(1) as u32;
+        let start_char = d.column.saturating_sub(1) as u32;
+        let message = match d.code {
+            Some(code) => format!("{} [{}]", d.message, code),
+            None => d.message,
+        };
+
+        FileDiagnostic {
+            file: d.file,
+            diagnostic: Diagnostic {
+                range: DiagnosticRange {
+                    start: DiagnosticPosition { line: start_line, character: start_char },
+                    end: DiagnosticPosition {
+                        line: d.end_line.map(|l| l.saturating_sub(1) as u32).unwrap_or(start_line),
+                        character: d.end_column.map(|c| c.saturating_sub(1) as u32).unwrap_or(start_char),
+                    },
+                },
+                severity,
+                message,
+                source: Some(d.source),
+            },
+        }
+    }
+}
+
+/// Snapshot of a task run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRunInfo {
+    pub run_id: String,
+    pub label: String,
+    pub status: TaskStatus,
+    pub exit_code: Option<i32>,
+    pub output: String,
+    pub diagnostics: Vec<TaskDiagnostic>,
+    pub is_background: bool,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+// Tauri invoke bridge (defined in index.html)
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// Check if running in Tauri context
+#[cfg(target_arch = "wasm32")]
+fn is_tauri_context() -> bool {
+    if let Some(window) = web_sys::window() {
+        let js_val = js_sys::Reflect::get(&window, &"berry_invoke".into()).ok();
+        if js_val.is_some() && !js_val.unwrap().is_undefined() {
+            return true;
+        }
+        let js_val = js_sys::Reflect::get(&window, &"__TAURI_INTERNALS__".into()).ok();
+        return js_val.is_some() && !js_val.unwrap().is_undefined();
+    }
+    false
+}
+
+/// List detected and user-defined tasks
+#[cfg(target_arch = "wasm32")]
+pub async fn task_list(project_path: String) -> Result<Vec<TaskDefinition>, String> {
+    if !is_tauri_context() {
+        return Ok(vec![]);
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("task_list", args)
+        .await
+        .map_err(|e| format!("Failed to list tasks: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn task_list(_project_path: String) -> Result<Vec<TaskDefinition>, String> {
+    Err("Tasks only available in WASM context".to_string())
+}
+
+/// Run a task and its dependencies; returns the run ID
+#[cfg(target_arch = "wasm32")]
+pub async fn task_run(project_path: String, label: String) -> Result<String, String> {
+    if !is_tauri_context() {
+        return Err("Tasks not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+        "label": label,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("task_run", args)
+        .await
+        .map_err(|e| format!("Failed to run task: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn task_run(_project_path: String, _label: String) -> Result<String, String> {
+    Err("Tasks only available in WASM context".to_string())
+}
+
+/// All known task runs, newest first
+#[cfg(target_arch = "wasm32")]
+pub async fn task_list_runs() -> Result<Vec<TaskRunInfo>, String> {
+    if !is_tauri_context() {
+        return Ok(vec![]);
+    }
+
+    let result = tauri_invoke("task_list_runs", JsValue::NULL)
+        .await
+        .map_err(|e| format!("Failed to list task runs: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn task_list_runs() -> Result<Vec<TaskRunInfo>, String> {
+    Err("Tasks only available in WASM context".to_string())
+}
+
+/// Stop a running task
+#[cfg(target_arch = "wasm32")]
+pub async fn task_cancel(run_id: String) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Err("Tasks not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "runId": run_id,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    tauri_invoke("task_cancel", args)
+        .await
+        .map_err(|e| format!("Failed to cancel task: {:?}", e))?;
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn task_cancel(_run_id: String) -> Result<(), String> {
+    Err("Tasks only available in WASM context".to_string())
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings_remote::is_remote;
 
 /// Terminal command response
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +9,26 @@ pub struct TerminalCommandResponse {
     pub output: String,
     pub success: bool,
     pub process_id: Option<String>,
+    #[serde(default)]
+    pub command_id: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+/// Finished command reported by shell integration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRecordInfo {
+    pub id: String,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub output: String,
+    pub exit_code: Option<i32>,
+    pub started_at: i64,
+    pub duration_ms: u64,
 }
 
 /// Background process info
@@ -52,6 +74,12 @@ pub async fn terminal_execute_command(
     if !is_tauri_context() {
         return Err("Terminal not available in web mode".to_string());
     }
+    // Remote folders run commands over SSH
+    let cmd = if is_remote(&project_path) {
+        "remote_terminal_execute"
+    } else {
+        "terminal_execute_command"
+    };
 
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
         "projectPath": project_path,
@@ -60,7 +88,7 @@ pub async fn terminal_execute_command(
     }))
     .map_err(|e| format!("Failed to serialize args: {}", e))?;
 
-    let result = tauri_invoke("terminal_execute_command", args)
+    let result = tauri_invoke(cmd, args)
         .await
         .map_err(|e| format!("Failed to execute command: {:?}", e))?;
 
@@ -80,7 +108,8 @@ pub async fn terminal_execute_command(
 /// Get command history
 #[cfg(target_arch = "wasm32")]
 pub async fn terminal_get_history(project_path: String) -> Result<Vec<String>, String> {
-    if !is_tauri_context() {
+    // Remote terminals keep no command history
+    if !is_tauri_context() || is_remote(&project_path) {
         return Ok(vec![]);
     }
 
@@ -107,7 +136,8 @@ pub async fn terminal_get_history(_project_path: String) -> Result<Vec<String>,
 pub async fn terminal_list_background_processes(
     project_path: String,
 ) -> Result<Vec<BackgroundProcessInfo>, String> {
-    if !is_tauri_context() {
+    // Remote terminals run no background processes
+    if !is_tauri_context() || is_remote(&project_path) {
         return Ok(vec![]);
     }
 
@@ -187,13 +217,18 @@ pub async fn terminal_get_current_directory(project_path: String) -> Result<Stri
     if !is_tauri_context() {
         return Ok("~".to_string());
     }
+    let cmd = if is_remote(&project_path) {
+        "remote_terminal_current_directory"
+    } else {
+        "terminal_get_current_directory"
+    };
 
     let args = serde_wasm_bindgen::to_value(&serde_json::json!({
         "projectPath": project_path,
     }))
     .map_err(|e| format!("Failed to serialize args: {}", e))?;
 
-    let result = tauri_invoke("terminal_get_current_directory", args)
+    let result = tauri_invoke(cmd, args)
         .await
         .map_err(|e| format!("Failed to get current directory: {:?}", e))?;
 
@@ -205,3 +240,89 @@ pub async fn terminal_get_current_directory(project_path: String) -> Result<Stri
 pub async fn terminal_get_current_directory(_project_path: String) -> Result<String, String> {
     Err("Terminal only available in WASM context".to_string())
 }
+
+/// List finished commands with exit codes and durations
+#[cfg(target_arch = "wasm32")]
+pub async fn terminal_list_commands(project_path: String) -> Result<Vec<CommandRecordInfo>, String> {
+    if !is_tauri_context() || is_remote(&project_path) {
+        return Ok(vec![]);
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("terminal_list_commands", args)
+        .await
+        .map_err(|e| format!("Failed to list commands: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn terminal_list_commands(_project_path: String) -> Result<Vec<CommandRecordInfo>, String> {
+    Err("Terminal only available in WASM context".to_string())
+}
+
+/// Get the most recently finished command
+#[cfg(target_arch = "wasm32")]
+pub async fn terminal_get_last_command(
+    project_path: String,
+) -> Result<Option<CommandRecordInfo>, String> {
+    if !is_tauri_context() || is_remote(&project_path) {
+        return Ok(None);
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("terminal_get_last_command", args)
+        .await
+        .map_err(|e| format!("Failed to get last command: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn terminal_get_last_command(
+    _project_path: String,
+) -> Result<Option<CommandRecordInfo>, String> {
+    Err("Terminal only available in WASM context".to_string())
+}
+
+/// Re-run a previously executed command
+#[cfg(target_arch = "wasm32")]
+pub async fn terminal_rerun_command(
+    project_path: String,
+    command_id: String,
+) -> Result<TerminalCommandResponse, String> {
+    if !is_tauri_context() {
+        return Err("Terminal not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+        "commandId": command_id,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("terminal_rerun_command", args)
+        .await
+        .map_err(|e| format!("Failed to re-run command: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn terminal_rerun_command(
+    _project_path: String,
+    _command_id: String,
+) -> Result<TerminalCommandResponse, String> {
+    Err("Terminal only available in WASM context".to_string())
+}
@@ -0,0 +1,235 @@
+//! Tauri bindings for the test explorer
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFramework {
+    Cargo,
+    Pytest,
+    Jest,
+}
+
+/// A discovered test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// `<framework>::<framework-specific name>`
+    pub id: String,
+    pub framework: TestFramework,
+    pub name: String,
+    /// Parent nodes in the tree (modules, files, classes, describe blocks)
+    pub path: Vec<String>,
+    pub file: Option<String>,
+    /// 1-based line of the test definition
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Queued,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+    pub id: String,
+    pub outcome: TestOutcome,
+    pub duration_ms: Option<u64>,
+    pub message: Option<String>,
+    pub failure_file: Option<String>,
+    /// 1-based
+    pub failure_line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunStatus {
+    Running,
+    Finished,
+    Cancelled,
+}
+
+/// Snapshot of a test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRunInfo {
+    pub run_id: String,
+    pub framework: TestFramework,
+    pub status: TestRunStatus,
+    pub results: Vec<TestResult>,
+    pub output: String,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// Program and arguments that run a single test under a debugger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebugTarget {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+}
+
+// Tauri invoke bridge (defined in index.html)
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// Check if running in Tauri context
+#[cfg(target_arch = "wasm32")]
+fn is_tauri_context() -> bool {
+    if let Some(window) = web_sys::window() {
+        let js_val = js_sys::Reflect::get(&window, &"berry_invoke".into()).ok();
+        if js_val.is_some() && !js_val.unwrap().is_undefined() {
+            return true;
+        }
+        let js_val = js_sys::Reflect::get(&window, &"__TAURI_INTERNALS__".into()).ok();
+        return js_val.is_some() && !js_val.unwrap().is_undefined();
+    }
+    false
+}
+
+/// Discover the tests of every framework configured in the project
+#[cfg(target_arch = "wasm32")]
+pub async fn test_discover(project_path: String) -> Result<Vec<TestCase>, String> {
+    if !is_tauri_context() {
+        return Ok(vec![]);
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("test_discover", args)
+        .await
+        .map_err(|e| format!("Failed to discover tests: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn test_discover(_project_path: String) -> Result<Vec<TestCase>, String> {
+    Err("Testing only available in WASM context".to_string())
+}
+
+/// Run tests of one framework (all of them when `test_ids` is empty); returns the run ID
+#[cfg(target_arch = "wasm32")]
+pub async fn test_run(
+    project_path: String,
+    framework: TestFramework,
+    test_ids: Vec<String>,
+) -> Result<String, String> {
+    if !is_tauri_context() {
+        return Err("Testing not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+        "request": {
+            "framework": framework,
+            "test_ids": test_ids,
+        },
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("test_run", args)
+        .await
+        .map_err(|e| format!("Failed to run tests: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn test_run(
+    _project_path: String,
+    _framework: TestFramework,
+    _test_ids: Vec<String>,
+) -> Result<String, String> {
+    Err("Testing only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn test_get_run(run_id: String) -> Result<Option<TestRunInfo>, String> {
+    if !is_tauri_context() {
+        return Ok(None);
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "runId": run_id,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("test_get_run", args)
+        .await
+        .map_err(|e| format!("Failed to get test run: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn test_get_run(_run_id: String) -> Result<Option<TestRunInfo>, String> {
+    Err("Testing only available in WASM context".to_string())
+}
+
+/// Stop a running test run
+#[cfg(target_arch = "wasm32")]
+pub async fn test_cancel(run_id: String) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Err("Testing not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "runId": run_id,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    tauri_invoke("test_cancel", args)
+        .await
+        .map_err(|e| format!("Failed to cancel tests: {:?}", e))?;
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn test_cancel(_run_id: String) -> Result<(), String> {
+    Err("Testing only available in WASM context".to_string())
+}
+
+/// Program and arguments for debugging a single test
+#[cfg(target_arch = "wasm32")]
+pub async fn test_debug_target(project_path: String, test_id: String) -> Result<DebugTarget, String> {
+    if !is_tauri_context() {
+        return Err("Testing not available in web mode".to_string());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "projectPath": project_path,
+        "testId": test_id,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("test_debug_target", args)
+        .await
+        .map_err(|e| format!("Failed to prepare test for debugging: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result)
+        .map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn test_debug_target(_project_path: String, _test_id: String) -> Result<DebugTarget, String> {
+    Err("Testing only available in WASM context".to_string())
+}
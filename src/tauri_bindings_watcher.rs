@@ -0,0 +1,162 @@
+//! Tauri bindings for the workspace file watcher
+
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// A change on disk reported by the watcher
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FsChange {
+    Created { path: String, is_dir: bool },
+    Modified { path: String },
+    Deleted { path: String },
+    Renamed { from: String, to: String },
+}
+
+impl FsChange {
+    /// Path whose content may differ from what the editor loaded
+    pub fn changed_file(&self) -> Option<&str> {
+        match self {
+            FsChange::Created { path, is_dir: false } | FsChange::Modified { path } => Some(path),
+            FsChange::Renamed { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+
+    /// Directories whose listing changed
+    pub fn changed_dirs(&self) -> Vec<&str> {
+        match self {
+            FsChange::Modified { .. } => Vec::new(),
+            FsChange::Created { path, .. } | FsChange::Deleted { path } => parent(path).into_iter().collect(),
+            FsChange::Renamed { from, to } => parent(from).into_iter().chain(parent(to)).collect(),
+        }
+    }
+}
+
+/// Parent folder of a path reported by the watcher
+pub fn parent(path: &str) -> Option<&str> {
+    path.rfind(['/', '\\']).map(|idx| &path[..idx])
+}
+
+/// Changes of one debounce window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FsChangeBatch {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextMerge {
+    pub result: String,
+    pub conflicts: usize,
+}
+
+/// Event carrying a `FsChangeBatch`
+#[cfg(target_arch = "wasm32")]
+const FS_CHANGED_EVENT: &str = "workspace-changed";
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    /// Subscribe to a Tauri event via the berry_listen bridge defined in index.html
+    #[wasm_bindgen(js_name = berry_listen, catch)]
+    async fn tauri_listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> Result<JsValue, JsValue>;
+}
+
+/// Watch the workspace folders (folders no longer listed stop being watched)
+#[cfg(target_arch = "wasm32")]
+pub async fn watch_workspace(roots: Vec<String>) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "roots": roots }))
+        .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    tauri_invoke("watch_workspace", args)
+        .await
+        .map_err(|e| format!("Failed to watch workspace: {:?}", e))?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn watch_workspace(_roots: Vec<String>) -> Result<(), String> {
+    Err("File watching only available in WASM context".to_string())
+}
+
+/// Three-way merge of a tab's text (`ours`) with the file on disk (`theirs`)
+#[cfg(target_arch = "wasm32")]
+pub async fn merge_external_change(base: String, ours: String, theirs: String) -> Result<TextMerge, String> {
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "base": base,
+        "ours": ours,
+        "theirs": theirs,
+    }))
+    .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    let result = tauri_invoke("merge_external_change", args)
+        .await
+        .map_err(|e| format!("Failed to merge: {:?}", e))?;
+
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn merge_external_change(_base: String, _ours: String, _theirs: String) -> Result<TextMerge, String> {
+    Err("File watching only available in WASM context".to_string())
+}
+
+/// Call `handler` with every batch of changes for the rest of the session
+#[cfg(target_arch = "wasm32")]
+pub async fn listen_fs_changes(handler: impl Fn(FsChangeBatch) + 'static) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+
+    let closure = Closure::<dyn FnMut(JsValue)>::new(move |payload: JsValue| {
+        match serde_wasm_bindgen::from_value::<FsChangeBatch>(payload) {
+            Ok(batch) => handler(batch),
+            Err(e) => leptos::logging::warn!("Invalid file change event: {}", e),
+        }
+    });
+    tauri_listen(FS_CHANGED_EVENT, &closure)
+        .await
+        .map_err(|e| format!("Failed to listen for file changes: {:?}", e))?;
+    // The listener lives as long as the app
+    closure.forget();
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn listen_fs_changes(_handler: impl Fn(FsChangeBatch) + 'static) -> Result<(), String> {
+    Err("File watching only available in WASM context".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_paths() {
+        let renamed = FsChange::Renamed {
+            from: "/ws/a/x.rs".to_string(),
+            to: "/ws/b/x.rs".to_string(),
+        };
+        assert_eq!(renamed.changed_file(), Some("/ws/b/x.rs"));
+        assert_eq!(renamed.changed_dirs(), vec!["/ws/a", "/ws/b"]);
+
+        let folder = FsChange::Created {
+            path: "/ws/new".to_string(),
+            is_dir: true,
+        };
+        assert_eq!(folder.changed_file(), None);
+        assert_eq!(folder.changed_dirs(), vec!["/ws"]);
+        assert!(FsChange::Modified { path: "/ws/a".to_string() }.changed_dirs().is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+//! Tauri bindings for multi-root workspace files
+
+#[cfg(target_arch = "wasm32")]
+use crate::tauri_bindings::is_tauri_context;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// File extension of workspace files
+pub const WORKSPACE_EXTENSION: &str = "berry-workspace";
+
+/// A folder of a multi-root workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceFolder {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Settings overrides for files in this folder
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+}
+
+impl WorkspaceFolder {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            name: None,
+            settings: Map::new(),
+        }
+    }
+
+    /// The configured name, else the folder name
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let path = self.path.trim_end_matches(['/', '\\']);
+            path.rsplit(['/', '\\']).next().unwrap_or(path).to_string()
+        })
+    }
+
+    /// Whether `path` is this folder or lies below it
+    pub fn contains(&self, path: &str) -> bool {
+        let root = self.path.trim_end_matches(['/', '\\']);
+        path.strip_prefix(root)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub folders: Vec<WorkspaceFolder>,
+    /// Settings overrides for all folders
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub settings: Map<String, Value>,
+}
+
+impl Workspace {
+    /// The folder containing `path`; the innermost one when folders are nested
+    pub fn folder_for(&self, path: &str) -> Option<&WorkspaceFolder> {
+        self.folders
+            .iter()
+            .filter(|folder| folder.contains(path))
+            .max_by_key(|folder| folder.path.len())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = berry_invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Read a workspace file (folder paths come back absolute)
+#[cfg(target_arch = "wasm32")]
+pub async fn open_workspace(path: &str) -> Result<Workspace, String> {
+    if !is_tauri_context() {
+        return Err("Not running in Tauri context".to_string());
+    }
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "path": path }))
+        .map_err(|e| format!("Failed to serialize args: {}", e))?;
+    let result = tauri_invoke("open_workspace", args)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    serde_wasm_bindgen::from_value(result).map_err(|e| format!("Failed to deserialize result: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn open_workspace(_path: &str) -> Result<Workspace, String> {
+    Err("Workspaces only available in WASM context".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn save_workspace(path: &str, workspace: &Workspace) -> Result<(), String> {
+    if !is_tauri_context() {
+        return Ok(());
+    }
+    let args = serde_wasm_bindgen::to_value(&serde_json::json!({ "path": path, "workspace": workspace }))
+        .map_err(|e| format!("Failed to serialize args: {}", e))?;
+    tauri_invoke("save_workspace", args)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn save_workspace(_path: &str, _workspace: &Workspace) -> Result<(), String> {
+    Err("Workspaces only available in WASM context".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folder_for() {
+        let workspace = Workspace {
+            folders: vec![
+                WorkspaceFolder::new("/ws/api"),
+                WorkspaceFolder::new("/ws/api/vendor/"),
+                WorkspaceFolder::new("C:\\ws\\web"),
+            ],
+            settings: Map::new(),
+        };
+        let root = |path: &str| workspace.folder_for(path).map(|f| f.path.as_str());
+        assert_eq!(root("/ws/api/src/main.rs"), Some("/ws/api"));
+        assert_eq!(root("/ws/api/vendor/lib.rs"), Some("/ws/api/vendor/"));
+        assert_eq!(root("/ws/apis/main.rs"), None);
+        assert_eq!(root("C:\\ws\\web\\index.ts"), Some("C:\\ws\\web"));
+        assert_eq!(workspace.folders[1].display_name(), "vendor");
+    }
+}
@@ -1,7 +1,9 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos::html::Input;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use wasm_bindgen::JsCast;
 use crate::tauri_bindings_terminal::*;
 
@@ -12,21 +14,208 @@ pub struct TerminalLine {
     pub is_command: bool,
 }
 
+/// A command and its output, delimited by shell integration markers
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandBlock {
+    /// Backend command ID (set once the command has finished)
+    pub id: Option<String>,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub output: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    pub running: bool,
+}
+
+impl CommandBlock {
+    fn running(command: String) -> Self {
+        Self {
+            id: None,
+            command,
+            cwd: None,
+            output: Vec::new(),
+            exit_code: None,
+            duration_ms: None,
+            running: true,
+        }
+    }
+
+    /// A finished command without an exit code (e.g. it timed out) counts as failed
+    fn is_failure(&self) -> bool {
+        !self.running && self.exit_code != Some(0)
+    }
+}
+
+/// Entry in the terminal output area
+#[derive(Debug, Clone)]
+enum TerminalEntry {
+    Line(TerminalLine),
+    Command(CommandBlock),
+}
+
+/// A `path:line[:col]` reference found in terminal output
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileLink {
+    /// Byte range of the reference within the line
+    pub start: usize,
+    pub end: usize,
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column (1 when absent)
+    pub column: usize,
+}
+
+fn file_link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?:^|[\s'"(\[<])((?:[A-Za-z]:)?[\w.~/\\-]*[\w-]\.[A-Za-z0-9]+):(\d+)(?::(\d+))?"#)
+            .expect("valid file link regex")
+    })
+}
+
+/// Find clickable `path:line:col` references (compiler errors, stack traces, grep output)
+pub fn find_file_links(text: &str) -> Vec<FileLink> {
+    file_link_regex()
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let path = caps.get(1)?;
+            let line: usize = caps.get(2)?.as_str().parse().ok()?;
+            let column = caps
+                .get(3)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(1);
+            let end = caps.get(3).or_else(|| caps.get(2))?.end();
+
+            Some(FileLink {
+                start: path.start(),
+                end,
+                path: path.as_str().to_string(),
+                line,
+                column,
+            })
+        })
+        .collect()
+}
+
+/// Resolve a link path against the directory the command ran in
+pub fn resolve_link_path(path: &str, cwd: Option<&str>) -> String {
+    let is_absolute = path.starts_with('/')
+        || path.starts_with('~')
+        || path.as_bytes().get(1) == Some(&b':');
+
+    match cwd {
+        Some(dir) if !is_absolute => {
+            let relative = path.strip_prefix("./").unwrap_or(path);
+            format!("{}/{}", dir.trim_end_matches('/'), relative)
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Copy text to the system clipboard
+fn copy_to_clipboard(text: &str) {
+    if let Some(window) = web_sys::window() {
+        let _ = window.navigator().clipboard().write_text(text);
+    }
+}
+
+/// Update a running command block with the backend response
+fn finish_command_block(
+    terminal_output: RwSignal<Vec<TerminalEntry>>,
+    index: usize,
+    result: Result<TerminalCommandResponse, String>,
+) {
+    terminal_output.update(|entries| {
+        if let Some(TerminalEntry::Command(block)) = entries.get_mut(index) {
+            block.running = false;
+            match result {
+                Ok(response) => {
+                    block.id = response.command_id;
+                    block.cwd = response.cwd;
+                    block.exit_code = response.exit_code;
+                    block.duration_ms = response.duration_ms;
+                    block.output = response.output.lines().map(|l| l.to_string()).collect();
+                }
+                Err(e) => {
+                    block.output = vec![format!("Error: {}", e)];
+                }
+            }
+        }
+    });
+}
+
+fn format_duration(duration_ms: u64) -> String {
+    if duration_ms < 1000 {
+        format!("{} ms", duration_ms)
+    } else {
+        format!("{:.1} s", duration_ms as f64 / 1000.0)
+    }
+}
+
+/// Render an output line, turning `path:line:col` references into links
+fn render_output_line(
+    text: String,
+    cwd: Option<String>,
+    on_open_location: Option<Callback<(String, usize, usize)>>,
+) -> AnyView {
+    let links = find_file_links(&text);
+    if links.is_empty() {
+        return view! { <div class="terminal-output-line">{text}</div> }.into_any();
+    }
+
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    for link in links {
+        if link.start > last {
+            pieces.push(view! { <span>{text[last..link.start].to_string()}</span> }.into_any());
+        }
+        let target = resolve_link_path(&link.path, cwd.as_deref());
+        let (line, column) = (link.line, link.column);
+        pieces.push(view! {
+            <span
+                class="terminal-file-link"
+                title=format!("Open {}:{}:{}", target, line, column)
+                style="color: #3794ff; text-decoration: underline; cursor: pointer;"
+                on:click=move |_| {
+                    if let Some(callback) = on_open_location {
+                        callback.run((target.clone(), line, column));
+                    }
+                }
+            >
+                {text[link.start..link.end].to_string()}
+            </span>
+        }.into_any());
+        last = link.end;
+    }
+    if last < text.len() {
+        pieces.push(view! { <span>{text[last..].to_string()}</span> }.into_any());
+    }
+
+    view! { <div class="terminal-output-line">{pieces}</div> }.into_any()
+}
+
 #[component]
 pub fn TerminalPanel(
     /// Project root path for terminal session
     #[prop(into)]
     project_path: Signal<String>,
+    /// Called with (path, line, column) when a `path:line:col` link is clicked
+    #[prop(optional)]
+    on_open_location: Option<Callback<(String, usize, usize)>>,
 ) -> impl IntoView {
     leptos::logging::log!("🚀 TerminalPanel component created!");
 
     let command_input = RwSignal::new(String::new());
-    let terminal_output = RwSignal::new(Vec::<TerminalLine>::new());
+    let terminal_output = RwSignal::new(Vec::<TerminalEntry>::new());
     let current_dir = RwSignal::new(String::from("~"));
     let command_history = RwSignal::new(Vec::<String>::new());
     let history_index = RwSignal::new(0usize);
     let background_processes = RwSignal::new(Vec::<BackgroundProcessInfo>::new());
 
+    // Command block selected with Ctrl/Cmd+ArrowUp/ArrowDown
+    let focused_command = RwSignal::new(Option::<usize>::None);
+
     // Input element reference for manual focus
     let input_ref = NodeRef::<Input>::new();
 
@@ -96,76 +285,138 @@ pub fn TerminalPanel(
             leptos::logging::warn!("⚠️ Command is empty, skipping execution");
             return;
         }
-
-        leptos::logging::log!("📝 Adding command to terminal output: $ {}", cmd);
-
-        // Add to terminal output
-        terminal_output.update(|lines| {
-            lines.push(TerminalLine {
-                text: format!("$ {}", cmd),
-                is_command: true,
-            });
-        });
-
         // Add to history
         command_history.update(|history| {
             history.push(cmd.clone());
         });
         history_index.set(0);
+        focused_command.set(None);
 
         // Clear input
         command_input.set(String::new());
 
-        // Execute
         let path = project_path.get();
-        leptos::logging::log!("🚀 Spawning terminal command execution for path: {}", path);
 
-        spawn_local(async move {
-            leptos::logging::log!("🔄 Calling terminal_execute_command(path='{}', cmd='{}', background={})", path, cmd, background);
+        if background {
+            leptos::logging::log!("📝 Adding background command to terminal output: $ {}", cmd);
+            terminal_output.update(|entries| {
+                entries.push(TerminalEntry::Line(TerminalLine {
+                    text: format!("$ {}", cmd),
+                    is_command: true,
+                }));
+            });
 
-            match terminal_execute_command(path.clone(), cmd.clone(), Some(background)).await {
+            spawn_local(async move {
+                match terminal_execute_command(path.clone(), cmd.clone(), Some(true)).await {
                     Ok(response) => {
-                        leptos::logging::log!("✅ Terminal command succeeded: success={}, process_id={:?}", response.success, response.process_id);
-
                         if let Some(process_id) = response.process_id {
-                            terminal_output.update(|lines| {
-                                lines.push(TerminalLine {
+                            terminal_output.update(|entries| {
+                                entries.push(TerminalEntry::Line(TerminalLine {
                                     text: format!("[Background process started: {}]", process_id),
                                     is_command: false,
-                                });
+                                }));
                             });
                             // Refresh background processes list
                             if let Ok(processes) = terminal_list_background_processes(path.clone()).await {
                                 background_processes.set(processes);
                             }
-                        } else {
-                            // Normal command output
-                            for line in response.output.lines() {
-                                terminal_output.update(|lines| {
-                                    lines.push(TerminalLine {
-                                        text: line.to_string(),
-                                        is_command: false,
-                                    });
-                                });
-                            }
-                        }
-
-                        // Update current directory
-                        if let Ok(cwd) = terminal_get_current_directory(path).await {
-                            current_dir.set(cwd);
                         }
                     }
                     Err(e) => {
                         leptos::logging::error!("❌ Terminal command failed: {}", e);
-                        terminal_output.update(|lines| {
-                            lines.push(TerminalLine {
+                        terminal_output.update(|entries| {
+                            entries.push(TerminalEntry::Line(TerminalLine {
                                 text: format!("Error: {}", e),
                                 is_command: false,
-                            });
+                            }));
                         });
                     }
                 }
+            });
+            return;
+        }
+
+        // Foreground command: shown as a block that fills in when the shell reports completion
+        let index = terminal_output.with_untracked(|entries| entries.len());
+        terminal_output.update(|entries| {
+            entries.push(TerminalEntry::Command(CommandBlock::running(cmd.clone())));
+        });
+
+        leptos::logging::log!("🚀 Spawning terminal command execution for path: {}", path);
+
+        spawn_local(async move {
+            let result = terminal_execute_command(path.clone(), cmd.clone(), Some(false)).await;
+            if let Err(e) = &result {
+                leptos::logging::error!("❌ Terminal command failed: {}", e);
+            }
+            finish_command_block(terminal_output, index, result);
+
+            // Update current directory
+            if let Ok(cwd) = terminal_get_current_directory(path).await {
+                current_dir.set(cwd);
+            }
+        });
+    };
+
+    // Re-run a finished command block
+    let rerun_command = move |command_id: String, command: String| {
+        let path = project_path.get();
+        let index = terminal_output.with_untracked(|entries| entries.len());
+        terminal_output.update(|entries| {
+            entries.push(TerminalEntry::Command(CommandBlock::running(command)));
+        });
+
+        spawn_local(async move {
+            let result = terminal_rerun_command(path.clone(), command_id).await;
+            finish_command_block(terminal_output, index, result);
+
+            if let Ok(cwd) = terminal_get_current_directory(path).await {
+                current_dir.set(cwd);
+            }
+        });
+    };
+
+    // Copy the output of the last finished command
+    let copy_last_output = move || {
+        let path = project_path.get();
+        spawn_local(async move {
+            match terminal_get_last_command(path).await {
+                Ok(Some(record)) => copy_to_clipboard(&record.output),
+                Ok(None) => {}
+                Err(e) => leptos::logging::error!("❌ Failed to get last command: {}", e),
+            }
+        });
+    };
+
+    // Jump to the previous (-1) or next (+1) command block
+    let jump_to_command = move |direction: i32| {
+        let command_indices: Vec<usize> = terminal_output.with_untracked(|entries| {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| matches!(entry, TerminalEntry::Command(_)))
+                .map(|(i, _)| i)
+                .collect()
         });
+        if command_indices.is_empty() {
+            return;
+        }
+
+        let target = match focused_command.get_untracked() {
+            None => command_indices.last().copied(),
+            Some(current) if direction < 0 => command_indices.iter().rev().find(|&&i| i < current).copied(),
+            Some(current) => command_indices.iter().find(|&&i| i > current).copied(),
+        };
+
+        if let Some(index) = target {
+            focused_command.set(Some(index));
+            if let Some(element) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&format!("terminal-command-{}", index)))
+            {
+                element.scroll_into_view();
+            }
+        }
     };
 
     // Handle Enter key
@@ -178,6 +429,12 @@ pub fn TerminalPanel(
             ev.prevent_default();
             let shift_key = ev.shift_key();
             execute_command(shift_key); // Shift+Enter = background
+        } else if (ev.ctrl_key() || ev.meta_key()) && key == "ArrowUp" {
+            ev.prevent_default();
+            jump_to_command(-1);
+        } else if (ev.ctrl_key() || ev.meta_key()) && key == "ArrowDown" {
+            ev.prevent_default();
+            jump_to_command(1);
         } else if key == "ArrowUp" {
             ev.prevent_default();
             let history = command_history.get();
@@ -209,11 +466,11 @@ pub fn TerminalPanel(
         let path = project_path.get();
         spawn_local(async move {
             if terminal_kill_process(path.clone(), process_id.clone()).await.is_ok() {
-                    terminal_output.update(|lines| {
-                        lines.push(TerminalLine {
+                    terminal_output.update(|entries| {
+                        entries.push(TerminalEntry::Line(TerminalLine {
                             text: format!("[Killed background process: {}]", process_id),
                             is_command: false,
-                        });
+                        }));
                     });
                     // Refresh background processes list
                     if let Ok(processes) = terminal_list_background_processes(path).await {
@@ -228,26 +485,103 @@ pub fn TerminalPanel(
             // Header
             <div class="terminal-header" style="padding: 8px 12px; background: #2d2d30; border-bottom: 1px solid #3e3e42; display: flex; justify-content: space-between; align-items: center;">
                 <div style="font-weight: bold; color: #ffffff;">Terminal</div>
-                <div style="font-size: 12px; color: #cccccc;">{move || current_dir.get()}</div>
+                <div style="display: flex; gap: 12px; align-items: center;">
+                    <button
+                        class="terminal-copy-last-btn"
+                        title="Copy output of last command"
+                        on:click=move |_| copy_last_output()
+                        style="background: transparent; color: #cccccc; border: 1px solid #3e3e42; padding: 1px 8px; border-radius: 3px; cursor: pointer; font-size: 11px;"
+                    >
+                        "Copy Last Output"
+                    </button>
+                    <div style="font-size: 12px; color: #cccccc;">{move || current_dir.get()}</div>
+                </div>
             </div>
 
             // Output area
             <div class="terminal-output" style="flex: 1; overflow-y: auto; padding: 8px 12px; font-size: 13px; line-height: 1.5;">
-                <For
-                    each=move || terminal_output.get()
-                    key=|line| line.text.clone()
-                    children=move |line| {
-                        view! {
-                            <div style=move || if line.is_command {
-                                "color: #4ec9b0; font-weight: bold;"
-                            } else {
-                                "color: #d4d4d4;"
-                            }>
-                                {line.text.clone()}
-                            </div>
+                {move || {
+                    let focused = focused_command.get();
+                    terminal_output.get().into_iter().enumerate().map(|(index, entry)| {
+                        match entry {
+                            TerminalEntry::Line(line) => view! {
+                                <div style=if line.is_command {
+                                    "color: #4ec9b0; font-weight: bold;"
+                                } else {
+                                    "color: #d4d4d4;"
+                                }>
+                                    {line.text.clone()}
+                                </div>
+                            }.into_any(),
+                            TerminalEntry::Command(block) => {
+                                let (marker, marker_color) = if block.running {
+                                    ("…", "#858585")
+                                } else if block.is_failure() {
+                                    ("✗", "#f14c4c")
+                                } else {
+                                    ("✓", "#4ec9b0")
+                                };
+                                let status = match (block.exit_code, block.duration_ms) {
+                                    (Some(code), Some(ms)) => format!("exit {} · {}", code, format_duration(ms)),
+                                    (None, Some(ms)) => format!("still running after {}", format_duration(ms)),
+                                    _ => String::new(),
+                                };
+                                let failed = block.is_failure();
+                                let border = if failed { "#f14c4c" } else { "transparent" };
+                                let background = if focused == Some(index) { "#2a2d2e" } else { "transparent" };
+                                let output_text = block.output.join("\n");
+                                let rerun = block.id.clone().map(|id| (id, block.command.clone()));
+                                let cwd = block.cwd.clone();
+
+                                view! {
+                                    <div
+                                        id=format!("terminal-command-{}", index)
+                                        class="terminal-command-block"
+                                        class:terminal-command-failed=failed
+                                        style=format!("display: flex; border-left: 2px solid {}; background: {}; margin: 2px 0;", border, background)
+                                    >
+                                        <div
+                                            class="terminal-command-gutter"
+                                            title=block.exit_code.map(|c| format!("Exit code {}", c)).unwrap_or_default()
+                                            style=format!("width: 16px; flex-shrink: 0; text-align: center; color: {}; user-select: none;", marker_color)
+                                        >
+                                            {marker}
+                                        </div>
+                                        <div style="flex: 1; min-width: 0;">
+                                            <div style="display: flex; justify-content: space-between; align-items: center;">
+                                                <span style="color: #4ec9b0; font-weight: bold;">{format!("$ {}", block.command)}</span>
+                                                <span style="display: flex; gap: 8px; align-items: center; font-size: 11px; color: #858585;">
+                                                    <span>{status}</span>
+                                                    {rerun.map(|(id, command)| view! {
+                                                        <button
+                                                            class="terminal-rerun-btn"
+                                                            title="Re-run command"
+                                                            on:click=move |_| rerun_command(id.clone(), command.clone())
+                                                            style="background: transparent; color: #cccccc; border: none; cursor: pointer; font-size: 12px;"
+                                                        >
+                                                            "↻"
+                                                        </button>
+                                                    })}
+                                                    <button
+                                                        class="terminal-copy-output-btn"
+                                                        title="Copy output"
+                                                        on:click=move |_| copy_to_clipboard(&output_text)
+                                                        style="background: transparent; color: #cccccc; border: none; cursor: pointer; font-size: 12px;"
+                                                    >
+                                                        "⧉"
+                                                    </button>
+                                                </span>
+                                            </div>
+                                            {block.output.into_iter().map(|line| {
+                                                render_output_line(line, cwd.clone(), on_open_location)
+                                            }).collect::<Vec<_>>()}
+                                        </div>
+                                    </div>
+                                }.into_any()
+                            }
                         }
-                    }
-                />
+                    }).collect::<Vec<_>>()
+                }}
             </div>
 
             // Background processes
@@ -309,10 +643,65 @@ pub fn TerminalPanel(
                     on:blur=move |_| {
                         leptos::logging::log!("👋 Terminal input blurred");
                     }
-                    placeholder="Enter command (Shift+Enter for background)"
+                    placeholder="Enter command (Shift+Enter for background, Ctrl+↑/↓ to jump between commands)"
                     style="flex: 1; background: transparent; color: #d4d4d4; border: none; padding: 0; outline: none; font-family: 'Consolas', 'Courier New', monospace; font-size: 13px; caret-color: #BBBBBB;"
                 />
             </div>
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_file_links_rustc_location() {
+        let links = find_file_links("  --> src/main.rs:12:5");
+        assert_eq!(
+            links,
+            vec![FileLink {
+                start: 6,
+                end: 22,
+                path: "src/main.rs".to_string(),
+                line: 12,
+                column: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_file_links_without_column() {
+        let links = find_file_links("tests/app.py:40: AssertionError");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].path, "tests/app.py");
+        assert_eq!(links[0].line, 40);
+        assert_eq!(links[0].column, 1);
+    }
+
+    #[test]
+    fn test_find_file_links_ignores_urls_and_plain_text() {
+        assert!(find_file_links("listening on http://localhost:8080").is_empty());
+        assert!(find_file_links("took 12:30 minutes").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_link_path() {
+        assert_eq!(resolve_link_path("./src/lib.rs", Some("/work/app/")), "/work/app/src/lib.rs");
+        assert_eq!(resolve_link_path("/abs/file.rs", Some("/work")), "/abs/file.rs");
+        assert_eq!(resolve_link_path("src/lib.rs", None), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_command_block_failure() {
+        let mut block = CommandBlock::running("false".to_string());
+        assert!(!block.is_failure());
+        block.running = false;
+        block.exit_code = Some(1);
+        assert!(block.is_failure());
+        block.exit_code = Some(0);
+        assert!(!block.is_failure());
+        block.exit_code = None;
+        assert!(block.is_failure());
+    }
+}
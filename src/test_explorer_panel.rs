@@ -0,0 +1,685 @@
+//! Test Explorer Panel
+//!
+//! Shows discovered tests (cargo, pytest, jest) as a tree, runs or debugs
+//! single tests and streams per-test results while a run is in progress.
+//! Results are also published as editor line decorations: run/status icons in
+//! the gutter at each test and failure messages at the failing line.
+
+use crate::core::virtual_editor::LineDecoration;
+use crate::debugger::DebugSession;
+use crate::tauri_bindings_testing::*;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use std::collections::{HashMap, HashSet};
+
+/// Polling interval while a test run is in progress
+const POLL_INTERVAL_MS: u32 = 300;
+
+const COLOR_PASSED: &str = "#89d185";
+const COLOR_FAILED: &str = "#f14c4c";
+const COLOR_IDLE: &str = "#858585";
+
+/// Test state shared between the explorer panel and the editor gutter
+#[derive(Clone, Copy)]
+pub struct TestExplorer {
+    pub tests: RwSignal<Vec<TestCase>>,
+    /// Latest result of each test, by test ID
+    pub results: RwSignal<HashMap<String, TestResult>>,
+    /// Output of the most recent run
+    pub output: RwSignal<String>,
+    pub active_runs: RwSignal<Vec<String>>,
+    pub discovering: RwSignal<bool>,
+    pub error: RwSignal<Option<String>>,
+    pub debug_session: DebugSession,
+}
+
+impl TestExplorer {
+    pub fn new() -> Self {
+        Self {
+            tests: RwSignal::new(Vec::new()),
+            results: RwSignal::new(HashMap::new()),
+            output: RwSignal::new(String::new()),
+            active_runs: RwSignal::new(Vec::new()),
+            discovering: RwSignal::new(false),
+            error: RwSignal::new(None),
+            debug_session: DebugSession::new(),
+        }
+    }
+
+    /// Discover the project's tests
+    pub fn discover(&self, project_path: String) {
+        let explorer = *self;
+        explorer.discovering.set(true);
+        spawn_local(async move {
+            match test_discover(project_path).await {
+                Ok(tests) => {
+                    explorer.tests.set(tests);
+                    explorer.error.set(None);
+                }
+                Err(e) => {
+                    leptos::logging::error!("❌ Failed to discover tests: {}", e);
+                    explorer.error.set(Some(e));
+                }
+            }
+            explorer.discovering.set(false);
+        });
+    }
+
+    /// Run the given tests; an empty list runs every framework's whole suite
+    pub fn run(&self, project_path: String, test_ids: Vec<String>) {
+        let explorer = *self;
+        let tests = self.tests.get_untracked();
+
+        let mut by_framework: Vec<(TestFramework, Vec<String>)> = Vec::new();
+        if test_ids.is_empty() {
+            for test in &tests {
+                if !by_framework.iter().any(|(f, _)| *f == test.framework) {
+                    by_framework.push((test.framework, Vec::new()));
+                }
+            }
+        } else {
+            for id in &test_ids {
+                let Some(test) = tests.iter().find(|t| &t.id == id) else {
+                    continue;
+                };
+                match by_framework.iter_mut().find(|(f, _)| *f == test.framework) {
+                    Some((_, ids)) => ids.push(id.clone()),
+                    None => by_framework.push((test.framework, vec![id.clone()])),
+                }
+            }
+        }
+
+        // Show the tests as queued until the backend reports on them
+        self.results.update(|results| {
+            for test in &tests {
+                let selected = by_framework
+                    .iter()
+                    .any(|(f, ids)| *f == test.framework && (ids.is_empty() || ids.contains(&test.id)));
+                if selected {
+                    results.insert(test.id.clone(), queued(&test.id));
+                }
+            }
+        });
+
+        for (framework, ids) in by_framework {
+            let project_path = project_path.clone();
+            spawn_local(async move {
+                leptos::logging::log!("🧪 Running {} {:?} tests", if ids.is_empty() { "all".to_string() } else { ids.len().to_string() }, framework);
+                match test_run(project_path, framework, ids).await {
+                    Ok(run_id) => {
+                        explorer.active_runs.update(|runs| runs.push(run_id.clone()));
+                        explorer.poll(run_id);
+                    }
+                    Err(e) => {
+                        leptos::logging::error!("❌ Failed to run tests: {}", e);
+                        explorer.error.set(Some(e));
+                    }
+                }
+            });
+        }
+    }
+
+    /// Rerun only the tests that failed last time
+    pub fn rerun_failed(&self, project_path: String) {
+        let failed = failed_ids(&self.results.get_untracked());
+        if !failed.is_empty() {
+            self.run(project_path, failed);
+        }
+    }
+
+    /// Run the test defined at a 0-based line of a file (gutter click); false when there is none
+    pub fn run_at_line(&self, project_path: String, file: &str, line: usize) -> bool {
+        match test_at_line(&self.tests.get_untracked(), file, line) {
+            Some(id) => {
+                self.run(project_path, vec![id]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start a debug session for a single test
+    pub fn debug(&self, project_path: String, test_id: String) {
+        let explorer = *self;
+        spawn_local(async move {
+            let target = match test_debug_target(project_path, test_id.clone()).await {
+                Ok(target) => target,
+                Err(e) => {
+                    leptos::logging::error!("❌ Failed to debug {}: {}", test_id, e);
+                    explorer.error.set(Some(e));
+                    return;
+                }
+            };
+            leptos::logging::log!("🐞 Debugging {}: {} {:?}", test_id, target.program, target.args);
+            if let Err(e) = explorer
+                .debug_session
+                .start_with_args(target.program, target.args, target.cwd, target.env)
+                .await
+            {
+                leptos::logging::error!("❌ Failed to start debug session: {}", e);
+                explorer.error.set(Some(e));
+            }
+        });
+    }
+
+    /// Stop all running test runs
+    pub fn cancel(&self) {
+        for run_id in self.active_runs.get_untracked() {
+            spawn_local(async move {
+                if let Err(e) = test_cancel(run_id).await {
+                    leptos::logging::error!("❌ Failed to cancel tests: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Poll a run, merging its results as they arrive, until it finishes
+    fn poll(&self, run_id: String) {
+        let explorer = *self;
+        spawn_local(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(POLL_INTERVAL_MS).await;
+                match test_get_run(run_id.clone()).await {
+                    Ok(Some(run)) => {
+                        explorer.results.update(|results| {
+                            for result in &run.results {
+                                results.insert(result.id.clone(), result.clone());
+                            }
+                        });
+                        explorer.output.set(run.output);
+                        if run.status != TestRunStatus::Running {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        leptos::logging::error!("❌ Failed to poll test run: {}", e);
+                        break;
+                    }
+                }
+            }
+            explorer.active_runs.update(|runs| runs.retain(|r| r != &run_id));
+        });
+    }
+
+    /// Gutter icons and inline failure messages for the editor
+    pub fn line_decorations(&self) -> Vec<LineDecoration> {
+        line_decorations(&self.tests.get(), &self.results.get())
+    }
+}
+
+impl Default for TestExplorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn queued(id: &str) -> TestResult {
+    TestResult {
+        id: id.to_string(),
+        outcome: TestOutcome::Queued,
+        duration_ms: None,
+        message: None,
+        failure_file: None,
+        failure_line: None,
+    }
+}
+
+/// A node of the test tree: a group (framework, module, file, class) or a test
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestNode {
+    pub label: String,
+    /// Unique key (path of labels)
+    pub key: String,
+    /// The test itself for leaf nodes
+    pub test: Option<TestCase>,
+    pub children: Vec<TestNode>,
+}
+
+impl TestNode {
+    /// IDs of all tests in this subtree
+    pub fn test_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        if let Some(test) = &self.test {
+            ids.push(test.id.clone());
+        }
+        for child in &self.children {
+            ids.extend(child.test_ids());
+        }
+        ids
+    }
+}
+
+/// Tree of tests grouped by framework and then by each test's path
+pub fn build_tree(tests: &[TestCase]) -> Vec<TestNode> {
+    fn insert(nodes: &mut Vec<TestNode>, parent_key: &str, path: &[String], test: &TestCase) {
+        match path.split_first() {
+            Some((segment, rest)) => {
+                let key = format!("{}/{}", parent_key, segment);
+                let index = match nodes.iter().position(|n| n.test.is_none() && n.label == *segment) {
+                    Some(index) => index,
+                    None => {
+                        nodes.push(TestNode {
+                            label: segment.clone(),
+                            key: key.clone(),
+                            test: None,
+                            children: Vec::new(),
+                        });
+                        nodes.len() - 1
+                    }
+                };
+                insert(&mut nodes[index].children, &key, rest, test);
+            }
+            None => nodes.push(TestNode {
+                label: test.name.clone(),
+                key: test.id.clone(),
+                test: Some(test.clone()),
+                children: Vec::new(),
+            }),
+        }
+    }
+
+    let mut roots = Vec::new();
+    for test in tests {
+        let mut path = vec![framework_label(test.framework).to_string()];
+        path.extend(test.path.iter().cloned());
+        insert(&mut roots, "", &path, test);
+    }
+    roots
+}
+
+fn framework_label(framework: TestFramework) -> &'static str {
+    match framework {
+        TestFramework::Cargo => "Cargo",
+        TestFramework::Pytest => "pytest",
+        TestFramework::Jest => "Jest",
+    }
+}
+
+/// Combined outcome of several tests: failures win, then in-progress, then passes
+pub fn aggregate_outcome(ids: &[String], results: &HashMap<String, TestResult>) -> Option<TestOutcome> {
+    let outcomes: Vec<TestOutcome> = ids.iter().filter_map(|id| results.get(id)).map(|r| r.outcome).collect();
+    if outcomes.is_empty() {
+        None
+    } else if outcomes.contains(&TestOutcome::Failed) {
+        Some(TestOutcome::Failed)
+    } else if outcomes.contains(&TestOutcome::Running) {
+        Some(TestOutcome::Running)
+    } else if outcomes.contains(&TestOutcome::Queued) {
+        Some(TestOutcome::Queued)
+    } else if outcomes.contains(&TestOutcome::Passed) {
+        Some(TestOutcome::Passed)
+    } else {
+        Some(TestOutcome::Skipped)
+    }
+}
+
+pub fn failed_ids(results: &HashMap<String, TestResult>) -> Vec<String> {
+    let mut ids: Vec<String> = results
+        .values()
+        .filter(|r| r.outcome == TestOutcome::Failed)
+        .map(|r| r.id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// ID of the test defined at a 0-based line of a file
+pub fn test_at_line(tests: &[TestCase], file: &str, line: usize) -> Option<String> {
+    tests
+        .iter()
+        .find(|t| t.file.as_deref() == Some(file) && t.line == Some(line + 1))
+        .map(|t| t.id.clone())
+}
+
+/// Gutter icons at each test definition and failure messages at the failing line
+pub fn line_decorations(tests: &[TestCase], results: &HashMap<String, TestResult>) -> Vec<LineDecoration> {
+    let mut decorations = Vec::new();
+    for test in tests {
+        let result = results.get(&test.id);
+
+        if let (Some(file), Some(line)) = (&test.file, test.line) {
+            let (glyph, color) = match result.map(|r| r.outcome) {
+                Some(TestOutcome::Passed) => ("✓", COLOR_PASSED),
+                Some(TestOutcome::Failed) => ("✗", COLOR_FAILED),
+                Some(TestOutcome::Queued) | Some(TestOutcome::Running) => ("◌", COLOR_IDLE),
+                Some(TestOutcome::Skipped) | None => ("▶", COLOR_PASSED),
+            };
+            decorations.push(LineDecoration {
+                file: file.clone(),
+                line: line.saturating_sub(1),
+                gutter_glyph: Some(glyph.to_string()),
+                message: None,
+                color: color.to_string(),
+            });
+        }
+
+        let Some(result) = result.filter(|r| r.outcome == TestOutcome::Failed) else {
+            continue;
+        };
+        if let (Some(file), Some(line)) = (&result.failure_file, result.failure_line) {
+            decorations.push(LineDecoration {
+                file: file.clone(),
+                line: line.saturating_sub(1),
+                gutter_glyph: None,
+                message: Some(result.message.clone().unwrap_or_else(|| format!("{} failed", test.name))),
+                color: COLOR_FAILED.to_string(),
+            });
+        }
+    }
+    decorations
+}
+
+fn outcome_icon(outcome: Option<TestOutcome>) -> (&'static str, &'static str) {
+    match outcome {
+        Some(TestOutcome::Passed) => ("codicon-pass", COLOR_PASSED),
+        Some(TestOutcome::Failed) => ("codicon-error", COLOR_FAILED),
+        Some(TestOutcome::Running) => ("codicon-loading codicon-modifier-spin", "#cccccc"),
+        Some(TestOutcome::Queued) => ("codicon-history", COLOR_IDLE),
+        Some(TestOutcome::Skipped) => ("codicon-circle-slash", COLOR_IDLE),
+        None => ("codicon-circle-large-outline", COLOR_IDLE),
+    }
+}
+
+fn render_node(
+    node: TestNode,
+    depth: usize,
+    explorer: TestExplorer,
+    results: &HashMap<String, TestResult>,
+    collapsed: RwSignal<HashSet<String>>,
+    project_path: Signal<String>,
+    on_open_location: Option<Callback<(String, usize, usize)>>,
+) -> AnyView {
+    let ids = node.test_ids();
+    let (icon, color) = outcome_icon(aggregate_outcome(&ids, results));
+    let result = node.test.as_ref().and_then(|t| results.get(&t.id)).cloned();
+    let is_collapsed = collapsed.get_untracked().contains(&node.key);
+    let has_children = !node.children.is_empty();
+    let key = node.key.clone();
+    let test = node.test.clone();
+    let debug_id = test.as_ref().map(|t| t.id.clone());
+
+    let on_row_click = move |_| {
+        if has_children {
+            let key = key.clone();
+            collapsed.update(|set| {
+                if !set.remove(&key) {
+                    set.insert(key);
+                }
+            });
+        } else if let (Some(test), Some(callback)) = (&test, on_open_location) {
+            if let Some(file) = &test.file {
+                callback.run((file.clone(), test.line.unwrap_or(1), 1));
+            }
+        }
+    };
+
+    let failure = result.as_ref().filter(|r| r.outcome == TestOutcome::Failed).map(|r| {
+        let location = r.failure_file.clone().zip(r.failure_line);
+        (r.message.clone().unwrap_or_default(), location)
+    });
+
+    let children = (!is_collapsed).then(|| {
+        node.children
+            .into_iter()
+            .map(|child| render_node(child, depth + 1, explorer, results, collapsed, project_path, on_open_location))
+            .collect_view()
+    });
+
+    view! {
+        <div
+            class="test-explorer-item"
+            style=format!(
+                "display: flex; align-items: center; gap: 6px; padding: 2px 8px 2px {}px; font-size: 13px; color: #cccccc; cursor: pointer;",
+                8 + depth * 14
+            )
+            on:click=on_row_click
+        >
+            <i
+                class=format!("codicon {}", if !has_children { "codicon-blank" } else if is_collapsed { "codicon-chevron-right" } else { "codicon-chevron-down" })
+                style="width: 16px;"
+            ></i>
+            <i class=format!("codicon {}", icon) style=format!("color: {};", color)></i>
+            <span style="flex: 1; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{node.label}</span>
+            {result.and_then(|r| r.duration_ms).map(|ms| view! {
+                <span style="font-size: 11px; color: #858585;">{format!("{}ms", ms)}</span>
+            })}
+            <i
+                class="codicon codicon-play"
+                title="Run"
+                style=format!("color: {};", COLOR_PASSED)
+                on:click=move |ev| {
+                    ev.stop_propagation();
+                    explorer.run(project_path.get_untracked(), ids.clone());
+                }
+            ></i>
+            {debug_id.map(|id| view! {
+                <i
+                    class="codicon codicon-debug-alt"
+                    title="Debug"
+                    style="color: #cccccc;"
+                    on:click=move |ev| {
+                        ev.stop_propagation();
+                        explorer.debug(project_path.get_untracked(), id.clone());
+                    }
+                ></i>
+            })}
+        </div>
+        {failure.map(|(message, location)| view! {
+            <div
+                class="test-explorer-failure"
+                style=format!(
+                    "padding: 2px 8px 4px {}px; font-size: 12px; color: {}; white-space: pre-wrap; cursor: pointer;",
+                    8 + (depth + 1) * 14 + 16,
+                    COLOR_FAILED
+                )
+                on:click=move |_| {
+                    if let (Some((file, line)), Some(callback)) = (location.clone(), on_open_location) {
+                        callback.run((file, line, 1));
+                    }
+                }
+            >
+                {message}
+            </div>
+        })}
+        {children}
+    }
+    .into_any()
+}
+
+#[component]
+pub fn TestExplorerPanel(
+    /// Project root path
+    #[prop(into)]
+    project_path: Signal<String>,
+    explorer: TestExplorer,
+    /// Opens a file at (path, line, column), 1-based
+    #[prop(optional)]
+    on_open_location: Option<Callback<(String, usize, usize)>>,
+) -> impl IntoView {
+    let collapsed = RwSignal::new(HashSet::<String>::new());
+
+    // Discover tests when the project changes
+    Effect::new(move |_| {
+        let path = project_path.get();
+        if !path.is_empty() {
+            explorer.discover(path);
+        }
+    });
+
+    let summary = move || {
+        let results = explorer.results.get();
+        let count = |outcome| results.values().filter(|r| r.outcome == outcome).count();
+        format!(
+            "{} passed · {} failed · {} skipped",
+            count(TestOutcome::Passed),
+            count(TestOutcome::Failed),
+            count(TestOutcome::Skipped)
+        )
+    };
+
+    view! {
+        <div class="berry-editor-sidebar test-explorer-panel" style="background: #252526; display: flex; flex-direction: column;">
+            <div class="berry-editor-sidebar-header" style="
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                padding: 8px 12px;
+                background: #2D2D30;
+                border-bottom: 1px solid #1e1e1e;
+                font-size: 12px;
+                font-weight: 600;
+                color: #cccccc;
+            ">
+                <span>"TESTING"</span>
+                <div style="display: flex; gap: 8px;">
+                    <i
+                        class="codicon codicon-run-all"
+                        title="Run all tests"
+                        style="cursor: pointer;"
+                        on:click=move |_| explorer.run(project_path.get_untracked(), Vec::new())
+                    ></i>
+                    <i
+                        class="codicon codicon-debug-rerun"
+                        title="Rerun failed tests"
+                        style="cursor: pointer;"
+                        on:click=move |_| explorer.rerun_failed(project_path.get_untracked())
+                    ></i>
+                    {move || (!explorer.active_runs.get().is_empty()).then(|| view! {
+                        <i
+                            class="codicon codicon-debug-stop"
+                            title="Stop"
+                            style=format!("cursor: pointer; color: {};", COLOR_FAILED)
+                            on:click=move |_| explorer.cancel()
+                        ></i>
+                    })}
+                    <i
+                        class="codicon codicon-refresh"
+                        title="Refresh tests"
+                        style="cursor: pointer;"
+                        on:click=move |_| explorer.discover(project_path.get_untracked())
+                    ></i>
+                </div>
+            </div>
+
+            {move || explorer.error.get().map(|e| view! {
+                <div style=format!("padding: 6px 12px; font-size: 12px; color: {};", COLOR_FAILED)>{e}</div>
+            })}
+
+            <div class="test-explorer-tree" style="overflow-y: auto; padding: 4px 0; flex: 1;">
+                {move || {
+                    let tests = explorer.tests.get();
+                    if tests.is_empty() {
+                        let message = if explorer.discovering.get() {
+                            "Discovering tests..."
+                        } else {
+                            "No tests found. Cargo, pytest and Jest projects are supported."
+                        };
+                        return view! {
+                            <div style="padding: 16px; color: #858585; font-size: 12px;">{message}</div>
+                        }.into_any();
+                    }
+
+                    let results = explorer.results.get();
+                    let _ = collapsed.get();
+                    build_tree(&tests)
+                        .into_iter()
+                        .map(|node| render_node(node, 0, explorer, &results, collapsed, project_path, on_open_location))
+                        .collect_view()
+                        .into_any()
+                }}
+            </div>
+
+            {move || {
+                let output = explorer.output.get();
+                (!output.is_empty()).then(|| view! {
+                    <pre class="test-explorer-output" style="
+                        margin: 0;
+                        max-height: 30%;
+                        overflow: auto;
+                        padding: 8px 12px;
+                        background: #1e1e1e;
+                        border-top: 1px solid #3e3e42;
+                        color: #d4d4d4;
+                        font-size: 12px;
+                        white-space: pre-wrap;
+                    ">
+                        {output}
+                    </pre>
+                })
+            }}
+
+            <div style="padding: 6px 12px; font-size: 11px; color: #858585; border-top: 1px solid #1e1e1e;">
+                {summary}
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(id: &str, path: &[&str], file: &str, line: usize) -> TestCase {
+        TestCase {
+            id: id.to_string(),
+            framework: TestFramework::Cargo,
+            name: id.rsplit("::").next().unwrap().to_string(),
+            path: path.iter().map(|s| s.to_string()).collect(),
+            file: Some(file.to_string()),
+            line: Some(line),
+        }
+    }
+
+    fn result(id: &str, outcome: TestOutcome) -> (String, TestResult) {
+        let mut result = queued(id);
+        result.outcome = outcome;
+        (id.to_string(), result)
+    }
+
+    #[test]
+    fn test_build_tree_and_aggregate() {
+        let tests = vec![
+            case("cargo::tests::adds", &["tests"], "/p/src/lib.rs", 5),
+            case("cargo::tests::subs", &["tests"], "/p/src/lib.rs", 9),
+            case("cargo::parser::tests::parses", &["parser", "tests"], "/p/src/parser.rs", 3),
+        ];
+        let tree = build_tree(&tests);
+
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].label, "Cargo");
+        assert_eq!(tree[0].children.len(), 2);
+        assert_eq!(tree[0].children[0].test_ids(), vec!["cargo::tests::adds", "cargo::tests::subs"]);
+
+        let results: HashMap<String, TestResult> = vec![
+            result("cargo::tests::adds", TestOutcome::Passed),
+            result("cargo::tests::subs", TestOutcome::Failed),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(aggregate_outcome(&tree[0].test_ids(), &results), Some(TestOutcome::Failed));
+        assert_eq!(aggregate_outcome(&tree[0].children[1].test_ids(), &results), None);
+        assert_eq!(failed_ids(&results), vec!["cargo::tests::subs"]);
+        assert_eq!(test_at_line(&tests, "/p/src/lib.rs", 8).as_deref(), Some("cargo::tests::subs"));
+    }
+
+    #[test]
+    fn test_line_decorations() {
+        let tests = vec![case("cargo::tests::subs", &["tests"], "/p/src/lib.rs", 9)];
+        let mut results = HashMap::new();
+        assert_eq!(line_decorations(&tests, &results)[0].gutter_glyph.as_deref(), Some("▶"));
+
+        let (id, mut failed) = result("cargo::tests::subs", TestOutcome::Failed);
+        failed.message = Some("assertion failed".to_string());
+        failed.failure_file = Some("/p/src/lib.rs".to_string());
+        failed.failure_line = Some(11);
+        results.insert(id, failed);
+
+        let decorations = line_decorations(&tests, &results);
+        assert_eq!(decorations.len(), 2);
+        assert_eq!(decorations[0].line, 8);
+        assert_eq!(decorations[0].gutter_glyph.as_deref(), Some("✗"));
+        assert_eq!(decorations[1].line, 10);
+        assert_eq!(decorations[1].message.as_deref(), Some("assertion failed"));
+    }
+}
@@ -4,6 +4,10 @@
 //! - Unused variable grayout
 //! - Function call graph
 //! - Instant indexing (no "Building indexes..." wait)
+//!
+//! Structural editing works on a lighter tree of brackets, strings, comments
+//! and words that every language shares ([`parse_structure`]); only comment
+//! and quote tokens differ per language ([`LanguageSyntax`]).
 
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
@@ -265,6 +269,388 @@ impl TreeSitterEngine {
     }
 }
 
+/// Comment and string tokens of a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanguageSyntax {
+    pub line_comment: Option<&'static str>,
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Characters that delimit strings (and are auto-closed)
+    pub quotes: &'static [char],
+    /// A line ending with `:` opens an indented block (Python)
+    pub colon_blocks: bool,
+}
+
+impl LanguageSyntax {
+    const PLAIN: LanguageSyntax = LanguageSyntax {
+        line_comment: None,
+        block_comment: None,
+        quotes: &['"'],
+        colon_blocks: false,
+    };
+    const C_LIKE: LanguageSyntax = LanguageSyntax {
+        line_comment: Some("//"),
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        colon_blocks: false,
+    };
+    const HASH: LanguageSyntax = LanguageSyntax {
+        line_comment: Some("#"),
+        block_comment: None,
+        quotes: &['"', '\''],
+        colon_blocks: false,
+    };
+    const MARKUP: LanguageSyntax = LanguageSyntax {
+        line_comment: None,
+        block_comment: Some(("<!--", "-->")),
+        quotes: &['"', '\''],
+        colon_blocks: false,
+    };
+
+    /// Syntax of a file, chosen by its extension or name
+    pub fn for_path(path: &str) -> Self {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+        let ext = name.rsplit_once('.').map_or("", |(_, ext)| ext);
+        match ext {
+            // ' also starts lifetimes; char literals are recognised on their own
+            "rs" => LanguageSyntax { quotes: &['"'], ..Self::C_LIKE },
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "kt" | "go"
+            | "swift" | "cs" | "scala" | "dart" | "php" | "scss" | "less" | "json" | "jsonc" => Self::C_LIKE,
+            "css" => LanguageSyntax { line_comment: None, ..Self::C_LIKE },
+            "py" | "pyi" => LanguageSyntax { colon_blocks: true, ..Self::HASH },
+            "sh" | "bash" | "zsh" | "rb" | "yaml" | "yml" | "toml" | "r" | "pl" | "nix" => Self::HASH,
+            "html" | "htm" | "xml" | "svg" | "vue" | "md" | "markdown" => Self::MARKUP,
+            "sql" => LanguageSyntax { line_comment: Some("--"), quotes: &['\'', '"'], ..Self::C_LIKE },
+            "lua" => LanguageSyntax { line_comment: Some("--"), block_comment: Some(("--[[", "]]")), quotes: &['"', '\''], colon_blocks: false },
+            "hs" => LanguageSyntax { line_comment: Some("--"), block_comment: Some(("{-", "-}")), quotes: &['"'], colon_blocks: false },
+            _ if matches!(name.as_str(), "makefile" | "dockerfile" | ".gitignore" | ".env") => Self::HASH,
+            _ => Self::PLAIN,
+        }
+    }
+}
+
+/// Kind of a node in the structure tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    Document,
+    /// `(...)`, `[...]` or `{...}`
+    Bracket,
+    String,
+    Comment,
+    Word,
+}
+
+/// A node of the structure tree; offsets are in chars, `end` exclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub start: usize,
+    pub end: usize,
+    /// Length of the opening and closing delimiters (0 when unterminated)
+    pub open_len: usize,
+    pub close_len: usize,
+    pub children: Vec<SyntaxNode>,
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxKind, start: usize, end: usize, open_len: usize, close_len: usize) -> Self {
+        Self {
+            kind,
+            start,
+            end,
+            open_len,
+            close_len,
+            children: Vec::new(),
+        }
+    }
+
+    /// Range between the delimiters
+    pub fn inner(&self) -> (usize, usize) {
+        (self.start + self.open_len, self.end - self.close_len)
+    }
+
+    /// Nodes containing `start..end`, outermost first
+    pub fn path(&self, start: usize, end: usize) -> Vec<&SyntaxNode> {
+        let mut path = vec![self];
+        let mut node = self;
+        while let Some(child) = node.children.iter().find(|c| c.start <= start && end <= c.end) {
+            path.push(child);
+            node = child;
+        }
+        path
+    }
+
+    fn for_each(&self, f: &mut impl FnMut(&SyntaxNode)) {
+        f(self);
+        for child in &self.children {
+            child.for_each(f);
+        }
+    }
+}
+
+fn closing_bracket(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// End of a char literal such as `'x'` or `'\n'` starting at `i`
+fn char_literal_end(chars: &[char], i: usize) -> Option<usize> {
+    if chars.get(i) != Some(&'\'') {
+        return None;
+    }
+    if chars.get(i + 1) == Some(&'\\') {
+        let close = (i + 2..chars.len().min(i + 12)).find(|&k| chars[k] == '\'')?;
+        return Some(close + 1);
+    }
+    (chars.get(i + 2) == Some(&'\'') && chars.get(i + 1) != Some(&'\n')).then_some(i + 3)
+}
+
+/// Parse brackets, strings, comments and words. Unterminated brackets
+/// run to the end of their parent; stray closing brackets are ignored.
+pub fn parse_structure(text: &str, syntax: &LanguageSyntax) -> SyntaxNode {
+    parse_chars(&text.chars().collect::<Vec<_>>(), syntax)
+}
+
+fn parse_chars(chars: &[char], syntax: &LanguageSyntax) -> SyntaxNode {
+    let len = chars.len();
+    let starts_with = |i: usize, token: &str| token.chars().enumerate().all(|(k, c)| chars.get(i + k) == Some(&c));
+
+    // Open nodes with their closing bracket; the document is at the bottom
+    let mut stack: Vec<(SyntaxNode, char)> = vec![(SyntaxNode::new(SyntaxKind::Document, 0, len, 0, 0), '\0')];
+    let push_leaf = |stack: &mut Vec<(SyntaxNode, char)>, node: SyntaxNode| {
+        if let Some((parent, _)) = stack.last_mut() {
+            parent.children.push(node);
+        }
+    };
+
+    let mut i = 0;
+    while i < len {
+        let c = chars[i];
+        if let Some(token) = syntax.line_comment.filter(|t| starts_with(i, t)) {
+            let end = (i..len).find(|&k| chars[k] == '\n').unwrap_or(len);
+            push_leaf(&mut stack, SyntaxNode::new(SyntaxKind::Comment, i, end, token.chars().count(), 0));
+            i = end;
+            continue;
+        }
+        if let Some((open, close)) = syntax.block_comment.filter(|(open, _)| starts_with(i, open)) {
+            let body = i + open.chars().count();
+            let close_len = close.chars().count();
+            let node = match (body..len).find(|&k| starts_with(k, close)) {
+                Some(k) => SyntaxNode::new(SyntaxKind::Comment, i, k + close_len, body - i, close_len),
+                None => SyntaxNode::new(SyntaxKind::Comment, i, len, body - i, 0),
+            };
+            i = node.end;
+            push_leaf(&mut stack, node);
+            continue;
+        }
+        if syntax.quotes.contains(&c) {
+            let triple = syntax.colon_blocks && chars.get(i + 1) == Some(&c) && chars.get(i + 2) == Some(&c);
+            let quote_len = if triple { 3 } else { 1 };
+            // Only backtick and triple-quoted strings span lines
+            let multiline = triple || c == '`';
+            let mut k = i + quote_len;
+            let mut node = None;
+            while k < len {
+                if chars[k] == '\\' {
+                    k += 2;
+                    continue;
+                }
+                if chars[k] == '\n' && !multiline {
+                    break;
+                }
+                if chars[k] == c && (!triple || starts_with(k, &c.to_string().repeat(3))) {
+                    node = Some(SyntaxNode::new(SyntaxKind::String, i, k + quote_len, quote_len, quote_len));
+                    break;
+                }
+                k += 1;
+            }
+            let node = node.unwrap_or_else(|| SyntaxNode::new(SyntaxKind::String, i, k.min(len), quote_len, 0));
+            i = node.end;
+            push_leaf(&mut stack, node);
+            continue;
+        }
+        if let Some(end) = char_literal_end(chars, i) {
+            push_leaf(&mut stack, SyntaxNode::new(SyntaxKind::String, i, end, 1, 1));
+            i = end;
+            continue;
+        }
+        if let Some(close) = closing_bracket(c) {
+            stack.push((SyntaxNode::new(SyntaxKind::Bracket, i, len, 1, 0), close));
+            i += 1;
+            continue;
+        }
+        if matches!(c, ')' | ']' | '}') {
+            // Close the matching bracket and any unterminated ones inside it
+            if let Some(depth) = stack.iter().skip(1).rposition(|(_, close)| *close == c) {
+                while stack.len() > depth + 1 {
+                    let (mut node, close) = stack.pop().unwrap_or_else(|| unreachable!());
+                    if close == c && stack.len() == depth + 1 {
+                        node.end = i + 1;
+                        node.close_len = 1;
+                    } else {
+                        node.end = i;
+                    }
+                    push_leaf(&mut stack, node);
+                }
+            }
+            i += 1;
+            continue;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            let end = (i..len).find(|&k| !(chars[k].is_alphanumeric() || chars[k] == '_')).unwrap_or(len);
+            push_leaf(&mut stack, SyntaxNode::new(SyntaxKind::Word, i, end, 0, 0));
+            i = end;
+            continue;
+        }
+        i += 1;
+    }
+
+    while stack.len() > 1 {
+        let (node, _) = stack.pop().unwrap_or_else(|| unreachable!());
+        push_leaf(&mut stack, node);
+    }
+    stack.pop().map(|(root, _)| root).unwrap_or_else(|| SyntaxNode::new(SyntaxKind::Document, 0, len, 0, 0))
+}
+
+/// `start..end` widened to whole lines, without leading and trailing whitespace
+fn line_span(chars: &[char], start: usize, end: usize) -> (usize, usize) {
+    let line_start = chars[..start].iter().rposition(|&c| c == '\n').map_or(0, |p| p + 1);
+    let line_end = chars[end..].iter().position(|&c| c == '\n').map_or(chars.len(), |p| end + p);
+    trim_span(chars, line_start, line_end)
+}
+
+/// The run of word characters around `start..end`, also inside strings and comments
+fn word_span(chars: &[char], start: usize, end: usize) -> (usize, usize) {
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+    if !chars[start..end].iter().all(is_word) {
+        return (start, end);
+    }
+    let word_start = chars[..start].iter().rposition(|c| !is_word(c)).map_or(0, |p| p + 1);
+    let word_end = chars[end..].iter().position(|c| !is_word(c)).map_or(chars.len(), |p| end + p);
+    (word_start, word_end)
+}
+
+fn trim_span(chars: &[char], mut start: usize, mut end: usize) -> (usize, usize) {
+    while start < end && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    (start, end)
+}
+
+/// A text with its structure tree, char index and line starts, built once
+/// and queried until the text changes
+#[derive(Debug, Clone)]
+pub struct ParsedText {
+    pub syntax: LanguageSyntax,
+    text: String,
+    chars: Vec<char>,
+    line_starts: Vec<usize>,
+    pub tree: SyntaxNode,
+}
+
+impl ParsedText {
+    pub fn new(text: String, syntax: &LanguageSyntax) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let tree = parse_chars(&chars, syntax);
+        let line_starts = std::iter::once(0)
+            .chain(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            syntax: *syntax,
+            text,
+            chars,
+            line_starts,
+            tree,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The smallest syntactic range that strictly contains the selection
+    /// `start..end`: word, string or bracket contents, the delimited node,
+    /// the lines of its statement, and finally the whole document
+    pub fn expand_selection(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        let chars = &self.chars;
+        let mut candidates = vec![word_span(chars, start, end), line_span(chars, start, end)];
+        for node in self.tree.path(start, end) {
+            candidates.push((node.start, node.end));
+            if matches!(node.kind, SyntaxKind::Bracket | SyntaxKind::String | SyntaxKind::Comment) {
+                let (inner_start, inner_end) = node.inner();
+                candidates.push((inner_start, inner_end));
+                candidates.push(trim_span(chars, inner_start, inner_end));
+            }
+            if node.kind == SyntaxKind::Bracket {
+                candidates.push(line_span(chars, node.start, node.end));
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|&(a, b)| a <= start && end <= b && b - a > end - start)
+            .min_by_key(|&(a, b)| b - a)
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&s| s <= offset).saturating_sub(1)
+    }
+
+    /// Lines of the statement starting on `line`: through the closing line of
+    /// any bracket opened on it
+    pub fn statement_lines(&self, line: usize) -> (usize, usize) {
+        let mut last = line;
+        self.tree.for_each(&mut |node| {
+            if node.kind != SyntaxKind::Document && self.line_of(node.start) == line {
+                last = last.max(self.line_of(node.end.saturating_sub(1).max(node.start)));
+            }
+        });
+        (line, last)
+    }
+
+    /// Lines of the statement ending on `line`: from the opening line of the
+    /// outermost bracket closed on it
+    pub fn statement_ending_at(&self, line: usize) -> (usize, usize) {
+        let mut first = line;
+        self.tree.for_each(&mut |node| {
+            if node.kind != SyntaxKind::Document && node.end > node.start && self.line_of(node.end - 1) == line {
+                first = first.min(self.line_of(node.start));
+            }
+        });
+        (first, line)
+    }
+}
+
+/// Offset of the bracket matching the one at or just before `offset`;
+/// elsewhere the closing bracket of the innermost enclosing pair
+pub fn matching_bracket(tree: &SyntaxNode, offset: usize) -> Option<usize> {
+    let mut adjacent = None;
+    tree.for_each(&mut |node| {
+        if node.kind != SyntaxKind::Bracket || node.close_len == 0 || adjacent.is_some() {
+            return;
+        }
+        let close = node.end - 1;
+        adjacent = match offset {
+            o if o == node.start || o == node.start + 1 => Some(close),
+            o if o == close || o == node.end => Some(node.start),
+            _ => None,
+        };
+    });
+    adjacent.or_else(|| {
+        tree.path(offset, offset)
+            .into_iter()
+            .rev()
+            .find(|node| node.kind == SyntaxKind::Bracket && node.close_len > 0 && node.start < offset)
+            .map(|node| node.end - 1)
+    })
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,4 +690,79 @@ mod tests {
         // Note: This is simplified - real implementation would track usage
         assert!(unused.contains(&"unused".to_string()));
     }
+
+    #[test]
+    fn test_parse_structure() {
+        let rust = LanguageSyntax::for_path("/p/main.rs");
+        let code = "fn f<'a>(x: &'a str) { g(\"(\", ')'); } // }";
+        let tree = parse_structure(code, &rust);
+        let kinds: Vec<SyntaxKind> = tree.children.iter().map(|n| n.kind).collect();
+        use SyntaxKind::*;
+        assert_eq!(kinds, vec![Word, Word, Word, Bracket, Bracket, Comment]);
+        let body = &tree.children[4];
+        assert_eq!((body.start, body.end, body.close_len), (21, 37, 1));
+        let call = &body.children[1];
+        assert_eq!(call.children.iter().map(|n| n.kind).collect::<Vec<_>>(), vec![String, String]);
+
+        // Stray closers are skipped, unterminated brackets run to the end
+        let tree = parse_structure("a) (b", &LanguageSyntax::PLAIN);
+        assert_eq!(tree.children[1].kind, Bracket);
+        assert_eq!((tree.children[1].end, tree.children[1].close_len), (5, 0));
+    }
+
+    #[test]
+    fn test_expand_selection() {
+        let syntax = LanguageSyntax::for_path("a.js");
+        let code = "if (x) {\n    call(\"some text\", y);\n}";
+        let parsed = ParsedText::new(code.to_string(), &syntax);
+        let offset = code.find("some").unwrap() + 1;
+        let mut selection = (offset, offset);
+        let mut steps = Vec::new();
+        while let Some(next) = parsed.expand_selection(selection.0, selection.1) {
+            steps.push(code.chars().skip(next.0).take(next.1 - next.0).collect::<std::string::String>());
+            selection = next;
+        }
+        assert_eq!(
+            steps,
+            vec![
+                "some",
+                "some text",
+                "\"some text\"",
+                "\"some text\", y",
+                "(\"some text\", y)",
+                "call(\"some text\", y);",
+                "\n    call(\"some text\", y);\n",
+                "{\n    call(\"some text\", y);\n}",
+                "if (x) {\n    call(\"some text\", y);\n}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matching_bracket_and_statements() {
+        let syntax = LanguageSyntax::for_path("a.rs");
+        let code = "fn a() {\n    b();\n}\nfn c() {}";
+        let parsed = ParsedText::new(code.to_string(), &syntax);
+        let tree = &parsed.tree;
+        let open = code.find('{').unwrap();
+        let close = code.find('}').unwrap();
+        assert_eq!(matching_bracket(tree, open), Some(close));
+        assert_eq!(matching_bracket(tree, close + 1), Some(open));
+        // Inside `b();` the innermost pair around the cursor is the body
+        assert_eq!(matching_bracket(tree, code.find('b').unwrap()), Some(close));
+        assert_eq!(parsed.statement_lines(0), (0, 2));
+        assert_eq!(parsed.statement_lines(1), (1, 1));
+        assert_eq!(parsed.statement_ending_at(2), (0, 2));
+        assert_eq!(parsed.statement_ending_at(3), (3, 3));
+    }
+
+    #[test]
+    fn test_language_syntax() {
+        assert_eq!(LanguageSyntax::for_path("/p/app.py").line_comment, Some("#"));
+        assert!(LanguageSyntax::for_path("/p/app.py").colon_blocks);
+        assert_eq!(LanguageSyntax::for_path("/p/style.css").block_comment, Some(("/*", "*/")));
+        assert_eq!(LanguageSyntax::for_path("/p/style.css").line_comment, None);
+        assert_eq!(LanguageSyntax::for_path("/p/Makefile").line_comment, Some("#"));
+        assert_eq!(LanguageSyntax::for_path("/p/notes.txt"), LanguageSyntax::PLAIN);
+    }
 }
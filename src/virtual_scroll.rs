@@ -149,6 +149,11 @@ impl VirtualScroll {
         self.scroll_velocity
     }
 
+    /// Current scroll position, clamped to the content
+    pub fn scroll_top(&self) -> f64 {
+        self.scroll_top
+    }
+
     /// Get the Y offset for a specific line number
     pub fn get_line_offset(&self, line: usize) -> f64 {
         line as f64 * self.line_height
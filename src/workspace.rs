@@ -0,0 +1,255 @@
+//! Multi-root workspaces
+//!
+//! The open folders, the workspace file they came from and the settings that
+//! apply to each folder. The first folder is the primary root used by panels
+//! that work on a single project (terminal, tasks, tests).
+
+use crate::common::dialogs::InputDialog;
+use crate::settings::EditorSettings;
+use crate::tauri_bindings_remote;
+use crate::tauri_bindings_workspace::{self, Workspace, WorkspaceFolder, WORKSPACE_EXTENSION};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+#[derive(Clone, Copy)]
+pub struct WorkspaceState {
+    pub workspace: RwSignal<Workspace>,
+    /// Workspace file the folders were opened from or saved to
+    pub file: RwSignal<Option<String>>,
+    pub error: RwSignal<Option<String>>,
+}
+
+impl WorkspaceState {
+    pub fn new() -> Self {
+        Self {
+            workspace: RwSignal::new(Workspace::default()),
+            file: RwSignal::new(None),
+            error: RwSignal::new(None),
+        }
+    }
+
+    pub fn folders(&self) -> Vec<WorkspaceFolder> {
+        self.workspace.with(|w| w.folders.clone())
+    }
+
+    pub fn roots(&self) -> Vec<String> {
+        self.workspace.with(|w| w.folders.iter().map(|f| f.path.clone()).collect())
+    }
+
+    /// The workspace folder containing `path`
+    pub fn root_for(&self, path: &str) -> Option<String> {
+        self.workspace
+            .with_untracked(|w| w.folder_for(path).map(|f| f.path.clone()))
+    }
+
+    /// User settings with the workspace's and the containing folder's overrides
+    pub fn settings_for(&self, path: &str) -> EditorSettings {
+        self.workspace
+            .with_untracked(|w| settings_for(&EditorSettings::load(), w, path))
+    }
+
+    /// Replace the workspace with a single folder
+    pub fn open_folder(&self, path: String) {
+        self.file.set(None);
+        self.workspace.set(Workspace {
+            folders: vec![WorkspaceFolder::new(path)],
+            ..Workspace::default()
+        });
+    }
+
+    /// Replace the workspace with plain folders (no workspace file)
+    pub fn open_folders(&self, paths: Vec<String>) {
+        self.file.set(None);
+        self.workspace.set(Workspace {
+            folders: paths.into_iter().map(WorkspaceFolder::new).collect(),
+            ..Workspace::default()
+        });
+    }
+
+    pub fn add_folder(&self, path: String) {
+        let path = path.trim_end_matches(['/', '\\']).to_string();
+        if self.workspace.with_untracked(|w| w.folders.iter().any(|f| f.path == path)) {
+            return;
+        }
+        self.workspace.update(|w| w.folders.push(WorkspaceFolder::new(path)));
+        self.save();
+    }
+
+    pub fn remove_folder(&self, path: &str) {
+        self.workspace.update(|w| {
+            if w.folders.len() > 1 {
+                w.folders.retain(|f| f.path != path);
+            }
+        });
+        self.save();
+    }
+
+    /// Open a workspace file, replacing the open folders
+    pub async fn open_file(self, path: String) {
+        match tauri_bindings_workspace::open_workspace(&path).await {
+            Ok(workspace) if !workspace.folders.is_empty() => {
+                self.workspace.set(workspace);
+                self.file.set(Some(path));
+                self.error.set(None);
+            }
+            Ok(_) => self.error.set(Some(format!("{} has no folders", path))),
+            Err(e) => self.error.set(Some(e)),
+        }
+    }
+
+    /// Connect to `[user@]host:path` over SSH and add it as a folder
+    pub async fn add_remote_folder(self, target: String) {
+        match tauri_bindings_remote::remote_connect(&target).await {
+            Ok(uri) => {
+                self.error.set(None);
+                self.add_folder(uri);
+            }
+            Err(e) => self.error.set(Some(e)),
+        }
+    }
+
+    /// Save to `path` and keep saving there as folders change
+    pub fn save_as(&self, path: String) {
+        self.file.set(Some(path));
+        self.save();
+    }
+
+    /// Write the workspace file, if the workspace has one
+    fn save(&self) {
+        let Some(path) = self.file.get_untracked() else {
+            return;
+        };
+        let workspace = self.workspace.get_untracked();
+        let error = self.error;
+        spawn_local(async move {
+            if let Err(e) = tauri_bindings_workspace::save_workspace(&path, &workspace).await {
+                error.set(Some(e));
+            }
+        });
+    }
+}
+
+impl Default for WorkspaceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `base` with the workspace's overrides, then those of the folder containing `path`
+pub fn settings_for(base: &EditorSettings, workspace: &Workspace, path: &str) -> EditorSettings {
+    let settings = base.with_overrides(&workspace.settings);
+    match workspace.folder_for(path) {
+        Some(folder) => settings.with_overrides(&folder.settings),
+        None => settings,
+    }
+}
+
+/// Which path the toolbar dialog asks for
+#[derive(Clone, Copy, PartialEq)]
+enum PathPrompt {
+    AddFolder,
+    RemoteFolder,
+    OpenWorkspace,
+    SaveWorkspace,
+}
+
+/// Folder and workspace file actions shown above the explorer
+#[component]
+pub fn WorkspaceToolbar(workspace: WorkspaceState) -> impl IntoView {
+    let prompt = RwSignal::new(Option::<PathPrompt>::None);
+    let dialog_open = RwSignal::new(false);
+    Effect::new(move |_| {
+        if !dialog_open.get() {
+            prompt.set(None);
+        }
+    });
+    let ask = move |kind: PathPrompt| {
+        prompt.set(Some(kind));
+        dialog_open.set(true);
+    };
+
+    view! {
+        <div class="berry-workspace-toolbar">
+            <span class="berry-workspace-name">
+                {move || {
+                    workspace.file.get()
+                        .map(|file| {
+                            let name = file.rsplit(['/', '\\']).next().unwrap_or(&file).to_string();
+                            name.trim_end_matches(&format!(".{}", WORKSPACE_EXTENSION)).to_string()
+                        })
+                        .unwrap_or_else(|| "Folders".to_string())
+                }}
+            </span>
+            <button title="Add Folder to Workspace" on:click=move |_| ask(PathPrompt::AddFolder)>"+"</button>
+            <button title="Open Remote Folder over SSH..." on:click=move |_| ask(PathPrompt::RemoteFolder)>"SSH"</button>
+            <button title="Open Workspace..." on:click=move |_| ask(PathPrompt::OpenWorkspace)>"Open"</button>
+            <button title="Save Workspace As..." on:click=move |_| ask(PathPrompt::SaveWorkspace)>"Save"</button>
+        </div>
+        {move || workspace.error.get().map(|e| view! { <div class="berry-workspace-error">{e}</div> })}
+        {move || prompt.get().map(|kind| {
+            let (title, placeholder) = match kind {
+                PathPrompt::AddFolder => ("Add Folder to Workspace", "/path/to/folder".to_string()),
+                PathPrompt::RemoteFolder => ("Open Remote Folder", "user@host:/path/to/folder".to_string()),
+                PathPrompt::OpenWorkspace | PathPrompt::SaveWorkspace => (
+                    if kind == PathPrompt::OpenWorkspace { "Open Workspace" } else { "Save Workspace As" },
+                    format!("/path/to/project.{}", WORKSPACE_EXTENSION),
+                ),
+            };
+            let initial = match kind {
+                PathPrompt::SaveWorkspace => workspace.file.get_untracked().unwrap_or_else(|| {
+                    let root = workspace.roots().into_iter().next().unwrap_or_default();
+                    format!("{}/project.{}", root, WORKSPACE_EXTENSION)
+                }),
+                _ => String::new(),
+            };
+            view! {
+                <InputDialog
+                    is_open=dialog_open
+                    title=title.to_string()
+                    placeholder=placeholder
+                    initial_value=initial
+                    on_submit=move |path: String| {
+                        let path = path.trim().to_string();
+                        match kind {
+                            PathPrompt::AddFolder => workspace.add_folder(path),
+                            PathPrompt::RemoteFolder => spawn_local(workspace.add_remote_folder(path)),
+                            PathPrompt::OpenWorkspace => spawn_local(workspace.open_file(path)),
+                            PathPrompt::SaveWorkspace => workspace.save_as(path),
+                        }
+                    }
+                    on_cancel=|| {}
+                />
+            }
+        })}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_settings_for() {
+        let mut api = WorkspaceFolder::new("/ws/api");
+        api.settings.insert("tab_size".to_string(), Value::from(2));
+        api.settings.insert("files_exclude".to_string(), serde_json::json!(["target"]));
+        let mut workspace = Workspace {
+            folders: vec![api, WorkspaceFolder::new("/ws/web")],
+            ..Workspace::default()
+        };
+        workspace.settings.insert("word_wrap".to_string(), Value::from(true));
+        // Values of the wrong type are ignored
+        workspace.settings.insert("font_size".to_string(), Value::from("big"));
+
+        let base = EditorSettings::default();
+        let api = settings_for(&base, &workspace, "/ws/api/src/main.rs");
+        assert_eq!((api.tab_size, api.word_wrap, api.font_size), (2, true, base.font_size));
+        assert_eq!(api.files_exclude, vec!["target".to_string()]);
+        assert_eq!(api.indent_unit(), "  ");
+
+        let web = settings_for(&base, &workspace, "/ws/web/index.ts");
+        assert_eq!((web.tab_size, web.word_wrap), (base.tab_size, true));
+        assert_eq!(web.files_exclude, base.files_exclude);
+    }
+}